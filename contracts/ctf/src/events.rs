@@ -0,0 +1,152 @@
+// NEP-297 events for indexers.
+//
+// Position balances move as NEP-171 token events, with position ids as token ids: splits mint,
+// merges and redemptions burn, transfers move. NEP-171 has no amounts, so each event carries an
+// `amounts` list parallel to `token_ids` that plain NFT consumers can ignore. Condition and split
+// lifecycle events use the contract's own "ctf" standard. The ERC-1155 style text logs
+// (TransferSingle, PositionsMerge, ...) are still written next to these.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, serde_json, AccountId};
+
+pub const NFT_STANDARD: &str = "nep171";
+pub const NFT_STANDARD_VERSION: &str = "1.0.0";
+pub const CTF_STANDARD: &str = "ctf";
+pub const CTF_STANDARD_VERSION: &str = "1.0.0";
+
+const EVENT_JSON_PREFIX: &str = "EVENT_JSON:";
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NearEvent<T> {
+    pub standard: String,
+    pub version: String,
+    pub event: String,
+    pub data: Vec<T>,
+}
+
+impl<T: Serialize> NearEvent<T> {
+    pub fn new(standard: &str, version: &str, event: &str, data: Vec<T>) -> Self {
+        Self { standard: standard.to_string(), version: version.to_string(), event: event.to_string(), data }
+    }
+
+    pub fn emit(&self) {
+        let json = serde_json::to_string(self)
+            .unwrap_or_else(|e| env::panic_str(&format!("Failed to serialize event: {}", e)));
+        env::log_str(&format!("{}{}", EVENT_JSON_PREFIX, json));
+    }
+}
+
+/// nft_mint data
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftMintLog {
+    pub owner_id: AccountId,
+    pub token_ids: Vec<String>,
+    pub amounts: Vec<U128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,                                      // operation that minted: split, merge, redeem
+}
+
+/// nft_burn data
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftBurnLog {
+    pub owner_id: AccountId,
+    pub token_ids: Vec<String>,
+    pub amounts: Vec<U128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// nft_transfer data; authorized_id is the approved operator when it isn't the owner moving tokens
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftTransferLog {
+    pub old_owner_id: AccountId,
+    pub new_owner_id: AccountId,
+    pub token_ids: Vec<String>,
+    pub amounts: Vec<U128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_id: Option<AccountId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// condition_prepared data
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConditionPreparedLog {
+    pub condition_id: String,
+    pub oracle: AccountId,
+    pub question_id: String,
+    pub outcome_slot_count: u8,
+}
+
+/// payouts_reported data; parent_condition_id is set for composite payouts made in positions
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PayoutsReportedLog {
+    pub condition_id: String,
+    pub question_id: String,
+    pub reporter: AccountId,
+    pub payout_numerators: Vec<U128>,
+    pub payout_denominator: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_condition_id: Option<String>,
+}
+
+/// Emit nft_mint unless nothing was minted
+pub fn emit_nft_mint(owner_id: &AccountId, minted: Vec<(String, U128)>, memo: &str) {
+    if minted.is_empty() {
+        return;
+    }
+    let (token_ids, amounts) = minted.into_iter().unzip();
+    NearEvent::new(NFT_STANDARD, NFT_STANDARD_VERSION, "nft_mint", vec![NftMintLog {
+        owner_id: owner_id.clone(),
+        token_ids,
+        amounts,
+        memo: Some(memo.to_string()),
+    }]).emit();
+}
+
+/// Emit nft_burn unless nothing was burned
+pub fn emit_nft_burn(owner_id: &AccountId, burned: Vec<(String, U128)>, memo: &str) {
+    if burned.is_empty() {
+        return;
+    }
+    let (token_ids, amounts) = burned.into_iter().unzip();
+    NearEvent::new(NFT_STANDARD, NFT_STANDARD_VERSION, "nft_burn", vec![NftBurnLog {
+        owner_id: owner_id.clone(),
+        token_ids,
+        amounts,
+        memo: Some(memo.to_string()),
+    }]).emit();
+}
+
+/// Emit nft_transfer unless nothing moved. `operator` is the account that called the transfer
+pub fn emit_nft_transfer(
+    operator: &AccountId,
+    old_owner_id: &AccountId,
+    new_owner_id: &AccountId,
+    transferred: Vec<(String, U128)>,
+    memo: Option<String>,
+) {
+    if transferred.is_empty() {
+        return;
+    }
+    let (token_ids, amounts) = transferred.into_iter().unzip();
+    NearEvent::new(NFT_STANDARD, NFT_STANDARD_VERSION, "nft_transfer", vec![NftTransferLog {
+        old_owner_id: old_owner_id.clone(),
+        new_owner_id: new_owner_id.clone(),
+        token_ids,
+        amounts,
+        authorized_id: Some(operator.clone()).filter(|operator| operator != old_owner_id),
+        memo,
+    }]).emit();
+}
+
+pub fn emit_ctf_event<T: Serialize>(event: &str, data: Vec<T>) {
+    NearEvent::new(CTF_STANDARD, CTF_STANDARD_VERSION, event, data).emit();
+}
@@ -2,9 +2,50 @@ use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{UnorderedMap, UnorderedSet};
 use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault};
+use near_sdk::{env, near_bindgen, AccountId, CryptoHash, PanicOnDefault};
 use near_sdk::env::sha256;
 use schemars::JsonSchema;
+use std::collections::HashMap;
+
+mod events;
+mod migration;
+pub use events::NearEvent;
+pub use migration::StateVersion;
+use events::{emit_ctf_event, emit_nft_burn, emit_nft_mint, emit_nft_transfer, ConditionPreparedLog, PayoutsReportedLog};
+
+/// Holder lists stop growing here; balances are unaffected
+const MAX_POSITION_HOLDERS: usize = 1000;
+
+/// Per-owner approval lists are capped; granting past the cap fails until something is revoked
+const MAX_APPROVALS_PER_OWNER: usize = 100;
+
+/// Positions moved by one batch_safe_transfer_all call
+const MAX_TRANSFER_ALL_POSITIONS: usize = 20;
+
+/// Operations in one batch_split_position / batch_merge_positions call. A binary split or merge
+/// that has to create its positions costs roughly 10 Tgas, so 20 stays well inside the 300 Tgas
+/// a transaction may attach
+const MAX_BATCH_POSITION_OPS: usize = 20;
+
+/// Longest name, symbol, label or URI accepted in position metadata, in bytes
+const MAX_METADATA_FIELD_LEN: usize = 256;
+
+/// Longest position metadata description, in bytes
+const MAX_METADATA_DESCRIPTION_LEN: usize = 1024;
+
+/// Longest outcome label given to prepare_condition, in bytes
+const MAX_OUTCOME_LABEL_LEN: usize = 32;
+
+/// Positions are minted 1:1 against collateral, and every collateral token in use is a
+/// 6-decimal stablecoin
+const POSITION_DECIMALS: u8 = 6;
+
+/// Highest interest rate a lien can record (100% over its term)
+const MAX_LIEN_INTEREST_BPS: u16 = 10_000;
+
+/// Collection-level metadata() for indexers treating positions as a multi-token contract
+const CTF_METADATA_NAME: &str = "Conditional Tokens";
+const CTF_METADATA_SYMBOL_PREFIX: &str = "CTF";
 
 // Core CTF data structures following Polymarket/Gnosis CTF architecture
 
@@ -21,6 +62,14 @@ pub struct Condition {
     pub payout_numerators: Option<Vec<U128>>,  // Set when resolved
     #[schemars(with = "Option<String>")]
     pub payout_denominator: Option<U128>,      // Set when resolved
+    #[serde(default)]
+    pub payout_in_positions: bool,             // redemptions pay out complete sets of parent_condition_id
+    #[serde(default)]
+    pub parent_condition_id: Option<String>,   // set with set_composite_payout
+    #[serde(default)]
+    pub outcome_labels: Option<Vec<String>>,   // one display name per outcome slot, from prepare_condition
+    #[serde(default)]
+    pub title: Option<String>,                 // display title, set by the oracle with set_condition_metadata
 }
 
 /// Position represents a conditional token position
@@ -91,6 +140,23 @@ pub struct PositionsMerge {
     pub amount: U128,
 }
 
+/// One split in a batch_split_position call; arguments as for split_position
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SplitOp {
+    #[schemars(with = "String")]
+    pub collateral_token: AccountId,
+    pub parent_collection_id: String,
+    pub condition_id: String,
+    #[schemars(with = "Vec<String>")]
+    pub partition: Vec<U128>,
+    #[schemars(with = "String")]
+    pub amount: U128,
+}
+
+/// One merge in a batch_merge_positions call; same shape as a split
+pub type MergeOp = SplitOp;
+
 /// Event emitted when payouts are reported
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
@@ -101,6 +167,171 @@ pub struct PayoutRedemption {
     pub condition_id: String,
     pub index_sets: Vec<Vec<U128>>,
     pub payout: U128,
+    pub per_index_set: Vec<(Vec<U128>, U128)>,
+}
+
+/// Event emitted when an operator approval or a single-position allowance is removed
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ApprovalRevoked {
+    pub owner: AccountId,
+    pub operator: AccountId,
+    pub position_id: Option<String>,                             // None for an approval for all positions
+}
+
+/// A non-zero single-position allowance granted by an owner
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenApproval {
+    #[schemars(with = "String")]
+    pub operator: AccountId,
+    pub position_id: String,
+    #[schemars(with = "String")]
+    pub allowance: U128,
+}
+
+/// A position an account holds, with what it pays out if the condition is resolved
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UserPosition {
+    pub position_id: String,
+    #[schemars(with = "String")]
+    pub balance: U128,
+    pub condition_id: String,
+    pub outcome_index: u8,                        // lowest outcome in the position's index set
+    pub is_resolved: bool,
+    #[schemars(with = "Option<String>")]
+    pub payout_amount: Option<U128>,              // redemption value of the balance once resolved
+}
+
+/// When and by whom a condition's payouts were reported, for dispute evidence
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConditionResolution {
+    pub condition_id: String,
+    pub question_id: String,
+    #[schemars(with = "Vec<String>")]
+    pub payout_numerators: Vec<U128>,
+    pub resolved_at: u64,                         // block timestamp of report_payouts
+    #[schemars(with = "String")]
+    pub submitter: AccountId,
+}
+
+/// Display metadata for a position (ERC-1155 metadata URI, NEAR style), e.g.
+/// name "YES - Will BTC reach $100k", outcome_label "YES"
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PositionMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    pub icon_uri: Option<String>,
+    pub market_id: String,
+    pub outcome_label: String,
+}
+
+/// What a wallet shows for a position instead of its hex id
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PositionTokenMetadata {
+    pub title: String,                         // condition title, or its question_id until one is set
+    pub outcome_label: String,
+    pub condition_id: String,
+    #[schemars(with = "String")]
+    pub collateral_token: AccountId,
+    pub decimals: u8,
+}
+
+/// Contract-level metadata, so positions can be indexed as one multi-token collection
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CtfContractMetadata {
+    pub name: String,
+    pub symbol_prefix: String,
+    pub version: String,
+    pub base_uri: Option<String>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum LienStatus {
+    Active,
+    Released,                                  // the lender confirmed repayment
+    Liquidated,                                // the lender took the position after maturity
+}
+
+/// Position tokens a borrower pledged to a lender. The interest rate is recorded for the
+/// lender's books; repayment itself happens outside the contract
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LienRecord {
+    pub lien_id: String,
+    pub position_id: String,
+    #[schemars(with = "String")]
+    pub borrower: AccountId,
+    #[schemars(with = "String")]
+    pub lender: AccountId,
+    #[schemars(with = "String")]
+    pub amount: U128,
+    pub interest_rate_bps: u16,
+    pub maturity_timestamp: u64,
+    pub created_at: u64,
+    pub status: LienStatus,
+}
+
+/// Collateral a condition took in through splits and paid out through merges and redemptions,
+/// per collateral token
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConditionCollateral {
+    #[schemars(with = "String")]
+    pub deposited: U128,
+    #[schemars(with = "String")]
+    pub withdrawn: U128,
+}
+
+/// Total supply of one of a condition's outcome positions held directly against collateral
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OutcomeSupply {
+    #[schemars(with = "String")]
+    pub index_set: U128,
+    pub position_id: String,
+    #[schemars(with = "String")]
+    pub supply: U128,
+}
+
+/// Complete-set accounting of a condition in one collateral token, for integrity monitoring
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConditionAccounting {
+    pub condition_id: String,
+    #[schemars(with = "String")]
+    pub collateral_token: AccountId,
+    #[schemars(with = "String")]
+    pub deposited: U128,
+    #[schemars(with = "String")]
+    pub withdrawn: U128,
+    #[schemars(with = "String")]
+    pub net_collateral: U128,
+    pub outcome_supplies: Vec<OutcomeSupply>,     // one per outcome, in outcome order
+    pub is_resolved: bool,
+    pub invariant_holds: bool,
+}
+
+/// Result of a redemption: total payout plus what each index set paid
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RedemptionResult {
+    pub total: U128,
+    pub per_index_set: Vec<(Vec<U128>, U128)>,
+}
+
+// One index set of a pending redemption
+struct RedemptionLeg {
+    position_id: String,
+    position_balance: U128,
+    position_payout: U128,
 }
 
 #[near_bindgen]
@@ -131,6 +362,52 @@ pub struct ConditionalTokenFramework {
     
     /// Contract owner for administrative functions
     pub owner: AccountId,
+
+    /// Maps position_id -> accounts with a non-zero balance, in the order they first acquired it
+    pub position_holders: UnorderedMap<String, Vec<AccountId>>,
+
+    /// Maps owner -> (operator, position_id) pairs with a non-zero allowance, in the order granted
+    pub owner_token_approvals: UnorderedMap<AccountId, Vec<(AccountId, String)>>,
+
+    /// Maps owner -> operators approved for all of the owner's positions
+    pub owner_operators: UnorderedMap<AccountId, Vec<AccountId>>,
+
+    /// Maps account -> position_ids it holds a non-zero balance of, in the order first acquired
+    pub user_position_index: UnorderedMap<AccountId, Vec<String>>,
+
+    /// Layout marker checked by migrate()
+    pub state_version: StateVersion,
+
+    /// sha256 of the code the owner approved for upgrade()
+    pub pending_upgrade_hash: Option<CryptoHash>,
+
+    /// Maps position_id -> display metadata set by the owner or the condition's oracle
+    pub position_metadata: UnorderedMap<String, PositionMetadata>,
+
+    /// Maps collateral token -> collateral held against outstanding positions (TVL)
+    pub collateral_locked: UnorderedMap<AccountId, U128>,
+
+    /// Maps condition_id -> block timestamp its payouts were reported, in resolution order
+    pub resolution_timestamps: UnorderedMap<String, u64>,
+
+    /// Maps condition_id -> account that called report_payouts
+    pub resolution_submitter: UnorderedMap<String, AccountId>,
+
+    /// Maps position_id -> sum of all balances
+    pub position_supply: UnorderedMap<String, U128>,
+
+    /// Maps "condition_id:collateral_token" -> collateral moved in and out by splits, merges and redemptions
+    pub condition_collateral: UnorderedMap<String, ConditionCollateral>,
+
+    /// Base URI reported by metadata(), set by the owner
+    pub base_uri: Option<String>,
+
+    /// Maps lien_id -> lien; settled liens are kept with their final status
+    pub liens: UnorderedMap<String, LienRecord>,
+
+    /// Maps "position_id:account_id" -> balance pledged under active liens, which cannot be
+    /// transferred, split, merged or redeemed
+    pub liened_balances: UnorderedMap<String, U128>,
 }
 
 #[near_bindgen]
@@ -146,6 +423,21 @@ impl ConditionalTokenFramework {
             token_approvals: UnorderedMap::new(b"t"),
             collateral_tokens: UnorderedSet::new(b"k"),
             owner,
+            position_holders: UnorderedMap::new(b"h"),
+            owner_token_approvals: UnorderedMap::new(b"l"),
+            owner_operators: UnorderedMap::new(b"r"),
+            user_position_index: UnorderedMap::new(b"u"),
+            state_version: StateVersion::CURRENT,
+            pending_upgrade_hash: None,
+            position_metadata: UnorderedMap::new(b"m"),
+            collateral_locked: UnorderedMap::new(b"v"),
+            resolution_timestamps: UnorderedMap::new(b"e"),
+            resolution_submitter: UnorderedMap::new(b"s"),
+            position_supply: UnorderedMap::new(b"y"),
+            condition_collateral: UnorderedMap::new(b"n"),
+            base_uri: None,
+            liens: UnorderedMap::new(b"i"),
+            liened_balances: UnorderedMap::new(b"g"),
         }
     }
 
@@ -154,15 +446,20 @@ impl ConditionalTokenFramework {
     // ============================================================================
 
     /// Prepare a condition for use in prediction markets
-    /// This is equivalent to Gnosis CTF's prepareCondition
+    /// This is equivalent to Gnosis CTF's prepareCondition. `outcome_labels`, one per outcome
+    /// slot, name the outcomes in position metadata; without them binary conditions read NO/YES
     pub fn prepare_condition(
         &mut self,
         oracle: AccountId,
         question_id: String,
         outcome_slot_count: u8,
+        outcome_labels: Option<Vec<String>>,
     ) -> String {
         assert!(outcome_slot_count > 1, "Must have at least 2 outcomes");
         assert!(outcome_slot_count <= 255, "Too many outcomes");
+        if let Some(labels) = &outcome_labels {
+            Self::assert_valid_outcome_labels(labels, outcome_slot_count);
+        }
         
         // Generate condition_id using same logic as Gnosis CTF
         let condition_id = self.get_condition_id(oracle.clone(), question_id.clone(), outcome_slot_count);
@@ -179,14 +476,20 @@ impl ConditionalTokenFramework {
             outcome_slot_count,
             payout_numerators: None,
             payout_denominator: None,
+            payout_in_positions: false,
+            parent_condition_id: None,
+            outcome_labels,
+            title: None,
         };
         
         self.conditions.insert(&condition_id, &condition);
         
-        env::log_str(&format!(
-            "ConditionPreparation: oracle={}, questionId={}, outcomeSlotCount={}, conditionId={}",
-            condition.oracle, condition.question_id, condition.outcome_slot_count, condition_id
-        ));
+        emit_ctf_event("condition_prepared", vec![ConditionPreparedLog {
+            condition_id: condition_id.clone(),
+            oracle: condition.oracle,
+            question_id: condition.question_id,
+            outcome_slot_count: condition.outcome_slot_count,
+        }]);
         
         condition_id
     }
@@ -230,11 +533,136 @@ impl ConditionalTokenFramework {
         condition.payout_denominator = Some(U128(total_payout));
         
         self.conditions.insert(&condition_id, &condition);
+        self.record_resolution(&condition_id, &caller);
         
+        emit_ctf_event("payouts_reported", vec![PayoutsReportedLog {
+            condition_id,
+            question_id,
+            reporter: caller,
+            payout_numerators: payouts,
+            payout_denominator: U128(total_payout),
+            parent_condition_id: None,
+        }]);
+    }
+
+    /// Report payouts for several conditions in one call (oracle only)
+    /// Already-resolved conditions are skipped; returns the number newly resolved
+    pub fn batch_report_payouts(&mut self, resolutions: Vec<(String, Vec<U128>)>) -> u64 {
+        assert!(!resolutions.is_empty(), "No resolutions provided");
+        assert!(resolutions.len() <= 20, "Batch size too large (max 20)");
+
+        let caller = env::predecessor_account_id();
+
+        // Validate the whole batch up front so an unauthorized entry can't partially apply
+        let mut resolved_batch: Vec<(String, Condition, Vec<U128>)> = Vec::new();
+        for (question_id, payouts) in resolutions {
+            let (condition_id, condition) = self
+                .find_condition_by_question_id(&question_id)
+                .expect("Condition not found");
+            assert_eq!(condition.oracle, caller, "Only oracle can report payouts");
+            assert_eq!(
+                payouts.len() as u8,
+                condition.outcome_slot_count,
+                "Payout count must match outcome count"
+            );
+            resolved_batch.push((condition_id, condition, payouts));
+        }
+
+        let mut newly_resolved = 0u64;
+
+        for (condition_id, mut condition, payouts) in resolved_batch {
+            // Re-read in case the same question appears twice in the batch
+            if self.is_condition_resolved(condition_id.clone()) {
+                env::log_str(&format!(
+                    "WARNING: Skipping already resolved condition: questionId={}, conditionId={}",
+                    condition.question_id, condition_id
+                ));
+                continue;
+            }
+
+            let total_payout: u128 = payouts.iter().map(|p| p.0).sum();
+            assert!(total_payout > 0, "Total payout must be positive");
+
+            condition.payout_numerators = Some(payouts.clone());
+            condition.payout_denominator = Some(U128(total_payout));
+            self.conditions.insert(&condition_id, &condition);
+            self.record_resolution(&condition_id, &caller);
+
+            emit_ctf_event("payouts_reported", vec![PayoutsReportedLog {
+                condition_id,
+                question_id: condition.question_id,
+                reporter: caller.clone(),
+                payout_numerators: payouts,
+                payout_denominator: U128(total_payout),
+                parent_condition_id: None,
+            }]);
+
+            newly_resolved += 1;
+        }
+
         env::log_str(&format!(
-            "PayoutRedemption: questionId={}, payouts={:?}, totalPayout={}",
-            question_id, payouts, total_payout
+            "BatchPayoutReport: oracle={}, newlyResolved={}",
+            caller, newly_resolved
         ));
+
+        newly_resolved
+    }
+
+    /// Resolve a condition to a composite outcome (oracle only), e.g. a draw that is settled by a
+    /// replay rather than refunded. With a parent_condition_id, redeeming pays out complete sets
+    /// of the parent condition's outcomes instead of collateral; without one this is
+    /// report_payouts by condition id
+    pub fn set_composite_payout(
+        &mut self,
+        condition_id: String,
+        payout_numerators: Vec<U128>,
+        parent_condition_id: Option<String>,
+    ) {
+        let caller = env::predecessor_account_id();
+        let mut condition = self.conditions.get(&condition_id)
+            .expect("Condition not found");
+        assert_eq!(condition.oracle, caller, "Only oracle can report payouts");
+        assert!(condition.payout_numerators.is_none(), "Payouts already reported");
+        assert_eq!(
+            payout_numerators.len() as u8,
+            condition.outcome_slot_count,
+            "Payout count must match outcome count"
+        );
+        let total_payout: u128 = payout_numerators.iter().map(|p| p.0).sum();
+        assert!(total_payout > 0, "Total payout must be positive");
+
+        if let Some(parent_condition_id) = &parent_condition_id {
+            assert!(*parent_condition_id != condition_id, "Condition cannot pay out in its own positions");
+            // An unresolved parent has no parent of its own yet, so chains can't loop
+            assert!(
+                !self.is_condition_resolved(parent_condition_id.clone()),
+                "Parent condition already resolved"
+            );
+            assert!(self.conditions.get(parent_condition_id).is_some(), "Parent condition not found");
+        }
+
+        condition.payout_numerators = Some(payout_numerators.clone());
+        condition.payout_denominator = Some(U128(total_payout));
+        condition.payout_in_positions = parent_condition_id.is_some();
+        condition.parent_condition_id = parent_condition_id.clone();
+        self.conditions.insert(&condition_id, &condition);
+        self.record_resolution(&condition_id, &caller);
+
+        emit_ctf_event("payouts_reported", vec![PayoutsReportedLog {
+            condition_id,
+            question_id: condition.question_id,
+            reporter: caller,
+            payout_numerators,
+            payout_denominator: U128(total_payout),
+            parent_condition_id,
+        }]);
+    }
+
+    /// Look up a condition by its question_id
+    fn find_condition_by_question_id(&self, question_id: &str) -> Option<(String, Condition)> {
+        self.conditions
+            .iter()
+            .find(|(_, condition)| condition.question_id == question_id)
     }
 
     /// Get condition by ID
@@ -242,6 +670,46 @@ impl ConditionalTokenFramework {
         self.conditions.get(&condition_id)
     }
 
+    /// Reported payout numerators and denominator, None until the condition is resolved
+    pub fn get_payouts(&self, condition_id: String) -> Option<(Vec<U128>, U128)> {
+        let condition = self.conditions.get(&condition_id)?;
+        Some((condition.payout_numerators?, condition.payout_denominator?))
+    }
+
+    /// Block timestamp the condition's payouts were reported at; None if unresolved or resolved
+    /// before timestamps were recorded
+    pub fn get_condition_resolution_timestamp(&self, condition_id: String) -> Option<u64> {
+        self.resolution_timestamps.get(&condition_id)
+    }
+
+    pub fn get_condition_resolution_submitter(&self, condition_id: String) -> Option<AccountId> {
+        self.resolution_submitter.get(&condition_id)
+    }
+
+    /// Recorded resolutions, oldest first
+    pub fn get_condition_resolution_history(&self, from_index: u64, limit: u64) -> Vec<ConditionResolution> {
+        self.resolution_timestamps
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .filter_map(|(condition_id, resolved_at)| {
+                let condition = self.conditions.get(&condition_id)?;
+                Some(ConditionResolution {
+                    submitter: self.resolution_submitter.get(&condition_id)?,
+                    payout_numerators: condition.payout_numerators?,
+                    question_id: condition.question_id,
+                    condition_id,
+                    resolved_at,
+                })
+            })
+            .collect()
+    }
+
+    fn record_resolution(&mut self, condition_id: &String, submitter: &AccountId) {
+        self.resolution_timestamps.insert(condition_id, &env::block_timestamp());
+        self.resolution_submitter.insert(condition_id, submitter);
+    }
+
     /// Check if condition is resolved
     pub fn is_condition_resolved(&self, condition_id: String) -> bool {
         if let Some(condition) = self.conditions.get(&condition_id) {
@@ -266,164 +734,267 @@ impl ConditionalTokenFramework {
         amount: U128,
     ) {
         let caller = env::predecessor_account_id();
-        
-        // Validate inputs
-        assert!(amount.0 > 0, "Amount must be positive");
-        assert!(!partition.is_empty(), "Partition cannot be empty");
+        let event = self.apply_split(&caller, SplitOp { collateral_token, parent_collection_id, condition_id, partition, amount });
+        emit_ctf_event("position_split", vec![event]);
+    }
+
+    /// Merge positions back into parent position or collateral
+    /// This is the reverse of split_position
+    pub fn merge_positions(
+        &mut self,
+        collateral_token: AccountId,
+        parent_collection_id: String,
+        condition_id: String,
+        partition: Vec<U128>,
+        amount: U128,
+    ) {
+        let caller = env::predecessor_account_id();
+        let event = self.apply_merge(&caller, MergeOp { collateral_token, parent_collection_id, condition_id, partition, amount });
+        env::log_str(&format!("PositionsMerge: {:?}", event));
+    }
+
+    /// Run several splits in one call (market makers minting across markets). Every op is
+    /// checked against the caller's balances, including what earlier ops in the batch mint or
+    /// burn, before anything is written, so one bad op fails the whole batch. At most
+    /// MAX_BATCH_POSITION_OPS ops; emits a single position_split event with one entry per op
+    pub fn batch_split_position(&mut self, operations: Vec<SplitOp>) {
+        let caller = env::predecessor_account_id();
+        self.check_position_batch(&caller, &operations, true);
+
+        let events: Vec<PositionSplit> = operations.into_iter()
+            .map(|op| self.apply_split(&caller, op))
+            .collect();
+        emit_ctf_event("position_split", events);
+    }
+
+    /// Run several merges in one call; validated up front like batch_split_position. Logs a
+    /// single PositionsMergeBatch
+    pub fn batch_merge_positions(&mut self, operations: Vec<MergeOp>) {
+        let caller = env::predecessor_account_id();
+        self.check_position_batch(&caller, &operations, false);
+
+        let events: Vec<PositionsMerge> = operations.into_iter()
+            .map(|op| self.apply_merge(&caller, op))
+            .collect();
+        env::log_str(&format!("PositionsMergeBatch: {:?}", events));
+    }
+
+    /// Validate a split or merge and return the partition's union, the full index set and the
+    /// position on the other side of the partition: the union position for a partial partition,
+    /// the parent position, or None for collateral
+    fn check_position_op(&self, op: &SplitOp) -> (u128, u128, Option<String>) {
+        assert!(op.amount.0 > 0, "Amount must be positive");
+        assert!(!op.partition.is_empty(), "Partition cannot be empty");
         
         // Verify condition exists
-        let condition = self.conditions.get(&condition_id)
+        let condition = self.conditions.get(&op.condition_id)
             .expect("Condition not found");
         
-        // Validate partition matches condition outcomes
-        let _full_index_set: Vec<U128> = (0..condition.outcome_slot_count)
-            .map(|i| U128(1u128 << i))
-            .collect();
-        
-        // Ensure partition covers all outcomes exactly once
-        let mut covered_outcomes = 0u128;
-        for index_set in &partition {
-            assert!(index_set.0 != 0, "Empty index set not allowed");
-            assert!(index_set.0 & covered_outcomes == 0, "Overlapping outcomes in partition");
-            covered_outcomes |= index_set.0;
-        }
-        
-        let expected_full_set = (1u128 << condition.outcome_slot_count) - 1;
-        assert_eq!(covered_outcomes, expected_full_set, "Partition must cover all outcomes");
+        // Partition must be disjoint; it may cover only part of the outcomes
+        let (covered_outcomes, full_index_set) = Self::validate_partition(&op.partition, condition.outcome_slot_count);
         
-        // Get or create parent collection
-        let parent_collection_key = if parent_collection_id.is_empty() {
-            String::new()
+        let outer_position_id = if covered_outcomes != full_index_set {
+            // Partial partition (Gnosis semantics): the union of the partition's outcomes under
+            // the same parent, e.g. {B|C} <-> {B}, {C}
+            let collection_id = self.get_collection_id(op.parent_collection_id.clone(), op.condition_id.clone(), vec![U128(covered_outcomes)]);
+            Some(self.get_position_id(op.collateral_token.clone(), collection_id))
+        } else if op.parent_collection_id.is_empty() {
+            None
         } else {
-            parent_collection_id.clone()
+            Some(self.get_position_id(op.collateral_token.clone(), op.parent_collection_id.clone()))
         };
         
-        // Check caller has sufficient balance of parent position
-        if parent_collection_key.is_empty() {
-            // Splitting from collateral token - transfer from caller
-            self.transfer_collateral_from(caller.clone(), env::current_account_id(), collateral_token.clone(), amount);
-        } else {
-            // Splitting from parent position
-            let parent_position_id = self.get_position_id(collateral_token.clone(), parent_collection_key.clone());
-            let parent_balance_key = format!("{}:{}", parent_position_id, caller);
-            let parent_balance = self.balances.get(&parent_balance_key).unwrap_or(U128(0));
-            
-            assert!(parent_balance.0 >= amount.0, "Insufficient parent position balance");
-            
-            // Burn parent position tokens
-            self.balances.insert(&parent_balance_key, &U128(parent_balance.0 - amount.0));
+        (covered_outcomes, full_index_set, outer_position_id)
+    }
+
+    /// Dry run of a batch against a scratch copy of the caller's balances; panics on the first
+    /// op that would fail so nothing is written
+    fn check_position_batch(&self, caller: &AccountId, operations: &[SplitOp], split: bool) {
+        assert!(!operations.is_empty(), "No operations in batch");
+        assert!(
+            operations.len() <= MAX_BATCH_POSITION_OPS,
+            "Too many operations (max {} per batch)", MAX_BATCH_POSITION_OPS
+        );
+
+        let mut projected: HashMap<String, u128> = HashMap::new();
+        for (i, op) in operations.iter().enumerate() {
+            let (_, _, outer_position_id) = self.check_position_op(op);
+            let child_position_ids: Vec<String> = op.partition.iter()
+                .map(|index_set| {
+                    let collection_id = self.get_collection_id(op.parent_collection_id.clone(), op.condition_id.clone(), vec![*index_set]);
+                    self.get_position_id(op.collateral_token.clone(), collection_id)
+                })
+                .collect();
+            let (burned, minted) = if split {
+                (outer_position_id.into_iter().collect::<Vec<_>>(), child_position_ids)
+            } else {
+                (child_position_ids, outer_position_id.into_iter().collect::<Vec<_>>())
+            };
+
+            for position_id in burned {
+                let balance = projected.entry(position_id.clone())
+                    .or_insert_with(|| self.available_balance(&position_id, caller));
+                assert!(*balance >= op.amount.0, "Insufficient balance for operation {} in batch", i);
+                *balance -= op.amount.0;
+            }
+            for position_id in minted {
+                let balance = projected.entry(position_id.clone())
+                    .or_insert_with(|| self.available_balance(&position_id, caller));
+                *balance += op.amount.0;
+            }
         }
-        
-        // Create child positions and mint tokens
-        for index_set in &partition {
-            let collection_id = self.get_collection_id(parent_collection_key.clone(), condition_id.clone(), vec![*index_set]);
-            let position_id = self.get_position_id(collateral_token.clone(), collection_id.clone());
-            
-            // Store collection if not exists
-            if self.collections.get(&collection_id).is_none() {
-                let collection = Collection {
-                    collection_id: collection_id.clone(),
-                    parent_collection_id: parent_collection_key.clone(),
-                    condition_id: condition_id.clone(),
-                    index_set: vec![*index_set],
+    }
+
+    fn apply_split(&mut self, caller: &AccountId, op: SplitOp) -> PositionSplit {
+        let (covered_outcomes, full_index_set, source_position_id) = self.check_position_op(&op);
+        let SplitOp { collateral_token, parent_collection_id, condition_id, partition, amount } = op;
+        
+        // Burn the position being split, or take collateral from the caller
+        match source_position_id {
+            Some(source_position_id) => {
+                let source_balance = self.balances.get(&format!("{}:{}", source_position_id, caller)).unwrap_or(U128(0));
+                let message = if covered_outcomes != full_index_set {
+                    "Insufficient balance of position being split"
+                } else {
+                    "Insufficient parent position balance"
                 };
-                self.collections.insert(&collection_id, &collection);
+                assert!(source_balance.0 >= amount.0, "{}", message);
+                assert!(self.available_balance(&source_position_id, caller) >= amount.0, "Position balance is under lien");
+                self.set_balance(&source_position_id, caller, source_balance.0 - amount.0);
+                emit_nft_burn(caller, vec![(source_position_id, amount)], "split_position");
             }
-            
-            // Store position if not exists
-            if self.positions.get(&position_id).is_none() {
-                let position = Position {
-                    position_id: position_id.clone(),
-                    collateral_token: collateral_token.clone(),
-                    collection_id: collection_id.clone(),
-                    condition_id: condition_id.clone(),
-                    index_set: vec![*index_set],
-                };
-                self.positions.insert(&position_id, &position);
+            None => {
+                self.transfer_collateral_from(caller.clone(), env::current_account_id(), collateral_token.clone(), amount);
+                self.adjust_collateral_locked(&condition_id, &collateral_token, amount.0, true);
             }
+        }
+        
+        // Create child positions and mint tokens
+        let mut minted = Vec::with_capacity(partition.len());
+        for index_set in &partition {
+            let position_id = self.ensure_position(&collateral_token, &parent_collection_id, &condition_id, *index_set);
             
-            // Mint tokens to caller
             let balance_key = format!("{}:{}", position_id, caller);
             let current_balance = self.balances.get(&balance_key).unwrap_or(U128(0));
-            self.balances.insert(&balance_key, &U128(current_balance.0 + amount.0));
+            self.set_balance(&position_id, caller, current_balance.0 + amount.0);
+            minted.push((position_id, amount));
+        }
+        emit_nft_mint(caller, minted, "split_position");
+
+        if parent_collection_id.is_empty() {
+            self.check_condition_accounting(&condition_id, &collateral_token);
         }
         
-        // Emit event
-        let event = PositionSplit {
-            stakeholder: caller,
+        PositionSplit {
+            stakeholder: caller.clone(),
             collateral_token,
-            parent_collection_id: parent_collection_key,
+            parent_collection_id,
             condition_id,
             partition,
             amount,
-        };
-        
-        env::log_str(&format!("PositionSplit: {:?}", event));
+        }
     }
 
-    /// Merge positions back into parent position or collateral
-    /// This is the reverse of split_position
-    pub fn merge_positions(
-        &mut self,
-        collateral_token: AccountId,
-        parent_collection_id: String,
-        condition_id: String,
-        partition: Vec<U128>,
-        amount: U128,
-    ) {
-        let caller = env::predecessor_account_id();
-        
-        // Validate inputs
-        assert!(amount.0 > 0, "Amount must be positive");
-        assert!(!partition.is_empty(), "Partition cannot be empty");
-        
-        // Verify condition exists
-        let _condition = self.conditions.get(&condition_id)
-            .expect("Condition not found");
-        
-        // Get parent collection
-        let parent_collection_key = if parent_collection_id.is_empty() {
-            String::new()
-        } else {
-            parent_collection_id.clone()
-        };
+    fn apply_merge(&mut self, caller: &AccountId, op: MergeOp) -> PositionsMerge {
+        let (covered_outcomes, full_index_set, _) = self.check_position_op(&op);
+        let MergeOp { collateral_token, parent_collection_id, condition_id, partition, amount } = op;
         
         // Burn child position tokens
+        let mut burned = Vec::with_capacity(partition.len());
         for index_set in &partition {
-            let collection_id = self.get_collection_id(parent_collection_key.clone(), condition_id.clone(), vec![*index_set]);
-            let position_id = self.get_position_id(collateral_token.clone(), collection_id.clone());
+            let collection_id = self.get_collection_id(parent_collection_id.clone(), condition_id.clone(), vec![*index_set]);
+            let position_id = self.get_position_id(collateral_token.clone(), collection_id);
             let balance_key = format!("{}:{}", position_id, caller);
             
             let balance = self.balances.get(&balance_key).unwrap_or(U128(0));
             assert!(balance.0 >= amount.0, "Insufficient balance for position merge");
+            assert!(self.available_balance(&position_id, caller) >= amount.0, "Position balance is under lien");
             
-            self.balances.insert(&balance_key, &U128(balance.0 - amount.0));
+            self.set_balance(&position_id, caller, balance.0 - amount.0);
+            burned.push((position_id, amount));
         }
+        emit_nft_burn(caller, burned, "merge_positions");
         
-        // Mint parent position or transfer collateral
-        if parent_collection_key.is_empty() {
+        // Mint the union position, parent position or transfer collateral
+        if covered_outcomes != full_index_set {
+            // Partial partition merges into the position for the union of its outcomes
+            let target_position_id = self.ensure_position(&collateral_token, &parent_collection_id, &condition_id, U128(covered_outcomes));
+            
+            let target_balance_key = format!("{}:{}", target_position_id, caller);
+            let target_balance = self.balances.get(&target_balance_key).unwrap_or(U128(0));
+            self.set_balance(&target_position_id, caller, target_balance.0 + amount.0);
+            emit_nft_mint(caller, vec![(target_position_id, amount)], "merge_positions");
+        } else if parent_collection_id.is_empty() {
             // Merging to collateral token - transfer to caller
             self.transfer_collateral_to(env::current_account_id(), caller.clone(), collateral_token.clone(), amount);
+            self.adjust_collateral_locked(&condition_id, &collateral_token, amount.0, false);
         } else {
             // Merging to parent position
-            let parent_position_id = self.get_position_id(collateral_token.clone(), parent_collection_key.clone());
+            let parent_position_id = self.get_position_id(collateral_token.clone(), parent_collection_id.clone());
             let parent_balance_key = format!("{}:{}", parent_position_id, caller);
             let parent_balance = self.balances.get(&parent_balance_key).unwrap_or(U128(0));
             
-            self.balances.insert(&parent_balance_key, &U128(parent_balance.0 + amount.0));
+            self.set_balance(&parent_position_id, caller, parent_balance.0 + amount.0);
+            emit_nft_mint(caller, vec![(parent_position_id, amount)], "merge_positions");
+        }
+
+        if parent_collection_id.is_empty() {
+            self.check_condition_accounting(&condition_id, &collateral_token);
         }
         
-        // Emit event
-        let event = PositionsMerge {
-            stakeholder: caller,
+        PositionsMerge {
+            stakeholder: caller.clone(),
             collateral_token,
-            parent_collection_id: parent_collection_key,
+            parent_collection_id,
             condition_id,
             partition,
             amount,
-        };
+        }
+    }
+
+    /// Check a partition is disjoint and each index set is a non-empty strict subset of the
+    /// condition's outcomes. Returns (union of the partition, full index set)
+    fn validate_partition(partition: &[U128], outcome_slot_count: u8) -> (u128, u128) {
+        let full_index_set = (1u128 << outcome_slot_count) - 1;
         
-        env::log_str(&format!("PositionsMerge: {:?}", event));
+        let mut covered_outcomes = 0u128;
+        for index_set in partition {
+            assert!(index_set.0 != 0, "Empty index set not allowed");
+            assert!(index_set.0 < full_index_set, "Index set must be a strict subset of the outcomes");
+            assert!(index_set.0 & covered_outcomes == 0, "Overlapping outcomes in partition");
+            covered_outcomes |= index_set.0;
+        }
+        
+        (covered_outcomes, full_index_set)
+    }
+
+    /// Store the collection and position for (parent, condition, index_set) if new; returns the position id
+    fn ensure_position(&mut self, collateral_token: &AccountId, parent_collection_id: &String, condition_id: &String, index_set: U128) -> String {
+        let collection_id = self.get_collection_id(parent_collection_id.clone(), condition_id.clone(), vec![index_set]);
+        let position_id = self.get_position_id(collateral_token.clone(), collection_id.clone());
+        
+        if self.collections.get(&collection_id).is_none() {
+            let collection = Collection {
+                collection_id: collection_id.clone(),
+                parent_collection_id: parent_collection_id.clone(),
+                condition_id: condition_id.clone(),
+                index_set: vec![index_set],
+            };
+            self.collections.insert(&collection_id, &collection);
+        }
+        
+        if self.positions.get(&position_id).is_none() {
+            let position = Position {
+                position_id: position_id.clone(),
+                collateral_token: collateral_token.clone(),
+                collection_id: collection_id.clone(),
+                condition_id: condition_id.clone(),
+                index_set: vec![index_set],
+            };
+            self.positions.insert(&position_id, &position);
+        }
+        
+        position_id
     }
 
     // ============================================================================
@@ -432,6 +1003,7 @@ impl ConditionalTokenFramework {
 
     /// Redeem positions for collateral after condition resolution
     /// This is the final step that converts winning outcome tokens back to collateral
+    /// Legacy interface used by the solver's ext_ctf; losing positions are kept
     pub fn redeem_positions(
         &mut self,
         collateral_token: AccountId,
@@ -439,66 +1011,99 @@ impl ConditionalTokenFramework {
         condition_id: String,
         index_sets: Vec<Vec<U128>>,
     ) -> U128 {
+        self.redeem_positions_detailed(collateral_token, parent_collection_id, condition_id, index_sets, false)
+            .total
+    }
+
+    /// Redeem positions and report the payout of each index set.
+    /// Positions with zero payout are only burned when `burn_losing` is set; an unresolved
+    /// condition redeems nothing instead of panicking so batch redemptions can skip it.
+    /// Tokens under an active lien are left in place for whoever ends up holding them
+    pub fn redeem_positions_detailed(
+        &mut self,
+        collateral_token: AccountId,
+        parent_collection_id: String,
+        condition_id: String,
+        index_sets: Vec<Vec<U128>>,
+        burn_losing: bool,
+    ) -> RedemptionResult {
         let caller = env::predecessor_account_id();
-        
-        // Verify condition is resolved
-        let condition = self.conditions.get(&condition_id)
-            .expect("Condition not found");
-        
-        let payout_numerators = condition.payout_numerators
-            .as_ref()
-            .expect("Condition not resolved yet");
-        
-        let payout_denominator = condition.payout_denominator
-            .expect("Condition not resolved yet");
-        
-        let mut total_payout = 0u128;
         let parent_collection_key = if parent_collection_id.is_empty() {
             String::new()
         } else {
             parent_collection_id.clone()
         };
         
+        let legs = match self.compute_redemption(&caller, &collateral_token, &parent_collection_key, &condition_id, &index_sets) {
+            Some(legs) => legs,
+            None => {
+                env::log_str(&format!("RedemptionSkipped: condition {} not resolved yet", condition_id));
+                return RedemptionResult {
+                    total: U128(0),
+                    per_index_set: index_sets.into_iter().map(|index_set| (index_set, U128(0))).collect(),
+                };
+            }
+        };
+        
+        let mut total_payout = 0u128;
+        let mut per_index_set = Vec::with_capacity(index_sets.len());
+        let mut redeemed_any = false;
+        let mut burned = Vec::new();
+        
         // Process each index set (position type)
-        for index_set in &index_sets {
-            let collection_id = self.get_collection_id(parent_collection_key.clone(), condition_id.clone(), index_set.clone());
-            let position_id = self.get_position_id(collateral_token.clone(), collection_id);
-            let balance_key = format!("{}:{}", position_id, caller);
-            
-            // Get user's balance for this position
-            let position_balance = self.balances.get(&balance_key).unwrap_or(U128(0));
+        for (index_set, leg) in index_sets.iter().zip(legs) {
+            let RedemptionLeg { position_id, position_balance, position_payout } = leg;
             if position_balance.0 == 0 {
+                per_index_set.push((index_set.clone(), U128(0)));
                 continue; // Skip if user has no balance
             }
             
-            // Calculate payout for this position
-            let position_payout = self.calculate_position_payout(
-                index_set,
-                position_balance,
-                payout_numerators,
-                payout_denominator,
-            );
-            
-            // Burn the position tokens
-            self.balances.insert(&balance_key, &U128(0));
+            // Burn the position tokens (losing legs only on request)
+            if position_payout.0 > 0 || burn_losing {
+                let liened = self.liened_balance(&position_id, &caller);
+                self.set_balance(&position_id, &caller, liened);
+                burned.push((position_id, position_balance));
+                redeemed_any = true;
+            }
             
+            per_index_set.push((index_set.clone(), position_payout));
             total_payout += position_payout.0;
         }
+        emit_nft_burn(&caller, burned, "redeem_positions");
         
-        if total_payout > 0 {
+        let composite_parent = self.conditions.get(&condition_id)
+            .filter(|condition| condition.payout_in_positions)
+            .and_then(|condition| condition.parent_condition_id);
+
+        if let Some(parent_condition_id) = &composite_parent {
+            if total_payout > 0 {
+                self.mint_payout_positions(&caller, &collateral_token, &parent_collection_key, &condition_id, parent_condition_id, total_payout);
+            }
+        } else if total_payout > 0 {
             // Transfer collateral to user
             if parent_collection_key.is_empty() {
                 // Redeeming for base collateral
                 self.transfer_collateral_to(env::current_account_id(), caller.clone(), collateral_token.clone(), U128(total_payout));
+                self.adjust_collateral_locked(&condition_id, &collateral_token, total_payout, false);
             } else {
                 // Redeeming for parent position
                 let parent_position_id = self.get_position_id(collateral_token.clone(), parent_collection_key.clone());
                 let parent_balance_key = format!("{}:{}", parent_position_id, caller);
                 let parent_balance = self.balances.get(&parent_balance_key).unwrap_or(U128(0));
                 
-                self.balances.insert(&parent_balance_key, &U128(parent_balance.0 + total_payout));
+                self.set_balance(&parent_position_id, &caller, parent_balance.0 + total_payout);
+                emit_nft_mint(&caller, vec![(parent_position_id, U128(total_payout))], "redeem_positions");
             }
-            
+        }
+        
+        if redeemed_any && parent_collection_key.is_empty() {
+            self.check_condition_accounting(&condition_id, &collateral_token);
+            if let Some(parent_condition_id) = &composite_parent {
+                self.check_condition_accounting(parent_condition_id, &collateral_token);
+            }
+        }
+
+        if redeemed_any {
             // Emit redemption event
             let event = PayoutRedemption {
                 redeemer: caller,
@@ -507,12 +1112,99 @@ impl ConditionalTokenFramework {
                 condition_id,
                 index_sets,
                 payout: U128(total_payout),
+                per_index_set: per_index_set.clone(),
             };
             
             env::log_str(&format!("PayoutRedemption: {:?}", event));
         }
         
-        U128(total_payout)
+        RedemptionResult {
+            total: U128(total_payout),
+            per_index_set,
+        }
+    }
+
+    /// Pay a composite redemption as `amount` complete sets of the parent condition, under the
+    /// same parent collection the redeemed positions sat in. At the top level the backing
+    /// collateral stays locked and moves from the redeemed condition to the parent
+    fn mint_payout_positions(
+        &mut self,
+        caller: &AccountId,
+        collateral_token: &AccountId,
+        parent_collection_key: &String,
+        condition_id: &String,
+        parent_condition_id: &String,
+        amount: u128,
+    ) {
+        let parent_condition = self.conditions.get(parent_condition_id)
+            .expect("Parent condition not found");
+
+        let mut minted = Vec::new();
+        for index_set in self.get_full_partition(parent_condition.outcome_slot_count) {
+            let position_id = self.ensure_position(collateral_token, parent_collection_key, parent_condition_id, index_set);
+            let balance = self.balances.get(&format!("{}:{}", position_id, caller)).unwrap_or(U128(0));
+            self.set_balance(&position_id, caller, balance.0 + amount);
+            minted.push((position_id, U128(amount)));
+        }
+        emit_nft_mint(caller, minted, "redeem_positions");
+
+        if parent_collection_key.is_empty() {
+            self.adjust_collateral_locked(condition_id, collateral_token, amount, false);
+            self.adjust_collateral_locked(parent_condition_id, collateral_token, amount, true);
+        }
+
+        env::log_str(&format!(
+            "PayoutPositionsMinted: redeemer={}, conditionId={}, parentConditionId={}, amount={}",
+            caller, condition_id, parent_condition_id, amount
+        ));
+    }
+
+    /// What `owner` would receive from redeem_positions right now, without burning anything.
+    /// Zero while the condition is unresolved; for a composite payout it is the number of parent
+    /// complete sets
+    pub fn estimate_redemption(
+        &self,
+        owner: AccountId,
+        collateral_token: AccountId,
+        parent_collection_id: String,
+        condition_id: String,
+        index_sets: Vec<Vec<U128>>,
+    ) -> U128 {
+        let legs = self.compute_redemption(&owner, &collateral_token, &parent_collection_id, &condition_id, &index_sets)
+            .unwrap_or_default();
+        U128(legs.iter().map(|leg| leg.position_payout.0).sum())
+    }
+
+    /// Unliened balance and payout of each index set for `owner`, None while the condition is
+    /// unresolved. Both redemption and its estimate go through here
+    fn compute_redemption(
+        &self,
+        owner: &AccountId,
+        collateral_token: &AccountId,
+        parent_collection_key: &String,
+        condition_id: &String,
+        index_sets: &[Vec<U128>],
+    ) -> Option<Vec<RedemptionLeg>> {
+        let condition = self.conditions.get(condition_id)
+            .expect("Condition not found");
+        let payout_numerators = condition.payout_numerators?;
+        let payout_denominator = condition.payout_denominator?;
+
+        let legs = index_sets.iter().map(|index_set| {
+            let collection_id = self.get_collection_id(parent_collection_key.clone(), condition_id.clone(), index_set.clone());
+            let position_id = self.get_position_id(collateral_token.clone(), collection_id);
+            let position_balance = U128(self.available_balance(&position_id, owner));
+
+            let position_payout = if position_balance.0 == 0 {
+                U128(0)
+            } else {
+                self.calculate_position_payout(index_set, position_balance, &payout_numerators, payout_denominator)
+            };
+
+            RedemptionLeg { position_id, position_balance, position_payout }
+        }).collect();
+
+        Some(legs)
     }
 
     /// Calculate payout for a specific position based on reported payouts
@@ -573,8 +1265,7 @@ impl ConditionalTokenFramework {
     /// Set approval for all tokens for an operator (ERC-1155 style)
     pub fn set_approval_for_all(&mut self, operator: AccountId, approved: bool) {
         let owner = env::predecessor_account_id();
-        let approval_key = format!("{}:{}", owner, operator);
-        self.operator_approvals.insert(&approval_key, &approved);
+        self.set_operator_approval(&owner, &operator, approved);
         
         env::log_str(&format!(
             "ApprovalForAll: owner={} operator={} approved={}",
@@ -591,8 +1282,7 @@ impl ConditionalTokenFramework {
     /// Approve specific amount for a specific token (ERC-20 style for individual tokens)
     pub fn approve(&mut self, operator: AccountId, position_id: String, amount: U128) {
         let owner = env::predecessor_account_id();
-        let approval_key = format!("{}:{}:{}", owner, position_id, operator);
-        self.token_approvals.insert(&approval_key, &amount);
+        self.set_token_allowance(&owner, &operator, &position_id, amount.0);
         
         env::log_str(&format!(
             "Approval: owner={} operator={} position_id={} amount={}",
@@ -600,6 +1290,108 @@ impl ConditionalTokenFramework {
         ));
     }
 
+    /// Record an operator approval; revoking one that existed emits ApprovalRevoked
+    fn set_operator_approval(&mut self, owner: &AccountId, operator: &AccountId, approved: bool) {
+        let approval_key = format!("{}:{}", owner, operator);
+        let was_approved = self.operator_approvals.get(&approval_key).unwrap_or(false);
+        if approved {
+            self.operator_approvals.insert(&approval_key, &true);
+        } else {
+            self.operator_approvals.remove(&approval_key);
+        }
+
+        self.list_operator(owner, operator, approved);
+        if was_approved && !approved {
+            Self::log_approval_revoked(owner, operator, None);
+        }
+    }
+
+    /// Record a single-position allowance; a zero amount removes it, and removing a non-zero
+    /// allowance (revoked or fully spent) emits ApprovalRevoked
+    fn set_token_allowance(&mut self, owner: &AccountId, operator: &AccountId, position_id: &str, amount: u128) {
+        let approval_key = format!("{}:{}:{}", owner, position_id, operator);
+        let previous = self.token_approvals.get(&approval_key).map_or(0, |allowance| allowance.0);
+        if amount > 0 {
+            self.token_approvals.insert(&approval_key, &U128(amount));
+        } else {
+            self.token_approvals.remove(&approval_key);
+        }
+
+        self.list_token_approval(owner, operator, position_id, amount > 0);
+        if previous > 0 && amount == 0 {
+            Self::log_approval_revoked(owner, operator, Some(position_id.to_string()));
+        }
+    }
+
+    fn list_operator(&mut self, owner: &AccountId, operator: &AccountId, approved: bool) {
+        let mut operators = self.owner_operators.get(owner).unwrap_or_default();
+        match operators.iter().position(|listed| listed == operator) {
+            Some(index) if !approved => {
+                operators.remove(index);
+            }
+            None if approved => {
+                assert!(operators.len() < MAX_APPROVALS_PER_OWNER, "Too many operator approvals; revoke one first");
+                operators.push(operator.clone());
+            }
+            _ => return,
+        }
+
+        if operators.is_empty() {
+            self.owner_operators.remove(owner);
+        } else {
+            self.owner_operators.insert(owner, &operators);
+        }
+    }
+
+    fn list_token_approval(&mut self, owner: &AccountId, operator: &AccountId, position_id: &str, listed: bool) {
+        let mut approvals = self.owner_token_approvals.get(owner).unwrap_or_default();
+        match approvals.iter().position(|(op, id)| op == operator && id == position_id) {
+            Some(index) if !listed => {
+                approvals.remove(index);
+            }
+            None if listed => {
+                assert!(approvals.len() < MAX_APPROVALS_PER_OWNER, "Too many token approvals; revoke one first");
+                approvals.push((operator.clone(), position_id.to_string()));
+            }
+            _ => return,
+        }
+
+        if approvals.is_empty() {
+            self.owner_token_approvals.remove(owner);
+        } else {
+            self.owner_token_approvals.insert(owner, &approvals);
+        }
+    }
+
+    fn log_approval_revoked(owner: &AccountId, operator: &AccountId, position_id: Option<String>) {
+        let event = ApprovalRevoked {
+            owner: owner.clone(),
+            operator: operator.clone(),
+            position_id,
+        };
+        env::log_str(&format!("ApprovalRevoked: {:?}", event));
+    }
+
+    /// Non-zero single-position allowances granted by an owner, paginated in the order granted
+    pub fn get_approvals_for_owner(&self, owner: AccountId, from_index: u64, limit: u64) -> Vec<TokenApproval> {
+        self.owner_token_approvals
+            .get(&owner)
+            .unwrap_or_default()
+            .into_iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|(operator, position_id)| {
+                let allowance = self.allowance(owner.clone(), operator.clone(), position_id.clone());
+                TokenApproval { operator, position_id, allowance }
+            })
+            .collect()
+    }
+
+    /// Operators approved for all of the owner's positions
+    pub fn get_operators_for_owner(&self, owner: AccountId) -> Vec<AccountId> {
+        self.owner_operators.get(&owner).unwrap_or_default()
+    }
+
     /// Get allowance for specific token
     pub fn allowance(&self, owner: AccountId, operator: AccountId, position_id: String) -> U128 {
         let approval_key = format!("{}:{}:{}", owner, position_id, operator);
@@ -627,10 +1419,9 @@ impl ConditionalTokenFramework {
         
         // Update specific token allowance if used
         if caller != from && !self.is_approved_for_all(from.clone(), caller.clone()) {
-            let approval_key = format!("{}:{}:{}", from, position_id, caller);
-            let current_allowance = self.token_approvals.get(&approval_key).unwrap_or(U128(0));
+            let current_allowance = self.allowance(from.clone(), caller.clone(), position_id.clone());
             assert!(current_allowance.0 >= amount.0, "Insufficient allowance");
-            self.token_approvals.insert(&approval_key, &U128(current_allowance.0 - amount.0));
+            self.set_token_allowance(&from, &caller, &position_id, current_allowance.0 - amount.0);
         }
         
         // Perform transfer
@@ -640,6 +1431,7 @@ impl ConditionalTokenFramework {
             "TransferSingle: operator={} from={} to={} id={} value={}",
             caller, from, to, position_id, amount.0
         ));
+        emit_nft_transfer(&caller, &from, &to, vec![(position_id, amount)], data.clone());
         
         if let Some(data) = data {
             env::log_str(&format!("Transfer data: {}", data));
@@ -677,9 +1469,8 @@ impl ConditionalTokenFramework {
             
             // Update allowance if needed
             if !is_approved {
-                let approval_key = format!("{}:{}:{}", from, position_id, caller);
-                let current_allowance = self.token_approvals.get(&approval_key).unwrap_or(U128(0));
-                self.token_approvals.insert(&approval_key, &U128(current_allowance.0 - amount.0));
+                let current_allowance = self.allowance(from.clone(), caller.clone(), position_id.clone());
+                self.set_token_allowance(&from, &caller, position_id, current_allowance.0 - amount.0);
             }
             
             self.transfer_position(from.clone(), to.clone(), position_id.clone(), amount);
@@ -689,12 +1480,66 @@ impl ConditionalTokenFramework {
             "TransferBatch: operator={} from={} to={} ids={:?} values={:?}",
             caller, from, to, position_ids, amounts
         ));
+        emit_nft_transfer(&caller, &from, &to, position_ids.into_iter().zip(amounts).collect(), data.clone());
         
         if let Some(data) = data {
             env::log_str(&format!("Batch transfer data: {}", data));
         }
     }
 
+    /// Move `from`'s whole unliened balance of each listed position to `to` (portfolio migration,
+    /// liquidation). Needs the owner or an operator approved for all; positions with nothing to
+    /// move are skipped. Logs one TransferBatch for everything moved and returns it
+    pub fn batch_safe_transfer_all(
+        &mut self,
+        from: AccountId,
+        to: AccountId,
+        position_ids: Vec<String>,
+        data: Option<String>,
+    ) -> Vec<(String, U128)> {
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == from || self.is_approved_for_all(from.clone(), caller.clone()),
+            "Transfer not authorized"
+        );
+        assert_ne!(from, to, "Cannot transfer to the same account");
+        assert!(!position_ids.is_empty(), "No positions to transfer");
+        assert!(
+            position_ids.len() <= MAX_TRANSFER_ALL_POSITIONS,
+            "Too many positions (max {} per call)", MAX_TRANSFER_ALL_POSITIONS
+        );
+
+        let mut transferred = Vec::new();
+        for (i, position_id) in position_ids.iter().enumerate() {
+            assert!(!position_ids[..i].contains(position_id), "Duplicate position: {}", position_id);
+            let balance = U128(self.available_balance(position_id, &from));
+            if balance.0 == 0 {
+                continue;
+            }
+            self.transfer_position(from.clone(), to.clone(), position_id.clone(), balance);
+            transferred.push((position_id.clone(), balance));
+        }
+
+        let (ids, values): (Vec<String>, Vec<U128>) = transferred.iter().cloned().unzip();
+        env::log_str(&format!(
+            "TransferBatch: operator={} from={} to={} ids={:?} values={:?}",
+            caller, from, to, ids, values
+        ));
+        emit_nft_transfer(&caller, &from, &to, transferred.clone(), data.clone());
+
+        if let Some(data) = data {
+            env::log_str(&format!("Batch transfer data: {}", data));
+        }
+        transferred
+    }
+
+    /// Everything batch_safe_transfer_all would move from `from`, for a confirmation step.
+    /// Callers split it into chunks of MAX_TRANSFER_ALL_POSITIONS
+    pub fn get_positions_for_transfer(&self, from: AccountId, to: AccountId) -> Vec<(String, U128)> {
+        assert_ne!(from, to, "Cannot transfer to the same account");
+        self.get_user_positions(from)
+    }
+
     /// Internal transfer function
     fn transfer_position(&mut self, from: AccountId, to: AccountId, position_id: String, amount: U128) {
         let from_key = format!("{}:{}", position_id, from);
@@ -702,11 +1547,87 @@ impl ConditionalTokenFramework {
         
         let from_balance = self.balances.get(&from_key).unwrap_or(U128(0));
         assert!(from_balance.0 >= amount.0, "Insufficient balance");
+        assert!(self.available_balance(&position_id, &from) >= amount.0, "Position balance is under lien");
         
-        self.balances.insert(&from_key, &U128(from_balance.0 - amount.0));
+        self.set_balance(&position_id, &from, from_balance.0 - amount.0);
         
         let to_balance = self.balances.get(&to_key).unwrap_or(U128(0));
-        self.balances.insert(&to_key, &U128(to_balance.0 + amount.0));
+        self.set_balance(&position_id, &to, to_balance.0 + amount.0);
+    }
+
+    /// Write a balance and keep position_holders and user_position_index in step; a zero
+    /// balance drops the holder
+    fn set_balance(&mut self, position_id: &str, owner: &AccountId, balance: u128) {
+        let previous = self.balances.insert(&format!("{}:{}", position_id, owner), &U128(balance)).map_or(0, |b| b.0);
+        if previous != balance {
+            let supply_key = position_id.to_string();
+            let supply = (self.position_supply.get(&supply_key).map_or(0, |s| s.0) + balance).saturating_sub(previous);
+            if supply == 0 {
+                self.position_supply.remove(&supply_key);
+            } else {
+                self.position_supply.insert(&supply_key, &U128(supply));
+            }
+        }
+        self.index_user_position(owner, position_id, balance > 0);
+
+        let holders_key = position_id.to_string();
+        let mut holders = self.position_holders.get(&holders_key).unwrap_or_default();
+        match holders.iter().position(|holder| holder == owner) {
+            Some(index) if balance == 0 => {
+                holders.remove(index);
+            }
+            None if balance > 0 => {
+                if holders.len() >= MAX_POSITION_HOLDERS {
+                    env::log_str(&format!("Holder list for position {} is full; {} not listed", position_id, owner));
+                    return;
+                }
+                holders.push(owner.clone());
+            }
+            _ => return,
+        }
+
+        if holders.is_empty() {
+            self.position_holders.remove(&holders_key);
+        } else {
+            self.position_holders.insert(&holders_key, &holders);
+        }
+    }
+
+    fn index_user_position(&mut self, owner: &AccountId, position_id: &str, held: bool) {
+        let mut position_ids = self.user_position_index.get(owner).unwrap_or_default();
+        match position_ids.iter().position(|id| id == position_id) {
+            Some(index) if !held => {
+                position_ids.remove(index);
+            }
+            None if held => position_ids.push(position_id.to_string()),
+            _ => return,
+        }
+
+        if position_ids.is_empty() {
+            self.user_position_index.remove(owner);
+        } else {
+            self.user_position_index.insert(owner, &position_ids);
+        }
+    }
+
+    /// Holders of a position with their balances, paginated in the order they first acquired it
+    pub fn get_position_holders(&self, position_id: String, from_index: u64, limit: u64) -> Vec<(AccountId, U128)> {
+        self.position_holders
+            .get(&position_id)
+            .unwrap_or_default()
+            .into_iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|holder| {
+                let balance = self.balance_of(holder.clone(), position_id.clone());
+                (holder, balance)
+            })
+            .collect()
+    }
+
+    /// Listed holders of a position; stops at MAX_POSITION_HOLDERS
+    pub fn get_position_holder_count(&self, position_id: String) -> u64 {
+        self.position_holders.get(&position_id).map_or(0, |holders| holders.len() as u64)
     }
 
     /// Get balance of a specific position for an account (ERC-1155 style)
@@ -790,6 +1711,24 @@ impl ConditionalTokenFramework {
         self.collateral_tokens.to_vec()
     }
 
+    /// Track collateral entering (split from collateral) or leaving (merge, redemption) the
+    /// contract, in total and for the condition it moved through. The total saturates at zero:
+    /// collateral deposited before tracking began was never counted
+    fn adjust_collateral_locked(&mut self, condition_id: &String, token: &AccountId, amount: u128, lock: bool) {
+        let locked = self.collateral_locked.get(token).unwrap_or(U128(0)).0;
+        let updated = if lock { locked + amount } else { locked.saturating_sub(amount) };
+        self.collateral_locked.insert(token, &U128(updated));
+
+        let key = format!("{}:{}", condition_id, token);
+        let mut collateral = self.condition_collateral.get(&key).unwrap_or_default();
+        if lock {
+            collateral.deposited.0 += amount;
+        } else {
+            collateral.withdrawn.0 += amount;
+        }
+        self.condition_collateral.insert(&key, &collateral);
+    }
+
     /// Transfer collateral from user to contract (internal)
     fn transfer_collateral_from(&self, from: AccountId, to: AccountId, token: AccountId, amount: U128) {
         // In production, this would call the fungible token contract
@@ -818,6 +1757,16 @@ impl ConditionalTokenFramework {
     // QUERY FUNCTIONS
     // ============================================================================
 
+    /// Collateral of `token` backing outstanding positions
+    pub fn get_total_collateral_locked(&self, token: AccountId) -> U128 {
+        self.collateral_locked.get(&token).unwrap_or(U128(0))
+    }
+
+    /// Collateral locked per token, for TVL dashboards
+    pub fn get_all_collateral_locked(&self) -> Vec<(AccountId, U128)> {
+        self.collateral_locked.iter().collect()
+    }
+
     /// Get all conditions
     pub fn get_conditions(&self) -> Vec<(String, Condition)> {
         self.conditions.iter().collect()
@@ -837,11 +1786,159 @@ impl ConditionalTokenFramework {
         positions
     }
 
+    /// A user's positions with balance, outcome and payout, paginated in the order first
+    /// acquired. Reads only the user's own index rather than every position
+    pub fn get_all_user_positions_paginated(&self, user: AccountId, from_index: u64, limit: u64) -> Vec<UserPosition> {
+        self.user_position_index
+            .get(&user)
+            .unwrap_or_default()
+            .into_iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|position_id| self.user_position(&user, position_id))
+            .collect()
+    }
+
+    /// Positions with a non-zero balance held by `user`, for paging get_all_user_positions_paginated
+    pub fn get_user_position_count(&self, user: AccountId) -> u64 {
+        self.user_position_index.get(&user).map_or(0, |position_ids| position_ids.len() as u64)
+    }
+
+    fn user_position(&self, user: &AccountId, position_id: String) -> UserPosition {
+        let balance = self.balance_of(user.clone(), position_id.clone());
+        let position = self.positions.get(&position_id);
+        let index_set = position.as_ref().map(|p| p.index_set.clone()).unwrap_or_default();
+        let outcome_index = index_set.iter()
+            .map(|index| index.0.trailing_zeros() as u8)
+            .min()
+            .unwrap_or(0);
+
+        let condition_id = position.map(|p| p.condition_id).unwrap_or_default();
+        let payouts = self.conditions.get(&condition_id)
+            .and_then(|condition| Some((condition.payout_numerators?, condition.payout_denominator?)));
+        let payout_amount = payouts.as_ref().map(|(numerators, denominator)| {
+            self.calculate_position_payout(&index_set, balance, numerators, *denominator)
+        });
+
+        UserPosition {
+            position_id,
+            balance,
+            condition_id,
+            outcome_index,
+            is_resolved: payouts.is_some(),
+            payout_amount,
+        }
+    }
+
     /// Get position details
     pub fn get_position(&self, position_id: String) -> Option<Position> {
         self.positions.get(&position_id)
     }
 
+    /// Name a position for display. Callable by the owner or the oracle of the position's
+    /// condition; replaces any earlier metadata
+    pub fn set_position_metadata(&mut self, position_id: String, metadata: PositionMetadata) {
+        let position = self.positions.get(&position_id).expect("Position not found");
+        let condition = self.conditions.get(&position.condition_id).expect("Condition not found");
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == self.owner || caller == condition.oracle,
+            "Only owner or the condition's oracle can set position metadata"
+        );
+
+        assert!(!metadata.name.trim().is_empty(), "Metadata name cannot be empty");
+        for field in [&metadata.name, &metadata.symbol, &metadata.market_id, &metadata.outcome_label] {
+            assert!(field.len() <= MAX_METADATA_FIELD_LEN, "Metadata field too long (max {} bytes)", MAX_METADATA_FIELD_LEN);
+        }
+        if let Some(icon_uri) = &metadata.icon_uri {
+            assert!(icon_uri.len() <= MAX_METADATA_FIELD_LEN, "Icon URI too long (max {} bytes)", MAX_METADATA_FIELD_LEN);
+        }
+        assert!(
+            metadata.description.len() <= MAX_METADATA_DESCRIPTION_LEN,
+            "Metadata description too long (max {} bytes)", MAX_METADATA_DESCRIPTION_LEN
+        );
+
+        self.position_metadata.insert(&position_id, &metadata);
+        env::log_str(&format!(
+            "PositionMetadataSet: position_id={} name={} by={}",
+            position_id, metadata.name, caller
+        ));
+    }
+
+    /// Display metadata for a position, if any has been set
+    pub fn get_position_metadata(&self, position_id: String) -> Option<PositionMetadata> {
+        self.position_metadata.get(&position_id)
+    }
+
+    fn assert_valid_outcome_labels(labels: &[String], outcome_slot_count: u8) {
+        assert_eq!(labels.len(), outcome_slot_count as usize, "Expected {} outcome labels", outcome_slot_count);
+        for (i, label) in labels.iter().enumerate() {
+            assert!(!label.trim().is_empty(), "Outcome labels cannot be empty");
+            assert!(label.len() <= MAX_OUTCOME_LABEL_LEN, "Outcome label too long (max {} bytes)", MAX_OUTCOME_LABEL_LEN);
+            assert!(!labels[..i].contains(label), "Duplicate outcome label: {}", label);
+        }
+    }
+
+    /// Title a condition for display; callable by its oracle
+    pub fn set_condition_metadata(&mut self, condition_id: String, title: String) {
+        let mut condition = self.conditions.get(&condition_id).expect("Condition not found");
+        assert_eq!(env::predecessor_account_id(), condition.oracle, "Only the condition's oracle can set its metadata");
+        assert!(!title.trim().is_empty(), "Title cannot be empty");
+        assert!(title.len() <= MAX_METADATA_FIELD_LEN, "Title too long (max {} bytes)", MAX_METADATA_FIELD_LEN);
+
+        condition.title = Some(title.clone());
+        self.conditions.insert(&condition_id, &condition);
+        env::log_str(&format!("ConditionMetadataSet: condition_id={} title={}", condition_id, title));
+    }
+
+    /// Label of one outcome slot: the label given at prepare_condition, NO/YES for a binary
+    /// condition without labels, otherwise "Outcome <n>"
+    fn outcome_label(condition: &Condition, outcome: usize) -> String {
+        match &condition.outcome_labels {
+            Some(labels) => labels[outcome].clone(),
+            None if condition.outcome_slot_count == 2 => ["NO", "YES"][outcome].to_string(),
+            None => format!("Outcome {}", outcome),
+        }
+    }
+
+    /// Wallet display metadata for a position. A position covering several outcomes lists their
+    /// labels joined with " | "
+    pub fn position_metadata(&self, position_id: String) -> Option<PositionTokenMetadata> {
+        let position = self.positions.get(&position_id)?;
+        let condition = self.conditions.get(&position.condition_id)?;
+        let index_set = position.index_set.iter().fold(0u128, |set, index| set | index.0);
+        let outcome_label = (0..condition.outcome_slot_count as usize)
+            .filter(|outcome| index_set & (1u128 << outcome) != 0)
+            .map(|outcome| Self::outcome_label(&condition, outcome))
+            .collect::<Vec<String>>()
+            .join(" | ");
+
+        Some(PositionTokenMetadata {
+            title: condition.title.clone().unwrap_or_else(|| condition.question_id.clone()),
+            outcome_label,
+            condition_id: position.condition_id,
+            collateral_token: position.collateral_token,
+            decimals: POSITION_DECIMALS,
+        })
+    }
+
+    pub fn metadata(&self) -> CtfContractMetadata {
+        CtfContractMetadata {
+            name: CTF_METADATA_NAME.to_string(),
+            symbol_prefix: CTF_METADATA_SYMBOL_PREFIX.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            base_uri: self.base_uri.clone(),
+        }
+    }
+
+    pub fn set_base_uri(&mut self, base_uri: Option<String>) {
+        assert_eq!(env::predecessor_account_id(), self.owner, "Only owner can set the base URI");
+        if let Some(uri) = &base_uri {
+            assert!(uri.len() <= MAX_METADATA_FIELD_LEN, "Base URI too long (max {} bytes)", MAX_METADATA_FIELD_LEN);
+        }
+        self.base_uri = base_uri;
+    }
+
     /// Get collection details
     pub fn get_collection(&self, collection_id: String) -> Option<Collection> {
         self.collections.get(&collection_id)
@@ -849,17 +1946,70 @@ impl ConditionalTokenFramework {
 
     /// Get total supply for a position
     pub fn total_supply(&self, position_id: String) -> U128 {
-        let mut total = 0u128;
-        
-        // This is inefficient but works for demonstration
-        // In production, you'd maintain a separate total supply mapping
-        for (balance_key, balance) in self.balances.iter() {
-            if balance_key.starts_with(&format!("{}:", position_id)) {
-                total += balance.0;
+        self.position_supply.get(&position_id).unwrap_or(U128(0))
+    }
+
+    /// Collateral a condition has taken in and paid out in `collateral_token` next to the supply
+    /// of each of its outcome positions. Only positions split straight from collateral count:
+    /// tokens split further under another condition leave the outcome supply
+    pub fn get_condition_accounting(&self, condition_id: String, collateral_token: AccountId) -> ConditionAccounting {
+        let condition = self.conditions.get(&condition_id).expect("Condition not found");
+        let collateral = self.condition_collateral.get(&format!("{}:{}", condition_id, collateral_token)).unwrap_or_default();
+        let net_collateral = collateral.deposited.0.saturating_sub(collateral.withdrawn.0);
+
+        let outcome_supplies: Vec<OutcomeSupply> = self.get_full_partition(condition.outcome_slot_count)
+            .into_iter()
+            .map(|index_set| {
+                let collection_id = self.get_collection_id(String::new(), condition_id.clone(), vec![index_set]);
+                let position_id = self.get_position_id(collateral_token.clone(), collection_id);
+                let supply = self.total_supply(position_id.clone());
+                OutcomeSupply { index_set, position_id, supply }
+            })
+            .collect();
+        let invariant_holds = Self::complete_sets_backed(&condition, net_collateral, &outcome_supplies);
+
+        ConditionAccounting {
+            condition_id,
+            collateral_token,
+            deposited: collateral.deposited,
+            withdrawn: collateral.withdrawn,
+            net_collateral: U128(net_collateral),
+            outcome_supplies,
+            is_resolved: condition.payout_numerators.is_some(),
+            invariant_holds,
+        }
+    }
+
+    /// Whether the condition's outcome tokens are exactly backed by the collateral it holds
+    pub fn check_invariant(&self, condition_id: String, collateral_token: AccountId) -> bool {
+        self.get_condition_accounting(condition_id, collateral_token).invariant_holds
+    }
+
+    /// Before resolution every outcome's supply equals the net collateral (each token came from
+    /// a complete set). Once resolved, winners redeem one by one, so the collateral left only has
+    /// to cover what the remaining tokens pay out
+    fn complete_sets_backed(condition: &Condition, net_collateral: u128, outcome_supplies: &[OutcomeSupply]) -> bool {
+        match (&condition.payout_numerators, condition.payout_denominator) {
+            (Some(numerators), Some(denominator)) => {
+                let owed: u128 = outcome_supplies.iter().zip(numerators)
+                    .map(|(outcome, numerator)| outcome.supply.0.saturating_mul(numerator.0))
+                    .fold(0, u128::saturating_add);
+                net_collateral.saturating_mul(denominator.0) >= owed
             }
+            _ => outcome_supplies.iter().all(|outcome| outcome.supply.0 == net_collateral),
+        }
+    }
+
+    /// Log AccountingInvariantBroken if a mutation left a binary condition's tokens out of step
+    /// with its collateral, which points at a bug in split, merge or redeem accounting
+    fn check_condition_accounting(&self, condition_id: &String, collateral_token: &AccountId) {
+        if self.conditions.get(condition_id).map_or(true, |c| c.outcome_slot_count != 2) {
+            return;
+        }
+        let accounting = self.get_condition_accounting(condition_id.clone(), collateral_token.clone());
+        if !accounting.invariant_holds {
+            env::log_str(&format!("AccountingInvariantBroken: {:?}", accounting));
         }
-        
-        U128(total)
     }
 
     /// Check if position exists
@@ -867,6 +2017,123 @@ impl ConditionalTokenFramework {
         self.positions.get(&position_id).is_some()
     }
 
+    // ============================================================================
+    // POSITION-BACKED LENDING
+    // ============================================================================
+
+    /// Pledge `amount` of the caller's position to `lender` until `maturity_timestamp`. The
+    /// pledged tokens stay with the caller but cannot move until the lender releases or
+    /// liquidates the lien. Returns the lien id
+    pub fn create_lien(
+        &mut self,
+        position_id: String,
+        amount: U128,
+        lender: AccountId,
+        interest_rate_bps: u16,
+        maturity_timestamp: u64,
+    ) -> String {
+        let borrower = env::predecessor_account_id();
+        assert!(amount.0 > 0, "Amount must be positive");
+        assert_ne!(borrower, lender, "Cannot pledge a position to yourself");
+        assert!(interest_rate_bps <= MAX_LIEN_INTEREST_BPS, "Interest rate too high (max {} bps)", MAX_LIEN_INTEREST_BPS);
+        assert!(maturity_timestamp > env::block_timestamp(), "Maturity must be in the future");
+        assert!(self.positions.get(&position_id).is_some(), "Position not found");
+        assert!(self.available_balance(&position_id, &borrower) >= amount.0, "Insufficient unliened balance");
+
+        let lien_id = format!("lien_{}", self.liens.len());
+        let lien = LienRecord {
+            lien_id: lien_id.clone(),
+            position_id: position_id.clone(),
+            borrower: borrower.clone(),
+            lender: lender.clone(),
+            amount,
+            interest_rate_bps,
+            maturity_timestamp,
+            created_at: env::block_timestamp(),
+            status: LienStatus::Active,
+        };
+        self.liens.insert(&lien_id, &lien);
+        let liened = self.liened_balance(&position_id, &borrower);
+        self.liened_balances.insert(&format!("{}:{}", position_id, borrower), &U128(liened + amount.0));
+
+        env::log_str(&format!(
+            "LienCreated: lien_id={} position_id={} borrower={} lender={} amount={} interest_rate_bps={} maturity={}",
+            lien_id, position_id, borrower, lender, amount.0, interest_rate_bps, maturity_timestamp
+        ));
+        lien_id
+    }
+
+    /// Lift a lien once the lender has been repaid; the tokens become the borrower's to move again
+    pub fn release_lien(&mut self, lien_id: String) {
+        let lien = self.settle_lien(&lien_id, LienStatus::Released);
+        env::log_str(&format!(
+            "LienReleased: lien_id={} position_id={} borrower={} amount={}",
+            lien_id, lien.position_id, lien.borrower, lien.amount.0
+        ));
+    }
+
+    /// Take the pledged tokens from an unpaid borrower after maturity. If the condition has
+    /// resolved meanwhile the lender redeems them like any other holder
+    pub fn liquidate_lien(&mut self, lien_id: String) {
+        let lien = self.liens.get(&lien_id).expect("Lien not found");
+        assert!(env::block_timestamp() >= lien.maturity_timestamp, "Lien has not matured");
+
+        let lien = self.settle_lien(&lien_id, LienStatus::Liquidated);
+        self.transfer_position(lien.borrower.clone(), lien.lender.clone(), lien.position_id.clone(), lien.amount);
+        env::log_str(&format!(
+            "LienLiquidated: lien_id={} position_id={} borrower={} lender={} amount={}",
+            lien_id, lien.position_id, lien.borrower, lien.lender, lien.amount.0
+        ));
+        emit_nft_transfer(
+            &lien.lender,
+            &lien.borrower,
+            &lien.lender,
+            vec![(lien.position_id.clone(), lien.amount)],
+            Some(format!("lien_liquidated:{}", lien_id)),
+        );
+    }
+
+    /// Close an active lien on behalf of its lender and free the pledged balance
+    fn settle_lien(&mut self, lien_id: &String, status: LienStatus) -> LienRecord {
+        let mut lien = self.liens.get(lien_id).expect("Lien not found");
+        assert_eq!(env::predecessor_account_id(), lien.lender, "Only the lender can settle a lien");
+        assert_eq!(lien.status, LienStatus::Active, "Lien is not active");
+
+        let liened_key = format!("{}:{}", lien.position_id, lien.borrower);
+        let remaining = self.liened_balance(&lien.position_id, &lien.borrower).saturating_sub(lien.amount.0);
+        if remaining == 0 {
+            self.liened_balances.remove(&liened_key);
+        } else {
+            self.liened_balances.insert(&liened_key, &U128(remaining));
+        }
+
+        lien.status = status;
+        self.liens.insert(lien_id, &lien);
+        lien
+    }
+
+    fn liened_balance(&self, position_id: &str, owner: &AccountId) -> u128 {
+        self.liened_balances.get(&format!("{}:{}", position_id, owner)).map_or(0, |liened| liened.0)
+    }
+
+    /// Balance `owner` can transfer, split, merge or redeem
+    fn available_balance(&self, position_id: &str, owner: &AccountId) -> u128 {
+        self.balance_of(owner.clone(), position_id.to_string()).0.saturating_sub(self.liened_balance(position_id, owner))
+    }
+
+    pub fn get_lien(&self, lien_id: String) -> Option<LienRecord> {
+        self.liens.get(&lien_id)
+    }
+
+    /// Part of `owner`'s balance pledged under active liens
+    pub fn get_liened_balance(&self, owner: AccountId, position_id: String) -> U128 {
+        U128(self.liened_balance(&position_id, &owner))
+    }
+
+    pub fn get_available_balance(&self, owner: AccountId, position_id: String) -> U128 {
+        U128(self.available_balance(&position_id, &owner))
+    }
+
     // ============================================================================
     // ADMIN FUNCTIONS
     // ============================================================================
@@ -944,6 +2211,7 @@ mod tests {
             "oracle.testnet".parse().unwrap(),
             "Will BTC reach $100k by 2025?".to_string(),
             2, // Binary outcome
+            None,
         );
         
         // Verify condition exists
@@ -970,6 +2238,7 @@ mod tests {
             "oracle.testnet".parse().unwrap(),
             "Test Market".to_string(),
             2,
+            None,
         );
         
         // Split position
@@ -1014,6 +2283,7 @@ mod tests {
             "oracle.testnet".parse().unwrap(),
             "Test Market".to_string(),
             2,
+            None,
         );
         
         testing_env!(get_context("user.testnet"));
@@ -1043,6 +2313,120 @@ mod tests {
         assert_eq!(balance_yes.0, 50_000_000); // Original 100 - merged 50
     }
 
+    #[test]
+    fn test_split_subset_of_outcomes() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        contract.register_collateral_token("usdc.testnet".parse().unwrap());
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+
+        testing_env!(get_context("oracle.testnet"));
+        let condition_id = contract.prepare_condition(
+            "oracle.testnet".parse().unwrap(),
+            "Three outcome market".to_string(),
+            3,
+            None,
+        );
+
+        // Collateral -> {A}, {B|C}
+        testing_env!(get_context("user.testnet"));
+        contract.split_position(usdc.clone(), String::new(), condition_id.clone(), vec![U128(1), U128(6)], U128(100));
+
+        // {B|C} -> {B}, {C} without touching collateral
+        contract.split_position(usdc.clone(), String::new(), condition_id.clone(), vec![U128(2), U128(4)], U128(40));
+
+        let position = |contract: &ConditionalTokenFramework, index_set: u128| {
+            let collection_id = contract.get_collection_id(String::new(), condition_id.clone(), vec![U128(index_set)]);
+            contract.get_position_id(usdc.clone(), collection_id)
+        };
+        let user: AccountId = "user.testnet".parse().unwrap();
+
+        assert_eq!(contract.balance_of(user.clone(), position(&contract, 1)).0, 100);
+        assert_eq!(contract.balance_of(user.clone(), position(&contract, 6)).0, 60);
+        assert_eq!(contract.balance_of(user.clone(), position(&contract, 2)).0, 40);
+        assert_eq!(contract.balance_of(user.clone(), position(&contract, 4)).0, 40);
+
+        // Merge {B}, {C} back into {B|C}
+        contract.merge_positions(usdc.clone(), String::new(), condition_id.clone(), vec![U128(2), U128(4)], U128(40));
+        assert_eq!(contract.balance_of(user.clone(), position(&contract, 6)).0, 100);
+        assert_eq!(contract.balance_of(user.clone(), position(&contract, 2)).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient balance of position being split")]
+    fn test_subset_split_requires_union_position() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        contract.register_collateral_token("usdc.testnet".parse().unwrap());
+
+        testing_env!(get_context("oracle.testnet"));
+        let condition_id = contract.prepare_condition(
+            "oracle.testnet".parse().unwrap(),
+            "Three outcome market".to_string(),
+            3,
+            None,
+        );
+
+        // A subset split never pulls collateral
+        testing_env!(get_context("user.testnet"));
+        contract.split_position("usdc.testnet".parse().unwrap(), String::new(), condition_id, vec![U128(2), U128(4)], U128(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index set must be a strict subset of the outcomes")]
+    fn test_split_rejects_full_index_set() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        contract.register_collateral_token("usdc.testnet".parse().unwrap());
+
+        testing_env!(get_context("oracle.testnet"));
+        let condition_id = contract.prepare_condition(
+            "oracle.testnet".parse().unwrap(),
+            "Test Market".to_string(),
+            2,
+            None,
+        );
+
+        testing_env!(get_context("user.testnet"));
+        contract.split_position("usdc.testnet".parse().unwrap(), String::new(), condition_id, vec![U128(3)], U128(10));
+    }
+
+    #[test]
+    fn test_nested_split_and_merge_two_levels() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        contract.register_collateral_token("usdc.testnet".parse().unwrap());
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        let user: AccountId = "user.testnet".parse().unwrap();
+
+        testing_env!(get_context("oracle.testnet"));
+        let condition_a = contract.prepare_condition("oracle.testnet".parse().unwrap(), "Market A".to_string(), 2, None);
+        let condition_b = contract.prepare_condition("oracle.testnet".parse().unwrap(), "Market B".to_string(), 2, None);
+
+        // Level 1: collateral -> A-YES, A-NO
+        testing_env!(get_context("user.testnet"));
+        contract.split_position(usdc.clone(), String::new(), condition_a.clone(), vec![U128(1), U128(2)], U128(100));
+        let a_yes = contract.get_collection_id(String::new(), condition_a.clone(), vec![U128(1)]);
+        let a_yes_position = contract.get_position_id(usdc.clone(), a_yes.clone());
+
+        // Level 2: A-YES -> (A-YES & B-YES), (A-YES & B-NO)
+        contract.split_position(usdc.clone(), a_yes.clone(), condition_b.clone(), vec![U128(1), U128(2)], U128(30));
+        let a_yes_b_yes = contract.get_collection_id(a_yes.clone(), condition_b.clone(), vec![U128(1)]);
+        let a_yes_b_yes_position = contract.get_position_id(usdc.clone(), a_yes_b_yes.clone());
+
+        assert_eq!(contract.balance_of(user.clone(), a_yes_position.clone()).0, 70);
+        assert_eq!(contract.balance_of(user.clone(), a_yes_b_yes_position.clone()).0, 30);
+        assert_eq!(contract.get_collection(a_yes_b_yes).unwrap().parent_collection_id, a_yes);
+
+        // Merge level 2 back into A-YES, then level 1 back into collateral
+        contract.merge_positions(usdc.clone(), a_yes.clone(), condition_b, vec![U128(1), U128(2)], U128(30));
+        assert_eq!(contract.balance_of(user.clone(), a_yes_position.clone()).0, 100);
+        assert_eq!(contract.balance_of(user.clone(), a_yes_b_yes_position).0, 0);
+
+        contract.merge_positions(usdc, String::new(), condition_a, vec![U128(1), U128(2)], U128(100));
+        assert_eq!(contract.balance_of(user, a_yes_position).0, 0);
+    }
+
     #[test]
     fn test_report_payouts_and_redeem() {
         testing_env!(get_context("user.testnet"));
@@ -1058,6 +2442,7 @@ mod tests {
             "oracle.testnet".parse().unwrap(),
             "Test Market".to_string(),
             2,
+            None,
         );
         
         // Split position to get outcome tokens
@@ -1097,6 +2482,93 @@ mod tests {
         assert_eq!(balance_yes.0, 0); // Tokens burned during redemption
     }
 
+    #[test]
+    fn test_redeem_positions_detailed_breakdown() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        contract.register_collateral_token("usdc.testnet".parse().unwrap());
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        let user: AccountId = "user.testnet".parse().unwrap();
+
+        testing_env!(get_context("oracle.testnet"));
+        let condition_id = contract.prepare_condition("oracle.testnet".parse().unwrap(), "Test Market".to_string(), 2, None);
+
+        testing_env!(get_context("user.testnet"));
+        contract.split_position(usdc.clone(), String::new(), condition_id.clone(), vec![U128(1), U128(2)], U128(100));
+
+        // Unresolved: nothing paid, nothing burned
+        let result = contract.redeem_positions_detailed(usdc.clone(), String::new(), condition_id.clone(), vec![vec![U128(1)]], true);
+        assert_eq!(result.total.0, 0);
+
+        testing_env!(get_context("oracle.testnet"));
+        contract.report_payouts("Test Market".to_string(), vec![U128(1), U128(0)]);
+
+        testing_env!(get_context("user.testnet"));
+        let no_collection = contract.get_collection_id(String::new(), condition_id.clone(), vec![U128(2)]);
+        let no_position = contract.get_position_id(usdc.clone(), no_collection);
+
+        let result = contract.redeem_positions_detailed(
+            usdc.clone(),
+            String::new(),
+            condition_id.clone(),
+            vec![vec![U128(1)], vec![U128(2)]],
+            false,
+        );
+        assert_eq!(result.total.0, 100);
+        assert_eq!(result.per_index_set[0].1 .0, 100);
+        assert_eq!(result.per_index_set[1].1 .0, 0);
+        // Losing leg kept without burn_losing
+        assert_eq!(contract.balance_of(user.clone(), no_position.clone()).0, 100);
+
+        let result = contract.redeem_positions_detailed(usdc, String::new(), condition_id, vec![vec![U128(2)]], true);
+        assert_eq!(result.total.0, 0);
+        assert_eq!(contract.balance_of(user, no_position).0, 0);
+    }
+
+    #[test]
+    fn test_batch_report_payouts_partial_success() {
+        testing_env!(get_context("oracle.testnet"));
+
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+
+        let oracle: AccountId = "oracle.testnet".parse().unwrap();
+        let game_1 = contract.prepare_condition(oracle.clone(), "Game 1".to_string(), 2, None);
+        let game_2 = contract.prepare_condition(oracle.clone(), "Game 2".to_string(), 2, None);
+        let game_3 = contract.prepare_condition(oracle, "Game 3".to_string(), 2, None);
+
+        // Game 1 resolved individually beforehand
+        contract.report_payouts("Game 1".to_string(), vec![U128(1), U128(0)]);
+
+        let resolved = contract.batch_report_payouts(vec![
+            ("Game 1".to_string(), vec![U128(0), U128(1)]),
+            ("Game 2".to_string(), vec![U128(1), U128(0)]),
+            ("Game 3".to_string(), vec![U128(1), U128(1)]),
+        ]);
+
+        assert_eq!(resolved, 2);
+        assert!(contract.is_condition_resolved(game_2));
+        assert!(contract.is_condition_resolved(game_3.clone()));
+
+        // Previously reported payouts are left untouched
+        let condition_1 = contract.get_condition(game_1).unwrap();
+        assert_eq!(condition_1.payout_numerators.unwrap()[0].0, 1);
+
+        let condition_3 = contract.get_condition(game_3).unwrap();
+        assert_eq!(condition_3.payout_denominator.unwrap().0, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only oracle can report payouts")]
+    fn test_batch_report_payouts_unauthorized() {
+        testing_env!(get_context("oracle.testnet"));
+
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        contract.prepare_condition("oracle.testnet".parse().unwrap(), "Game 1".to_string(), 2, None);
+
+        testing_env!(get_context("attacker.testnet"));
+        contract.batch_report_payouts(vec![("Game 1".to_string(), vec![U128(1), U128(0)])]);
+    }
+
     #[test]
     fn test_erc1155_transfers() {
         testing_env!(get_context("user.testnet"));
@@ -1112,6 +2584,7 @@ mod tests {
             "oracle.testnet".parse().unwrap(),
             "Test Market".to_string(),
             2,
+            None,
         );
         
         testing_env!(get_context("user.testnet"));
@@ -1167,6 +2640,7 @@ mod tests {
             "oracle.testnet".parse().unwrap(),
             "Test Market".to_string(),
             2,
+            None,
         );
         
         testing_env!(get_context("user.testnet"));
@@ -1269,12 +2743,14 @@ mod tests {
             "oracle.testnet".parse().unwrap(),
             "Market 1".to_string(),
             2,
+            None,
         );
         
         let condition_id2 = contract.prepare_condition(
             "oracle.testnet".parse().unwrap(),
             "Market 2".to_string(),
             2,
+            None,
         );
         
         // Create positions
@@ -1357,6 +2833,7 @@ mod tests {
             "oracle.testnet".parse().unwrap(),
             "Test Market".to_string(),
             2,
+            None,
         );
         
         // Create positions
@@ -1384,4 +2861,1032 @@ mod tests {
         let version = contract.get_version();
         assert!(version.contains("ConditionalTokenFramework-NEAR"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_estimate_redemption_matches_redeem() {
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        let user: AccountId = "user.testnet".parse().unwrap();
+        let index_sets = vec![vec![U128(1)], vec![U128(2)]];
+
+        // YES wins, NO wins, invalid 50/50, and an uneven split
+        for payouts in [vec![1, 0], vec![0, 1], vec![1, 1], vec![3, 1]] {
+            testing_env!(get_context("owner.testnet"));
+            let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+            contract.register_collateral_token(usdc.clone());
+
+            testing_env!(get_context("oracle.testnet"));
+            let condition_id = contract.prepare_condition("oracle.testnet".parse().unwrap(), "Test Market".to_string(), 2, None);
+
+            testing_env!(get_context("user.testnet"));
+            contract.split_position(usdc.clone(), String::new(), condition_id.clone(), vec![U128(1), U128(2)], U128(1_000_001));
+            assert_eq!(
+                contract.estimate_redemption(user.clone(), usdc.clone(), String::new(), condition_id.clone(), index_sets.clone()).0,
+                0
+            );
+
+            testing_env!(get_context("oracle.testnet"));
+            contract.report_payouts("Test Market".to_string(), payouts.iter().map(|p| U128(*p)).collect());
+            let (numerators, _) = contract.get_payouts(condition_id.clone()).unwrap();
+            assert_eq!(numerators.len(), 2);
+
+            testing_env!(get_context("user.testnet"));
+            let estimate = contract.estimate_redemption(user.clone(), usdc.clone(), String::new(), condition_id.clone(), index_sets.clone());
+            let redeemed = contract.redeem_positions(usdc.clone(), String::new(), condition_id, index_sets.clone());
+            assert_eq!(estimate.0, redeemed.0, "estimate drifted for payouts {:?}", payouts);
+        }
+    }
+
+    #[test]
+    fn test_migrate_v1_state() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("ctf.testnet".parse().unwrap())
+            .predecessor_account_id("ctf.testnet".parse().unwrap())
+            .build());
+
+        // Write state the way the unversioned contract stored it
+        let mut old = migration::ConditionalTokenFrameworkV1 {
+            conditions: UnorderedMap::new(b"c"),
+            collections: UnorderedMap::new(b"o"),
+            positions: UnorderedMap::new(b"p"),
+            balances: UnorderedMap::new(b"b"),
+            operator_approvals: UnorderedMap::new(b"a"),
+            token_approvals: UnorderedMap::new(b"t"),
+            collateral_tokens: UnorderedSet::new(b"k"),
+            owner: "owner.testnet".parse().unwrap(),
+        };
+        old.balances.insert(&"position_1:user.testnet".to_string(), &U128(42));
+        old.operator_approvals.insert(&"user.testnet:market.testnet".to_string(), &true);
+        old.operator_approvals.insert(&"user.testnet:former.testnet".to_string(), &false);
+        old.token_approvals.insert(&"user.testnet:position_1:solver.testnet".to_string(), &U128(7));
+        old.token_approvals.insert(&"user.testnet:position_2:solver.testnet".to_string(), &U128(0));
+        old.collateral_tokens.insert(&"usdc.testnet".parse().unwrap());
+        env::state_write(&old);
+
+        let contract = ConditionalTokenFramework::migrate();
+        assert_eq!(contract.get_state_version(), StateVersion::V4);
+        assert_eq!(contract.get_owner(), "owner.testnet".parse::<AccountId>().unwrap());
+        assert_eq!(contract.balance_of("user.testnet".parse().unwrap(), "position_1".to_string()), U128(42));
+        assert_eq!(
+            contract.get_position_holders("position_1".to_string(), 0, 10),
+            vec![("user.testnet".parse().unwrap(), U128(42))]
+        );
+        assert_eq!(contract.get_user_position_count("user.testnet".parse().unwrap()), 1);
+        assert_eq!(contract.total_supply("position_1".to_string()), U128(42));
+        assert!(contract.is_collateral_token_registered("usdc.testnet".parse().unwrap()));
+        assert!(contract.get_pending_upgrade().is_none());
+        // Approval lists are rebuilt from the live approvals only
+        assert_eq!(contract.get_operators_for_owner("user.testnet".parse().unwrap()), vec!["market.testnet".parse::<AccountId>().unwrap()]);
+        assert_eq!(contract.get_approvals_for_owner("user.testnet".parse().unwrap(), 0, 10), vec![TokenApproval {
+            operator: "solver.testnet".parse().unwrap(),
+            position_id: "position_1".to_string(),
+            allowance: U128(7),
+        }]);
+
+        // Running migrate against current state leaves it untouched
+        env::state_write(&contract);
+        let again = ConditionalTokenFramework::migrate();
+        assert_eq!(again.get_state_version(), StateVersion::V4);
+        assert_eq!(again.balance_of("user.testnet".parse().unwrap(), "position_1".to_string()), U128(42));
+    }
+
+    #[test]
+    fn test_migrate_v2_conditions() {
+        testing_env!(get_context("ctf.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+
+        // Conditions written before the composite payout fields existed
+        let mut legacy: UnorderedMap<String, migration::ConditionV2> = UnorderedMap::new(b"c");
+        legacy.insert(&"condition_1".to_string(), &migration::ConditionV2 {
+            oracle: "oracle.testnet".parse().unwrap(),
+            question_id: "Resolved".to_string(),
+            outcome_slot_count: 2,
+            payout_numerators: Some(vec![U128(1), U128(0)]),
+            payout_denominator: Some(U128(1)),
+        });
+        legacy.insert(&"condition_2".to_string(), &migration::ConditionV2 {
+            oracle: "oracle.testnet".parse().unwrap(),
+            question_id: "Open".to_string(),
+            outcome_slot_count: 3,
+            payout_numerators: None,
+            payout_denominator: None,
+        });
+        contract.conditions = borsh::from_slice(&borsh::to_vec(&legacy).unwrap()).unwrap();
+        contract.state_version = StateVersion::V2;
+        env::state_write(&contract);
+
+        let migrated = ConditionalTokenFramework::migrate();
+        assert_eq!(migrated.get_state_version(), StateVersion::V4);
+        let resolved = migrated.get_condition("condition_1".to_string()).unwrap();
+        assert_eq!(resolved.payout_numerators, Some(vec![U128(1), U128(0)]));
+        assert!(!resolved.payout_in_positions);
+        assert_eq!(resolved.parent_condition_id, None);
+        let open = migrated.get_condition("condition_2".to_string()).unwrap();
+        assert_eq!((open.question_id.as_str(), open.outcome_slot_count), ("Open", 3));
+        assert_eq!(migrated.get_conditions().len(), 2);
+    }
+
+    #[test]
+    fn test_migrate_v3_conditions() {
+        testing_env!(get_context("ctf.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+
+        // A composite-payout condition written before outcome labels and titles existed
+        let mut legacy: UnorderedMap<String, migration::ConditionV3> = UnorderedMap::new(b"c");
+        legacy.insert(&"condition_1".to_string(), &migration::ConditionV3 {
+            oracle: "oracle.testnet".parse().unwrap(),
+            question_id: "Game".to_string(),
+            outcome_slot_count: 2,
+            payout_numerators: Some(vec![U128(1), U128(1)]),
+            payout_denominator: Some(U128(2)),
+            payout_in_positions: true,
+            parent_condition_id: Some("condition_2".to_string()),
+        });
+        contract.conditions = borsh::from_slice(&borsh::to_vec(&legacy).unwrap()).unwrap();
+        contract.state_version = StateVersion::V3;
+        env::state_write(&contract);
+
+        let migrated = ConditionalTokenFramework::migrate();
+        assert_eq!(migrated.get_state_version(), StateVersion::V4);
+        let condition = migrated.get_condition("condition_1".to_string()).unwrap();
+        assert!(condition.payout_in_positions);
+        assert_eq!(condition.parent_condition_id, Some("condition_2".to_string()));
+        assert_eq!((condition.outcome_labels, condition.title), (None, None));
+    }
+
+    #[test]
+    #[should_panic(expected = "Code does not match the proposed upgrade")]
+    fn test_upgrade_rejects_unapproved_code() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        contract.propose_upgrade(env::sha256_array(b"approved code").into());
+        assert!(contract.get_pending_upgrade().is_some());
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("owner.testnet".parse().unwrap())
+            .input(b"some other code".to_vec())
+            .build());
+        contract.upgrade();
+    }
+
+    #[test]
+    fn test_position_holders_track_balances() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        contract.register_collateral_token("usdc.testnet".parse().unwrap());
+
+        testing_env!(get_context("oracle.testnet"));
+        let condition_id = contract.prepare_condition("oracle.testnet".parse().unwrap(), "Holders".to_string(), 2, None);
+
+        testing_env!(get_context("alice.testnet"));
+        contract.split_position(
+            "usdc.testnet".parse().unwrap(),
+            String::new(),
+            condition_id.clone(),
+            vec![U128(1), U128(2)],
+            U128(100),
+        );
+        let collection_id_yes = contract.get_collection_id(String::new(), condition_id.clone(), vec![U128(1)]);
+        let position_id_yes = contract.get_position_id("usdc.testnet".parse().unwrap(), collection_id_yes);
+
+        contract.safe_transfer_from("alice.testnet".parse().unwrap(), "bob.testnet".parse().unwrap(), position_id_yes.clone(), U128(40), None);
+        contract.safe_transfer_from("alice.testnet".parse().unwrap(), "carol.testnet".parse().unwrap(), position_id_yes.clone(), U128(10), None);
+        assert_eq!(contract.get_position_holder_count(position_id_yes.clone()), 3);
+        assert_eq!(
+            contract.get_position_holders(position_id_yes.clone(), 1, 10),
+            vec![("bob.testnet".parse().unwrap(), U128(40)), ("carol.testnet".parse().unwrap(), U128(10))]
+        );
+
+        // Emptying a balance drops the holder
+        testing_env!(get_context("carol.testnet"));
+        contract.safe_transfer_from("carol.testnet".parse().unwrap(), "bob.testnet".parse().unwrap(), position_id_yes.clone(), U128(10), None);
+        assert_eq!(
+            contract.get_position_holders(position_id_yes.clone(), 0, 10),
+            vec![("alice.testnet".parse().unwrap(), U128(50)), ("bob.testnet".parse().unwrap(), U128(50))]
+        );
+
+        // Redeeming the winning leg burns it
+        testing_env!(get_context("oracle.testnet"));
+        contract.report_payouts("Holders".to_string(), vec![U128(1), U128(0)]);
+        testing_env!(get_context("bob.testnet"));
+        contract.redeem_positions("usdc.testnet".parse().unwrap(), String::new(), condition_id, vec![vec![U128(1)]]);
+        assert_eq!(contract.get_position_holder_count(position_id_yes.clone()), 1);
+        assert_eq!(contract.get_position_holder_count("unknown".to_string()), 0);
+    }
+
+    #[test]
+    fn test_position_holder_list_is_capped() {
+        testing_env!(get_context("ctf.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+
+        for i in 0..=MAX_POSITION_HOLDERS {
+            contract.set_balance("position_1", &format!("holder{}.testnet", i).parse().unwrap(), 1);
+        }
+        assert_eq!(contract.get_position_holder_count("position_1".to_string()), MAX_POSITION_HOLDERS as u64);
+        // The unlisted holder's balance is still credited
+        assert_eq!(contract.balance_of(format!("holder{}.testnet", MAX_POSITION_HOLDERS).parse().unwrap(), "position_1".to_string()), U128(1));
+    }
+
+    #[test]
+    fn test_approval_lists_follow_grants_spends_and_revocations() {
+        testing_env!(get_context("ctf.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        let alice: AccountId = "alice.testnet".parse().unwrap();
+        contract.set_balance("position_1", &alice, 100);
+
+        testing_env!(get_context("alice.testnet"));
+        contract.approve("bob.testnet".parse().unwrap(), "position_1".to_string(), U128(60));
+        contract.approve("carol.testnet".parse().unwrap(), "position_2".to_string(), U128(50));
+        contract.set_approval_for_all("dave.testnet".parse().unwrap(), true);
+
+        assert_eq!(contract.get_approvals_for_owner(alice.clone(), 0, 10).len(), 2);
+        assert_eq!(contract.get_approvals_for_owner(alice.clone(), 1, 10), vec![TokenApproval {
+            operator: "carol.testnet".parse().unwrap(),
+            position_id: "position_2".to_string(),
+            allowance: U128(50),
+        }]);
+        assert_eq!(contract.get_operators_for_owner(alice.clone()), vec!["dave.testnet".parse::<AccountId>().unwrap()]);
+
+        // A partly spent allowance stays listed with what is left; a fully spent one is dropped
+        testing_env!(get_context("bob.testnet"));
+        contract.safe_transfer_from(alice.clone(), "bob.testnet".parse().unwrap(), "position_1".to_string(), U128(20), None);
+        assert_eq!(contract.get_approvals_for_owner(alice.clone(), 0, 1)[0].allowance, U128(40));
+        contract.safe_transfer_from(alice.clone(), "bob.testnet".parse().unwrap(), "position_1".to_string(), U128(40), None);
+        assert_eq!(contract.get_approvals_for_owner(alice.clone(), 0, 10).len(), 1);
+
+        testing_env!(get_context("alice.testnet"));
+        contract.approve("carol.testnet".parse().unwrap(), "position_2".to_string(), U128(0));
+        contract.set_approval_for_all("dave.testnet".parse().unwrap(), false);
+        assert!(contract.get_approvals_for_owner(alice.clone(), 0, 10).is_empty());
+        assert!(contract.get_operators_for_owner(alice.clone()).is_empty());
+        assert!(!contract.is_approved_for_all(alice, "dave.testnet".parse().unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Too many token approvals; revoke one first")]
+    fn test_token_approval_list_is_capped() {
+        testing_env!(get_context("alice.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+
+        for i in 0..=MAX_APPROVALS_PER_OWNER {
+            contract.approve("bob.testnet".parse().unwrap(), format!("position_{}", i), U128(1));
+        }
+    }
+
+    #[test]
+    fn test_batch_safe_transfer_all_moves_whole_balances() {
+        let (mut contract, position_ids) = transfer_all_setup();
+
+        // An approved operator sweeps everything, including a position the user holds none of
+        testing_env!(get_context("user.testnet"));
+        contract.set_approval_for_all("migrator.testnet".parse().unwrap(), true);
+        contract.safe_transfer_from(
+            "user.testnet".parse().unwrap(),
+            "friend.testnet".parse().unwrap(),
+            position_ids[3].clone(),
+            U128(100_000_000),
+            None,
+        );
+
+        let preview = contract.get_positions_for_transfer("user.testnet".parse().unwrap(), "vault.testnet".parse().unwrap());
+        assert_eq!(preview.len(), 3);
+
+        testing_env!(get_context("migrator.testnet"));
+        let moved = contract.batch_safe_transfer_all(
+            "user.testnet".parse().unwrap(),
+            "vault.testnet".parse().unwrap(),
+            position_ids.clone(),
+            None,
+        );
+
+        assert_eq!(moved.len(), 3);
+        assert!(moved.iter().all(|(_, amount)| amount.0 == 100_000_000));
+        for position_id in &position_ids[..3] {
+            assert_eq!(contract.balance_of("user.testnet".parse().unwrap(), position_id.clone()), U128(0));
+            assert_eq!(contract.balance_of("vault.testnet".parse().unwrap(), position_id.clone()), U128(100_000_000));
+        }
+        assert_eq!(contract.balance_of("friend.testnet".parse().unwrap(), position_ids[3].clone()), U128(100_000_000));
+        assert!(contract.get_user_positions("user.testnet".parse().unwrap()).is_empty());
+
+        // One batch event for the whole sweep
+        let batch_logs = near_sdk::test_utils::get_logs().into_iter().filter(|log| log.starts_with("TransferBatch")).count();
+        assert_eq!(batch_logs, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfer not authorized")]
+    fn test_batch_safe_transfer_all_requires_operator_approval() {
+        let (mut contract, position_ids) = transfer_all_setup();
+
+        // A per-token allowance isn't enough to sweep a portfolio
+        testing_env!(get_context("user.testnet"));
+        contract.approve("migrator.testnet".parse().unwrap(), position_ids[0].clone(), U128(100_000_000));
+
+        testing_env!(get_context("migrator.testnet"));
+        contract.batch_safe_transfer_all(
+            "user.testnet".parse().unwrap(),
+            "vault.testnet".parse().unwrap(),
+            vec![position_ids[0].clone()],
+            None,
+        );
+    }
+
+    /// Four positions of 100 USDC each for user.testnet (YES and NO of two conditions)
+    fn transfer_all_setup() -> (ConditionalTokenFramework, Vec<String>) {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        contract.register_collateral_token("usdc.testnet".parse().unwrap());
+
+        let mut position_ids = Vec::new();
+        for question in ["Market 1", "Market 2"] {
+            testing_env!(get_context("oracle.testnet"));
+            let condition_id = contract.prepare_condition("oracle.testnet".parse().unwrap(), question.to_string(), 2, None);
+
+            testing_env!(get_context("user.testnet"));
+            contract.split_position(
+                "usdc.testnet".parse().unwrap(),
+                String::new(),
+                condition_id.clone(),
+                vec![U128(1), U128(2)],
+                U128(100_000_000),
+            );
+            for index_set in [1, 2] {
+                let collection_id = contract.get_collection_id(String::new(), condition_id.clone(), vec![U128(index_set)]);
+                position_ids.push(contract.get_position_id("usdc.testnet".parse().unwrap(), collection_id));
+            }
+        }
+        (contract, position_ids)
+    }
+
+    #[test]
+    fn test_user_positions_paginated() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        contract.register_collateral_token("usdc.testnet".parse().unwrap());
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        let user: AccountId = "user.testnet".parse().unwrap();
+        let bob: AccountId = "bob.testnet".parse().unwrap();
+
+        testing_env!(get_context("oracle.testnet"));
+        let condition_id = contract.prepare_condition("oracle.testnet".parse().unwrap(), "Paginated".to_string(), 2, None);
+
+        testing_env!(get_context("user.testnet"));
+        contract.split_position(usdc.clone(), String::new(), condition_id.clone(), vec![U128(1), U128(2)], U128(100));
+        assert_eq!(contract.get_user_position_count(user.clone()), 2);
+
+        let first = contract.get_all_user_positions_paginated(user.clone(), 0, 1);
+        let rest = contract.get_all_user_positions_paginated(user.clone(), 1, 10);
+        assert_eq!((first.len(), rest.len()), (1, 1));
+        assert_eq!(
+            (first[0].outcome_index, rest[0].outcome_index, first[0].condition_id.clone()),
+            (0, 1, condition_id.clone())
+        );
+        assert_eq!((first[0].balance, first[0].is_resolved, first[0].payout_amount), (U128(100), false, None));
+
+        // Moving the whole balance moves the position between the two indexes
+        let no_position = rest[0].position_id.clone();
+        contract.safe_transfer_from(user.clone(), bob.clone(), no_position.clone(), U128(100), None);
+        assert_eq!(contract.get_user_position_count(user.clone()), 1);
+        assert_eq!(contract.get_all_user_positions_paginated(bob.clone(), 0, 10)[0].position_id, no_position);
+
+        testing_env!(get_context("oracle.testnet"));
+        contract.report_payouts("Paginated".to_string(), vec![U128(1), U128(0)]);
+        let yes = &contract.get_all_user_positions_paginated(user.clone(), 0, 10)[0];
+        assert_eq!((yes.is_resolved, yes.payout_amount), (true, Some(U128(100))));
+        let no = &contract.get_all_user_positions_paginated(bob.clone(), 0, 10)[0];
+        assert_eq!(no.payout_amount, Some(U128(0)));
+
+        // Redeeming burns the winning balance and empties the user's index
+        testing_env!(get_context("user.testnet"));
+        contract.redeem_positions(usdc, String::new(), condition_id, vec![vec![U128(1)]]);
+        assert_eq!(contract.get_user_position_count(user.clone()), 0);
+        assert!(contract.get_all_user_positions_paginated(user, 0, 10).is_empty());
+    }
+
+    fn batch_test_setup(markets: usize) -> (ConditionalTokenFramework, Vec<String>) {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        contract.register_collateral_token("usdc.testnet".parse().unwrap());
+
+        testing_env!(get_context("oracle.testnet"));
+        let condition_ids = (0..markets)
+            .map(|i| contract.prepare_condition("oracle.testnet".parse().unwrap(), format!("Market {}", i), 2, None))
+            .collect();
+        (contract, condition_ids)
+    }
+
+    fn split_op(condition_id: &str, amount: u128) -> SplitOp {
+        SplitOp {
+            collateral_token: "usdc.testnet".parse().unwrap(),
+            parent_collection_id: String::new(),
+            condition_id: condition_id.to_string(),
+            partition: vec![U128(1), U128(2)],
+            amount: U128(amount),
+        }
+    }
+
+    #[test]
+    fn test_batch_split_matches_individual_splits() {
+        let (mut contract, condition_ids) = batch_test_setup(5);
+        let ops: Vec<SplitOp> = condition_ids.iter().enumerate()
+            .map(|(i, condition_id)| split_op(condition_id, (i as u128 + 1) * 1_000_000))
+            .collect();
+
+        testing_env!(get_context("alice.testnet"));
+        for op in ops.clone() {
+            contract.split_position(op.collateral_token, op.parent_collection_id, op.condition_id, op.partition, op.amount);
+        }
+        testing_env!(get_context("bob.testnet"));
+        contract.batch_split_position(ops);
+
+        let alice: AccountId = "alice.testnet".parse().unwrap();
+        let bob: AccountId = "bob.testnet".parse().unwrap();
+        assert_eq!(contract.get_user_positions(alice.clone()).len(), 10);
+        for (position_id, balance) in contract.get_user_positions(alice) {
+            assert_eq!(contract.balance_of(bob.clone(), position_id), balance);
+        }
+
+        // Merging it all back in one batch empties the account
+        let merges: Vec<MergeOp> = condition_ids.iter().enumerate()
+            .map(|(i, condition_id)| split_op(condition_id, (i as u128 + 1) * 1_000_000))
+            .collect();
+        contract.batch_merge_positions(merges);
+        assert_eq!(contract.get_user_position_count(bob), 0);
+    }
+
+    #[test]
+    fn test_invalid_op_aborts_whole_batch() {
+        let (mut contract, condition_ids) = batch_test_setup(2);
+        let user: AccountId = "user.testnet".parse().unwrap();
+        testing_env!(get_context("user.testnet"));
+
+        contract.split_position("usdc.testnet".parse().unwrap(), String::new(), condition_ids[0].clone(), vec![U128(1), U128(2)], U128(100));
+        // Each merge alone is covered, together they overdraw; the first is not applied either
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.batch_merge_positions(vec![split_op(&condition_ids[0], 60), split_op(&condition_ids[0], 60)]);
+        }));
+        assert!(result.is_err());
+        for (_, balance) in contract.get_user_positions(user.clone()) {
+            assert_eq!(balance.0, 100);
+        }
+
+        // A split later in the batch may spend what an earlier one minted, but an unknown
+        // condition stops the batch before the first split is applied
+        let yes_collection = contract.get_collection_id(String::new(), condition_ids[1].clone(), vec![U128(1)]);
+        let mut nested = split_op(&condition_ids[0], 50);
+        nested.parent_collection_id = yes_collection;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.batch_split_position(vec![split_op(&condition_ids[1], 50), nested.clone(), split_op("missing", 50)]);
+        }));
+        assert!(result.is_err());
+        assert_eq!(contract.get_user_position_count(user.clone()), 2);
+
+        contract.batch_split_position(vec![split_op(&condition_ids[1], 50), nested]);
+        assert_eq!(contract.get_user_position_count(user), 5);
+    }
+
+    #[test]
+    fn test_position_metadata_set_by_owner_or_oracle() {
+        let (mut contract, condition_ids) = batch_test_setup(1);
+        testing_env!(get_context("user.testnet"));
+        contract.batch_split_position(vec![split_op(&condition_ids[0], 100)]);
+
+        let collection_id = contract.get_collection_id(String::new(), condition_ids[0].clone(), vec![U128(1)]);
+        let position_id = contract.get_position_id("usdc.testnet".parse().unwrap(), collection_id);
+        assert!(contract.get_position_metadata(position_id.clone()).is_none());
+
+        let mut metadata = PositionMetadata {
+            name: "YES - Will BTC reach $100k".to_string(),
+            symbol: "BTC100K-YES".to_string(),
+            description: "Pays 1 USDC if BTC trades above $100k".to_string(),
+            icon_uri: None,
+            market_id: "market_1".to_string(),
+            outcome_label: "YES".to_string(),
+        };
+        testing_env!(get_context("oracle.testnet"));
+        contract.set_position_metadata(position_id.clone(), metadata.clone());
+        assert_eq!(contract.get_position_metadata(position_id.clone()), Some(metadata.clone()));
+
+        metadata.icon_uri = Some("https://example.com/btc.svg".to_string());
+        testing_env!(get_context("owner.testnet"));
+        contract.set_position_metadata(position_id.clone(), metadata.clone());
+        assert_eq!(contract.get_position_metadata(position_id.clone()).unwrap().icon_uri, metadata.icon_uri);
+
+        testing_env!(get_context("user.testnet"));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_position_metadata(position_id.clone(), metadata.clone());
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_collateral_locked_follows_splits_merges_and_redemptions() {
+        let (mut contract, condition_ids) = batch_test_setup(2);
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        assert_eq!(contract.get_total_collateral_locked(usdc.clone()), U128(0));
+
+        testing_env!(get_context("user.testnet"));
+        contract.batch_split_position(vec![split_op(&condition_ids[0], 100), split_op(&condition_ids[1], 100)]);
+        assert_eq!(contract.get_total_collateral_locked(usdc.clone()), U128(200));
+
+        // Splitting an outcome position further moves no collateral
+        let yes_collection = contract.get_collection_id(String::new(), condition_ids[1].clone(), vec![U128(1)]);
+        let mut nested = split_op(&condition_ids[0], 40);
+        nested.parent_collection_id = yes_collection;
+        contract.batch_split_position(vec![nested]);
+        assert_eq!(contract.get_total_collateral_locked(usdc.clone()), U128(200));
+
+        contract.merge_positions(usdc.clone(), String::new(), condition_ids[0].clone(), vec![U128(1), U128(2)], U128(30));
+        assert_eq!(contract.get_total_collateral_locked(usdc.clone()), U128(170));
+
+        testing_env!(get_context("oracle.testnet"));
+        contract.report_payouts("Market 0".to_string(), vec![U128(1), U128(0)]);
+        testing_env!(get_context("user.testnet"));
+        contract.redeem_positions(usdc.clone(), String::new(), condition_ids[0].clone(), vec![vec![U128(1)]]);
+        assert_eq!(contract.get_total_collateral_locked(usdc.clone()), U128(100));
+        assert_eq!(contract.get_all_collateral_locked(), vec![(usdc, U128(100))]);
+    }
+
+    #[test]
+    fn test_condition_accounting_tracks_complete_sets() {
+        let (mut contract, condition_ids) = batch_test_setup(2);
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        let condition_id = condition_ids[0].clone();
+
+        testing_env!(get_context("user.testnet"));
+        contract.split_position(usdc.clone(), String::new(), condition_id.clone(), vec![U128(1), U128(2)], U128(100));
+        let yes_position = contract.get_condition_accounting(condition_id.clone(), usdc.clone()).outcome_supplies[0].position_id.clone();
+        contract.safe_transfer_from("user.testnet".parse().unwrap(), "bob.testnet".parse().unwrap(), yes_position.clone(), U128(40), None);
+        contract.merge_positions(usdc.clone(), String::new(), condition_id.clone(), vec![U128(1), U128(2)], U128(30));
+
+        let accounting = contract.get_condition_accounting(condition_id.clone(), usdc.clone());
+        assert_eq!((accounting.deposited, accounting.withdrawn, accounting.net_collateral), (U128(100), U128(30), U128(70)));
+        let supplies: Vec<U128> = accounting.outcome_supplies.iter().map(|outcome| outcome.supply).collect();
+        assert_eq!(supplies, vec![U128(70), U128(70)]);
+        assert_eq!(contract.total_supply(yes_position), U128(70));
+        assert!(accounting.invariant_holds && !accounting.is_resolved);
+
+        // After resolution the collateral left only has to cover what the remaining tokens pay
+        testing_env!(get_context("oracle.testnet"));
+        contract.report_payouts("Market 0".to_string(), vec![U128(1), U128(0)]);
+        testing_env!(get_context("user.testnet"));
+        contract.redeem_positions(usdc.clone(), String::new(), condition_id.clone(), vec![vec![U128(1)]]);
+        let accounting = contract.get_condition_accounting(condition_id.clone(), usdc.clone());
+        assert_eq!(accounting.net_collateral, U128(40));
+        assert_eq!(accounting.outcome_supplies[0].supply, U128(40)); // bob's YES, not redeemed yet
+        assert!(contract.check_invariant(condition_id, usdc));
+    }
+
+    #[test]
+    fn test_broken_accounting_is_reported() {
+        let (mut contract, condition_ids) = batch_test_setup(1);
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        let user: AccountId = "user.testnet".parse().unwrap();
+
+        testing_env!(get_context("user.testnet"));
+        contract.split_position(usdc.clone(), String::new(), condition_ids[0].clone(), vec![U128(1), U128(2)], U128(100));
+        assert!(contract.check_invariant(condition_ids[0].clone(), usdc.clone()));
+        assert!(!near_sdk::test_utils::get_logs().iter().any(|log| log.starts_with("AccountingInvariantBroken")));
+
+        // YES minted without collateral behind it
+        let yes_position = contract.get_condition_accounting(condition_ids[0].clone(), usdc.clone()).outcome_supplies[0].position_id.clone();
+        contract.set_balance(&yes_position, &user, 150);
+        assert!(!contract.check_invariant(condition_ids[0].clone(), usdc.clone()));
+
+        contract.split_position(usdc, String::new(), condition_ids[0].clone(), vec![U128(1), U128(2)], U128(10));
+        assert!(near_sdk::test_utils::get_logs().iter().any(|log| log.starts_with("AccountingInvariantBroken")));
+    }
+
+    #[test]
+    fn test_resolution_timestamp_and_submitter_recorded() {
+        testing_env!(get_context("oracle.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        let oracle: AccountId = "oracle.testnet".parse().unwrap();
+        let game_1 = contract.prepare_condition(oracle.clone(), "Game 1".to_string(), 2, None);
+        let game_2 = contract.prepare_condition(oracle.clone(), "Game 2".to_string(), 2, None);
+        assert_eq!(contract.get_condition_resolution_timestamp(game_1.clone()), None);
+
+        contract.report_payouts("Game 1".to_string(), vec![U128(1), U128(0)]);
+
+        let mut later = get_context("oracle.testnet");
+        later.block_timestamp += 60_000_000_000;
+        testing_env!(later);
+        contract.batch_report_payouts(vec![("Game 2".to_string(), vec![U128(0), U128(1)])]);
+
+        assert_eq!(contract.get_condition_resolution_timestamp(game_1.clone()), Some(1000000000000000000));
+        assert_eq!(contract.get_condition_resolution_timestamp(game_2.clone()), Some(1000000060000000000));
+        assert_eq!(contract.get_condition_resolution_submitter(game_2.clone()), Some(oracle.clone()));
+
+        let history = contract.get_condition_resolution_history(0, 10);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].condition_id, game_1);
+        assert_eq!(history[1], ConditionResolution {
+            condition_id: game_2,
+            question_id: "Game 2".to_string(),
+            payout_numerators: vec![U128(0), U128(1)],
+            resolved_at: 1000000060000000000,
+            submitter: oracle,
+        });
+        assert_eq!(contract.get_condition_resolution_history(1, 10).len(), 1);
+    }
+
+    fn outcome_position(contract: &ConditionalTokenFramework, condition_id: &str, index_set: u128) -> String {
+        let collection_id = contract.get_collection_id(String::new(), condition_id.to_string(), vec![U128(index_set)]);
+        contract.get_position_id("usdc.testnet".parse().unwrap(), collection_id)
+    }
+
+    #[test]
+    fn test_composite_payout_moves_collateral_to_parent_condition() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        let alice: AccountId = "alice.testnet".parse().unwrap();
+        contract.register_collateral_token(usdc.clone());
+
+        testing_env!(get_context("oracle.testnet"));
+        let oracle: AccountId = "oracle.testnet".parse().unwrap();
+        let game = contract.prepare_condition(oracle.clone(), "Game".to_string(), 2, None);
+        let replay = contract.prepare_condition(oracle.clone(), "Replay".to_string(), 2, None);
+
+        testing_env!(get_context("alice.testnet"));
+        contract.split_position(usdc.clone(), String::new(), game.clone(), vec![U128(1), U128(2)], U128(100));
+
+        // Only the oracle resolves, and never into its own positions
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_composite_payout(game.clone(), vec![U128(1), U128(1)], Some(replay.clone()));
+        }));
+        assert!(result.is_err());
+        testing_env!(get_context("oracle.testnet"));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_composite_payout(game.clone(), vec![U128(1), U128(1)], Some(game.clone()));
+        }));
+        assert!(result.is_err());
+
+        // The game ends in a draw that is settled by a replay
+        contract.set_composite_payout(game.clone(), vec![U128(1), U128(1)], Some(replay.clone()));
+        let resolved = contract.get_condition(game.clone()).unwrap();
+        assert!(resolved.payout_in_positions);
+        assert_eq!(resolved.parent_condition_id, Some(replay.clone()));
+
+        testing_env!(get_context("alice.testnet"));
+        let result = contract.redeem_positions_detailed(usdc.clone(), String::new(), game.clone(), vec![vec![U128(1)], vec![U128(2)]], false);
+        assert_eq!(result.total, U128(100));
+        assert_eq!(contract.balance_of(alice.clone(), outcome_position(&contract, &game, 1)), U128(0));
+        assert_eq!(contract.balance_of(alice.clone(), outcome_position(&contract, &replay, 1)), U128(100));
+        assert_eq!(contract.balance_of(alice.clone(), outcome_position(&contract, &replay, 2)), U128(100));
+
+        // No collateral left the contract; it now backs the replay's complete sets
+        assert_eq!(contract.get_total_collateral_locked(usdc.clone()), U128(100));
+        let game_accounting = contract.get_condition_accounting(game.clone(), usdc.clone());
+        assert_eq!((game_accounting.deposited, game_accounting.withdrawn), (U128(100), U128(100)));
+        let replay_accounting = contract.get_condition_accounting(replay.clone(), usdc.clone());
+        assert_eq!(replay_accounting.net_collateral, U128(100));
+        assert!(game_accounting.invariant_holds && replay_accounting.invariant_holds);
+
+        // The replay resolves normally and pays collateral
+        testing_env!(get_context("oracle.testnet"));
+        contract.set_composite_payout(replay.clone(), vec![U128(0), U128(1)], None);
+        assert!(!contract.get_condition(replay.clone()).unwrap().payout_in_positions);
+        testing_env!(get_context("alice.testnet"));
+        assert_eq!(contract.redeem_positions(usdc.clone(), String::new(), replay.clone(), vec![vec![U128(2)]]), U128(100));
+        assert_eq!(contract.get_total_collateral_locked(usdc), U128(0));
+    }
+
+    #[test]
+    fn test_composite_payout_three_level_condition_tree() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        let alice: AccountId = "alice.testnet".parse().unwrap();
+        contract.register_collateral_token(usdc.clone());
+
+        // Match -> Replay -> Penalties: each level settles a draw of the one below
+        testing_env!(get_context("oracle.testnet"));
+        let oracle: AccountId = "oracle.testnet".parse().unwrap();
+        let game = contract.prepare_condition(oracle.clone(), "Match".to_string(), 2, None);
+        let replay = contract.prepare_condition(oracle.clone(), "Replay".to_string(), 2, None);
+        let penalties = contract.prepare_condition(oracle.clone(), "Penalties".to_string(), 2, None);
+
+        testing_env!(get_context("alice.testnet"));
+        contract.split_position(usdc.clone(), String::new(), game.clone(), vec![U128(1), U128(2)], U128(100));
+        contract.safe_transfer_from(alice.clone(), "bob.testnet".parse().unwrap(), outcome_position(&contract, &game, 2), U128(100), None);
+
+        // Level 1: a draw, alice's home leg is worth half a replay complete set
+        testing_env!(get_context("oracle.testnet"));
+        contract.set_composite_payout(game.clone(), vec![U128(1), U128(1)], Some(replay.clone()));
+        testing_env!(get_context("alice.testnet"));
+        assert_eq!(contract.redeem_positions(usdc.clone(), String::new(), game.clone(), vec![vec![U128(1)]]), U128(50));
+
+        // Level 2: the replay is also inconclusive, 1:3 towards away, and rolls into penalties.
+        // A resolved condition can't take composite payouts any more
+        testing_env!(get_context("oracle.testnet"));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_composite_payout(penalties.clone(), vec![U128(1), U128(0)], Some(game.clone()));
+        }));
+        assert!(result.is_err());
+        contract.set_composite_payout(replay.clone(), vec![U128(1), U128(3)], Some(penalties.clone()));
+        testing_env!(get_context("alice.testnet"));
+        let result = contract.redeem_positions_detailed(usdc.clone(), String::new(), replay.clone(), vec![vec![U128(1)], vec![U128(2)]], false);
+        // 50 * 1/4 and 50 * 3/4, each rounded down
+        assert_eq!(result.per_index_set, vec![(vec![U128(1)], U128(12)), (vec![U128(2)], U128(37))]);
+        assert_eq!(contract.balance_of(alice.clone(), outcome_position(&contract, &penalties, 1)), U128(49));
+        assert_eq!(contract.balance_of(alice.clone(), outcome_position(&contract, &penalties, 2)), U128(49));
+
+        // Level 3: penalties resolve outright and pay collateral
+        testing_env!(get_context("oracle.testnet"));
+        contract.report_payouts("Penalties".to_string(), vec![U128(0), U128(1)]);
+        testing_env!(get_context("alice.testnet"));
+        assert_eq!(contract.redeem_positions(usdc.clone(), String::new(), penalties.clone(), vec![vec![U128(2)]]), U128(49));
+
+        // Bob's away leg still redeems into the replay; every level stays fully backed
+        testing_env!(get_context("bob.testnet"));
+        assert_eq!(contract.redeem_positions(usdc.clone(), String::new(), game.clone(), vec![vec![U128(2)]]), U128(50));
+        for condition_id in [&game, &replay, &penalties] {
+            assert!(contract.check_invariant(condition_id.clone(), usdc.clone()), "{} out of balance", condition_id);
+        }
+        assert_eq!(contract.get_total_collateral_locked(usdc), U128(51));
+    }
+
+    #[test]
+    fn test_position_metadata_defaults_to_no_yes_labels() {
+        let (mut contract, condition_ids) = batch_test_setup(1);
+        testing_env!(get_context("user.testnet"));
+        contract.batch_split_position(vec![split_op(&condition_ids[0], 100)]);
+        let question_id = contract.get_condition(condition_ids[0].clone()).unwrap().question_id;
+
+        let no = contract.position_metadata(outcome_position(&contract, &condition_ids[0], 1)).unwrap();
+        assert_eq!(no, PositionTokenMetadata {
+            title: question_id,
+            outcome_label: "NO".to_string(),
+            condition_id: condition_ids[0].clone(),
+            collateral_token: "usdc.testnet".parse().unwrap(),
+            decimals: 6,
+        });
+        let yes = contract.position_metadata(outcome_position(&contract, &condition_ids[0], 2)).unwrap();
+        assert_eq!(yes.outcome_label, "YES");
+        assert!(contract.position_metadata("unknown".to_string()).is_none());
+
+        // The oracle titles the condition; nobody else can
+        testing_env!(get_context("user.testnet"));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_condition_metadata(condition_ids[0].clone(), "Hijacked".to_string());
+        }));
+        assert!(result.is_err());
+        testing_env!(get_context("oracle.testnet"));
+        contract.set_condition_metadata(condition_ids[0].clone(), "Will BTC reach $100k?".to_string());
+        let yes = contract.position_metadata(outcome_position(&contract, &condition_ids[0], 2)).unwrap();
+        assert_eq!(yes.title, "Will BTC reach $100k?");
+    }
+
+    #[test]
+    fn test_position_metadata_uses_custom_outcome_labels() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        contract.register_collateral_token(usdc.clone());
+
+        testing_env!(get_context("oracle.testnet"));
+        let oracle: AccountId = "oracle.testnet".parse().unwrap();
+        let labels = vec!["Home".to_string(), "Draw".to_string(), "Away".to_string()];
+        let match_result = contract.prepare_condition(oracle.clone(), "Match".to_string(), 3, Some(labels));
+        let unlabelled = contract.prepare_condition(oracle, "Podium".to_string(), 3, None);
+
+        testing_env!(get_context("alice.testnet"));
+        contract.split_position(usdc.clone(), String::new(), match_result.clone(), vec![U128(1), U128(6)], U128(10));
+        contract.split_position(usdc, String::new(), unlabelled.clone(), vec![U128(1), U128(2), U128(4)], U128(10));
+
+        let home = contract.position_metadata(outcome_position(&contract, &match_result, 1)).unwrap();
+        assert_eq!(home.outcome_label, "Home");
+        // A position over several outcomes lists each of them
+        let not_home = contract.position_metadata(outcome_position(&contract, &match_result, 6)).unwrap();
+        assert_eq!(not_home.outcome_label, "Draw | Away");
+        let third = contract.position_metadata(outcome_position(&contract, &unlabelled, 4)).unwrap();
+        assert_eq!(third.outcome_label, "Outcome 2");
+    }
+
+    #[test]
+    fn test_outcome_labels_are_validated() {
+        testing_env!(get_context("oracle.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        let oracle: AccountId = "oracle.testnet".parse().unwrap();
+        let labels = |names: &[&str]| Some(names.iter().map(|name| name.to_string()).collect::<Vec<String>>());
+
+        for (question, bad_labels) in [
+            ("Wrong count", labels(&["YES"])),
+            ("Empty", labels(&["YES", " "])),
+            ("Duplicate", labels(&["YES", "YES"])),
+            ("Too long", labels(&["YES", &"N".repeat(MAX_OUTCOME_LABEL_LEN + 1)])),
+        ] {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                contract.prepare_condition(oracle.clone(), question.to_string(), 2, bad_labels.clone());
+            }));
+            assert!(result.is_err(), "{} labels accepted", question);
+        }
+
+        let at_limit = "N".repeat(MAX_OUTCOME_LABEL_LEN);
+        let condition_id = contract.prepare_condition(oracle, "At the limit".to_string(), 2, labels(&["YES", &at_limit]));
+        assert_eq!(contract.get_condition(condition_id).unwrap().outcome_labels, labels(&["YES", &at_limit]));
+    }
+
+    #[test]
+    fn test_contract_metadata() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = ConditionalTokenFramework::new("owner.testnet".parse().unwrap());
+        let metadata = contract.metadata();
+        assert_eq!((metadata.name.as_str(), metadata.symbol_prefix.as_str()), ("Conditional Tokens", "CTF"));
+        assert_eq!(metadata.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metadata.base_uri, None);
+
+        contract.set_base_uri(Some("https://markets.example.com/positions/".to_string()));
+        assert_eq!(contract.metadata().base_uri.as_deref(), Some("https://markets.example.com/positions/"));
+
+        testing_env!(get_context("user.testnet"));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_base_uri(None);
+        }));
+        assert!(result.is_err());
+    }
+
+    fn lien_context(predecessor: &str, block_timestamp: u64) -> VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(predecessor.parse().unwrap())
+            .block_timestamp(block_timestamp)
+            .build()
+    }
+
+    #[test]
+    fn test_lien_locks_pledged_tokens_until_released() {
+        let (mut contract, condition_ids) = batch_test_setup(1);
+        let user: AccountId = "user.testnet".parse().unwrap();
+        testing_env!(get_context("user.testnet"));
+        contract.batch_split_position(vec![split_op(&condition_ids[0], 100)]);
+        let yes = outcome_position(&contract, &condition_ids[0], 2);
+
+        let maturity = 2_000_000_000_000_000_000;
+        let lien_id = contract.create_lien(yes.clone(), U128(60), "lender.testnet".parse().unwrap(), 500, maturity);
+        assert_eq!(contract.get_lien(lien_id.clone()).unwrap().status, LienStatus::Active);
+        assert_eq!(contract.get_liened_balance(user.clone(), yes.clone()), U128(60));
+        assert_eq!(contract.get_available_balance(user.clone(), yes.clone()), U128(40));
+
+        // Only the unliened part moves, and it cannot be pledged twice
+        contract.safe_transfer_from(user.clone(), "bob.testnet".parse().unwrap(), yes.clone(), U128(40), None);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.safe_transfer_from(user.clone(), "bob.testnet".parse().unwrap(), yes.clone(), U128(1), None);
+        }));
+        assert!(result.is_err());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.merge_positions("usdc.testnet".parse().unwrap(), String::new(), condition_ids[0].clone(), vec![U128(1), U128(2)], U128(1));
+        }));
+        assert!(result.is_err());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.create_lien(yes.clone(), U128(1), "other.testnet".parse().unwrap(), 0, maturity);
+        }));
+        assert!(result.is_err());
+        assert_eq!(contract.batch_safe_transfer_all(user.clone(), "bob.testnet".parse().unwrap(), vec![yes.clone()], None), vec![]);
+
+        // The borrower cannot release their own lien
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| contract.release_lien(lien_id.clone())));
+        assert!(result.is_err());
+
+        testing_env!(get_context("lender.testnet"));
+        contract.release_lien(lien_id.clone());
+        assert_eq!(contract.get_lien(lien_id.clone()).unwrap().status, LienStatus::Released);
+        assert_eq!(contract.get_available_balance(user.clone(), yes.clone()), U128(60));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| contract.release_lien(lien_id.clone())));
+        assert!(result.is_err());
+
+        testing_env!(get_context("user.testnet"));
+        contract.safe_transfer_from(user.clone(), "bob.testnet".parse().unwrap(), yes.clone(), U128(60), None);
+        assert_eq!(contract.balance_of("bob.testnet".parse().unwrap(), yes), U128(100));
+    }
+
+    #[test]
+    fn test_lien_liquidated_after_maturity() {
+        let (mut contract, condition_ids) = batch_test_setup(1);
+        let lender: AccountId = "lender.testnet".parse().unwrap();
+        testing_env!(get_context("user.testnet"));
+        contract.batch_split_position(vec![split_op(&condition_ids[0], 100)]);
+        let yes = outcome_position(&contract, &condition_ids[0], 2);
+
+        let maturity = 2_000_000_000_000_000_000;
+        let lien_id = contract.create_lien(yes.clone(), U128(70), lender.clone(), 1200, maturity);
+
+        testing_env!(lien_context("lender.testnet", maturity - 1));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| contract.liquidate_lien(lien_id.clone())));
+        assert!(result.is_err());
+
+        testing_env!(lien_context("lender.testnet", maturity));
+        contract.liquidate_lien(lien_id.clone());
+        assert_eq!(contract.get_lien(lien_id).unwrap().status, LienStatus::Liquidated);
+        assert_eq!(contract.balance_of(lender.clone(), yes.clone()), U128(70));
+        assert_eq!(contract.balance_of("user.testnet".parse().unwrap(), yes.clone()), U128(30));
+        assert_eq!(contract.get_liened_balance("user.testnet".parse().unwrap(), yes), U128(0));
+    }
+
+    #[test]
+    fn test_redemption_leaves_liened_tokens_for_the_lender() {
+        let (mut contract, condition_ids) = batch_test_setup(1);
+        let user: AccountId = "user.testnet".parse().unwrap();
+        let lender: AccountId = "lender.testnet".parse().unwrap();
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        testing_env!(get_context("user.testnet"));
+        contract.batch_split_position(vec![split_op(&condition_ids[0], 100)]);
+        let yes = outcome_position(&contract, &condition_ids[0], 2);
+        let maturity = 2_000_000_000_000_000_000;
+        let lien_id = contract.create_lien(yes.clone(), U128(25), lender.clone(), 0, maturity);
+
+        testing_env!(get_context("oracle.testnet"));
+        contract.report_payouts("Market 0".to_string(), vec![U128(0), U128(1)]);
+
+        // The borrower redeems only what is not pledged
+        testing_env!(get_context("user.testnet"));
+        assert_eq!(contract.estimate_redemption(user.clone(), usdc.clone(), String::new(), condition_ids[0].clone(), vec![vec![U128(2)]]), U128(75));
+        assert_eq!(contract.redeem_positions(usdc.clone(), String::new(), condition_ids[0].clone(), vec![vec![U128(2)]]), U128(75));
+        assert_eq!(contract.balance_of(user.clone(), yes.clone()), U128(25));
+
+        // Unpaid at maturity, the lender takes the winning tokens and redeems them
+        testing_env!(lien_context("lender.testnet", maturity));
+        contract.liquidate_lien(lien_id);
+        assert_eq!(contract.redeem_positions(usdc.clone(), String::new(), condition_ids[0].clone(), vec![vec![U128(2)]]), U128(25));
+        assert_eq!(contract.balance_of(lender, yes), U128(0));
+        assert!(contract.check_invariant(condition_ids[0].clone(), usdc));
+    }
+
+    // NEP-297 events logged since the last testing_env!, parsed back from their JSON
+    fn take_events() -> Vec<NearEvent<near_sdk::serde_json::Value>> {
+        near_sdk::test_utils::get_logs()
+            .iter()
+            .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+            .map(|json| near_sdk::serde_json::from_str(json).expect("Event is not valid JSON"))
+            .collect()
+    }
+
+    fn event_data<T: near_sdk::serde::de::DeserializeOwned>(event: &NearEvent<near_sdk::serde_json::Value>) -> T {
+        near_sdk::serde_json::from_value(event.data[0].clone()).expect("Unexpected event data")
+    }
+
+    #[test]
+    fn test_operations_emit_nep297_events() {
+        let (mut contract, condition_ids) = batch_test_setup(1);
+        let condition_id = condition_ids[0].clone();
+        let usdc: AccountId = "usdc.testnet".parse().unwrap();
+        let user: AccountId = "user.testnet".parse().unwrap();
+        let no = outcome_position(&contract, &condition_id, 1);
+        let yes = outcome_position(&contract, &condition_id, 2);
+
+        let events = take_events();
+        assert_eq!((events[0].standard.as_str(), events[0].version.as_str(), events[0].event.as_str()), ("ctf", "1.0.0", "condition_prepared"));
+        let prepared: events::ConditionPreparedLog = event_data(&events[0]);
+        assert_eq!((prepared.condition_id, prepared.outcome_slot_count), (condition_id.clone(), 2));
+
+        // Split: both outcome tokens minted, then the CTF's own position_split
+        testing_env!(get_context("user.testnet"));
+        contract.split_position(usdc.clone(), String::new(), condition_id.clone(), vec![U128(1), U128(2)], U128(100));
+        let events = take_events();
+        assert_eq!(events.iter().map(|e| (e.standard.as_str(), e.event.as_str())).collect::<Vec<_>>(), vec![("nep171", "nft_mint"), ("ctf", "position_split")]);
+        let minted: events::NftMintLog = event_data(&events[0]);
+        assert_eq!(minted.owner_id, user);
+        assert_eq!(minted.token_ids, vec![no.clone(), yes.clone()]);
+        assert_eq!(minted.amounts, vec![U128(100), U128(100)]);
+        let split: PositionSplit = event_data(&events[1]);
+        assert_eq!((split.stakeholder, split.amount), (user.clone(), U128(100)));
+
+        // Transfers by an approved operator name it as authorized_id
+        contract.set_approval_for_all("operator.testnet".parse().unwrap(), true);
+        testing_env!(get_context("operator.testnet"));
+        contract.safe_transfer_from(user.clone(), "bob.testnet".parse().unwrap(), yes.clone(), U128(40), Some("gift".to_string()));
+        let events = take_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, "nft_transfer");
+        let transfer: events::NftTransferLog = event_data(&events[0]);
+        assert_eq!(transfer.old_owner_id, user);
+        assert_eq!(transfer.new_owner_id.as_str(), "bob.testnet");
+        assert_eq!((transfer.token_ids, transfer.amounts), (vec![yes.clone()], vec![U128(40)]));
+        assert_eq!(transfer.authorized_id.map(String::from), Some("operator.testnet".to_string()));
+        assert_eq!(transfer.memo.as_deref(), Some("gift"));
+
+        // Merging back to collateral only burns
+        testing_env!(get_context("user.testnet"));
+        contract.merge_positions(usdc.clone(), String::new(), condition_id.clone(), vec![U128(1), U128(2)], U128(50));
+        let events = take_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, "nft_burn");
+        let burned: events::NftBurnLog = event_data(&events[0]);
+        assert_eq!((burned.token_ids, burned.amounts), (vec![no.clone(), yes.clone()], vec![U128(50), U128(50)]));
+
+        testing_env!(get_context("oracle.testnet"));
+        contract.report_payouts("Market 0".to_string(), vec![U128(1), U128(0)]);
+        let events = take_events();
+        assert_eq!((events.len(), events[0].event.as_str()), (1, "payouts_reported"));
+        let reported: events::PayoutsReportedLog = event_data(&events[0]);
+        assert_eq!(reported.condition_id, condition_id);
+        assert_eq!((reported.payout_numerators, reported.payout_denominator), (vec![U128(1), U128(0)], U128(1)));
+
+        // Redeeming burns the winning NO tokens
+        testing_env!(get_context("user.testnet"));
+        assert_eq!(contract.redeem_positions(usdc, String::new(), condition_id, vec![vec![U128(1)]]), U128(50));
+        let events = take_events();
+        assert_eq!(events.len(), 1);
+        let burned: events::NftBurnLog = event_data(&events[0]);
+        assert_eq!((burned.token_ids, burned.amounts, burned.memo.as_deref()), (vec![no], vec![U128(50)], Some("redeem_positions")));
+    }
+}
@@ -0,0 +1,261 @@
+// State versioning and code upgrades.
+//
+// upgrade() deploys owner-approved code and calls migrate() in the same batch, so new code never
+// runs against an old layout. To change the stored contract struct: copy the current struct here
+// as ConditionalTokenFrameworkV<n>, add a StateVersion variant, bump StateVersion::CURRENT and
+// teach migrate() to convert the frozen layout. Collections hold their values in their own
+// storage, so a changed value type needs its entries rewritten as well (see ConditionV2).
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{UnorderedMap, UnorderedSet};
+use near_sdk::json_types::{Base58CryptoHash, U128};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, NearToken, Promise};
+use schemars::JsonSchema;
+
+use crate::{Collection, Condition, ConditionCollateral, ConditionalTokenFramework, ConditionalTokenFrameworkExt, Position};
+
+const MIGRATE_TGAS: u64 = 100;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StateVersion {
+    V1,             // unversioned layout deployed before migrations existed
+    V2,             // adds state_version, pending_upgrade_hash, position_holders, user_position_index, position_metadata, collateral_locked, the resolution timestamps and submitters, the per-owner approval lists, position supplies, per-condition collateral, the metadata base_uri and position liens
+    V3,             // adds payout_in_positions and parent_condition_id to every stored Condition
+    V4,             // adds outcome_labels and title to every stored Condition
+}
+
+impl StateVersion {
+    pub const CURRENT: StateVersion = StateVersion::V4;
+}
+
+/// Condition layout up to V2, frozen
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ConditionV2 {
+    pub oracle: AccountId,
+    pub question_id: String,
+    pub outcome_slot_count: u8,
+    pub payout_numerators: Option<Vec<U128>>,
+    pub payout_denominator: Option<U128>,
+}
+
+impl From<ConditionV2> for Condition {
+    fn from(old: ConditionV2) -> Self {
+        Self {
+            oracle: old.oracle,
+            question_id: old.question_id,
+            outcome_slot_count: old.outcome_slot_count,
+            payout_numerators: old.payout_numerators,
+            payout_denominator: old.payout_denominator,
+            payout_in_positions: false,
+            parent_condition_id: None,
+            outcome_labels: None,
+            title: None,
+        }
+    }
+}
+
+/// Condition layout in V3, frozen
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ConditionV3 {
+    pub oracle: AccountId,
+    pub question_id: String,
+    pub outcome_slot_count: u8,
+    pub payout_numerators: Option<Vec<U128>>,
+    pub payout_denominator: Option<U128>,
+    pub payout_in_positions: bool,
+    pub parent_condition_id: Option<String>,
+}
+
+impl From<ConditionV3> for Condition {
+    fn from(old: ConditionV3) -> Self {
+        Self {
+            oracle: old.oracle,
+            question_id: old.question_id,
+            outcome_slot_count: old.outcome_slot_count,
+            payout_numerators: old.payout_numerators,
+            payout_denominator: old.payout_denominator,
+            payout_in_positions: old.payout_in_positions,
+            parent_condition_id: old.parent_condition_id,
+            outcome_labels: None,
+            title: None,
+        }
+    }
+}
+
+/// Rewrite every entry of a conditions map still stored in an older layout. The map's own borsh
+/// form is just its prefixes and length, so it can be read back with the old value type
+fn upgrade_conditions<T: BorshDeserialize + BorshSerialize + Into<Condition>>(conditions: &mut UnorderedMap<String, Condition>) {
+    let legacy: UnorderedMap<String, T> = borsh::from_slice(&borsh::to_vec(conditions).unwrap())
+        .expect("Unrecognized conditions layout");
+    let entries: Vec<(String, T)> = legacy.iter().collect();
+    for (condition_id, condition) in entries {
+        conditions.insert(&condition_id, &condition.into());
+    }
+}
+
+/// V1 layout, frozen. Stored without a version marker
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ConditionalTokenFrameworkV1 {
+    pub conditions: UnorderedMap<String, Condition>,
+    pub collections: UnorderedMap<String, Collection>,
+    pub positions: UnorderedMap<String, Position>,
+    pub balances: UnorderedMap<String, U128>,
+    pub operator_approvals: UnorderedMap<String, bool>,
+    pub token_approvals: UnorderedMap<String, U128>,
+    pub collateral_tokens: UnorderedSet<AccountId>,
+    pub owner: AccountId,
+}
+
+impl From<ConditionalTokenFrameworkV1> for ConditionalTokenFramework {
+    fn from(mut old: ConditionalTokenFrameworkV1) -> Self {
+        upgrade_conditions::<ConditionV2>(&mut old.conditions);
+        let mut contract = Self {
+            conditions: old.conditions,
+            collections: old.collections,
+            positions: old.positions,
+            balances: old.balances,
+            operator_approvals: old.operator_approvals,
+            token_approvals: old.token_approvals,
+            collateral_tokens: old.collateral_tokens,
+            owner: old.owner,
+            position_holders: UnorderedMap::new(b"h"),
+            owner_token_approvals: UnorderedMap::new(b"l"),
+            owner_operators: UnorderedMap::new(b"r"),
+            user_position_index: UnorderedMap::new(b"u"),
+            state_version: StateVersion::CURRENT,
+            pending_upgrade_hash: None,
+            position_metadata: UnorderedMap::new(b"m"),
+            // Collateral split under V1 isn't known; TVL counts from the upgrade on
+            collateral_locked: UnorderedMap::new(b"v"),
+            // Conditions resolved under V1 have no recorded time or submitter
+            resolution_timestamps: UnorderedMap::new(b"e"),
+            resolution_submitter: UnorderedMap::new(b"s"),
+            position_supply: UnorderedMap::new(b"y"),
+            condition_collateral: UnorderedMap::new(b"n"),
+            base_uri: None,
+            liens: UnorderedMap::new(b"i"),
+            liened_balances: UnorderedMap::new(b"g"),
+        };
+
+        // V1 only had balances; list every non-zero "position_id:account" entry as a holder and
+        // in its account's position index
+        let held: Vec<(String, AccountId, u128)> = contract.balances.iter()
+            .filter(|(_, balance)| balance.0 > 0)
+            .filter_map(|(key, balance)| {
+                let (position_id, account) = key.rsplit_once(':')?;
+                Some((position_id.to_string(), account.parse().ok()?, balance.0))
+            })
+            .collect();
+        for (position_id, account, balance) in held {
+            contract.set_balance(&position_id, &account, balance);
+            // set_balance only moves supply by a change in balance
+            let supply = contract.position_supply.get(&position_id).map_or(0, |s| s.0);
+            contract.position_supply.insert(&position_id, &U128(supply + balance));
+        }
+
+        // V1 kept no per-condition collateral. Seed each unresolved condition with its outstanding
+        // complete sets (the smallest outcome supply) so migrated markets start out balanced
+        let conditions: Vec<(String, Condition)> = contract.conditions.iter()
+            .filter(|(_, condition)| condition.payout_numerators.is_none())
+            .collect();
+        let tokens: Vec<AccountId> = contract.collateral_tokens.iter().collect();
+        for (condition_id, condition) in conditions {
+            for token in &tokens {
+                let complete_sets = contract.get_full_partition(condition.outcome_slot_count).into_iter()
+                    .map(|index_set| {
+                        let collection_id = contract.get_collection_id(String::new(), condition_id.clone(), vec![index_set]);
+                        contract.total_supply(contract.get_position_id(token.clone(), collection_id)).0
+                    })
+                    .min()
+                    .unwrap_or(0);
+                if complete_sets > 0 {
+                    contract.condition_collateral.insert(
+                        &format!("{}:{}", condition_id, token),
+                        &ConditionCollateral { deposited: U128(complete_sets), withdrawn: U128(0) },
+                    );
+                }
+            }
+        }
+
+        // Same for approvals: "owner:operator" -> true and "owner:position_id:operator" -> non-zero
+        let operators: Vec<(AccountId, AccountId)> = contract.operator_approvals.iter()
+            .filter(|(_, approved)| *approved)
+            .filter_map(|(key, _)| {
+                let (owner, operator) = key.split_once(':')?;
+                Some((owner.parse().ok()?, operator.parse().ok()?))
+            })
+            .collect();
+        for (owner, operator) in operators {
+            contract.list_operator(&owner, &operator, true);
+        }
+
+        let allowances: Vec<(AccountId, String, AccountId)> = contract.token_approvals.iter()
+            .filter(|(_, allowance)| allowance.0 > 0)
+            .filter_map(|(key, _)| {
+                let mut parts = key.splitn(3, ':');
+                Some((parts.next()?.parse().ok()?, parts.next()?.to_string(), parts.next()?.parse().ok()?))
+            })
+            .collect();
+        for (owner, position_id, operator) in allowances {
+            contract.list_token_approval(&owner, &operator, &position_id, true);
+        }
+        contract
+    }
+}
+
+#[near_bindgen]
+impl ConditionalTokenFramework {
+    /// Approve the sha256 of the next contract code; upgrade() only deploys code matching it
+    pub fn propose_upgrade(&mut self, code_hash: Base58CryptoHash) {
+        assert_eq!(env::predecessor_account_id(), self.owner, "Only owner can propose upgrades");
+        self.pending_upgrade_hash = Some(code_hash.into());
+        env::log_str(&format!("UpgradeProposed: code_hash={}", String::from(&code_hash)));
+    }
+
+    /// Deploy the approved code, passed as the raw call input, and migrate state in the same batch
+    pub fn upgrade(&mut self) -> Promise {
+        assert_eq!(env::predecessor_account_id(), self.owner, "Only owner can upgrade");
+        let code = env::input().expect("Contract code missing from input");
+        let approved = self.pending_upgrade_hash.take().expect("No upgrade proposed");
+        assert_eq!(env::sha256_array(&code), approved, "Code does not match the proposed upgrade");
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call("migrate".to_string(), Vec::new(), NearToken::from_yoctonear(0), Gas::from_tgas(MIGRATE_TGAS))
+    }
+
+    /// Rewrite stored state into the current layout. Already-current state passes through
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = env::storage_read(b"STATE").expect("No contract state to migrate");
+
+        let (from, mut contract) = match Self::try_from_slice(&state) {
+            Ok(current) => (current.state_version, current),
+            Err(_) => {
+                let old = ConditionalTokenFrameworkV1::try_from_slice(&state)
+                    .expect("Unrecognized contract state layout");
+                (StateVersion::V1, old.into())
+            }
+        };
+        match from {
+            StateVersion::V2 => upgrade_conditions::<ConditionV2>(&mut contract.conditions),
+            StateVersion::V3 => upgrade_conditions::<ConditionV3>(&mut contract.conditions),
+            _ => {}
+        }
+        env::log_str(&format!("StateMigrated: {:?} -> {:?}", from, StateVersion::CURRENT));
+        contract.state_version = StateVersion::CURRENT;
+        contract.pending_upgrade_hash = None;
+        contract
+    }
+
+    pub fn get_state_version(&self) -> StateVersion {
+        self.state_version
+    }
+
+    pub fn get_pending_upgrade(&self) -> Option<Base58CryptoHash> {
+        self.pending_upgrade_hash.map(Base58CryptoHash::from)
+    }
+}
@@ -0,0 +1 @@
+// Sandbox integration tests for the contract suite live in tests/; see tests/market_lifecycle.rs
@@ -0,0 +1,318 @@
+// End-to-end market lifecycle on a near-workspaces sandbox:
+// verifier -> solver -> CTF -> resolver, with a mock NEP-141 token as USDC.
+//
+// Run from contracts/integration-tests after ./build.sh has written res/*.wasm:
+//     cargo test --features sandbox
+// The mock token is compiled from contracts/mock-usdc, which needs cargo-near.
+//
+// CTF collateral movements are still log-only, so redemptions are checked through the
+// amount redeem_positions reports rather than the redeemer's USDC balance.
+
+#![cfg(feature = "sandbox")]
+
+use anyhow::Result;
+use near_workspaces::network::Sandbox;
+use near_workspaces::types::NearToken;
+use near_workspaces::{Account, Contract, Worker};
+use serde_json::{json, Value};
+
+const USDC: u128 = 1_000_000; // 6 decimals
+const DISPUTE_PERIOD_NS: u64 = 1_000_000_000;
+const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+const HOUR_NS: u64 = 3_600_000_000_000;
+
+// CTF index sets for a binary condition: bit i is outcome i, matching the resolver's payout vector
+const NO_INDEX_SET: &str = "1";
+const YES_INDEX_SET: &str = "2";
+
+struct Suite {
+    worker: Worker<Sandbox>,
+    usdc: Contract,
+    ctf: Contract,
+    resolver: Contract,
+    verifier: Contract,
+    solver: Contract,
+    owner: Account,
+    oracle: Account,
+    operator: Account, // orderbook authority and solver daemon
+    user: Account,
+}
+
+fn wasm(name: &str) -> Result<Vec<u8>> {
+    let path = format!("{}/../../res/{}.wasm", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read(&path).map_err(|e| anyhow::anyhow!("{}: {} (run ./build.sh first)", path, e))
+}
+
+async fn account(root: &Account, name: &str, near: u128) -> Result<Account> {
+    Ok(root
+        .create_subaccount(name)
+        .initial_balance(NearToken::from_near(near))
+        .transact()
+        .await?
+        .into_result()?)
+}
+
+async fn deploy(account: &Account, code: &[u8]) -> Result<Contract> {
+    Ok(account.deploy(code).await?.into_result()?)
+}
+
+async fn call(caller: &Account, contract: &Contract, method: &str, args: Value) -> Result<near_workspaces::result::ExecutionFinalResult> {
+    let outcome = caller
+        .call(contract.id(), method)
+        .args_json(args)
+        .max_gas()
+        .transact()
+        .await?;
+    anyhow::ensure!(outcome.is_success(), "{} failed: {:?}", method, outcome.failures());
+    Ok(outcome)
+}
+
+async fn view(contract: &Contract, method: &str, args: Value) -> Result<Value> {
+    Ok(contract.view(method).args_json(args).await?.json()?)
+}
+
+async fn usdc_balance(suite: &Suite, account: &Account) -> Result<u128> {
+    let balance = view(&suite.usdc, "ft_balance_of", json!({ "account_id": account.id() })).await?;
+    Ok(balance.as_str().unwrap_or("0").parse()?)
+}
+
+async fn setup() -> Result<Suite> {
+    let worker = near_workspaces::sandbox().await?;
+    let root = worker.root_account()?;
+
+    let owner = account(&root, "owner", 50).await?;
+    let oracle = account(&root, "oracle", 10).await?;
+    let operator = account(&root, "operator", 10).await?;
+    let user = account(&root, "user", 10).await?;
+
+    let usdc = deploy(&account(&root, "usdc", 20).await?, &near_workspaces::compile_project("../mock-usdc").await?).await?;
+    let ctf = deploy(&account(&root, "ctf", 30).await?, &wasm("ctf")?).await?;
+    let resolver = deploy(&account(&root, "resolver", 30).await?, &wasm("resolver")?).await?;
+    let verifier = deploy(&account(&root, "verifier", 30).await?, &wasm("verifier")?).await?;
+    let solver = deploy(&account(&root, "solver", 30).await?, &wasm("solver")?).await?;
+
+    call(&owner, &usdc, "new", json!({})).await?;
+    call(&owner, &ctf, "new", json!({ "owner": owner.id() })).await?;
+    call(&owner, &resolver, "new", json!({
+        "owner_id": owner.id(),
+        "verifier_contract": verifier.id(),
+        "ctf_contract": ctf.id(),
+        "dispute_period": DISPUTE_PERIOD_NS,
+        "dispute_bond": ONE_NEAR.to_string(),
+    })).await?;
+    call(&owner, &verifier, "new", json!({
+        "owner_id": owner.id(),
+        "ctf_contract": ctf.id(),
+        "resolver_contract": resolver.id(),
+        "min_bet_amount": USDC.to_string(),
+        "max_bet_amount": (1_000_000 * USDC).to_string(),
+        "platform_fee_bps": 0,
+    })).await?;
+    call(&owner, &solver, "new", json!({
+        "owner_id": owner.id(),
+        "verifier_contract": verifier.id(),
+        "ctf_contract": ctf.id(),
+        "usdc_contract": usdc.id(),
+        "orderbook_authority": operator.id(),
+        "solver_fee_bps": 0,
+        "min_order_size": "1",
+    })).await?;
+
+    call(&owner, &verifier, "register_solver", json!({ "solver": solver.id() })).await?;
+    call(&owner, &verifier, "set_usdc_contract", json!({ "usdc_contract": usdc.id() })).await?;
+    call(&owner, &resolver, "add_oracle", json!({ "oracle": oracle.id() })).await?;
+    call(&owner, &solver, "authorize_daemon", json!({ "daemon_account": operator.id() })).await?;
+    call(&owner, &usdc, "mint", json!({ "account_id": user.id(), "amount": (100 * USDC).to_string() })).await?;
+
+    Ok(Suite { worker, usdc, ctf, resolver, verifier, solver, owner, oracle, operator, user })
+}
+
+/// Create a market resolved by the resolver contract, returns (market_id, condition_id)
+async fn create_market(suite: &Suite) -> Result<(String, String)> {
+    let now = suite.worker.view_block().await?.timestamp();
+    let market_id: String = call(&suite.owner, &suite.verifier, "create_market", json!({
+        "title": "BTC above 100k",
+        "description": "Resolves YES if BTC trades above $100k",
+        "end_time": now + HOUR_NS,
+        "resolution_time": now + 2 * HOUR_NS,
+        "category": "crypto",
+        "resolver": suite.resolver.id(),
+    })).await?.json()?;
+
+    let market = view(&suite.verifier, "get_market", json!({ "market_id": market_id })).await?;
+    let condition_id = market["condition_id"].as_str().unwrap().to_string();
+
+    let condition = view(&suite.ctf, "get_condition", json!({ "condition_id": condition_id })).await?;
+    assert_eq!(condition["oracle"], json!(suite.resolver.id()), "CTF oracle must be the resolver");
+
+    Ok((market_id, condition_id))
+}
+
+async fn buy_intent(suite: &Suite, intent_id: &str, market_id: &str, amount: u128) -> Result<Value> {
+    let now = suite.worker.view_block().await?.timestamp();
+    Ok(json!({
+        "intent_id": intent_id,
+        "user": suite.user.id(),
+        "market_id": market_id,
+        "intent_type": "BuyShares",
+        "outcome": 1,
+        "amount": amount.to_string(),
+        "max_price": 60000,
+        "min_price": null,
+        "deadline": now + HOUR_NS,
+        "order_type": "Limit",
+        "cross_chain": null,
+    }))
+}
+
+async fn position_id(suite: &Suite, condition_id: &str, index_set: &str) -> Result<String> {
+    let collection_id = view(&suite.ctf, "get_collection_id", json!({
+        "parent_collection_id": "",
+        "condition_id": condition_id,
+        "index_set": [index_set],
+    })).await?;
+    let position_id = view(&suite.ctf, "get_position_id", json!({
+        "collateral_token": suite.usdc.id(),
+        "collection_id": collection_id,
+    })).await?;
+    Ok(position_id.as_str().unwrap().to_string())
+}
+
+async fn position_balance(suite: &Suite, owner: &Account, position_id: &str) -> Result<u128> {
+    let balance = view(&suite.ctf, "balance_of", json!({ "owner": owner.id(), "position_id": position_id })).await?;
+    Ok(balance.as_str().unwrap().parse()?)
+}
+
+#[tokio::test]
+async fn test_buy_resolve_redeem() -> Result<()> {
+    let suite = setup().await?;
+    let (market_id, condition_id) = create_market(&suite).await?;
+    let amount = 10 * USDC;
+
+    // The user funds and submits the intent in one ft_transfer_call
+    let mut msg = buy_intent(&suite, "intent_buy_yes", &market_id, amount).await?;
+    msg["solver_account"] = json!(suite.solver.id());
+    suite.user
+        .call(suite.usdc.id(), "ft_transfer_call")
+        .args_json(json!({
+            "receiver_id": suite.verifier.id(),
+            "amount": amount.to_string(),
+            "memo": null,
+            "msg": msg.to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(usdc_balance(&suite, &suite.user).await?, 90 * USDC);
+    assert_eq!(usdc_balance(&suite, suite.solver.as_account()).await?, amount);
+    assert_eq!(view(&suite.verifier, "is_intent_verified", json!({ "intent_id": "intent_buy_yes" })).await?, json!(true));
+    let pending = view(&suite.solver, "get_pending_for_daemon", json!({})).await?;
+    assert_eq!(pending, json!(["intent_buy_yes"]));
+
+    // The daemon fills it: mint a complete set and hand the YES leg to the user
+    call(&suite.operator, &suite.ctf, "split_position", json!({
+        "collateral_token": suite.usdc.id(),
+        "parent_collection_id": "",
+        "condition_id": condition_id,
+        "partition": [NO_INDEX_SET, YES_INDEX_SET],
+        "amount": amount.to_string(),
+    })).await?;
+    let yes_position = position_id(&suite, &condition_id, YES_INDEX_SET).await?;
+    call(&suite.operator, &suite.ctf, "safe_transfer_from", json!({
+        "from": suite.operator.id(),
+        "to": suite.user.id(),
+        "position_id": yes_position,
+        "amount": amount.to_string(),
+        "data": null,
+    })).await?;
+    call(&suite.operator, &suite.solver, "complete_intent", json!({
+        "intent_id": "intent_buy_yes",
+        "result": {
+            "intent_id": "intent_buy_yes",
+            "success": true,
+            "output_amount": amount.to_string(),
+            "fee_amount": "0",
+            "execution_details": "filled by sandbox daemon",
+        },
+    })).await?;
+    assert_eq!(view(&suite.solver, "is_intent_processed", json!({ "intent_id": "intent_buy_yes" })).await?, json!(true));
+    assert_eq!(position_balance(&suite, &suite.user, &yes_position).await?, amount);
+
+    // Resolve YES and let the dispute window lapse
+    call(&suite.oracle, &suite.resolver, "submit_resolution", json!({
+        "market_id": market_id,
+        "winning_outcome": 1,
+        "resolution_data": "{\"source\":\"sandbox\"}",
+    })).await?;
+    suite.worker.fast_forward(10).await?;
+    call(&suite.owner, &suite.resolver, "finalize_resolution", json!({ "market_id": market_id })).await?;
+
+    assert_eq!(view(&suite.resolver, "is_market_finalized", json!({ "market_id": market_id })).await?, json!(true));
+    assert_eq!(view(&suite.ctf, "is_condition_resolved", json!({ "condition_id": condition_id })).await?, json!(true));
+    let market = view(&suite.verifier, "get_market", json!({ "market_id": market_id })).await?;
+    assert_eq!(market["is_resolved"], json!(true));
+    assert_eq!(market["winning_outcome"], json!(1));
+
+    // Winning shares redeem 1:1, losing ones for nothing
+    let redeemed: String = call(&suite.user, &suite.ctf, "redeem_positions", json!({
+        "collateral_token": suite.usdc.id(),
+        "parent_collection_id": "",
+        "condition_id": condition_id,
+        "index_sets": [[YES_INDEX_SET]],
+    })).await?.json()?;
+    assert_eq!(redeemed, amount.to_string());
+    assert_eq!(position_balance(&suite, &suite.user, &yes_position).await?, 0);
+
+    let no_position = position_id(&suite, &condition_id, NO_INDEX_SET).await?;
+    let redeemed: String = call(&suite.operator, &suite.ctf, "redeem_positions", json!({
+        "collateral_token": suite.usdc.id(),
+        "parent_collection_id": "",
+        "condition_id": condition_id,
+        "index_sets": [[NO_INDEX_SET]],
+    })).await?.json()?;
+    assert_eq!(redeemed, "0");
+    assert_eq!(position_balance(&suite, &suite.operator, &no_position).await?, amount);
+
+    assert_eq!(usdc_balance(&suite, &suite.user).await?, 90 * USDC);
+    assert_eq!(usdc_balance(&suite, suite.solver.as_account()).await?, amount);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_verify_and_solve_and_rejected_deposit() -> Result<()> {
+    let suite = setup().await?;
+    let (market_id, _) = create_market(&suite).await?;
+
+    // Direct submission by the user reaches the solver's daemon queue
+    let intent = buy_intent(&suite, "intent_direct", &market_id, 5 * USDC).await?;
+    call(&suite.user, &suite.verifier, "verify_and_solve", json!({
+        "intent": intent,
+        "solver_account": suite.solver.id(),
+    })).await?;
+    let pending = view(&suite.solver, "get_pending_for_daemon", json!({})).await?;
+    assert_eq!(pending, json!(["intent_direct"]));
+
+    // A deposit that doesn't match its intent comes straight back
+    let msg = buy_intent(&suite, "intent_mismatch", &market_id, 5 * USDC).await?;
+    suite.user
+        .call(suite.usdc.id(), "ft_transfer_call")
+        .args_json(json!({
+            "receiver_id": suite.verifier.id(),
+            "amount": (7 * USDC).to_string(),
+            "memo": null,
+            "msg": msg.to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(usdc_balance(&suite, &suite.user).await?, 100 * USDC);
+    assert_eq!(usdc_balance(&suite, suite.verifier.as_account()).await?, 0);
+    assert_eq!(view(&suite.verifier, "is_intent_verified", json!({ "intent_id": "intent_mismatch" })).await?, json!(false));
+    Ok(())
+}
@@ -0,0 +1,129 @@
+// Minimal NEP-141 token standing in for USDC in the sandbox integration tests.
+// Anyone can mint and no storage registration is needed; ft_transfer_call and its refund
+// resolution follow the standard so ft_on_transfer receivers behave as they would on testnet.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, AccountId, Gas, PanicOnDefault, PromiseOrValue, PromiseResult};
+
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas::from_tgas(10);
+const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas::from_tgas(30);
+
+#[near_sdk::ext_contract(ext_ft_receiver)]
+pub trait FungibleTokenReceiver {
+    fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128>;
+}
+
+#[near_sdk::ext_contract(ext_self)]
+pub trait MockUsdcCallbacks {
+    fn ft_resolve_transfer(&mut self, sender_id: AccountId, receiver_id: AccountId, amount: U128) -> U128;
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct MockUsdc {
+    pub balances: LookupMap<AccountId, u128>,
+    pub total_supply: u128,
+}
+
+#[near_bindgen]
+impl MockUsdc {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            balances: LookupMap::new(b"b"),
+            total_supply: 0,
+        }
+    }
+
+    pub fn mint(&mut self, account_id: AccountId, amount: U128) {
+        let balance = self.balances.get(&account_id).unwrap_or(0);
+        self.balances.insert(&account_id, &(balance + amount.0));
+        self.total_supply += amount.0;
+    }
+
+    #[payable]
+    pub fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        self.internal_transfer(&env::predecessor_account_id(), &receiver_id, amount.0, memo);
+    }
+
+    #[payable]
+    pub fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        assert_one_yocto();
+        assert!(env::prepaid_gas() > GAS_FOR_FT_TRANSFER_CALL, "More gas is required");
+
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer(&sender_id, &receiver_id, amount.0, memo);
+
+        let receiver_gas = env::prepaid_gas()
+            .saturating_sub(GAS_FOR_FT_TRANSFER_CALL)
+            .saturating_sub(GAS_FOR_RESOLVE_TRANSFER);
+        ext_ft_receiver::ext(receiver_id.clone())
+            .with_static_gas(receiver_gas)
+            .ft_on_transfer(sender_id.clone(), amount, msg)
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .ft_resolve_transfer(sender_id, receiver_id, amount)
+            )
+            .into()
+    }
+
+    /// Refund whatever the receiver reported as unused; returns the amount actually spent
+    #[private]
+    pub fn ft_resolve_transfer(&mut self, sender_id: AccountId, receiver_id: AccountId, amount: U128) -> U128 {
+        let unused = match env::promise_result(0) {
+            PromiseResult::Successful(value) => near_sdk::serde_json::from_slice::<U128>(&value)
+                .map(|unused| unused.0.min(amount.0))
+                .unwrap_or(amount.0),
+            PromiseResult::Failed => amount.0,
+        };
+
+        let refund = unused.min(self.balances.get(&receiver_id).unwrap_or(0));
+        if refund > 0 {
+            self.internal_transfer(&receiver_id, &sender_id, refund, Some("refund".to_string()));
+        }
+        U128(amount.0 - refund)
+    }
+
+    pub fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.balances.get(&account_id).unwrap_or(0))
+    }
+
+    pub fn ft_total_supply(&self) -> U128 {
+        U128(self.total_supply)
+    }
+
+    fn internal_transfer(&mut self, sender_id: &AccountId, receiver_id: &AccountId, amount: u128, memo: Option<String>) {
+        assert!(amount > 0, "The amount should be a positive number");
+        assert_ne!(sender_id, receiver_id, "Sender and receiver should be different");
+
+        let sender_balance = self.balances.get(sender_id).unwrap_or(0);
+        assert!(sender_balance >= amount, "The account doesn't have enough balance");
+        self.balances.insert(sender_id, &(sender_balance - amount));
+
+        let receiver_balance = self.balances.get(receiver_id).unwrap_or(0);
+        self.balances.insert(receiver_id, &(receiver_balance + amount));
+
+        env::log_str(&format!(
+            "Transfer {} from {} to {}{}",
+            amount, sender_id, receiver_id,
+            memo.map(|m| format!(" ({})", m)).unwrap_or_default()
+        ));
+    }
+}
+
+fn assert_one_yocto() {
+    assert_eq!(
+        env::attached_deposit().as_yoctonear(), 1,
+        "Requires attached deposit of exactly 1 yoctoNEAR"
+    );
+}
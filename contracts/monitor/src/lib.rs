@@ -1,9 +1,12 @@
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{UnorderedMap, UnorderedSet};
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault, Promise};
+use near_sdk::{env, near_bindgen, AccountId, CryptoHash, PanicOnDefault, Promise};
 use serde_json;
 
+mod migration;
+pub use migration::StateVersion;
+
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
 pub struct BridgeTransaction {
     pub tx_hash: String,
@@ -15,7 +18,10 @@ pub struct BridgeTransaction {
     pub status: TransactionStatus,
     pub created_at: u64,
     pub updated_at: u64,
+    pub completed_at: Option<u64>,
     pub retry_count: u8,
+    pub next_retry_at: u64, // ns; retry_transaction is refused before this
+    pub intent_id: String,  // prediction intent the bridge transfer serves
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
@@ -35,6 +41,16 @@ pub struct ProgressTracker {
     pub last_update: u64,
 }
 
+// One step in a bridge transaction's life: its status and progress step after the change
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+pub struct BridgeTimelineEntry {
+    pub tx_hash: String,
+    pub timestamp: u64,
+    pub status: TransactionStatus,
+    pub step: BridgeStep,
+    pub note: Option<String>, // failure reason or retry number
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
 pub enum TransactionStatus {
     Initiated,
@@ -70,6 +86,51 @@ pub struct AlertThresholds {
     pub stuck_transaction_threshold: u64,
 }
 
+// Per-chain counters for one hour of activity; summed over the stats window by get_monitor_stats
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ChainBucket {
+    pub started: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub total_completion_time: u64,
+}
+
+// Whether a source chain looks operational, judged from how its bridge transactions end
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+pub struct ChainHealth {
+    pub chain_id: u32,
+    pub last_successful_tx: u64,          // ns; 0 until a transaction completes
+    pub last_failure: Option<u64>,
+    pub consecutive_failures: u8,
+    pub is_degraded: bool,                // set after CHAIN_DEGRADED_AFTER_FAILURES failures in a row
+    pub avg_confirmation_time_ms: u64,    // moving average, weighted toward recent completions
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChainStats {
+    pub source_chain: u32,
+    pub started: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub average_completion_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MonitorStats {
+    pub status_counts: Vec<(String, u64)>,
+    pub chains: Vec<ChainStats>,
+    pub window: u64,
+    pub retry_queue_depth: u64,
+    pub stuck_transactions: u64,
+}
+
+const STATS_BUCKET_SIZE: u64 = 3600000000000; // 1 hour in nanoseconds
+const STATS_WINDOW_BUCKETS: u64 = 24;
+const RETRY_BASE_DELAY: u64 = 60_000_000_000; // 1 minute in nanoseconds, doubled on every retry
+const MAX_TIMELINE_ENTRIES: usize = 50;        // per transaction; the first entry is always kept
+const CHAIN_DEGRADED_AFTER_FAILURES: u8 = 3;   // consecutive failures before a chain is degraded
+const CONFIRMATION_TIME_SMOOTHING: u64 = 8;    // latest completion weighs 1/8 in the average
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct CrossChainMonitor {
@@ -80,6 +141,14 @@ pub struct CrossChainMonitor {
     pub retry_queue: UnorderedSet<String>,
     pub alert_thresholds: AlertThresholds,
     pub monitoring_enabled: bool,
+    pub status_counts: UnorderedMap<String, u64>,
+    pub chain_buckets: UnorderedMap<String, ChainBucket>, // "chain:hour" -> counters
+    pub tracked_chains: UnorderedSet<u32>,
+    pub intent_to_txs: UnorderedMap<String, Vec<String>>, // intent_id -> tx hashes, oldest first
+    pub tx_timelines: UnorderedMap<String, Vec<BridgeTimelineEntry>>,
+    pub chain_health: UnorderedMap<u32, ChainHealth>, // source chain -> health
+    pub state_version: StateVersion,                  // layout marker checked by migrate()
+    pub pending_upgrade_hash: Option<CryptoHash>,     // sha256 of owner-approved code for upgrade()
 }
 
 #[near_bindgen]
@@ -98,6 +167,14 @@ impl CrossChainMonitor {
                 stuck_transaction_threshold: 7200000000000, // 2 hours
             },
             monitoring_enabled: true,
+            status_counts: UnorderedMap::new(b"s"),
+            chain_buckets: UnorderedMap::new(b"c"),
+            tracked_chains: UnorderedSet::new(b"t"),
+            intent_to_txs: UnorderedMap::new(b"i"),
+            tx_timelines: UnorderedMap::new(b"l"),
+            chain_health: UnorderedMap::new(b"h"),
+            state_version: StateVersion::CURRENT,
+            pending_upgrade_hash: None,
         }
     }
 
@@ -109,6 +186,7 @@ impl CrossChainMonitor {
         user: AccountId,
         amount: String,
         token: String,
+        intent_id: String,
     ) {
         let transaction = BridgeTransaction {
             tx_hash: tx_hash.clone(),
@@ -120,7 +198,10 @@ impl CrossChainMonitor {
             status: TransactionStatus::Initiated,
             created_at: env::block_timestamp(),
             updated_at: env::block_timestamp(),
+            completed_at: None,
             retry_count: 0,
+            next_retry_at: 0,
+            intent_id: intent_id.clone(),
         };
 
         let progress = ProgressTracker {
@@ -133,12 +214,25 @@ impl CrossChainMonitor {
 
         self.bridge_transactions.insert(&tx_hash, &transaction);
         self.progress_tracking.insert(&tx_hash, &progress);
+
+        let mut intent_txs = self.intent_to_txs.get(&intent_id).unwrap_or_default();
+        if !intent_txs.contains(&tx_hash) {
+            intent_txs.push(tx_hash.clone());
+            self.intent_to_txs.insert(&intent_id, &intent_txs);
+        }
+        self.record_timeline(&tx_hash, None);
+
+        self.tracked_chains.insert(&source_chain);
+        self.adjust_status_count(&TransactionStatus::Initiated, true);
+        self.update_chain_bucket(source_chain, |bucket| bucket.started += 1);
     }
 
     pub fn update_transaction_status(&mut self, tx_hash: String, status: TransactionStatus) {
         if let Some(mut transaction) = self.bridge_transactions.get(&tx_hash) {
+            let previous_status = transaction.status.clone();
             transaction.status = status;
             transaction.updated_at = env::block_timestamp();
+            self.record_status_change(&mut transaction, &previous_status);
             self.bridge_transactions.insert(&tx_hash, &transaction);
 
             if let Some(mut progress) = self.progress_tracking.get(&tx_hash) {
@@ -153,6 +247,7 @@ impl CrossChainMonitor {
                 progress.last_update = env::block_timestamp();
                 self.progress_tracking.insert(&tx_hash, &progress);
             }
+            self.record_timeline(&tx_hash, None);
         }
     }
 
@@ -166,10 +261,14 @@ impl CrossChainMonitor {
 
     pub fn mark_transaction_failed(&mut self, tx_hash: String, error_message: String) {
         if let Some(mut transaction) = self.bridge_transactions.get(&tx_hash) {
+            let previous_status = transaction.status.clone();
             transaction.status = TransactionStatus::Failed;
             transaction.updated_at = env::block_timestamp();
+            self.record_status_change(&mut transaction, &previous_status);
             self.bridge_transactions.insert(&tx_hash, &transaction);
 
+            self.record_timeline(&tx_hash, Some(error_message.clone()));
+
             let failed_tx = FailedTransaction {
                 tx_hash: tx_hash.clone(),
                 error_message,
@@ -182,20 +281,59 @@ impl CrossChainMonitor {
         }
     }
 
+    /// Retry a transaction whose backoff has elapsed. Each retry pushes the next one out to
+    /// RETRY_BASE_DELAY * 2^retry_count, so a bridge outage isn't hammered
     pub fn retry_transaction(&mut self, tx_hash: String) -> bool {
         if let Some(mut transaction) = self.bridge_transactions.get(&tx_hash) {
-            if transaction.retry_count < self.alert_thresholds.max_retry_count {
+            let now = env::block_timestamp();
+            if transaction.retry_count < self.alert_thresholds.max_retry_count && now >= transaction.next_retry_at {
+                let previous_status = transaction.status.clone();
+                transaction.next_retry_at = now + Self::retry_delay(transaction.retry_count);
                 transaction.retry_count += 1;
                 transaction.status = TransactionStatus::Initiated;
                 transaction.updated_at = env::block_timestamp();
+                self.record_status_change(&mut transaction, &previous_status);
                 self.bridge_transactions.insert(&tx_hash, &transaction);
                 self.retry_queue.remove(&tx_hash);
+                self.record_timeline(&tx_hash, Some(format!("retry {}", transaction.retry_count)));
                 return true;
             }
         }
         false
     }
 
+    /// Queued transactions with retries left whose backoff has elapsed, for the keeper to retry
+    pub fn get_retryable_transactions(&self) -> Vec<BridgeTransaction> {
+        let now = env::block_timestamp();
+        self.retry_queue
+            .iter()
+            .filter_map(|tx_hash| self.bridge_transactions.get(&tx_hash))
+            .filter(|tx| tx.retry_count < self.alert_thresholds.max_retry_count && now >= tx.next_retry_at)
+            .collect()
+    }
+
+    pub fn get_bridge_transactions_for_intent(&self, intent_id: String) -> Vec<BridgeTransaction> {
+        self.intent_to_txs
+            .get(&intent_id)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|tx_hash| self.bridge_transactions.get(tx_hash))
+            .collect()
+    }
+
+    /// Status changes and progress steps of every bridge transaction for the intent, oldest first
+    pub fn get_bridge_timeline(&self, intent_id: String) -> Vec<BridgeTimelineEntry> {
+        let mut timeline: Vec<BridgeTimelineEntry> = self.intent_to_txs
+            .get(&intent_id)
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|tx_hash| self.tx_timelines.get(tx_hash).unwrap_or_default())
+            .collect();
+        // Stable: entries from the same block keep transaction order
+        timeline.sort_by_key(|entry| entry.timestamp);
+        timeline
+    }
+
     pub fn get_failed_transactions(&self) -> Vec<FailedTransaction> {
         self.failed_transactions.values().collect()
     }
@@ -220,6 +358,56 @@ impl CrossChainMonitor {
             .collect()
     }
 
+    /// Aggregate view: counts by status, per-source-chain activity over the last 24 hours,
+    /// retry queue depth and stuck transactions
+    pub fn get_monitor_stats(&self) -> MonitorStats {
+        let current_bucket = env::block_timestamp() / STATS_BUCKET_SIZE;
+        let first_bucket = current_bucket.saturating_sub(STATS_WINDOW_BUCKETS - 1);
+
+        let chains = self.tracked_chains
+            .iter()
+            .map(|chain| {
+                let mut totals = ChainBucket::default();
+                for bucket_index in first_bucket..=current_bucket {
+                    if let Some(bucket) = self.chain_buckets.get(&Self::chain_bucket_key(chain, bucket_index)) {
+                        totals.started += bucket.started;
+                        totals.completed += bucket.completed;
+                        totals.failed += bucket.failed;
+                        totals.total_completion_time += bucket.total_completion_time;
+                    }
+                }
+
+                ChainStats {
+                    source_chain: chain,
+                    started: totals.started,
+                    completed: totals.completed,
+                    failed: totals.failed,
+                    average_completion_time: if totals.completed > 0 {
+                        totals.total_completion_time / totals.completed
+                    } else {
+                        0
+                    },
+                }
+            })
+            .collect();
+
+        MonitorStats {
+            status_counts: self.status_counts.iter().collect(),
+            chains,
+            window: STATS_BUCKET_SIZE * STATS_WINDOW_BUCKETS,
+            retry_queue_depth: self.retry_queue.len(),
+            stuck_transactions: self.get_stuck_transactions().len() as u64,
+        }
+    }
+
+    pub fn get_chain_health(&self, chain_id: u32) -> Option<ChainHealth> {
+        self.chain_health.get(&chain_id)
+    }
+
+    pub fn get_all_chain_health(&self) -> Vec<ChainHealth> {
+        self.chain_health.values().collect()
+    }
+
     pub fn toggle_monitoring(&mut self, enabled: bool) {
         assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can toggle monitoring");
         self.monitoring_enabled = enabled;
@@ -229,4 +417,372 @@ impl CrossChainMonitor {
         assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can update thresholds");
         self.alert_thresholds = thresholds;
     }
-}
\ No newline at end of file
+
+    // Keep status counters and per-chain buckets in step with a status transition
+    fn record_status_change(&mut self, transaction: &mut BridgeTransaction, previous_status: &TransactionStatus) {
+        if Self::status_key(previous_status) == Self::status_key(&transaction.status) {
+            return;
+        }
+
+        self.adjust_status_count(previous_status, false);
+        self.adjust_status_count(&transaction.status, true);
+
+        match transaction.status {
+            TransactionStatus::Completed => {
+                let completed_at = env::block_timestamp();
+                let duration = completed_at.saturating_sub(transaction.created_at);
+                transaction.completed_at = Some(completed_at);
+                self.update_chain_bucket(transaction.source_chain, |bucket| {
+                    bucket.completed += 1;
+                    bucket.total_completion_time += duration;
+                });
+                self.record_chain_success(transaction.source_chain, completed_at, duration);
+            }
+            TransactionStatus::Failed => {
+                self.update_chain_bucket(transaction.source_chain, |bucket| bucket.failed += 1);
+                self.record_chain_failure(transaction.source_chain, env::block_timestamp());
+            }
+            _ => {}
+        }
+    }
+
+    // Append the transaction's current status and step to its timeline
+    fn record_timeline(&mut self, tx_hash: &String, note: Option<String>) {
+        let (Some(transaction), Some(progress)) =
+            (self.bridge_transactions.get(tx_hash), self.progress_tracking.get(tx_hash))
+        else {
+            return;
+        };
+
+        let mut timeline = self.tx_timelines.get(tx_hash).unwrap_or_default();
+        if timeline.len() >= MAX_TIMELINE_ENTRIES {
+            timeline.remove(1);
+        }
+        timeline.push(BridgeTimelineEntry {
+            tx_hash: tx_hash.clone(),
+            timestamp: env::block_timestamp(),
+            status: transaction.status,
+            step: progress.current_step,
+            note,
+        });
+        self.tx_timelines.insert(tx_hash, &timeline);
+    }
+
+    fn adjust_status_count(&mut self, status: &TransactionStatus, increment: bool) {
+        let key = Self::status_key(status);
+        let count = self.status_counts.get(&key).unwrap_or(0);
+        let updated = if increment { count + 1 } else { count.saturating_sub(1) };
+        self.status_counts.insert(&key, &updated);
+    }
+
+    fn update_chain_bucket<F: FnOnce(&mut ChainBucket)>(&mut self, chain: u32, update: F) {
+        let key = Self::chain_bucket_key(chain, env::block_timestamp() / STATS_BUCKET_SIZE);
+        let mut bucket = self.chain_buckets.get(&key).unwrap_or_default();
+        update(&mut bucket);
+        self.chain_buckets.insert(&key, &bucket);
+    }
+
+    // A completion clears the chain's failure streak and feeds the confirmation time average
+    fn record_chain_success(&mut self, chain_id: u32, completed_at: u64, duration: u64) {
+        self.update_chain_health(chain_id, |health| {
+            let confirmation_ms = duration / 1_000_000;
+            health.avg_confirmation_time_ms = if health.last_successful_tx == 0 {
+                confirmation_ms
+            } else {
+                (health.avg_confirmation_time_ms * (CONFIRMATION_TIME_SMOOTHING - 1) + confirmation_ms)
+                    / CONFIRMATION_TIME_SMOOTHING
+            };
+            health.last_successful_tx = completed_at;
+            health.consecutive_failures = 0;
+            health.is_degraded = false;
+        });
+    }
+
+    fn record_chain_failure(&mut self, chain_id: u32, failed_at: u64) {
+        self.update_chain_health(chain_id, |health| {
+            health.last_failure = Some(failed_at);
+            health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+            if health.consecutive_failures >= CHAIN_DEGRADED_AFTER_FAILURES {
+                health.is_degraded = true;
+            }
+        });
+    }
+
+    fn update_chain_health<F: FnOnce(&mut ChainHealth)>(&mut self, chain_id: u32, update: F) {
+        let mut health = self.chain_health.get(&chain_id).unwrap_or(ChainHealth {
+            chain_id,
+            last_successful_tx: 0,
+            last_failure: None,
+            consecutive_failures: 0,
+            is_degraded: false,
+            avg_confirmation_time_ms: 0,
+        });
+        update(&mut health);
+        self.chain_health.insert(&chain_id, &health);
+    }
+
+    fn retry_delay(retry_count: u8) -> u64 {
+        RETRY_BASE_DELAY.saturating_mul(2u64.saturating_pow(retry_count as u32))
+    }
+
+    fn chain_bucket_key(chain: u32, bucket_index: u64) -> String {
+        format!("{}:{}", chain, bucket_index)
+    }
+
+    fn status_key(status: &TransactionStatus) -> String {
+        format!("{:?}", status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{testing_env, VMContext};
+
+    const START: u64 = 1_700_000_000_000_000_000;
+    const SECOND: u64 = 1_000_000_000;
+
+    fn get_context(predecessor: &str, block_timestamp: u64) -> VMContext {
+        VMContextBuilder::new()
+            .current_account_id("monitor.testnet".parse().unwrap())
+            .predecessor_account_id(predecessor.parse().unwrap())
+            .block_timestamp(block_timestamp)
+            .build()
+    }
+
+    fn setup() -> CrossChainMonitor {
+        testing_env!(get_context("owner.testnet", START));
+        CrossChainMonitor::new("owner.testnet".parse().unwrap())
+    }
+
+    fn start(contract: &mut CrossChainMonitor, tx_hash: &str, source_chain: u32) {
+        contract.start_bridge_transaction(
+            tx_hash.to_string(),
+            source_chain,
+            0,
+            "user.testnet".parse().unwrap(),
+            "1000000".to_string(),
+            "usdc.testnet".to_string(),
+            "intent_1".to_string(),
+        );
+    }
+
+    #[test]
+    fn test_retry_backoff_doubles() {
+        let mut contract = setup();
+        start(&mut contract, "tx_1", 1);
+        contract.mark_transaction_failed("tx_1".to_string(), "bridge timeout".to_string());
+
+        // First retry is immediate, then 1, 2, 4 and 8 minutes apart
+        let mut now = START;
+        for delay_minutes in [1, 2, 4, 8] {
+            testing_env!(get_context("keeper.testnet", now));
+            assert!(contract.retry_transaction("tx_1".to_string()));
+            let next_retry_at = contract.get_bridge_status("tx_1".to_string()).unwrap().next_retry_at;
+            assert_eq!(next_retry_at, now + delay_minutes * 60 * SECOND);
+
+            testing_env!(get_context("keeper.testnet", next_retry_at - 1));
+            assert!(!contract.retry_transaction("tx_1".to_string()), "retried before the backoff elapsed");
+            now = next_retry_at;
+        }
+
+        // The fifth retry uses up max_retry_count
+        testing_env!(get_context("keeper.testnet", now));
+        assert!(contract.retry_transaction("tx_1".to_string()));
+        let transaction = contract.get_bridge_status("tx_1".to_string()).unwrap();
+        assert_eq!(transaction.retry_count, 5);
+        testing_env!(get_context("keeper.testnet", transaction.next_retry_at));
+        assert!(!contract.retry_transaction("tx_1".to_string()));
+    }
+
+    #[test]
+    fn test_get_retryable_transactions() {
+        let mut contract = setup();
+        for tx_hash in ["tx_1", "tx_2", "tx_3"] {
+            start(&mut contract, tx_hash, 1);
+        }
+        contract.mark_transaction_failed("tx_1".to_string(), "bridge timeout".to_string());
+        contract.mark_transaction_failed("tx_2".to_string(), "bridge timeout".to_string());
+
+        let mut retryable: Vec<String> = contract.get_retryable_transactions().into_iter().map(|tx| tx.tx_hash).collect();
+        retryable.sort();
+        assert_eq!(retryable, vec!["tx_1", "tx_2"]);
+
+        // A retried transaction that fails again waits out its backoff before it is offered again
+        assert!(contract.retry_transaction("tx_1".to_string()));
+        contract.mark_transaction_failed("tx_1".to_string(), "bridge timeout".to_string());
+        let retryable: Vec<String> = contract.get_retryable_transactions().into_iter().map(|tx| tx.tx_hash).collect();
+        assert_eq!(retryable, vec!["tx_2"]);
+
+        testing_env!(get_context("keeper.testnet", START + 60 * SECOND));
+        assert_eq!(contract.get_retryable_transactions().len(), 2);
+
+        // Out of retries: left for an operator
+        testing_env!(get_context("owner.testnet", START + 60 * SECOND));
+        contract.update_alert_thresholds(AlertThresholds {
+            max_processing_time: 3600 * SECOND,
+            max_retry_count: 1,
+            stuck_transaction_threshold: 7200 * SECOND,
+        });
+        let retryable: Vec<String> = contract.get_retryable_transactions().into_iter().map(|tx| tx.tx_hash).collect();
+        assert_eq!(retryable, vec!["tx_2"]);
+    }
+
+    #[test]
+    fn test_chain_degrades_after_consecutive_failures() {
+        let mut contract = setup();
+        for tx_hash in ["tx_1", "tx_2", "tx_3", "tx_4", "tx_5"] {
+            start(&mut contract, tx_hash, 1);
+        }
+        start(&mut contract, "tx_other", 2);
+        assert!(contract.get_chain_health(1).is_none());
+
+        testing_env!(get_context("keeper.testnet", START + 30 * SECOND));
+        contract.mark_transaction_failed("tx_1".to_string(), "bridge timeout".to_string());
+        contract.mark_transaction_failed("tx_2".to_string(), "bridge timeout".to_string());
+        let health = contract.get_chain_health(1).unwrap();
+        assert_eq!((health.consecutive_failures, health.is_degraded), (2, false));
+
+        contract.mark_transaction_failed("tx_3".to_string(), "bridge timeout".to_string());
+        let health = contract.get_chain_health(1).unwrap();
+        assert_eq!((health.consecutive_failures, health.is_degraded), (CHAIN_DEGRADED_AFTER_FAILURES, true));
+        assert_eq!(health.last_failure, Some(START + 30 * SECOND));
+        assert_eq!(health.last_successful_tx, 0);
+
+        // Another chain's failures don't count toward this one
+        contract.mark_transaction_failed("tx_other".to_string(), "bridge timeout".to_string());
+        assert_eq!(contract.get_chain_health(2).unwrap().consecutive_failures, 1);
+        assert!(!contract.get_chain_health(2).unwrap().is_degraded);
+        assert_eq!(contract.get_all_chain_health().len(), 2);
+
+        // One completion resets the streak and lifts the degraded flag
+        testing_env!(get_context("keeper.testnet", START + 120 * SECOND));
+        contract.update_transaction_status("tx_4".to_string(), TransactionStatus::Completed);
+        let health = contract.get_chain_health(1).unwrap();
+        assert_eq!((health.consecutive_failures, health.is_degraded), (0, false));
+        assert_eq!(health.last_successful_tx, START + 120 * SECOND);
+        assert_eq!(health.last_failure, Some(START + 30 * SECOND));
+
+        // Non-terminal status changes leave it alone
+        contract.update_transaction_status("tx_5".to_string(), TransactionStatus::BridgeProcessing);
+        assert_eq!(contract.get_chain_health(1).unwrap().consecutive_failures, 0);
+    }
+
+    #[test]
+    fn test_confirmation_time_running_average() {
+        let mut contract = setup();
+
+        // (started, completed) in seconds after START: 80s, then 160s, then 8s to confirm
+        for (tx_hash, started, completed) in [("tx_1", 0, 80), ("tx_2", 100, 260), ("tx_3", 300, 308)] {
+            testing_env!(get_context("keeper.testnet", START + started * SECOND));
+            start(&mut contract, tx_hash, 1);
+            testing_env!(get_context("keeper.testnet", START + completed * SECOND));
+            contract.update_transaction_status(tx_hash.to_string(), TransactionStatus::Completed);
+        }
+
+        // The first completion sets the average, later ones weigh 1/8:
+        // (80000 * 7 + 160000) / 8 = 90000, then (90000 * 7 + 8000) / 8 = 79750
+        let health = contract.get_chain_health(1).unwrap();
+        assert_eq!(health.avg_confirmation_time_ms, 79_750);
+        assert_eq!(health.last_successful_tx, START + 308 * SECOND);
+        assert_eq!(contract.get_bridge_status("tx_3".to_string()).unwrap().completed_at, Some(START + 308 * SECOND));
+    }
+
+    #[test]
+    fn test_migrate_v1_state() {
+        testing_env!(get_context("monitor.testnet", START));
+
+        // Write state the way the unversioned contract stored it
+        let mut old = migration::CrossChainMonitorV1 {
+            owner_id: "owner.testnet".parse().unwrap(),
+            bridge_transactions: UnorderedMap::new(b"b"),
+            failed_transactions: UnorderedMap::new(b"f"),
+            progress_tracking: UnorderedMap::new(b"p"),
+            retry_queue: UnorderedSet::new(b"r"),
+            alert_thresholds: AlertThresholds {
+                max_processing_time: 3600 * SECOND,
+                max_retry_count: 5,
+                stuck_transaction_threshold: 7200 * SECOND,
+            },
+            monitoring_enabled: true,
+        };
+        for (tx_hash, source_chain, status, updated_secs_ago) in [
+            ("tx_1", 1, TransactionStatus::Completed, 300),
+            ("tx_2", 1, TransactionStatus::Failed, 200),
+            ("tx_3", 2, TransactionStatus::BridgeProcessing, 100),
+        ] {
+            old.bridge_transactions.insert(&tx_hash.to_string(), &migration::BridgeTransactionV1 {
+                tx_hash: tx_hash.to_string(),
+                source_chain,
+                target_chain: 0,
+                user: "user.testnet".parse().unwrap(),
+                amount: "1000000".to_string(),
+                token: "usdc.testnet".to_string(),
+                status,
+                created_at: START - 600 * SECOND,
+                updated_at: START - updated_secs_ago * SECOND,
+                retry_count: 0,
+            });
+        }
+        old.retry_queue.insert(&"tx_2".to_string());
+        env::state_write(&old);
+
+        let mut contract = CrossChainMonitor::migrate();
+        assert_eq!(contract.get_state_version(), StateVersion::V2);
+        assert!(contract.get_pending_upgrade().is_none());
+
+        let completed = contract.get_bridge_status("tx_1".to_string()).unwrap();
+        assert_eq!(completed.completed_at, Some(START - 300 * SECOND));
+        assert_eq!((completed.next_retry_at, completed.intent_id.as_str()), (0, ""));
+        assert_eq!(contract.get_bridge_status("tx_3".to_string()).unwrap().completed_at, None);
+
+        // The failed transaction is retryable straight away
+        let retryable: Vec<String> = contract.get_retryable_transactions().into_iter().map(|tx| tx.tx_hash).collect();
+        assert_eq!(retryable, vec!["tx_2"]);
+
+        let stats = contract.get_monitor_stats();
+        let mut counts = stats.status_counts.clone();
+        counts.sort();
+        assert_eq!(counts, vec![
+            ("BridgeProcessing".to_string(), 1),
+            ("Completed".to_string(), 1),
+            ("Failed".to_string(), 1),
+        ]);
+        let mut chains: Vec<u32> = stats.chains.iter().map(|chain| chain.source_chain).collect();
+        chains.sort();
+        assert_eq!(chains, vec![1, 2]);
+
+        // Chain health is replayed from the stored outcomes, oldest first
+        let health = contract.get_chain_health(1).unwrap();
+        assert_eq!(health.last_successful_tx, START - 300 * SECOND);
+        assert_eq!(health.avg_confirmation_time_ms, 300_000);
+        assert_eq!((health.last_failure, health.consecutive_failures, health.is_degraded), (Some(START - 200 * SECOND), 1, false));
+        assert!(contract.get_chain_health(2).is_none());
+
+        // Migrated transactions keep working under the new code
+        assert!(contract.retry_transaction("tx_2".to_string()));
+        contract.update_transaction_status("tx_3".to_string(), TransactionStatus::Completed);
+        assert!(contract.get_bridge_status("tx_3".to_string()).unwrap().completed_at.is_some());
+
+        // Running migrate against current state leaves it untouched
+        env::state_write(&contract);
+        let again = CrossChainMonitor::migrate();
+        assert_eq!(again.get_state_version(), StateVersion::V2);
+        assert_eq!(again.get_bridge_status("tx_2".to_string()).unwrap().retry_count, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Code does not match the proposed upgrade")]
+    fn test_upgrade_rejects_unapproved_code() {
+        let mut contract = setup();
+        contract.propose_upgrade(env::sha256_array(b"approved code").into());
+        assert!(contract.get_pending_upgrade().is_some());
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("owner.testnet".parse().unwrap())
+            .input(b"some other code".to_vec())
+            .build());
+        contract.upgrade();
+    }
+}
@@ -0,0 +1,180 @@
+// Upgrade and migration entry points for the cross-chain monitor.
+//
+// The owner approves a code hash with propose_upgrade, then calls upgrade with the wasm as raw
+// input; the deploy and the migrate call go out in one batch. Before changing the
+// CrossChainMonitor struct or a stored value type, freeze the current layout here as the next
+// V<n> and teach migrate() to convert it. Stored BridgeTransactions are rewritten one by one and
+// replayed into the status counts and chain health, so a monitor with a very large history may
+// need more than MIGRATE_TGAS.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{UnorderedMap, UnorderedSet};
+use near_sdk::json_types::Base58CryptoHash;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, NearToken, Promise};
+use schemars::JsonSchema;
+
+use crate::{
+    AlertThresholds, BridgeTransaction, CrossChainMonitor, CrossChainMonitorExt, FailedTransaction, ProgressTracker,
+    TransactionStatus,
+};
+
+const MIGRATE_TGAS: u64 = 100;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StateVersion {
+    V1,             // unversioned layout, up to monitoring_enabled
+    V2,             // adds state_version, pending_upgrade_hash, status counts, per-chain buckets, intent links, timelines and chain health; completed_at, next_retry_at and intent_id on every stored BridgeTransaction
+}
+
+impl StateVersion {
+    pub const CURRENT: StateVersion = StateVersion::V2;
+}
+
+/// BridgeTransaction layout in V1, frozen
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct BridgeTransactionV1 {
+    pub tx_hash: String,
+    pub source_chain: u32,
+    pub target_chain: u32,
+    pub user: AccountId,
+    pub amount: String,
+    pub token: String,
+    pub status: TransactionStatus,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub retry_count: u8,
+}
+
+impl From<BridgeTransactionV1> for BridgeTransaction {
+    fn from(old: BridgeTransactionV1) -> Self {
+        Self {
+            // V1 didn't record completion; its last update is the closest there is
+            completed_at: matches!(old.status, TransactionStatus::Completed).then_some(old.updated_at),
+            tx_hash: old.tx_hash,
+            source_chain: old.source_chain,
+            target_chain: old.target_chain,
+            user: old.user,
+            amount: old.amount,
+            token: old.token,
+            status: old.status,
+            created_at: old.created_at,
+            updated_at: old.updated_at,
+            retry_count: old.retry_count,
+            // No backoff was scheduled under V1, and transactions weren't linked to intents
+            next_retry_at: 0,
+            intent_id: String::new(),
+        }
+    }
+}
+
+/// V1 layout, frozen. Stored without a version marker
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct CrossChainMonitorV1 {
+    pub owner_id: AccountId,
+    pub bridge_transactions: UnorderedMap<String, BridgeTransactionV1>,
+    pub failed_transactions: UnorderedMap<String, FailedTransaction>,
+    pub progress_tracking: UnorderedMap<String, ProgressTracker>,
+    pub retry_queue: UnorderedSet<String>,
+    pub alert_thresholds: AlertThresholds,
+    pub monitoring_enabled: bool,
+}
+
+impl From<CrossChainMonitorV1> for CrossChainMonitor {
+    fn from(old: CrossChainMonitorV1) -> Self {
+        let mut legacy: Vec<(String, BridgeTransactionV1)> = old.bridge_transactions.iter().collect();
+        legacy.sort_by_key(|(_, transaction)| transaction.updated_at);
+        // The map's own borsh form is just its prefixes and length, so it reads back with the new
+        // value type; every entry is rewritten below
+        let bridge_transactions: UnorderedMap<String, BridgeTransaction> =
+            borsh::from_slice(&borsh::to_vec(&old.bridge_transactions).unwrap()).unwrap();
+
+        let mut contract = Self {
+            owner_id: old.owner_id,
+            bridge_transactions,
+            failed_transactions: old.failed_transactions,
+            progress_tracking: old.progress_tracking,
+            retry_queue: old.retry_queue,
+            alert_thresholds: old.alert_thresholds,
+            monitoring_enabled: old.monitoring_enabled,
+            status_counts: UnorderedMap::new(b"s"),
+            // Hourly activity before the upgrade isn't known; the stats window fills from here on
+            chain_buckets: UnorderedMap::new(b"c"),
+            tracked_chains: UnorderedSet::new(b"t"),
+            intent_to_txs: UnorderedMap::new(b"i"),
+            tx_timelines: UnorderedMap::new(b"l"),
+            chain_health: UnorderedMap::new(b"h"),
+            state_version: StateVersion::CURRENT,
+            pending_upgrade_hash: None,
+        };
+
+        // Oldest first, so chain health ends up reflecting each chain's most recent outcomes
+        for (tx_hash, transaction) in legacy {
+            let transaction: BridgeTransaction = transaction.into();
+            contract.tracked_chains.insert(&transaction.source_chain);
+            contract.adjust_status_count(&transaction.status, true);
+            match transaction.status {
+                TransactionStatus::Completed => contract.record_chain_success(
+                    transaction.source_chain,
+                    transaction.updated_at,
+                    transaction.updated_at.saturating_sub(transaction.created_at),
+                ),
+                TransactionStatus::Failed => contract.record_chain_failure(transaction.source_chain, transaction.updated_at),
+                _ => {}
+            }
+            contract.bridge_transactions.insert(&tx_hash, &transaction);
+        }
+        contract
+    }
+}
+
+#[near_bindgen]
+impl CrossChainMonitor {
+    /// Approve the sha256 of the next monitor code; replaces any earlier proposal
+    pub fn propose_upgrade(&mut self, code_hash: Base58CryptoHash) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can propose upgrades");
+
+        self.pending_upgrade_hash = Some(code_hash.into());
+        env::log_str(&format!("Upgrade proposed with code hash {}", String::from(&code_hash)));
+    }
+
+    /// Deploy the approved wasm (raw call input) and run migrate in the same batch
+    pub fn upgrade(&mut self) -> Promise {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can upgrade");
+        let code = env::input().expect("Contract code missing from input");
+        let approved = self.pending_upgrade_hash.take().expect("No upgrade proposed");
+        assert_eq!(env::sha256_array(&code), approved, "Code does not match the proposed upgrade");
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call("migrate".to_string(), Vec::new(), NearToken::from_yoctonear(0), Gas::from_tgas(MIGRATE_TGAS))
+    }
+
+    /// Convert stored state to the current layout; a no-op for state that is already current
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = env::storage_read(b"STATE").expect("No contract state to migrate");
+
+        let (from, mut contract) = match Self::try_from_slice(&state) {
+            Ok(current) => (current.state_version, current),
+            Err(_) => {
+                let old = CrossChainMonitorV1::try_from_slice(&state).expect("Unrecognized contract state layout");
+                (StateVersion::V1, old.into())
+            }
+        };
+        env::log_str(&format!("Monitor state migrated from {:?} to {:?}", from, StateVersion::CURRENT));
+        contract.state_version = StateVersion::CURRENT;
+        contract.pending_upgrade_hash = None;
+        contract
+    }
+
+    pub fn get_state_version(&self) -> StateVersion {
+        self.state_version
+    }
+
+    pub fn get_pending_upgrade(&self) -> Option<Base58CryptoHash> {
+        self.pending_upgrade_hash.map(Base58CryptoHash::from)
+    }
+}
@@ -2,9 +2,12 @@ use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{UnorderedMap, UnorderedSet};
 use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, near_bindgen, AccountId, Promise, PanicOnDefault};
+use near_sdk::{env, near_bindgen, AccountId, CryptoHash, Promise, PromiseOrValue, PanicOnDefault};
 use schemars::JsonSchema;
 
+mod migration;
+pub use migration::StateVersion;
+
 // Local type definitions for standalone contract
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(crate = "near_sdk::serde")]
@@ -38,13 +41,37 @@ pub struct Market {
 // External contract interfaces
 #[near_sdk::ext_contract(ext_ctf)]
 pub trait ConditionalTokenFramework {
-    fn report_payout_numerators(&mut self, condition_id: String, payout_numerators: Vec<U128>);
+    fn report_payouts(&mut self, question_id: String, payouts: Vec<U128>);
     fn get_condition(&self, condition_id: String) -> Option<Condition>;
+    fn get_condition_resolution_timestamp(&self, condition_id: String) -> Option<u64>;
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MarketStats {
+    pub market_id: String,
+    #[schemars(with = "String")]
+    pub total_volume: U128,
+    pub intent_count: u64,
 }
 
 #[near_sdk::ext_contract(ext_verifier)]
 pub trait PredictionVerifier {
     fn get_market(&self, market_id: String) -> Option<Market>;
+    fn get_market_stats(&self, market_id: String) -> MarketStats;
+    fn mark_market_resolved(&mut self, market_id: String, winning_outcome: u8);
+}
+
+#[near_sdk::ext_contract(ext_fungible_token)]
+pub trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+// Minimal price feed interface (Pyth/Chainlink style, one feed account per asset pair)
+#[near_sdk::ext_contract(ext_price_feed)]
+pub trait PriceFeed {
+    /// Latest observation with timestamp <= `timestamp`, None if the feed has no data that old
+    fn get_price_at(&self, timestamp: u64) -> Option<PricePoint>;
 }
 
 #[near_sdk::ext_contract(ext_self)]
@@ -55,6 +82,63 @@ pub trait ResolverCallbacks {
         winning_outcome: u8,
         #[callback_result] market_result: Result<Option<Market>, near_sdk::PromiseError>
     ) -> Promise;
+    fn on_price_feed_observation(
+        &mut self,
+        market_id: String,
+        #[callback_result] price_result: Result<Option<PricePoint>, near_sdk::PromiseError>
+    ) -> Option<u8>;
+    fn on_market_info_for_stale_fallback(
+        &mut self,
+        market_id: String,
+        #[callback_result] market_result: Result<Option<Market>, near_sdk::PromiseError>
+    ) -> Option<String>;
+    fn on_market_stats_for_dispute(
+        &mut self,
+        market_id: String,
+        disputer: AccountId,
+        reason: String,
+        evidence: String,
+        bond: U128,
+        #[callback_result] stats_result: Result<MarketStats, near_sdk::PromiseError>
+    ) -> Option<String>;
+    fn on_verifier_notified(
+        &mut self,
+        market_id: String,
+        winning_outcome: u8,
+        #[callback_result] notify_result: Result<(), near_sdk::PromiseError>
+    ) -> bool;
+    fn on_usdc_bond_paid(&mut self, receiver: AccountId, amount: U128) -> bool;
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PricePoint {
+    #[schemars(with = "String")]
+    pub price: U128,                                               // same decimals as the configured threshold
+    pub timestamp: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PriceComparison {
+    Above,          // YES if price > threshold
+    AboveOrEqual,   // YES if price >= threshold
+    Below,          // YES if price < threshold
+    BelowOrEqual,   // YES if price <= threshold
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ResolutionSource {
+    Manual,
+    PriceFeed {
+        #[schemars(with = "String")]
+        feed_account: AccountId,
+        comparison: PriceComparison,
+        #[schemars(with = "String")]
+        threshold: U128,
+        observation_time: u64,                                     // nanoseconds
+    },
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone)]
@@ -95,6 +179,26 @@ pub struct Dispute {
     pub dispute_outcome: Option<DisputeOutcome>,
 }
 
+/// Asset a dispute bond is posted in. NEAR is attached to dispute_resolution, USDC arrives
+/// through ft_transfer_call
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum BondCurrency {
+    Near,
+    Usdc,
+}
+
+/// ft_transfer_call msg understood by ft_on_transfer
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde", tag = "action", rename_all = "snake_case")]
+pub enum BondTransferMsg {
+    Dispute {
+        market_id: String,
+        reason: String,
+        evidence: String,
+    },
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub enum DisputeOutcome {
@@ -103,6 +207,67 @@ pub enum DisputeOutcome {
     MarketInvalid,  // Market declared invalid
 }
 
+/// NEAR staked on an outcome of a disputed market (submit_schelling_vote)
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SchellingVote {
+    #[schemars(with = "String")]
+    pub voter: AccountId,
+    #[schemars(with = "String")]
+    pub stake: U128,
+    pub outcome: u8,                                               // 0=NO, 1=YES, 2=INVALID
+    pub created_at: u64,
+}
+
+/// Oracle hand-over: `next` may resolve from effective_at, `previous` until the grace period ends
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuthorityRotation {
+    #[schemars(with = "String")]
+    pub previous: AccountId,
+    #[schemars(with = "String")]
+    pub next: AccountId,
+    pub effective_at: u64,
+    #[schemars(with = "String")]
+    pub requested_by: AccountId,
+    pub requested_at: u64,
+}
+
+/// An oracle's sealed outcome for a commit-reveal market. The outcome only becomes public in
+/// reveal_resolution, after reveal_from
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ResolutionCommitment {
+    pub market_id: String,
+    #[schemars(with = "String")]
+    pub oracle: AccountId,
+    pub commitment: [u8; 32],                                      // sha256(outcome || salt || oracle)
+    pub committed_at: u64,
+    pub reveal_from: u64,                                          // commit window closes; reveals accepted after this
+    pub reveal_by: u64,                                            // last reveal; the owner may void it afterwards
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OracleRotationInfo {
+    pub pending: Vec<AuthorityRotation>,
+    pub grace_period: u64,
+    pub history: Vec<AuthorityRotation>,
+}
+
+const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+const DEFAULT_MAX_DISPUTE_BOND: u128 = 100 * ONE_NEAR;
+const DEFAULT_AUTHORITY_GRACE_PERIOD: u64 = 600_000_000_000;                 // 10 minutes in nanoseconds
+const USDC_FT_TRANSFER_TGAS: u64 = 10;
+const USDC_PAYOUT_CALLBACK_TGAS: u64 = 10;
+const DEFAULT_SCHELLING_VOTE_PERIOD: u64 = 172_800_000_000_000;              // 2 days in nanoseconds
+const MIN_SCHELLING_STAKE: u128 = 100_000_000_000_000_000_000_000;           // 0.1 NEAR
+const SCHELLING_SUPERMAJORITY_PERCENT: u128 = 60;                            // stake share the plurality needs
+const MAX_SCHELLING_VOTES: usize = 100;                                      // bounds the payouts made by tabulate
+const DEFAULT_COMMIT_WINDOW: u64 = 3_600_000_000_000;                        // 1 hour in nanoseconds
+const DEFAULT_REVEAL_WINDOW: u64 = 86_400_000_000_000;                       // 1 day in nanoseconds
+const DEFAULT_MAX_RESOLUTION_DELAY: u64 = 2_592_000_000_000_000;             // 30 days in nanoseconds
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct MarketResolver {
@@ -114,6 +279,39 @@ pub struct MarketResolver {
     pub disputes: UnorderedMap<String, Dispute>,                   // market_id -> Dispute
     pub dispute_period: u64,                                       // Time window for disputes (nanoseconds)
     pub dispute_bond: U128,                                        // NEAR required to start dispute
+    pub pending_owner: Option<AccountId>,                          // proposed owner awaiting accept_ownership
+    pub config_admin: Option<AccountId>,                           // may update dispute period and bond
+    pub resolution_sources: UnorderedMap<String, ResolutionSource>, // market_id -> how the market resolves
+    pub dispute_bond_percent_of_volume: u16,                       // basis points of market volume
+    pub max_dispute_bond: U128,                                    // cap on the volume-scaled bond
+    pub collateral_per_near: U128,                                 // collateral base units worth one NEAR; converts volume into the bond
+    pub market_volumes: UnorderedMap<String, U128>,                // market_id -> last volume seen from verifier
+    pub unsynced_resolutions: UnorderedMap<String, u8>,            // market_id -> outcome the verifier hasn't recorded
+    pub oracle_rotations: UnorderedMap<AccountId, AuthorityRotation>, // outgoing oracle -> rotation in progress
+    pub oracle_rotation_history: Vec<AuthorityRotation>,           // every scheduled rotation, oldest first
+    pub authority_grace_period: u64,                               // ns an outgoing oracle stays valid after effective_at
+    pub escrowed_bonds: UnorderedMap<String, U128>,                // market_id -> dispute bond held until resolve_dispute, in bond_currencies units
+    pub treasury_account: AccountId,                               // receives bonds from lost disputes
+    pub pending_set: UnorderedSet<String>,                         // market_ids with a Pending resolution
+    pub disputed_set: UnorderedSet<String>,                        // market_ids with a Disputed resolution
+    pub finalized_set: UnorderedSet<String>,                       // market_ids resolved Finalized or Invalid
+    pub resolutions_by_resolver: UnorderedMap<AccountId, Vec<String>>, // resolver -> market_ids, submission order
+    pub state_version: StateVersion,                               // layout marker checked by migrate()
+    pub pending_upgrade_hash: Option<CryptoHash>,                  // sha256 of owner-approved code for upgrade()
+    pub usdc_contract: Option<AccountId>,                          // NEP-141 token accepted for USDC bonds
+    pub usdc_dispute_bond: U128,                                   // USDC base units required to start a dispute
+    pub accepted_bond_currencies: Vec<BondCurrency>,               // which dispute entry points are open
+    pub bond_currencies: UnorderedMap<String, BondCurrency>,       // market_id -> currency of the escrowed bond; NEAR if absent
+    pub claimable_refunds: UnorderedMap<AccountId, U128>,          // USDC payouts whose ft_transfer failed
+    pub schelling_votes: UnorderedMap<String, Vec<SchellingVote>>, // market_id -> stakes on the disputed outcome
+    pub schelling_vote_period: u64,                                // ns voting stays open after a dispute is raised
+    pub commit_reveal_markets: UnorderedSet<String>,               // market_ids resolved through commit_resolution/reveal_resolution
+    pub resolution_commitments: UnorderedMap<String, ResolutionCommitment>, // market_id -> unrevealed commitment
+    pub commit_window: u64,                                        // ns after a commit before it can be revealed
+    pub reveal_window: u64,                                        // ns after the commit window a reveal is accepted
+    pub resolution_windows: UnorderedMap<String, u64>,             // market_id -> when the verifier closed trading and opened resolution
+    pub max_resolution_delay: u64,                                 // ns after resolution_time before the stale-market fallback opens
+    pub resolution_delay_overrides: UnorderedMap<String, u64>,     // market_id -> its own max_resolution_delay
 }
 
 #[near_bindgen]
@@ -127,6 +325,7 @@ impl MarketResolver {
         dispute_bond: U128,
     ) -> Self {
         Self {
+            treasury_account: owner_id.clone(),
             owner_id,
             verifier_contract,
             ctf_contract,
@@ -135,6 +334,38 @@ impl MarketResolver {
             disputes: UnorderedMap::new(b"d"),
             dispute_period,
             dispute_bond,
+            pending_owner: None,
+            config_admin: None,
+            resolution_sources: UnorderedMap::new(b"a"),
+            dispute_bond_percent_of_volume: 0,
+            max_dispute_bond: U128(dispute_bond.0.max(DEFAULT_MAX_DISPUTE_BOND)),
+            collateral_per_near: U128(0),
+            market_volumes: UnorderedMap::new(b"v"),
+            unsynced_resolutions: UnorderedMap::new(b"u"),
+            oracle_rotations: UnorderedMap::new(b"t"),
+            oracle_rotation_history: Vec::new(),
+            authority_grace_period: DEFAULT_AUTHORITY_GRACE_PERIOD,
+            escrowed_bonds: UnorderedMap::new(b"b"),
+            pending_set: UnorderedSet::new(b"p"),
+            disputed_set: UnorderedSet::new(b"q"),
+            finalized_set: UnorderedSet::new(b"f"),
+            resolutions_by_resolver: UnorderedMap::new(b"s"),
+            state_version: StateVersion::CURRENT,
+            pending_upgrade_hash: None,
+            usdc_contract: None,
+            usdc_dispute_bond: U128(0),
+            accepted_bond_currencies: vec![BondCurrency::Near],
+            bond_currencies: UnorderedMap::new(b"c"),
+            claimable_refunds: UnorderedMap::new(b"e"),
+            schelling_votes: UnorderedMap::new(b"g"),
+            schelling_vote_period: DEFAULT_SCHELLING_VOTE_PERIOD,
+            commit_reveal_markets: UnorderedSet::new(b"m"),
+            resolution_commitments: UnorderedMap::new(b"h"),
+            commit_window: DEFAULT_COMMIT_WINDOW,
+            reveal_window: DEFAULT_REVEAL_WINDOW,
+            resolution_windows: UnorderedMap::new(b"w"),
+            max_resolution_delay: DEFAULT_MAX_RESOLUTION_DELAY,
+            resolution_delay_overrides: UnorderedMap::new(b"x"),
         }
     }
 
@@ -148,13 +379,14 @@ impl MarketResolver {
         let caller = env::predecessor_account_id();
         
         // Check authorization
-        assert!(
-            self.authorized_oracles.contains(&caller) || caller == self.owner_id,
-            "Not authorized to submit resolutions"
-        );
+        self.assert_oracle("Not authorized to submit resolutions");
 
         // Validate outcome (0=NO, 1=YES, 2=INVALID)
         assert!(winning_outcome <= 2, "Invalid outcome value");
+        assert!(
+            !self.commit_reveal_markets.contains(&market_id),
+            "Market requires commit-reveal resolution"
+        );
 
         // Check if already resolved
         assert!(
@@ -166,12 +398,23 @@ impl MarketResolver {
         // In production, this would be a cross-contract call
         // For now, we'll assume the resolver can submit after resolution_time
 
+        self.file_resolution(market_id, caller, winning_outcome, resolution_data)
+    }
+
+    // Store a pending resolution; the dispute window starts now
+    fn file_resolution(
+        &mut self,
+        market_id: String,
+        resolver: AccountId,
+        winning_outcome: u8,
+        resolution_data: String,
+    ) -> String {
         let resolution_id = format!("resolution_{}_{}", market_id, env::block_timestamp());
         
         let resolution = Resolution {
             market_id: market_id.clone(),
             condition_id: String::new(), // Will be filled from market data
-            resolver: caller.clone(),
+            resolver: resolver.clone(),
             winning_outcome,
             resolution_data,
             submitted_at: env::block_timestamp(),
@@ -179,16 +422,339 @@ impl MarketResolver {
             status: ResolutionStatus::Pending,
         };
 
-        self.resolutions.insert(&market_id, &resolution);
+        self.store_resolution(&resolution);
+        let mut submitted = self.resolutions_by_resolver.get(&resolver).unwrap_or_default();
+        submitted.push(market_id.clone());
+        self.resolutions_by_resolver.insert(&resolver, &submitted);
 
         env::log_str(&format!(
             "Resolution submitted for market {}: outcome {} by {}",
-            market_id, winning_outcome, caller
+            market_id, winning_outcome, resolver
         ));
 
         resolution_id
     }
 
+    // Auto-resolution from price feeds
+    /// Configure how a market resolves. PriceFeed markets can then be resolved by anyone
+    /// through trigger_auto_resolution once observation_time has passed
+    pub fn register_auto_resolution(&mut self, market_id: String, config: ResolutionSource) {
+        self.assert_oracle("Not authorized to configure resolution");
+        assert!(
+            self.resolutions.get(&market_id).is_none(),
+            "Market already has a resolution"
+        );
+
+        if let ResolutionSource::PriceFeed { observation_time, .. } = &config {
+            assert!(*observation_time > env::block_timestamp(), "Observation time must be in the future");
+        }
+
+        self.resolution_sources.insert(&market_id, &config);
+        env::log_str(&format!("Resolution source for market {} set to {:?}", market_id, config));
+    }
+
+    pub fn get_resolution_source(&self, market_id: String) -> ResolutionSource {
+        self.resolution_sources.get(&market_id).unwrap_or(ResolutionSource::Manual)
+    }
+
+    /// Read the configured price feed at observation_time and file the resolution
+    pub fn trigger_auto_resolution(&mut self, market_id: String) -> Promise {
+        let (feed_account, observation_time) = match self.resolution_sources.get(&market_id) {
+            Some(ResolutionSource::PriceFeed { feed_account, observation_time, .. }) => (feed_account, observation_time),
+            _ => panic!("Market is not configured for price feed resolution"),
+        };
+
+        assert!(env::block_timestamp() >= observation_time, "Observation time not reached");
+        assert!(
+            self.resolutions.get(&market_id).is_none(),
+            "Market already has a resolution"
+        );
+
+        ext_price_feed::ext(feed_account)
+            .with_static_gas(near_sdk::Gas::from_tgas(5))
+            .get_price_at(observation_time)
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(near_sdk::Gas::from_tgas(10))
+                    .on_price_feed_observation(market_id)
+            )
+    }
+
+    #[private]
+    pub fn on_price_feed_observation(
+        &mut self,
+        market_id: String,
+        #[callback_result] price_result: Result<Option<PricePoint>, near_sdk::PromiseError>
+    ) -> Option<u8> {
+        let (comparison, threshold, observation_time) = match self.resolution_sources.get(&market_id) {
+            Some(ResolutionSource::PriceFeed { comparison, threshold, observation_time, .. }) => (comparison, threshold, observation_time),
+            _ => {
+                env::log_str(&format!("Market {} no longer configured for price feed resolution", market_id));
+                return None;
+            }
+        };
+
+        // Someone may have resolved it while the feed call was in flight
+        if self.resolutions.get(&market_id).is_some() {
+            env::log_str(&format!("Market {} already resolved, ignoring price feed", market_id));
+            return None;
+        }
+
+        let price_point = match price_result {
+            Ok(Some(price_point)) if price_point.timestamp <= observation_time => price_point,
+            Ok(Some(price_point)) => {
+                env::log_str(&format!(
+                    "Price feed returned observation at {} after observation time {} for market {}",
+                    price_point.timestamp, observation_time, market_id
+                ));
+                return None;
+            }
+            Ok(None) => {
+                env::log_str(&format!("Price feed has no observation for market {}", market_id));
+                return None;
+            }
+            Err(e) => {
+                env::log_str(&format!("Price feed call failed for market {}: {:?}", market_id, e));
+                return None;
+            }
+        };
+
+        let winning_outcome = Self::evaluate_price_condition(&comparison, price_point.price.0, threshold.0);
+        let resolution_data = format!(
+            "{{\"source\":\"price_feed\",\"price\":\"{}\",\"observed_at\":{},\"comparison\":\"{:?}\",\"threshold\":\"{}\"}}",
+            price_point.price.0, price_point.timestamp, comparison, threshold.0
+        );
+
+        self.file_resolution(market_id, env::current_account_id(), winning_outcome, resolution_data);
+        Some(winning_outcome)
+    }
+
+    // 1 = YES when the comparison holds, otherwise 0 = NO
+    fn evaluate_price_condition(comparison: &PriceComparison, price: u128, threshold: u128) -> u8 {
+        let holds = match comparison {
+            PriceComparison::Above => price > threshold,
+            PriceComparison::AboveOrEqual => price >= threshold,
+            PriceComparison::Below => price < threshold,
+            PriceComparison::BelowOrEqual => price <= threshold,
+        };
+
+        if holds { 1 } else { 0 }
+    }
+
+    // Commit-reveal resolution
+    /// Require a market to be resolved through commit_resolution/reveal_resolution, so the
+    /// outcome isn't visible to traders before the oracle is bound to it. The verifier calls this
+    /// for markets created with the flag; the owner and config admin can set it for any market
+    pub fn set_commit_reveal(&mut self, market_id: String, enabled: bool) {
+        if env::predecessor_account_id() != self.verifier_contract {
+            self.assert_config_admin("Only the verifier, owner or config admin can set commit-reveal");
+        }
+        assert!(
+            self.resolutions.get(&market_id).is_none(),
+            "Market already has a resolution"
+        );
+
+        if enabled {
+            self.commit_reveal_markets.insert(&market_id);
+        } else {
+            assert!(
+                self.resolution_commitments.get(&market_id).is_none(),
+                "Market has an unrevealed commitment"
+            );
+            self.commit_reveal_markets.remove(&market_id);
+        }
+        env::log_str(&format!("Commit-reveal resolution for market {} set to {}", market_id, enabled));
+    }
+
+    /// Seal an outcome: commitment = sha256([outcome] ++ salt ++ oracle account id). It can be
+    /// revealed once the commit window has passed and until the reveal window closes
+    pub fn commit_resolution(&mut self, market_id: String, commitment: [u8; 32]) {
+        self.assert_oracle("Not authorized to submit resolutions");
+        assert!(
+            self.commit_reveal_markets.contains(&market_id),
+            "Market does not use commit-reveal resolution"
+        );
+        assert!(
+            self.resolutions.get(&market_id).is_none(),
+            "Market already has a resolution"
+        );
+        assert!(
+            self.resolution_commitments.get(&market_id).is_none(),
+            "Resolution already committed"
+        );
+
+        let oracle = env::predecessor_account_id();
+        let committed_at = env::block_timestamp();
+        let reveal_from = committed_at + self.commit_window;
+        let commitment = ResolutionCommitment {
+            market_id: market_id.clone(),
+            oracle: oracle.clone(),
+            commitment,
+            committed_at,
+            reveal_from,
+            reveal_by: reveal_from + self.reveal_window,
+        };
+        self.resolution_commitments.insert(&market_id, &commitment);
+
+        env::log_str(&format!(
+            "Resolution committed for market {} by {}, reveal after {} and by {}",
+            market_id, oracle, commitment.reveal_from, commitment.reveal_by
+        ));
+    }
+
+    /// Open the committing oracle's commitment and file it as a pending resolution; the dispute
+    /// period starts now
+    pub fn reveal_resolution(&mut self, market_id: String, winning_outcome: u8, salt: String) -> String {
+        self.assert_oracle("Not authorized to submit resolutions");
+        let commitment = self.resolution_commitments.get(&market_id)
+            .expect("No resolution commitment for market");
+        assert_eq!(env::predecessor_account_id(), commitment.oracle, "Only the committing oracle can reveal");
+
+        let now = env::block_timestamp();
+        assert!(now > commitment.reveal_from, "Commit window has not closed");
+        assert!(now <= commitment.reveal_by, "Reveal deadline has passed");
+        assert!(
+            Self::resolution_commitment(winning_outcome, &salt, &commitment.oracle) == commitment.commitment,
+            "Reveal does not match the commitment"
+        );
+        assert!(winning_outcome <= 2, "Invalid outcome value");
+
+        self.resolution_commitments.remove(&market_id);
+        let resolution_data = format!(
+            "{{\"source\":\"commit_reveal\",\"committed_at\":{}}}",
+            commitment.committed_at
+        );
+        self.file_resolution(market_id, commitment.oracle, winning_outcome, resolution_data)
+    }
+
+    /// Drop a commitment nobody revealed in time so the market can be committed again
+    pub fn void_commitment(&mut self, market_id: String) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can void commitments");
+        let commitment = self.resolution_commitments.get(&market_id)
+            .expect("No resolution commitment for market");
+        assert!(env::block_timestamp() > commitment.reveal_by, "Reveal deadline has not passed");
+
+        self.resolution_commitments.remove(&market_id);
+        env::log_str(&format!(
+            "Unrevealed commitment by {} for market {} voided",
+            commitment.oracle, market_id
+        ));
+    }
+
+    fn resolution_commitment(winning_outcome: u8, salt: &str, oracle: &AccountId) -> [u8; 32] {
+        let mut preimage = vec![winning_outcome];
+        preimage.extend_from_slice(salt.as_bytes());
+        preimage.extend_from_slice(oracle.as_str().as_bytes());
+        env::sha256_array(&preimage)
+    }
+
+    pub fn is_commit_reveal_market(&self, market_id: String) -> bool {
+        self.commit_reveal_markets.contains(&market_id)
+    }
+
+    pub fn get_resolution_commitment(&self, market_id: String) -> Option<ResolutionCommitment> {
+        self.resolution_commitments.get(&market_id)
+    }
+
+    /// Commit window and reveal window, in nanoseconds
+    pub fn get_commit_reveal_config(&self) -> (u64, u64) {
+        (self.commit_window, self.reveal_window)
+    }
+
+    // Resolution windows
+    /// Called by the verifier when it terminates an ended market: trading is closed and the market
+    /// is waiting for its outcome. Returns when the window opened; a repeat call keeps the first time
+    pub fn set_resolution_window_open(&mut self, market_id: String) -> u64 {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.verifier_contract,
+            "Only the verifier can open a resolution window"
+        );
+
+        if let Some(opened_at) = self.resolution_windows.get(&market_id) {
+            return opened_at;
+        }
+        let opened_at = env::block_timestamp();
+        self.resolution_windows.insert(&market_id, &opened_at);
+        env::log_str(&format!("Resolution window opened for market {}", market_id));
+        opened_at
+    }
+
+    pub fn get_resolution_window_opened_at(&self, market_id: String) -> Option<u64> {
+        self.resolution_windows.get(&market_id)
+    }
+
+    // Stale-market fallback
+    /// Anyone can call this once a market's resolution_time is more than its max resolution delay
+    /// behind and nothing was filed: the market gets a pending INVALID (50/50) resolution so funds
+    /// aren't locked forever. It goes through the dispute period like any other resolution, and the
+    /// owner can still emergency_resolve the real outcome in the meantime
+    pub fn trigger_stale_market_fallback(&mut self, market_id: String) -> Promise {
+        assert!(
+            self.resolutions.get(&market_id).is_none(),
+            "Market already has a resolution"
+        );
+        if let Some(commitment) = self.resolution_commitments.get(&market_id) {
+            assert!(env::block_timestamp() > commitment.reveal_by, "Resolution commitment awaiting reveal");
+        }
+
+        ext_verifier::ext(self.verifier_contract.clone())
+            .with_static_gas(near_sdk::Gas::from_tgas(5))
+            .get_market(market_id.clone())
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(near_sdk::Gas::from_tgas(10))
+                    .on_market_info_for_stale_fallback(market_id)
+            )
+    }
+
+    #[private]
+    pub fn on_market_info_for_stale_fallback(
+        &mut self,
+        market_id: String,
+        #[callback_result] market_result: Result<Option<Market>, near_sdk::PromiseError>
+    ) -> Option<String> {
+        let market = match market_result {
+            Ok(Some(market)) => market,
+            Ok(None) => {
+                env::log_str(&format!("Market {} not found, no stale-market fallback", market_id));
+                return None;
+            }
+            Err(e) => {
+                env::log_str(&format!("Failed to get market info for {}: {:?}", market_id, e));
+                return None;
+            }
+        };
+
+        // The oracle may have resolved it while the verifier call was in flight
+        if self.resolutions.get(&market_id).is_some() {
+            env::log_str(&format!("Market {} already resolved, ignoring stale-market fallback", market_id));
+            return None;
+        }
+
+        let deadline = market.resolution_time.saturating_add(self.get_max_resolution_delay(market_id.clone()));
+        if env::block_timestamp() <= deadline {
+            env::log_str(&format!(
+                "Stale-market fallback for market {} rejected: resolution deadline {} has not passed",
+                market_id, deadline
+            ));
+            return None;
+        }
+
+        // An unrevealed commitment past its reveal window would otherwise still be revealable
+        self.resolution_commitments.remove(&market_id);
+        let resolution_data = format!(
+            "{{\"source\":\"stale_market_fallback\",\"resolution_time\":{},\"deadline\":{}}}",
+            market.resolution_time, deadline
+        );
+        Some(self.file_resolution(market_id, env::current_account_id(), 2, resolution_data))
+    }
+
+    /// Delay after resolution_time before the stale-market fallback opens for the market
+    pub fn get_max_resolution_delay(&self, market_id: String) -> u64 {
+        self.resolution_delay_overrides.get(&market_id).unwrap_or(self.max_resolution_delay)
+    }
+
     // Finalize resolution after dispute period
     pub fn finalize_resolution(&mut self, market_id: String) -> Promise {
         let mut resolution = self.resolutions.get(&market_id)
@@ -212,7 +778,7 @@ impl MarketResolver {
         // Update resolution status
         resolution.status = ResolutionStatus::Finalized;
         resolution.finalized_at = Some(env::block_timestamp());
-        self.resolutions.insert(&market_id, &resolution);
+        self.store_resolution(&resolution);
 
         env::log_str(&format!("Resolution finalized for market {}", market_id));
 
@@ -222,11 +788,58 @@ impl MarketResolver {
             .get_market(market_id.clone())
             .then(
                 ext_self::ext(env::current_account_id())
-                    .with_static_gas(near_sdk::Gas::from_tgas(10))
-                    .on_market_info_for_resolution(market_id, resolution.winning_outcome)
+                    .with_static_gas(near_sdk::Gas::from_tgas(30))
+                    .on_market_info_for_resolution(market_id.clone(), resolution.winning_outcome)
+            )
+            .and(self.notify_verifier(market_id, resolution.winning_outcome))
+    }
+
+    /// Re-send a finalized outcome the verifier failed to record
+    pub fn retry_verifier_notification(&mut self, market_id: String) -> Promise {
+        let winning_outcome = self.unsynced_resolutions.get(&market_id)
+            .expect("No pending verifier notification for market");
+        self.notify_verifier(market_id, winning_outcome)
+    }
+
+    pub fn get_unsynced_resolutions(&self) -> Vec<(String, u8)> {
+        self.unsynced_resolutions.to_vec()
+    }
+
+    // Tell the verifier to stop trading and open redemptions for the market
+    fn notify_verifier(&self, market_id: String, winning_outcome: u8) -> Promise {
+        ext_verifier::ext(self.verifier_contract.clone())
+            .with_static_gas(near_sdk::Gas::from_tgas(5))
+            .mark_market_resolved(market_id.clone(), winning_outcome)
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(near_sdk::Gas::from_tgas(5))
+                    .on_verifier_notified(market_id, winning_outcome)
             )
     }
 
+    #[private]
+    pub fn on_verifier_notified(
+        &mut self,
+        market_id: String,
+        winning_outcome: u8,
+        #[callback_result] notify_result: Result<(), near_sdk::PromiseError>
+    ) -> bool {
+        match notify_result {
+            Ok(()) => {
+                self.unsynced_resolutions.remove(&market_id);
+                true
+            }
+            Err(e) => {
+                self.unsynced_resolutions.insert(&market_id, &winning_outcome);
+                env::log_str(&format!(
+                    "Failed to mark market {} resolved on verifier: {:?}; call retry_verifier_notification",
+                    market_id, e
+                ));
+                false
+            }
+        }
+    }
+
     // Check if market is resolved and finalized
     pub fn is_market_finalized(&self, market_id: String) -> bool {
         if let Some(resolution) = self.resolutions.get(&market_id) {
@@ -237,45 +850,164 @@ impl MarketResolver {
     }
 
     // Dispute Mechanism
+    /// Raise a dispute. The attached bond must cover calculate_dispute_bond; the market's volume
+    /// is re-read from the verifier and an insufficient bond is refunded. Accepted bonds stay in
+    /// escrow until resolve_dispute pays them out
     #[payable]
     pub fn dispute_resolution(
         &mut self,
         market_id: String,
         reason: String,
         evidence: String,
-    ) -> String {
-        let resolution = self.resolutions.get(&market_id)
-            .expect("Resolution not found");
-
-        // Check if resolution is in dispute period
-        let dispute_deadline = resolution.submitted_at + self.dispute_period;
-        assert!(
-            env::block_timestamp() <= dispute_deadline,
-            "Dispute period has ended"
-        );
-
-        // Check if already disputed
-        assert!(
-            self.disputes.get(&market_id).is_none(),
-            "Market already disputed"
-        );
+    ) -> Promise {
+        assert!(self.accepted_bond_currencies.contains(&BondCurrency::Near), "NEAR dispute bonds are not accepted");
+        self.assert_disputable(&market_id);
 
-        // Check bond amount
+        // The flat bond is the floor whatever the volume, so reject early below it
         let attached_deposit = env::attached_deposit();
         assert!(
             attached_deposit.as_yoctonear() >= self.dispute_bond.0,
             "Insufficient dispute bond"
         );
+        // A second dispute can't be raised while the first one's bond is still in flight
+        assert!(self.escrowed_bonds.get(&market_id).is_none(), "Dispute already pending for market");
+        self.escrowed_bonds.insert(&market_id, &U128(attached_deposit.as_yoctonear()));
+
+        ext_verifier::ext(self.verifier_contract.clone())
+            .with_static_gas(near_sdk::Gas::from_tgas(5))
+            .get_market_stats(market_id.clone())
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(near_sdk::Gas::from_tgas(15))
+                    .on_market_stats_for_dispute(
+                        market_id,
+                        env::predecessor_account_id(),
+                        reason,
+                        evidence,
+                        U128(attached_deposit.as_yoctonear()),
+                    )
+            )
+    }
+
+    #[private]
+    pub fn on_market_stats_for_dispute(
+        &mut self,
+        market_id: String,
+        disputer: AccountId,
+        reason: String,
+        evidence: String,
+        bond: U128,
+        #[callback_result] stats_result: Result<MarketStats, near_sdk::PromiseError>
+    ) -> Option<String> {
+        match stats_result {
+            Ok(stats) => {
+                self.market_volumes.insert(&market_id, &stats.total_volume);
+            }
+            Err(e) => {
+                env::log_str(&format!(
+                    "Failed to get market stats for {}: {:?}, using last known volume",
+                    market_id, e
+                ));
+            }
+        }
+
+        let required_bond = self.calculate_dispute_bond(market_id.clone());
+        let still_disputable = self.resolutions.get(&market_id)
+            .map(|resolution| env::block_timestamp() <= resolution.submitted_at + self.dispute_period)
+            .unwrap_or(false)
+            && self.disputes.get(&market_id).is_none();
+
+        if bond.0 < required_bond.0 || !still_disputable {
+            env::log_str(&format!(
+                "Dispute for market {} rejected (bond {} required {}, disputable {}), refunding {}",
+                market_id, bond.0, required_bond.0, still_disputable, disputer
+            ));
+            let refund = self.escrowed_bonds.remove(&market_id).unwrap_or(bond);
+            Promise::new(disputer).transfer(near_sdk::NearToken::from_yoctonear(refund.0));
+            return None;
+        }
+
+        self.escrowed_bonds.insert(&market_id, &bond);
+        Some(self.record_dispute(market_id, disputer, reason, evidence, bond, BondCurrency::Near))
+    }
+
+    /// NEP-141 receiver for USDC dispute bonds. msg is
+    /// {"action":"dispute","market_id":...,"reason":...,"evidence":...}; the dispute is recorded
+    /// for usdc_dispute_bond and anything above it is handed back to the token contract as unused.
+    /// A rejected transfer panics so the token contract refunds all of it
+    pub fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        let usdc_contract = self.usdc_contract.clone().expect("USDC contract not configured");
+        assert_eq!(env::predecessor_account_id(), usdc_contract, "Only the USDC contract can send bonds");
+        assert!(self.accepted_bond_currencies.contains(&BondCurrency::Usdc), "USDC dispute bonds are not accepted");
+
+        let BondTransferMsg::Dispute { market_id, reason, evidence } = near_sdk::serde_json::from_str(&msg)
+            .unwrap_or_else(|e| env::panic_str(&format!("Invalid transfer msg: {}", e)));
+
+        self.assert_disputable(&market_id);
+        assert!(self.escrowed_bonds.get(&market_id).is_none(), "Dispute already pending for market");
+        let bond = self.usdc_dispute_bond;
+        assert!(amount.0 >= bond.0, "Insufficient dispute bond");
+
+        self.escrowed_bonds.insert(&market_id, &bond);
+        self.bond_currencies.insert(&market_id, &BondCurrency::Usdc);
+        self.record_dispute(market_id, sender_id, reason, evidence, bond, BondCurrency::Usdc);
+
+        PromiseOrValue::Value(U128(amount.0 - bond.0))
+    }
+
+    /// Bond needed to dispute a market: max(dispute_bond, volume * percent_of_volume), capped at
+    /// max_dispute_bond. Uses the volume last fetched from the verifier, converted from collateral
+    /// base units to yoctoNEAR at collateral_per_near; without a rate the flat bond applies
+    pub fn calculate_dispute_bond(&self, market_id: String) -> U128 {
+        if self.collateral_per_near.0 == 0 {
+            return self.dispute_bond;
+        }
+        let volume = self.market_volumes.get(&market_id).unwrap_or(U128(0));
+        let scaled_collateral = volume.0.saturating_mul(self.dispute_bond_percent_of_volume as u128) / 10000;
+        // Only volumes far past any cap overflow; they get the cap
+        let scaled_bond = scaled_collateral
+            .checked_mul(ONE_NEAR)
+            .map(|yocto| yocto / self.collateral_per_near.0)
+            .unwrap_or(u128::MAX);
+
+        U128(self.dispute_bond.0.max(scaled_bond).min(self.max_dispute_bond.0))
+    }
+
+    fn assert_disputable(&self, market_id: &String) {
+        let resolution = self.resolutions.get(market_id)
+            .expect("Resolution not found");
+
+        // Check if resolution is in dispute period
+        let dispute_deadline = resolution.submitted_at + self.dispute_period;
+        assert!(
+            env::block_timestamp() <= dispute_deadline,
+            "Dispute period has ended"
+        );
+
+        // Check if already disputed
+        assert!(
+            self.disputes.get(market_id).is_none(),
+            "Market already disputed"
+        );
+    }
+
+    fn record_dispute(
+        &mut self,
+        market_id: String,
+        disputer: AccountId,
+        reason: String,
+        evidence: String,
+        bond: U128,
+        currency: BondCurrency,
+    ) -> String {
+        let dispute_id = format!("dispute_{}_{}", market_id, env::block_timestamp());
 
-        let caller = env::predecessor_account_id();
-        let dispute_id = format!("dispute_{}_{}", market_id, env::block_timestamp());
-
         let dispute = Dispute {
             market_id: market_id.clone(),
-            disputer: caller.clone(),
+            disputer: disputer.clone(),
             reason,
             evidence,
-            bond_amount: U128(attached_deposit.as_yoctonear()),
+            bond_amount: bond,
             created_at: env::block_timestamp(),
             resolved_at: None,
             dispute_outcome: None,
@@ -286,11 +1018,15 @@ impl MarketResolver {
         // Update resolution status
         let mut resolution = self.resolutions.get(&market_id).unwrap();
         resolution.status = ResolutionStatus::Disputed;
-        self.resolutions.insert(&market_id, &resolution);
+        self.store_resolution(&resolution);
 
+        let unit = match currency {
+            BondCurrency::Near => "yoctoNEAR",
+            BondCurrency::Usdc => "USDC base unit",
+        };
         env::log_str(&format!(
-            "Dispute raised for market {} by {} with {} NEAR bond",
-            market_id, caller, attached_deposit
+            "Dispute raised for market {} by {} with {} {} bond",
+            market_id, disputer, bond.0, unit
         ));
 
         dispute_id
@@ -309,48 +1045,237 @@ impl MarketResolver {
             .expect("Dispute not found");
         
         assert!(dispute.resolved_at.is_none(), "Dispute already resolved");
+        // Stakes are only paid out by tabulate_schelling_vote
+        assert!(self.schelling_votes.get(&market_id).is_none(), "Dispute is being settled by Schelling vote");
 
         dispute.resolved_at = Some(env::block_timestamp());
         dispute.dispute_outcome = Some(outcome.clone());
         self.disputes.insert(&market_id, &dispute);
 
         let mut resolution = self.resolutions.get(&market_id).unwrap();
+        // Disputes raised before bonds were escrowed fall back to the recorded bond
+        let bond = self.escrowed_bonds.remove(&market_id).unwrap_or(dispute.bond_amount);
+        let currency = self.bond_currencies.remove(&market_id).unwrap_or(BondCurrency::Near);
 
         match outcome {
             DisputeOutcome::DisputeWins => {
                 // Disputer wins - need to update resolution or invalidate market
                 resolution.status = ResolutionStatus::Invalid;
-                self.resolutions.insert(&market_id, &resolution);
+                self.store_resolution(&resolution);
                 
                 env::log_str(&format!("Dispute won for market {}: {}", market_id, explanation));
                 
                 // Return bond to disputer
-                Promise::new(dispute.disputer).transfer(near_sdk::NearToken::from_yoctonear(dispute.bond_amount.0))
+                self.pay_out_bond(dispute.disputer, bond, currency)
             }
             DisputeOutcome::DisputeLoses => {
                 // Original resolution stands
                 resolution.status = ResolutionStatus::Pending;
-                self.resolutions.insert(&market_id, &resolution);
+                self.store_resolution(&resolution);
                 
                 env::log_str(&format!("Dispute lost for market {}: {}", market_id, explanation));
                 
-                // Keep dispute bond (could be used for platform treasury)
-                Promise::new(env::current_account_id())
+                // Forfeited bond goes to the platform treasury
+                self.pay_out_bond(self.treasury_account.clone(), bond, currency)
             }
             DisputeOutcome::MarketInvalid => {
                 // Market declared invalid
                 resolution.status = ResolutionStatus::Invalid;
                 resolution.winning_outcome = 2; // INVALID
-                self.resolutions.insert(&market_id, &resolution);
+                self.store_resolution(&resolution);
                 
                 env::log_str(&format!("Market {} declared invalid: {}", market_id, explanation));
                 
                 // Return bond to disputer
-                Promise::new(dispute.disputer).transfer(near_sdk::NearToken::from_yoctonear(dispute.bond_amount.0))
+                self.pay_out_bond(dispute.disputer, bond, currency)
             }
         }
     }
 
+    fn pay_out_bond(&mut self, receiver: AccountId, bond: U128, currency: BondCurrency) -> Promise {
+        match currency {
+            BondCurrency::Near => Promise::new(receiver).transfer(near_sdk::NearToken::from_yoctonear(bond.0)),
+            BondCurrency::Usdc => self.transfer_usdc(receiver, bond, "Dispute bond payout"),
+        }
+    }
+
+    /// ft_transfer with a callback that parks the amount in claimable_refunds if it fails,
+    /// e.g. when the receiver has no storage registered with the token
+    fn transfer_usdc(&self, receiver: AccountId, amount: U128, memo: &str) -> Promise {
+        let usdc_contract = self.usdc_contract.clone().expect("USDC contract not configured");
+        ext_fungible_token::ext(usdc_contract)
+            .with_attached_deposit(near_sdk::NearToken::from_yoctonear(1))
+            .with_static_gas(near_sdk::Gas::from_tgas(USDC_FT_TRANSFER_TGAS))
+            .ft_transfer(receiver.clone(), amount, Some(memo.to_string()))
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(near_sdk::Gas::from_tgas(USDC_PAYOUT_CALLBACK_TGAS))
+                    .on_usdc_bond_paid(receiver, amount)
+            )
+    }
+
+    #[private]
+    pub fn on_usdc_bond_paid(&mut self, receiver: AccountId, amount: U128) -> bool {
+        use near_sdk::PromiseResult;
+
+        if let PromiseResult::Failed = env::promise_result(0) {
+            let parked = self.claimable_refunds.get(&receiver).unwrap_or(U128(0));
+            self.claimable_refunds.insert(&receiver, &U128(parked.0 + amount.0));
+            env::log_str(&format!("USDC payout of {} to {} failed, claimable with claim_refund", amount.0, receiver));
+            return false;
+        }
+
+        env::log_str(&format!("Paid {} USDC to {}", amount.0, receiver));
+        true
+    }
+
+    /// Retry a USDC bond payout that failed earlier; a failed retry parks the amount again
+    pub fn claim_refund(&mut self) -> Promise {
+        let account = env::predecessor_account_id();
+        let amount = self.claimable_refunds.remove(&account).expect("No refund to claim");
+
+        self.transfer_usdc(account, amount, "Dispute bond refund")
+    }
+
+    pub fn get_claimable_refund(&self, account_id: AccountId) -> U128 {
+        self.claimable_refunds.get(&account_id).unwrap_or(U128(0))
+    }
+
+    // Schelling point voting
+    /// Stake NEAR on the outcome of a disputed market, for markets oracles can't agree on. Voting
+    /// opens when the dispute is raised and closes schelling_vote_period later; one vote per account
+    #[payable]
+    pub fn submit_schelling_vote(&mut self, market_id: String, outcome: u8) {
+        assert!(outcome <= 2, "Invalid outcome value");
+        let dispute = self.disputes.get(&market_id).expect("Market is not disputed");
+        assert!(dispute.resolved_at.is_none(), "Dispute already resolved");
+        assert!(
+            env::block_timestamp() <= dispute.created_at + self.schelling_vote_period,
+            "Schelling voting has ended"
+        );
+
+        let stake = env::attached_deposit().as_yoctonear();
+        assert!(stake >= MIN_SCHELLING_STAKE, "Stake below the minimum Schelling stake");
+
+        let voter = env::predecessor_account_id();
+        let mut votes = self.schelling_votes.get(&market_id).unwrap_or_default();
+        assert!(votes.iter().all(|vote| vote.voter != voter), "Account already voted on this market");
+        assert!(votes.len() < MAX_SCHELLING_VOTES, "Schelling vote is full");
+
+        votes.push(SchellingVote {
+            voter: voter.clone(),
+            stake: U128(stake),
+            outcome,
+            created_at: env::block_timestamp(),
+        });
+        self.schelling_votes.insert(&market_id, &votes);
+
+        env::log_str(&format!(
+            "Schelling vote on market {}: {} staked {} yoctoNEAR on outcome {}",
+            market_id, voter, stake, outcome
+        ));
+    }
+
+    /// Settle a market by its Schelling vote once voting has ended. The plurality outcome wins if it
+    /// holds more than 60% of the stake, and its voters share the losing stakes pro rata; otherwise
+    /// the market is declared invalid and every stake refunded. The dispute bond is forfeited to the
+    /// treasury only when the vote upholds the original resolution
+    pub fn tabulate_schelling_vote(&mut self, market_id: String) -> Promise {
+        let mut dispute = self.disputes.get(&market_id).expect("Market is not disputed");
+        assert!(dispute.resolved_at.is_none(), "Dispute already resolved");
+        assert!(
+            env::block_timestamp() > dispute.created_at + self.schelling_vote_period,
+            "Schelling voting has not ended"
+        );
+        let votes = self.schelling_votes.get(&market_id).expect("No Schelling votes cast");
+
+        let consensus = Self::schelling_consensus(&votes);
+        for (voter, amount) in Self::schelling_payouts(&votes, consensus) {
+            Promise::new(voter).transfer(near_sdk::NearToken::from_yoctonear(amount));
+        }
+
+        let mut resolution = self.resolutions.get(&market_id).unwrap();
+        let winning_outcome = consensus.unwrap_or(2);
+        let outcome = if winning_outcome == resolution.winning_outcome {
+            DisputeOutcome::DisputeLoses
+        } else if winning_outcome == 2 {
+            DisputeOutcome::MarketInvalid
+        } else {
+            DisputeOutcome::DisputeWins
+        };
+
+        dispute.resolved_at = Some(env::block_timestamp());
+        dispute.dispute_outcome = Some(outcome.clone());
+        self.disputes.insert(&market_id, &dispute);
+
+        let bond = self.escrowed_bonds.remove(&market_id).unwrap_or(dispute.bond_amount);
+        let currency = self.bond_currencies.remove(&market_id).unwrap_or(BondCurrency::Near);
+        let bond_receiver = match outcome {
+            DisputeOutcome::DisputeLoses => self.treasury_account.clone(),
+            _ => dispute.disputer,
+        };
+        let bond_payout = self.pay_out_bond(bond_receiver, bond, currency);
+
+        resolution.winning_outcome = winning_outcome;
+        resolution.status = if winning_outcome == 2 { ResolutionStatus::Invalid } else { ResolutionStatus::Finalized };
+        resolution.finalized_at = Some(env::block_timestamp());
+        self.store_resolution(&resolution);
+
+        env::log_str(&format!(
+            "Schelling vote settled market {}: outcome {} from {} votes ({})",
+            market_id,
+            winning_outcome,
+            votes.len(),
+            if consensus.is_some() { "consensus" } else { "no consensus" }
+        ));
+
+        ext_verifier::ext(self.verifier_contract.clone())
+            .with_static_gas(near_sdk::Gas::from_tgas(5))
+            .get_market(market_id.clone())
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(near_sdk::Gas::from_tgas(30))
+                    .on_market_info_for_resolution(market_id.clone(), winning_outcome)
+            )
+            .and(self.notify_verifier(market_id, winning_outcome))
+            .and(bond_payout)
+    }
+
+    /// Plurality outcome, if it holds more than SCHELLING_SUPERMAJORITY_PERCENT of the total stake
+    fn schelling_consensus(votes: &[SchellingVote]) -> Option<u8> {
+        let mut stakes = [0u128; 3];
+        for vote in votes {
+            stakes[vote.outcome as usize] += vote.stake.0;
+        }
+        let total: u128 = stakes.iter().sum();
+        let (outcome, stake) = stakes.iter().enumerate().max_by_key(|(_, stake)| **stake)?;
+
+        (stake * 100 > total * SCHELLING_SUPERMAJORITY_PERCENT).then_some(outcome as u8)
+    }
+
+    /// What each voter receives. With consensus, its voters get their stake plus their pro rata
+    /// share of the other stakes and everyone else gets nothing; without, all stakes are refunded.
+    /// Rounding dust stays with the resolver
+    fn schelling_payouts(votes: &[SchellingVote], consensus: Option<u8>) -> Vec<(AccountId, u128)> {
+        let Some(outcome) = consensus else {
+            return votes.iter().map(|vote| (vote.voter.clone(), vote.stake.0)).collect();
+        };
+
+        let (winners, losers): (Vec<&SchellingVote>, Vec<&SchellingVote>) =
+            votes.iter().partition(|vote| vote.outcome == outcome);
+        let winning_stake: u128 = winners.iter().map(|vote| vote.stake.0).sum();
+        let losing_stake: u128 = losers.iter().map(|vote| vote.stake.0).sum();
+
+        winners
+            .into_iter()
+            .map(|vote| (vote.voter.clone(), vote.stake.0 + mul_div(losing_stake, vote.stake.0, winning_stake)))
+            .collect()
+    }
+
+    pub fn get_schelling_votes(&self, market_id: String) -> Vec<SchellingVote> {
+        self.schelling_votes.get(&market_id).unwrap_or_default()
+    }
+
     // Oracle Management
     pub fn add_oracle(&mut self, oracle: AccountId) {
         assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can add oracles");
@@ -361,15 +1286,107 @@ impl MarketResolver {
     pub fn remove_oracle(&mut self, oracle: AccountId) {
         assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can remove oracles");
         self.authorized_oracles.remove(&oracle);
+        // Removal is immediate; it also cancels any grace period the oracle had left
+        self.oracle_rotations.remove(&oracle);
         env::log_str(&format!("Oracle {} removed", oracle));
     }
 
     pub fn is_authorized_oracle(&self, oracle: AccountId) -> bool {
+        let now = env::block_timestamp();
+        if let Some(rotation) = self.oracle_rotations.get(&oracle) {
+            return now < self.rotation_ends_at(&rotation);
+        }
         self.authorized_oracles.contains(&oracle)
+            || self.oracle_rotations.values().any(|rotation| rotation.next == oracle && now >= rotation.effective_at)
+    }
+
+    /// Replace `previous` with `next` at `effective_at`; both may resolve until the grace period ends
+    pub fn rotate_oracle(&mut self, previous: AccountId, next: AccountId, effective_at: u64) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can rotate oracles");
+        assert!(effective_at >= env::block_timestamp(), "Rotation cannot take effect in the past");
+
+        self.settle_oracle_rotations();
+        assert!(self.authorized_oracles.contains(&previous), "Oracle {} is not authorized", previous);
+        assert!(!self.authorized_oracles.contains(&next), "Oracle {} is already authorized", next);
+        assert!(self.oracle_rotations.get(&previous).is_none(), "Oracle rotation already in progress");
+
+        let rotation = AuthorityRotation {
+            previous: previous.clone(),
+            next,
+            effective_at,
+            requested_by: env::predecessor_account_id(),
+            requested_at: env::block_timestamp(),
+        };
+        self.oracle_rotations.insert(&previous, &rotation);
+        self.oracle_rotation_history.push(rotation.clone());
+
+        env::log_str(&format!(
+            "Oracle rotation scheduled: {} -> {} at {}",
+            rotation.previous, rotation.next, effective_at
+        ));
+        self.emit_role_event("oracle_rotation_scheduled", Some(&rotation.next));
+    }
+
+    pub fn update_authority_grace_period(&mut self, grace_period: u64) {
+        self.assert_config_admin("Only owner or config admin can update authority grace period");
+        self.authority_grace_period = grace_period;
+        env::log_str(&format!("Authority grace period updated to {} ns", grace_period));
+    }
+
+    pub fn get_oracle_rotation_info(&self) -> OracleRotationInfo {
+        let now = env::block_timestamp();
+        OracleRotationInfo {
+            pending: self.oracle_rotations.values()
+                .filter(|rotation| now < self.rotation_ends_at(rotation))
+                .collect(),
+            grace_period: self.authority_grace_period,
+            history: self.oracle_rotation_history.clone(),
+        }
+    }
+
+    fn rotation_ends_at(&self, rotation: &AuthorityRotation) -> u64 {
+        rotation.effective_at.saturating_add(self.authority_grace_period)
+    }
+
+    // Swap in incoming oracles whose predecessor's grace period is over
+    fn settle_oracle_rotations(&mut self) {
+        let now = env::block_timestamp();
+        let completed: Vec<AuthorityRotation> = self.oracle_rotations.values()
+            .filter(|rotation| now >= self.rotation_ends_at(rotation))
+            .collect();
+
+        for rotation in completed {
+            self.oracle_rotations.remove(&rotation.previous);
+            self.authorized_oracles.remove(&rotation.previous);
+            self.authorized_oracles.insert(&rotation.next);
+            env::log_str(&format!("Oracle {} rotated to {}", rotation.previous, rotation.next));
+        }
+    }
+
+    fn assert_oracle(&mut self, message: &str) {
+        self.settle_oracle_rotations();
+        let caller = env::predecessor_account_id();
+        if caller == self.owner_id || self.authorized_oracles.contains(&caller) {
+            return;
+        }
+
+        if let Some(rotation) = self.oracle_rotations.values().find(|rotation| rotation.next == caller) {
+            assert!(
+                env::block_timestamp() >= rotation.effective_at,
+                "Oracle {} is not effective until {}", caller, rotation.effective_at
+            );
+            return;
+        }
+        if self.oracle_rotation_history.iter().any(|rotation| rotation.previous == caller) {
+            env::panic_str(&format!("Oracle {} was rotated out", caller));
+        }
+        env::panic_str(message);
     }
 
     // Payout Distribution
-    fn set_payout_numerators(&self, condition_id: String, winning_outcome: u8) -> Promise {
+    /// The CTF looks conditions up by question_id and only accepts payouts from their oracle,
+    /// which the verifier sets to this contract
+    fn set_payout_numerators(&self, question_id: String, winning_outcome: u8) -> Promise {
         let payout_numerators = match winning_outcome {
             0 => vec![U128(1_000_000_000_000_000_000_000_000), U128(0)], // NO wins
             1 => vec![U128(0), U128(1_000_000_000_000_000_000_000_000)], // YES wins
@@ -378,18 +1395,19 @@ impl MarketResolver {
         };
 
         env::log_str(&format!(
-            "Setting payout numerators for condition {}: [{}, {}]",
-            condition_id, payout_numerators[0].0, payout_numerators[1].0
+            "Setting payout numerators for question {}: [{}, {}]",
+            question_id, payout_numerators[0].0, payout_numerators[1].0
         ));
 
         ext_ctf::ext(self.ctf_contract.clone())
-            .report_payout_numerators(condition_id, payout_numerators)
+            .with_static_gas(near_sdk::Gas::from_tgas(20))
+            .report_payouts(question_id, payout_numerators)
     }
 
     // Handle invalid market (full refunds)
-    fn handle_invalid_market(&self, condition_id: String) -> Promise {
+    fn handle_invalid_market(&self, question_id: String) -> Promise {
         // Set equal payouts for both outcomes (50/50 split)
-        self.set_payout_numerators(condition_id, 2)
+        self.set_payout_numerators(question_id, 2)
     }
 
     // View Methods
@@ -405,33 +1423,179 @@ impl MarketResolver {
         self.authorized_oracles.to_vec()
     }
 
+    /// Bond currently held for the market's dispute, if any
+    pub fn get_escrowed_bond(&self, market_id: String) -> Option<U128> {
+        self.escrowed_bonds.get(&market_id)
+    }
+
+    pub fn get_treasury_account(&self) -> AccountId {
+        self.treasury_account.clone()
+    }
+
+    pub fn get_bond_currency(&self, market_id: String) -> Option<BondCurrency> {
+        self.escrowed_bonds.get(&market_id)
+            .map(|_| self.bond_currencies.get(&market_id).unwrap_or(BondCurrency::Near))
+    }
+
+    /// USDC token, USDC bond and the accepted bond currencies
+    pub fn get_usdc_bond_config(&self) -> (Option<AccountId>, U128, Vec<BondCurrency>) {
+        (self.usdc_contract.clone(), self.usdc_dispute_bond, self.accepted_bond_currencies.clone())
+    }
+
     pub fn get_dispute_config(&self) -> (u64, U128) {
         (self.dispute_period, self.dispute_bond)
     }
 
+    pub fn get_schelling_vote_period(&self) -> u64 {
+        self.schelling_vote_period
+    }
+
     pub fn get_pending_resolutions(&self) -> Vec<Resolution> {
-        let mut pending = Vec::new();
-        for (_, resolution) in self.resolutions.iter() {
-            if matches!(resolution.status, ResolutionStatus::Pending) {
-                pending.push(resolution);
-            }
-        }
-        pending
+        self.pending_set.iter().filter_map(|market_id| self.resolutions.get(&market_id)).collect()
     }
 
     pub fn get_disputed_resolutions(&self) -> Vec<Resolution> {
-        let mut disputed = Vec::new();
-        for (_, resolution) in self.resolutions.iter() {
-            if matches!(resolution.status, ResolutionStatus::Disputed) {
-                disputed.push(resolution);
-            }
-        }
-        disputed
+        self.disputed_set.iter().filter_map(|market_id| self.resolutions.get(&market_id)).collect()
+    }
+
+    /// Pending resolutions submitted strictly between the optional bounds (nanoseconds), paginated
+    pub fn get_pending_resolutions_paginated(
+        &self,
+        from_index: u64,
+        limit: u64,
+        submitted_after: Option<u64>,
+        submitted_before: Option<u64>,
+    ) -> Vec<Resolution> {
+        self.page_resolutions(&self.pending_set, from_index, limit, submitted_after, submitted_before)
+    }
+
+    pub fn get_disputed_resolutions_paginated(
+        &self,
+        from_index: u64,
+        limit: u64,
+        submitted_after: Option<u64>,
+        submitted_before: Option<u64>,
+    ) -> Vec<Resolution> {
+        self.page_resolutions(&self.disputed_set, from_index, limit, submitted_after, submitted_before)
+    }
+
+    /// Finalized and invalidated resolutions
+    pub fn get_finalized_resolutions_paginated(
+        &self,
+        from_index: u64,
+        limit: u64,
+        submitted_after: Option<u64>,
+        submitted_before: Option<u64>,
+    ) -> Vec<Resolution> {
+        self.page_resolutions(&self.finalized_set, from_index, limit, submitted_after, submitted_before)
+    }
+
+    /// Every resolution an oracle submitted, oldest first, for auditing its own record
+    pub fn get_resolutions_by_resolver(&self, oracle_account: AccountId, from_index: u64, limit: u64) -> Vec<Resolution> {
+        self.resolutions_by_resolver
+            .get(&oracle_account)
+            .unwrap_or_default()
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .filter_map(|market_id| self.resolutions.get(market_id))
+            .collect()
+    }
+
+    // from_index counts resolutions that pass the time filter
+    fn page_resolutions(
+        &self,
+        market_ids: &UnorderedSet<String>,
+        from_index: u64,
+        limit: u64,
+        submitted_after: Option<u64>,
+        submitted_before: Option<u64>,
+    ) -> Vec<Resolution> {
+        market_ids
+            .iter()
+            .filter_map(|market_id| self.resolutions.get(&market_id))
+            .filter(|resolution| resolution.submitted_at > submitted_after.unwrap_or(0))
+            .filter(|resolution| resolution.submitted_at < submitted_before.unwrap_or(u64::MAX))
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// Persist a resolution and move its market into the index set for its status
+    fn store_resolution(&mut self, resolution: &Resolution) {
+        let market_id = &resolution.market_id;
+        self.pending_set.remove(market_id);
+        self.disputed_set.remove(market_id);
+        self.finalized_set.remove(market_id);
+        match resolution.status {
+            ResolutionStatus::Pending => self.pending_set.insert(market_id),
+            ResolutionStatus::Disputed => self.disputed_set.insert(market_id),
+            ResolutionStatus::Finalized | ResolutionStatus::Invalid => self.finalized_set.insert(market_id),
+        };
+        self.resolutions.insert(market_id, resolution);
+    }
+
+    // Ownership & roles
+    /// Hand over ownership in two steps so a typo can't lock the resolver; the new owner must accept
+    pub fn propose_owner(&mut self, new_owner: AccountId) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can propose a new owner");
+        assert!(new_owner != self.owner_id, "Account is already the owner");
+
+        self.pending_owner = Some(new_owner.clone());
+        self.emit_role_event("owner_proposed", Some(&new_owner));
+    }
+
+    pub fn accept_ownership(&mut self) {
+        let caller = env::predecessor_account_id();
+        assert_eq!(self.pending_owner.as_ref(), Some(&caller), "Only the proposed owner can accept ownership");
+
+        self.owner_id = caller.clone();
+        self.pending_owner = None;
+        self.emit_role_event("ownership_transferred", Some(&caller));
+    }
+
+    /// Config admin can tune the dispute period and bond but has no other owner powers; None removes the role
+    pub fn set_config_admin(&mut self, config_admin: Option<AccountId>) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can set config admin");
+
+        self.config_admin = config_admin.clone();
+        self.emit_role_event("config_admin_set", config_admin.as_ref());
+    }
+
+    pub fn set_treasury_account(&mut self, treasury_account: AccountId) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can set treasury account");
+
+        self.treasury_account = treasury_account.clone();
+        self.emit_role_event("treasury_account_set", Some(&treasury_account));
+    }
+
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner.clone()
+    }
+
+    pub fn get_config_admin(&self) -> Option<AccountId> {
+        self.config_admin.clone()
+    }
+
+    fn assert_config_admin(&self, message: &str) {
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == self.owner_id || self.config_admin.as_ref() == Some(&caller),
+            "{}", message
+        );
+    }
+
+    fn emit_role_event(&self, event: &str, account: Option<&AccountId>) {
+        let account = account.map(|a| format!("\"{}\"", a)).unwrap_or_else(|| "null".to_string());
+        env::log_str(&format!(
+            "EVENT_JSON:{{\"standard\":\"market_resolver\",\"version\":\"1.0.0\",\"event\":\"{}\",\"data\":[{{\"by\":\"{}\",\"account\":{}}}]}}",
+            event, env::predecessor_account_id(), account
+        ));
     }
 
     // Configuration
     pub fn update_dispute_period(&mut self, new_period: u64) {
-        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can update dispute period");
+        self.assert_config_admin("Only owner or config admin can update dispute period");
         
         // Minimum 1 hour, maximum 7 days
         assert!(new_period >= 3_600_000_000_000, "Dispute period too short (min 1 hour)");
@@ -442,15 +1606,113 @@ impl MarketResolver {
     }
 
     pub fn update_dispute_bond(&mut self, new_bond: U128) {
-        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can update dispute bond");
+        self.assert_config_admin("Only owner or config admin can update dispute bond");
         
         // Minimum 1 NEAR
         assert!(new_bond.0 >= 1_000_000_000_000_000_000_000_000, "Dispute bond too low (min 1 NEAR)");
+        assert!(new_bond.0 <= self.max_dispute_bond.0, "Dispute bond above max dispute bond");
         
         self.dispute_bond = new_bond;
         env::log_str(&format!("Dispute bond updated to {} yoctoNEAR", new_bond.0));
     }
 
+    /// collateral_per_near is the collateral base units one NEAR is worth (5_000_000 for $5 with
+    /// 6-decimal USDC); market volume is converted at this rate before it is compared to the bond
+    pub fn update_dispute_bond_scaling(&mut self, percent_of_volume_bps: u16, max_bond: U128, collateral_per_near: U128) {
+        self.assert_config_admin("Only owner or config admin can update dispute bond scaling");
+        assert!(percent_of_volume_bps <= 10000, "Percent of volume cannot exceed 100%");
+        assert!(max_bond.0 >= self.dispute_bond.0, "Max dispute bond below flat dispute bond");
+        assert!(
+            percent_of_volume_bps == 0 || collateral_per_near.0 > 0,
+            "Volume scaling needs a collateral per NEAR rate"
+        );
+
+        self.dispute_bond_percent_of_volume = percent_of_volume_bps;
+        self.max_dispute_bond = max_bond;
+        self.collateral_per_near = collateral_per_near;
+        env::log_str(&format!(
+            "Dispute bond scaling updated: {} bps of volume at {} collateral units per NEAR, max {} yoctoNEAR",
+            percent_of_volume_bps, collateral_per_near.0, max_bond.0
+        ));
+    }
+
+    /// Configure USDC bonds and choose which currencies disputes may be raised with. Bonds already
+    /// in escrow are paid out in the currency they were posted in
+    pub fn update_bond_currencies(
+        &mut self,
+        usdc_contract: Option<AccountId>,
+        usdc_dispute_bond: U128,
+        accepted: Vec<BondCurrency>,
+    ) {
+        self.assert_config_admin("Only owner or config admin can update bond currencies");
+        assert!(!accepted.is_empty(), "At least one bond currency must be accepted");
+        if accepted.contains(&BondCurrency::Usdc) {
+            assert!(usdc_contract.is_some(), "USDC bonds need a USDC contract");
+            assert!(usdc_dispute_bond.0 > 0, "USDC dispute bond must be positive");
+        }
+        // Escrowed USDC bonds and parked refunds are paid out through the configured token
+        if !self.bond_currencies.is_empty() || !self.claimable_refunds.is_empty() {
+            assert!(usdc_contract == self.usdc_contract, "USDC bonds are still held by the resolver");
+        }
+
+        self.usdc_contract = usdc_contract;
+        self.usdc_dispute_bond = usdc_dispute_bond;
+        self.accepted_bond_currencies = accepted;
+        env::log_str(&format!(
+            "Bond currencies updated: {:?}, USDC bond {}",
+            self.accepted_bond_currencies, usdc_dispute_bond.0
+        ));
+    }
+
+    pub fn update_schelling_vote_period(&mut self, new_period: u64) {
+        self.assert_config_admin("Only owner or config admin can update Schelling vote period");
+
+        // Same bounds as the dispute period
+        assert!(new_period >= 3_600_000_000_000, "Schelling vote period too short (min 1 hour)");
+        assert!(new_period <= 604_800_000_000_000, "Schelling vote period too long (max 7 days)");
+
+        self.schelling_vote_period = new_period;
+        env::log_str(&format!("Schelling vote period updated to {} nanoseconds", new_period));
+    }
+
+    /// Applies to commitments made from now on; pending ones keep the windows they were made with
+    pub fn update_commit_reveal_windows(&mut self, commit_window: u64, reveal_window: u64) {
+        self.assert_config_admin("Only owner or config admin can update commit-reveal windows");
+        assert!(commit_window > 0 && reveal_window > 0, "Commit-reveal windows must be positive");
+        assert!(reveal_window <= 604_800_000_000_000, "Reveal window too long (max 7 days)");
+
+        self.commit_window = commit_window;
+        self.reveal_window = reveal_window;
+        env::log_str(&format!(
+            "Commit-reveal windows updated: commit {} ns, reveal {} ns",
+            commit_window, reveal_window
+        ));
+    }
+
+    pub fn update_max_resolution_delay(&mut self, new_delay: u64) {
+        self.assert_config_admin("Only owner or config admin can update max resolution delay");
+        assert!(new_delay > 0, "Max resolution delay must be positive");
+
+        self.max_resolution_delay = new_delay;
+        env::log_str(&format!("Max resolution delay updated to {} nanoseconds", new_delay));
+    }
+
+    /// Give one market its own max resolution delay; None goes back to the default
+    pub fn set_market_resolution_delay(&mut self, market_id: String, delay: Option<u64>) {
+        self.assert_config_admin("Only owner or config admin can set a market's resolution delay");
+
+        match delay {
+            Some(delay) => {
+                assert!(delay > 0, "Max resolution delay must be positive");
+                self.resolution_delay_overrides.insert(&market_id, &delay);
+            }
+            None => {
+                self.resolution_delay_overrides.remove(&market_id);
+            }
+        }
+        env::log_str(&format!("Max resolution delay for market {} set to {:?}", market_id, delay));
+    }
+
     // Callback to handle market info and set payout numerators
     #[private]
     pub fn on_market_info_for_resolution(
@@ -466,8 +1728,9 @@ impl MarketResolver {
                     market_id, market.condition_id
                 ));
                 
-                // Now we have the real condition_id from the market
-                self.set_payout_numerators(market.condition_id, winning_outcome)
+                // Same question_id the verifier used when preparing the condition
+                let question_id = format!("{}_{}", market.market_id, market.title);
+                self.set_payout_numerators(question_id, winning_outcome)
             }
             Ok(None) => {
                 env::log_str(&format!("Market {} not found during resolution", market_id));
@@ -490,7 +1753,7 @@ impl MarketResolver {
         resolution.winning_outcome = winning_outcome;
         resolution.status = ResolutionStatus::Finalized;
         resolution.finalized_at = Some(env::block_timestamp());
-        self.resolutions.insert(&market_id, &resolution);
+        self.store_resolution(&resolution);
 
         env::log_str(&format!("Emergency resolution for market {}: outcome {}", market_id, winning_outcome));
 
@@ -500,8 +1763,879 @@ impl MarketResolver {
             .get_market(market_id.clone())
             .then(
                 ext_self::ext(env::current_account_id())
-                    .with_static_gas(near_sdk::Gas::from_tgas(10))
-                    .on_market_info_for_resolution(market_id, winning_outcome)
+                    .with_static_gas(near_sdk::Gas::from_tgas(30))
+                    .on_market_info_for_resolution(market_id.clone(), winning_outcome)
             )
+            .and(self.notify_verifier(market_id, winning_outcome))
+    }
+}
+
+/// a * b / c for b <= c without overflowing a * b, by long division over the bits of a.
+/// c must stay below 2^126, far above any NEAR amount
+fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    let (mut quotient, mut remainder) = (0u128, 0u128);
+    for bit in (0..128).rev() {
+        remainder = remainder * 2 + if (a >> bit) & 1 == 1 { b } else { 0 };
+        quotient = quotient * 2 + remainder / c;
+        remainder %= c;
+    }
+    quotient
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{testing_env, VMContext};
+
+    const OBSERVATION_TIME: u64 = 2000000000000000000;
+    const THRESHOLD: u128 = 100_000_000_000; // $100k with 6 decimals
+    const ONE_USDC: u128 = 1_000_000;
+
+    fn get_context(predecessor: &str, block_timestamp: u64) -> VMContext {
+        VMContextBuilder::new()
+            .current_account_id("resolver.testnet".parse().unwrap())
+            .predecessor_account_id(predecessor.parse().unwrap())
+            .block_timestamp(block_timestamp)
+            .build()
+    }
+
+    fn setup_price_feed_market(comparison: PriceComparison) -> MarketResolver {
+        testing_env!(get_context("owner.testnet", 1000000000000000000));
+
+        let mut contract = MarketResolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            86_400_000_000_000,
+            U128(1_000_000_000_000_000_000_000_000),
+        );
+
+        contract.register_auto_resolution(
+            "btc_100k".to_string(),
+            ResolutionSource::PriceFeed {
+                feed_account: "btc-usd.feeds.testnet".parse().unwrap(),
+                comparison,
+                threshold: U128(THRESHOLD),
+                observation_time: OBSERVATION_TIME,
+            },
+        );
+
+        testing_env!(get_context("resolver.testnet", OBSERVATION_TIME + 1));
+        contract
+    }
+
+    fn observe(contract: &mut MarketResolver, price: u128) -> Option<u8> {
+        contract.on_price_feed_observation(
+            "btc_100k".to_string(),
+            Ok(Some(PricePoint { price: U128(price), timestamp: OBSERVATION_TIME })),
+        )
+    }
+
+    #[test]
+    fn test_price_feed_resolves_yes_above_threshold() {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+
+        assert_eq!(observe(&mut contract, THRESHOLD + 1), Some(1));
+
+        let resolution = contract.get_resolution("btc_100k".to_string()).unwrap();
+        assert_eq!(resolution.winning_outcome, 1);
+        assert!(matches!(resolution.status, ResolutionStatus::Pending)); // still disputable
+        assert_eq!(resolution.resolver, "resolver.testnet".parse::<AccountId>().unwrap());
+    }
+
+    #[test]
+    fn test_price_feed_resolves_no_below_threshold() {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+
+        assert_eq!(observe(&mut contract, THRESHOLD - 1), Some(0));
+        assert_eq!(contract.get_resolution("btc_100k".to_string()).unwrap().winning_outcome, 0);
+    }
+
+    #[test]
+    fn test_price_feed_exactly_equal_threshold() {
+        // Strict comparison: equal is not above
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+        assert_eq!(observe(&mut contract, THRESHOLD), Some(0));
+
+        // Inclusive comparison: equal counts
+        let mut contract = setup_price_feed_market(PriceComparison::AboveOrEqual);
+        assert_eq!(observe(&mut contract, THRESHOLD), Some(1));
+    }
+
+    #[test]
+    fn test_price_feed_failure_leaves_market_unresolved() {
+        let mut contract = setup_price_feed_market(PriceComparison::Below);
+
+        assert_eq!(contract.on_price_feed_observation("btc_100k".to_string(), Ok(None)), None);
+        assert!(contract.get_resolution("btc_100k".to_string()).is_none());
+
+        // Observation newer than observation_time is rejected
+        let late = PricePoint { price: U128(1), timestamp: OBSERVATION_TIME + 1 };
+        assert_eq!(contract.on_price_feed_observation("btc_100k".to_string(), Ok(Some(late))), None);
+        assert!(contract.get_resolution("btc_100k".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_graduated_dispute_bond() {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+        observe(&mut contract, THRESHOLD + 1);
+
+        testing_env!(get_context("owner.testnet", OBSERVATION_TIME + 2));
+        let one_near = 1_000_000_000_000_000_000_000_000u128;
+        // 1% of volume with NEAR at $5, max 5 NEAR
+        contract.update_dispute_bond_scaling(100, U128(5 * one_near), U128(5 * ONE_USDC));
+
+        // No volume seen yet: flat bond
+        assert_eq!(contract.calculate_dispute_bond("btc_100k".to_string()).0, one_near);
+
+        // $1,000 of volume: a $10 bond is 2 NEAR
+        contract.market_volumes.insert(&"btc_100k".to_string(), &U128(1_000 * ONE_USDC));
+        assert_eq!(contract.calculate_dispute_bond("btc_100k".to_string()).0, 2 * one_near);
+
+        contract.market_volumes.insert(&"btc_100k".to_string(), &U128(10_000 * ONE_USDC));
+        assert_eq!(contract.calculate_dispute_bond("btc_100k".to_string()).0, 5 * one_near); // capped
+        contract.market_volumes.insert(&"btc_100k".to_string(), &U128(u128::MAX / 10_000));
+        assert_eq!(contract.calculate_dispute_bond("btc_100k".to_string()).0, 5 * one_near);
+
+        // Bond below the scaled requirement is refunded, not recorded
+        testing_env!(get_context("resolver.testnet", OBSERVATION_TIME + 3));
+        let stats = MarketStats { market_id: "btc_100k".to_string(), total_volume: U128(1_000 * ONE_USDC), intent_count: 3 };
+        let rejected = contract.on_market_stats_for_dispute(
+            "btc_100k".to_string(),
+            "alice.testnet".parse().unwrap(),
+            "Wrong price".to_string(),
+            "{}".to_string(),
+            U128(one_near),
+            Ok(stats.clone()),
+        );
+        assert!(rejected.is_none());
+        assert!(contract.get_dispute("btc_100k".to_string()).is_none());
+
+        let accepted = contract.on_market_stats_for_dispute(
+            "btc_100k".to_string(),
+            "alice.testnet".parse().unwrap(),
+            "Wrong price".to_string(),
+            "{}".to_string(),
+            U128(2 * one_near),
+            Ok(stats),
+        );
+        assert!(accepted.is_some());
+        assert!(matches!(contract.get_resolution("btc_100k".to_string()).unwrap().status, ResolutionStatus::Disputed));
+    }
+
+    #[test]
+    #[should_panic(expected = "Observation time not reached")]
+    fn test_trigger_before_observation_time() {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+
+        testing_env!(get_context("anyone.testnet", OBSERVATION_TIME - 1));
+        contract.trigger_auto_resolution("btc_100k".to_string());
+    }
+
+    #[test]
+    fn test_failed_verifier_notification_is_kept_for_retry() {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+
+        assert!(!contract.on_verifier_notified(
+            "btc_100k".to_string(),
+            1,
+            Err(near_sdk::PromiseError::Failed),
+        ));
+        assert_eq!(contract.get_unsynced_resolutions(), vec![("btc_100k".to_string(), 1)]);
+
+        assert!(contract.on_verifier_notified("btc_100k".to_string(), 1, Ok(())));
+        assert!(contract.get_unsynced_resolutions().is_empty());
+    }
+
+    fn raise_dispute(contract: &mut MarketResolver, bond: u128) {
+        let mut context = get_context("alice.testnet", OBSERVATION_TIME + 2);
+        context.attached_deposit = near_sdk::NearToken::from_yoctonear(bond);
+        testing_env!(context);
+        let _ = contract.dispute_resolution("btc_100k".to_string(), "Wrong price".to_string(), "{}".to_string());
+    }
+
+    #[test]
+    fn test_dispute_bond_is_escrowed_until_resolved() {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+        observe(&mut contract, THRESHOLD + 1);
+        let bond = 2_000_000_000_000_000_000_000_000u128;
+
+        raise_dispute(&mut contract, bond);
+        assert_eq!(contract.get_escrowed_bond("btc_100k".to_string()), Some(U128(bond)));
+
+        // A second dispute can't race the first one's stats callback
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            raise_dispute(&mut contract, bond);
+        }));
+        assert!(result.is_err());
+
+        testing_env!(get_context("resolver.testnet", OBSERVATION_TIME + 3));
+        let stats = MarketStats { market_id: "btc_100k".to_string(), total_volume: U128(0), intent_count: 1 };
+        contract.on_market_stats_for_dispute(
+            "btc_100k".to_string(),
+            "alice.testnet".parse().unwrap(),
+            "Wrong price".to_string(),
+            "{}".to_string(),
+            U128(bond),
+            Ok(stats),
+        );
+        assert_eq!(contract.get_escrowed_bond("btc_100k".to_string()), Some(U128(bond)));
+
+        testing_env!(get_context("owner.testnet", OBSERVATION_TIME + 4));
+        let _ = contract.resolve_dispute("btc_100k".to_string(), DisputeOutcome::DisputeWins, "Feed misread".to_string());
+        assert!(contract.get_escrowed_bond("btc_100k".to_string()).is_none());
+        assert!(matches!(contract.get_resolution("btc_100k".to_string()).unwrap().status, ResolutionStatus::Invalid));
+    }
+
+    #[test]
+    fn test_rejected_dispute_releases_escrow() {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+        observe(&mut contract, THRESHOLD + 1);
+        let one_near = 1_000_000_000_000_000_000_000_000u128;
+
+        testing_env!(get_context("owner.testnet", OBSERVATION_TIME + 2));
+        contract.update_dispute_bond_scaling(100, U128(5 * one_near), U128(5 * ONE_USDC));
+        raise_dispute(&mut contract, one_near);
+
+        // Volume now requires a 2 NEAR bond
+        testing_env!(get_context("resolver.testnet", OBSERVATION_TIME + 3));
+        let stats = MarketStats { market_id: "btc_100k".to_string(), total_volume: U128(1_000 * ONE_USDC), intent_count: 3 };
+        let rejected = contract.on_market_stats_for_dispute(
+            "btc_100k".to_string(),
+            "alice.testnet".parse().unwrap(),
+            "Wrong price".to_string(),
+            "{}".to_string(),
+            U128(one_near),
+            Ok(stats),
+        );
+        assert!(rejected.is_none());
+        assert!(contract.get_escrowed_bond("btc_100k".to_string()).is_none());
+
+        // The market can be disputed again with a large enough bond
+        raise_dispute(&mut contract, 2 * one_near);
+        assert_eq!(contract.get_escrowed_bond("btc_100k".to_string()), Some(U128(2 * one_near)));
+    }
+
+    #[test]
+    fn test_lost_dispute_bond_goes_to_treasury() {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+        observe(&mut contract, THRESHOLD + 1);
+        let bond = 1_000_000_000_000_000_000_000_000u128;
+
+        testing_env!(get_context("owner.testnet", OBSERVATION_TIME + 2));
+        assert_eq!(contract.get_treasury_account(), "owner.testnet".parse::<AccountId>().unwrap());
+        contract.set_treasury_account("treasury.testnet".parse().unwrap());
+
+        raise_dispute(&mut contract, bond);
+        testing_env!(get_context("resolver.testnet", OBSERVATION_TIME + 3));
+        let stats = MarketStats { market_id: "btc_100k".to_string(), total_volume: U128(0), intent_count: 1 };
+        contract.on_market_stats_for_dispute(
+            "btc_100k".to_string(),
+            "alice.testnet".parse().unwrap(),
+            "Wrong price".to_string(),
+            "{}".to_string(),
+            U128(bond),
+            Ok(stats),
+        );
+
+        testing_env!(get_context("owner.testnet", OBSERVATION_TIME + 4));
+        let _ = contract.resolve_dispute("btc_100k".to_string(), DisputeOutcome::DisputeLoses, "Feed was right".to_string());
+        assert!(contract.get_escrowed_bond("btc_100k".to_string()).is_none());
+        assert_eq!(contract.get_treasury_account(), "treasury.testnet".parse::<AccountId>().unwrap());
+        assert!(matches!(contract.get_resolution("btc_100k".to_string()).unwrap().status, ResolutionStatus::Pending));
+    }
+
+    #[test]
+    fn test_oracle_rotation_grace_period() {
+        testing_env!(get_context("owner.testnet", 1000000000000000000));
+
+        let mut contract = MarketResolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            86_400_000_000_000,
+            U128(1_000_000_000_000_000_000_000_000),
+        );
+        contract.add_oracle("oracle.testnet".parse().unwrap());
+
+        let effective_at = 1000000000000000000 + 1_000_000_000;
+        contract.rotate_oracle("oracle.testnet".parse().unwrap(), "oracle-v2.testnet".parse().unwrap(), effective_at);
+
+        // Both oracles resolve during the grace window
+        testing_env!(get_context("oracle.testnet", effective_at));
+        contract.submit_resolution("market_1".to_string(), 1, "old oracle".to_string());
+        testing_env!(get_context("oracle-v2.testnet", effective_at));
+        contract.submit_resolution("market_2".to_string(), 0, "new oracle".to_string());
+
+        let after_grace = effective_at + DEFAULT_AUTHORITY_GRACE_PERIOD;
+        testing_env!(get_context("oracle-v2.testnet", after_grace));
+        contract.submit_resolution("market_3".to_string(), 1, "new oracle".to_string());
+        assert!(contract.is_authorized_oracle("oracle-v2.testnet".parse().unwrap()));
+        assert!(!contract.is_authorized_oracle("oracle.testnet".parse().unwrap()));
+        assert!(contract.get_oracle_rotation_info().pending.is_empty());
+        assert_eq!(contract.get_oracle_rotation_info().history.len(), 1);
+
+        testing_env!(get_context("oracle.testnet", after_grace));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.submit_resolution("market_4".to_string(), 1, "old oracle".to_string());
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_migrate_v1_state() {
+        testing_env!(get_context("resolver.testnet", 1000000000000000000));
+
+        // State as the first deployed resolver wrote it
+        let mut old = migration::MarketResolverV1 {
+            owner_id: "owner.testnet".parse().unwrap(),
+            verifier_contract: "verifier.testnet".parse().unwrap(),
+            ctf_contract: "ctf.testnet".parse().unwrap(),
+            authorized_oracles: UnorderedSet::new(b"o"),
+            resolutions: UnorderedMap::new(b"r"),
+            disputes: UnorderedMap::new(b"d"),
+            dispute_period: 86_400_000_000_000,
+            dispute_bond: U128(1_000_000_000_000_000_000_000_000),
+        };
+        old.authorized_oracles.insert(&"oracle.testnet".parse().unwrap());
+        old.resolutions.insert(&"market_1".to_string(), &Resolution {
+            market_id: "market_1".to_string(),
+            condition_id: String::new(),
+            resolver: "oracle.testnet".parse().unwrap(),
+            winning_outcome: 1,
+            resolution_data: "{}".to_string(),
+            submitted_at: 900000000000000000,
+            finalized_at: None,
+            status: ResolutionStatus::Disputed,
+        });
+        old.disputes.insert(&"market_1".to_string(), &Dispute {
+            market_id: "market_1".to_string(),
+            disputer: "disputer.testnet".parse().unwrap(),
+            reason: "wrong outcome".to_string(),
+            evidence: "{}".to_string(),
+            bond_amount: U128(2_000_000_000_000_000_000_000_000),
+            created_at: 950000000000000000,
+            resolved_at: None,
+            dispute_outcome: None,
+        });
+        env::state_write(&old);
+
+        let mut contract = MarketResolver::migrate();
+        assert_eq!(contract.get_state_version(), StateVersion::V2);
+        assert!(contract.is_authorized_oracle("oracle.testnet".parse().unwrap()));
+        assert_eq!(contract.get_dispute_config(), (86_400_000_000_000, U128(1_000_000_000_000_000_000_000_000)));
+        assert_eq!(contract.get_treasury_account(), "owner.testnet".parse::<AccountId>().unwrap());
+        // Status and resolver indexes are rebuilt from the stored resolutions
+        assert_eq!(contract.get_disputed_resolutions_paginated(0, 10, None, None).len(), 1);
+        assert!(contract.get_pending_resolutions().is_empty());
+        assert_eq!(contract.get_resolutions_by_resolver("oracle.testnet".parse().unwrap(), 0, 10).len(), 1);
+        assert_eq!(contract.get_usdc_bond_config(), (None, U128(0), vec![BondCurrency::Near]));
+        assert_eq!(contract.get_commit_reveal_config(), (DEFAULT_COMMIT_WINDOW, DEFAULT_REVEAL_WINDOW));
+
+        // Already-current state passes through
+        env::state_write(&contract);
+        contract = MarketResolver::migrate();
+        assert_eq!(contract.get_state_version(), StateVersion::V2);
+        assert_eq!(contract.get_dispute("market_1".to_string()).unwrap().bond_amount, U128(2_000_000_000_000_000_000_000_000));
+
+        // The V1 dispute was never escrowed; resolving it pays out the bond it recorded
+        assert_eq!(contract.get_escrowed_bond("market_1".to_string()), None);
+        testing_env!(get_context("owner.testnet", 1000000000000000000));
+        contract.resolve_dispute("market_1".to_string(), DisputeOutcome::DisputeLoses, "resolution stands".to_string());
+        assert!(contract.get_dispute("market_1".to_string()).unwrap().resolved_at.is_some());
+        assert_eq!(contract.get_pending_resolutions().len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "No upgrade proposed")]
+    fn test_upgrade_requires_proposal() {
+        testing_env!(get_context("owner.testnet", 1000000000000000000));
+        let mut contract = MarketResolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            86_400_000_000_000,
+            U128(1_000_000_000_000_000_000_000_000),
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("owner.testnet".parse().unwrap())
+            .input(b"resolver wasm".to_vec())
+            .build());
+        contract.upgrade();
+    }
+
+    #[test]
+    fn test_resolution_pagination_and_status_indexes() {
+        const T: u64 = 1000000000000000000;
+        testing_env!(get_context("owner.testnet", T));
+        let mut contract = MarketResolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            86_400_000_000_000,
+            U128(1_000_000_000_000_000_000_000_000),
+        );
+        contract.add_oracle("oracle-a.testnet".parse().unwrap());
+        contract.add_oracle("oracle-b.testnet".parse().unwrap());
+
+        for (i, oracle) in ["oracle-a.testnet", "oracle-a.testnet", "oracle-a.testnet", "oracle-b.testnet"].iter().enumerate() {
+            testing_env!(get_context(oracle, T + i as u64 + 1));
+            contract.submit_resolution(format!("m{}", i + 1), 1, "{}".to_string());
+        }
+
+        let market_ids = |resolutions: Vec<Resolution>| -> Vec<String> {
+            let mut ids: Vec<String> = resolutions.into_iter().map(|r| r.market_id).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(contract.get_pending_resolutions_paginated(0, 10, None, None).len(), 4);
+        assert_eq!(
+            market_ids(contract.get_pending_resolutions_paginated(0, 10, Some(T + 1), Some(T + 4))),
+            vec!["m2", "m3"]
+        );
+        assert_eq!(contract.get_pending_resolutions_paginated(1, 10, Some(T + 1), Some(T + 4)).len(), 1);
+
+        // Disputing m2 moves it from the pending index to the disputed one
+        testing_env!(get_context("resolver.testnet", T + 5));
+        let stats = MarketStats { market_id: "m2".to_string(), total_volume: U128(0), intent_count: 1 };
+        contract.on_market_stats_for_dispute(
+            "m2".to_string(),
+            "alice.testnet".parse().unwrap(),
+            "Wrong outcome".to_string(),
+            "{}".to_string(),
+            U128(1_000_000_000_000_000_000_000_000),
+            Ok(stats),
+        );
+        assert_eq!(market_ids(contract.get_disputed_resolutions_paginated(0, 10, None, None)), vec!["m2"]);
+        assert_eq!(market_ids(contract.get_pending_resolutions()), vec!["m1", "m3", "m4"]);
+
+        testing_env!(get_context("owner.testnet", T + 6));
+        let _ = contract.emergency_resolve("m3".to_string(), 0);
+        assert_eq!(market_ids(contract.get_finalized_resolutions_paginated(0, 10, None, None)), vec!["m3"]);
+        assert_eq!(contract.get_pending_resolutions_paginated(0, 10, None, None).len(), 2);
+
+        // Submission order per oracle, whatever the current status
+        let audited = contract.get_resolutions_by_resolver("oracle-a.testnet".parse().unwrap(), 1, 5);
+        assert_eq!(audited.iter().map(|r| r.market_id.as_str()).collect::<Vec<_>>(), vec!["m2", "m3"]);
+        assert!(contract.get_resolutions_by_resolver("oracle-c.testnet".parse().unwrap(), 0, 5).is_empty());
+    }
+
+    const USDC_BOND: u128 = 50_000_000; // $50 with 6 decimals
+
+    fn enable_usdc_bonds(contract: &mut MarketResolver, accepted: Vec<BondCurrency>) {
+        testing_env!(get_context("owner.testnet", OBSERVATION_TIME + 2));
+        contract.update_bond_currencies(Some("usdc.testnet".parse().unwrap()), U128(USDC_BOND), accepted);
+    }
+
+    fn dispute_msg() -> String {
+        r#"{"action":"dispute","market_id":"btc_100k","reason":"Wrong price","evidence":"{}"}"#.to_string()
+    }
+
+    #[test]
+    fn test_usdc_dispute_bond_refunds_excess() {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+        observe(&mut contract, THRESHOLD + 1);
+        enable_usdc_bonds(&mut contract, vec![BondCurrency::Usdc]);
+
+        // Only the configured token can post bonds
+        testing_env!(get_context("fake-usdc.testnet", OBSERVATION_TIME + 3));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.ft_on_transfer("alice.testnet".parse().unwrap(), U128(USDC_BOND), dispute_msg());
+        }));
+        assert!(result.is_err());
+
+        testing_env!(get_context("usdc.testnet", OBSERVATION_TIME + 3));
+        let unused = contract.ft_on_transfer("alice.testnet".parse().unwrap(), U128(USDC_BOND + 7), dispute_msg());
+        assert!(matches!(unused, PromiseOrValue::Value(U128(7))));
+
+        assert_eq!(contract.get_escrowed_bond("btc_100k".to_string()), Some(U128(USDC_BOND)));
+        assert_eq!(contract.get_bond_currency("btc_100k".to_string()), Some(BondCurrency::Usdc));
+        let dispute = contract.get_dispute("btc_100k".to_string()).unwrap();
+        assert_eq!(dispute.disputer, "alice.testnet".parse::<AccountId>().unwrap());
+        assert_eq!(dispute.bond_amount, U128(USDC_BOND));
+        assert!(matches!(contract.get_resolution("btc_100k".to_string()).unwrap().status, ResolutionStatus::Disputed));
+
+        // NEAR bonds are switched off
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            raise_dispute(&mut contract, 2_000_000_000_000_000_000_000_000);
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_usdc_dispute_bond_below_minimum_rejected() {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+        observe(&mut contract, THRESHOLD + 1);
+        enable_usdc_bonds(&mut contract, vec![BondCurrency::Near, BondCurrency::Usdc]);
+
+        testing_env!(get_context("usdc.testnet", OBSERVATION_TIME + 3));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.ft_on_transfer("alice.testnet".parse().unwrap(), U128(USDC_BOND - 1), dispute_msg());
+        }));
+        assert!(result.is_err());
+        assert!(contract.get_dispute("btc_100k".to_string()).is_none());
+
+        // The NEAR path still works alongside USDC
+        raise_dispute(&mut contract, 2_000_000_000_000_000_000_000_000);
+        assert_eq!(contract.get_bond_currency("btc_100k".to_string()), Some(BondCurrency::Near));
+    }
+
+    #[test]
+    fn test_failed_usdc_payout_is_claimable() {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+        observe(&mut contract, THRESHOLD + 1);
+        enable_usdc_bonds(&mut contract, vec![BondCurrency::Usdc]);
+
+        testing_env!(get_context("usdc.testnet", OBSERVATION_TIME + 3));
+        let _ = contract.ft_on_transfer("alice.testnet".parse().unwrap(), U128(USDC_BOND), dispute_msg());
+
+        testing_env!(get_context("owner.testnet", OBSERVATION_TIME + 4));
+        let _ = contract.resolve_dispute("btc_100k".to_string(), DisputeOutcome::MarketInvalid, "Feed down".to_string());
+        assert!(contract.get_escrowed_bond("btc_100k".to_string()).is_none());
+        assert!(contract.get_bond_currency("btc_100k".to_string()).is_none());
+
+        // Alice isn't registered with the token, so the ft_transfer fails and the bond is parked
+        testing_env!(
+            get_context("resolver.testnet", OBSERVATION_TIME + 5),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![near_sdk::PromiseResult::Failed]
+        );
+        assert!(!contract.on_usdc_bond_paid("alice.testnet".parse().unwrap(), U128(USDC_BOND)));
+        assert_eq!(contract.get_claimable_refund("alice.testnet".parse().unwrap()), U128(USDC_BOND));
+
+        // The token can't be swapped out while a refund is parked
+        testing_env!(get_context("owner.testnet", OBSERVATION_TIME + 6));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.update_bond_currencies(None, U128(0), vec![BondCurrency::Near]);
+        }));
+        assert!(result.is_err());
+
+        testing_env!(get_context("alice.testnet", OBSERVATION_TIME + 7));
+        let _ = contract.claim_refund();
+        assert_eq!(contract.get_claimable_refund("alice.testnet".parse().unwrap()), U128(0));
+    }
+
+    // btc_100k resolved YES by the feed, then disputed by alice with a 1 NEAR bond
+    fn disputed_market() -> MarketResolver {
+        let mut contract = setup_price_feed_market(PriceComparison::Above);
+        observe(&mut contract, THRESHOLD + 1);
+        raise_dispute(&mut contract, ONE_NEAR);
+
+        testing_env!(get_context("resolver.testnet", OBSERVATION_TIME + 3));
+        let stats = MarketStats { market_id: "btc_100k".to_string(), total_volume: U128(0), intent_count: 1 };
+        contract.on_market_stats_for_dispute(
+            "btc_100k".to_string(),
+            "alice.testnet".parse().unwrap(),
+            "Wrong price".to_string(),
+            "{}".to_string(),
+            U128(ONE_NEAR),
+            Ok(stats),
+        );
+        contract
+    }
+
+    fn schelling_vote(contract: &mut MarketResolver, voter: &str, outcome: u8, stake: u128) {
+        let mut context = get_context(voter, OBSERVATION_TIME + 10);
+        context.attached_deposit = near_sdk::NearToken::from_yoctonear(stake);
+        testing_env!(context);
+        contract.submit_schelling_vote("btc_100k".to_string(), outcome);
+    }
+
+    fn payouts_of(contract: &MarketResolver, consensus: Option<u8>) -> Vec<(String, u128)> {
+        let votes = contract.get_schelling_votes("btc_100k".to_string());
+        MarketResolver::schelling_payouts(&votes, consensus)
+            .into_iter()
+            .map(|(voter, amount)| (voter.to_string(), amount))
+            .collect()
+    }
+
+    #[test]
+    fn test_schelling_supermajority_overturns_resolution() {
+        let mut contract = disputed_market();
+        schelling_vote(&mut contract, "bob.testnet", 0, 3 * ONE_NEAR);
+        schelling_vote(&mut contract, "carol.testnet", 0, ONE_NEAR);
+        schelling_vote(&mut contract, "dave.testnet", 1, ONE_NEAR);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            schelling_vote(&mut contract, "bob.testnet", 1, ONE_NEAR);
+        }));
+        assert!(result.is_err());
+
+        // 80% of the stake is on NO: its voters split dave's stake 3:1
+        let votes = contract.get_schelling_votes("btc_100k".to_string());
+        assert_eq!(MarketResolver::schelling_consensus(&votes), Some(0));
+        assert_eq!(payouts_of(&contract, Some(0)), vec![
+            ("bob.testnet".to_string(), 3 * ONE_NEAR + 3 * ONE_NEAR / 4),
+            ("carol.testnet".to_string(), ONE_NEAR + ONE_NEAR / 4),
+        ]);
+
+        let vote_ends = OBSERVATION_TIME + 3 + contract.get_schelling_vote_period();
+        testing_env!(get_context("anyone.testnet", vote_ends + 1));
+        let _ = contract.tabulate_schelling_vote("btc_100k".to_string());
+
+        let resolution = contract.get_resolution("btc_100k".to_string()).unwrap();
+        assert_eq!(resolution.winning_outcome, 0);
+        assert!(matches!(resolution.status, ResolutionStatus::Finalized));
+        assert!(matches!(
+            contract.get_dispute("btc_100k".to_string()).unwrap().dispute_outcome,
+            Some(DisputeOutcome::DisputeWins)
+        ));
+        assert!(contract.get_escrowed_bond("btc_100k".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_schelling_split_vote_declares_invalid() {
+        let mut contract = disputed_market();
+        schelling_vote(&mut contract, "bob.testnet", 1, 11 * ONE_NEAR);
+        schelling_vote(&mut contract, "carol.testnet", 0, 9 * ONE_NEAR);
+
+        // 55% is not enough: everyone gets their stake back
+        let votes = contract.get_schelling_votes("btc_100k".to_string());
+        assert_eq!(MarketResolver::schelling_consensus(&votes), None);
+        assert_eq!(payouts_of(&contract, None), vec![
+            ("bob.testnet".to_string(), 11 * ONE_NEAR),
+            ("carol.testnet".to_string(), 9 * ONE_NEAR),
+        ]);
+
+        // Too early to tabulate, and the owner can't settle the dispute around the vote
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = contract.tabulate_schelling_vote("btc_100k".to_string());
+        }));
+        assert!(result.is_err());
+        testing_env!(get_context("owner.testnet", OBSERVATION_TIME + 20));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = contract.resolve_dispute("btc_100k".to_string(), DisputeOutcome::DisputeLoses, "Feed was right".to_string());
+        }));
+        assert!(result.is_err());
+
+        let vote_ends = OBSERVATION_TIME + 3 + contract.get_schelling_vote_period();
+        testing_env!(get_context("anyone.testnet", vote_ends + 1));
+        let _ = contract.tabulate_schelling_vote("btc_100k".to_string());
+
+        let resolution = contract.get_resolution("btc_100k".to_string()).unwrap();
+        assert_eq!(resolution.winning_outcome, 2);
+        assert!(matches!(resolution.status, ResolutionStatus::Invalid));
+        assert!(matches!(
+            contract.get_dispute("btc_100k".to_string()).unwrap().dispute_outcome,
+            Some(DisputeOutcome::MarketInvalid)
+        ));
+
+        // Voting is closed once the period is over
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut context = get_context("dave.testnet", vote_ends + 2);
+            context.attached_deposit = near_sdk::NearToken::from_yoctonear(ONE_NEAR);
+            testing_env!(context);
+            contract.submit_schelling_vote("btc_100k".to_string(), 1);
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_mul_div_avoids_overflow() {
+        let stake = 400_000_000 * ONE_NEAR;
+        assert_eq!(mul_div(stake, stake, 2 * stake), stake / 2);
+        assert_eq!(mul_div(7, 2, 3), 4);
+    }
+
+    const COMMIT_TIME: u64 = 1000000000000000000;
+
+    // commit_market opted in by the verifier, with a YES commitment from oracle.testnet
+    fn committed_market() -> MarketResolver {
+        testing_env!(get_context("owner.testnet", COMMIT_TIME));
+        let mut contract = MarketResolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            86_400_000_000_000,
+            U128(ONE_NEAR),
+        );
+        contract.add_oracle("oracle.testnet".parse().unwrap());
+
+        testing_env!(get_context("verifier.testnet", COMMIT_TIME));
+        contract.set_commit_reveal("commit_market".to_string(), true);
+
+        testing_env!(get_context("oracle.testnet", COMMIT_TIME));
+        let oracle: AccountId = "oracle.testnet".parse().unwrap();
+        let commitment = MarketResolver::resolution_commitment(1, "pepper", &oracle);
+        contract.commit_resolution("commit_market".to_string(), commitment);
+        contract
+    }
+
+    #[test]
+    fn test_commit_reveal_files_resolution_after_commit_window() {
+        let mut contract = committed_market();
+        let commitment = contract.get_resolution_commitment("commit_market".to_string()).unwrap();
+        assert_eq!(commitment.reveal_from, COMMIT_TIME + DEFAULT_COMMIT_WINDOW);
+        assert_eq!(commitment.reveal_by, COMMIT_TIME + DEFAULT_COMMIT_WINDOW + DEFAULT_REVEAL_WINDOW);
+
+        // The outcome can't be filed in the open, nor revealed while commits are still open
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.submit_resolution("commit_market".to_string(), 1, "{}".to_string());
+        }));
+        assert!(result.is_err());
+        testing_env!(get_context("oracle.testnet", commitment.reveal_from));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.reveal_resolution("commit_market".to_string(), 1, "pepper".to_string());
+        }));
+        assert!(result.is_err());
+        assert!(contract.get_resolution("commit_market".to_string()).is_none());
+
+        // The dispute period runs from the reveal
+        let revealed_at = commitment.reveal_from + 1;
+        testing_env!(get_context("oracle.testnet", revealed_at));
+        contract.reveal_resolution("commit_market".to_string(), 1, "pepper".to_string());
+        let resolution = contract.get_resolution("commit_market".to_string()).unwrap();
+        assert_eq!(resolution.winning_outcome, 1);
+        assert_eq!(resolution.submitted_at, revealed_at);
+        assert!(matches!(resolution.status, ResolutionStatus::Pending));
+        assert!(contract.get_resolution_commitment("commit_market".to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Reveal does not match the commitment")]
+    fn test_commit_reveal_rejects_mismatched_reveal() {
+        let mut contract = committed_market();
+
+        testing_env!(get_context("oracle.testnet", COMMIT_TIME + DEFAULT_COMMIT_WINDOW + 1));
+        contract.reveal_resolution("commit_market".to_string(), 0, "pepper".to_string());
+    }
+
+    #[test]
+    fn test_unrevealed_commitment_expires_and_can_be_voided() {
+        let mut contract = committed_market();
+        let reveal_by = COMMIT_TIME + DEFAULT_COMMIT_WINDOW + DEFAULT_REVEAL_WINDOW;
+
+        // The owner has to wait out the reveal window
+        testing_env!(get_context("owner.testnet", reveal_by));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.void_commitment("commit_market".to_string());
+        }));
+        assert!(result.is_err());
+
+        testing_env!(get_context("oracle.testnet", reveal_by + 1));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.reveal_resolution("commit_market".to_string(), 1, "pepper".to_string());
+        }));
+        assert!(result.is_err());
+
+        testing_env!(get_context("owner.testnet", reveal_by + 1));
+        contract.void_commitment("commit_market".to_string());
+        assert!(contract.get_resolution_commitment("commit_market".to_string()).is_none());
+        assert!(contract.get_resolution("commit_market".to_string()).is_none());
+
+        // The market stays commit-reveal and takes a fresh commitment
+        assert!(contract.is_commit_reveal_market("commit_market".to_string()));
+        testing_env!(get_context("oracle.testnet", reveal_by + 2));
+        contract.commit_resolution("commit_market".to_string(), [7u8; 32]);
+        assert_eq!(
+            contract.get_resolution_commitment("commit_market".to_string()).unwrap().committed_at,
+            reveal_by + 2
+        );
+    }
+
+    #[test]
+    fn test_verifier_opens_resolution_window_once() {
+        testing_env!(get_context("owner.testnet", 1000000000000000000));
+        let mut contract = MarketResolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            86_400_000_000_000,
+            U128(ONE_NEAR),
+        );
+        assert_eq!(contract.get_resolution_window_opened_at("market_1".to_string()), None);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_resolution_window_open("market_1".to_string());
+        }));
+        assert!(result.is_err());
+
+        testing_env!(get_context("verifier.testnet", 1000000000000000001));
+        assert_eq!(contract.set_resolution_window_open("market_1".to_string()), 1000000000000000001);
+        testing_env!(get_context("verifier.testnet", 1000000000000000002));
+        assert_eq!(contract.set_resolution_window_open("market_1".to_string()), 1000000000000000001);
+        assert_eq!(contract.get_resolution_window_opened_at("market_1".to_string()), Some(1000000000000000001));
+    }
+
+    const STALE_RESOLUTION_TIME: u64 = 1000000000000000000;
+
+    fn stale_market() -> Market {
+        Market {
+            market_id: "stale_market".to_string(),
+            condition_id: "condition_stale".to_string(),
+            title: "Will the oracle show up?".to_string(),
+            description: String::new(),
+            creator: "creator.testnet".parse().unwrap(),
+            end_time: STALE_RESOLUTION_TIME - 1,
+            resolution_time: STALE_RESOLUTION_TIME,
+            category: "test".to_string(),
+            is_active: true,
+            resolver: "resolver.testnet".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_stale_market_fallback_waits_for_resolution_deadline() {
+        testing_env!(get_context("owner.testnet", STALE_RESOLUTION_TIME));
+        let mut contract = MarketResolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            86_400_000_000_000,
+            U128(ONE_NEAR),
+        );
+        let deadline = STALE_RESOLUTION_TIME + DEFAULT_MAX_RESOLUTION_DELAY;
+
+        testing_env!(get_context("resolver.testnet", deadline));
+        assert_eq!(contract.on_market_info_for_stale_fallback("stale_market".to_string(), Ok(Some(stale_market()))), None);
+        assert!(contract.get_resolution("stale_market".to_string()).is_none());
+
+        // A shorter per-market delay opens the fallback earlier
+        testing_env!(get_context("owner.testnet", deadline));
+        contract.set_market_resolution_delay("stale_market".to_string(), Some(86_400_000_000_000));
+        assert_eq!(contract.get_max_resolution_delay("stale_market".to_string()), 86_400_000_000_000);
+        assert_eq!(contract.get_max_resolution_delay("other_market".to_string()), DEFAULT_MAX_RESOLUTION_DELAY);
+
+        testing_env!(get_context("resolver.testnet", deadline));
+        assert!(contract.on_market_info_for_stale_fallback("stale_market".to_string(), Ok(Some(stale_market()))).is_some());
+        let resolution = contract.get_resolution("stale_market".to_string()).unwrap();
+        assert_eq!(resolution.winning_outcome, 2);
+        assert_eq!(resolution.resolver.as_str(), "resolver.testnet");
+        assert!(matches!(resolution.status, ResolutionStatus::Pending));
+
+        // Once a resolution is filed there is nothing left to fall back from
+        testing_env!(get_context("anyone.testnet", deadline + 1));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.trigger_stale_market_fallback("stale_market".to_string());
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_owner_overrides_stale_market_fallback_during_dispute_period() {
+        testing_env!(get_context("owner.testnet", STALE_RESOLUTION_TIME));
+        let mut contract = MarketResolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            86_400_000_000_000,
+            U128(ONE_NEAR),
+        );
+        let filed_at = STALE_RESOLUTION_TIME + DEFAULT_MAX_RESOLUTION_DELAY + 1;
+
+        testing_env!(get_context("resolver.testnet", filed_at));
+        contract.on_market_info_for_stale_fallback("stale_market".to_string(), Ok(Some(stale_market())));
+
+        // The real outcome turned up before the dispute period ended
+        testing_env!(get_context("owner.testnet", filed_at + 3_600_000_000_000));
+        let _ = contract.emergency_resolve("stale_market".to_string(), 1);
+
+        let resolution = contract.get_resolution("stale_market".to_string()).unwrap();
+        assert_eq!(resolution.winning_outcome, 1);
+        assert!(matches!(resolution.status, ResolutionStatus::Finalized));
     }
 }
@@ -0,0 +1,153 @@
+// Upgrade and migration entry points for the resolver.
+//
+// The owner approves a code hash with propose_upgrade, then calls upgrade with the wasm as raw
+// input; the deploy and the migrate call go out in one batch. Open disputes and escrowed bonds
+// carry over untouched since the collections keep their prefixes. Before changing the
+// MarketResolver struct, freeze the current layout here as the next MarketResolverV<n>.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{UnorderedMap, UnorderedSet};
+use near_sdk::json_types::{Base58CryptoHash, U128};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, NearToken, Promise};
+use schemars::JsonSchema;
+
+use crate::{BondCurrency, Dispute, MarketResolver, MarketResolverExt, Resolution};
+
+const MIGRATE_TGAS: u64 = 100;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StateVersion {
+    V1,             // unversioned layout deployed before migrations existed
+    V2,             // adds state_version, pending_upgrade_hash, ownership transfer and the config admin, resolution sources, volume-scaled and USDC bonds, unsynced resolutions, oracle rotations, escrowed bonds and the treasury, the resolution indexes, Schelling votes, commit-reveal resolution, resolution windows and the stale-market fallback delay
+}
+
+impl StateVersion {
+    pub const CURRENT: StateVersion = StateVersion::V2;
+}
+
+/// V1 layout, frozen. Stored without a version marker
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MarketResolverV1 {
+    pub owner_id: AccountId,
+    pub verifier_contract: AccountId,
+    pub ctf_contract: AccountId,
+    pub authorized_oracles: UnorderedSet<AccountId>,
+    pub resolutions: UnorderedMap<String, Resolution>,
+    pub disputes: UnorderedMap<String, Dispute>,
+    pub dispute_period: u64,
+    pub dispute_bond: U128,
+}
+
+impl From<MarketResolverV1> for MarketResolver {
+    fn from(old: MarketResolverV1) -> Self {
+        let mut contract = Self {
+            // Bonds from lost disputes go to the owner until a treasury is set, as in new()
+            treasury_account: old.owner_id.clone(),
+            owner_id: old.owner_id,
+            verifier_contract: old.verifier_contract,
+            ctf_contract: old.ctf_contract,
+            authorized_oracles: old.authorized_oracles,
+            resolutions: old.resolutions,
+            disputes: old.disputes,
+            dispute_period: old.dispute_period,
+            dispute_bond: old.dispute_bond,
+            pending_owner: None,
+            config_admin: None,
+            resolution_sources: UnorderedMap::new(b"a"),
+            dispute_bond_percent_of_volume: 0,
+            max_dispute_bond: U128(old.dispute_bond.0.max(crate::DEFAULT_MAX_DISPUTE_BOND)),
+            collateral_per_near: U128(0),
+            market_volumes: UnorderedMap::new(b"v"),
+            unsynced_resolutions: UnorderedMap::new(b"u"),
+            oracle_rotations: UnorderedMap::new(b"t"),
+            oracle_rotation_history: Vec::new(),
+            authority_grace_period: crate::DEFAULT_AUTHORITY_GRACE_PERIOD,
+            // Open V1 disputes kept no escrow entry; resolving one falls back to its recorded bond
+            escrowed_bonds: UnorderedMap::new(b"b"),
+            pending_set: UnorderedSet::new(b"p"),
+            disputed_set: UnorderedSet::new(b"q"),
+            finalized_set: UnorderedSet::new(b"f"),
+            resolutions_by_resolver: UnorderedMap::new(b"s"),
+            state_version: StateVersion::CURRENT,
+            pending_upgrade_hash: None,
+            usdc_contract: None,
+            usdc_dispute_bond: U128(0),
+            accepted_bond_currencies: vec![BondCurrency::Near],
+            bond_currencies: UnorderedMap::new(b"c"),
+            claimable_refunds: UnorderedMap::new(b"e"),
+            schelling_votes: UnorderedMap::new(b"g"),
+            schelling_vote_period: crate::DEFAULT_SCHELLING_VOTE_PERIOD,
+            commit_reveal_markets: UnorderedSet::new(b"m"),
+            resolution_commitments: UnorderedMap::new(b"h"),
+            commit_window: crate::DEFAULT_COMMIT_WINDOW,
+            reveal_window: crate::DEFAULT_REVEAL_WINDOW,
+            resolution_windows: UnorderedMap::new(b"w"),
+            max_resolution_delay: crate::DEFAULT_MAX_RESOLUTION_DELAY,
+            resolution_delay_overrides: UnorderedMap::new(b"x"),
+        };
+
+        // V1 had no status or resolver indexes; build them from the stored resolutions
+        let mut resolutions: Vec<Resolution> = contract.resolutions.values().collect();
+        resolutions.sort_by_key(|resolution| resolution.submitted_at);
+        for resolution in resolutions {
+            contract.store_resolution(&resolution);
+            let mut submitted = contract.resolutions_by_resolver.get(&resolution.resolver).unwrap_or_default();
+            submitted.push(resolution.market_id.clone());
+            contract.resolutions_by_resolver.insert(&resolution.resolver, &submitted);
+        }
+        contract
+    }
+}
+
+#[near_bindgen]
+impl MarketResolver {
+    /// Approve the sha256 of the next resolver code; replaces any earlier proposal
+    pub fn propose_upgrade(&mut self, code_hash: Base58CryptoHash) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can propose upgrades");
+
+        self.pending_upgrade_hash = Some(code_hash.into());
+        env::log_str(&format!("Upgrade proposed with code hash {}", String::from(&code_hash)));
+    }
+
+    /// Deploy the approved wasm (raw call input) and run migrate in the same batch
+    pub fn upgrade(&mut self) -> Promise {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can upgrade");
+        let code = env::input().expect("Contract code missing from input");
+        let approved = self.pending_upgrade_hash.take().expect("No upgrade proposed");
+        assert_eq!(env::sha256_array(&code), approved, "Code does not match the proposed upgrade");
+
+        self.emit_role_event("upgrade_deployed", None);
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call("migrate".to_string(), Vec::new(), NearToken::from_yoctonear(0), Gas::from_tgas(MIGRATE_TGAS))
+    }
+
+    /// Convert stored state to the current layout; a no-op for state that is already current
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = env::storage_read(b"STATE").expect("No contract state to migrate");
+
+        let (from, mut contract) = match Self::try_from_slice(&state) {
+            Ok(current) => (current.state_version, current),
+            Err(_) => {
+                let old = MarketResolverV1::try_from_slice(&state).expect("Unrecognized contract state layout");
+                (StateVersion::V1, old.into())
+            }
+        };
+        env::log_str(&format!("Resolver state migrated from {:?} to {:?}", from, StateVersion::CURRENT));
+        contract.state_version = StateVersion::CURRENT;
+        contract.pending_upgrade_hash = None;
+        contract
+    }
+
+    pub fn get_state_version(&self) -> StateVersion {
+        self.state_version
+    }
+
+    pub fn get_pending_upgrade(&self) -> Option<Base58CryptoHash> {
+        self.pending_upgrade_hash.map(Base58CryptoHash::from)
+    }
+}
@@ -2,13 +2,43 @@ use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{UnorderedMap, UnorderedSet};
 use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, near_bindgen, AccountId, Promise, PanicOnDefault};
+use near_sdk::{env, near_bindgen, AccountId, CryptoHash, Promise, PromiseOrValue, PanicOnDefault};
 use schemars::JsonSchema;
 
+mod migration;
+pub use migration::StateVersion;
+
 // Cross-chain utilities (simplified without external SDK dependencies) - currently unused
 // use hex;
 // use bs58;
 
+// Gas budget per hop of a RedeemWinning intent: verifier -> solver -> CTF redeem_positions -> USDC ft_transfer
+const VERIFIER_INTENT_TGAS: u64 = 20;
+const SOLVER_REDEMPTION_TGAS: u64 = 30;     // solve_intent + get_market + on_market_info_for_redemption
+const CTF_REDEEM_TGAS: u64 = 30;
+const USDC_FT_TRANSFER_TGAS: u64 = 10;
+const INSURANCE_FT_TRANSFER_CALL_TGAS: u64 = 30;   // USDC ft_transfer_call into the verifier's insurance fund
+const MIN_GAS_PRICE: u128 = 100_000_000;   // yoctoNEAR per gas (protocol minimum)
+const NEAR_USDC_PRICE: u128 = 3_000_000;   // reference NEAR price in USDC base units (6 decimals)
+const REDEMPTION_COST_CAP_BPS: u128 = 500; // fees + gas may take at most 5% of the payout
+
+const DEFAULT_INTENT_TIMEOUT: u64 = 300_000_000_000; // 5 minutes in nanoseconds
+const DEFAULT_COMPLETION_SLA: u64 = 60_000_000_000;  // 1 minute in nanoseconds
+const STUCK_INTENT_ALERT_THRESHOLD: u64 = 10;         // stuck intents per daemon before revocation alerts
+const DEFAULT_AUTHORITY_GRACE_PERIOD: u64 = 600_000_000_000; // 10 minutes in nanoseconds
+// Bump when a SolverConfig or SolverHealth field is renamed, removed or changes meaning
+const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+const MAX_PRICE: u64 = 100_000;                        // $1.00 in 1/100000 of a dollar
+const DAY_NS: u64 = 86_400_000_000_000;
+const FEE_VOLUME_WINDOW_DAYS: u64 = 30;                 // trailing window fee tiers are priced on
+const MAX_FEE_TIERS: usize = 10;
+const DEFAULT_RETURN_TIMEOUT: u64 = 86_400_000_000_000; // 24h before an unconfirmed return can be paid out on NEAR
+const MAX_BULK_ORDERS: usize = 50;                      // orders per place_bulk_orders / cancel_bulk_orders call
+const MAX_INTENT_TRANSITIONS: usize = 16;               // stage changes kept per intent
+const MAX_TRACE_TRADES: usize = 20;                     // trades embedded in get_intent_trace; page the rest with get_order_trades
+const DEFAULT_INSURANCE_FEE_BPS: u16 = 1;               // matches the verifier's default insurance fund fee
+const MAX_INSURANCE_FEE_BPS: u16 = 100;
+
 // Define local types (copied from verifier for standalone deployment)
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
@@ -57,6 +87,8 @@ pub struct PredictionIntent {
     pub deadline: u64,
     pub order_type: OrderType,
     pub cross_chain: Option<CrossChainParams>,
+    #[serde(default)]
+    pub max_slippage_bps: Option<u16>,                            // how far past max_price/min_price execution may land
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
@@ -116,6 +148,127 @@ pub struct ExecutionResult {
     pub execution_details: String,
 }
 
+// Per-daemon performance counters, updated on every complete_intent
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DaemonStats {
+    #[schemars(with = "String")]
+    pub account_id: AccountId,
+    pub intents_completed: u64,
+    pub failures_reported: u64,
+    pub total_completion_time: u64,     // ns summed over every reported intent
+    pub average_completion_time: u64,   // ns
+    pub last_active: u64,
+}
+
+// Fee charged once a user's trailing 30-day volume reaches volume_threshold. Users below the
+// lowest threshold pay solver_fee_bps
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeTier {
+    #[schemars(with = "String")]
+    pub volume_threshold: U128,
+    pub fee_bps: u16,
+}
+
+// Scheduled hand-over of the orderbook authority. From effective_at both accounts may report
+// fills until the grace period runs out, so reports signed by the old service still land
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuthorityRotation {
+    #[schemars(with = "String")]
+    pub previous: AccountId,
+    #[schemars(with = "String")]
+    pub next: AccountId,
+    pub effective_at: u64,
+    #[schemars(with = "String")]
+    pub requested_by: AccountId,
+    pub requested_at: u64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OrderbookAuthorityInfo {
+    #[schemars(with = "String")]
+    pub authority: AccountId,                  // sole authority once any rotation has completed
+    pub pending_rotation: Option<AuthorityRotation>,
+    pub grace_period: u64,                     // ns
+    pub history: Vec<AuthorityRotation>,       // oldest first, including the pending one
+}
+
+// Everything the daemon and orderbook need to bootstrap, in one view call
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SolverConfig {
+    pub schema_version: u32,
+    #[schemars(with = "String")]
+    pub owner_id: AccountId,
+    #[schemars(with = "String")]
+    pub verifier_contract: AccountId,
+    #[schemars(with = "String")]
+    pub ctf_contract: AccountId,
+    #[schemars(with = "String")]
+    pub usdc_contract: AccountId,
+    #[schemars(with = "String")]
+    pub orderbook_authority: AccountId,
+    pub solver_fee_bps: u16,
+    pub bridge_fee_bps: u16,
+    #[schemars(with = "String")]
+    pub min_order_size: U128,
+    pub cross_chain_enabled: bool,
+    #[schemars(with = "Option<String>")]
+    pub monitor_contract: Option<AccountId>,
+    #[schemars(with = "Vec<String>")]
+    pub authorized_daemons: Vec<AccountId>,
+    pub intent_timeout: u64,                   // ns
+    pub completion_sla: u64,                   // ns
+    pub fee_tiers: Vec<FeeTier>,               // volume discounts on top of solver_fee_bps
+    pub maker_rebate_bps: i16,                 // negative is a rebate
+    pub taker_fee_bps: u16,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SolverHealth {
+    pub schema_version: u32,
+    pub version: String,
+    pub active_orders: u64,
+    pub pending_for_daemon: u64,
+    pub processed_intents: u64,
+}
+
+// Pending intent a daemon appears to have abandoned
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StuckIntent {
+    pub intent_id: String,
+    pub order: Order,
+    pub pending_since: u64,
+    pub age_seconds: u64,
+    #[schemars(with = "Option<String>")]
+    pub responsible_daemon: Option<AccountId>,
+}
+
+// Pending intent that has waited longer than the completion SLA
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SlaBreach {
+    pub intent_id: String,
+    pub pending_since: u64,
+    pub waiting_time: u64,              // ns
+}
+
+// Expected cost of claiming a RedeemWinning intent
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GasEstimate {
+    pub estimated_tgas: u64,
+    #[schemars(with = "String")]
+    pub estimated_near_cost: U128,      // yoctoNEAR
+    #[schemars(with = "String")]
+    pub min_profitable_amount: U128,    // USDC base units
+}
+
 // Simplified bridge configuration (no external SDK dependencies)
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(crate = "near_sdk::serde")]
@@ -125,6 +278,35 @@ pub struct SimpleBridgeConfig {
     pub supported_chains: Vec<u64>,
 }
 
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ReturnStatus {
+    Pending,                                   // queued for the relayer
+    Confirmed,                                 // relayer bridged it back to the source chain
+    Failed,                                    // relayer gave up; claimable after the return timeout
+    Claimed,                                   // paid out on NEAR instead
+}
+
+// Payout owed back to a cross-chain user's source-chain address. The solver holds the funds
+// until the relayer confirms the return bridge or the amount is paid out on NEAR
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReturnObligation {
+    pub intent_id: String,
+    pub chain_id: u64,
+    pub address: String,                       // recipient on the source chain
+    pub token: String,                         // token contract on the source chain
+    #[schemars(with = "String")]
+    pub amount: U128,
+    #[schemars(with = "String")]
+    pub near_account: AccountId,               // intent user; may claim the amount on NEAR
+    pub status: ReturnStatus,
+    pub tx_hash: Option<String>,               // source-chain tx, once confirmed
+    pub failure_reason: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
 // External contract interfaces (Updated to match new CTF implementation)
 #[near_sdk::ext_contract(ext_ctf)]
 pub trait ConditionalTokenFramework {
@@ -170,6 +352,7 @@ pub trait PredictionVerifier {
 pub trait FungibleToken {
     fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
     fn ft_transfer_from(&mut self, sender_id: AccountId, receiver_id: AccountId, amount: U128, memo: Option<String>);
+    fn ft_transfer_call(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>, msg: String) -> U128;
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone)]
@@ -192,6 +375,10 @@ pub struct Order {
     pub status: OrderStatus,
     pub created_at: u64,
     pub expires_at: u64,
+    #[serde(default)]
+    pub price_bound: Option<u64>,                                  // worst fill price accepted: max for buys, min for sells
+    #[serde(default)]
+    pub market_maker: bool,                                        // placed directly by an approved market maker, no intent behind it
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone)]
@@ -239,6 +426,77 @@ pub enum TradeType {
     Burning,        // Destroy YES/NO pairs
 }
 
+/// One resting order in a place_bulk_orders batch
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NewOrderParams {
+    pub market_id: String,
+    pub outcome: u8,                                               // 0=NO, 1=YES
+    pub side: OrderSide,
+    pub order_type: OrderType,                                     // Limit, GTC or GTD
+    pub price: u64,                                                // price in 1/100000 of dollar
+    #[schemars(with = "String")]
+    pub amount: U128,
+    pub expires_at: u64,                                           // ns
+}
+
+/// Sell the shares a buy order bought once their price reaches `trigger_price`
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TakeProfitOrder {
+    pub position_id: String,                                       // "market_id:outcome" the shares belong to
+    pub trigger_price: u64,                                        // price in 1/100000 of dollar
+    #[schemars(with = "String")]
+    pub sell_amount: U128,                                         // shares filled on the buy order when set
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum IntentStage {
+    Registered,                                                    // order created and queued for the daemon
+    Acknowledged,                                                  // a daemon picked it up
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Expired,
+    Completed,                                                     // daemon reported success
+    Failed,                                                        // daemon reported failure
+    Refunded,                                                      // timed out and refunded to the user
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct IntentTransition {
+    pub stage: IntentStage,
+    pub at: u64,                                                   // block timestamp, ns
+}
+
+/// What the daemon reported when it completed an intent
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct IntentCompletion {
+    #[schemars(with = "String")]
+    pub daemon: AccountId,
+    pub result: ExecutionResult,
+    pub completed_at: u64,
+}
+
+/// Everything the solver knows about one intent, joined for support tooling
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct IntentTrace {
+    pub intent_id: String,
+    pub order: Option<Order>,                                      // current status and fills; None once refunded
+    pub pending_for_daemon: bool,
+    pub processed: bool,
+    #[schemars(with = "Option<String>")]
+    pub responsible_daemon: Option<AccountId>,
+    pub completion: Option<IntentCompletion>,
+    pub transitions: Vec<IntentTransition>,                        // oldest first
+    pub trades: Vec<TradeExecution>,                               // the order's first MAX_TRACE_TRADES trades
+    pub total_trades: u64,                                         // page past the embedded ones with get_order_trades
+}
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct PredictionSolver {
@@ -252,12 +510,41 @@ pub struct PredictionSolver {
     pub authorized_daemons: UnorderedSet<AccountId>,               // accounts authorized to complete intents
     pub active_orders: UnorderedMap<String, Order>,                // order_id -> Order
     pub user_orders: UnorderedMap<AccountId, Vec<String>>,         // user -> order_ids[]
+    pub order_sequences: UnorderedMap<String, u64>,                // order_id -> changes made to the order so far
+    pub take_profit_orders: UnorderedMap<String, TakeProfitOrder>, // buy order_id -> take-profit waiting to trigger
     pub solver_fee_bps: u16,                                       // basis points
     pub min_order_size: U128,
     pub cross_chain_enabled: bool,                                 // cross-chain functionality toggle
     pub bridge_fee_bps: u16,                                       // additional fee for cross-chain (basis points)
     pub bridge_config: Option<SimpleBridgeConfig>,                // Simplified bridge configuration
     pub monitor_contract: Option<AccountId>,                       // Cross-chain monitor contract
+    pub pending_owner: Option<AccountId>,                          // proposed owner awaiting accept_ownership
+    pub config_admin: Option<AccountId>,                           // may update solver and bridge fees
+    pub intent_timeout: u64,                                       // ns a daemon has to complete an intent
+    pub pending_since: UnorderedMap<String, u64>,                  // intent_id -> when it entered pending_for_daemon
+    pub intent_usdc: UnorderedMap<String, U128>,                   // intent_id -> USDC carried by buy/mint intents
+    pub daemon_stats: UnorderedMap<AccountId, DaemonStats>,        // daemon -> completion counters
+    pub completion_sla: u64,                                       // ns before a pending intent counts as an SLA breach
+    pub intent_daemon_assignments: UnorderedMap<String, AccountId>, // intent_id -> daemon that last touched it
+    pub pending_authority_rotation: Option<AuthorityRotation>,     // orderbook authority hand-over in progress
+    pub authority_grace_period: u64,                               // ns both authorities are accepted after effective_at
+    pub authority_rotations: Vec<AuthorityRotation>,               // every scheduled rotation, oldest first
+    pub state_version: StateVersion,                               // layout marker checked by migrate()
+    pub pending_upgrade_hash: Option<CryptoHash>,                  // sha256 of owner-approved code for upgrade()
+    pub fee_tiers: Vec<FeeTier>,                                   // ascending volume thresholds
+    pub user_daily_volume: UnorderedMap<AccountId, Vec<(u64, U128)>>, // user -> (day, completed volume), last 30 days
+    pub return_obligations: UnorderedMap<String, ReturnObligation>, // intent_id -> payout owed back to the source chain
+    pub return_timeout: u64,                                       // ns before an unconfirmed return may be paid out on NEAR
+    pub maker_rebate_bps: i16,                                     // charged on maker fills; negative is a rebate credited to the maker
+    pub taker_fee_bps: u16,                                        // charged on taker fills, out of the intent's USDC
+    pub rebate_balances: UnorderedMap<AccountId, U128>,            // maker -> USDC rebates waiting for claim_maker_rebate
+    pub market_makers: UnorderedSet<AccountId>,                    // owner-approved accounts that may call place_bulk_orders
+    pub intent_transitions: UnorderedMap<String, Vec<IntentTransition>>, // intent_id -> stage changes, at most MAX_INTENT_TRANSITIONS
+    pub intent_completions: UnorderedMap<String, IntentCompletion>, // intent_id -> what the daemon reported in complete_intent
+    pub trades: UnorderedMap<String, TradeExecution>,              // trade_id -> trade reported by the orderbook
+    pub order_trades: UnorderedMap<String, Vec<String>>,           // order_id -> trade_ids, oldest first
+    pub insurance_fee_bps: u16,                                    // share of funded intents paid into the verifier's insurance fund
+    pub taker_fee_pool: U128,                                      // taker fees collected and not yet credited as maker rebates
 }
 
 #[near_bindgen] 
@@ -284,12 +571,41 @@ impl PredictionSolver {
             authorized_daemons: UnorderedSet::new(b"a"),
             active_orders: UnorderedMap::new(b"o"),
             user_orders: UnorderedMap::new(b"u"),
+            order_sequences: UnorderedMap::new(b"q"),
+            take_profit_orders: UnorderedMap::new(b"t"),
             solver_fee_bps,
             min_order_size,
             cross_chain_enabled: true,
             bridge_fee_bps: 50, // 0.5% default bridge fee
             bridge_config: None,
             monitor_contract: None,
+            pending_owner: None,
+            config_admin: None,
+            intent_timeout: DEFAULT_INTENT_TIMEOUT,
+            pending_since: UnorderedMap::new(b"s"),
+            intent_usdc: UnorderedMap::new(b"c"),
+            daemon_stats: UnorderedMap::new(b"m"),
+            completion_sla: DEFAULT_COMPLETION_SLA,
+            intent_daemon_assignments: UnorderedMap::new(b"n"),
+            pending_authority_rotation: None,
+            authority_grace_period: DEFAULT_AUTHORITY_GRACE_PERIOD,
+            authority_rotations: Vec::new(),
+            state_version: StateVersion::CURRENT,
+            pending_upgrade_hash: None,
+            fee_tiers: Vec::new(),
+            user_daily_volume: UnorderedMap::new(b"v"),
+            return_obligations: UnorderedMap::new(b"r"),
+            return_timeout: DEFAULT_RETURN_TIMEOUT,
+            maker_rebate_bps: 0,
+            taker_fee_bps: 0,
+            rebate_balances: UnorderedMap::new(b"b"),
+            market_makers: UnorderedSet::new(b"k"),
+            intent_transitions: UnorderedMap::new(b"i"),
+            intent_completions: UnorderedMap::new(b"e"),
+            trades: UnorderedMap::new(b"x"),
+            order_trades: UnorderedMap::new(b"y"),
+            insurance_fee_bps: DEFAULT_INSURANCE_FEE_BPS,
+            taker_fee_pool: U128(0),
         }
     }
 
@@ -314,6 +630,8 @@ impl PredictionSolver {
             "Intent already pending for daemon"
         );
 
+        let price_bound = Self::intent_price_bound(&intent);
+
         // Create actual order that orderbook can update
         let order_id = format!("order_{}", intent.intent_id);
         let solver_order = Order {
@@ -345,6 +663,8 @@ impl PredictionSolver {
             status: OrderStatus::Pending,
             created_at: env::block_timestamp(),
             expires_at: intent.deadline,
+            price_bound,
+            market_maker: false,
         };
 
         // Store order so orderbook can update it
@@ -352,6 +672,8 @@ impl PredictionSolver {
 
         // Register for daemon processing (NOT marking as processed yet)
         self.pending_for_daemon.insert(&intent.intent_id);
+        self.pending_since.insert(&intent.intent_id, &env::block_timestamp());
+        self.record_intent_transition(&intent.intent_id, IntentStage::Registered);
 
         env::log_str(&format!(
             "Intent {} converted to order {} and registered for daemon processing", 
@@ -359,7 +681,7 @@ impl PredictionSolver {
         ));
 
         // Calculate estimated fees for optimistic response
-        let fee_amount = (intent.amount.0 * self.solver_fee_bps as u128) / 10000;
+        let fee_amount = self.intent_fee(&intent);
         let estimated_output = intent.amount.0 - fee_amount;
 
         // Return optimistic result - daemon will provide real result later
@@ -390,16 +712,289 @@ impl PredictionSolver {
             "Intent not pending for daemon processing"
         );
 
+        // Volume only counts once the intent went through, and after its own fee was priced
+        let order = self.active_orders.get(&format!("order_{}", intent_id));
+        let fee_bps = order.as_ref().map_or(self.solver_fee_bps, |order| self.effective_fee_bps(&order.user));
+        if let (true, Some(order)) = (result.success, &order) {
+            self.record_user_volume(&order.user, order.amount.0);
+        }
+
         // Mark as actually processed
         self.processed_intents.insert(&intent_id);
         self.pending_for_daemon.remove(&intent_id);
+        let dispatched_at = self.pending_since.remove(&intent_id);
+        if let (true, Some(deposit)) = (result.success, self.intent_usdc.remove(&intent_id)) {
+            self.pay_insurance_share(&intent_id, deposit, result.fee_amount);
+        }
+        self.intent_daemon_assignments.insert(&intent_id, &caller);
+        self.record_daemon_completion(&caller, dispatched_at, result.success);
+        self.record_intent_transition(&intent_id, if result.success { IntentStage::Completed } else { IntentStage::Failed });
+
+        env::log_str(&format!(
+            "Intent {} completed by daemon {}: success={} fee_bps={}",
+            intent_id, caller, result.success, fee_bps
+        ));
+
+        self.intent_completions.insert(&intent_id, &IntentCompletion {
+            daemon: caller,
+            result,
+            completed_at: env::block_timestamp(),
+        });
+    }
+
+    /// Send the verifier's insurance fund its share of a funded intent, out of the fee charged on it.
+    /// The verifier credits the fund when the USDC arrives
+    fn pay_insurance_share(&self, intent_id: &String, deposit: U128, fee: U128) {
+        let share = (deposit.0 * self.insurance_fee_bps as u128 / 10000).min(fee.0);
+        if share == 0 {
+            return;
+        }
+
+        env::log_str(&format!("Paying insurance share of {} for intent {}", share, intent_id));
+        ext_fungible_token::ext(self.usdc_contract.clone())
+            .with_attached_deposit(near_sdk::NearToken::from_yoctonear(1))
+            .with_static_gas(near_sdk::Gas::from_tgas(INSURANCE_FT_TRANSFER_CALL_TGAS))
+            .ft_transfer_call(
+                self.verifier_contract.clone(),
+                U128(share),
+                Some(format!("Insurance share for intent {}", intent_id)),
+                near_sdk::serde_json::json!({ "insurance_intent_id": intent_id }).to_string(),
+            );
+    }
+
+    pub fn update_insurance_fee(&mut self, fee_bps: u16) {
+        self.assert_config_admin("Only owner or config admin can update insurance fee");
+        assert!(fee_bps <= MAX_INSURANCE_FEE_BPS, "Insurance fee cannot exceed 1%");
+
+        self.insurance_fee_bps = fee_bps;
+        env::log_str(&format!("Insurance fee updated to {} bps", fee_bps));
+    }
+
+    pub fn get_insurance_fee_bps(&self) -> u16 {
+        self.insurance_fee_bps
+    }
+
+    /// Daemon picks up a pending intent, so it shows as responsible if the intent gets stuck
+    pub fn acknowledge_intent(&mut self, intent_id: String) {
+        let caller = env::predecessor_account_id();
+        assert!(
+            self.authorized_daemons.contains(&caller),
+            "Only authorized daemons can acknowledge intents"
+        );
+        assert!(
+            self.pending_for_daemon.contains(&intent_id),
+            "Intent not pending for daemon processing"
+        );
+
+        self.intent_daemon_assignments.insert(&intent_id, &caller);
+        self.record_intent_transition(&intent_id, IntentStage::Acknowledged);
+        env::log_str(&format!("Intent {} acknowledged by daemon {}", intent_id, caller));
+    }
+
+    /// Let the user reclaim an intent no daemon completed within intent_timeout.
+    /// Drops it from the daemon queue and the order book, refunding any unfilled USDC
+    pub fn refund_timed_out_intent(&mut self, intent_id: String) -> PromiseOrValue<U128> {
+        assert!(
+            self.pending_for_daemon.contains(&intent_id),
+            "Intent not pending for daemon processing"
+        );
+
+        let order_id = format!("order_{}", intent_id);
+        let order = self.active_orders.get(&order_id)
+            .expect("Order not found");
+        assert_eq!(env::predecessor_account_id(), order.user, "Only the intent owner can request a refund");
+
+        let pending_since = self.pending_since.get(&intent_id).unwrap_or(order.created_at);
+        assert!(
+            pending_since + self.intent_timeout < env::block_timestamp(),
+            "Intent has not timed out yet"
+        );
+
+        self.pending_for_daemon.remove(&intent_id);
+        self.pending_since.remove(&intent_id);
+        let daemon = self.intent_daemon_assignments.remove(&intent_id);
+        self.active_orders.remove(&order_id);
+        self.record_intent_transition(&intent_id, IntentStage::Refunded);
+
+        // Only USDC the verifier actually forwarded is ours to give back
+        let deposit = self.intent_usdc.remove(&intent_id);
+        let refund = deposit
+            .map(|usdc| usdc.0.saturating_sub(order.filled_amount.0))
+            .unwrap_or(0);
 
         env::log_str(&format!(
-            "Intent {} completed by daemon {}: success={}",
-            intent_id, caller, result.success
+            "Intent {} timed out after {} ns; order {} removed, refunding {} USDC to {}",
+            intent_id, self.intent_timeout, order_id, refund, order.user
         ));
 
-        // TODO: In full implementation, could store results or notify verifier
+        if refund == 0 {
+            return PromiseOrValue::Value(U128(0));
+        }
+
+        ext_fungible_token::ext(self.usdc_contract.clone())
+            .with_attached_deposit(near_sdk::NearToken::from_yoctonear(1))
+            .with_static_gas(near_sdk::Gas::from_tgas(USDC_FT_TRANSFER_TGAS))
+            .ft_transfer(order.user.clone(), U128(refund), Some(format!("Refund for timed out intent {}", intent_id)))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(near_sdk::Gas::from_tgas(5))
+                    .on_timed_out_refund(intent_id, order, pending_since, daemon, deposit.unwrap_or(U128(0)))
+            )
+            .into()
+    }
+
+    /// Put the intent back in the daemon queue if the refund transfer failed, so it can be retried
+    #[private]
+    pub fn on_timed_out_refund(
+        &mut self,
+        intent_id: String,
+        order: Order,
+        pending_since: u64,
+        daemon: Option<AccountId>,
+        deposit: U128,
+    ) -> U128 {
+        if let near_sdk::PromiseResult::Successful(_) = env::promise_result(0) {
+            return U128(deposit.0.saturating_sub(order.filled_amount.0));
+        }
+
+        self.pending_for_daemon.insert(&intent_id);
+        self.pending_since.insert(&intent_id, &pending_since);
+        if let Some(daemon) = daemon {
+            self.intent_daemon_assignments.insert(&intent_id, &daemon);
+        }
+        self.intent_usdc.insert(&intent_id, &deposit);
+        self.active_orders.insert(&order.order_id, &order);
+        self.record_intent_transition(&intent_id, IntentStage::Registered);
+        env::log_str(&format!("Refund for timed out intent {} failed; intent restored", intent_id));
+        U128(0)
+    }
+
+    /// NEP-141 receiver: the verifier forwards a deposited buy or mint intent's USDC with the
+    /// intent id as `msg`. Only USDC recorded here is refunded by refund_timed_out_intent
+    pub fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        if env::predecessor_account_id() != self.usdc_contract || sender_id != self.verifier_contract {
+            env::log_str(&format!("Transfer of {} from {} returned: not an intent deposit", amount.0, sender_id));
+            return PromiseOrValue::Value(amount);
+        }
+        if self.processed_intents.contains(&msg) || self.intent_usdc.get(&msg).is_some() {
+            env::log_str(&format!("Deposit for intent {} returned: already funded or completed", msg));
+            return PromiseOrValue::Value(amount);
+        }
+
+        self.intent_usdc.insert(&msg, &amount);
+        env::log_str(&format!("Intent {} funded with {} USDC", msg, amount.0));
+        PromiseOrValue::Value(U128(0))
+    }
+
+    pub fn update_intent_timeout(&mut self, timeout: u64) {
+        self.assert_config_admin("Only owner or config admin can update intent timeout");
+        assert!(timeout >= 60_000_000_000, "Intent timeout too short (min 1 minute)");
+
+        self.intent_timeout = timeout;
+        env::log_str(&format!("Intent timeout updated to {} ns", timeout));
+    }
+
+    pub fn get_intent_timeout(&self) -> u64 {
+        self.intent_timeout
+    }
+
+    pub fn update_completion_sla(&mut self, sla: u64) {
+        self.assert_config_admin("Only owner or config admin can update completion SLA");
+        assert!(sla > 0, "Completion SLA must be positive");
+
+        self.completion_sla = sla;
+        env::log_str(&format!("Completion SLA updated to {} ns", sla));
+    }
+
+    pub fn get_completion_sla(&self) -> u64 {
+        self.completion_sla
+    }
+
+    pub fn get_daemon_stats(&self, account_id: AccountId) -> Option<DaemonStats> {
+        self.daemon_stats.get(&account_id)
+    }
+
+    pub fn get_all_daemon_stats(&self) -> Vec<DaemonStats> {
+        self.daemon_stats.values().collect()
+    }
+
+    /// Pending intents older than the completion SLA, oldest first, so they can be reassigned
+    pub fn get_sla_breaches(&self, limit: u64) -> Vec<SlaBreach> {
+        let now = env::block_timestamp();
+        let mut breaches: Vec<SlaBreach> = self.pending_for_daemon
+            .iter()
+            .filter_map(|intent_id| {
+                let pending_since = self.pending_since.get(&intent_id)?;
+                let waiting_time = now.saturating_sub(pending_since);
+                (waiting_time > self.completion_sla).then(|| SlaBreach {
+                    intent_id,
+                    pending_since,
+                    waiting_time,
+                })
+            })
+            .collect();
+
+        breaches.sort_by_key(|breach| breach.pending_since);
+        breaches.truncate(limit as usize);
+        breaches
+    }
+
+    fn intent_fee(&self, intent: &PredictionIntent) -> u128 {
+        (intent.amount.0 * self.effective_fee_bps(&intent.user) as u128) / 10000
+    }
+
+    /// Fee of the highest tier the user's trailing volume reaches, else the base solver fee
+    fn effective_fee_bps(&self, user: &AccountId) -> u16 {
+        let volume = self.trailing_volume(user);
+        self.fee_tiers.iter()
+            .rev()
+            .find(|tier| volume >= tier.volume_threshold.0)
+            .map_or(self.solver_fee_bps, |tier| tier.fee_bps)
+    }
+
+    fn trailing_volume(&self, user: &AccountId) -> u128 {
+        let today = env::block_timestamp() / DAY_NS;
+        self.user_daily_volume.get(user)
+            .unwrap_or_default()
+            .iter()
+            .filter(|(day, _)| day + FEE_VOLUME_WINDOW_DAYS > today)
+            .map(|(_, volume)| volume.0)
+            .sum()
+    }
+
+    /// Add to today's bucket and drop buckets that left the window
+    fn record_user_volume(&mut self, user: &AccountId, amount: u128) {
+        let today = env::block_timestamp() / DAY_NS;
+        let mut buckets = self.user_daily_volume.get(user).unwrap_or_default();
+        match buckets.last_mut() {
+            Some((day, volume)) if *day == today => volume.0 += amount,
+            _ => buckets.push((today, U128(amount))),
+        }
+        buckets.retain(|(day, _)| day + FEE_VOLUME_WINDOW_DAYS > today);
+        self.user_daily_volume.insert(user, &buckets);
+    }
+
+    fn record_daemon_completion(&mut self, daemon: &AccountId, dispatched_at: Option<u64>, success: bool) {
+        let now = env::block_timestamp();
+        let mut stats = self.daemon_stats.get(daemon).unwrap_or(DaemonStats {
+            account_id: daemon.clone(),
+            intents_completed: 0,
+            failures_reported: 0,
+            total_completion_time: 0,
+            average_completion_time: 0,
+            last_active: now,
+        });
+
+        if success {
+            stats.intents_completed += 1;
+        } else {
+            stats.failures_reported += 1;
+        }
+        stats.total_completion_time += dispatched_at.map_or(0, |at| now.saturating_sub(at));
+        stats.average_completion_time = stats.total_completion_time / (stats.intents_completed + stats.failures_reported);
+        stats.last_active = now;
+
+        self.daemon_stats.insert(daemon, &stats);
     }
 
     // Helper methods for daemon management
@@ -413,6 +1008,75 @@ impl PredictionSolver {
         assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can revoke daemons");
         self.authorized_daemons.remove(&daemon_account);
         env::log_str(&format!("Revoked daemon: {}", daemon_account));
+
+        let stuck = self.collect_stuck_intents(self.intent_timeout)
+            .iter()
+            .filter(|stuck| stuck.responsible_daemon.as_ref() == Some(&daemon_account))
+            .count() as u64;
+        if stuck > STUCK_INTENT_ALERT_THRESHOLD {
+            env::log_str(&format!(
+                "ALERT: revoked daemon {} left {} stuck intents; reassign or refund them",
+                daemon_account, stuck
+            ));
+        }
+    }
+
+    // Market makers
+    pub fn add_market_maker(&mut self, account: AccountId) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can approve market makers");
+        self.market_makers.insert(&account);
+        self.emit_role_event("market_maker_added", Some(&account));
+    }
+
+    /// Revoked makers can still cancel the orders they already placed
+    pub fn remove_market_maker(&mut self, account: AccountId) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can remove market makers");
+        self.market_makers.remove(&account);
+        self.emit_role_event("market_maker_removed", Some(&account));
+    }
+
+    pub fn is_market_maker(&self, account: AccountId) -> bool {
+        self.market_makers.contains(&account)
+    }
+
+    pub fn get_market_makers(&self) -> Vec<AccountId> {
+        self.market_makers.to_vec()
+    }
+
+    /// Pending intents older than min_age_seconds, oldest first
+    pub fn get_stuck_intents(&self, min_age_seconds: u64) -> Vec<StuckIntent> {
+        self.collect_stuck_intents(min_age_seconds * 1_000_000_000)
+    }
+
+    /// Number of pending intents past intent_timeout
+    pub fn get_stuck_intents_count(&self) -> u64 {
+        self.collect_stuck_intents(self.intent_timeout).len() as u64
+    }
+
+    fn collect_stuck_intents(&self, min_age: u64) -> Vec<StuckIntent> {
+        let now = env::block_timestamp();
+        let mut stuck: Vec<StuckIntent> = self.pending_for_daemon
+            .iter()
+            .filter_map(|intent_id| {
+                let order = self.active_orders.get(&format!("order_{}", intent_id))?;
+                let pending_since = self.pending_since.get(&intent_id).unwrap_or(order.created_at);
+                let age = now.saturating_sub(pending_since);
+                if age < min_age {
+                    return None;
+                }
+
+                Some(StuckIntent {
+                    responsible_daemon: self.intent_daemon_assignments.get(&intent_id),
+                    intent_id,
+                    order,
+                    pending_since,
+                    age_seconds: age / 1_000_000_000,
+                })
+            })
+            .collect();
+
+        stuck.sort_by_key(|intent| intent.pending_since);
+        stuck
     }
 
     // Query methods
@@ -452,7 +1116,7 @@ impl PredictionSolver {
         }
         
         // Calculate fees (simplified with single bridge fee)
-        let base_fee = (intent.amount.0 * self.solver_fee_bps as u128) / 10000;
+        let base_fee = self.intent_fee(&intent);
         let bridge_fee = (intent.amount.0 * self.bridge_fee_bps as u128) / 10000;
         let total_fee = base_fee + bridge_fee;
         let net_amount = intent.amount.0 - total_fee;
@@ -507,30 +1171,50 @@ impl PredictionSolver {
         Ok(())
     }
     
-    /// Handle cross-chain return with error handling
-    fn handle_cross_chain_return(&self, intent: &PredictionIntent, params: &CrossChainParams, result: &mut ExecutionResult) {
+    /// Handle cross-chain return with error handling. Every return is recorded as an obligation;
+    /// only the relayer's confirm_return marks it done
+    fn handle_cross_chain_return(&mut self, intent: &PredictionIntent, params: &CrossChainParams, result: &mut ExecutionResult) {
         env::log_str(&format!(
             "🔄 Scheduling payout return to {} on chain {}",
             params.source_user, params.source_chain_id
         ));
         
         if let Some(output_amount) = result.output_amount {
+            let now = env::block_timestamp();
+            let mut obligation = ReturnObligation {
+                intent_id: intent.intent_id.clone(),
+                chain_id: params.source_chain_id,
+                address: params.source_user.clone(),
+                token: params.source_token.clone(),
+                amount: output_amount,
+                near_account: intent.user.clone(),
+                status: ReturnStatus::Pending,
+                tx_hash: None,
+                failure_reason: None,
+                created_at: now,
+                updated_at: now,
+            };
+
             match self.execute_cross_chain_return(
                 params.source_chain_id,
                 params.source_user.clone(),
                 params.source_token.clone(),
                 output_amount
             ) {
-                Ok(tx_hash) => {
+                Ok(()) => {
+                    self.return_obligations.insert(&intent.intent_id, &obligation);
                     result.execution_details = format!(
-                        "{} | Return bridge initiated: {}",
-                        result.execution_details, tx_hash
+                        "{} | Return bridge queued for relayer",
+                        result.execution_details
                     );
                     
-                    // Update monitoring with return transaction
-                    self.update_monitoring_status(&intent.intent_id, BridgeStatus::Completing, Some(tx_hash), None);
+                    self.update_monitoring_status(&intent.intent_id, BridgeStatus::Completing, None, None);
                 }
                 Err(e) => {
+                    obligation.status = ReturnStatus::Failed;
+                    obligation.failure_reason = Some(e.clone());
+                    self.return_obligations.insert(&intent.intent_id, &obligation);
+
                     env::log_str(&format!("⚠️ Return bridge failed: {}", e));
                     result.execution_details = format!(
                         "{} | Return bridge failed: {}",
@@ -551,6 +1235,23 @@ impl PredictionSolver {
         // Generate condition_id from market_id (simplified for integration)
         // In production, this would query the verifier contract for market details
         let condition_id = format!("condition_{}", intent.market_id);
+
+        // Refuse rather than fill further past the user's limit price than they tolerate
+        if let Some(execution_price) = Self::execution_price(intent, net_amount) {
+            if Self::slippage_exceeded(intent, execution_price) {
+                env::log_str(&format!(
+                    "Intent {} not executed: price {} is beyond max_price {:?} / min_price {:?} by more than {:?} bps",
+                    intent.intent_id, execution_price, intent.max_price, intent.min_price, intent.max_slippage_bps
+                ));
+                return ExecutionResult {
+                    intent_id: intent.intent_id.clone(),
+                    success: false,
+                    output_amount: None,
+                    fee_amount: U128(0),
+                    execution_details: "Slippage tolerance exceeded".to_string(),
+                };
+            }
+        }
         
         // NOTE: In a production system, these would be async Promise calls to the CTF
         // For now, we'll log the real CTF operations that would be executed
@@ -659,6 +1360,30 @@ impl PredictionSolver {
         }
     }
 
+    /// Price per outcome share, in 1/100000 of a dollar, that execute_core_intent_logic fills a
+    /// sell at: a merge pays net_amount USDC for the intent's shares. Buys are filled by a split,
+    /// which isn't matched against the book, so there is no price to hold them to; mints and
+    /// redemptions aren't priced either
+    fn execution_price(intent: &PredictionIntent, net_amount: u128) -> Option<u64> {
+        match intent.intent_type {
+            IntentType::SellShares if intent.amount.0 > 0 => Some((net_amount * MAX_PRICE as u128 / intent.amount.0) as u64),
+            _ => None,
+        }
+    }
+
+    /// Whether `execution_price` is worse than the intent's max_price (buys) or min_price (sells)
+    /// by more than max_slippage_bps of that price. Intents without a tolerance or a limit price
+    /// aren't checked
+    fn slippage_exceeded(intent: &PredictionIntent, execution_price: u64) -> bool {
+        let Some(max_slippage_bps) = intent.max_slippage_bps else { return false };
+        let tolerance = |price: u64| price as u128 * max_slippage_bps as u128 / 10_000;
+        match (&intent.intent_type, intent.max_price, intent.min_price) {
+            (IntentType::BuyShares, Some(max_price), _) => execution_price as u128 > max_price as u128 + tolerance(max_price),
+            (IntentType::SellShares, _, Some(min_price)) => execution_price as u128 + tolerance(min_price) < min_price as u128,
+            _ => false,
+        }
+    }
+
     fn handle_trading_intent(&mut self, intent: PredictionIntent) -> Promise {
         // Create order from intent
         let order = self.create_order_from_intent(intent.clone());
@@ -687,7 +1412,7 @@ impl PredictionSolver {
         let condition_id = format!("condition_{}", intent.market_id);
         
         // Calculate amounts after fees
-        let fee_amount = (intent.amount.0 * self.solver_fee_bps as u128) / 10000;
+        let fee_amount = self.intent_fee(&intent);
         let net_amount = intent.amount.0 - fee_amount;
         
         match intent.intent_type {
@@ -732,7 +1457,7 @@ impl PredictionSolver {
     // Synchronous minting intent handler with actual CTF integration
     fn handle_minting_intent_sync(&mut self, intent: PredictionIntent) -> ExecutionResult {
         // Calculate fees and net amounts
-        let fee_amount = (intent.amount.0 * self.solver_fee_bps as u128) / 10000;
+        let fee_amount = self.intent_fee(&intent);
         let net_amount = intent.amount.0 - fee_amount;
         
         // Get condition_id from market (would be cross-contract call in production)
@@ -770,7 +1495,7 @@ impl PredictionSolver {
     // Synchronous redemption intent handler with actual CTF integration
     fn handle_redemption_intent_sync(&mut self, intent: PredictionIntent) -> ExecutionResult {
         // Calculate fees
-        let fee_amount = (intent.amount.0 * self.solver_fee_bps as u128) / 10000;
+        let fee_amount = self.intent_fee(&intent);
         
         // Get condition_id from market (would be cross-contract call in production)
         let condition_id = format!("condition_{}", intent.market_id);
@@ -871,6 +1596,7 @@ impl PredictionSolver {
             IntentType::SellShares => OrderSide::Sell,
             _ => panic!("Invalid intent type for trading order"),
         };
+        let price_bound = Self::intent_price_bound(&intent);
 
         // Calculate price - use max_price for buy orders, min_price for sell orders
         let price = match side {
@@ -893,7 +1619,23 @@ impl PredictionSolver {
             status: OrderStatus::Pending,
             created_at: env::block_timestamp(),
             expires_at: intent.deadline,
+            price_bound,
+            market_maker: false,
+        }
+    }
+
+    /// The price a trading intent must not be filled beyond: max_price for buys, min_price for
+    /// sells. Market orders have to carry one; an unbounded market buy could fill at $0.99
+    fn intent_price_bound(intent: &PredictionIntent) -> Option<u64> {
+        let bound = match intent.intent_type {
+            IntentType::BuyShares => intent.max_price,
+            IntentType::SellShares => intent.min_price,
+            IntentType::MintComplete | IntentType::RedeemWinning => return None,
+        };
+        if matches!(intent.order_type, OrderType::Market) {
+            assert!(bound.is_some(), "Market orders need a max_price to buy or a min_price to sell");
         }
+        bound
     }
 
     fn submit_to_orderbook(&self, order: Order) -> Promise {
@@ -933,37 +1675,452 @@ impl PredictionSolver {
 
         order.status = OrderStatus::Cancelled;
         self.active_orders.insert(&order_id, &order);
+        self.bump_order_sequence(&order_id);
+        self.record_order_transition(&order);
 
         env::log_str(&format!("Order {} cancelled", order_id));
     }
 
-    pub fn update_order_fill(&mut self, order_id: String, filled_amount: U128) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.orderbook_authority,
-            "Only orderbook authority can update fills"
-        );
-
+    /// Shrink an open order to `new_amount` without cancelling the rest. The new amount may not
+    /// go below what is already filled; reducing to exactly that closes the order as Filled.
+    /// Bumps the order's sequence so fill reports made against the old amount are rejected
+    pub fn reduce_order(&mut self, order_id: String, new_amount: U128) {
         let mut order = self.active_orders.get(&order_id)
             .expect("Order not found");
-        
-        order.filled_amount = filled_amount;
-        
-        if filled_amount >= order.amount {
+
+        assert_eq!(env::predecessor_account_id(), order.user, "Only order owner can reduce");
+        assert!(
+            matches!(order.status, OrderStatus::Pending | OrderStatus::PartiallyFilled),
+            "Cannot reduce filled or cancelled order"
+        );
+        assert!(new_amount.0 < order.amount.0, "New amount must be below the current amount {}", order.amount.0);
+        assert!(
+            new_amount.0 >= order.filled_amount.0,
+            "New amount cannot be below the filled amount {}", order.filled_amount.0
+        );
+
+        let previous_amount = order.amount;
+        order.amount = new_amount;
+        if new_amount.0 == order.filled_amount.0 {
             order.status = OrderStatus::Filled;
-        } else if filled_amount.0 > 0 {
-            order.status = OrderStatus::PartiallyFilled;
         }
-
         self.active_orders.insert(&order_id, &order);
-    }
+        let sequence = self.bump_order_sequence(&order_id);
+        self.record_order_transition(&order);
 
-    // View methods
-    pub fn get_order(&self, order_id: String) -> Option<Order> {
-        self.active_orders.get(&order_id)
+        env::log_str(&format!(
+            "EVENT_JSON:{{\"standard\":\"prediction_solver\",\"version\":\"1.0.0\",\"event\":\"order_reduced\",\"data\":[{{\"order_id\":\"{}\",\"user\":\"{}\",\"previous_amount\":\"{}\",\"new_amount\":\"{}\",\"filled_amount\":\"{}\",\"sequence\":{}}}]}}",
+            order_id, order.user, previous_amount.0, new_amount.0, order.filled_amount.0, sequence
+        ));
     }
 
-    pub fn get_user_orders(&self, user: AccountId) -> Vec<Order> {
+    /// Place up to MAX_BULK_ORDERS resting orders for the calling market maker without an intent per
+    /// order. Every order is checked before any is created, so one bad order rejects the batch.
+    /// Market existence isn't checked: the solver only knows markets through the verifier's
+    /// get_market, which is a cross-contract call. Returns the new order ids in batch order
+    pub fn place_bulk_orders(&mut self, orders: Vec<NewOrderParams>) -> Vec<String> {
+        let maker = env::predecessor_account_id();
+        assert!(self.market_makers.contains(&maker), "Only approved market makers can place bulk orders");
+        assert!(!orders.is_empty(), "No orders to place");
+        assert!(orders.len() <= MAX_BULK_ORDERS, "At most {} orders per batch", MAX_BULK_ORDERS);
+
+        let now = env::block_timestamp();
+        for (i, params) in orders.iter().enumerate() {
+            assert!(params.outcome <= 1, "Order {}: invalid outcome {}", i, params.outcome);
+            assert!(
+                params.price > 0 && params.price < MAX_PRICE,
+                "Order {}: price must be between 1 and {}", i, MAX_PRICE - 1
+            );
+            assert!(
+                params.amount.0 >= self.min_order_size.0,
+                "Order {}: amount is below the minimum order size {}", i, self.min_order_size.0
+            );
+            assert!(
+                matches!(params.order_type, OrderType::Limit | OrderType::GTC | OrderType::GTD),
+                "Order {}: only Limit, GTC and GTD orders can rest on the book", i
+            );
+            assert!(params.expires_at > now, "Order {}: already expired", i);
+        }
+
+        // Ids count the maker's orders, so they stay unique across batches in the same block
+        let mut user_orders = self.user_orders.get(&maker).unwrap_or_default();
+        let mut order_ids = Vec::with_capacity(orders.len());
+        for params in orders {
+            let order_id = format!("mm_{}_{}", maker, user_orders.len());
+            let order = Order {
+                order_id: order_id.clone(),
+                intent_id: String::new(),
+                user: maker.clone(),
+                market_id: params.market_id,
+                condition_id: String::new(), // Will be filled by orderbook
+                outcome: params.outcome,
+                side: params.side,
+                order_type: params.order_type,
+                price: params.price,
+                amount: params.amount,
+                filled_amount: U128(0),
+                status: OrderStatus::Pending,
+                created_at: now,
+                expires_at: params.expires_at,
+                // A quote is never filled beyond its own price
+                price_bound: Some(params.price),
+                market_maker: true,
+            };
+            self.active_orders.insert(&order_id, &order);
+            user_orders.push(order_id.clone());
+            order_ids.push(order_id);
+        }
+        self.user_orders.insert(&maker, &user_orders);
+
+        env::log_str(&format!(
+            "EVENT_JSON:{{\"standard\":\"prediction_solver\",\"version\":\"1.0.0\",\"event\":\"bulk_orders_placed\",\"data\":[{{\"maker\":\"{}\",\"order_ids\":{}}}]}}",
+            maker, near_sdk::serde_json::to_string(&order_ids).unwrap()
+        ));
+        order_ids
+    }
+
+    /// Cancel several of the caller's open orders at once; like place_bulk_orders, the whole batch
+    /// is rejected if any order can't be cancelled
+    pub fn cancel_bulk_orders(&mut self, order_ids: Vec<String>) {
+        let caller = env::predecessor_account_id();
+        assert!(!order_ids.is_empty(), "No orders to cancel");
+        assert!(order_ids.len() <= MAX_BULK_ORDERS, "At most {} orders per batch", MAX_BULK_ORDERS);
+
+        let mut orders = Vec::with_capacity(order_ids.len());
+        for (i, order_id) in order_ids.iter().enumerate() {
+            assert!(!order_ids[..i].contains(order_id), "Order {} listed twice", order_id);
+            let order = self.active_orders.get(order_id)
+                .unwrap_or_else(|| env::panic_str(&format!("Order {} not found", order_id)));
+            assert_eq!(caller, order.user, "Only order owner can cancel");
+            assert!(
+                matches!(order.status, OrderStatus::Pending | OrderStatus::PartiallyFilled),
+                "Cannot cancel filled or cancelled order {}", order_id
+            );
+            orders.push(order);
+        }
+
+        for mut order in orders {
+            order.status = OrderStatus::Cancelled;
+            self.active_orders.insert(&order.order_id, &order);
+            self.bump_order_sequence(&order.order_id);
+            self.record_order_transition(&order);
+        }
+
+        env::log_str(&format!(
+            "EVENT_JSON:{{\"standard\":\"prediction_solver\",\"version\":\"1.0.0\",\"event\":\"bulk_orders_cancelled\",\"data\":[{{\"maker\":\"{}\",\"order_ids\":{}}}]}}",
+            caller, near_sdk::serde_json::to_string(&order_ids).unwrap()
+        ));
+    }
+
+    /// Record a fill reported by the orderbook. `expected_amount` is the order amount the book
+    /// matched against; a report made before the owner reduced the order is rejected.
+    /// `fill_price` is required for orders with a price bound and may not cross it. `is_maker`
+    /// says the order was resting on the book; its new fills then earn the maker rebate
+    pub fn update_order_fill(&mut self, order_id: String, filled_amount: U128, expected_amount: Option<U128>, fill_price: Option<u64>, is_maker: bool) {
+        self.assert_orderbook_authority();
+
+        let mut order = self.active_orders.get(&order_id)
+            .expect("Order not found");
+        if let Some(expected_amount) = expected_amount {
+            assert_eq!(
+                expected_amount.0, order.amount.0,
+                "Stale fill report: order {} amount is now {}", order_id, order.amount.0
+            );
+        }
+        if let Some(bound) = order.price_bound {
+            let fill_price = fill_price
+                .unwrap_or_else(|| env::panic_str(&format!("Fill price required for order {}", order_id)));
+            match order.side {
+                OrderSide::Buy => assert!(
+                    fill_price <= bound,
+                    "Fill price {} is above the max price {} of order {}", fill_price, bound, order_id
+                ),
+                OrderSide::Sell => assert!(
+                    fill_price >= bound,
+                    "Fill price {} is below the min price {} of order {}", fill_price, bound, order_id
+                ),
+            }
+        }
+        
+        let newly_filled = filled_amount.0.saturating_sub(order.filled_amount.0);
+        order.filled_amount = filled_amount;
+        
+        if filled_amount >= order.amount {
+            order.status = OrderStatus::Filled;
+        } else if filled_amount.0 > 0 {
+            order.status = OrderStatus::PartiallyFilled;
+        }
+
+        self.active_orders.insert(&order_id, &order);
+        self.bump_order_sequence(&order_id);
+        self.record_order_transition(&order);
+        if is_maker {
+            self.credit_maker_rebate(&order, newly_filled, fill_price.unwrap_or(order.price));
+        } else {
+            self.charge_taker_fee(&order, newly_filled, fill_price.unwrap_or(order.price));
+        }
+    }
+
+    /// Record a trade the orderbook executed, linking it to whichever of its two orders the solver
+    /// holds so it shows up in get_intent_trace. Fills are still reported through update_order_fill
+    pub fn record_trade(&mut self, trade: TradeExecution) {
+        self.assert_orderbook_authority();
+        assert!(self.trades.get(&trade.trade_id).is_none(), "Trade {} already recorded", trade.trade_id);
+        assert!(trade.amount.0 > 0, "Trade amount must be positive");
+
+        let mut order_ids = vec![trade.maker_order_id.clone(), trade.taker_order_id.clone()];
+        order_ids.dedup();
+        order_ids.retain(|order_id| self.active_orders.get(order_id).is_some());
+        assert!(!order_ids.is_empty(), "Trade {} matches no solver order", trade.trade_id);
+
+        for order_id in &order_ids {
+            let mut trade_ids = self.order_trades.get(order_id).unwrap_or_default();
+            trade_ids.push(trade.trade_id.clone());
+            self.order_trades.insert(order_id, &trade_ids);
+        }
+        self.trades.insert(&trade.trade_id, &trade);
+
+        env::log_str(&format!(
+            "EVENT_JSON:{{\"standard\":\"prediction_solver\",\"version\":\"1.0.0\",\"event\":\"trade_recorded\",\"data\":[{{\"trade_id\":\"{}\",\"order_ids\":{},\"price\":{},\"amount\":\"{}\"}}]}}",
+            trade.trade_id, near_sdk::serde_json::to_string(&order_ids).unwrap(), trade.price, trade.amount.0
+        ));
+    }
+
+    /// Charge the taker fee on `filled` shares traded at `price` out of the USDC the order's intent
+    /// brought in. Orders without deposited USDC behind them pay nothing here
+    fn charge_taker_fee(&mut self, order: &Order, filled: u128, price: u64) {
+        let Some(deposit) = self.intent_usdc.get(&order.intent_id) else { return };
+        let notional = filled * price as u128 / MAX_PRICE as u128;
+        let fee = (notional * self.taker_fee_bps as u128 / 10_000).min(deposit.0);
+        if fee == 0 {
+            return;
+        }
+
+        self.intent_usdc.insert(&order.intent_id, &U128(deposit.0 - fee));
+        self.taker_fee_pool = U128(self.taker_fee_pool.0 + fee);
+        env::log_str(&format!(
+            "Taker fee of {} charged to {} for order {} ({} filled at {})",
+            fee, order.user, order.order_id, filled, price
+        ));
+    }
+
+    /// Credit the rebate on `filled` shares of a resting order traded at `price`, out of the taker
+    /// fees collected so far. Only a negative maker_rebate_bps pays out here; a positive maker fee
+    /// is charged by the orderbook
+    fn credit_maker_rebate(&mut self, order: &Order, filled: u128, price: u64) {
+        // Market, FOK and FAK orders never rest on the book
+        if self.maker_rebate_bps >= 0 || !matches!(order.order_type, OrderType::Limit | OrderType::GTC | OrderType::GTD) {
+            return;
+        }
+        let notional = filled * price as u128 / MAX_PRICE as u128;
+        let earned = notional * self.maker_rebate_bps.unsigned_abs() as u128 / 10_000;
+        let rebate = earned.min(self.taker_fee_pool.0);
+        if rebate < earned {
+            env::log_str(&format!(
+                "Maker rebate for order {} short by {}: not covered by collected taker fees",
+                order.order_id, earned - rebate
+            ));
+        }
+        if rebate == 0 {
+            return;
+        }
+
+        self.taker_fee_pool = U128(self.taker_fee_pool.0 - rebate);
+        let balance = self.rebate_balances.get(&order.user).map_or(0, |balance| balance.0) + rebate;
+        self.rebate_balances.insert(&order.user, &U128(balance));
+        env::log_str(&format!(
+            "Maker rebate of {} credited to {} for order {} ({} filled at {})",
+            rebate, order.user, order.order_id, filled, price
+        ));
+    }
+
+    /// Transfer the caller's accrued maker rebates in USDC
+    pub fn claim_maker_rebate(&mut self) -> Promise {
+        let account = env::predecessor_account_id();
+        let amount = self.rebate_balances.remove(&account).expect("No maker rebate to claim");
+
+        env::log_str(&format!("Maker rebate of {} claimed by {}", amount.0, account));
+        ext_fungible_token::ext(self.usdc_contract.clone())
+            .with_attached_deposit(near_sdk::NearToken::from_yoctonear(1))
+            .with_static_gas(near_sdk::Gas::from_tgas(USDC_FT_TRANSFER_TGAS))
+            .ft_transfer(account.clone(), amount, Some("Maker rebate".to_string()))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(near_sdk::Gas::from_tgas(5))
+                    .on_maker_rebate_claimed(account, amount)
+            )
+    }
+
+    /// Credit the rebate back if the USDC transfer failed
+    #[private]
+    pub fn on_maker_rebate_claimed(&mut self, account: AccountId, amount: U128) -> bool {
+        if let near_sdk::PromiseResult::Failed = env::promise_result(0) {
+            let balance = self.rebate_balances.get(&account).map_or(0, |balance| balance.0) + amount.0;
+            self.rebate_balances.insert(&account, &U128(balance));
+            env::log_str(&format!("Maker rebate transfer to {} failed; {} credited back", account, amount.0));
+            return false;
+        }
+        true
+    }
+
+    pub fn get_taker_fee_pool(&self) -> U128 {
+        self.taker_fee_pool
+    }
+
+    pub fn get_maker_rebate_balance(&self, account: AccountId) -> U128 {
+        self.rebate_balances.get(&account).unwrap_or(U128(0))
+    }
+
+    /// Number of changes (fills, reductions, cancellation) made to an order; 0 if unknown
+    pub fn get_order_sequence(&self, order_id: String) -> u64 {
+        self.order_sequences.get(&order_id).unwrap_or(0)
+    }
+
+    fn bump_order_sequence(&mut self, order_id: &String) -> u64 {
+        let sequence = self.order_sequences.get(order_id).unwrap_or(0) + 1;
+        self.order_sequences.insert(order_id, &sequence);
+        sequence
+    }
+
+    /// Append a stage to the intent's log unless it is already the latest one. A full log drops
+    /// its oldest entry after Registered
+    fn record_intent_transition(&mut self, intent_id: &String, stage: IntentStage) {
+        let mut transitions = self.intent_transitions.get(intent_id).unwrap_or_default();
+        if matches!(transitions.last(), Some(last) if last.stage == stage) {
+            return;
+        }
+        if transitions.len() >= MAX_INTENT_TRANSITIONS {
+            transitions.remove(1);
+        }
+        transitions.push(IntentTransition { stage, at: env::block_timestamp() });
+        self.intent_transitions.insert(intent_id, &transitions);
+    }
+
+    /// Log an order's new status against the intent behind it; market maker orders have none
+    fn record_order_transition(&mut self, order: &Order) {
+        if order.intent_id.is_empty() {
+            return;
+        }
+        let stage = match order.status {
+            OrderStatus::Pending => return,
+            OrderStatus::PartiallyFilled => IntentStage::PartiallyFilled,
+            OrderStatus::Filled => IntentStage::Filled,
+            OrderStatus::Cancelled => IntentStage::Cancelled,
+            OrderStatus::Expired => IntentStage::Expired,
+        };
+        self.record_intent_transition(&order.intent_id, stage);
+    }
+
+    /// Sell everything a buy order has filled once the outcome trades at `trigger_price` or
+    /// higher. Setting it again replaces the trigger and picks up any newer fills
+    pub fn set_take_profit(&mut self, order_id: String, trigger_price: u64) {
+        let order = self.active_orders.get(&order_id)
+            .expect("Order not found");
+
+        assert_eq!(env::predecessor_account_id(), order.user, "Only order owner can set take profit");
+        assert!(matches!(order.side, OrderSide::Buy), "Take profit only applies to buy orders");
+        assert!(order.filled_amount.0 > 0, "Order has no filled shares to sell");
+        assert!(
+            trigger_price > order.price && trigger_price < MAX_PRICE,
+            "Trigger price must be above the order price {} and below {}", order.price, MAX_PRICE
+        );
+
+        let take_profit = TakeProfitOrder {
+            position_id: format!("{}:{}", order.market_id, order.outcome),
+            trigger_price,
+            sell_amount: order.filled_amount,
+        };
+        self.take_profit_orders.insert(&order_id, &take_profit);
+
+        env::log_str(&format!(
+            "Take profit set on order {}: sell {} of {} at {}",
+            order_id, take_profit.sell_amount.0, take_profit.position_id, trigger_price
+        ));
+    }
+
+    pub fn cancel_take_profit(&mut self, order_id: String) {
+        let order = self.active_orders.get(&order_id)
+            .expect("Order not found");
+        assert_eq!(env::predecessor_account_id(), order.user, "Only order owner can cancel take profit");
+        self.take_profit_orders.remove(&order_id).expect("No take profit set on order");
+
+        env::log_str(&format!("Take profit on order {} cancelled", order_id));
+    }
+
+    pub fn get_take_profit(&self, order_id: String) -> Option<TakeProfitOrder> {
+        self.take_profit_orders.get(&order_id)
+    }
+
+    /// Called by the daemon after each trade with the market's YES price (NO trades at
+    /// 100000 - price). Every take profit the price reaches becomes a SellShares intent queued
+    /// for the daemon like one from the verifier; the intents are returned so the daemon can
+    /// submit them right away. Triggered take profits are removed
+    pub fn check_take_profit_triggers(&mut self, market_id: String, current_price: u64) -> Vec<PredictionIntent> {
+        let caller = env::predecessor_account_id();
+        assert!(
+            self.authorized_daemons.contains(&caller) || caller == self.owner_id,
+            "Only authorized daemons or owner can check take profits"
+        );
+        assert!(current_price <= MAX_PRICE, "Price cannot exceed {}", MAX_PRICE);
+
+        let triggered: Vec<(String, Order, TakeProfitOrder)> = self.take_profit_orders
+            .iter()
+            .filter_map(|(order_id, take_profit)| {
+                let order = self.active_orders.get(&order_id)?;
+                let outcome_price = if order.outcome == 1 { current_price } else { MAX_PRICE - current_price };
+                (order.market_id == market_id && outcome_price >= take_profit.trigger_price)
+                    .then_some((order_id, order, take_profit))
+            })
+            .collect();
+
+        let mut intents = Vec::with_capacity(triggered.len());
+        for (order_id, order, take_profit) in triggered {
+            self.take_profit_orders.remove(&order_id);
+
+            let intent = PredictionIntent {
+                intent_id: format!("tp_{}_{}", order_id, env::block_timestamp()),
+                user: order.user.clone(),
+                market_id: order.market_id.clone(),
+                intent_type: IntentType::SellShares,
+                outcome: order.outcome,
+                amount: take_profit.sell_amount,
+                max_price: None,
+                min_price: Some(take_profit.trigger_price),
+                deadline: env::block_timestamp() + self.intent_timeout,
+                order_type: OrderType::Limit,
+                cross_chain: None,
+                max_slippage_bps: None,
+            };
+            let sell_order = Order {
+                order_id: format!("order_{}", intent.intent_id),
+                condition_id: order.condition_id.clone(),
+                ..self.create_order_from_intent(intent.clone())
+            };
+
+            self.active_orders.insert(&sell_order.order_id, &sell_order);
+            let mut user_orders = self.user_orders.get(&order.user).unwrap_or_default();
+            user_orders.push(sell_order.order_id.clone());
+            self.user_orders.insert(&order.user, &user_orders);
+            self.pending_for_daemon.insert(&intent.intent_id);
+            self.pending_since.insert(&intent.intent_id, &env::block_timestamp());
+            self.record_intent_transition(&intent.intent_id, IntentStage::Registered);
+
+            env::log_str(&format!(
+                "EVENT_JSON:{{\"standard\":\"prediction_solver\",\"version\":\"1.0.0\",\"event\":\"take_profit_triggered\",\"data\":[{{\"order_id\":\"{}\",\"user\":\"{}\",\"intent_id\":\"{}\",\"trigger_price\":{},\"current_price\":{},\"sell_amount\":\"{}\"}}]}}",
+                order_id, order.user, intent.intent_id, take_profit.trigger_price, current_price, take_profit.sell_amount.0
+            ));
+            intents.push(intent);
+        }
+
+        intents
+    }
+
+    // View methods
+    pub fn get_order(&self, order_id: String) -> Option<Order> {
+        self.active_orders.get(&order_id)
+    }
+
+    pub fn get_user_orders(&self, user: AccountId) -> Vec<Order> {
         let order_ids = self.user_orders.get(&user).unwrap_or_default();
         let mut orders = Vec::new();
         
@@ -984,23 +2141,298 @@ impl PredictionSolver {
         self.active_orders.len()
     }
 
+    /// Page through every order the solver holds, cancelled and filled ones included, in
+    /// storage order. The orderbook's reconciliation job compares these against its own book
+    pub fn get_active_orders_paginated(&self, from_index: u64, limit: u64) -> Vec<Order> {
+        self.active_orders
+            .values_as_vector()
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
     pub fn is_intent_processed(&self, intent_id: String) -> bool {
         self.processed_intents.contains(&intent_id)
     }
 
+    /// The intent's order, trades, daemon completion and stage history in one call. None if the
+    /// solver never saw the intent. Orders with more than MAX_TRACE_TRADES trades embed the
+    /// first ones; total_trades tells support tooling to page the rest with get_order_trades
+    pub fn get_intent_trace(&self, intent_id: String) -> Option<IntentTrace> {
+        let order_id = format!("order_{}", intent_id);
+        let order = self.active_orders.get(&order_id);
+        let transitions = self.intent_transitions.get(&intent_id).unwrap_or_default();
+        if order.is_none() && transitions.is_empty() && !self.processed_intents.contains(&intent_id) {
+            return None;
+        }
+
+        let trade_ids = self.order_trades.get(&order_id).unwrap_or_default();
+        Some(IntentTrace {
+            order,
+            pending_for_daemon: self.pending_for_daemon.contains(&intent_id),
+            processed: self.processed_intents.contains(&intent_id),
+            responsible_daemon: self.intent_daemon_assignments.get(&intent_id),
+            completion: self.intent_completions.get(&intent_id),
+            transitions,
+            trades: trade_ids.iter()
+                .take(MAX_TRACE_TRADES)
+                .filter_map(|trade_id| self.trades.get(trade_id))
+                .collect(),
+            total_trades: trade_ids.len() as u64,
+            intent_id,
+        })
+    }
+
+    /// An order's recorded trades, oldest first
+    pub fn get_order_trades(&self, order_id: String, from_index: u64, limit: u64) -> Vec<TradeExecution> {
+        self.order_trades.get(&order_id)
+            .unwrap_or_default()
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .filter_map(|trade_id| self.trades.get(trade_id))
+            .collect()
+    }
+
+    pub fn get_solver_config(&self) -> SolverConfig {
+        SolverConfig {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            owner_id: self.owner_id.clone(),
+            verifier_contract: self.verifier_contract.clone(),
+            ctf_contract: self.ctf_contract.clone(),
+            usdc_contract: self.usdc_contract.clone(),
+            orderbook_authority: self.orderbook_authority.clone(),
+            solver_fee_bps: self.solver_fee_bps,
+            bridge_fee_bps: self.bridge_fee_bps,
+            min_order_size: self.min_order_size,
+            cross_chain_enabled: self.cross_chain_enabled,
+            monitor_contract: self.monitor_contract.clone(),
+            authorized_daemons: self.authorized_daemons.to_vec(),
+            intent_timeout: self.intent_timeout,
+            completion_sla: self.completion_sla,
+            fee_tiers: self.fee_tiers.clone(),
+            maker_rebate_bps: self.maker_rebate_bps,
+            taker_fee_bps: self.taker_fee_bps,
+        }
+    }
+
+    pub fn get_health(&self) -> SolverHealth {
+        SolverHealth {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            version: format!("{}-v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            active_orders: self.active_orders.len(),
+            pending_for_daemon: self.pending_for_daemon.len(),
+            processed_intents: self.processed_intents.len(),
+        }
+    }
+
+    // Ownership & roles
+    /// Propose a new owner; nothing changes until they call accept_ownership
+    pub fn propose_owner(&mut self, new_owner: AccountId) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can propose a new owner");
+        assert!(new_owner != self.owner_id, "Account is already the owner");
+
+        self.pending_owner = Some(new_owner.clone());
+        self.emit_role_event("owner_proposed", Some(&new_owner));
+    }
+
+    pub fn accept_ownership(&mut self) {
+        let caller = env::predecessor_account_id();
+        assert_eq!(self.pending_owner.as_ref(), Some(&caller), "Only the proposed owner can accept ownership");
+
+        self.owner_id = caller.clone();
+        self.pending_owner = None;
+        self.emit_role_event("ownership_transferred", Some(&caller));
+    }
+
+    /// Config admin can tune the solver and bridge fees but has no other owner powers; None removes the role
+    pub fn set_config_admin(&mut self, config_admin: Option<AccountId>) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can set config admin");
+
+        self.config_admin = config_admin.clone();
+        self.emit_role_event("config_admin_set", config_admin.as_ref());
+    }
+
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner.clone()
+    }
+
+    pub fn get_config_admin(&self) -> Option<AccountId> {
+        self.config_admin.clone()
+    }
+
+    fn assert_config_admin(&self, message: &str) {
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == self.owner_id || self.config_admin.as_ref() == Some(&caller),
+            "{}", message
+        );
+    }
+
+    fn emit_role_event(&self, event: &str, account: Option<&AccountId>) {
+        let account = account.map(|a| format!("\"{}\"", a)).unwrap_or_else(|| "null".to_string());
+        env::log_str(&format!(
+            "EVENT_JSON:{{\"standard\":\"prediction_solver\",\"version\":\"1.0.0\",\"event\":\"{}\",\"data\":[{{\"by\":\"{}\",\"account\":{}}}]}}",
+            event, env::predecessor_account_id(), account
+        ));
+    }
+
     // Configuration
     pub fn update_solver_fee(&mut self, fee_bps: u16) {
-        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can update fee");
+        self.assert_config_admin("Only owner or config admin can update fee");
         assert!(fee_bps <= 500, "Solver fee cannot exceed 5%"); // 500 bps = 5%
         
         self.solver_fee_bps = fee_bps;
         env::log_str(&format!("Solver fee updated to {} bps", fee_bps));
     }
 
+    pub fn get_solver_fee_bps(&self) -> u16 {
+        self.solver_fee_bps
+    }
+
+    /// Set the maker/taker fee model. A negative maker_rebate_bps is a rebate and may not
+    /// exceed the taker fee, so rebates stay funded by takers
+    pub fn update_maker_taker_fees(&mut self, maker_rebate_bps: i16, taker_fee_bps: u16) {
+        self.assert_config_admin("Only owner or config admin can update maker and taker fees");
+        assert!(taker_fee_bps <= 500, "Taker fee cannot exceed 5%");
+        assert!(maker_rebate_bps <= 500, "Maker fee cannot exceed 5%");
+        assert!(
+            maker_rebate_bps >= -(taker_fee_bps as i16),
+            "Maker rebate cannot exceed the taker fee of {} bps", taker_fee_bps
+        );
+
+        self.maker_rebate_bps = maker_rebate_bps;
+        self.taker_fee_bps = taker_fee_bps;
+        env::log_str(&format!("Maker fee updated to {} bps, taker fee to {} bps", maker_rebate_bps, taker_fee_bps));
+    }
+
+    pub fn get_maker_rebate_bps(&self) -> i16 {
+        self.maker_rebate_bps
+    }
+
+    pub fn get_taker_fee_bps(&self) -> u16 {
+        self.taker_fee_bps
+    }
+
+    /// Replace the volume fee tiers; thresholds strictly ascending. An empty table charges
+    /// everyone solver_fee_bps
+    pub fn set_fee_tiers(&mut self, tiers: Vec<FeeTier>) {
+        self.assert_config_admin("Only owner or config admin can update fee tiers");
+        assert!(tiers.len() <= MAX_FEE_TIERS, "Too many fee tiers (max {})", MAX_FEE_TIERS);
+        for (i, tier) in tiers.iter().enumerate() {
+            assert!(tier.volume_threshold.0 > 0, "Tier threshold must be positive");
+            assert!(tier.fee_bps <= 500, "Solver fee cannot exceed 5%");
+            if i > 0 {
+                assert!(
+                    tier.volume_threshold.0 > tiers[i - 1].volume_threshold.0,
+                    "Tier thresholds must be strictly ascending"
+                );
+            }
+        }
+
+        self.fee_tiers = tiers;
+        env::log_str(&format!("Fee tiers updated: {:?}", self.fee_tiers));
+    }
+
+    pub fn get_fee_tiers(&self) -> Vec<FeeTier> {
+        self.fee_tiers.clone()
+    }
+
+    /// Fee the account's next intent is charged at, from its trailing 30-day volume
+    pub fn get_user_fee_bps(&self, account: AccountId) -> u16 {
+        self.effective_fee_bps(&account)
+    }
+
+    /// Volume of the account's successfully completed intents over the last 30 days
+    pub fn get_user_trailing_volume(&self, account: AccountId) -> U128 {
+        U128(self.trailing_volume(&account))
+    }
+
+    /// Rotate to `new_authority` now; the old authority keeps working for the grace period
     pub fn update_orderbook_authority(&mut self, new_authority: AccountId) {
+        self.set_pending_orderbook_authority(new_authority, env::block_timestamp());
+    }
+
+    /// Schedule an orderbook authority rotation. Before `effective_at` only the current
+    /// authority is accepted, then both until `effective_at + authority_grace_period`
+    pub fn set_pending_orderbook_authority(&mut self, new_authority: AccountId, effective_at: u64) {
         assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can update authority");
-        self.orderbook_authority = new_authority;
-        env::log_str(&format!("Orderbook authority updated to {}", self.orderbook_authority));
+        assert!(effective_at >= env::block_timestamp(), "Rotation cannot take effect in the past");
+
+        self.settle_authority_rotation();
+        assert!(self.pending_authority_rotation.is_none(), "Authority rotation already in progress");
+        assert!(new_authority != self.orderbook_authority, "Account is already the orderbook authority");
+
+        let rotation = AuthorityRotation {
+            previous: self.orderbook_authority.clone(),
+            next: new_authority,
+            effective_at,
+            requested_by: env::predecessor_account_id(),
+            requested_at: env::block_timestamp(),
+        };
+        self.authority_rotations.push(rotation.clone());
+
+        env::log_str(&format!(
+            "Orderbook authority rotation scheduled: {} -> {} at {}",
+            rotation.previous, rotation.next, effective_at
+        ));
+        self.emit_role_event("orderbook_authority_rotation_scheduled", Some(&rotation.next));
+        self.pending_authority_rotation = Some(rotation);
+    }
+
+    pub fn update_authority_grace_period(&mut self, grace_period: u64) {
+        self.assert_config_admin("Only owner or config admin can update authority grace period");
+        self.authority_grace_period = grace_period;
+        env::log_str(&format!("Authority grace period updated to {} ns", grace_period));
+    }
+
+    pub fn get_orderbook_authority_info(&self) -> OrderbookAuthorityInfo {
+        let (authority, pending_rotation) = match &self.pending_authority_rotation {
+            Some(rotation) if self.rotation_completed(rotation) => (rotation.next.clone(), None),
+            pending => (self.orderbook_authority.clone(), pending.clone()),
+        };
+
+        OrderbookAuthorityInfo {
+            authority,
+            pending_rotation,
+            grace_period: self.authority_grace_period,
+            history: self.authority_rotations.clone(),
+        }
+    }
+
+    fn rotation_completed(&self, rotation: &AuthorityRotation) -> bool {
+        env::block_timestamp() >= rotation.effective_at.saturating_add(self.authority_grace_period)
+    }
+
+    // Drop the old authority once its grace period is over
+    fn settle_authority_rotation(&mut self) {
+        if let Some(rotation) = self.pending_authority_rotation.clone() {
+            if self.rotation_completed(&rotation) {
+                self.orderbook_authority = rotation.next;
+                self.pending_authority_rotation = None;
+                env::log_str(&format!("Orderbook authority rotated to {}", self.orderbook_authority));
+            }
+        }
+    }
+
+    fn assert_orderbook_authority(&mut self) {
+        self.settle_authority_rotation();
+        let caller = env::predecessor_account_id();
+        if caller == self.orderbook_authority {
+            return;
+        }
+
+        match &self.pending_authority_rotation {
+            Some(rotation) if rotation.next == caller => assert!(
+                env::block_timestamp() >= rotation.effective_at,
+                "Orderbook authority {} is not effective until {}", caller, rotation.effective_at
+            ),
+            _ if self.authority_rotations.iter().any(|rotation| rotation.previous == caller) => {
+                env::panic_str(&format!("Orderbook authority {} was rotated out", caller))
+            }
+            _ => env::panic_str("Only orderbook authority can update fills"),
+        }
     }
 
     // Cross-chain management functions
@@ -1015,7 +2447,7 @@ impl PredictionSolver {
     }
 
     pub fn update_bridge_fee(&mut self, fee_bps: u16) {
-        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can update bridge fee");
+        self.assert_config_admin("Only owner or config admin can update bridge fee");
         assert!(fee_bps <= 200, "Bridge fee cannot exceed 2%"); // 200 bps = 2%
         self.bridge_fee_bps = fee_bps;
         env::log_str(&format!("Bridge fee updated to {} bps", fee_bps));
@@ -1034,17 +2466,46 @@ impl PredictionSolver {
         (U128(base_fee), U128(bridge_fee), U128(total_fee))
     }
     
+    /// Estimate the gas cost of redeeming `amount` winning tokens in `market_id`
+    /// min_profitable_amount is the position size where solver fee + gas equals 5% of the payout;
+    /// below it the user is better off holding until gas drops
+    pub fn estimate_redemption_gas(&self, market_id: String, amount: U128) -> GasEstimate {
+        assert!(!market_id.is_empty(), "Market ID cannot be empty");
+        assert!(amount.0 > 0, "Amount must be positive");
+
+        let estimated_tgas = VERIFIER_INTENT_TGAS + SOLVER_REDEMPTION_TGAS + CTF_REDEEM_TGAS + USDC_FT_TRANSFER_TGAS;
+        let estimated_near_cost = estimated_tgas as u128 * 1_000_000_000_000 * MIN_GAS_PRICE;
+
+        // Convert yoctoNEAR (24 decimals) to USDC base units (6 decimals)
+        let gas_cost_usdc = estimated_near_cost * NEAR_USDC_PRICE / 1_000_000_000_000_000_000_000_000;
+
+        // amount * fee_bps / 10000 + gas_cost = amount * 500 / 10000
+        let margin_bps = REDEMPTION_COST_CAP_BPS.saturating_sub(self.solver_fee_bps as u128);
+        let min_profitable_amount = if margin_bps == 0 {
+            u128::MAX // solver fee alone already takes the whole 5%
+        } else {
+            (gas_cost_usdc * 10000 + margin_bps - 1) / margin_bps
+        };
+
+        GasEstimate {
+            estimated_tgas,
+            estimated_near_cost: U128(estimated_near_cost),
+            min_profitable_amount: U128(min_profitable_amount),
+        }
+    }
+
     /// Bridge configuration is handled by the verifier contract and JavaScript relayer
     /// This solver focuses on intent execution and settlement
     
-    /// Execute cross-chain return using NEAR Bridge SDK
+    /// Check the return can be bridged and hand it to the relayer, which reports back through
+    /// confirm_return or fail_return
     fn execute_cross_chain_return(
         &self,
         target_chain_id: u64,
         target_user: String,
         target_token: String,
         amount: U128,
-    ) -> Result<String, String> {
+    ) -> Result<(), String> {
         if let Some(config) = &self.bridge_config {
             // Check if chain is supported
             if !config.supported_chains.contains(&target_chain_id) {
@@ -1067,21 +2528,12 @@ impl PredictionSolver {
                 }
             };
             
-            // Simulate bridge transaction (in production this would call actual bridge)
-            env::log_str(&format!(
-                "🌉 Simulating bridge return: {} tokens to {} on chain {} via {}",
-                amount.0, target_user, target_chain_id, rpc_url
-            ));
-            
-            // Generate a mock transaction hash for testing
-            let tx_hash = format!("0x{:x}", env::block_timestamp());
-            
             env::log_str(&format!(
-                "✅ Simulated return bridge to {} on chain {}: {}",
-                target_user, target_chain_id, tx_hash
+                "🌉 Return bridge requested: {} of {} to {} on chain {} via {}",
+                amount.0, target_token, target_user, target_chain_id, rpc_url
             ));
             
-            Ok(tx_hash)
+            Ok(())
         } else {
             let error_msg = "Bridge not configured - cannot execute cross-chain return";
             env::log_str(&format!("⚠️ {}", error_msg));
@@ -1133,10 +2585,11 @@ impl PredictionSolver {
         params: &CrossChainParams,
         monitor_contract: AccountId,
     ) {
-        // In production, this would make a cross-contract call to the monitor
+        // In production: cross-contract call to monitor.start_bridge_transaction(), passing
+        // intent.intent_id so the monitor can index the transfer under the intent
         env::log_str(&format!(
-            "📊 Starting monitoring for cross-chain intent {} ({}->NEAR)",
-            intent.intent_id, params.source_chain_id
+            "📊 Starting monitoring on {} for cross-chain intent {} ({}->NEAR)",
+            monitor_contract, intent.intent_id, params.source_chain_id
         ));
     }
     
@@ -1153,7 +2606,8 @@ impl PredictionSolver {
                 "📈 Updating monitor status for {}: {:?}",
                 intent_id, status
             ));
-            // In production: cross-contract call to monitor.update_status()
+            // In production: monitor.get_bridge_transactions_for_intent(intent_id) resolves the tx
+            // hashes, then a cross-contract call to monitor.update_transaction_status()
         }
     }
     
@@ -1184,28 +2638,154 @@ impl PredictionSolver {
     pub fn get_monitor_contract(&self) -> Option<AccountId> {
         self.monitor_contract.clone()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use near_sdk::test_utils::VMContextBuilder;
-    use near_sdk::{testing_env, VMContext};
-    use crate::verifier::{CrossChainParams, CrossChainIntent};
+    // Return-to-source obligations
+    /// Relayer report that the return bridge landed on the source chain. A failed return the
+    /// relayer retried successfully may be confirmed too
+    pub fn confirm_return(&mut self, intent_id: String, tx_hash: String) {
+        self.assert_return_relayer();
+        let mut obligation = self.return_obligations.get(&intent_id).expect("No return obligation for intent");
+        assert!(
+            matches!(obligation.status, ReturnStatus::Pending | ReturnStatus::Failed),
+            "Return is already {:?}", obligation.status
+        );
 
-    fn get_context(predecessor: &str) -> VMContext {
-        VMContextBuilder::new()
-            .predecessor_account_id(predecessor.parse().unwrap())
-            .block_timestamp(1000000000000000000)
-            .build()
+        obligation.status = ReturnStatus::Confirmed;
+        obligation.tx_hash = Some(tx_hash.clone());
+        obligation.failure_reason = None;
+        obligation.updated_at = env::block_timestamp();
+        self.return_obligations.insert(&intent_id, &obligation);
+
+        env::log_str(&format!("Return for intent {} confirmed on chain {}: {}", intent_id, obligation.chain_id, tx_hash));
+        self.update_monitoring_status(&intent_id, BridgeStatus::Completed, Some(tx_hash), None);
     }
 
-    #[test]
-    fn test_cross_chain_solver_initialization() {
-        testing_env!(get_context("alice.testnet"));
-        
-        let contract = PredictionSolver::new(
-            "owner.testnet".parse().unwrap(),
+    pub fn fail_return(&mut self, intent_id: String, reason: String) {
+        self.assert_return_relayer();
+        let mut obligation = self.return_obligations.get(&intent_id).expect("No return obligation for intent");
+        assert_eq!(obligation.status, ReturnStatus::Pending, "Only a pending return can fail");
+
+        obligation.status = ReturnStatus::Failed;
+        obligation.failure_reason = Some(reason.clone());
+        obligation.updated_at = env::block_timestamp();
+        self.return_obligations.insert(&intent_id, &obligation);
+
+        env::log_str(&format!("Return for intent {} failed: {}", intent_id, reason));
+        self.handle_cross_chain_failure(&intent_id, &reason, FailureCode::BridgeTimeout);
+    }
+
+    /// Take an unconfirmed return as USDC on NEAR instead, once return_timeout has passed
+    pub fn claim_return(&mut self, intent_id: String) -> Promise {
+        let obligation = self.return_obligations.get(&intent_id).expect("No return obligation for intent");
+        assert_eq!(env::predecessor_account_id(), obligation.near_account, "Only the intent user can claim the return");
+        self.pay_out_return(obligation, env::predecessor_account_id())
+    }
+
+    /// Owner-mediated payout of an unconfirmed return, e.g. to an account the user proved they control
+    pub fn payout_return(&mut self, intent_id: String, receiver: AccountId) -> Promise {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can pay out returns");
+        let obligation = self.return_obligations.get(&intent_id).expect("No return obligation for intent");
+        self.pay_out_return(obligation, receiver)
+    }
+
+    fn pay_out_return(&mut self, mut obligation: ReturnObligation, receiver: AccountId) -> Promise {
+        assert!(
+            matches!(obligation.status, ReturnStatus::Pending | ReturnStatus::Failed),
+            "Return is already {:?}", obligation.status
+        );
+        assert!(
+            env::block_timestamp() >= obligation.created_at + self.return_timeout,
+            "Return can be paid out on NEAR after {}", obligation.created_at + self.return_timeout
+        );
+
+        let previous_status = obligation.status.clone();
+        obligation.status = ReturnStatus::Claimed;
+        obligation.updated_at = env::block_timestamp();
+        self.return_obligations.insert(&obligation.intent_id, &obligation);
+
+        env::log_str(&format!("Return for intent {} paid out on NEAR to {}: {}", obligation.intent_id, receiver, obligation.amount.0));
+        ext_fungible_token::ext(self.usdc_contract.clone())
+            .with_attached_deposit(near_sdk::NearToken::from_yoctonear(1))
+            .with_static_gas(near_sdk::Gas::from_tgas(10))
+            .ft_transfer(receiver, obligation.amount, Some(format!("Return payout for intent {}", obligation.intent_id)))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(near_sdk::Gas::from_tgas(5))
+                    .on_return_paid_out(obligation.intent_id, previous_status)
+            )
+    }
+
+    /// Reopen the obligation if the USDC transfer failed
+    #[private]
+    pub fn on_return_paid_out(&mut self, intent_id: String, previous_status: ReturnStatus) -> bool {
+        if let near_sdk::PromiseResult::Failed = env::promise_result(0) {
+            if let Some(mut obligation) = self.return_obligations.get(&intent_id) {
+                obligation.status = previous_status;
+                obligation.updated_at = env::block_timestamp();
+                self.return_obligations.insert(&intent_id, &obligation);
+            }
+            env::log_str(&format!("Return payout for intent {} failed; obligation reopened", intent_id));
+            return false;
+        }
+        true
+    }
+
+    // The JavaScript relayer runs under an authorized daemon account
+    fn assert_return_relayer(&self) {
+        let caller = env::predecessor_account_id();
+        assert!(
+            self.authorized_daemons.contains(&caller) || caller == self.owner_id,
+            "Only authorized daemons or owner can report returns"
+        );
+    }
+
+    pub fn update_return_timeout(&mut self, timeout: u64) {
+        self.assert_config_admin("Only owner or config admin can update return timeout");
+        assert!(timeout >= 3_600_000_000_000, "Return timeout too short (min 1 hour)");
+
+        self.return_timeout = timeout;
+        env::log_str(&format!("Return timeout updated to {} ns", timeout));
+    }
+
+    pub fn get_return_timeout(&self) -> u64 {
+        self.return_timeout
+    }
+
+    pub fn get_return_obligation(&self, intent_id: String) -> Option<ReturnObligation> {
+        self.return_obligations.get(&intent_id)
+    }
+
+    /// Pending and failed returns, for the relayer to work through
+    pub fn get_outstanding_returns(&self, from_index: u64, limit: u64) -> Vec<ReturnObligation> {
+        self.return_obligations
+            .values()
+            .filter(|obligation| matches!(obligation.status, ReturnStatus::Pending | ReturnStatus::Failed))
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{testing_env, VMContext};
+    use crate::verifier::{CrossChainParams, CrossChainIntent};
+
+    fn get_context(predecessor: &str) -> VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(predecessor.parse().unwrap())
+            .block_timestamp(1000000000000000000)
+            .build()
+    }
+
+    #[test]
+    fn test_cross_chain_solver_initialization() {
+        testing_env!(get_context("alice.testnet"));
+        
+        let contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
             "verifier.testnet".parse().unwrap(),
             "ctf.testnet".parse().unwrap(),
             "usdc.testnet".parse().unwrap(),
@@ -1218,6 +2798,35 @@ mod tests {
         assert_eq!(contract.get_bridge_fee_bps(), 50); // Default 0.5% bridge fee
     }
 
+    #[test]
+    fn test_config_and_health_snapshots() {
+        testing_env!(get_context("owner.testnet"));
+
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+        contract.authorize_daemon("daemon.testnet".parse().unwrap());
+        contract.set_monitor_contract("monitor.testnet".parse().unwrap());
+
+        let config = contract.get_solver_config();
+        assert_eq!(config.schema_version, SNAPSHOT_SCHEMA_VERSION);
+        assert_eq!(config.usdc_contract, "usdc.testnet".parse::<AccountId>().unwrap());
+        assert_eq!(config.solver_fee_bps, 100);
+        assert_eq!(config.min_order_size, U128(1_000_000));
+        assert_eq!(config.monitor_contract, Some("monitor.testnet".parse().unwrap()));
+        assert_eq!(config.authorized_daemons, vec!["daemon.testnet".parse::<AccountId>().unwrap()]);
+
+        let health = contract.get_health();
+        assert_eq!(health.version, "prediction-solver-v0.1.0");
+        assert_eq!((health.active_orders, health.pending_for_daemon, health.processed_intents), (0, 0, 0));
+    }
+
     #[test]
     fn test_cross_chain_fee_calculation() {
         testing_env!(get_context("alice.testnet"));
@@ -1241,6 +2850,27 @@ mod tests {
         assert_eq!(total_fee.0, 1_500_000); // Total = 1.5 USDC
     }
 
+    #[test]
+    fn test_estimate_redemption_gas() {
+        testing_env!(get_context("alice.testnet"));
+
+        let contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100, // 1% solver fee
+            U128(1_000_000),
+        );
+
+        let estimate = contract.estimate_redemption_gas("market_1".to_string(), U128(10_000_000));
+        assert_eq!(estimate.estimated_tgas, 90);
+        assert_eq!(estimate.estimated_near_cost.0, 9_000_000_000_000_000_000_000); // 0.009 NEAR
+        // 0.027 USDC of gas / 4% margin left after the 1% fee = 0.675 USDC
+        assert_eq!(estimate.min_profitable_amount.0, 675_000);
+    }
+
     #[test]
     fn test_cross_chain_intent_processing() {
         testing_env!(get_context("verifier.testnet"));
@@ -1276,6 +2906,7 @@ mod tests {
             deadline: 2000000000000000000,
             order_type: OrderType::Limit,
             cross_chain: Some(cross_chain_params),
+            max_slippage_bps: None,
         };
 
         let result = contract.solve_intent(intent);
@@ -1327,6 +2958,7 @@ mod tests {
                 deadline: 1900000000000000000,
                 order_type: OrderType::Market,
                 cross_chain: Some(cross_chain_params),
+                max_slippage_bps: None,
             };
 
             let result = contract.solve_intent(intent);
@@ -1430,6 +3062,7 @@ mod tests {
             deadline: 2000000000000000000,
             order_type: OrderType::Market,
             cross_chain: Some(cross_chain_params),
+            max_slippage_bps: None,
         };
 
         // This should panic due to amount below bridge minimum
@@ -1473,6 +3106,7 @@ mod tests {
             deadline: 2000000000000000000,
             order_type: OrderType::Market,
             cross_chain: Some(cross_chain_params),
+            max_slippage_bps: None,
         };
 
         let result = contract.solve_intent(intent);
@@ -1482,4 +3116,1274 @@ mod tests {
         assert!(result.execution_details.contains("from chain 137"));
         // The return logic is triggered during execution
     }
+
+    #[test]
+    #[should_panic(expected = "Only owner can authorize daemons")]
+    fn test_solver_ownership_transfer() {
+        testing_env!(get_context("owner.testnet"));
+
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+
+        contract.propose_owner("new-owner.testnet".parse().unwrap());
+        // Still the owner until the proposal is accepted
+        contract.authorize_daemon("daemon.testnet".parse().unwrap());
+
+        testing_env!(get_context("new-owner.testnet"));
+        contract.accept_ownership();
+        contract.revoke_daemon("daemon.testnet".parse().unwrap());
+
+        testing_env!(get_context("owner.testnet"));
+        contract.authorize_daemon("daemon.testnet".parse().unwrap());
+    }
+
+    #[test]
+    fn test_solver_config_admin_updates_fees() {
+        testing_env!(get_context("owner.testnet"));
+
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+        contract.set_config_admin(Some("admin.testnet".parse().unwrap()));
+
+        testing_env!(get_context("admin.testnet"));
+        contract.update_solver_fee(150);
+        contract.update_bridge_fee(75);
+        assert_eq!(contract.solver_fee_bps, 150);
+        assert_eq!(contract.get_bridge_fee_bps(), 75);
+    }
+
+    #[test]
+    fn test_refund_timed_out_intent() {
+        testing_env!(get_context("verifier.testnet"));
+
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+
+        let intent = PredictionIntent {
+            intent_id: "slow_intent".to_string(),
+            user: "alice.testnet".parse().unwrap(),
+            market_id: "market_1".to_string(),
+            intent_type: IntentType::BuyShares,
+            outcome: 1,
+            amount: U128(10_000_000),
+            max_price: Some(50000),
+            min_price: None,
+            deadline: 2000000000000000000,
+            order_type: OrderType::Limit,
+            cross_chain: None,
+            max_slippage_bps: None,
+        };
+        contract.solve_intent(intent);
+        assert_eq!(contract.get_pending_for_daemon(), vec!["slow_intent".to_string()]);
+
+        // Registering the intent alone doesn't make any USDC refundable
+        assert!(contract.intent_usdc.get(&"slow_intent".to_string()).is_none());
+
+        // The deposit only counts when the verifier forwards it through the USDC contract
+        testing_env!(get_context("usdc.testnet"));
+        let forged = contract.ft_on_transfer("alice.testnet".parse().unwrap(), U128(10_000_000), "slow_intent".to_string());
+        assert!(matches!(forged, PromiseOrValue::Value(U128(10_000_000))));
+        let funded = contract.ft_on_transfer("verifier.testnet".parse().unwrap(), U128(10_000_000), "slow_intent".to_string());
+        assert!(matches!(funded, PromiseOrValue::Value(U128(0))));
+        assert_eq!(contract.intent_usdc.get(&"slow_intent".to_string()), Some(U128(10_000_000)));
+
+        // Past the 5 minute timeout
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("alice.testnet".parse().unwrap())
+            .block_timestamp(1000000000000000000 + DEFAULT_INTENT_TIMEOUT + 1)
+            .build());
+        let order = contract.get_order("order_slow_intent".to_string()).unwrap();
+        assert!(matches!(contract.refund_timed_out_intent("slow_intent".to_string()), PromiseOrValue::Promise(_)));
+
+        assert!(contract.get_pending_for_daemon().is_empty());
+        assert!(contract.get_order("order_slow_intent".to_string()).is_none());
+        assert!(!contract.is_intent_processed("slow_intent".to_string()));
+
+        // A failed transfer puts the intent back so the refund can be retried
+        testing_env!(
+            get_context("solver.testnet"),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![near_sdk::PromiseResult::Failed]
+        );
+        let refunded = contract.on_timed_out_refund("slow_intent".to_string(), order, 1000000000000000000, None, U128(10_000_000));
+        assert_eq!(refunded, U128(0));
+        assert_eq!(contract.get_pending_for_daemon(), vec!["slow_intent".to_string()]);
+        assert!(contract.get_order("order_slow_intent".to_string()).is_some());
+        assert_eq!(contract.intent_usdc.get(&"slow_intent".to_string()), Some(U128(10_000_000)));
+    }
+
+    #[test]
+    fn test_unfunded_intent_refunds_nothing() {
+        testing_env!(get_context("verifier.testnet"));
+
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+
+        // Submitted through the verifier's plain path: no USDC ever reached the solver
+        contract.solve_intent(PredictionIntent {
+            intent_id: "unfunded_intent".to_string(),
+            user: "alice.testnet".parse().unwrap(),
+            market_id: "market_1".to_string(),
+            intent_type: IntentType::BuyShares,
+            outcome: 1,
+            amount: U128(10_000_000),
+            max_price: Some(50000),
+            min_price: None,
+            deadline: 2000000000000000000,
+            order_type: OrderType::Limit,
+            cross_chain: None,
+            max_slippage_bps: None,
+        });
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("alice.testnet".parse().unwrap())
+            .block_timestamp(1000000000000000000 + DEFAULT_INTENT_TIMEOUT + 1)
+            .build());
+        let refund = contract.refund_timed_out_intent("unfunded_intent".to_string());
+        assert!(matches!(refund, PromiseOrValue::Value(U128(0))));
+        assert!(contract.get_pending_for_daemon().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent has not timed out yet")]
+    fn test_refund_before_timeout() {
+        testing_env!(get_context("verifier.testnet"));
+
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+
+        contract.solve_intent(PredictionIntent {
+            intent_id: "fresh_intent".to_string(),
+            user: "alice.testnet".parse().unwrap(),
+            market_id: "market_1".to_string(),
+            intent_type: IntentType::BuyShares,
+            outcome: 1,
+            amount: U128(10_000_000),
+            max_price: Some(50000),
+            min_price: None,
+            deadline: 2000000000000000000,
+            order_type: OrderType::Limit,
+            cross_chain: None,
+            max_slippage_bps: None,
+        });
+
+        testing_env!(get_context("alice.testnet"));
+        contract.refund_timed_out_intent("fresh_intent".to_string());
+    }
+
+    #[test]
+    fn test_daemon_stats_and_sla_breaches() {
+        testing_env!(get_context("verifier.testnet"));
+
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+
+        for intent_id in ["fast_intent", "failed_intent", "stuck_intent"] {
+            contract.solve_intent(PredictionIntent {
+                intent_id: intent_id.to_string(),
+                user: "alice.testnet".parse().unwrap(),
+                market_id: "market_1".to_string(),
+                intent_type: IntentType::BuyShares,
+                outcome: 1,
+                amount: U128(10_000_000),
+                max_price: Some(50000),
+                min_price: None,
+                deadline: 2000000000000000000,
+                order_type: OrderType::Limit,
+                cross_chain: None,
+                max_slippage_bps: None,
+            });
+        }
+
+        testing_env!(get_context("owner.testnet"));
+        contract.authorize_daemon("daemon.testnet".parse().unwrap());
+
+        let result = |intent_id: &str, success: bool| ExecutionResult {
+            intent_id: intent_id.to_string(),
+            success,
+            output_amount: None,
+            fee_amount: U128(0),
+            execution_details: String::new(),
+        };
+
+        // Daemon reports two intents 10s and 30s after dispatch
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("daemon.testnet".parse().unwrap())
+            .block_timestamp(1000000000000000000 + 10_000_000_000)
+            .build());
+        contract.complete_intent("fast_intent".to_string(), result("fast_intent", true));
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("daemon.testnet".parse().unwrap())
+            .block_timestamp(1000000000000000000 + 30_000_000_000)
+            .build());
+        contract.complete_intent("failed_intent".to_string(), result("failed_intent", false));
+
+        let stats = contract.get_daemon_stats("daemon.testnet".parse().unwrap()).unwrap();
+        assert_eq!(stats.intents_completed, 1);
+        assert_eq!(stats.failures_reported, 1);
+        assert_eq!(stats.average_completion_time, 20_000_000_000);
+        assert_eq!(stats.last_active, 1000000000000000000 + 30_000_000_000);
+        assert_eq!(contract.get_all_daemon_stats().len(), 1);
+
+        // Still inside the 1 minute SLA
+        assert!(contract.get_sla_breaches(10).is_empty());
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(1000000000000000000 + DEFAULT_COMPLETION_SLA + 1)
+            .build());
+        let breaches = contract.get_sla_breaches(10);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].intent_id, "stuck_intent");
+    }
+
+    #[test]
+    fn test_get_stuck_intents() {
+        testing_env!(get_context("verifier.testnet"));
+
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+
+        for intent_id in ["abandoned_intent", "unclaimed_intent"] {
+            contract.solve_intent(PredictionIntent {
+                intent_id: intent_id.to_string(),
+                user: "alice.testnet".parse().unwrap(),
+                market_id: "market_1".to_string(),
+                intent_type: IntentType::BuyShares,
+                outcome: 1,
+                amount: U128(10_000_000),
+                max_price: Some(50000),
+                min_price: None,
+                deadline: 2000000000000000000,
+                order_type: OrderType::Limit,
+                cross_chain: None,
+                max_slippage_bps: None,
+            });
+        }
+
+        testing_env!(get_context("owner.testnet"));
+        contract.authorize_daemon("daemon.testnet".parse().unwrap());
+        testing_env!(get_context("daemon.testnet"));
+        contract.acknowledge_intent("abandoned_intent".to_string());
+
+        // Nothing is past the timeout yet
+        assert_eq!(contract.get_stuck_intents_count(), 0);
+        assert_eq!(contract.get_stuck_intents(0).len(), 2);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("owner.testnet".parse().unwrap())
+            .block_timestamp(1000000000000000000 + DEFAULT_INTENT_TIMEOUT)
+            .build());
+        assert_eq!(contract.get_stuck_intents_count(), 2);
+
+        let stuck = contract.get_stuck_intents(300);
+        assert_eq!(stuck.len(), 2);
+        assert_eq!(stuck[0].age_seconds, 300);
+        let abandoned = stuck.iter().find(|s| s.intent_id == "abandoned_intent").unwrap();
+        assert_eq!(abandoned.responsible_daemon, Some("daemon.testnet".parse().unwrap()));
+        assert_eq!(abandoned.order.order_id, "order_abandoned_intent");
+        let unclaimed = stuck.iter().find(|s| s.intent_id == "unclaimed_intent").unwrap();
+        assert!(unclaimed.responsible_daemon.is_none());
+
+        assert!(contract.get_stuck_intents(301).is_empty());
+    }
+
+    #[test]
+    fn test_orderbook_authority_rotation_grace_period() {
+        testing_env!(get_context("verifier.testnet"));
+
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+
+        contract.solve_intent(PredictionIntent {
+            intent_id: "intent_1".to_string(),
+            user: "alice.testnet".parse().unwrap(),
+            market_id: "market_1".to_string(),
+            intent_type: IntentType::BuyShares,
+            outcome: 1,
+            amount: U128(10_000_000),
+            max_price: Some(50000),
+            min_price: None,
+            deadline: 2000000000000000000,
+            order_type: OrderType::Limit,
+            cross_chain: None,
+            max_slippage_bps: None,
+        });
+
+        let start = 1000000000000000000;
+        let effective_at = start + 1_000_000_000;
+        let at = |predecessor: &str, timestamp: u64| VMContextBuilder::new()
+            .predecessor_account_id(predecessor.parse().unwrap())
+            .block_timestamp(timestamp)
+            .build();
+
+        testing_env!(get_context("owner.testnet"));
+        contract.set_pending_orderbook_authority("orderbook-v2.testnet".parse().unwrap(), effective_at);
+        assert!(contract.get_orderbook_authority_info().pending_rotation.is_some());
+
+        // During the grace window both authorities report fills
+        testing_env!(at("orderbook.testnet", effective_at));
+        contract.update_order_fill("order_intent_1".to_string(), U128(1_000_000), None, Some(50000), false);
+        testing_env!(at("orderbook-v2.testnet", effective_at));
+        contract.update_order_fill("order_intent_1".to_string(), U128(2_000_000), None, Some(50000), false);
+
+        // Afterwards only the new one
+        let after_grace = effective_at + DEFAULT_AUTHORITY_GRACE_PERIOD;
+        testing_env!(at("orderbook-v2.testnet", after_grace));
+        contract.update_order_fill("order_intent_1".to_string(), U128(3_000_000), None, Some(50000), false);
+
+        let info = contract.get_orderbook_authority_info();
+        assert_eq!(info.authority, "orderbook-v2.testnet".parse::<AccountId>().unwrap());
+        assert!(info.pending_rotation.is_none());
+        assert_eq!(info.history.len(), 1);
+        assert_eq!(info.history[0].requested_by, "owner.testnet".parse::<AccountId>().unwrap());
+
+        testing_env!(at("orderbook.testnet", after_grace));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.update_order_fill("order_intent_1".to_string(), U128(4_000_000), None, Some(50000), false);
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "is not effective until")]
+    fn test_incoming_authority_rejected_before_effective_time() {
+        testing_env!(get_context("owner.testnet"));
+
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+
+        contract.set_pending_orderbook_authority("orderbook-v2.testnet".parse().unwrap(), 2000000000000000000);
+
+        testing_env!(get_context("orderbook-v2.testnet"));
+        contract.update_order_fill("order_missing".to_string(), U128(1), None, None, false);
+    }
+
+    #[test]
+    fn test_migrate_v1_state() {
+        testing_env!(get_context("solver.testnet"));
+
+        // State as the first deployed solver wrote it
+        let mut old = migration::PredictionSolverV1 {
+            owner_id: "owner.testnet".parse().unwrap(),
+            verifier_contract: "verifier.testnet".parse().unwrap(),
+            ctf_contract: "ctf.testnet".parse().unwrap(),
+            usdc_contract: "usdc.testnet".parse().unwrap(),
+            orderbook_authority: "orderbook.testnet".parse().unwrap(),
+            processed_intents: UnorderedSet::new(b"p"),
+            pending_for_daemon: UnorderedSet::new(b"d"),
+            authorized_daemons: UnorderedSet::new(b"a"),
+            active_orders: UnorderedMap::new(b"o"),
+            user_orders: UnorderedMap::new(b"u"),
+            solver_fee_bps: 100,
+            min_order_size: U128(1_000_000),
+            cross_chain_enabled: false,
+            bridge_fee_bps: 75,
+            bridge_config: None,
+            monitor_contract: None,
+        };
+        old.pending_for_daemon.insert(&"intent_1".to_string());
+        old.authorized_daemons.insert(&"daemon.testnet".parse().unwrap());
+        old.active_orders.insert(&"order_intent_1".to_string(), &migration::OrderV1 {
+            order_id: "order_intent_1".to_string(),
+            intent_id: "intent_1".to_string(),
+            user: "alice.testnet".parse().unwrap(),
+            market_id: "market_1".to_string(),
+            condition_id: String::new(),
+            outcome: 1,
+            side: OrderSide::Buy,
+            order_type: OrderType::Market,
+            price: 100000,
+            amount: U128(10_000_000),
+            filled_amount: U128(0),
+            status: OrderStatus::Pending,
+            created_at: 1000000000000000000 - 120_000_000_000,
+            expires_at: 2000000000000000000,
+        });
+        env::state_write(&old);
+
+        let contract = PredictionSolver::migrate();
+        assert_eq!(contract.get_state_version(), StateVersion::V3);
+        assert_eq!(contract.get_pending_for_daemon(), vec!["intent_1".to_string()]);
+        let order = contract.get_order("order_intent_1".to_string()).unwrap();
+        assert_eq!((order.amount, order.price_bound), (U128(10_000_000), None));
+        assert!(!contract.is_cross_chain_enabled());
+        assert_eq!(contract.get_bridge_fee_bps(), 75);
+        assert_eq!((contract.get_maker_rebate_bps(), contract.get_taker_fee_bps()), (0, 0));
+        assert_eq!(contract.get_solver_config().authorized_daemons, vec!["daemon.testnet".parse::<AccountId>().unwrap()]);
+        assert_eq!((contract.get_intent_timeout(), contract.get_completion_sla()), (DEFAULT_INTENT_TIMEOUT, DEFAULT_COMPLETION_SLA));
+        assert_eq!((contract.get_insurance_fee_bps(), contract.get_taker_fee_pool()), (DEFAULT_INSURANCE_FEE_BPS, U128(0)));
+        // The intent has waited since its order was placed, two minutes ago
+        let breaches = contract.get_sla_breaches(10);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!((breaches[0].intent_id.as_str(), breaches[0].pending_since), ("intent_1", 1000000000000000000 - 120_000_000_000));
+
+        // Already-current state passes through
+        env::state_write(&contract);
+        let again = PredictionSolver::migrate();
+        assert_eq!(again.get_state_version(), StateVersion::V3);
+        assert_eq!(again.get_pending_for_daemon(), vec!["intent_1".to_string()]);
+    }
+
+    #[test]
+    fn test_migrate_v2_orders() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+
+        // An order written before the market maker flag existed
+        let mut legacy: UnorderedMap<String, migration::OrderV2> = UnorderedMap::new(b"o");
+        legacy.insert(&"order_intent_1".to_string(), &migration::OrderV2 {
+            order_id: "order_intent_1".to_string(),
+            intent_id: "intent_1".to_string(),
+            user: "alice.testnet".parse().unwrap(),
+            market_id: "market_1".to_string(),
+            condition_id: String::new(),
+            outcome: 1,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            price: 60000,
+            amount: U128(10_000_000),
+            filled_amount: U128(2_000_000),
+            status: OrderStatus::PartiallyFilled,
+            created_at: 1000000000000000000,
+            expires_at: 2000000000000000000,
+            price_bound: Some(60000),
+        });
+        contract.active_orders = borsh::from_slice(&borsh::to_vec(&legacy).unwrap()).unwrap();
+        contract.state_version = StateVersion::V2;
+        env::state_write(&contract);
+
+        let migrated = PredictionSolver::migrate();
+        assert_eq!(migrated.get_state_version(), StateVersion::V3);
+        let order = migrated.get_order("order_intent_1".to_string()).unwrap();
+        assert_eq!((order.filled_amount, order.price_bound, order.market_maker), (U128(2_000_000), Some(60000), false));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can upgrade")]
+    fn test_upgrade_is_owner_only() {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+        let code = b"solver wasm".to_vec();
+        contract.propose_upgrade(env::sha256_array(&code).into());
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("daemon.testnet".parse().unwrap())
+            .input(code)
+            .build());
+        contract.upgrade();
+    }
+
+    fn reduce_contract() -> PredictionSolver {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+        contract.active_orders.insert(&"order_1".to_string(), &Order {
+            order_id: "order_1".to_string(),
+            intent_id: "intent_1".to_string(),
+            user: "maker.testnet".parse().unwrap(),
+            market_id: "market_1".to_string(),
+            condition_id: "condition_1".to_string(),
+            outcome: 1,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            price: 50000,
+            amount: U128(10_000_000),
+            filled_amount: U128(0),
+            status: OrderStatus::Pending,
+            created_at: 1000000000000000000,
+            expires_at: 2000000000000000000,
+            price_bound: None,
+            market_maker: false,
+        });
+        contract
+    }
+
+    #[test]
+    fn test_reduce_order_rejects_stale_fill_reports() {
+        let mut contract = reduce_contract();
+
+        testing_env!(get_context("orderbook.testnet"));
+        contract.update_order_fill("order_1".to_string(), U128(3_000_000), Some(U128(10_000_000)), None, false);
+        assert_eq!(contract.get_order_sequence("order_1".to_string()), 1);
+
+        testing_env!(get_context("maker.testnet"));
+        contract.reduce_order("order_1".to_string(), U128(6_000_000));
+        let order = contract.get_order("order_1".to_string()).unwrap();
+        assert_eq!(order.amount, U128(6_000_000));
+        assert!(matches!(order.status, OrderStatus::PartiallyFilled));
+        assert_eq!(contract.get_order_sequence("order_1".to_string()), 2);
+
+        // The book matched against the old size; its report must not overwrite the reduction
+        testing_env!(get_context("orderbook.testnet"));
+        let stale = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.update_order_fill("order_1".to_string(), U128(8_000_000), Some(U128(10_000_000)), None, false);
+        }));
+        assert!(stale.is_err());
+        assert_eq!(contract.get_order("order_1".to_string()).unwrap().filled_amount, U128(3_000_000));
+
+        contract.update_order_fill("order_1".to_string(), U128(4_000_000), Some(U128(6_000_000)), None, false);
+
+        // Reducing down to the filled amount closes the order
+        testing_env!(get_context("maker.testnet"));
+        contract.reduce_order("order_1".to_string(), U128(4_000_000));
+        assert!(matches!(contract.get_order("order_1".to_string()).unwrap().status, OrderStatus::Filled));
+        assert_eq!(contract.get_order_sequence("order_1".to_string()), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "New amount cannot be below the filled amount")]
+    fn test_reduce_order_below_filled_amount() {
+        let mut contract = reduce_contract();
+
+        testing_env!(get_context("orderbook.testnet"));
+        contract.update_order_fill("order_1".to_string(), U128(3_000_000), None, None, false);
+
+        testing_env!(get_context("maker.testnet"));
+        contract.reduce_order("order_1".to_string(), U128(2_000_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only order owner can reduce")]
+    fn test_reduce_order_is_owner_only() {
+        let mut contract = reduce_contract();
+        testing_env!(get_context("someone.testnet"));
+        contract.reduce_order("order_1".to_string(), U128(5_000_000));
+    }
+
+    #[test]
+    fn test_take_profit_triggers_sell_intent_once() {
+        let mut contract = reduce_contract();
+        contract.authorize_daemon("daemon.testnet".parse().unwrap());
+
+        testing_env!(get_context("orderbook.testnet"));
+        contract.update_order_fill("order_1".to_string(), U128(4_000_000), None, None, false);
+
+        testing_env!(get_context("maker.testnet"));
+        contract.set_take_profit("order_1".to_string(), 70000);
+        let take_profit = contract.get_take_profit("order_1".to_string()).unwrap();
+        assert_eq!(take_profit.position_id, "market_1:1");
+        assert_eq!(take_profit.sell_amount, U128(4_000_000));
+
+        testing_env!(get_context("daemon.testnet"));
+        assert!(contract.check_take_profit_triggers("market_1".to_string(), 69999).is_empty());
+        assert!(contract.check_take_profit_triggers("market_2".to_string(), 80000).is_empty());
+
+        let intents = contract.check_take_profit_triggers("market_1".to_string(), 70000);
+        assert_eq!(intents.len(), 1);
+        let intent = &intents[0];
+        assert!(matches!(intent.intent_type, IntentType::SellShares));
+        assert_eq!((intent.amount, intent.min_price), (U128(4_000_000), Some(70000)));
+        assert!(contract.get_pending_for_daemon().contains(&intent.intent_id));
+
+        let sell_order = contract.get_order(format!("order_{}", intent.intent_id)).unwrap();
+        assert!(matches!(sell_order.side, OrderSide::Sell));
+        assert_eq!((sell_order.price, sell_order.condition_id.as_str()), (70000, "condition_1"));
+        assert_eq!(contract.get_user_orders("maker.testnet".parse().unwrap()).len(), 1);
+
+        // Triggered take profits are gone, so the next trade doesn't sell again
+        assert!(contract.get_take_profit("order_1".to_string()).is_none());
+        assert!(contract.check_take_profit_triggers("market_1".to_string(), 90000).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Trigger price must be above the order price 50000")]
+    fn test_take_profit_below_entry_price() {
+        let mut contract = reduce_contract();
+
+        testing_env!(get_context("orderbook.testnet"));
+        contract.update_order_fill("order_1".to_string(), U128(4_000_000), None, None, false);
+
+        testing_env!(get_context("maker.testnet"));
+        contract.set_take_profit("order_1".to_string(), 45000);
+    }
+
+    fn market_intent(intent_id: &str, intent_type: IntentType, max_price: Option<u64>, min_price: Option<u64>) -> PredictionIntent {
+        PredictionIntent {
+            intent_id: intent_id.to_string(),
+            user: "alice.testnet".parse().unwrap(),
+            market_id: "market_1".to_string(),
+            intent_type,
+            outcome: 1,
+            amount: U128(10_000_000),
+            max_price,
+            min_price,
+            deadline: 2000000000000000000,
+            order_type: OrderType::Market,
+            cross_chain: None,
+            max_slippage_bps: None,
+        }
+    }
+
+    #[test]
+    fn test_market_order_fills_respect_price_bound() {
+        let mut contract = reduce_contract();
+        testing_env!(get_context("verifier.testnet"));
+        contract.solve_intent(market_intent("buy_1", IntentType::BuyShares, Some(55000), None));
+        contract.solve_intent(market_intent("sell_1", IntentType::SellShares, None, Some(45000)));
+        assert_eq!(contract.get_order("order_buy_1".to_string()).unwrap().price_bound, Some(55000));
+
+        testing_env!(get_context("orderbook.testnet"));
+        contract.update_order_fill("order_buy_1".to_string(), U128(5_000_000), None, Some(55000), false);
+        contract.update_order_fill("order_sell_1".to_string(), U128(5_000_000), None, Some(45000), false);
+
+        // One tick past either bound is refused and leaves the order as it was
+        for (order_id, price) in [("order_buy_1", 55001), ("order_sell_1", 44999)] {
+            let crossed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                contract.update_order_fill(order_id.to_string(), U128(10_000_000), None, Some(price), false);
+            }));
+            assert!(crossed.is_err());
+            assert_eq!(contract.get_order(order_id.to_string()).unwrap().filled_amount, U128(5_000_000));
+        }
+
+        // A bounded order can't be filled without saying at what price
+        let unpriced = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.update_order_fill("order_buy_1".to_string(), U128(10_000_000), None, None, false);
+        }));
+        assert!(unpriced.is_err());
+    }
+
+    #[test]
+    fn test_maker_fills_accrue_rebates() {
+        let mut contract = reduce_contract();
+        contract.update_maker_taker_fees(-10, 20);
+        let maker: AccountId = "maker.testnet".parse().unwrap();
+
+        // Nothing collected from takers yet, so there is nothing to rebate
+        testing_env!(get_context("orderbook.testnet"));
+        contract.update_order_fill("order_1".to_string(), U128(1_000_000), None, Some(50000), true);
+        assert_eq!(contract.get_maker_rebate_balance(maker.clone()), U128(0));
+
+        // A funded taker buying 10 shares at $0.50 pays 20 bps of $5
+        testing_env!(get_context("verifier.testnet"));
+        contract.solve_intent(market_intent("taker_intent", IntentType::BuyShares, Some(55000), None));
+        testing_env!(get_context("usdc.testnet"));
+        contract.ft_on_transfer("verifier.testnet".parse().unwrap(), U128(10_000_000), "taker_intent".to_string());
+        testing_env!(get_context("orderbook.testnet"));
+        contract.update_order_fill("order_taker_intent".to_string(), U128(10_000_000), None, Some(50000), false);
+        assert_eq!(contract.get_taker_fee_pool(), U128(10_000));
+        assert_eq!(contract.intent_usdc.get(&"taker_intent".to_string()), Some(U128(9_990_000)));
+
+        // 3 more shares resting at $0.50 are $1.50 of notional, 10 bps of which comes back
+        contract.update_order_fill("order_1".to_string(), U128(4_000_000), None, Some(50000), true);
+        assert_eq!(contract.get_maker_rebate_balance(maker.clone()), U128(1_500));
+
+        // Unfunded taker fills pay nothing, and only shares filled since the last report count
+        contract.update_order_fill("order_1".to_string(), U128(6_000_000), None, Some(50000), false);
+        assert_eq!(contract.get_taker_fee_pool(), U128(8_500));
+        contract.update_order_fill("order_1".to_string(), U128(10_000_000), None, Some(60000), true);
+        assert_eq!(contract.get_maker_rebate_balance(maker.clone()), U128(3_900));
+        assert_eq!(contract.get_taker_fee_pool(), U128(6_100));
+
+        testing_env!(get_context("maker.testnet"));
+        contract.claim_maker_rebate();
+        assert_eq!(contract.get_maker_rebate_balance(maker), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Maker rebate cannot exceed the taker fee of 20 bps")]
+    fn test_maker_rebate_must_be_funded_by_taker_fee() {
+        let mut contract = reduce_contract();
+        contract.update_maker_taker_fees(-25, 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "Market orders need a max_price to buy or a min_price to sell")]
+    fn test_market_buy_without_max_price() {
+        let mut contract = reduce_contract();
+        testing_env!(get_context("verifier.testnet"));
+        // A min_price doesn't protect a buyer
+        contract.solve_intent(market_intent("buy_1", IntentType::BuyShares, None, Some(45000)));
+    }
+
+    #[test]
+    fn test_slippage_tolerance_stops_execution() {
+        let mut contract = reduce_contract();
+
+        // 10 shares sold for 5.5 USDC is $0.55 a share; 5% under the $0.60 min_price is $0.57
+        let sell = PredictionIntent {
+            max_slippage_bps: Some(500),
+            ..market_intent("sell_1", IntentType::SellShares, None, Some(60000))
+        };
+        let result = contract.execute_core_intent_logic(&sell, 5_500_000);
+        assert!(!result.success);
+        assert!(result.output_amount.is_none());
+        assert_eq!(result.execution_details, "Slippage tolerance exceeded");
+        assert!(contract.execute_core_intent_logic(&sell, 5_800_000).success);
+
+        // Without a tolerance nothing is checked here
+        let unprotected = PredictionIntent { max_slippage_bps: None, ..sell.clone() };
+        assert!(contract.execute_core_intent_logic(&unprotected, 5_500_000).success);
+
+        // Splits aren't matched against the book, so a buy has no fill price to hold to max_price
+        let buy = PredictionIntent {
+            max_slippage_bps: Some(0),
+            ..market_intent("buy_1", IntentType::BuyShares, Some(60000), None)
+        };
+        assert_eq!(PredictionSolver::execution_price(&buy, 9_900_000), None);
+        assert!(contract.execute_core_intent_logic(&buy, 9_900_000).success);
+        assert_eq!(PredictionSolver::execution_price(&sell, 5_500_000), Some(55000));
+    }
+
+    #[test]
+    fn test_active_orders_paginated() {
+        let mut contract = reduce_contract();
+        let mut second = contract.get_order("order_1".to_string()).unwrap();
+        second.order_id = "order_2".to_string();
+        second.status = OrderStatus::Cancelled;
+        contract.active_orders.insert(&second.order_id.clone(), &second);
+
+        let first_page = contract.get_active_orders_paginated(0, 1);
+        assert_eq!(first_page.len(), 1);
+        assert_eq!(first_page[0].order_id, "order_1");
+
+        // Cancelled orders stay visible so the orderbook can see the cancel
+        let second_page = contract.get_active_orders_paginated(1, 10);
+        assert_eq!(second_page.len(), 1);
+        assert!(matches!(second_page[0].status, OrderStatus::Cancelled));
+        assert!(contract.get_active_orders_paginated(2, 10).is_empty());
+    }
+
+    fn context_at(predecessor: &str, timestamp: u64) -> VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(predecessor.parse().unwrap())
+            .block_timestamp(timestamp)
+            .build()
+    }
+
+    // Solve a 10 USDC buy for alice and have the owner report it; returns the optimistic fee
+    fn complete_buy(contract: &mut PredictionSolver, intent_id: &str, success: bool, timestamp: u64) -> U128 {
+        testing_env!(context_at("verifier.testnet", timestamp));
+        let solved = contract.solve_intent(market_intent(intent_id, IntentType::BuyShares, Some(55000), None));
+        testing_env!(context_at("owner.testnet", timestamp));
+        contract.complete_intent(intent_id.to_string(), ExecutionResult {
+            intent_id: intent_id.to_string(),
+            success,
+            output_amount: None,
+            fee_amount: solved.fee_amount,
+            execution_details: String::new(),
+        });
+        solved.fee_amount
+    }
+
+    #[test]
+    fn test_insurance_share_paid_only_for_funded_intents() {
+        let mut contract = reduce_contract();
+        let start = 1000000000000000000;
+        let paid = || near_sdk::test_utils::get_logs().iter().any(|log| log.starts_with("Paying insurance share"));
+
+        // Nothing reached the solver for this intent, so there is nothing to pay in
+        complete_buy(&mut contract, "unfunded", true, start);
+        assert!(!paid());
+
+        testing_env!(context_at("verifier.testnet", start));
+        let solved = contract.solve_intent(market_intent("funded", IntentType::BuyShares, Some(55000), None));
+        testing_env!(context_at("usdc.testnet", start));
+        contract.ft_on_transfer("verifier.testnet".parse().unwrap(), U128(10_000_000), "funded".to_string());
+        testing_env!(context_at("owner.testnet", start));
+        contract.complete_intent("funded".to_string(), ExecutionResult {
+            intent_id: "funded".to_string(),
+            success: true,
+            output_amount: None,
+            fee_amount: solved.fee_amount,
+            execution_details: String::new(),
+        });
+
+        // 1 bps of the 10 USDC deposit
+        assert!(near_sdk::test_utils::get_logs().contains(&"Paying insurance share of 1000 for intent funded".to_string()));
+        assert!(contract.intent_usdc.get(&"funded".to_string()).is_none());
+
+        contract.update_insurance_fee(50);
+        assert_eq!(contract.get_insurance_fee_bps(), 50);
+    }
+
+    #[test]
+    fn test_fee_tiers_by_trailing_volume() {
+        let mut contract = reduce_contract();
+        let alice: AccountId = "alice.testnet".parse().unwrap();
+        contract.set_fee_tiers(vec![
+            FeeTier { volume_threshold: U128(20_000_000), fee_bps: 50 },
+            FeeTier { volume_threshold: U128(50_000_000), fee_bps: 20 },
+        ]);
+        assert_eq!(contract.get_solver_config().fee_tiers.len(), 2);
+
+        // New users pay the base fee
+        assert_eq!(contract.get_user_fee_bps("bob.testnet".parse().unwrap()), 100);
+
+        let start = 1000000000000000000;
+        assert_eq!(complete_buy(&mut contract, "buy_1", true, start), U128(100_000));
+        assert_eq!(contract.get_user_fee_bps(alice.clone()), 100);
+
+        // Exactly on the threshold moves into the tier; failed intents add nothing
+        assert_eq!(complete_buy(&mut contract, "buy_2", true, start), U128(100_000));
+        assert_eq!(contract.get_user_trailing_volume(alice.clone()), U128(20_000_000));
+        assert_eq!(contract.get_user_fee_bps(alice.clone()), 50);
+        assert_eq!(complete_buy(&mut contract, "buy_3", false, start), U128(50_000));
+        assert_eq!(contract.get_user_trailing_volume(alice.clone()), U128(20_000_000));
+
+        testing_env!(get_context("owner.testnet"));
+        let unordered = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_fee_tiers(vec![
+                FeeTier { volume_threshold: U128(50_000_000), fee_bps: 20 },
+                FeeTier { volume_threshold: U128(50_000_000), fee_bps: 10 },
+            ]);
+        }));
+        assert!(unordered.is_err());
+    }
+
+    #[test]
+    fn test_fee_volume_rolls_off_after_thirty_days() {
+        let mut contract = reduce_contract();
+        let alice: AccountId = "alice.testnet".parse().unwrap();
+        contract.set_fee_tiers(vec![FeeTier { volume_threshold: U128(20_000_000), fee_bps: 50 }]);
+
+        let start = 1000000000000000000;
+        complete_buy(&mut contract, "buy_1", true, start);
+        complete_buy(&mut contract, "buy_2", true, start + DAY_NS);
+        assert_eq!(contract.user_daily_volume.get(&alice).unwrap().len(), 2);
+        assert_eq!(contract.get_user_fee_bps(alice.clone()), 50);
+
+        // The first day's bucket leaves the window 30 days later
+        testing_env!(context_at("alice.testnet", start + 29 * DAY_NS));
+        assert_eq!(contract.get_user_fee_bps(alice.clone()), 50);
+        testing_env!(context_at("alice.testnet", start + 30 * DAY_NS));
+        assert_eq!(contract.get_user_trailing_volume(alice.clone()), U128(10_000_000));
+        assert_eq!(contract.get_user_fee_bps(alice.clone()), 100);
+
+        // The next completion prunes it from storage
+        complete_buy(&mut contract, "buy_3", true, start + 30 * DAY_NS);
+        let buckets = contract.user_daily_volume.get(&alice).unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(contract.get_user_fee_bps(alice), 50);
+    }
+
+    fn queue_return(contract: &mut PredictionSolver, intent_id: &str) -> ExecutionResult {
+        let params = crate::CrossChainParams {
+            source_chain_id: 137,
+            source_user: "0x1234567890123456789012345678901234567890".to_string(),
+            source_token: "0xusdc".to_string(),
+            bridge_min_amount: U128(1_000_000),
+            return_to_source: true,
+        };
+        let mut result = ExecutionResult {
+            intent_id: intent_id.to_string(),
+            success: true,
+            output_amount: Some(U128(9_000_000)),
+            fee_amount: U128(0),
+            execution_details: "filled".to_string(),
+        };
+        contract.handle_cross_chain_return(&market_intent(intent_id, IntentType::BuyShares, Some(55000), None), &params, &mut result);
+        result
+    }
+
+    #[test]
+    fn test_return_obligation_confirmed_by_relayer() {
+        let mut contract = reduce_contract();
+        contract.authorize_daemon("relayer.testnet".parse().unwrap());
+        contract.bridge_config = Some(SimpleBridgeConfig {
+            ethereum_rpc: "https://eth.example".to_string(),
+            polygon_rpc: "https://polygon.example".to_string(),
+            supported_chains: vec![1, 137],
+        });
+
+        let result = queue_return(&mut contract, "intent_return");
+        assert!(result.execution_details.ends_with("Return bridge queued for relayer"));
+        let obligation = contract.get_return_obligation("intent_return".to_string()).unwrap();
+        assert_eq!((obligation.status, obligation.amount), (ReturnStatus::Pending, U128(9_000_000)));
+        assert_eq!(contract.get_outstanding_returns(0, 10).len(), 1);
+
+        let outsider = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            testing_env!(get_context("mallory.testnet"));
+            contract.confirm_return("intent_return".to_string(), "0xabc".to_string());
+        }));
+        assert!(outsider.is_err());
+
+        testing_env!(get_context("relayer.testnet"));
+        contract.fail_return("intent_return".to_string(), "bridge congested".to_string());
+        assert_eq!(contract.get_return_obligation("intent_return".to_string()).unwrap().status, ReturnStatus::Failed);
+
+        // A retried return can still be confirmed after failing
+        contract.confirm_return("intent_return".to_string(), "0xabc".to_string());
+        let obligation = contract.get_return_obligation("intent_return".to_string()).unwrap();
+        assert_eq!(obligation.status, ReturnStatus::Confirmed);
+        assert_eq!(obligation.tx_hash, Some("0xabc".to_string()));
+        assert!(contract.get_outstanding_returns(0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_unconfirmed_return_claimable_after_timeout() {
+        let mut contract = reduce_contract();
+        // No bridge configured: the return fails immediately but the payout is still owed
+        queue_return(&mut contract, "intent_return");
+        let obligation = contract.get_return_obligation("intent_return".to_string()).unwrap();
+        assert_eq!(obligation.status, ReturnStatus::Failed);
+
+        let too_early = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            testing_env!(get_context("alice.testnet"));
+            contract.claim_return("intent_return".to_string());
+        }));
+        assert!(too_early.is_err());
+
+        testing_env!(context_at("alice.testnet", obligation.created_at + DEFAULT_RETURN_TIMEOUT));
+        contract.claim_return("intent_return".to_string());
+        assert_eq!(contract.get_return_obligation("intent_return".to_string()).unwrap().status, ReturnStatus::Claimed);
+
+        let twice = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            testing_env!(get_context("owner.testnet"));
+            contract.payout_return("intent_return".to_string(), "alice.testnet".parse().unwrap());
+        }));
+        assert!(twice.is_err());
+    }
+
+    fn maker_contract() -> PredictionSolver {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+        contract.add_market_maker("mm.testnet".parse().unwrap());
+        contract
+    }
+
+    fn quote(side: OrderSide, price: u64, amount: u128) -> NewOrderParams {
+        NewOrderParams {
+            market_id: "market_1".to_string(),
+            outcome: 1,
+            side,
+            order_type: OrderType::GTC,
+            price,
+            amount: U128(amount),
+            expires_at: 2000000000000000000,
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Only approved market makers can place bulk orders")]
+    fn test_bulk_orders_require_approved_maker() {
+        let mut contract = maker_contract();
+        assert!(contract.is_market_maker("mm.testnet".parse().unwrap()));
+        contract.remove_market_maker("mm.testnet".parse().unwrap());
+        assert!(contract.get_market_makers().is_empty());
+
+        testing_env!(get_context("mm.testnet"));
+        contract.place_bulk_orders(vec![quote(OrderSide::Buy, 45000, 5_000_000)]);
+    }
+
+    #[test]
+    fn test_bulk_orders_round_trip_through_user_orders() {
+        let mut contract = maker_contract();
+        let maker: AccountId = "mm.testnet".parse().unwrap();
+
+        testing_env!(get_context("mm.testnet"));
+        let order_ids = contract.place_bulk_orders(vec![
+            quote(OrderSide::Buy, 45000, 5_000_000),
+            quote(OrderSide::Sell, 55000, 5_000_000),
+        ]);
+        let more = contract.place_bulk_orders(vec![quote(OrderSide::Buy, 44000, 2_000_000)]);
+        assert_eq!(order_ids, vec!["mm_mm.testnet_0".to_string(), "mm_mm.testnet_1".to_string()]);
+        assert_eq!(more, vec!["mm_mm.testnet_2".to_string()]);
+
+        let orders = contract.get_user_orders(maker.clone());
+        let listed: Vec<String> = orders.iter().map(|order| order.order_id.clone()).collect();
+        assert_eq!(listed, [order_ids.clone(), more].concat());
+        assert!(orders.iter().all(|order| order.market_maker && order.intent_id.is_empty()));
+        assert_eq!((orders[1].price, orders[1].price_bound), (55000, Some(55000)));
+
+        contract.cancel_bulk_orders(order_ids.clone());
+        for order in contract.get_user_orders(maker) {
+            let cancelled = matches!(order.status, OrderStatus::Cancelled);
+            assert_eq!(cancelled, order_ids.contains(&order.order_id));
+        }
+        assert_eq!(contract.get_order_sequence(order_ids[0].clone()), 1);
+    }
+
+    #[test]
+    fn test_bulk_orders_reject_the_whole_batch() {
+        let mut contract = maker_contract();
+        let maker: AccountId = "mm.testnet".parse().unwrap();
+        testing_env!(get_context("mm.testnet"));
+
+        // The second order is below min_order_size, so the first isn't placed either
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.place_bulk_orders(vec![
+                quote(OrderSide::Buy, 45000, 5_000_000),
+                quote(OrderSide::Buy, 44000, 999_999),
+            ]);
+        }));
+        assert!(result.is_err());
+        assert!(contract.get_user_orders(maker.clone()).is_empty());
+
+        let market_order = NewOrderParams { order_type: OrderType::Market, ..quote(OrderSide::Buy, 45000, 5_000_000) };
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.place_bulk_orders(vec![market_order]);
+        }));
+        assert!(result.is_err());
+
+        // Cancels are all-or-nothing too: another user's order fails the batch
+        let placed = contract.place_bulk_orders(vec![quote(OrderSide::Buy, 45000, 5_000_000)]);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.cancel_bulk_orders(vec![placed[0].clone(), "order_someone_else".to_string()]);
+        }));
+        assert!(result.is_err());
+        assert!(matches!(contract.get_order(placed[0].clone()).unwrap().status, OrderStatus::Pending));
+    }
+
+    fn trace_contract() -> PredictionSolver {
+        testing_env!(get_context("owner.testnet"));
+        let mut contract = PredictionSolver::new(
+            "owner.testnet".parse().unwrap(),
+            "verifier.testnet".parse().unwrap(),
+            "ctf.testnet".parse().unwrap(),
+            "usdc.testnet".parse().unwrap(),
+            "orderbook.testnet".parse().unwrap(),
+            100,
+            U128(1_000_000),
+        );
+        contract.authorize_daemon("daemon.testnet".parse().unwrap());
+
+        testing_env!(get_context("verifier.testnet"));
+        contract.solve_intent(PredictionIntent {
+            intent_id: "intent_1".to_string(),
+            user: "alice.testnet".parse().unwrap(),
+            market_id: "market_1".to_string(),
+            intent_type: IntentType::BuyShares,
+            outcome: 1,
+            amount: U128(10_000_000),
+            max_price: Some(50000),
+            min_price: None,
+            deadline: 2000000000000000000,
+            order_type: OrderType::Limit,
+            cross_chain: None,
+            max_slippage_bps: None,
+        });
+        contract
+    }
+
+    fn context_at(predecessor: &str, offset: u64) -> VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(predecessor.parse().unwrap())
+            .block_timestamp(1000000000000000000 + offset)
+            .build()
+    }
+
+    fn trade(trade_id: &str, taker_order_id: &str, amount: u128) -> TradeExecution {
+        TradeExecution {
+            trade_id: trade_id.to_string(),
+            maker_order_id: "book_order".to_string(),
+            taker_order_id: taker_order_id.to_string(),
+            market_id: "market_1".to_string(),
+            condition_id: "condition_1".to_string(),
+            outcome: 1,
+            price: 50000,
+            amount: U128(amount),
+            trade_type: TradeType::DirectMatch,
+            maker: "bob.testnet".parse().unwrap(),
+            taker: "alice.testnet".parse().unwrap(),
+            executed_at: 1000000000000000000,
+        }
+    }
+
+    #[test]
+    fn test_intent_trace_follows_partially_filled_intent() {
+        let mut contract = trace_contract();
+        assert!(contract.get_intent_trace("intent_unknown".to_string()).is_none());
+
+        // Registered only: order resting, nothing filled, no daemon yet
+        let trace = contract.get_intent_trace("intent_1".to_string()).unwrap();
+        assert!(matches!(trace.order.as_ref().unwrap().status, OrderStatus::Pending));
+        assert!(trace.pending_for_daemon && !trace.processed);
+        assert!(trace.responsible_daemon.is_none() && trace.completion.is_none());
+        assert_eq!(trace.transitions, vec![IntentTransition { stage: IntentStage::Registered, at: 1000000000000000000 }]);
+        assert!(trace.trades.is_empty());
+
+        testing_env!(context_at("daemon.testnet", 5_000_000_000));
+        contract.acknowledge_intent("intent_1".to_string());
+
+        testing_env!(context_at("orderbook.testnet", 10_000_000_000));
+        contract.update_order_fill("order_intent_1".to_string(), U128(3_000_000), None, Some(50000), false);
+        contract.record_trade(trade("trade_0", "order_intent_1", 3_000_000));
+
+        // A second partial fill doesn't add another PartiallyFilled entry
+        testing_env!(context_at("orderbook.testnet", 20_000_000_000));
+        contract.update_order_fill("order_intent_1".to_string(), U128(5_000_000), None, Some(50000), false);
+        contract.record_trade(trade("trade_1", "order_intent_1", 2_000_000));
+
+        let trace = contract.get_intent_trace("intent_1".to_string()).unwrap();
+        let order = trace.order.unwrap();
+        assert!(matches!(order.status, OrderStatus::PartiallyFilled));
+        assert_eq!(order.filled_amount, U128(5_000_000));
+        assert_eq!(trace.responsible_daemon, Some("daemon.testnet".parse().unwrap()));
+        assert!(trace.pending_for_daemon && trace.completion.is_none());
+        assert_eq!(trace.transitions, vec![
+            IntentTransition { stage: IntentStage::Registered, at: 1000000000000000000 },
+            IntentTransition { stage: IntentStage::Acknowledged, at: 1000000000000000000 + 5_000_000_000 },
+            IntentTransition { stage: IntentStage::PartiallyFilled, at: 1000000000000000000 + 10_000_000_000 },
+        ]);
+        let trade_ids: Vec<String> = trace.trades.into_iter().map(|trade| trade.trade_id).collect();
+        assert_eq!(trade_ids, vec!["trade_0".to_string(), "trade_1".to_string()]);
+        assert_eq!(trace.total_trades, 2);
+    }
+
+    #[test]
+    fn test_intent_trace_pages_trades_and_records_completion() {
+        let mut contract = trace_contract();
+
+        testing_env!(context_at("orderbook.testnet", 10_000_000_000));
+        for i in 0..25u128 {
+            contract.update_order_fill("order_intent_1".to_string(), U128((i + 1) * 400_000), None, Some(50000), false);
+            contract.record_trade(trade(&format!("trade_{}", i), "order_intent_1", 400_000));
+        }
+
+        // Trades against orders the solver doesn't hold, and replays, are rejected
+        let unknown = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.record_trade(trade("trade_x", "order_missing", 1));
+        }));
+        assert!(unknown.is_err());
+        let replay = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.record_trade(trade("trade_0", "order_intent_1", 400_000));
+        }));
+        assert!(replay.is_err());
+
+        testing_env!(context_at("daemon.testnet", 30_000_000_000));
+        contract.complete_intent("intent_1".to_string(), ExecutionResult {
+            intent_id: "intent_1".to_string(),
+            success: true,
+            output_amount: Some(U128(9_900_000)),
+            fee_amount: U128(100_000),
+            execution_details: "filled".to_string(),
+        });
+
+        let trace = contract.get_intent_trace("intent_1".to_string()).unwrap();
+        assert!(matches!(trace.order.unwrap().status, OrderStatus::Filled));
+        assert!(trace.processed && !trace.pending_for_daemon);
+        assert_eq!(trace.trades.len(), MAX_TRACE_TRADES);
+        assert_eq!(trace.total_trades, 25);
+        let stages: Vec<IntentStage> = trace.transitions.iter().map(|transition| transition.stage.clone()).collect();
+        assert_eq!(stages, vec![IntentStage::Registered, IntentStage::PartiallyFilled, IntentStage::Filled, IntentStage::Completed]);
+
+        let completion = trace.completion.unwrap();
+        assert_eq!(completion.daemon, "daemon.testnet".parse::<AccountId>().unwrap());
+        assert_eq!(completion.completed_at, 1000000000000000000 + 30_000_000_000);
+        assert!(completion.result.success);
+        assert_eq!(completion.result.output_amount, Some(U128(9_900_000)));
+
+        // The overflow past the embedded trades
+        let rest = contract.get_order_trades("order_intent_1".to_string(), MAX_TRACE_TRADES as u64, 10);
+        assert_eq!(rest.len(), 5);
+        assert_eq!(rest[0].trade_id, "trade_20");
+    }
+
+    #[test]
+    fn test_intent_trace_after_refund() {
+        let mut contract = trace_contract();
+
+        testing_env!(context_at("orderbook.testnet", 10_000_000_000));
+        contract.update_order_fill("order_intent_1".to_string(), U128(2_000_000), None, Some(50000), false);
+        contract.record_trade(trade("trade_0", "order_intent_1", 2_000_000));
+
+        testing_env!(context_at("alice.testnet", DEFAULT_INTENT_TIMEOUT + 1));
+        contract.refund_timed_out_intent("intent_1".to_string());
+
+        // The order is gone but its history and trades remain
+        let trace = contract.get_intent_trace("intent_1".to_string()).unwrap();
+        assert!(trace.order.is_none());
+        assert!(!trace.pending_for_daemon && !trace.processed && trace.completion.is_none());
+        assert_eq!(trace.transitions.last(), Some(&IntentTransition {
+            stage: IntentStage::Refunded,
+            at: 1000000000000000000 + DEFAULT_INTENT_TIMEOUT + 1,
+        }));
+        assert_eq!(trace.total_trades, 1);
+    }
 }
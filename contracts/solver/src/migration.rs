@@ -0,0 +1,272 @@
+// Solver upgrades and state migration.
+//
+// propose_upgrade records the sha256 of the next wasm, upgrade deploys exactly that code and
+// schedules migrate() in the same receipt batch. Intents pending for the daemon survive the
+// upgrade because the collections keep their storage prefixes. Freeze the current struct as
+// PredictionSolverV<n> here before adding or removing PredictionSolver fields. Orders live in their
+// own storage, so a changed Order layout needs the stored entries rewritten too (see OrderV2).
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{UnorderedMap, UnorderedSet};
+use near_sdk::json_types::{Base58CryptoHash, U128};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, NearToken, Promise};
+use schemars::JsonSchema;
+
+use crate::{
+    Order, OrderSide, OrderStatus, OrderType, PredictionSolver, PredictionSolverExt, SimpleBridgeConfig,
+    DEFAULT_AUTHORITY_GRACE_PERIOD, DEFAULT_COMPLETION_SLA, DEFAULT_INSURANCE_FEE_BPS, DEFAULT_INTENT_TIMEOUT,
+    DEFAULT_RETURN_TIMEOUT,
+};
+
+const MIGRATE_TGAS: u64 = 100;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StateVersion {
+    V1,             // unversioned layout deployed before migrations existed
+    V2,             // adds state_version, pending_upgrade_hash, ownership transfer and the config admin, intent timeouts, intent USDC deposits, daemon stats and assignments, orderbook authority rotation, order_sequences, take_profit_orders, fee_tiers, user_daily_volume, return_obligations, return_timeout, Order.price_bound, the maker/taker fees with rebate_balances and the market_makers allow-list
+    V3,             // adds market_maker to every stored Order, the per-intent transition log, daemon completion records, recorded trades, the insurance fee and the taker fee pool
+}
+
+impl StateVersion {
+    pub const CURRENT: StateVersion = StateVersion::V3;
+}
+
+/// V1 order layout, frozen. Fills weren't checked against a price bound
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OrderV1 {
+    pub order_id: String,
+    pub intent_id: String,
+    pub user: AccountId,
+    pub market_id: String,
+    pub condition_id: String,
+    pub outcome: u8,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub price: u64,
+    pub amount: U128,
+    pub filled_amount: U128,
+    pub status: OrderStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+/// Order layout up to V2, frozen
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OrderV2 {
+    pub order_id: String,
+    pub intent_id: String,
+    pub user: AccountId,
+    pub market_id: String,
+    pub condition_id: String,
+    pub outcome: u8,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub price: u64,
+    pub amount: U128,
+    pub filled_amount: U128,
+    pub status: OrderStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub price_bound: Option<u64>,
+}
+
+impl From<OrderV2> for Order {
+    fn from(old: OrderV2) -> Self {
+        Self {
+            order_id: old.order_id,
+            intent_id: old.intent_id,
+            user: old.user,
+            market_id: old.market_id,
+            condition_id: old.condition_id,
+            outcome: old.outcome,
+            side: old.side,
+            order_type: old.order_type,
+            price: old.price,
+            amount: old.amount,
+            filled_amount: old.filled_amount,
+            status: old.status,
+            created_at: old.created_at,
+            expires_at: old.expires_at,
+            price_bound: old.price_bound,
+            market_maker: false,
+        }
+    }
+}
+
+/// Rewrite every order still stored in the V2 layout. The map's own borsh form is just its
+/// prefixes and length, so it can be read back with the old value type
+fn upgrade_orders(orders: &mut UnorderedMap<String, Order>) {
+    let legacy: UnorderedMap<String, OrderV2> = borsh::from_slice(&borsh::to_vec(orders).unwrap())
+        .expect("Unrecognized orders layout");
+    let entries: Vec<(String, OrderV2)> = legacy.iter().collect();
+    for (order_id, order) in entries {
+        orders.insert(&order_id, &order.into());
+    }
+}
+
+/// V1 layout, frozen. Stored without a version marker
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PredictionSolverV1 {
+    pub owner_id: AccountId,
+    pub verifier_contract: AccountId,
+    pub ctf_contract: AccountId,
+    pub usdc_contract: AccountId,
+    pub orderbook_authority: AccountId,
+    pub processed_intents: UnorderedSet<String>,
+    pub pending_for_daemon: UnorderedSet<String>,
+    pub authorized_daemons: UnorderedSet<AccountId>,
+    pub active_orders: UnorderedMap<String, OrderV1>,
+    pub user_orders: UnorderedMap<AccountId, Vec<String>>,
+    pub solver_fee_bps: u16,
+    pub min_order_size: U128,
+    pub cross_chain_enabled: bool,
+    pub bridge_fee_bps: u16,
+    pub bridge_config: Option<SimpleBridgeConfig>,
+    pub monitor_contract: Option<AccountId>,
+}
+
+impl From<PredictionSolverV1> for PredictionSolver {
+    fn from(mut old: PredictionSolverV1) -> Self {
+        // Rewrite open orders under the same prefix in the new layout
+        let old_orders = old.active_orders.to_vec();
+        old.active_orders.clear();
+
+        let mut contract = Self {
+            owner_id: old.owner_id,
+            verifier_contract: old.verifier_contract,
+            ctf_contract: old.ctf_contract,
+            usdc_contract: old.usdc_contract,
+            orderbook_authority: old.orderbook_authority,
+            processed_intents: old.processed_intents,
+            pending_for_daemon: old.pending_for_daemon,
+            authorized_daemons: old.authorized_daemons,
+            active_orders: UnorderedMap::new(b"o"),
+            user_orders: old.user_orders,
+            order_sequences: UnorderedMap::new(b"q"),
+            take_profit_orders: UnorderedMap::new(b"t"),
+            solver_fee_bps: old.solver_fee_bps,
+            min_order_size: old.min_order_size,
+            cross_chain_enabled: old.cross_chain_enabled,
+            bridge_fee_bps: old.bridge_fee_bps,
+            bridge_config: old.bridge_config,
+            monitor_contract: old.monitor_contract,
+            pending_owner: None,
+            config_admin: None,
+            intent_timeout: DEFAULT_INTENT_TIMEOUT,
+            pending_since: UnorderedMap::new(b"s"),
+            // V1 recorded no USDC forwarded with an intent; its timed-out intents refund nothing
+            intent_usdc: UnorderedMap::new(b"c"),
+            daemon_stats: UnorderedMap::new(b"m"),
+            completion_sla: DEFAULT_COMPLETION_SLA,
+            intent_daemon_assignments: UnorderedMap::new(b"n"),
+            pending_authority_rotation: None,
+            authority_grace_period: DEFAULT_AUTHORITY_GRACE_PERIOD,
+            authority_rotations: Vec::new(),
+            state_version: StateVersion::CURRENT,
+            pending_upgrade_hash: None,
+            fee_tiers: Vec::new(),
+            user_daily_volume: UnorderedMap::new(b"v"),
+            return_obligations: UnorderedMap::new(b"r"),
+            return_timeout: DEFAULT_RETURN_TIMEOUT,
+            maker_rebate_bps: 0,
+            taker_fee_bps: 0,
+            rebate_balances: UnorderedMap::new(b"b"),
+            market_makers: UnorderedSet::new(b"k"),
+            intent_transitions: UnorderedMap::new(b"i"),
+            intent_completions: UnorderedMap::new(b"e"),
+            trades: UnorderedMap::new(b"x"),
+            order_trades: UnorderedMap::new(b"y"),
+            insurance_fee_bps: DEFAULT_INSURANCE_FEE_BPS,
+            taker_fee_pool: U128(0),
+        };
+
+        // The bound an old order was placed with isn't known, so its fills stay unchecked
+        for (order_id, order) in old_orders {
+            contract.active_orders.insert(&order_id, &Order {
+                order_id: order.order_id,
+                intent_id: order.intent_id,
+                user: order.user,
+                market_id: order.market_id,
+                condition_id: order.condition_id,
+                outcome: order.outcome,
+                side: order.side,
+                order_type: order.order_type,
+                price: order.price,
+                amount: order.amount,
+                filled_amount: order.filled_amount,
+                status: order.status,
+                created_at: order.created_at,
+                expires_at: order.expires_at,
+                price_bound: None,
+                market_maker: false,
+            });
+        }
+
+        // Intents waiting for a daemon count as pending since their order was placed, so the ones
+        // already past the SLA show up as breaches
+        let pending: Vec<String> = contract.pending_for_daemon.iter().collect();
+        for intent_id in pending {
+            if let Some(order) = contract.active_orders.get(&format!("order_{}", intent_id)) {
+                contract.pending_since.insert(&intent_id, &order.created_at);
+            }
+        }
+
+        contract
+    }
+}
+
+#[near_bindgen]
+impl PredictionSolver {
+    /// Approve the sha256 of the next solver code; replaces any earlier proposal
+    pub fn propose_upgrade(&mut self, code_hash: Base58CryptoHash) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can propose upgrades");
+
+        self.pending_upgrade_hash = Some(code_hash.into());
+        env::log_str(&format!("Upgrade proposed with code hash {}", String::from(&code_hash)));
+    }
+
+    /// Deploy the approved wasm (raw call input), then migrate
+    pub fn upgrade(&mut self) -> Promise {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can upgrade");
+        let code = env::input().expect("Contract code missing from input");
+        let approved = self.pending_upgrade_hash.take().expect("No upgrade proposed");
+        assert_eq!(env::sha256_array(&code), approved, "Code does not match the proposed upgrade");
+
+        self.emit_role_event("upgrade_deployed", None);
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call("migrate".to_string(), Vec::new(), NearToken::from_yoctonear(0), Gas::from_tgas(MIGRATE_TGAS))
+    }
+
+    /// Convert stored state to the current layout; current state is returned as-is
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = env::storage_read(b"STATE").expect("No contract state to migrate");
+
+        let (from, mut contract) = match Self::try_from_slice(&state) {
+            Ok(current) => (current.state_version, current),
+            Err(_) => {
+                let old = PredictionSolverV1::try_from_slice(&state).expect("Unrecognized contract state layout");
+                (StateVersion::V1, old.into())
+            }
+        };
+        if from == StateVersion::V2 {
+            upgrade_orders(&mut contract.active_orders);
+        }
+        env::log_str(&format!("Solver state migrated from {:?} to {:?}", from, StateVersion::CURRENT));
+        contract.state_version = StateVersion::CURRENT;
+        contract.pending_upgrade_hash = None;
+        contract
+    }
+
+    pub fn get_state_version(&self) -> StateVersion {
+        self.state_version
+    }
+
+    pub fn get_pending_upgrade(&self) -> Option<Base58CryptoHash> {
+        self.pending_upgrade_hash.map(Base58CryptoHash::from)
+    }
+}
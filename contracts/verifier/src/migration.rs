@@ -0,0 +1,319 @@
+// Verifier upgrades and state migration.
+//
+// Upgrades are two owner calls: propose_upgrade pins the sha256 of the new wasm, upgrade deploys
+// that wasm and calls migrate() in the same batch. Bridge requests live in prefixed collections and
+// are not rewritten; only the root struct is converted. Markets are the exception when coming from
+// V1: they switch from a category string to a registry id, so every market is rewritten and the
+// categories already in use are registered. Stored intents are rewritten when coming from V1 or V2,
+// which predate max_slippage_bps (see PredictionIntentV2). Freeze the current struct as
+// PredictionVerifierV<n> here before changing PredictionVerifier's fields.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{UnorderedMap, UnorderedSet, Vector};
+use near_sdk::json_types::{Base58CryptoHash, U128};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, NearToken, Promise};
+use schemars::JsonSchema;
+
+use crate::{
+    AuctionIntent, AwaitingBridgeIntent, BridgeConnectorConfig, BridgeRequest, BridgeSecurityConfig,
+    CrossChainParams, ExecutionResult, IntentType, Market, OrderType, PredictionIntent, PredictionVerifier,
+    PredictionVerifierExt, DEFAULT_AUTHORITY_GRACE_PERIOD, DEFAULT_INSURANCE_FUND_FEE_BPS,
+    DEFAULT_MAX_PENDING_INTENTS_PER_USER,
+};
+
+const MIGRATE_TGAS: u64 = 100;
+
+/// Category given to V1 markets created with a blank category
+const UNCATEGORIZED: &str = "Uncategorized";
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StateVersion {
+    V1,             // unversioned layout deployed before migrations existed
+    V2,             // adds state_version, pending_upgrade_hash, intent timestamps and failures, market stats, market tags and the tag index, conditional markets and market resolution, ownership transfer and the config admin, intent signing keys, bridge connector rotation, the USDC contract, market creation permissions, fees and deposits, the market end time index, the solver auction maps, the insurance fund, awaiting_bridge, cross_chain_balances, pending_commitments, the category registry (markets store category_id), category_configs, per-market price feeds, the question_index, market timelines, delegation, the solver capability registry, the pending intent quota, intent bundles, the duplicate intent fingerprints and market termination state
+    V3,             // adds max_slippage_bps to every stored intent (intent_data, auction_intents, awaiting_bridge)
+}
+
+impl StateVersion {
+    pub const CURRENT: StateVersion = StateVersion::V3;
+}
+
+/// Intent layout up to V2, frozen. No slippage tolerance
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PredictionIntentV2 {
+    pub intent_id: String,
+    pub user: AccountId,
+    pub market_id: String,
+    pub intent_type: IntentType,
+    pub outcome: u8,
+    pub amount: U128,
+    pub max_price: Option<u64>,
+    pub min_price: Option<u64>,
+    pub deadline: u64,
+    pub order_type: OrderType,
+    pub cross_chain: Option<CrossChainParams>,
+}
+
+impl From<PredictionIntentV2> for PredictionIntent {
+    fn from(old: PredictionIntentV2) -> Self {
+        Self {
+            intent_id: old.intent_id,
+            user: old.user,
+            market_id: old.market_id,
+            intent_type: old.intent_type,
+            outcome: old.outcome,
+            amount: old.amount,
+            max_price: old.max_price,
+            min_price: old.min_price,
+            deadline: old.deadline,
+            order_type: old.order_type,
+            cross_chain: old.cross_chain,
+            max_slippage_bps: None,
+        }
+    }
+}
+
+/// AuctionIntent up to V2, frozen
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct AuctionIntentV2 {
+    pub intent: PredictionIntentV2,
+    pub auction_ends_at: u64,
+}
+
+/// AwaitingBridgeIntent up to V2, frozen
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct AwaitingBridgeIntentV2 {
+    pub intent: PredictionIntentV2,
+    pub solver_account: AccountId,
+    pub request_id: String,
+    pub tx_hash: String,
+}
+
+/// Read `map` back with the V2 value type: its own borsh form is just its prefixes and length
+fn legacy_entries<V, L>(map: &UnorderedMap<String, V>) -> Vec<(String, L)>
+where
+    V: BorshSerialize + BorshDeserialize,
+    L: BorshSerialize + BorshDeserialize,
+{
+    let legacy: UnorderedMap<String, L> = borsh::from_slice(&borsh::to_vec(map).unwrap())
+        .expect("Unrecognized intent map layout");
+    legacy.iter().collect()
+}
+
+/// Rewrite every intent still stored in the V2 layout
+fn upgrade_intents(contract: &mut PredictionVerifier) {
+    for (intent_id, intent) in legacy_entries::<_, PredictionIntentV2>(&contract.intent_data) {
+        contract.intent_data.insert(&intent_id, &intent.into());
+    }
+    for (intent_id, auction) in legacy_entries::<_, AuctionIntentV2>(&contract.auction_intents) {
+        contract.auction_intents.insert(&intent_id, &AuctionIntent {
+            intent: auction.intent.into(),
+            auction_ends_at: auction.auction_ends_at,
+        });
+    }
+    for (intent_id, parked) in legacy_entries::<_, AwaitingBridgeIntentV2>(&contract.awaiting_bridge) {
+        contract.awaiting_bridge.insert(&intent_id, &AwaitingBridgeIntent {
+            intent: parked.intent.into(),
+            solver_account: parked.solver_account,
+            request_id: parked.request_id,
+            tx_hash: parked.tx_hash,
+        });
+    }
+}
+
+/// V1 market layout, frozen. The category was a free-form string
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MarketV1 {
+    pub market_id: String,
+    pub condition_id: String,
+    pub title: String,
+    pub description: String,
+    pub creator: AccountId,
+    pub end_time: u64,
+    pub resolution_time: u64,
+    pub category: String,
+    pub is_active: bool,
+    pub resolver: AccountId,
+}
+
+/// V1 layout, frozen. Stored without a version marker. Intents are still in the V2 layout
+/// (PredictionIntentV2) and are rewritten by migrate()
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PredictionVerifierV1 {
+    pub owner_id: AccountId,
+    pub verified_intents: UnorderedSet<String>,
+    pub intent_data: UnorderedMap<String, PredictionIntent>,
+    pub markets: UnorderedMap<String, MarketV1>,
+    pub registered_solvers: UnorderedSet<AccountId>,
+    pub ctf_contract: AccountId,
+    pub resolver_contract: AccountId,
+    pub min_bet_amount: U128,
+    pub max_bet_amount: U128,
+    pub platform_fee_bps: u16,
+    pub executed_intents: UnorderedMap<String, ExecutionResult>,
+    pub pending_intents: UnorderedSet<String>,
+    pub bridge_connector: Option<AccountId>,
+    pub bridge_connector_config: Option<BridgeConnectorConfig>,
+    pub pending_bridge_requests: UnorderedMap<String, BridgeRequest>,
+    pub verified_bridge_txs: UnorderedSet<String>,
+    pub bridge_security_config: BridgeSecurityConfig,
+}
+
+impl From<PredictionVerifierV1> for PredictionVerifier {
+    fn from(mut old: PredictionVerifierV1) -> Self {
+        // Read every market out and clear the map so the same prefix can hold the new layout
+        let old_markets = old.markets.to_vec();
+        old.markets.clear();
+
+        let mut contract = Self {
+            owner_id: old.owner_id,
+            verified_intents: old.verified_intents,
+            intent_data: old.intent_data,
+            markets: UnorderedMap::new(b"m"),
+            registered_solvers: old.registered_solvers,
+            solver_registry: UnorderedMap::new(b"S"),
+            ctf_contract: old.ctf_contract,
+            resolver_contract: old.resolver_contract,
+            min_bet_amount: old.min_bet_amount,
+            max_bet_amount: old.max_bet_amount,
+            platform_fee_bps: old.platform_fee_bps,
+            executed_intents: old.executed_intents,
+            intent_timestamps: UnorderedMap::new(b"l"),
+            failed_intents: UnorderedMap::new(b"x"),
+            market_stats: UnorderedMap::new(b"g"),
+            pending_intents: old.pending_intents,
+            // Intents already pending when the quota shipped aren't counted; their completion is a no-op
+            pending_intent_counts: UnorderedMap::new(b"Q"),
+            max_pending_intents_per_user: DEFAULT_MAX_PENDING_INTENTS_PER_USER,
+            quota_exempt_accounts: UnorderedSet::new(b"E"),
+            intent_bundles: UnorderedMap::new(b"B"),
+            recent_intent_hashes: UnorderedMap::new(b"H"),
+            bridge_connector: old.bridge_connector,
+            bridge_connector_config: old.bridge_connector_config,
+            pending_bridge_requests: old.pending_bridge_requests,
+            verified_bridge_txs: old.verified_bridge_txs,
+            bridge_security_config: old.bridge_security_config,
+            tag_index: UnorderedMap::new(b"t"),
+            pending_owner: None,
+            config_admin: None,
+            intent_keys: UnorderedMap::new(b"k"),
+            parent_resolutions: UnorderedMap::new(b"w"),
+            pending_bridge_connector_rotation: None,
+            bridge_connector_rotations: Vec::new(),
+            authority_grace_period: DEFAULT_AUTHORITY_GRACE_PERIOD,
+            usdc_contract: None,
+            market_creators: UnorderedSet::new(b"c"),
+            category_creators: UnorderedMap::new(b"a"),
+            // Anyone could create a market in V1; keep creation open and free
+            creation_fee: Some(U128(0)),
+            creation_deposits: UnorderedMap::new(b"d"),
+            market_end_times: Vector::new(b"n"),
+            auction_intents: UnorderedMap::new(b"u"),
+            intent_bids: UnorderedMap::new(b"b"),
+            insurance_fund_fee_bps: DEFAULT_INSURANCE_FUND_FEE_BPS,
+            insurance_fund_balance: U128(0),
+            awaiting_bridge: UnorderedMap::new(b"y"),
+            cross_chain_balances: UnorderedMap::new(b"z"),
+            pending_commitments: UnorderedMap::new(b"f"),
+            categories: UnorderedMap::new(b"h"),
+            category_ids: UnorderedMap::new(b"j"),
+            next_category_id: 0,
+            category_configs: UnorderedMap::new(b"o"),
+            question_index: UnorderedMap::new(b"q"),
+            market_timelines: UnorderedMap::new(b"L"),
+            delegated_accounts: UnorderedMap::new(b"D"),
+            delegate_principals: UnorderedMap::new(b"P"),
+            termination_state: UnorderedMap::new(b"T"),
+            state_version: StateVersion::CURRENT,
+            pending_upgrade_hash: None,
+        };
+
+        // "crypto", "Crypto" and "crypto " collapse into one category named after the first seen
+        for (market_id, market) in old_markets {
+            let name = if market.category.trim().is_empty() { UNCATEGORIZED } else { market.category.as_str() };
+            let category_id = contract.ensure_category(name);
+            contract.count_category_market(category_id, true);
+            // Duplicates created before the index existed are kept; only new ones are rejected
+            contract.index_market_question(&PredictionVerifier::title_hash(&market.title), &market_id);
+            contract.index_market_end_time(market.end_time, &market_id);
+            contract.markets.insert(&market_id, &Market {
+                market_id: market.market_id,
+                condition_id: market.condition_id,
+                title: market.title,
+                description: market.description,
+                creator: market.creator,
+                end_time: market.end_time,
+                resolution_time: market.resolution_time,
+                category_id,
+                category: String::new(),
+                is_active: market.is_active,
+                resolver: market.resolver,
+                tags: Vec::new(),
+                parent_market_id: None,
+                required_parent_outcome: None,
+                // V1 kept no resolution on the market
+                is_resolved: false,
+                winning_outcome: None,
+                price_feed_oracle: None,
+                resolution_rule: None,
+            });
+        }
+
+        contract
+    }
+}
+
+#[near_bindgen]
+impl PredictionVerifier {
+    /// Approve the sha256 of the next verifier code; replaces any earlier proposal
+    pub fn propose_upgrade(&mut self, code_hash: Base58CryptoHash) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can propose upgrades");
+
+        self.pending_upgrade_hash = Some(code_hash.into());
+        env::log_str(&format!("Upgrade proposed with code hash {}", String::from(&code_hash)));
+    }
+
+    /// Deploy the approved wasm (raw call input), then migrate
+    pub fn upgrade(&mut self) -> Promise {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can upgrade");
+        let code = env::input().expect("Contract code missing from input");
+        let approved = self.pending_upgrade_hash.take().expect("No upgrade proposed");
+        assert_eq!(env::sha256_array(&code), approved, "Code does not match the proposed upgrade");
+
+        self.emit_role_event("upgrade_deployed", None);
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call("migrate".to_string(), Vec::new(), NearToken::from_yoctonear(0), Gas::from_tgas(MIGRATE_TGAS))
+    }
+
+    /// Convert stored state to the current layout; current state is returned as-is
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = env::storage_read(b"STATE").expect("No contract state to migrate");
+
+        let (from, mut contract) = match Self::try_from_slice(&state) {
+            Ok(current) => (current.state_version, current),
+            Err(_) => {
+                let old = PredictionVerifierV1::try_from_slice(&state).expect("Unrecognized contract state layout");
+                (StateVersion::V1, old.into())
+            }
+        };
+        if from != StateVersion::CURRENT {
+            upgrade_intents(&mut contract);
+        }
+        env::log_str(&format!("Verifier state migrated from {:?} to {:?}", from, StateVersion::CURRENT));
+        contract.state_version = StateVersion::CURRENT;
+        contract.pending_upgrade_hash = None;
+        contract
+    }
+
+    pub fn get_state_version(&self) -> StateVersion {
+        self.state_version
+    }
+
+    pub fn get_pending_upgrade(&self) -> Option<Base58CryptoHash> {
+        self.pending_upgrade_hash.map(Base58CryptoHash::from)
+    }
+}
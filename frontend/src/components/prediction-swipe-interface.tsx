@@ -5,7 +5,8 @@ import { useWallet } from '@/components/near-wallet';
 import { TinderCardStack } from '@/components/tinder-card-stack';
 import { Market } from '@/lib/near';
 import { marketService } from '@/services/market';
-import { generateIntentId } from '@/lib/utils';
+import { generateIntentId, marketBuyMaxPrice } from '@/lib/utils';
+import { orderbookService } from '@/services/orderbook';
 import { 
   Star, 
   Zap, 
@@ -104,15 +105,19 @@ export function PredictionSwipeInterface() {
     if (isSignedIn && predictionDirection !== 'skip') {
       setIsSubmitting(true);
       try {
+        const outcome = predictionDirection === 'yes' ? 1 : 0;
+        // Market orders need a price cap; without a quote, rest a limit order at 50¢
+        const quote = await orderbookService.getMarketPrice(market.market_id, 1).catch(() => null);
         const intent = {
           intent_id: generateIntentId(),
           user: nearService.getAccountId() || '',
           market_id: market.market_id,
           intent_type: 'BuyShares' as const,
-          outcome: predictionDirection === 'yes' ? 1 : 0,
+          outcome,
           amount: nearService.parseUsdcAmount('1'), // Micro-stake: $1
+          max_price: quote ? marketBuyMaxPrice(quote, outcome) : 50000,
           deadline: String((Date.now() + 3600000) * 1000000),
-          order_type: 'Market' as const
+          order_type: quote ? 'Market' as const : 'Limit' as const
         };
 
         await nearService.submitIntent(intent, 'solver.ashpk20.testnet');
@@ -8,7 +8,7 @@ import { Select } from '@/components/ui/select';
 import { Badge } from '@/components/ui/badge';
 import { useWallet } from '@/components/near-wallet';
 import { Market, PredictionIntent } from '@/lib/near';
-import { generateIntentId, formatCurrency, INTENT_TYPES, basisPointsToPercent } from '@/lib/utils';
+import { generateIntentId, formatCurrency, INTENT_TYPES, basisPointsToPercent, marketBuyMaxPrice } from '@/lib/utils';
 import { TrendingUp, TrendingDown, Info, AlertCircle, DollarSign, Percent } from 'lucide-react';
 import { useMarketPrice } from '@/hooks/useOrderbook';
 
@@ -138,6 +138,8 @@ export function TradingInterface({ market, onTradeSubmitted }: TradingInterfaceP
         // Both buy and sell use max_price since they're both BuyShares intents
         // Buy = buy YES shares at max price, Sell = buy NO shares at max price
         intent.max_price = priceInBasisPoints;
+      } else if (orderType === 'Market' && !showAdvanced && marketPriceData) {
+        intent.max_price = marketBuyMaxPrice(marketPriceData, intent.outcome);
       }
 
       const result = await nearService.submitIntent(intent, 'solver.testnet');
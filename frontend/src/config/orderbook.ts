@@ -7,7 +7,7 @@ export const orderbookConfig = {
   // API endpoints
   api: {
     url: process.env.NEXT_PUBLIC_ORDERBOOK_API_URL || 'http://localhost:8080',
-    wsUrl: process.env.NEXT_PUBLIC_ORDERBOOK_WS_URL || 'ws://localhost:8080/ws',
+    wsUrl: process.env.NEXT_PUBLIC_ORDERBOOK_WS_URL || 'ws://localhost:8080/v1/ws',
   },
 
   // WebSocket settings
@@ -64,7 +64,7 @@ export const environments = {
     ...orderbookConfig,
     api: {
       url: 'http://localhost:8080',
-      wsUrl: 'ws://localhost:8080/ws',
+      wsUrl: 'ws://localhost:8080/v1/ws',
     },
   },
 
@@ -72,7 +72,7 @@ export const environments = {
     ...orderbookConfig,
     api: {
       url: 'https://staging-orderbook.yourapp.com',
-      wsUrl: 'wss://staging-orderbook.yourapp.com/ws',
+      wsUrl: 'wss://staging-orderbook.yourapp.com/v1/ws',
     },
   },
 
@@ -80,7 +80,7 @@ export const environments = {
     ...orderbookConfig,
     api: {
       url: 'https://orderbook.yourapp.com',
-      wsUrl: 'wss://orderbook.yourapp.com/ws',
+      wsUrl: 'wss://orderbook.yourapp.com/v1/ws',
     },
     websocket: {
       ...orderbookConfig.websocket,
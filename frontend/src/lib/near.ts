@@ -53,8 +53,13 @@ export type PredictionIntent = {
     bridge_min_amount: string;
     return_to_source: boolean;
   };
+  max_slippage_bps?: number;
 };
 
+// Tolerance sent with intents that don't set one: the solver refuses fills more than 0.5% past
+// max_price (buys) or min_price (sells)
+export const DEFAULT_MAX_SLIPPAGE_BPS = 50;
+
 export type ExecutionResult = {
   intent_id: string;
   success: boolean;
@@ -189,6 +194,15 @@ export class NearService {
         viewMethods: [
           'get_market',
           'get_markets',
+          'get_markets_expiring_soon',
+          'get_markets_awaiting_resolution',
+          'get_categories',
+          'get_market_count_by_category',
+          'get_market_category',
+          'get_category_config',
+          'get_market_fee_bps',
+          'get_title_hash',
+          'find_markets_by_title_hash',
           'is_intent_verified',
           'get_verified_intents',
           'get_execution_result',
@@ -212,7 +226,29 @@ export class NearService {
           'get_position_id',
           'get_collection_id',
           'get_user_positions',
-          'get_position'
+          'get_all_user_positions_paginated',
+          'get_user_position_count',
+          'get_position',
+          'get_payouts',
+          'estimate_redemption',
+          'get_position_holders',
+          'get_position_holder_count',
+          'get_approvals_for_owner',
+          'get_operators_for_owner',
+          'get_positions_for_transfer',
+          'get_position_metadata',
+          'position_metadata',
+          'metadata',
+          'get_lien',
+          'get_liened_balance',
+          'get_available_balance',
+          'get_total_collateral_locked',
+          'get_all_collateral_locked',
+          'get_condition_resolution_timestamp',
+          'get_condition_resolution_submitter',
+          'get_condition_resolution_history',
+          'get_condition_accounting',
+          'check_invariant'
         ],
         changeMethods: []
       }
@@ -237,16 +273,62 @@ export class NearService {
         viewMethods: [
           'get_market',
           'get_markets',
+          'get_markets_expiring_soon',
+          'get_markets_awaiting_resolution',
+          'get_categories',
+          'get_market_count_by_category',
+          'get_market_category',
+          'get_category_config',
+          'get_market_fee_bps',
+          'get_title_hash',
+          'find_markets_by_title_hash',
           'is_intent_verified',
           'get_verified_intents',
           'get_execution_result',
           'is_intent_pending',
-          'get_platform_config'
+          'get_platform_config',
+          'get_intent_keys',
+          'get_intent_hash',
+          'get_usdc_contract',
+          'get_market_creators',
+          'get_category_creators',
+          'get_creation_fee',
+          'get_auction',
+          'get_intent_bids',
+          'get_insurance_fund_balance',
+          'get_awaiting_bridge_intent',
+          'get_cross_chain_balance',
+          'get_commitment_status',
+          'get_market_timeline',
+          'get_delegates',
+          'get_principals',
+          'get_solver_capabilities',
+          'find_best_solver',
+          'get_pending_count',
+          'get_intent_quota_config',
+          'get_recent_intent_hash_count',
+          'get_intent_bundle',
+          'get_termination_state'
         ],
         changeMethods: [
           'create_market',
+          'create_conditional_market',
+          'activate_conditional_market',
+          'set_market_price_feed',
+          'auto_resolve_market',
           'verify_and_solve',
-          'set_market_status'
+          'verify_and_solve_signed',
+          'verify_and_solve_bundle',
+          'verify_and_auction',
+          'commit_intent',
+          'reveal_intent',
+          'get_market_resolution_countdown',
+          'register_intent_key',
+          'revoke_intent_key',
+          'set_delegate',
+          'set_market_status',
+          'initiate_market_termination',
+          'confirm_market_payouts'
         ]
       }
     );
@@ -264,13 +346,44 @@ export class NearService {
           'get_position_id',
           'get_collection_id',
           'get_user_positions',
-          'get_position'
+          'get_all_user_positions_paginated',
+          'get_user_position_count',
+          'get_position',
+          'get_payouts',
+          'estimate_redemption',
+          'get_position_holders',
+          'get_position_holder_count',
+          'get_approvals_for_owner',
+          'get_operators_for_owner',
+          'get_positions_for_transfer',
+          'get_position_metadata',
+          'position_metadata',
+          'metadata',
+          'get_lien',
+          'get_liened_balance',
+          'get_available_balance',
+          'get_total_collateral_locked',
+          'get_all_collateral_locked',
+          'get_condition_resolution_timestamp',
+          'get_condition_resolution_submitter',
+          'get_condition_resolution_history',
+          'get_condition_accounting',
+          'check_invariant'
         ],
         changeMethods: [
           'split_position',
           'merge_positions', 
           'redeem_positions',
+          'redeem_positions_detailed',
           'safe_transfer_from',
+          'batch_safe_transfer_all',
+          'batch_split_position',
+          'batch_merge_positions',
+          'set_position_metadata',
+          'set_condition_metadata',
+          'create_lien',
+          'release_lien',
+          'liquidate_lien',
           'approve',
           'set_approval_for_all'
         ]
@@ -287,11 +400,29 @@ export class NearService {
           'is_intent_processed',
           'get_order',
           'get_user_orders',
-          'is_cross_chain_enabled'
+          'get_order_sequence',
+          'get_take_profit',
+          'is_cross_chain_enabled',
+          'get_fee_tiers',
+          'get_user_fee_bps',
+          'get_user_trailing_volume',
+          'get_return_obligation',
+          'get_maker_rebate_balance',
+          'get_maker_rebate_bps',
+          'get_taker_fee_bps',
+          'is_market_maker',
+          'get_market_makers'
         ],
         changeMethods: [
           'solve_intent',
-          'cancel_order'
+          'cancel_order',
+          'reduce_order',
+          'set_take_profit',
+          'cancel_take_profit',
+          'claim_return',
+          'claim_maker_rebate',
+          'place_bulk_orders',
+          'cancel_bulk_orders'
         ]
       }
     );
@@ -305,12 +436,31 @@ export class NearService {
           'get_resolution',
           'get_dispute',
           'is_market_finalized',
-          'get_pending_resolutions'
+          'get_pending_resolutions',
+          'calculate_dispute_bond',
+          'get_escrowed_bond',
+          'get_pending_resolutions_paginated',
+          'get_disputed_resolutions_paginated',
+          'get_resolutions_by_resolver',
+          'get_bond_currency',
+          'get_usdc_bond_config',
+          'get_claimable_refund',
+          'get_schelling_votes',
+          'get_schelling_vote_period',
+          'is_commit_reveal_market',
+          'get_resolution_commitment',
+          'get_commit_reveal_config',
+          'get_resolution_window_opened_at'
         ],
         changeMethods: [
           'submit_resolution',
           'dispute_resolution',
-          'finalize_resolution'
+          'finalize_resolution',
+          'claim_refund',
+          'submit_schelling_vote',
+          'tabulate_schelling_vote',
+          'commit_resolution',
+          'reveal_resolution'
         ]
       }
     );
@@ -504,7 +654,10 @@ export class NearService {
     if (!this.verifierContract) return null;
     try {
       const result = await (this.verifierContract as any).verify_and_solve(
-        { intent, solver_account: solverAccount },
+        {
+          intent: { ...intent, max_slippage_bps: intent.max_slippage_bps ?? DEFAULT_MAX_SLIPPAGE_BPS },
+          solver_account: solverAccount
+        },
         '300000000000000' // 300 TGas
       );
       return result;
@@ -151,6 +151,16 @@ export function percentToBasisPoints(percent: number): number {
   return Math.round(percent * 10000);
 }
 
+// Slippage allowed on market buys, in 1/100000 of a dollar (2000 = 2¢)
+export const MARKET_ORDER_SLIPPAGE = 2000;
+
+// Worst price a market buy of `outcome` may fill at, from the YES quote. NO is bought against
+// the YES bid (NO price = 100000 - YES price). The verifier rejects market buys without one
+export function marketBuyMaxPrice(yesQuote: { bid: number; ask: number }, outcome: number): number {
+  const expected = outcome === 1 ? yesQuote.ask : 100000 - yesQuote.bid;
+  return Math.min(100000, Math.round(expected) + MARKET_ORDER_SLIPPAGE);
+}
+
 export function calculateProbability(yesShares: number, noShares: number): number {
   if (yesShares + noShares === 0) return 0.5;
   return yesShares / (yesShares + noShares);
@@ -42,6 +42,8 @@ export interface Trade {
   price: number;
   size: number;
   executed_at: string;
+  maker_fee?: number; // collateral units; protocol v2 and later
+  taker_fee?: number;
 }
 
 export interface OrderSubmission {
@@ -286,7 +288,7 @@ export class OrderbookService {
     console.log(`[OrderbookService] Fetching fresh price data for ${marketId}:${outcome}`);
 
     try {
-      const url = `${this.config.apiUrl}/price/${marketId}/${outcome}`;
+      const url = `${this.config.apiUrl}/v1/price/${marketId}/${outcome}`;
       const response = await fetch(url);
 
       if (!response.ok) {
@@ -345,7 +347,7 @@ export class OrderbookService {
     console.log(`[OrderbookService] Fetching fresh orderbook data for ${marketId}:${outcome}`);
 
     try {
-      const response = await fetch(`${this.config.apiUrl}/orderbook/${marketId}/${outcome}`);
+      const response = await fetch(`${this.config.apiUrl}/v1/orderbook/${marketId}/${outcome}`);
       if (!response.ok) {
         if (response.status === 404) {
           console.log(`[OrderbookService] No orderbook data for ${marketId}:${outcome} (normal for new markets)`);
@@ -389,7 +391,7 @@ export class OrderbookService {
   /**
    * Submit an order to the orderbook
    */
-  async submitOrder(order: OrderSubmission): Promise<{success: boolean, orderId?: string, error?: string}> {
+  async submitOrder(order: OrderSubmission): Promise<{success: boolean, orderId?: string, error?: string, errorCode?: string}> {
     try {
       const orderbookOrder = {
         ...order,
@@ -397,7 +399,7 @@ export class OrderbookService {
         size: OrderbookService.sizeToOrderbook(order.size),
       };
 
-      const response = await fetch(`${this.config.apiUrl}/orders`, {
+      const response = await fetch(`${this.config.apiUrl}/v1/orders`, {
         method: 'POST',
         headers: {
           'Content-Type': 'application/json',
@@ -413,9 +415,11 @@ export class OrderbookService {
           orderId: result.order_id,
         };
       } else {
+        // Structured API error: { code, message, details }
         return {
           success: false,
-          error: result.error || 'Order submission failed',
+          error: result.message || 'Order submission failed',
+          errorCode: result.code,
         };
       }
     } catch (error) {
@@ -432,7 +436,7 @@ export class OrderbookService {
    */
   async getCollateralBalance(accountId: string, marketId: string): Promise<CollateralBalance | null> {
     try {
-      const response = await fetch(`${this.config.apiUrl}/collateral/balance`, {
+      const response = await fetch(`${this.config.apiUrl}/v1/collateral/balance`, {
         method: 'POST',
         headers: {
           'Content-Type': 'application/json',
@@ -447,14 +451,14 @@ export class OrderbookService {
         throw new Error(`HTTP ${response.status}`);
       }
 
-      const data = await response.json();
+      const { balance } = await response.json();
 
       // Convert from micro-USDC to USDC
       return {
-        ...data,
-        available_balance: data.available_balance / 1_000_000,
-        reserved_balance: data.reserved_balance / 1_000_000,
-        position_balance: data.position_balance / 1_000_000,
+        ...balance,
+        available_balance: balance.available_balance / 1_000_000,
+        reserved_balance: balance.reserved_balance / 1_000_000,
+        position_balance: balance.position_balance / 1_000_000,
       };
     } catch (error) {
       console.error('Failed to get collateral balance:', error);
@@ -467,7 +471,7 @@ export class OrderbookService {
    */
   async getMarketLiquidity(marketId: string, outcome: number): Promise<{asks: PriceLevel[], bids: PriceLevel[]} | null> {
     try {
-      const response = await fetch(`${this.config.apiUrl}/solver/liquidity/${marketId}/${outcome}`);
+      const response = await fetch(`${this.config.apiUrl}/v1/solver/liquidity/${marketId}/${outcome}`);
       if (!response.ok) {
         throw new Error(`HTTP ${response.status}`);
       }
@@ -499,7 +503,7 @@ export class OrderbookService {
  */
 export const defaultOrderbookConfig: OrderbookConfig = {
   apiUrl: process.env.NEXT_PUBLIC_ORDERBOOK_API_URL || 'http://localhost:8080',
-  wsUrl: process.env.NEXT_PUBLIC_ORDERBOOK_WS_URL || 'ws://localhost:8080/ws',
+  wsUrl: process.env.NEXT_PUBLIC_ORDERBOOK_WS_URL || 'ws://localhost:8080/v1/ws',
 };
 
 /**
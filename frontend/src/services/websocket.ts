@@ -5,8 +5,12 @@
 
 import { OrderbookSnapshot, PriceLevel, Trade } from './orderbook';
 
+// Protocol versions this client understands; the server answers a hello with the one it picked
+export const SUPPORTED_PROTOCOL_VERSIONS = [1, 2];
+
 export interface WebSocketMessage {
-  type: 'OrderbookUpdate' | 'TradeExecuted' | 'OrderUpdate';
+  type: 'OrderbookUpdate' | 'TradeExecuted' | 'OrderUpdate' | 'OrderExpired';
+  v: number;
 }
 
 export interface OrderbookUpdateMessage extends WebSocketMessage {
@@ -14,6 +18,8 @@ export interface OrderbookUpdateMessage extends WebSocketMessage {
   market_id: string;
   outcome: number;
   snapshot: OrderbookSnapshot;
+  sequence: number;
+  checksum: number; // compare with GET /orderbook/:market_id/:outcome/checksum; refetch the snapshot on mismatch
 }
 
 export interface TradeExecutedMessage extends WebSocketMessage {
@@ -28,18 +34,42 @@ export interface OrderUpdateMessage extends WebSocketMessage {
   filled_size: number;
 }
 
-export interface ConnectionEstablishedMessage extends WebSocketMessage {
+export interface OrderExpiredMessage extends WebSocketMessage {
+  type: 'OrderExpired';
+  order_id: string;
+  market_id: string;
+  outcome: number;
+  user_account: string;
+  expires_at: string | null;
+}
+
+export interface ConnectionEstablishedMessage {
   type: 'connection_established';
+  v: number;
   message: string;
   timestamp: string;
+  supported_versions: number[];
+}
+
+export interface HelloAckMessage {
+  type: 'hello_ack';
+  v: number;
+  supported_versions: number[];
+}
+
+export interface ProtocolErrorMessage {
+  type: 'error';
+  v: number;
+  message: string;
 }
 
-export type OrderbookWebSocketMessage = OrderbookUpdateMessage | TradeExecutedMessage | OrderUpdateMessage | ConnectionEstablishedMessage;
+export type OrderbookWebSocketMessage = OrderbookUpdateMessage | TradeExecutedMessage | OrderUpdateMessage | OrderExpiredMessage | ConnectionEstablishedMessage | HelloAckMessage | ProtocolErrorMessage;
 
 export interface WebSocketEventHandlers {
   onOrderbookUpdate?: (message: OrderbookUpdateMessage) => void;
   onTradeExecuted?: (message: TradeExecutedMessage) => void;
   onOrderUpdate?: (message: OrderUpdateMessage) => void;
+  onOrderExpired?: (message: OrderExpiredMessage) => void;
   onConnect?: () => void;
   onDisconnect?: () => void;
   onError?: (error: Event) => void;
@@ -95,6 +125,9 @@ export class OrderbookWebSocketService {
           this.reconnectAttempts = 0;
           this.reconnectDelay = 1000;
 
+          // Negotiate the protocol version; without a hello the server assumes v1
+          this.ws?.send(JSON.stringify({ type: 'hello', versions: SUPPORTED_PROTOCOL_VERSIONS }));
+
           this.handlers.onConnect?.();
           resolve();
         };
@@ -192,6 +225,11 @@ export class OrderbookWebSocketService {
         this.handlers.onOrderUpdate?.(convertedOrderUpdate);
         break;
 
+      case 'OrderExpired':
+        // The order's TTL passed and it was purged from the orderbook database
+        this.handlers.onOrderExpired?.(message);
+        break;
+
       case 'connection_established':
         console.log('[WebSocket] 🎉 Connection established:', message.message);
         console.log('[WebSocket] Server timestamp:', message.timestamp);
@@ -199,6 +237,14 @@ export class OrderbookWebSocketService {
         // It's just confirmation that the WebSocket is working
         break;
 
+      case 'hello_ack':
+        console.log('[WebSocket] Negotiated protocol version:', message.v);
+        break;
+
+      case 'error':
+        console.error('[WebSocket] Server error:', message.message);
+        break;
+
       default:
         console.log('Unknown WebSocket message type:', (message as any).type);
     }
@@ -283,6 +329,6 @@ export class OrderbookWebSocketService {
  * Create a WebSocket service instance
  */
 export function createOrderbookWebSocket(url?: string): OrderbookWebSocketService {
-  const wsUrl = url || process.env.NEXT_PUBLIC_ORDERBOOK_WS_URL || 'ws://localhost:8080/ws';
+  const wsUrl = url || process.env.NEXT_PUBLIC_ORDERBOOK_WS_URL || 'ws://localhost:8080/v1/ws';
   return new OrderbookWebSocketService(wsUrl);
 }
\ No newline at end of file
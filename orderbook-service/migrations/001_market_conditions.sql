@@ -0,0 +1,15 @@
+-- Adds the market registry to databases created before it existed.
+-- Fresh installs get this table from supabase-schema.sql.
+-- Existing market_conditions.json mappings are imported by the service on startup.
+
+CREATE TABLE IF NOT EXISTS market_conditions (
+    market_id TEXT PRIMARY KEY,
+    condition_id TEXT NOT NULL,
+    source TEXT NOT NULL DEFAULT 'Manual', -- 'Manual', 'VerifierSync', 'LegacyImport'
+    registered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_conditions_registered ON market_conditions (registered_at DESC);
+
+ALTER TABLE market_conditions DISABLE ROW LEVEL SECURITY;
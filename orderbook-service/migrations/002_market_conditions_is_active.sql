@@ -0,0 +1,4 @@
+-- Tracks whether a registered market is active on the verifier.
+-- Orders for inactive (paused) markets are rejected with MARKET_PAUSED.
+
+ALTER TABLE market_conditions ADD COLUMN IF NOT EXISTS is_active BOOLEAN NOT NULL DEFAULT TRUE;
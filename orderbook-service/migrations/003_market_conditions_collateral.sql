@@ -0,0 +1,5 @@
+-- Records the collateral token and outcome slot count of each registered condition.
+-- Existing rows are backfilled by the registry refresh task from the verifier and CTF.
+
+ALTER TABLE market_conditions ADD COLUMN IF NOT EXISTS collateral_token TEXT NOT NULL DEFAULT '';
+ALTER TABLE market_conditions ADD COLUMN IF NOT EXISTS outcome_count SMALLINT NOT NULL DEFAULT 2;
@@ -0,0 +1,115 @@
+-- Hourly candles for trades compacted by the retention job.
+-- Settled trades older than 7 days are folded into trade_candles and deleted from trades,
+-- so market_stats totals now read both tables.
+
+CREATE TABLE IF NOT EXISTS trade_candles (
+    market_id TEXT NOT NULL,
+    outcome SMALLINT NOT NULL,
+    bucket_start TIMESTAMPTZ NOT NULL,
+    open BIGINT NOT NULL,
+    high BIGINT NOT NULL,
+    low BIGINT NOT NULL,
+    close BIGINT NOT NULL,
+    volume NUMERIC(39,0) NOT NULL,
+    trade_count INTEGER NOT NULL,
+    PRIMARY KEY (market_id, outcome, bucket_start)
+);
+
+ALTER TABLE trade_candles DISABLE ROW LEVEL SECURITY;
+
+CREATE INDEX IF NOT EXISTS idx_trades_market_outcome_executed ON trades (market_id, outcome, executed_at);
+
+-- Function to update market stats after order/trade changes
+CREATE OR REPLACE FUNCTION update_market_stats(p_market_id TEXT, p_outcome SMALLINT)
+RETURNS VOID AS $$
+DECLARE
+    v_best_bid BIGINT;
+    v_best_ask BIGINT;
+    v_last_price BIGINT;
+    v_bid_volume NUMERIC(39,0);
+    v_ask_volume NUMERIC(39,0);
+    v_total_volume NUMERIC(39,0);
+    v_trade_count INTEGER;
+    v_spread BIGINT;
+    v_mid_price BIGINT;
+BEGIN
+    -- Calculate best bid (highest buy price)
+    SELECT MAX(price) INTO v_best_bid
+    FROM orders
+    WHERE market_id = p_market_id
+      AND outcome = p_outcome
+      AND side = 'Buy'
+      AND status IN ('Pending', 'PartiallyFilled');
+
+    -- Calculate best ask (lowest sell price)
+    SELECT MIN(price) INTO v_best_ask
+    FROM orders
+    WHERE market_id = p_market_id
+      AND outcome = p_outcome
+      AND side = 'Sell'
+      AND status IN ('Pending', 'PartiallyFilled');
+
+    -- Get last trade price
+    SELECT price INTO v_last_price
+    FROM trades
+    WHERE market_id = p_market_id
+      AND outcome = p_outcome
+    ORDER BY executed_at DESC
+    LIMIT 1;
+
+    -- Calculate bid volume
+    SELECT COALESCE(SUM(remaining_size), 0) INTO v_bid_volume
+    FROM orders
+    WHERE market_id = p_market_id
+      AND outcome = p_outcome
+      AND side = 'Buy'
+      AND status IN ('Pending', 'PartiallyFilled');
+
+    -- Calculate ask volume
+    SELECT COALESCE(SUM(remaining_size), 0) INTO v_ask_volume
+    FROM orders
+    WHERE market_id = p_market_id
+      AND outcome = p_outcome
+      AND side = 'Sell'
+      AND status IN ('Pending', 'PartiallyFilled');
+
+    -- Calculate total volume and trade count, including trades compacted into hourly candles
+    SELECT COALESCE(SUM(volume), 0), COALESCE(SUM(n), 0) INTO v_total_volume, v_trade_count
+    FROM (
+        SELECT size AS volume, 1 AS n FROM trades
+        WHERE market_id = p_market_id AND outcome = p_outcome
+        UNION ALL
+        SELECT volume, trade_count AS n FROM trade_candles
+        WHERE market_id = p_market_id AND outcome = p_outcome
+    ) history;
+
+    -- Calculate spread and mid price
+    IF v_best_bid IS NOT NULL AND v_best_ask IS NOT NULL THEN
+        v_spread := v_best_ask - v_best_bid;
+        v_mid_price := (v_best_bid + v_best_ask) / 2;
+    END IF;
+
+    -- Insert or update market stats
+    INSERT INTO market_stats (
+        market_id, outcome, last_price, best_bid, best_ask,
+        bid_volume, ask_volume, total_volume, trade_count,
+        spread, mid_price, updated_at
+    ) VALUES (
+        p_market_id, p_outcome, v_last_price, v_best_bid, v_best_ask,
+        v_bid_volume, v_ask_volume, v_total_volume, v_trade_count,
+        v_spread, v_mid_price, NOW()
+    )
+    ON CONFLICT (market_id, outcome)
+    DO UPDATE SET
+        last_price = EXCLUDED.last_price,
+        best_bid = EXCLUDED.best_bid,
+        best_ask = EXCLUDED.best_ask,
+        bid_volume = EXCLUDED.bid_volume,
+        ask_volume = EXCLUDED.ask_volume,
+        total_volume = EXCLUDED.total_volume,
+        trade_count = EXCLUDED.trade_count,
+        spread = EXCLUDED.spread,
+        mid_price = EXCLUDED.mid_price,
+        updated_at = NOW();
+END;
+$$ LANGUAGE plpgsql;
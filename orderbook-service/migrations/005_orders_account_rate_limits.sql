@@ -0,0 +1,5 @@
+-- Per-account order throttles count an account's open orders per market and its orders
+-- created in the last minute on every submission.
+
+CREATE INDEX IF NOT EXISTS idx_orders_user_market_status ON orders (user_account, market_id, status);
+CREATE INDEX IF NOT EXISTS idx_orders_user_created ON orders (user_account, created_at);
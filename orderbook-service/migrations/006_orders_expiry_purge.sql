@@ -0,0 +1,8 @@
+-- Expired orders are deleted by the engine's purge task once a minute. Databases created before
+-- order TTLs may lack the column; the partial index keeps the purge scan off filled/cancelled rows.
+-- Orders referenced by a trade are kept (trades.maker_order_id / taker_order_id have no cascade).
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_orders_expiry_purge ON orders (expires_at)
+    WHERE expires_at IS NOT NULL AND status NOT IN ('Filled', 'Cancelled');
@@ -0,0 +1,15 @@
+-- API keys for market makers and bots, created through POST /admin/api-keys.
+-- Requests carrying a valid X-API-Key skip the global order bucket; request_count and
+-- last_used_at are bumped on every authenticated request.
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    key_id TEXT PRIMARY KEY,
+    secret_hash TEXT NOT NULL,
+    rate_limit_multiplier INTEGER NOT NULL DEFAULT 1,
+    allowed_endpoints TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ,
+    request_count BIGINT NOT NULL DEFAULT 0
+);
+
+ALTER TABLE api_keys DISABLE ROW LEVEL SECURITY;
@@ -0,0 +1,21 @@
+-- Chain reconciliation: links between orderbook orders and the solver contract orders they were
+-- created from, and the report of each reconciliation pass.
+-- Links used to live only in memory, so orders submitted before this migration are not checked.
+
+CREATE TABLE IF NOT EXISTS solver_order_links (
+    order_id UUID PRIMARY KEY,
+    solver_order_id TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS reconciliation_reports (
+    report_id UUID PRIMARY KEY,
+    started_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL,
+    divergence_count INTEGER NOT NULL,
+    report JSONB NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_reconciliation_reports_completed ON reconciliation_reports (completed_at DESC);
+
+ALTER TABLE solver_order_links DISABLE ROW LEVEL SECURITY;
+ALTER TABLE reconciliation_reports DISABLE ROW LEVEL SECURITY;
@@ -0,0 +1,21 @@
+-- Trading fees: each trade records the maker and taker fee it was charged, and the platform fee
+-- ledger holds what settlement actually collected until the sweep moves it to the platform account.
+-- Trades executed before this migration carry no fee.
+
+ALTER TABLE trades ADD COLUMN IF NOT EXISTS maker_fee NUMERIC(39,0) NOT NULL DEFAULT 0;
+ALTER TABLE trades ADD COLUMN IF NOT EXISTS taker_fee NUMERIC(39,0) NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS platform_fees (
+    trade_id UUID PRIMARY KEY,
+    market_id TEXT NOT NULL,
+    collateral_token TEXT NOT NULL,
+    maker_fee NUMERIC(39,0) NOT NULL,
+    taker_fee NUMERIC(39,0) NOT NULL,
+    collected_at TIMESTAMPTZ NOT NULL,
+    sweep_id UUID              -- NULL until swept to the platform account
+);
+
+CREATE INDEX IF NOT EXISTS idx_platform_fees_market ON platform_fees (market_id);
+CREATE INDEX IF NOT EXISTS idx_platform_fees_unswept ON platform_fees (collected_at) WHERE sweep_id IS NULL;
+
+ALTER TABLE platform_fees DISABLE ROW LEVEL SECURITY;
@@ -0,0 +1,10 @@
+-- Collateral yield: USDC principal each user has parked in the yield protocol through the service's
+-- signer account. Accrued interest is not stored; it is the protocol balance above the principal sum.
+
+CREATE TABLE IF NOT EXISTS yield_deposits (
+    account_id TEXT PRIMARY KEY,
+    amount NUMERIC(39,0) NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE yield_deposits DISABLE ROW LEVEL SECURITY;
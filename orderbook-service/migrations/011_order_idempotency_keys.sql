@@ -0,0 +1,15 @@
+-- Idempotent order submission: the first POST /orders (or solver order) under a key stores the
+-- order id and a hash of its payload; repeats within the TTL get the stored response back. Rows
+-- older than IDEMPOTENCY_KEY_TTL_SECS are deleted when the next key is claimed.
+
+CREATE TABLE IF NOT EXISTS order_idempotency_keys (
+    idempotency_key TEXT PRIMARY KEY,    -- "<account>:<client key>"
+    payload_hash TEXT NOT NULL,
+    order_id UUID NOT NULL,
+    response TEXT,                       -- JSON response body, NULL while the first request runs
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_idempotency_keys_created ON order_idempotency_keys (created_at);
+
+ALTER TABLE order_idempotency_keys DISABLE ROW LEVEL SECURITY;
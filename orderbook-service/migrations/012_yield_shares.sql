@@ -0,0 +1,8 @@
+-- Collateral yield shares: each deposit mints shares at the protocol's balance per share, so a user's
+-- interest is what their shares are worth above their principal, not a cut of everyone's interest.
+-- Existing positions become one share per unit of principal; any interest that built up before this
+-- migration is then shared in proportion to principal, as it was before.
+
+ALTER TABLE yield_deposits ADD COLUMN IF NOT EXISTS shares NUMERIC(39,0) NOT NULL DEFAULT 0;
+
+UPDATE yield_deposits SET shares = amount WHERE shares = 0;
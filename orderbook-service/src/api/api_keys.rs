@@ -0,0 +1,126 @@
+// API key authentication
+// Market makers and bots authenticate with `X-API-Key: <key_id>.<secret>` on the high-throughput
+// routes. A valid key skips the global order bucket and multiplies the per-account order limits by
+// its rate_limit_multiplier; each use bumps the key's request_count and last_used_at. Keys are
+// created with POST /admin/api-keys and the secret is only returned then - the database keeps its
+// sha256.
+//
+// Keyless requests are refused unless ServiceConfig::api_key_required (API_KEY_REQUIRED) is turned
+// off for deployments where browsers submit orders directly; they then fall back to the global bucket.
+
+use axum::{
+    extract::{Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use rand::RngCore;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::storage::DatabaseTrait;
+use crate::types::ApiKey;
+use crate::AppState;
+use super::error::ApiError;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// POST /admin/api-keys body
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateApiKeyRequest {
+    #[serde(default = "default_multiplier")]
+    pub rate_limit_multiplier: u32,
+    #[serde(default)]
+    pub allowed_endpoints: Vec<String>,
+}
+
+fn default_multiplier() -> u32 {
+    1
+}
+
+pub fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// New key record plus the `<key_id>.<secret>` value to hand to the client
+pub fn generate_api_key(request: &CreateApiKeyRequest) -> Result<(ApiKey, String), ApiError> {
+    if request.rate_limit_multiplier == 0 {
+        return Err(ApiError::InvalidRequest("rate_limit_multiplier must be at least 1".to_string()));
+    }
+    if let Some(pattern) = request.allowed_endpoints.iter().find(|p| !p.starts_with('/')) {
+        return Err(ApiError::InvalidRequest(format!("Endpoint pattern {} must start with /", pattern)));
+    }
+
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut secret);
+    let secret = hex::encode(secret);
+    let key_id = format!("ak_{}", &Uuid::new_v4().simple().to_string()[..16]);
+
+    let key = ApiKey {
+        key_id: key_id.clone(),
+        secret_hash: hash_secret(&secret),
+        rate_limit_multiplier: request.rate_limit_multiplier,
+        allowed_endpoints: request.allowed_endpoints.clone(),
+        created_at: Utc::now(),
+        last_used_at: None,
+        request_count: 0,
+    };
+    Ok((key, format!("{}.{}", key_id, secret)))
+}
+
+/// `*` in a pattern matches exactly one path segment
+pub fn endpoint_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.trim_end_matches('/').split('/').collect();
+    let path: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    pattern.len() == path.len() && pattern.iter().zip(&path).all(|(p, s)| *p == "*" || p == s)
+}
+
+/// Check the X-API-Key header for `path`. Ok(None) when no key was sent
+pub async fn authenticate(
+    database: &dyn DatabaseTrait,
+    headers: &HeaderMap,
+    path: &str,
+) -> Result<Option<ApiKey>, ApiError> {
+    let Some(value) = headers.get(API_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let value = value.to_str().map_err(|_| ApiError::Unauthorized("Invalid API key".to_string()))?;
+    let (key_id, secret) = value.split_once('.')
+        .ok_or_else(|| ApiError::Unauthorized("Invalid API key".to_string()))?;
+
+    let key = database.get_api_key(key_id).await?
+        .filter(|key| key.secret_hash == hash_secret(secret))
+        .ok_or_else(|| {
+            warn!("Rejected API key {} for {}", key_id, path);
+            ApiError::Unauthorized("Invalid API key".to_string())
+        })?;
+
+    if !key.allowed_endpoints.is_empty() && !key.allowed_endpoints.iter().any(|p| endpoint_matches(p, path)) {
+        return Err(ApiError::Unauthorized(format!("API key {} is not allowed to call {}", key.key_id, path)));
+    }
+
+    database.record_api_key_use(&key.key_id, Utc::now()).await?;
+    Ok(Some(key))
+}
+
+/// Middleware for the high-throughput routes. Handlers find the key in the request extensions
+pub async fn require_api_key(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let path = request.uri().path().to_string();
+    match authenticate(state.database.as_ref(), request.headers(), &path).await? {
+        Some(key) => {
+            request.extensions_mut().insert(key);
+        }
+        None if state.config.api_key_required => {
+            return Err(ApiError::Unauthorized("X-API-Key header required".to_string()));
+        }
+        None => state.rate_limiter.try_acquire_global()?,
+    }
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,166 @@
+// Structured API errors
+// Handlers return ApiError so clients can branch on a stable `code` instead of parsing messages.
+// Internal code raises these through anyhow (`Err(ApiError::... .into())`); the From impl below
+// recovers them at the HTTP boundary and anything unrecognised becomes INTERNAL.
+//
+// Response body: { "code": "...", "message": "...", "details": { ... } | null }
+//
+// | code                 | status | meaning                                                   |
+// |----------------------|--------|-----------------------------------------------------------|
+// | INVALID_REQUEST      | 400    | Malformed or inconsistent request fields                  |
+// | INSUFFICIENT_BALANCE | 422    | Not enough USDC / outcome tokens - user needs to top up   |
+// | MARKET_NOT_FOUND     | 404    | Market has no registered condition id                     |
+// | MARKET_PAUSED        | 409    | Market is deactivated on the verifier                     |
+// | IDEMPOTENCY_CONFLICT | 409    | Idempotency key reused for a different order, or in use   |
+// | PRICE_OUT_OF_BAND    | 422    | Price outside the allowed band                            |
+// | ORDER_NOT_FOUND      | 404    | Order doesn't exist or is no longer resting in the book   |
+// | UNAUTHORIZED         | 403    | Caller doesn't own the resource                           |
+// | RATE_LIMITED         | 429    | Too many orders - retry after the Retry-After header      |
+// | CHAIN_UNAVAILABLE    | 503    | NEAR RPC unreachable - retry later, not a user error      |
+// | SHUTTING_DOWN        | 503    | Service is draining for shutdown - resubmit after restart |
+// | INTERNAL             | 500    | Anything else                                             |
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+use tracing::error;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    InvalidRequest(String),
+
+    #[error("Insufficient {asset} balance: need {required}, have {available} available")]
+    InsufficientBalance {
+        asset: String,      // "USDC", another collateral token id, or "outcome tokens"
+        required: u128,
+        available: u128,
+    },
+
+    #[error("Market {0} not found")]
+    MarketNotFound(String),
+
+    #[error("Market {0} is paused")]
+    MarketPaused(String),
+
+    #[error("{0}")]
+    IdempotencyConflict(String),
+
+    #[error("Price {price} outside allowed band {min}-{max}")]
+    PriceOutOfBand { price: u64, min: u64, max: u64 },
+
+    #[error("Order {0} not found")]
+    OrderNotFound(Uuid),
+
+    #[error("{0}")]
+    Unauthorized(String),
+
+    #[error("Rate limited: {reason}")]
+    RateLimited { reason: String, retry_after_secs: u64 },
+
+    #[error("Blockchain unavailable: {0}")]
+    ChainUnavailable(String),
+
+    #[error("Service is shutting down and no longer accepts orders")]
+    ShuttingDown,
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::InvalidRequest(_) => "INVALID_REQUEST",
+            ApiError::InsufficientBalance { .. } => "INSUFFICIENT_BALANCE",
+            ApiError::MarketNotFound(_) => "MARKET_NOT_FOUND",
+            ApiError::MarketPaused(_) => "MARKET_PAUSED",
+            ApiError::IdempotencyConflict(_) => "IDEMPOTENCY_CONFLICT",
+            ApiError::PriceOutOfBand { .. } => "PRICE_OUT_OF_BAND",
+            ApiError::OrderNotFound(_) => "ORDER_NOT_FOUND",
+            ApiError::Unauthorized(_) => "UNAUTHORIZED",
+            ApiError::RateLimited { .. } => "RATE_LIMITED",
+            ApiError::ChainUnavailable(_) => "CHAIN_UNAVAILABLE",
+            ApiError::ShuttingDown => "SHUTTING_DOWN",
+            ApiError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::InsufficientBalance { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::MarketNotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::MarketPaused(_) | ApiError::IdempotencyConflict(_) => StatusCode::CONFLICT,
+            ApiError::PriceOutOfBand { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::OrderNotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Unauthorized(_) => StatusCode::FORBIDDEN,
+            ApiError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::ChainUnavailable(_) | ApiError::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn details(&self) -> Value {
+        match self {
+            ApiError::InsufficientBalance { asset, required, available } => json!({
+                "asset": asset,
+                "required": required.to_string(),
+                "available": available.to_string(),
+            }),
+            ApiError::MarketNotFound(market_id) | ApiError::MarketPaused(market_id) => json!({
+                "market_id": market_id,
+            }),
+            ApiError::PriceOutOfBand { price, min, max } => json!({
+                "price": price,
+                "min": min,
+                "max": max,
+            }),
+            ApiError::OrderNotFound(order_id) => json!({
+                "order_id": order_id,
+            }),
+            ApiError::RateLimited { retry_after_secs, .. } => json!({
+                "retry_after_secs": retry_after_secs,
+            }),
+            _ => Value::Null,
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast::<ApiError>() {
+            Ok(api_error) => api_error,
+            Err(err) => ApiError::Internal(err.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let message = match &self {
+            // Don't leak internals to clients; the full error goes to the log
+            ApiError::Internal(detail) => {
+                error!("❌ Internal API error: {}", detail);
+                "Internal server error".to_string()
+            }
+            other => other.to_string(),
+        };
+
+        let body = json!({
+            "code": self.code(),
+            "message": message,
+            "details": self.details(),
+        });
+
+        let mut response = (self.status(), Json(body)).into_response();
+        if let ApiError::RateLimited { retry_after_secs, .. } = &self {
+            response.headers_mut().insert(header::RETRY_AFTER, header::HeaderValue::from(*retry_after_secs));
+        }
+        response
+    }
+}
@@ -1,75 +1,109 @@
 // HTTP API handlers
 
 use axum::{
-    extract::{Path, State, WebSocketUpgrade, ws::WebSocket},
-    http::StatusCode,
+    extract::{Extension, Path, Query, State, WebSocketUpgrade, ws::WebSocket},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use futures_util::{SinkExt, StreamExt};
-use serde_json::json;
+use serde_json::{json, Value};
 use uuid::Uuid;
-use chrono::Utc;
-use tracing::{info, error};
+use chrono::{DateTime, Utc};
+use tracing::{info, warn, error};
 use anyhow::Result;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 use crate::types::{
-    Order, SubmitOrderRequest, SubmitOrderResponse, CancelOrderRequest, TradeMatch, OrderStatus
+    Order, SubmitOrderRequest, SubmitOrderResponse, CancelOrderRequest, TradeMatch, OrderStatus,
+    MarketConditionRecord, MarketRegistrationSource, OrderbookSnapshot, OrderbookSyncSnapshot, OrderbookChecksum, MarketPrice, OHLCV,
+    CollateralBalance, CollateralStatus, OrderQuote, OrderSide, OrderType, ApiKey, ReconciliationReport, FeeReport,
+    YieldBalance, YieldHarvest
 };
+use crate::market_registry::{resolve_active_condition, fetch_market_record, BINARY_OUTCOME_COUNT};
+use crate::idempotency::{self, IDEMPOTENCY_KEY_HEADER};
+use crate::near_client::rpc_metrics;
 use crate::AppState;
+use super::error::ApiError;
+use super::rate_limit::{RateLimitConfig, RateLimitUpdate};
+use super::api_keys::{generate_api_key, CreateApiKeyRequest};
+use super::protocol::{self, ClientMessage, ControlMessage, DEFAULT_VERSION, SUPPORTED_VERSIONS};
 use serde::Deserialize;
 
-pub async fn health_check() -> impl IntoResponse {
+// Allowed limit price band in 1/100000 of a dollar ($0.001 - $0.99999)
+const MIN_ORDER_PRICE: u64 = 100;
+const MAX_ORDER_PRICE: u64 = 99999;
+
+/// Liveness plus chain health; "degraded" while the NEAR RPC is unhealthy and trading is halted
+pub async fn health_check(State(state): State<AppState>) -> impl IntoResponse {
+    let chain = state.matching_engine.chain_status();
     Json(json!({
-        "status": "healthy",
+        "status": if chain.degraded { "degraded" } else { "healthy" },
         "service": "orderbook",
+        "chain": chain,
         "timestamp": Utc::now()
     }))
 }
 
+/// Prometheus scrape endpoint (NEAR RPC metrics plus matching engine counters and latencies)
+pub async fn get_metrics() -> Result<Response, ApiError> {
+    let body = rpc_metrics::render()?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response())
+}
+
+/// POST /orders: 201 with the new order. With an Idempotency-Key header (or client_order_id) a
+/// retry of the same order gets the original response back with 200 instead of placing it again
 pub async fn submit_order(
     State(state): State<AppState>,
+    api_key: Option<Extension<ApiKey>>,
+    headers: HeaderMap,
     Json(request): Json<SubmitOrderRequest>,
-) -> impl IntoResponse {
+) -> Result<Response, ApiError> {
     info!("Received order submission: {:?}", request);
 
     // Validate request
-    if let Err(e) = validate_order_request(&request) {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "error": e,
-                "order_id": null
-            }))
-        ).into_response();
-    }
-
-    // Get market info to validate and get condition_id
-    let condition_id = match state.near_client.get_market_condition_id(&request.market_id).await {
-        Ok(Some(id)) => id,
-        Ok(None) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(json!({
-                    "error": "Market not found",
-                    "order_id": null
-                }))
-            ).into_response();
-        }
-        Err(e) => {
-            error!("Failed to get market info: {}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to validate market",
-                    "order_id": null
-                }))
-            ).into_response();
-        }
+    validate_order_request(&request)?;
+    let header_key = headers.get(IDEMPOTENCY_KEY_HEADER)
+        .map(|value| value.to_str().map_err(|_| ApiError::InvalidRequest("Idempotency-Key header must be visible ASCII".to_string())))
+        .transpose()?;
+    let key = idempotency::client_key(header_key.or(request.client_order_id.as_deref()))?;
+    let multiplier = api_key.map(|Extension(key)| key.rate_limit_multiplier).unwrap_or(1);
+
+    let Some(key) = key else {
+        let response = place_order(&state, request, multiplier, Uuid::new_v4()).await?;
+        return Ok((StatusCode::CREATED, Json(response)).into_response());
     };
 
+    let database = state.database.clone();
+    let account_id = request.user_account.clone();
+    let payload_hash = idempotency::payload_hash(&request)?;
+    let ttl_secs = state.config.idempotency_key_ttl_secs;
+    let submitted = idempotency::submit_once(database.as_ref(), &account_id, &key, payload_hash, ttl_secs, |order_id| async move {
+        let response = place_order(&state, request, multiplier, order_id).await?;
+        serde_json::to_value(response).map_err(|e| ApiError::Internal(format!("Failed to serialize order response: {}", e)))
+    }).await?;
+
+    let status = if submitted.replayed { StatusCode::OK } else { StatusCode::CREATED };
+    Ok((status, Json(submitted.response)).into_response())
+}
+
+/// Throttle, match and book one order under `order_id`
+async fn place_order(
+    state: &AppState,
+    request: SubmitOrderRequest,
+    multiplier: u32,
+    order_id: Uuid,
+) -> Result<SubmitOrderResponse, ApiError> {
+    // Per-account throttles (open orders in this market, orders per minute), scaled for API keys
+    state.rate_limiter
+        .check_account_scaled(state.database.as_ref(), &request.user_account, &request.market_id, multiplier)
+        .await?;
+
+    // Get market info to validate and get condition_id (rejects unknown and paused markets)
+    let condition_id = resolve_active_condition(state.database.as_ref(), &request.market_id).await?;
+
     // Create order
-    let order_id = Uuid::new_v4();
     let user_account = request.user_account.clone();
     let order = Order {
         order_id,
@@ -90,142 +124,225 @@ pub async fn submit_order(
     };
 
     // Submit to matching engine
-    match state.matching_engine.submit_order(order).await {
-        Ok(trades) => {
-            let matches: Vec<TradeMatch> = trades.iter().map(|trade| {
-                let counterparty = if trade.maker_account == user_account {
-                    &trade.taker_account
-                } else {
-                    &trade.maker_account
-                };
-
-                TradeMatch {
-                    trade_id: trade.trade_id,
-                    counterparty: counterparty.clone(),
-                    price: trade.price,
-                    size: trade.size,
-                    settlement_pending: true,
-                }
-            }).collect();
+    let trades = state.matching_engine.submit_order(order).await.map_err(|e| {
+        error!("Failed to submit order {}: {}", order_id, e);
+        ApiError::from(e)
+    })?;
+
+    let matches: Vec<TradeMatch> = trades.iter().map(|trade| {
+        let counterparty = if trade.maker_account == user_account {
+            &trade.taker_account
+        } else {
+            &trade.maker_account
+        };
+
+        TradeMatch {
+            trade_id: trade.trade_id,
+            counterparty: counterparty.clone(),
+            price: trade.price,
+            size: trade.size,
+            settlement_pending: true,
+        }
+    }).collect();
 
-            let response = SubmitOrderResponse {
-                order_id,
-                status: if matches.is_empty() { "pending".to_string() } else { "partially_filled".to_string() },
-                message: format!("Order submitted successfully with {} matches", matches.len()),
-                matches,
-            };
+    let response = SubmitOrderResponse {
+        order_id,
+        status: if matches.is_empty() { "pending".to_string() } else { "partially_filled".to_string() },
+        message: format!("Order submitted successfully with {} matches", matches.len()),
+        matches,
+    };
 
-            info!("Order {} submitted successfully with {} matches", order_id, trades.len());
+    info!("Order {} submitted successfully with {} matches", order_id, trades.len());
 
-            (StatusCode::OK, Json(response)).into_response()
-        }
-        Err(e) => {
-            error!("Failed to submit order: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": format!("Failed to submit order: {}", e),
-                    "order_id": order_id
-                }))
-            ).into_response()
-        }
-    }
+    Ok(response)
 }
 
 pub async fn cancel_order(
     State(state): State<AppState>,
     Path(order_id): Path<Uuid>,
     Json(request): Json<CancelOrderRequest>,
-) -> impl IntoResponse {
+) -> Result<Json<Value>, ApiError> {
     info!("Cancelling order: {}", order_id);
 
     // Verify order_id matches
     if request.order_id != order_id {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "error": "Order ID mismatch",
-                "cancelled": false
-            }))
-        ).into_response();
+        return Err(ApiError::InvalidRequest("Order ID mismatch".to_string()));
     }
 
-    match state.matching_engine.cancel_order(order_id, &request.user_account).await {
-        Ok(cancelled) => {
-            if cancelled {
-                info!("Order {} cancelled successfully", order_id);
-                (StatusCode::OK, Json(json!({
-                    "message": "Order cancelled successfully",
-                    "cancelled": true
-                }))).into_response()
-            } else {
-                (StatusCode::BAD_REQUEST, Json(json!({
-                    "error": "Order could not be cancelled",
-                    "cancelled": false
-                }))).into_response()
-            }
-        }
-        Err(e) => {
-            error!("Failed to cancel order {}: {}", order_id, e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": format!("Failed to cancel order: {}", e),
-                    "cancelled": false
-                }))
-            ).into_response()
-        }
+    let cancelled = state.matching_engine.cancel_order(order_id, &request.user_account).await.map_err(|e| {
+        error!("Failed to cancel order {}: {}", order_id, e);
+        ApiError::from(e)
+    })?;
+
+    if !cancelled {
+        return Err(ApiError::InvalidRequest("Order could not be cancelled".to_string()));
     }
+
+    info!("Order {} cancelled successfully", order_id);
+    Ok(Json(json!({
+        "message": "Order cancelled successfully",
+        "cancelled": true
+    })))
 }
 
 pub async fn get_orderbook(
     State(state): State<AppState>,
     Path((market_id, outcome)): Path<(String, u8)>,
-) -> impl IntoResponse {
-    match state.matching_engine.get_orderbook_snapshot(&market_id, outcome).await {
-        Ok(Some(snapshot)) => {
-            (StatusCode::OK, Json(snapshot)).into_response()
-        }
-        Ok(None) => {
-            (StatusCode::NOT_FOUND, Json(json!({
-                "error": "Orderbook not found"
-            }))).into_response()
-        }
-        Err(e) => {
-            error!("Failed to get orderbook snapshot: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": format!("Failed to get orderbook: {}", e)
-                }))
-            ).into_response()
-        }
-    }
+) -> Result<Json<OrderbookSnapshot>, ApiError> {
+    state.matching_engine.get_orderbook_snapshot(&market_id, outcome).await?
+        .map(Json)
+        .ok_or(ApiError::MarketNotFound(market_id))
+}
+
+/// Live book at its sequence number. Clients buffer OrderbookDelta messages, fetch this, then
+/// apply the buffered deltas with a sequence above the snapshot's
+pub async fn get_orderbook_sync_snapshot(
+    State(state): State<AppState>,
+    Path((market_id, outcome)): Path<(String, u8)>,
+) -> Result<Json<OrderbookSyncSnapshot>, ApiError> {
+    state.matching_engine.get_orderbook_sync_snapshot(&market_id, outcome).await?
+        .map(Json)
+        .ok_or(ApiError::MarketNotFound(market_id))
+}
+
+/// Integrity checksum of the live book; clients compare it with their local copy
+pub async fn get_orderbook_checksum(
+    State(state): State<AppState>,
+    Path((market_id, outcome)): Path<(String, u8)>,
+) -> Result<Json<OrderbookChecksum>, ApiError> {
+    state.matching_engine.get_orderbook_checksum(&market_id, outcome).await
+        .map(Json)
+        .ok_or(ApiError::MarketNotFound(market_id))
 }
 
 pub async fn get_market_price(
     State(state): State<AppState>,
     Path((market_id, outcome)): Path<(String, u8)>,
-) -> impl IntoResponse {
-    match state.matching_engine.get_market_price(&market_id, outcome).await {
-        Ok(Some(price)) => {
-            (StatusCode::OK, Json(price)).into_response()
-        }
-        Ok(None) => {
-            (StatusCode::NOT_FOUND, Json(json!({
-                "error": "Market not found"
-            }))).into_response()
-        }
-        Err(e) => {
-            error!("Failed to get market price: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": format!("Failed to get market price: {}", e)
-                }))
-            ).into_response()
-        }
+) -> Result<Json<MarketPrice>, ApiError> {
+    state.matching_engine.get_market_price(&market_id, outcome).await?
+        .map(Json)
+        .ok_or(ApiError::MarketNotFound(market_id))
+}
+
+/// Default candle window when `from` is omitted
+const DEFAULT_CANDLE_WINDOW_SECS: u64 = 24 * 60 * 60;
+const DEFAULT_CANDLE_BUCKET_SECS: u64 = 300;
+/// Upper bound on candles per request so one call can't scan years at 1s resolution
+const MAX_CANDLES_PER_REQUEST: u64 = 5000;
+
+#[derive(Deserialize)]
+pub struct CandleQuery {
+    pub from: Option<u64>,              // unix seconds, inclusive
+    pub to: Option<u64>,                // unix seconds, exclusive
+    pub bucket_seconds: Option<u64>,
+}
+
+/// Price history: GET /price/:market_id/:outcome/candles?from=&to=&bucket_seconds=300
+pub async fn get_price_candles(
+    State(state): State<AppState>,
+    Path((market_id, outcome)): Path<(String, u8)>,
+    Query(query): Query<CandleQuery>,
+) -> Result<Json<Vec<OHLCV>>, ApiError> {
+    let bucket_seconds = query.bucket_seconds.unwrap_or(DEFAULT_CANDLE_BUCKET_SECS);
+    if bucket_seconds == 0 {
+        return Err(ApiError::InvalidRequest("bucket_seconds must be greater than 0".to_string()));
+    }
+
+    let to = query.to.unwrap_or_else(|| Utc::now().timestamp().max(0) as u64);
+    let from = query.from.unwrap_or_else(|| to.saturating_sub(DEFAULT_CANDLE_WINDOW_SECS));
+    if from >= to {
+        return Err(ApiError::InvalidRequest(format!("from ({}) must be before to ({})", from, to)));
+    }
+    if (to - from).div_ceil(bucket_seconds) > MAX_CANDLES_PER_REQUEST {
+        return Err(ApiError::InvalidRequest(format!(
+            "Range covers more than {} candles; use a larger bucket_seconds", MAX_CANDLES_PER_REQUEST
+        )));
     }
+
+    let to_datetime = |secs: u64| {
+        i64::try_from(secs).ok()
+            .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+            .ok_or_else(|| ApiError::InvalidRequest(format!("Timestamp {} out of range", secs)))
+    };
+
+    let candles = state.database
+        .get_price_candles(&market_id, outcome, to_datetime(from)?, to_datetime(to)?, bucket_seconds)
+        .await?;
+    Ok(Json(candles))
+}
+
+#[derive(Deserialize)]
+pub struct QuoteQuery {
+    pub market_id: String,
+    pub outcome: u8,
+    pub side: OrderSide,
+    pub size: u64,      // query strings can't carry u128; POST /quote takes the full range
+}
+
+/// Quote a market order: GET /quote?market_id=&outcome=&side=&size=
+pub async fn get_quote(
+    State(state): State<AppState>,
+    Query(query): Query<QuoteQuery>,
+) -> Result<Json<OrderQuote>, ApiError> {
+    let request = SubmitOrderRequest {
+        market_id: query.market_id,
+        user_account: "quote".to_string(),
+        solver_account: "quote".to_string(),
+        outcome: query.outcome,
+        side: query.side,
+        order_type: OrderType::Market,
+        price: None,
+        size: query.size as u128,
+        expires_at: None,
+        client_order_id: None,
+    };
+
+    quote_order(&state, request).await.map(Json)
+}
+
+/// Quote any order type with the same body as POST /orders
+pub async fn post_quote(
+    State(state): State<AppState>,
+    Json(request): Json<SubmitOrderRequest>,
+) -> Result<Json<OrderQuote>, ApiError> {
+    quote_order(&state, request).await.map(Json)
+}
+
+async fn quote_order(state: &AppState, request: SubmitOrderRequest) -> Result<OrderQuote, ApiError> {
+    validate_order_request(&request)?;
+    let condition_id = resolve_active_condition(state.database.as_ref(), &request.market_id).await?;
+
+    // The quoted order would take liquidity, so it pays the market's taker fee
+    let fee_bps = state.matching_engine.get_collateral_manager()
+        .get_market_config(&request.market_id).await?
+        .taker_fee_bps;
+
+    // Same shape submit_order builds, so the dry run sees exactly what the matcher would
+    let order = Order {
+        order_id: Uuid::new_v4(),
+        market_id: request.market_id,
+        condition_id,
+        user_account: request.user_account,
+        outcome: request.outcome,
+        side: request.side,
+        order_type: request.order_type,
+        price: request.price.unwrap_or(0),
+        original_size: request.size,
+        remaining_size: request.size,
+        filled_size: 0,
+        status: OrderStatus::Pending,
+        created_at: Utc::now(),
+        expires_at: request.expires_at,
+        solver_account: request.solver_account,
+    };
+
+    let quote = state.matching_engine.quote_order(order, fee_bps).await?;
+    info!(
+        "Quoted {:?} {} of market {} outcome {}: {} filled, avg {:?}",
+        quote.side, quote.requested_size, quote.market_id, quote.outcome, quote.filled_size, quote.average_price
+    );
+
+    Ok(quote)
 }
 
 pub async fn websocket_handler(
@@ -241,14 +358,20 @@ async fn websocket_connection(socket: WebSocket, state: AppState) {
     let (mut ws_sender, mut ws_receiver) = socket.split();
     let mut broadcast_receiver = state.ws_broadcaster.subscribe();
 
+    // Protocol version for this connection; v1 until the client's hello picks another
+    let version = Arc::new(AtomicU32::new(DEFAULT_VERSION));
+    // Replies to client messages go out through the broadcast task, which owns the sender
+    let (control_tx, mut control_rx) = tokio::sync::mpsc::channel::<ControlMessage>(16);
+
     // Send welcome message immediately to confirm connection
-    let welcome_message = serde_json::json!({
-        "type": "connection_established",
-        "message": "WebSocket connection successful",
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    });
+    let welcome_message = ControlMessage::ConnectionEstablished {
+        v: DEFAULT_VERSION,
+        message: "WebSocket connection successful".to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        supported_versions: SUPPORTED_VERSIONS.to_vec(),
+    };
 
-    if let Err(e) = ws_sender.send(axum::extract::ws::Message::Text(welcome_message.to_string())).await {
+    if let Err(e) = ws_sender.send(axum::extract::ws::Message::Text(json!(welcome_message).to_string())).await {
         error!("Failed to send welcome message: {}", e);
         return;
     }
@@ -256,12 +379,30 @@ async fn websocket_connection(socket: WebSocket, state: AppState) {
     info!("Sent WebSocket welcome message");
 
     // Handle incoming WebSocket messages from client (if any)
+    let client_version = version.clone();
     let client_task = tokio::spawn(async move {
         while let Some(msg) = ws_receiver.next().await {
             match msg {
                 Ok(axum::extract::ws::Message::Text(text)) => {
                     info!("Received WebSocket message from client: {}", text);
-                    // Could handle client commands here (subscribe to specific markets, etc.)
+                    let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                        Ok(ClientMessage::Hello { versions }) => match protocol::negotiate_version(&versions) {
+                            Some(negotiated) => {
+                                client_version.store(negotiated, Ordering::Relaxed);
+                                info!("WebSocket client negotiated protocol v{}", negotiated);
+                                ControlMessage::HelloAck { v: negotiated, supported_versions: SUPPORTED_VERSIONS.to_vec() }
+                            }
+                            None => ControlMessage::Error {
+                                v: client_version.load(Ordering::Relaxed),
+                                message: format!("No common protocol version; server supports {:?}", SUPPORTED_VERSIONS),
+                            },
+                        },
+                        // Anything else is ignored, as before versioning
+                        Err(_) => continue,
+                    };
+                    if control_tx.send(reply).await.is_err() {
+                        break;
+                    }
                 }
                 Ok(axum::extract::ws::Message::Ping(data)) => {
                     info!("Received WebSocket ping from client");
@@ -298,8 +439,10 @@ async fn websocket_connection(socket: WebSocket, state: AppState) {
                 msg_result = broadcast_receiver.recv() => {
                     match msg_result {
                         Ok(message) => {
-                            let json_message = match serde_json::to_string(&message) {
-                                Ok(json) => json,
+                            let json_message = match protocol::encode(&message, version.load(Ordering::Relaxed)) {
+                                Ok(Some(json)) => json,
+                                // Not part of the client's protocol version
+                                Ok(None) => continue,
                                 Err(e) => {
                                     error!("Failed to serialize WebSocket message: {}", e);
                                     continue;
@@ -322,6 +465,13 @@ async fn websocket_connection(socket: WebSocket, state: AppState) {
                         }
                     }
                 }
+                // Replies to the client's own messages (hello)
+                Some(control) = control_rx.recv() => {
+                    if let Err(e) = ws_sender.send(axum::extract::ws::Message::Text(json!(control).to_string())).await {
+                        error!("Failed to send WebSocket message: {}", e);
+                        break;
+                    }
+                }
                 // Send periodic ping
                 _ = ping_interval.tick() => {
                     if let Err(e) = ws_sender.send(axum::extract::ws::Message::Ping(vec![])).await {
@@ -351,80 +501,49 @@ async fn websocket_connection(socket: WebSocket, state: AppState) {
     info!("WebSocket connection closed");
 }
 
-fn validate_order_request(request: &SubmitOrderRequest) -> Result<(), String> {
+fn validate_order_request(request: &SubmitOrderRequest) -> Result<(), ApiError> {
     if request.market_id.is_empty() {
-        return Err("Market ID cannot be empty".to_string());
+        return Err(ApiError::InvalidRequest("Market ID cannot be empty".to_string()));
     }
     
     if request.user_account.is_empty() {
-        return Err("User account cannot be empty".to_string());
+        return Err(ApiError::InvalidRequest("User account cannot be empty".to_string()));
     }
     
     if request.solver_account.is_empty() {
-        return Err("Solver account cannot be empty".to_string());
+        return Err(ApiError::InvalidRequest("Solver account cannot be empty".to_string()));
     }
     
     if request.outcome > 1 {
-        return Err("Outcome must be 0 (NO) or 1 (YES)".to_string());
+        return Err(ApiError::InvalidRequest("Outcome must be 0 (NO) or 1 (YES)".to_string()));
     }
     
     if request.size == 0 {
-        return Err("Order size must be greater than 0".to_string());
+        return Err(ApiError::InvalidRequest("Order size must be greater than 0".to_string()));
     }
     
-    // Validate price based on order type (cents format: 0-100)
+    // Validate price based on order type (1/100000 of a dollar: 100-99999)
     match request.order_type {
         crate::types::OrderType::Limit |
         crate::types::OrderType::GTC |
-        crate::types::OrderType::GTD => {
-            // Limit/GTC/GTD orders MUST have a valid price
-            match request.price {
-                Some(price) => {
-                    // Use Polymarket-style tick size validation
-                    let tick_config = crate::types::TickSizeConfig::default();
-                    match tick_config.round_price(price) {
-                        Ok(rounded_price) => {
-                            if rounded_price != price {
-                                return Err(format!("Price {} invalid for tick size. Use {}", price, rounded_price));
-                            }
-                        }
-                        Err(e) => {
-                            return Err(e);
-                        }
-                    }
-                }
-                None => {
-                    return Err("Limit/GTC/GTD orders must specify a price between 100-99999 (0.001-0.99999)".to_string());
-                }
-            }
-        }
+        crate::types::OrderType::GTD |
         crate::types::OrderType::FOK |
         crate::types::OrderType::FAK => {
-            // FOK/FAK orders MUST have a valid price
+            // Limit-style orders MUST have a valid price
             match request.price {
-                Some(price) => {
-                    // Use Polymarket-style tick size validation
-                    let tick_config = crate::types::TickSizeConfig::default();
-                    match tick_config.round_price(price) {
-                        Ok(rounded_price) => {
-                            if rounded_price != price {
-                                return Err(format!("Price {} invalid for tick size. Use {}", price, rounded_price));
-                            }
-                        }
-                        Err(e) => {
-                            return Err(e);
-                        }
-                    }
-                }
+                Some(price) => validate_limit_price(price)?,
                 None => {
-                    return Err("FOK/FAK orders must specify a price between 100-99999 (0.001-0.99999)".to_string());
+                    return Err(ApiError::InvalidRequest(format!(
+                        "{:?} orders must specify a price between {}-{} (0.001-0.99999)",
+                        request.order_type, MIN_ORDER_PRICE, MAX_ORDER_PRICE
+                    )));
                 }
             }
         }
         crate::types::OrderType::Market => {
             // Market orders should NOT specify a price
             if request.price.is_some() && request.price != Some(0) {
-                return Err("Market orders should not specify a price".to_string());
+                return Err(ApiError::InvalidRequest("Market orders should not specify a price".to_string()));
             }
         }
     }
@@ -432,6 +551,25 @@ fn validate_order_request(request: &SubmitOrderRequest) -> Result<(), String> {
     Ok(())
 }
 
+/// Price band check followed by Polymarket-style tick size validation
+fn validate_limit_price(price: u64) -> Result<(), ApiError> {
+    if !(MIN_ORDER_PRICE..=MAX_ORDER_PRICE).contains(&price) {
+        return Err(ApiError::PriceOutOfBand {
+            price,
+            min: MIN_ORDER_PRICE,
+            max: MAX_ORDER_PRICE,
+        });
+    }
+
+    let tick_config = crate::types::TickSizeConfig::default();
+    let rounded_price = tick_config.round_price(price).map_err(ApiError::InvalidRequest)?;
+    if rounded_price != price {
+        return Err(ApiError::InvalidRequest(format!("Price {} invalid for tick size. Use {}", price, rounded_price)));
+    }
+
+    Ok(())
+}
+
 // ================================
 // POLYMARKET-STYLE COLLATERAL API
 // ================================
@@ -440,70 +578,115 @@ fn validate_order_request(request: &SubmitOrderRequest) -> Result<(), String> {
 pub struct CollateralBalanceRequest {
     pub account_id: String,
     pub market_id: String,
+    #[serde(default)]
+    pub collateral_token: Option<String>,   // Defaults to the token the market settles in
 }
 
 pub async fn get_collateral_balance(
     State(state): State<AppState>,
     Json(request): Json<CollateralBalanceRequest>,
-) -> impl IntoResponse {
+) -> Result<Json<Value>, ApiError> {
     info!("Getting collateral balance for {} in market {}", request.account_id, request.market_id);
 
-    // Get balance from database through collateral manager
-    match state.database.get_collateral_balance(&request.account_id, &request.market_id).await {
-        Ok(Some(balance)) => {
-            Json(json!({
-                "balance": balance,
-                "status": "success"
-            }))
-        }
-        Ok(None) => {
-            // Create demo balance for new users
-            let balance = crate::types::CollateralBalance {
-                account_id: request.account_id.clone(),
-                market_id: request.market_id.clone(),
-                available_balance: 1_000_000_000, // $1,000 USDC for demo
-                reserved_balance: 0,
-                position_balance: 0,
-                total_deposited: 1_000_000_000,
-                total_withdrawn: 0,
-                last_updated: Utc::now(),
-            };
-            
-            // Store the demo balance
-            if let Err(e) = state.database.update_collateral_balance(&balance).await {
-                error!("Failed to store demo balance: {}", e);
-            }
-            
-            Json(json!({
-                "balance": balance,
-                "status": "success",
-                "message": "Created demo balance with $1,000 USDC"
-            }))
-        }
-        Err(e) => {
-            error!("Database error getting collateral balance: {}", e);
-            Json(json!({
-                "error": "Database error",
-                "status": "error"
-            }))
-        }
+    // Live balances from the collateral and CTF contracts rather than the local ledger
+    let collateral_manager = state.matching_engine.get_collateral_manager();
+    let collateral_token = match request.collateral_token.as_deref() {
+        Some(token) => state.config.resolve_collateral_token(Some(token))?,
+        None => collateral_manager.collateral_token_for_market(&request.market_id).await?,
+    };
+    let status = collateral_manager
+        .get_full_collateral_status(&request.account_id, vec![request.market_id.clone()], &collateral_token)
+        .await?;
+
+    // Deposit/withdraw totals are only tracked in the local ledger
+    let ledger = state.database.get_collateral_balance(&request.account_id, &request.market_id).await?;
+
+    let balance = CollateralBalance {
+        account_id: request.account_id,
+        market_id: request.market_id,
+        available_balance: status.available_usdc,
+        reserved_balance: status.reserved_usdc,
+        position_balance: status.outcome_positions.iter().map(|p| p.balance).sum(),
+        total_deposited: ledger.as_ref().map(|b| b.total_deposited).unwrap_or(0),
+        total_withdrawn: ledger.as_ref().map(|b| b.total_withdrawn).unwrap_or(0),
+        last_updated: Utc::now(),
+    };
+
+    Ok(Json(json!({
+        "balance": balance,
+        "collateral_token": collateral_token,
+        "status": "success"
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct CollateralStatusRequest {
+    pub account_id: String,
+    pub market_ids: Vec<String>,
+    #[serde(default)]
+    pub collateral_token: Option<String>,   // Defaults to USDC
+}
+
+pub async fn get_collateral_status(
+    State(state): State<AppState>,
+    Json(request): Json<CollateralStatusRequest>,
+) -> Result<Json<CollateralStatus>, ApiError> {
+    info!("Getting collateral status for {} across {} markets", request.account_id, request.market_ids.len());
+
+    if request.account_id.is_empty() {
+        return Err(ApiError::InvalidRequest("Account ID cannot be empty".to_string()));
     }
+
+    let collateral_token = state.config.resolve_collateral_token(request.collateral_token.as_deref())?;
+    let status = state.matching_engine.get_collateral_manager()
+        .get_full_collateral_status(&request.account_id, request.market_ids, &collateral_token)
+        .await?;
+
+    Ok(Json(status))
+}
+
+/// Idle USDC a user has parked in the yield protocol: GET /collateral/yield/:account_id
+pub async fn get_yield_balance(
+    State(state): State<AppState>,
+    Path(account_id): Path<String>,
+) -> Result<Json<YieldBalance>, ApiError> {
+    let position = state.matching_engine.get_collateral_manager()
+        .get_yield_position(&account_id)
+        .await?;
+    Ok(Json(position))
+}
+
+/// Pay a user's accrued yield out to their wallet: POST /collateral/yield/:account_id/harvest
+pub async fn harvest_yield(
+    State(state): State<AppState>,
+    Path(account_id): Path<String>,
+) -> Result<Json<YieldHarvest>, ApiError> {
+    info!("Harvesting collateral yield for {}", account_id);
+    let harvest = state.matching_engine.get_collateral_manager()
+        .harvest_yield(&account_id)
+        .await?;
+    Ok(Json(harvest))
 }
 
 #[derive(Deserialize)]
 pub struct DepositCollateralRequest {
     pub account_id: String,
     pub market_id: String,
-    pub amount: u128, // USDC amount in microunits
+    pub amount: u128, // Collateral amount in the token's smallest unit
+    #[serde(default)]
+    pub collateral_token: Option<String>,   // Defaults to USDC
 }
 
 pub async fn deposit_collateral(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(request): Json<DepositCollateralRequest>,
-) -> impl IntoResponse {
-    info!("Depositing {} USDC collateral for {} in market {}", 
-        request.amount as f64 / 1_000_000.0,
-        request.account_id, 
+) -> Result<Json<Value>, ApiError> {
+    let collateral_token = state.config.resolve_collateral_token(request.collateral_token.as_deref())?;
+    let asset = state.config.collateral_name(&collateral_token);
+    info!("Depositing {} {} collateral for {} in market {}",
+        request.amount,
+        asset,
+        request.account_id,
         request.market_id
     );
 
@@ -513,45 +696,222 @@ pub async fn deposit_collateral(
     // 3. Emit events for tracking
     
     // For now, just return success
-    Json(json!({
+    Ok(Json(json!({
         "status": "success",
-        "message": format!("Deposited {} USDC as collateral", request.amount as f64 / 1_000_000.0),
+        "message": format!("Deposited {} {} as collateral", request.amount, asset),
+        "collateral_token": collateral_token,
         "new_balance": request.amount
-    }))
+    })))
 }
 
 #[derive(Deserialize)]
 pub struct RegisterMarketRequest {
     pub market_id: String,
-    pub condition_id: String,
+    #[serde(default)]
+    pub condition_id: Option<String>,   // Optional cross-check; the verifier's value wins
+    #[serde(default)]
+    pub collateral_token: Option<String>, // Token the market's condition is split from; defaults to USDC
 }
 
+/// Register a market from the verifier's on-chain record.
+/// A supplied condition_id is only stored as-is when the verifier can't be reached
 pub async fn register_market_condition(
     State(state): State<AppState>,
     Json(request): Json<RegisterMarketRequest>,
-) -> impl IntoResponse {
-    info!("Registering market {} with condition {}", request.market_id, request.condition_id);
-
-    match state.near_client.register_market_condition(&request.market_id, &request.condition_id).await {
-        Ok(_) => {
-            // Update the "latest market" tracking file for solver/TUI sync
-            if let Err(e) = update_latest_market_file(&request.market_id) {
-                error!("Failed to update latest market file: {}", e);
-            }
+) -> Result<Json<Value>, ApiError> {
+    info!("Registering market {}", request.market_id);
 
-            Json(json!({
-                "status": "success",
-                "message": format!("Registered market {} with condition {}", request.market_id, request.condition_id)
-            }))
-        }
-        Err(e) => {
-            error!("Failed to register market: {}", e);
-            Json(json!({
-                "status": "error",
-                "error": format!("Failed to register market: {}", e)
-            }))
+    if request.market_id.is_empty() {
+        return Err(ApiError::InvalidRequest("Market ID is required".to_string()));
+    }
+    let expected_condition = request.condition_id.filter(|condition_id| !condition_id.is_empty());
+    // Re-registering without a token keeps the one already on record
+    let collateral_token = match request.collateral_token.as_deref() {
+        Some(token) => state.config.resolve_collateral_token(Some(token))?,
+        None => state.database.get_market_condition(&request.market_id).await?
+            .map(|existing| existing.collateral_token)
+            .unwrap_or_else(|| state.config.default_collateral_token().to_string()),
+    };
+
+    let record = match fetch_market_record(&state.near_client, &request.market_id, MarketRegistrationSource::Manual, &collateral_token).await {
+        Ok(Some(record)) => {
+            if let Some(expected) = &expected_condition {
+                if *expected != record.condition_id {
+                    return Err(ApiError::InvalidRequest(format!(
+                        "Condition {} does not match verifier condition {}",
+                        expected, record.condition_id
+                    )));
+                }
+            }
+            record
         }
+        Ok(None) => return Err(ApiError::MarketNotFound(request.market_id)),
+        Err(e) => match expected_condition {
+            // The refresh task reconciles this record once the verifier is reachable again
+            Some(condition_id) => {
+                warn!("⚠️ Verifier unavailable, registering {} unverified: {}", request.market_id, e);
+                let now = Utc::now();
+                MarketConditionRecord {
+                    market_id: request.market_id.clone(),
+                    condition_id,
+                    source: MarketRegistrationSource::Manual,
+                    is_active: true,
+                    collateral_token,
+                    outcome_count: BINARY_OUTCOME_COUNT,
+                    registered_at: now,
+                    updated_at: now,
+                }
+            }
+            None => return Err(ApiError::ChainUnavailable(e.to_string())),
+        },
+    };
+
+    state.database.upsert_market_condition(&record).await.map_err(|e| {
+        error!("Failed to register market: {}", e);
+        ApiError::from(e)
+    })?;
+
+    // Update the "latest market" tracking file for solver/TUI sync
+    if let Err(e) = update_latest_market_file(&request.market_id) {
+        error!("Failed to update latest market file: {}", e);
     }
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": format!("Registered market {} with condition {}", record.market_id, record.condition_id),
+        "market": record
+    })))
+}
+
+/// Cached registry entry for a market
+pub async fn get_market_condition(
+    State(state): State<AppState>,
+    Path(market_id): Path<String>,
+) -> Result<Json<MarketConditionRecord>, ApiError> {
+    state.database.get_market_condition(&market_id).await?
+        .map(Json)
+        .ok_or(ApiError::MarketNotFound(market_id))
+}
+
+/// Admin endpoints require `x-admin-token` to match ORDERBOOK_ADMIN_TOKEN; unset disables them
+fn require_admin(headers: &HeaderMap) -> Result<(), ApiError> {
+    let expected = std::env::var("ORDERBOOK_ADMIN_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| ApiError::Unauthorized("Admin API is disabled".to_string()))?;
+
+    match headers.get("x-admin-token").and_then(|v| v.to_str().ok()) {
+        Some(token) if token == expected => Ok(()),
+        _ => Err(ApiError::Unauthorized("Invalid admin token".to_string())),
+    }
+}
+
+/// Current order rate limits: GET /admin/rate-limits
+pub async fn get_rate_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<RateLimitConfig>, ApiError> {
+    require_admin(&headers)?;
+    Ok(Json(state.rate_limiter.config()))
+}
+
+/// Change order rate limits at runtime: PUT /admin/rate-limits
+pub async fn update_rate_limits(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(update): Json<RateLimitUpdate>,
+) -> Result<Json<RateLimitConfig>, ApiError> {
+    require_admin(&headers)?;
+    let config = state.rate_limiter.update_config(update);
+    info!("Rate limits updated: {:?}", config);
+    Ok(Json(config))
+}
+
+/// Issue an API key: POST /admin/api-keys
+/// The `api_key` value is only shown in this response
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&headers)?;
+    let (key, api_key) = generate_api_key(&request)?;
+    state.database.insert_api_key(&key).await?;
+    info!("API key {} created (multiplier {}, endpoints {:?})", key.key_id, key.rate_limit_multiplier, key.allowed_endpoints);
+
+    Ok(Json(json!({
+        "key_id": key.key_id,
+        "api_key": api_key,
+        "secret_hash": key.secret_hash,
+        "rate_limit_multiplier": key.rate_limit_multiplier,
+        "allowed_endpoints": key.allowed_endpoints,
+        "created_at": key.created_at
+    })))
+}
+
+/// Usage of one API key: GET /admin/api-keys/:key_id/stats
+pub async fn get_api_key_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(key_id): Path<String>,
+) -> Result<Json<ApiKey>, ApiError> {
+    require_admin(&headers)?;
+    state.database.get_api_key(&key_id).await?
+        .map(Json)
+        .ok_or_else(|| ApiError::InvalidRequest(format!("API key {} not found", key_id)))
+}
+
+#[derive(Deserialize)]
+pub struct PurgeExpiredQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Delete orders past their TTL now: GET /admin/purge-expired?dry_run=true
+/// A dry run lists the orders that would be deleted without touching them
+pub async fn purge_expired_orders(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<PurgeExpiredQuery>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&headers)?;
+    let orders = state.matching_engine.purge_expired_orders(query.dry_run).await?;
+    info!("Expired order purge via admin API: {} orders (dry_run={})", orders.len(), query.dry_run);
+
+    Ok(Json(json!({
+        "dry_run": query.dry_run,
+        "count": orders.len(),
+        "orders": orders
+    })))
+}
+
+/// Reconcile the book against the solver contract now: POST /admin/reconcile
+pub async fn reconcile_now(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ReconciliationReport>, ApiError> {
+    require_admin(&headers)?;
+    let report = state.reconciler.reconcile().await?;
+    info!("Chain reconciliation via admin API: {} divergences across {} orders", report.divergences.len(), report.orders_checked);
+    Ok(Json(report))
+}
+
+/// Most recent reconciliation report: GET /admin/reconciliation/latest
+pub async fn get_latest_reconciliation(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Option<ReconciliationReport>>, ApiError> {
+    require_admin(&headers)?;
+    Ok(Json(state.database.get_latest_reconciliation_report().await?))
+}
+
+/// Collected trading fees per market and what the sweep has yet to move: GET /admin/fees
+pub async fn get_platform_fees(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<FeeReport>, ApiError> {
+    require_admin(&headers)?;
+    Ok(Json(crate::fees::fee_report(state.database.as_ref(), &state.config).await?))
 }
 
 fn update_latest_market_file(market_id: &str) -> Result<()> {
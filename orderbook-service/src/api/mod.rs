@@ -1,8 +1,13 @@
 // REST API handlers for the orderbook service
 
 pub mod handlers;
+pub mod error;
+pub mod rate_limit;
+pub mod api_keys;
+pub mod protocol;
 
 
 
 // Re-export handlers
-pub use handlers::*;
\ No newline at end of file
+pub use handlers::*;
+pub use error::ApiError;
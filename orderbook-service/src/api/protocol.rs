@@ -0,0 +1,572 @@
+// Wire protocol versioning
+// REST routes live under /v1/; the unversioned paths remain as deprecated aliases for one release.
+// Every WebSocket message carries "type" and "v". A client opens with
+//   {"type":"hello","versions":[1]}
+// and the server answers {"type":"hello_ack","v":1,"supported_versions":[1]} with the highest version
+// both sides speak. Clients that never say hello get DEFAULT_VERSION.
+//
+// Each version has its own frozen serde structs, converted from the internal WebSocketMessage, so a
+// field added for v2 never reaches a v1 client. tests/fixtures/protocol/v<n> pins every message type:
+// changing one of those shapes needs a new version, not an edit to the fixture.
+//
+// v2: trades carry maker_fee and taker_fee.
+// v3: OrderbookDelta, the price levels changed since the previous delta of a book.
+// v4: ChainStatus, and snapshots flagged degraded with their staleness while the NEAR RPC is unhealthy.
+
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::types::WebSocketMessage;
+
+/// Protocol versions this server speaks, oldest first
+pub const SUPPORTED_VERSIONS: &[u32] = &[1, 2, 3, 4];
+/// Version used until a client's hello says otherwise
+pub const DEFAULT_VERSION: u32 = 1;
+/// Prefix the current REST routes are served under
+pub const REST_PREFIX: &str = "/v1";
+
+/// Messages a WebSocket client may send
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Hello { versions: Vec<u32> },
+}
+
+/// Connection-level messages the server sends outside the market data stream
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlMessage {
+    ConnectionEstablished {
+        v: u32,
+        message: String,
+        timestamp: String,
+        supported_versions: Vec<u32>,
+    },
+    HelloAck {
+        v: u32,
+        supported_versions: Vec<u32>,
+    },
+    Error {
+        v: u32,
+        message: String,
+    },
+}
+
+/// A versioned message on the wire: the version's own payload plus "v"
+#[derive(Debug, Clone, Serialize)]
+pub struct Envelope<T> {
+    pub v: u32,
+    #[serde(flatten)]
+    pub message: T,
+}
+
+/// Highest version both the client and this server speak
+pub fn negotiate_version(client_versions: &[u32]) -> Option<u32> {
+    SUPPORTED_VERSIONS.iter().rev().copied().find(|v| client_versions.contains(v))
+}
+
+/// Serialize a broadcast for a client on `version`; None when that version has no such message
+pub fn encode(message: &WebSocketMessage, version: u32) -> serde_json::Result<Option<String>> {
+    debug_assert!(SUPPORTED_VERSIONS.contains(&version), "unsupported protocol version {}", version);
+    match version {
+        4 => serde_json::to_string(&Envelope { v: 4, message: v4::Message::from(message) }).map(Some),
+        3 => v3::Message::from_broadcast(message)
+            .map(|message| serde_json::to_string(&Envelope { v: 3, message }))
+            .transpose(),
+        2 => v2::Message::from_broadcast(message)
+            .map(|message| serde_json::to_string(&Envelope { v: 2, message }))
+            .transpose(),
+        _ => v1::Message::from_broadcast(message)
+            .map(|message| serde_json::to_string(&Envelope { v: 1, message }))
+            .transpose(),
+    }
+}
+
+/// Marks a response from an unversioned alias as deprecated and points at the /v1 route
+pub async fn deprecated_alias(request: Request, next: Next) -> Response {
+    let successor = format!("<{}{}>; rel=\"successor-version\"", REST_PREFIX, request.uri().path());
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    headers.insert("Deprecation", HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&successor) {
+        headers.insert("Link", link);
+    }
+    response
+}
+
+/// Version 1 wire format, frozen
+pub mod v1 {
+    use chrono::{DateTime, Utc};
+    use serde::Serialize;
+    use uuid::Uuid;
+
+    use crate::types::{self, OrderSide, OrderStatus, SettlementStatus, TradeType, WebSocketMessage};
+
+    #[derive(Debug, Clone, Serialize)]
+    #[serde(tag = "type")]
+    pub enum Message {
+        OrderbookUpdate {
+            market_id: String,
+            outcome: u8,
+            snapshot: OrderbookSnapshot,
+            sequence: u64,
+            checksum: u64,
+        },
+        TradeExecuted {
+            trade: Trade,
+        },
+        OrderUpdate {
+            order_id: Uuid,
+            status: OrderStatus,
+            filled_size: u128,
+        },
+        OrderExpired {
+            order_id: Uuid,
+            market_id: String,
+            outcome: u8,
+            user_account: String,
+            expires_at: Option<DateTime<Utc>>,
+        },
+    }
+
+    #[derive(Debug, Clone, Serialize)]
+    pub struct OrderbookSnapshot {
+        pub market_id: String,
+        pub outcome: u8,
+        pub bids: Vec<PriceLevel>,
+        pub asks: Vec<PriceLevel>,
+        pub last_trade_price: Option<u64>,
+        pub timestamp: DateTime<Utc>,
+    }
+
+    #[derive(Debug, Clone, Serialize)]
+    pub struct PriceLevel {
+        pub price: u64,
+        pub size: u128,
+        pub order_count: u32,
+    }
+
+    #[derive(Debug, Clone, Serialize)]
+    pub struct Trade {
+        pub trade_id: Uuid,
+        pub market_id: String,
+        pub condition_id: String,
+        pub maker_order_id: Uuid,
+        pub taker_order_id: Uuid,
+        pub maker_account: String,
+        pub taker_account: String,
+        pub maker_side: OrderSide,
+        pub taker_side: OrderSide,
+        pub outcome: u8,
+        pub price: u64,
+        pub size: u128,
+        pub trade_type: TradeType,
+        pub executed_at: DateTime<Utc>,
+        pub settlement_status: SettlementStatus,
+        pub settlement_tx_hash: Option<String>,
+    }
+
+    impl From<&types::PriceLevel> for PriceLevel {
+        fn from(level: &types::PriceLevel) -> Self {
+            Self { price: level.price, size: level.size, order_count: level.order_count }
+        }
+    }
+
+    impl From<&types::OrderbookSnapshot> for OrderbookSnapshot {
+        fn from(snapshot: &types::OrderbookSnapshot) -> Self {
+            Self {
+                market_id: snapshot.market_id.clone(),
+                outcome: snapshot.outcome,
+                bids: snapshot.bids.iter().map(PriceLevel::from).collect(),
+                asks: snapshot.asks.iter().map(PriceLevel::from).collect(),
+                last_trade_price: snapshot.last_trade_price,
+                timestamp: snapshot.timestamp,
+            }
+        }
+    }
+
+    impl From<&types::Trade> for Trade {
+        fn from(trade: &types::Trade) -> Self {
+            Self {
+                trade_id: trade.trade_id,
+                market_id: trade.market_id.clone(),
+                condition_id: trade.condition_id.clone(),
+                maker_order_id: trade.maker_order_id,
+                taker_order_id: trade.taker_order_id,
+                maker_account: trade.maker_account.clone(),
+                taker_account: trade.taker_account.clone(),
+                maker_side: trade.maker_side.clone(),
+                taker_side: trade.taker_side.clone(),
+                outcome: trade.outcome,
+                price: trade.price,
+                size: trade.size,
+                trade_type: trade.trade_type.clone(),
+                executed_at: trade.executed_at,
+                settlement_status: trade.settlement_status.clone(),
+                settlement_tx_hash: trade.settlement_tx_hash.clone(),
+            }
+        }
+    }
+
+    impl Message {
+        /// None for broadcasts this version has no message for
+        pub fn from_broadcast(message: &WebSocketMessage) -> Option<Self> {
+            Some(match message {
+                WebSocketMessage::OrderbookDelta(_) | WebSocketMessage::ChainStatus { .. } => return None,
+                WebSocketMessage::OrderbookUpdate { market_id, outcome, snapshot, sequence, checksum } => Message::OrderbookUpdate {
+                    market_id: market_id.clone(),
+                    outcome: *outcome,
+                    snapshot: snapshot.into(),
+                    sequence: *sequence,
+                    checksum: *checksum,
+                },
+                WebSocketMessage::TradeExecuted { trade } => Message::TradeExecuted { trade: trade.into() },
+                WebSocketMessage::OrderUpdate { order_id, status, filled_size } => Message::OrderUpdate {
+                    order_id: *order_id,
+                    status: status.clone(),
+                    filled_size: *filled_size,
+                },
+                WebSocketMessage::OrderExpired { order_id, market_id, outcome, user_account, expires_at } => Message::OrderExpired {
+                    order_id: *order_id,
+                    market_id: market_id.clone(),
+                    outcome: *outcome,
+                    user_account: user_account.clone(),
+                    expires_at: *expires_at,
+                },
+            })
+        }
+    }
+}
+
+/// Version 2 wire format, frozen: v1 plus the fees charged on each trade
+pub mod v2 {
+    use chrono::{DateTime, Utc};
+    use serde::Serialize;
+    use uuid::Uuid;
+
+    use crate::types::{self, OrderSide, OrderStatus, SettlementStatus, TradeType, WebSocketMessage};
+
+    // Book snapshots did not change
+    pub use super::v1::{OrderbookSnapshot, PriceLevel};
+
+    #[derive(Debug, Clone, Serialize)]
+    #[serde(tag = "type")]
+    pub enum Message {
+        OrderbookUpdate {
+            market_id: String,
+            outcome: u8,
+            snapshot: OrderbookSnapshot,
+            sequence: u64,
+            checksum: u64,
+        },
+        TradeExecuted {
+            trade: Trade,
+        },
+        OrderUpdate {
+            order_id: Uuid,
+            status: OrderStatus,
+            filled_size: u128,
+        },
+        OrderExpired {
+            order_id: Uuid,
+            market_id: String,
+            outcome: u8,
+            user_account: String,
+            expires_at: Option<DateTime<Utc>>,
+        },
+    }
+
+    #[derive(Debug, Clone, Serialize)]
+    pub struct Trade {
+        pub trade_id: Uuid,
+        pub market_id: String,
+        pub condition_id: String,
+        pub maker_order_id: Uuid,
+        pub taker_order_id: Uuid,
+        pub maker_account: String,
+        pub taker_account: String,
+        pub maker_side: OrderSide,
+        pub taker_side: OrderSide,
+        pub outcome: u8,
+        pub price: u64,
+        pub size: u128,
+        pub trade_type: TradeType,
+        pub executed_at: DateTime<Utc>,
+        pub settlement_status: SettlementStatus,
+        pub settlement_tx_hash: Option<String>,
+        pub maker_fee: u128,
+        pub taker_fee: u128,
+    }
+
+    impl From<&types::Trade> for Trade {
+        fn from(trade: &types::Trade) -> Self {
+            Self {
+                trade_id: trade.trade_id,
+                market_id: trade.market_id.clone(),
+                condition_id: trade.condition_id.clone(),
+                maker_order_id: trade.maker_order_id,
+                taker_order_id: trade.taker_order_id,
+                maker_account: trade.maker_account.clone(),
+                taker_account: trade.taker_account.clone(),
+                maker_side: trade.maker_side.clone(),
+                taker_side: trade.taker_side.clone(),
+                outcome: trade.outcome,
+                price: trade.price,
+                size: trade.size,
+                trade_type: trade.trade_type.clone(),
+                executed_at: trade.executed_at,
+                settlement_status: trade.settlement_status.clone(),
+                settlement_tx_hash: trade.settlement_tx_hash.clone(),
+                maker_fee: trade.maker_fee,
+                taker_fee: trade.taker_fee,
+            }
+        }
+    }
+
+    impl Message {
+        /// None for broadcasts this version has no message for
+        pub fn from_broadcast(message: &WebSocketMessage) -> Option<Self> {
+            Some(match message {
+                WebSocketMessage::OrderbookDelta(_) | WebSocketMessage::ChainStatus { .. } => return None,
+                WebSocketMessage::OrderbookUpdate { market_id, outcome, snapshot, sequence, checksum } => Message::OrderbookUpdate {
+                    market_id: market_id.clone(),
+                    outcome: *outcome,
+                    snapshot: snapshot.into(),
+                    sequence: *sequence,
+                    checksum: *checksum,
+                },
+                WebSocketMessage::TradeExecuted { trade } => Message::TradeExecuted { trade: trade.into() },
+                WebSocketMessage::OrderUpdate { order_id, status, filled_size } => Message::OrderUpdate {
+                    order_id: *order_id,
+                    status: status.clone(),
+                    filled_size: *filled_size,
+                },
+                WebSocketMessage::OrderExpired { order_id, market_id, outcome, user_account, expires_at } => Message::OrderExpired {
+                    order_id: *order_id,
+                    market_id: market_id.clone(),
+                    outcome: *outcome,
+                    user_account: user_account.clone(),
+                    expires_at: *expires_at,
+                },
+            })
+        }
+    }
+}
+
+/// Version 3 wire format, frozen: v2 plus per-level book deltas
+pub mod v3 {
+    use chrono::{DateTime, Utc};
+    use serde::Serialize;
+    use uuid::Uuid;
+
+    use crate::types::{OrderStatus, WebSocketMessage};
+
+    // Snapshots and trades did not change
+    pub use super::v2::{OrderbookSnapshot, PriceLevel, Trade};
+
+    #[derive(Debug, Clone, Serialize)]
+    #[serde(tag = "type")]
+    pub enum Message {
+        OrderbookUpdate {
+            market_id: String,
+            outcome: u8,
+            snapshot: OrderbookSnapshot,
+            sequence: u64,
+            checksum: u64,
+        },
+        OrderbookDelta {
+            market_id: String,
+            outcome: u8,
+            prev_sequence: u64,
+            sequence: u64,
+            bids: Vec<PriceLevel>,
+            asks: Vec<PriceLevel>,
+            last_trade_price: Option<u64>,
+            timestamp: DateTime<Utc>,
+        },
+        TradeExecuted {
+            trade: Trade,
+        },
+        OrderUpdate {
+            order_id: Uuid,
+            status: OrderStatus,
+            filled_size: u128,
+        },
+        OrderExpired {
+            order_id: Uuid,
+            market_id: String,
+            outcome: u8,
+            user_account: String,
+            expires_at: Option<DateTime<Utc>>,
+        },
+    }
+
+    impl Message {
+        /// None for broadcasts this version has no message for
+        pub fn from_broadcast(message: &WebSocketMessage) -> Option<Self> {
+            Some(match message {
+                WebSocketMessage::ChainStatus { .. } => return None,
+                WebSocketMessage::OrderbookUpdate { market_id, outcome, snapshot, sequence, checksum } => Message::OrderbookUpdate {
+                    market_id: market_id.clone(),
+                    outcome: *outcome,
+                    snapshot: snapshot.into(),
+                    sequence: *sequence,
+                    checksum: *checksum,
+                },
+                WebSocketMessage::OrderbookDelta(delta) => Message::OrderbookDelta {
+                    market_id: delta.market_id.clone(),
+                    outcome: delta.outcome,
+                    prev_sequence: delta.prev_sequence,
+                    sequence: delta.sequence,
+                    bids: delta.bids.iter().map(PriceLevel::from).collect(),
+                    asks: delta.asks.iter().map(PriceLevel::from).collect(),
+                    last_trade_price: delta.last_trade_price,
+                    timestamp: delta.timestamp,
+                },
+                WebSocketMessage::TradeExecuted { trade } => Message::TradeExecuted { trade: trade.into() },
+                WebSocketMessage::OrderUpdate { order_id, status, filled_size } => Message::OrderUpdate {
+                    order_id: *order_id,
+                    status: status.clone(),
+                    filled_size: *filled_size,
+                },
+                WebSocketMessage::OrderExpired { order_id, market_id, outcome, user_account, expires_at } => Message::OrderExpired {
+                    order_id: *order_id,
+                    market_id: market_id.clone(),
+                    outcome: *outcome,
+                    user_account: user_account.clone(),
+                    expires_at: *expires_at,
+                },
+            })
+        }
+    }
+}
+
+/// Version 4 wire format, frozen: v3 plus chain health
+pub mod v4 {
+    use chrono::{DateTime, Utc};
+    use serde::Serialize;
+    use uuid::Uuid;
+
+    use crate::types::{self, OrderStatus, WebSocketMessage};
+
+    // Price levels and trades did not change
+    pub use super::v3::{PriceLevel, Trade};
+
+    #[derive(Debug, Clone, Serialize)]
+    #[serde(tag = "type")]
+    pub enum Message {
+        OrderbookUpdate {
+            market_id: String,
+            outcome: u8,
+            snapshot: OrderbookSnapshot,
+            sequence: u64,
+            checksum: u64,
+        },
+        OrderbookDelta {
+            market_id: String,
+            outcome: u8,
+            prev_sequence: u64,
+            sequence: u64,
+            bids: Vec<PriceLevel>,
+            asks: Vec<PriceLevel>,
+            last_trade_price: Option<u64>,
+            timestamp: DateTime<Utc>,
+        },
+        TradeExecuted {
+            trade: Trade,
+        },
+        OrderUpdate {
+            order_id: Uuid,
+            status: OrderStatus,
+            filled_size: u128,
+        },
+        OrderExpired {
+            order_id: Uuid,
+            market_id: String,
+            outcome: u8,
+            user_account: String,
+            expires_at: Option<DateTime<Utc>>,
+        },
+        ChainStatus {
+            degraded: bool,
+            staleness_secs: u64,
+        },
+    }
+
+    /// v1 snapshot plus `degraded` and `staleness_secs`, present only while the chain is unhealthy
+    #[derive(Debug, Clone, Serialize)]
+    pub struct OrderbookSnapshot {
+        pub market_id: String,
+        pub outcome: u8,
+        pub bids: Vec<PriceLevel>,
+        pub asks: Vec<PriceLevel>,
+        pub last_trade_price: Option<u64>,
+        pub timestamp: DateTime<Utc>,
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        pub degraded: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub staleness_secs: Option<u64>,
+    }
+
+    impl From<&types::OrderbookSnapshot> for OrderbookSnapshot {
+        fn from(snapshot: &types::OrderbookSnapshot) -> Self {
+            Self {
+                market_id: snapshot.market_id.clone(),
+                outcome: snapshot.outcome,
+                bids: snapshot.bids.iter().map(PriceLevel::from).collect(),
+                asks: snapshot.asks.iter().map(PriceLevel::from).collect(),
+                last_trade_price: snapshot.last_trade_price,
+                timestamp: snapshot.timestamp,
+                degraded: snapshot.degraded,
+                staleness_secs: snapshot.staleness_secs,
+            }
+        }
+    }
+
+    impl From<&WebSocketMessage> for Message {
+        fn from(message: &WebSocketMessage) -> Self {
+            match message {
+                WebSocketMessage::OrderbookUpdate { market_id, outcome, snapshot, sequence, checksum } => Message::OrderbookUpdate {
+                    market_id: market_id.clone(),
+                    outcome: *outcome,
+                    snapshot: snapshot.into(),
+                    sequence: *sequence,
+                    checksum: *checksum,
+                },
+                WebSocketMessage::OrderbookDelta(delta) => Message::OrderbookDelta {
+                    market_id: delta.market_id.clone(),
+                    outcome: delta.outcome,
+                    prev_sequence: delta.prev_sequence,
+                    sequence: delta.sequence,
+                    bids: delta.bids.iter().map(PriceLevel::from).collect(),
+                    asks: delta.asks.iter().map(PriceLevel::from).collect(),
+                    last_trade_price: delta.last_trade_price,
+                    timestamp: delta.timestamp,
+                },
+                WebSocketMessage::TradeExecuted { trade } => Message::TradeExecuted { trade: trade.into() },
+                WebSocketMessage::OrderUpdate { order_id, status, filled_size } => Message::OrderUpdate {
+                    order_id: *order_id,
+                    status: status.clone(),
+                    filled_size: *filled_size,
+                },
+                WebSocketMessage::OrderExpired { order_id, market_id, outcome, user_account, expires_at } => Message::OrderExpired {
+                    order_id: *order_id,
+                    market_id: market_id.clone(),
+                    outcome: *outcome,
+                    user_account: user_account.clone(),
+                    expires_at: *expires_at,
+                },
+                WebSocketMessage::ChainStatus { degraded, staleness_secs } => Message::ChainStatus {
+                    degraded: *degraded,
+                    staleness_secs: *staleness_secs,
+                },
+            }
+        }
+    }
+}
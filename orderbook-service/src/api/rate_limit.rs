@@ -0,0 +1,210 @@
+// Order rate limiting
+// A global token bucket runs as middleware on the order submission routes and caps total order
+// traffic into the matching engine. Per-account throttles (open orders per market, new orders per
+// minute) run in the submit path and read the account's orders from the database. They are keyed
+// by account rather than client IP: solver orders all arrive from the same relayer address.
+// Requests authenticated with an API key skip the global bucket instead (see api_keys.rs).
+//
+// Env: ORDER_RATE_LIMIT_PER_SECOND (100, 0 disables), MAX_OPEN_ORDERS_PER_MARKET (50),
+//      MAX_ORDERS_PER_MINUTE (120), RATE_LIMIT_EXEMPT_ACCOUNTS (comma-separated market makers)
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::storage::DatabaseTrait;
+use super::error::ApiError;
+
+/// Sliding window for MAX_ORDERS_PER_MINUTE
+const ORDER_WINDOW_SECS: i64 = 60;
+/// Open-order limits clear when orders fill or are cancelled, which we can't predict
+const OPEN_ORDERS_RETRY_AFTER_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RateLimitConfig {
+    pub global_orders_per_second: u32,
+    pub max_open_orders_per_market: usize,
+    pub max_orders_per_minute: usize,
+    pub exempt_accounts: HashSet<String>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            global_orders_per_second: 100,
+            max_open_orders_per_market: 50,
+            max_orders_per_minute: 120,
+            exempt_accounts: HashSet::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        Self {
+            global_orders_per_second: env_or("ORDER_RATE_LIMIT_PER_SECOND", defaults.global_orders_per_second),
+            max_open_orders_per_market: env_or("MAX_OPEN_ORDERS_PER_MARKET", defaults.max_open_orders_per_market),
+            max_orders_per_minute: env_or("MAX_ORDERS_PER_MINUTE", defaults.max_orders_per_minute),
+            exempt_accounts: std::env::var("RATE_LIMIT_EXEMPT_ACCOUNTS")
+                .map(|v| v.split(',').map(str::trim).filter(|a| !a.is_empty()).map(String::from).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Admin update (PUT /admin/rate-limits); omitted fields keep their current value
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RateLimitUpdate {
+    pub global_orders_per_second: Option<u32>,
+    pub max_open_orders_per_market: Option<usize>,
+    pub max_orders_per_minute: Option<usize>,
+    pub exempt_accounts: Option<HashSet<String>>,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+pub struct RateLimiter {
+    config: RwLock<RateLimitConfig>,
+    bucket: Mutex<TokenBucket>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            bucket: Mutex::new(TokenBucket {
+                tokens: config.global_orders_per_second as f64,
+                last_refill: Instant::now(),
+            }),
+            config: RwLock::new(config),
+        }
+    }
+
+    pub fn config(&self) -> RateLimitConfig {
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn update_config(&self, update: RateLimitUpdate) -> RateLimitConfig {
+        let mut config = self.config.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(rate) = update.global_orders_per_second {
+            config.global_orders_per_second = rate;
+        }
+        if let Some(max) = update.max_open_orders_per_market {
+            config.max_open_orders_per_market = max;
+        }
+        if let Some(max) = update.max_orders_per_minute {
+            config.max_orders_per_minute = max;
+        }
+        if let Some(accounts) = update.exempt_accounts {
+            config.exempt_accounts = accounts;
+        }
+        config.clone()
+    }
+
+    /// Take one token from the global bucket (burst = one second of traffic)
+    pub fn try_acquire_global(&self) -> Result<(), ApiError> {
+        let rate = self.config().global_orders_per_second as f64;
+        if rate == 0.0 {
+            return Ok(());
+        }
+
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        Err(ApiError::RateLimited {
+            reason: "Order service is at capacity".to_string(),
+            retry_after_secs: ((1.0 - bucket.tokens) / rate).ceil().max(1.0) as u64,
+        })
+    }
+
+    /// Per-account throttles for a new order in `market_id`
+    pub async fn check_account(
+        &self,
+        database: &dyn DatabaseTrait,
+        account_id: &str,
+        market_id: &str,
+    ) -> Result<(), ApiError> {
+        self.check_account_scaled(database, account_id, market_id, 1).await
+    }
+
+    /// check_account with both limits multiplied, for requests made with an API key
+    pub async fn check_account_scaled(
+        &self,
+        database: &dyn DatabaseTrait,
+        account_id: &str,
+        market_id: &str,
+        multiplier: u32,
+    ) -> Result<(), ApiError> {
+        let mut config = self.config();
+        if config.exempt_accounts.contains(account_id) {
+            return Ok(());
+        }
+        config.max_open_orders_per_market = config.max_open_orders_per_market.saturating_mul(multiplier as usize);
+        config.max_orders_per_minute = config.max_orders_per_minute.saturating_mul(multiplier as usize);
+
+        let open_orders = database.count_open_orders(account_id, market_id).await?;
+        if open_orders >= config.max_open_orders_per_market {
+            warn!("Account {} hit the open order limit in market {}", account_id, market_id);
+            return Err(ApiError::RateLimited {
+                reason: format!(
+                    "{} already has {} open orders in market {} (max {})",
+                    account_id, open_orders, market_id, config.max_open_orders_per_market
+                ),
+                retry_after_secs: OPEN_ORDERS_RETRY_AFTER_SECS,
+            });
+        }
+
+        let now = Utc::now();
+        let recent = database
+            .get_recent_order_times(account_id, now - chrono::Duration::seconds(ORDER_WINDOW_SECS))
+            .await?;
+        if recent.len() >= config.max_orders_per_minute {
+            // Wait until enough orders age out of the window to get back under the limit
+            let blocking = recent.get(recent.len() - config.max_orders_per_minute).copied().unwrap_or(now);
+            let frees_at = blocking + chrono::Duration::seconds(ORDER_WINDOW_SECS);
+            warn!("Account {} hit the order rate limit", account_id);
+            return Err(ApiError::RateLimited {
+                reason: format!(
+                    "{} submitted {} orders in the last minute (max {})",
+                    account_id, recent.len(), config.max_orders_per_minute
+                ),
+                retry_after_secs: (frees_at - now).num_seconds().max(1) as u64,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Global order-rate middleware for the submission routes
+pub async fn limit_order_rate(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    limiter.try_acquire_global()?;
+    Ok(next.run(request).await)
+}
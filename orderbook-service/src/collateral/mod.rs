@@ -1,7 +1,20 @@
 // Polymarket-style collateral management system
 // Handles USDC deposits, reservations, and position calculations
-
-use std::collections::HashMap;
+// Each market settles in its own NEP-141 collateral token (USDC unless its registry record names
+// another one); buy-side locks only net against orders in markets sharing that token
+// Trading fees are charged on each side's collateral leg at the market's maker/taker rate: the buyer
+// pays its fee on top of the price and the seller's comes out of what it receives, so buy
+// reservations include the fee. Direct matches net their fees into the batch's collateral
+// transfers to the service's signer account; a mint's fees follow its swap in a transfer of their
+// own, which leaves the trade settled and is retried by itself if it fails. A trade's fees are
+// recorded in the platform fee ledger once all of them were collected
+// With YIELD_ENABLED, USDC a user leaves idle above YIELD_IDLE_THRESHOLD is parked in the yield
+// protocol through the signer account and pulled back when an order needs it. The protocol is
+// expected to take deposits by ft_transfer_call, return them by withdraw(token_id, amount) and report
+// get_balance(account_id, token_id); users' principal is tracked per account and the interest on
+// top of it is shared pro rata
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use anyhow::Result;
 use tracing::{info, warn, error};
@@ -11,37 +24,477 @@ use chrono::Utc;
 use crate::types::{
     CollateralBalance, CollateralReservation, CollateralSettlement,
     CollateralTransfer, CollateralSettlementType, MarketCollateralConfig,
-    Order, Trade, OrderSide
+    Order, Trade, OrderSide, CollateralStatus, PositionBalance,
+    BatchSettlementResult, NetTransfer, TradeSettlementResult, TradeType, FeeLedgerEntry,
+    YieldBalance, YieldDeposit, YieldHarvest
 };
 use crate::storage::DatabaseTrait;
 use crate::near_client::NearClient;
+use crate::near_client::settlement::SettlementClient;
+use crate::config::ServiceConfig;
+use crate::api::ApiError;
+
+/// Give up on a trade's fees after this many failed collection rounds
+const MAX_FEE_COLLECTION_ATTEMPTS: u32 = 5;
+/// Give up on a batch's failed transfers after this many rounds
+const MAX_BATCH_TRANSFER_ATTEMPTS: u32 = 5;
+
+/// One payer's share of a settled trade's fees, owed to the signer account
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeLeg {
+    pub payer: String,
+    pub amount: u128,
+}
+
+/// Fees of a settled trade still being collected. `legs` are the transfers that haven't gone
+/// through yet; once it is empty the trade's fees are recorded in the ledger
+#[derive(Debug, Clone)]
+pub struct PendingTradeFees {
+    pub trade: Trade,
+    pub legs: Vec<FeeLeg>,
+    pub attempts: u32,
+}
+
+/// Transfers of a settlement batch that failed, and the trades they hold up along with the
+/// indexes of the transfers each one waits on. The rest of the batch already went through, so only
+/// these are retried; a trade is settled once all of its transfers have
+#[derive(Debug, Clone)]
+pub struct PendingBatchTransfers {
+    pub batch_id: Uuid,
+    pub transfers: Vec<NetTransfer>,
+    pub trades: Vec<(Trade, Vec<usize>)>,
+    pub attempts: u32,
+}
 
 pub struct CollateralManager {
     database: Arc<dyn DatabaseTrait>,
     near_client: Arc<NearClient>,
+    settlement_client: Arc<dyn SettlementClient>, // order balance reads and settlement transfers; the NEAR client outside tests
+    config: Arc<ServiceConfig>,
     market_configs: HashMap<String, MarketCollateralConfig>,
+    yield_lock: tokio::sync::Mutex<()>, // one yield protocol deposit or withdrawal at a time
+    // Fee transfers to try again, fed by settlement and drained on its retry timer
+    fee_queue: tokio::sync::Mutex<VecDeque<PendingTradeFees>>,
+    // Batch transfers to try again, likewise
+    batch_queue: tokio::sync::Mutex<VecDeque<PendingBatchTransfers>>,
 }
 
 impl CollateralManager {
-    pub fn new(database: Arc<dyn DatabaseTrait>, near_client: Arc<NearClient>) -> Self {
+    pub fn new(database: Arc<dyn DatabaseTrait>, near_client: Arc<NearClient>, config: Arc<ServiceConfig>) -> Self {
         Self {
             database,
+            settlement_client: near_client.clone(),
             near_client,
+            config,
             market_configs: HashMap::new(),
+            yield_lock: tokio::sync::Mutex::new(()),
+            fee_queue: tokio::sync::Mutex::new(VecDeque::new()),
+            batch_queue: tokio::sync::Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Read order balances and make settlement transfers through `client` instead of the NEAR client
+    pub fn with_settlement_client(mut self, client: Arc<dyn SettlementClient>) -> Self {
+        self.settlement_client = client;
+        self
+    }
+
+    /// Collateral settings for a market. Without an explicit config the token comes from the
+    /// market's registry record, or the default collateral for markets not registered yet, and the
+    /// fee rates are the service defaults
+    pub async fn get_market_config(&self, market_id: &str) -> Result<MarketCollateralConfig> {
+        if let Some(config) = self.market_configs.get(market_id) {
+            return Ok(config.clone());
+        }
+
+        let collateral_token = self.database.get_market_condition(market_id).await?
+            .map(|record| record.collateral_token)
+            .unwrap_or_else(|| self.config.default_collateral_token().to_string());
+
+        Ok(MarketCollateralConfig {
+            market_id: market_id.to_string(),
+            collateral_token,
+            min_collateral: 0,
+            margin_requirement: 1.0,
+            max_leverage: 1.0,
+            maker_fee_bps: self.config.maker_fee_bps.unwrap_or(0),
+            taker_fee_bps: self.config.taker_fee_bps.unwrap_or(0),
+        })
+    }
+
+    pub async fn collateral_token_for_market(&self, market_id: &str) -> Result<String> {
+        Ok(self.get_market_config(market_id).await?.collateral_token)
+    }
+
+    /// Collateral each side of a trade pays or receives: price * size for both sides of a direct
+    /// match or burn. A mint splits the $1 complete set, so the maker pays the complement
+    pub fn collateral_legs(trade: &Trade) -> (u128, u128) {
+        let taker_leg = trade.size * trade.price as u128 / 100000;
+        let maker_leg = match trade.trade_type {
+            TradeType::Minting => trade.size.saturating_sub(taker_leg),
+            TradeType::DirectMatch | TradeType::Burning => taker_leg,
+        };
+        (maker_leg, taker_leg)
+    }
+
+    /// (maker fee, taker fee) for a trade, rounded down
+    pub fn trade_fees(trade: &Trade, maker_fee_bps: u16, taker_fee_bps: u16) -> (u128, u128) {
+        let (maker_leg, taker_leg) = Self::collateral_legs(trade);
+        (maker_leg * maker_fee_bps as u128 / 10_000, taker_leg * taker_fee_bps as u128 / 10_000)
+    }
+
+    /// Price a freshly matched trade's fees at its market's rates, before it is stored and broadcast
+    pub async fn apply_trade_fees(&self, trade: &mut Trade) -> Result<()> {
+        let config = self.get_market_config(&trade.market_id).await?;
+        (trade.maker_fee, trade.taker_fee) = Self::trade_fees(trade, config.maker_fee_bps, config.taker_fee_bps);
+        Ok(())
+    }
+
+    /// (maker, taker) fee rates of a market, without the registry lookup get_market_config makes
+    fn fee_rates(&self, market_id: &str) -> (u16, u16) {
+        match self.market_configs.get(market_id) {
+            Some(config) => (config.maker_fee_bps, config.taker_fee_bps),
+            None => (self.config.maker_fee_bps.unwrap_or(0), self.config.taker_fee_bps.unwrap_or(0)),
+        }
+    }
+
+    /// Fee a buy worth `value` reserves on top of it: the higher of the maker and taker rates, since
+    /// whether the order makes or takes is only known once it fills. Rounded up so the fee charged
+    /// on any fill fits
+    pub fn fee_reserve(value: u128, maker_fee_bps: u16, taker_fee_bps: u16) -> u128 {
+        (value * maker_fee_bps.max(taker_fee_bps) as u128).div_ceil(10_000)
+    }
+
+    fn buy_fee_reserve(&self, market_id: &str, value: u128) -> u128 {
+        let (maker_fee_bps, taker_fee_bps) = self.fee_rates(market_id);
+        Self::fee_reserve(value, maker_fee_bps, taker_fee_bps)
+    }
+
+    /// Record the fees of trades whose settlement moved them, in one ledger entry per trade
+    pub async fn record_settled_fees(&self, trades: &[Trade]) -> Result<()> {
+        for trade in trades.iter().filter(|t| t.maker_fee > 0 || t.taker_fee > 0) {
+            let collateral_token = self.collateral_token_for_market(&trade.market_id).await?;
+            self.record_trade_fees(trade, &collateral_token, trade.maker_fee, trade.taker_fee).await?;
+        }
+        Ok(())
+    }
+
+    /// Collect a settled trade's fees into the signer account, one transfer per leg, and record them
+    /// in the fee ledger once every leg went through. A failed leg never unsettles the trade: it is
+    /// queued for retry_fee_collection. Returns the hashes of the fee transfers made
+    pub async fn collect_trade_fees(&self, trade: &Trade, legs: Vec<FeeLeg>) -> Vec<String> {
+        let legs: Vec<FeeLeg> = legs.into_iter().filter(|leg| leg.amount > 0).collect();
+        if legs.is_empty() {
+            return Vec::new();
+        }
+
+        let mut pending = PendingTradeFees { trade: trade.clone(), legs, attempts: 0 };
+        let (tx_hashes, result) = self.collect_pending_fees(&mut pending).await;
+        if let Err(e) = result {
+            warn!("Fees for trade {} not fully collected, queued for retry: {}", trade.trade_id, e);
+            pending.attempts = 1;
+            self.fee_queue.lock().await.push_back(pending);
+        }
+        tx_hashes
+    }
+
+    pub async fn pending_trade_fees(&self) -> Vec<PendingTradeFees> {
+        self.fee_queue.lock().await.iter().cloned().collect()
+    }
+
+    /// Try every queued fee collection once, transferring only the legs still owed. Failed ones go
+    /// back on the queue until they reach MAX_FEE_COLLECTION_ATTEMPTS. Returns how many trades had
+    /// their fees collected in full
+    pub async fn retry_fee_collection(&self) -> usize {
+        let queued: Vec<PendingTradeFees> = self.fee_queue.lock().await.drain(..).collect();
+        let mut collected = 0;
+
+        for mut pending in queued {
+            let (_, result) = self.collect_pending_fees(&mut pending).await;
+            match result {
+                Ok(()) => collected += 1,
+                Err(e) => {
+                    pending.attempts += 1;
+                    if pending.attempts >= MAX_FEE_COLLECTION_ATTEMPTS {
+                        error!("❌ Giving up on fees for trade {} after {} attempts: {}",
+                            pending.trade.trade_id, pending.attempts, e);
+                    } else {
+                        warn!("Fees for trade {} not collected (attempt {}): {}", pending.trade.trade_id, pending.attempts, e);
+                        self.fee_queue.lock().await.push_back(pending);
+                    }
+                }
+            }
+        }
+        collected
+    }
+
+    /// Transfer the legs `pending` still owes, keeping only those that failed, and record the
+    /// trade's fees once none are left. Returns the hashes of the transfers that went through
+    async fn collect_pending_fees(&self, pending: &mut PendingTradeFees) -> (Vec<String>, Result<()>) {
+        let mut tx_hashes = Vec::new();
+        let collateral_token = match self.collateral_token_for_market(&pending.trade.market_id).await {
+            Ok(token) => token,
+            Err(e) => return (tx_hashes, Err(e)),
+        };
+        let fee_account = self.settlement_client.signer_account_id().to_string();
+
+        let mut failure = None;
+        let mut owed = Vec::new();
+        for leg in pending.legs.drain(..) {
+            match self.execute_reserved_usdc_transfer(&collateral_token, &leg.payer, &fee_account, leg.amount, "trading_fee").await {
+                Ok(tx) => tx_hashes.push(tx),
+                Err(e) => {
+                    failure = Some(anyhow::anyhow!("Fee transfer from {} failed: {}", leg.payer, e));
+                    owed.push(leg);
+                }
+            }
+        }
+        pending.legs = owed;
+
+        let result = match failure {
+            Some(e) => Err(e),
+            None => self.record_trade_fees(&pending.trade, &collateral_token, pending.trade.maker_fee, pending.trade.taker_fee).await,
+        };
+        (tx_hashes, result)
+    }
+
+    async fn record_trade_fees(&self, trade: &Trade, collateral_token: &str, maker_fee: u128, taker_fee: u128) -> Result<()> {
+        let recorded = self.database.record_trade_fees(&FeeLedgerEntry {
+            trade_id: trade.trade_id,
+            market_id: trade.market_id.clone(),
+            collateral_token: collateral_token.to_string(),
+            maker_fee,
+            taker_fee,
+            collected_at: Utc::now(),
+            sweep_id: None,
+        }).await?;
+
+        if recorded {
+            info!("🧾 Collected {} + {} {} fees for trade {}",
+                maker_fee, taker_fee, self.config.collateral_name(collateral_token), trade.trade_id);
+        }
+        Ok(())
+    }
+
+    /// Shares minted for `amount` deposited while the protocol held `protocol_balance` against
+    /// `total_shares`, rounded down. The first deposit mints one share per unit
+    pub fn shares_for_deposit(amount: u128, total_shares: u128, protocol_balance: u128) -> u128 {
+        if total_shares == 0 || protocol_balance == 0 {
+            return amount;
+        }
+        amount * total_shares / protocol_balance
+    }
+
+    /// What `shares` are worth out of the protocol balance, rounded down
+    pub fn share_value(shares: u128, total_shares: u128, protocol_balance: u128) -> u128 {
+        if total_shares == 0 {
+            return 0;
+        }
+        shares * protocol_balance / total_shares
+    }
+
+    /// Shares burned to take `amount` out of the protocol, rounded up so the shares left keep their value
+    pub fn shares_for_withdrawal(amount: u128, total_shares: u128, protocol_balance: u128) -> u128 {
+        if protocol_balance == 0 {
+            return total_shares;
         }
+        (amount * total_shares).div_ceil(protocol_balance)
+    }
+
+    /// Interest a position earned since its USDC went in: what its shares are worth above the
+    /// principal, rounded down. Nothing accrues while they are worth less
+    pub fn accrued_yield(deposit: &YieldDeposit, total_shares: u128, protocol_balance: u128) -> u128 {
+        Self::share_value(deposit.shares, total_shares, protocol_balance).saturating_sub(deposit.principal)
+    }
+
+    fn yield_protocol(&self) -> Result<&str> {
+        self.config.yield_protocol()
+            .ok_or_else(|| ApiError::InvalidRequest("Collateral yield is not enabled".to_string()).into())
+    }
+
+    /// Every position with the total shares and what the protocol holds against them
+    async fn yield_pool(&self, protocol: &str, collateral_token: &str) -> Result<(HashMap<String, YieldDeposit>, u128, u128)> {
+        let deposits = self.database.get_yield_deposits().await?;
+        let total_shares = deposits.values().map(|deposit| deposit.shares).sum();
+        let protocol_balance = if total_shares == 0 {
+            0
+        } else {
+            self.near_client.get_yield_protocol_balance(protocol, collateral_token).await
+                .map_err(|e| ApiError::ChainUnavailable(format!("Failed to read yield balance from {}: {}", protocol, e)))?
+        };
+        Ok((deposits, total_shares, protocol_balance))
     }
 
-    /// Calculate required balance for order placement (Polymarket style)
-    /// Formula: maxOrderSize = underlyingAssetBalance - Σ(orderSize - orderFillAmount)
+    /// Principal and unharvested interest a user has in the yield protocol
+    pub async fn get_yield_position(&self, account_id: &str) -> Result<YieldBalance> {
+        let protocol = self.yield_protocol()?;
+        let collateral_token = self.config.default_collateral_token();
+
+        let deposit = self.database.get_yield_deposits().await?.get(account_id).copied().unwrap_or_default();
+        let accrued = if deposit.shares == 0 {
+            0
+        } else {
+            let (_, total_shares, protocol_balance) = self.yield_pool(protocol, collateral_token).await?;
+            Self::accrued_yield(&deposit, total_shares, protocol_balance)
+        };
+
+        Ok(YieldBalance {
+            account_id: account_id.to_string(),
+            yield_protocol: protocol.to_string(),
+            collateral_token: collateral_token.to_string(),
+            deposited: deposit.principal,
+            accrued,
+        })
+    }
+
+    /// USDC a user has in the yield protocol, interest included
+    pub async fn get_yield_balance(&self, account_id: &str) -> Result<u128> {
+        let position = self.get_yield_position(account_id).await?;
+        Ok(position.deposited + position.accrued)
+    }
+
+    /// Deposit the user's available USDC above YIELD_IDLE_THRESHOLD into the yield protocol;
+    /// returns the amount deposited
+    pub async fn deposit_idle_collateral(&self, account_id: &str) -> Result<u128> {
+        let protocol = self.yield_protocol()?;
+        let collateral_token = self.config.default_collateral_token();
+        let _guard = self.yield_lock.lock().await;
+
+        let balance = self.get_user_collateral_balance(account_id, collateral_token).await?;
+        let available = balance.saturating_sub(self.get_locked_collateral(account_id, collateral_token).await?);
+        let excess = available.saturating_sub(self.config.yield_idle_threshold);
+        if excess == 0 {
+            return Ok(0);
+        }
+
+        // Priced before the USDC goes in, so the interest earned so far stays with earlier depositors
+        let (_, total_shares, protocol_balance) = self.yield_pool(protocol, collateral_token).await?;
+        let shares = Self::shares_for_deposit(excess, total_shares, protocol_balance);
+
+        let yield_account = self.near_client.signer_account_id().to_string();
+        self.execute_reserved_usdc_transfer(collateral_token, account_id, &yield_account, excess, "yield_deposit").await?;
+        if let Err(e) = self.near_client.yield_deposit(protocol, collateral_token, excess).await {
+            // Hand the tokens back instead of leaving them in the signer account
+            if let Err(refund_error) = self.near_client.transfer_ft(collateral_token, &yield_account, account_id, excess).await {
+                error!("❌ Returning {} USDC to {} after a failed yield deposit failed: {}", excess, account_id, refund_error);
+            }
+            return Err(e);
+        }
+
+        let position = self.database.credit_yield_deposit(account_id, excess, shares).await?;
+        info!("🌱 Deposited {} idle USDC from {} into {} (principal now {}, {} shares)",
+            excess, account_id, protocol, position.principal, position.shares);
+        Ok(excess)
+    }
+
+    /// Park a user's idle USDC in the background once an order stops locking it
+    pub fn spawn_idle_deposit(self: &Arc<Self>, account_id: &str) {
+        if self.config.yield_protocol().is_none() {
+            return;
+        }
+
+        let manager = Arc::clone(self);
+        let account_id = account_id.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = manager.deposit_idle_collateral(&account_id).await {
+                warn!("⚠️ Depositing idle USDC for {} failed: {}", account_id, e);
+            }
+        });
+    }
+
+    /// Send USDC just withdrawn from the yield protocol on to the user. If that fails it goes back
+    /// into the protocol, so the ledger, which callers only update once this succeeds, still matches
+    async fn pay_out_of_yield(&self, protocol: &str, collateral_token: &str, account_id: &str, amount: u128) -> Result<String> {
+        match self.near_client.transfer_ft(collateral_token, self.near_client.signer_account_id(), account_id, amount).await {
+            Ok(tx_hash) => Ok(tx_hash),
+            Err(e) => {
+                if let Err(redeposit_error) = self.near_client.yield_deposit(protocol, collateral_token, amount).await {
+                    error!("❌ Returning {} USDC to {} after a failed payout to {} failed: {}", amount, protocol, account_id, redeposit_error);
+                }
+                Err(anyhow::anyhow!("Withdrew {} USDC from {} for {} but paying it out failed: {}", amount, protocol, account_id, e))
+            }
+        }
+    }
+
+    /// Withdraw up to `amount` of a user's principal from the yield protocol back to their wallet;
+    /// returns the amount withdrawn
+    pub async fn withdraw_from_yield(&self, account_id: &str, amount: u128) -> Result<u128> {
+        let protocol = self.yield_protocol()?;
+        let collateral_token = self.config.default_collateral_token();
+        let _guard = self.yield_lock.lock().await;
+
+        let (deposits, total_shares, protocol_balance) = self.yield_pool(protocol, collateral_token).await?;
+        let deposit = deposits.get(account_id).copied().unwrap_or_default();
+        // Never more than the shares are worth, should the protocol be under water
+        let amount = amount
+            .min(deposit.principal)
+            .min(Self::share_value(deposit.shares, total_shares, protocol_balance));
+        if amount == 0 {
+            return Ok(0);
+        }
+        let shares = Self::shares_for_withdrawal(amount, total_shares, protocol_balance).min(deposit.shares);
+
+        self.near_client.yield_withdraw(protocol, collateral_token, amount).await?;
+        self.pay_out_of_yield(protocol, collateral_token, account_id, amount).await?;
+        let position = self.database.debit_yield_deposit(account_id, amount, shares).await?;
+
+        info!("🌾 Withdrew {} USDC for {} from {} (principal now {}, {} shares)",
+            amount, account_id, protocol, position.principal, position.shares);
+        Ok(amount)
+    }
+
+    /// Pay a user's accrued interest out to their wallet, leaving the principal deposited
+    pub async fn harvest_yield(&self, account_id: &str) -> Result<YieldHarvest> {
+        let protocol = self.yield_protocol()?;
+        let collateral_token = self.config.default_collateral_token();
+        let _guard = self.yield_lock.lock().await;
+
+        let (deposits, total_shares, protocol_balance) = self.yield_pool(protocol, collateral_token).await?;
+        let deposit = deposits.get(account_id).copied().unwrap_or_default();
+        let amount = Self::accrued_yield(&deposit, total_shares, protocol_balance);
+        if amount == 0 {
+            return Ok(YieldHarvest { account_id: account_id.to_string(), amount, tx_hash: None });
+        }
+        let shares = Self::shares_for_withdrawal(amount, total_shares, protocol_balance).min(deposit.shares);
+
+        self.near_client.yield_withdraw(protocol, collateral_token, amount).await?;
+        let tx_hash = self.pay_out_of_yield(protocol, collateral_token, account_id, amount).await?;
+        self.database.debit_yield_deposit(account_id, 0, shares).await?;
+
+        info!("🌾 Harvested {} USDC of interest for {}", amount, account_id);
+        Ok(YieldHarvest { account_id: account_id.to_string(), amount, tx_hash: Some(tx_hash) })
+    }
+
+    /// Bring a USDC shortfall back out of the yield protocol before an order is rejected for it;
+    /// returns the amount withdrawn
+    async fn top_up_from_yield(&self, account_id: &str, market_id: &str, shortfall: u128) -> Result<u128> {
+        if self.config.yield_protocol().is_none()
+            || self.collateral_token_for_market(market_id).await? != self.config.default_collateral_token()
+        {
+            return Ok(0);
+        }
+
+        match self.withdraw_from_yield(account_id, shortfall).await {
+            Ok(withdrawn) => Ok(withdrawn),
+            Err(e) => {
+                warn!("⚠️ Withdrawing {} USDC from yield for {} failed: {}", shortfall, account_id, e);
+                Ok(0)
+            }
+        }
+    }
+
+    /// Calculate required balance for a single order's unfilled size (Polymarket style)
+    /// Compared against calculate_max_order_size when the order is placed
     pub fn calculate_required_balance(
         &self,
         order: &Order,
     ) -> Result<u128> {
         let required = match order.side {
             OrderSide::Buy => {
-                // Buy orders: Need USDC = price * size (price in cents)
-                // Example: Buy 1000 YES @ 50¢ = need 500 USDC
-                (order.remaining_size as u128 * order.price as u128) / 100000
+                // Buy orders: Need USDC = price * size (price in cents) plus the fee paid on top
+                // Example: Buy 1000 YES @ 50¢ = need 500 USDC + fee
+                let value = Self::netted_collateral_requirement(&order.side, order.price, order.remaining_size, 0);
+                value + self.buy_fee_reserve(&order.market_id, value)
             }
             OrderSide::Sell => {
                 // Sell orders: Need outcome tokens = size
@@ -53,16 +506,261 @@ impl CollateralManager {
         Ok(required)
     }
 
+    /// USDC collateral for an order once held outcome tokens are netted against it
+    /// Buys always need price * size. A sell backed by tokens the user already holds carries no
+    /// exposure; only the unhedged remainder needs (1 - price) * size of USDC, the cost of minting
+    /// the complete set that settlement would otherwise have to split
+    pub fn netted_collateral_requirement(side: &OrderSide, price: u64, size: u128, hedged_tokens: u128) -> u128 {
+        match side {
+            OrderSide::Buy => (size * price as u128) / 100000,
+            OrderSide::Sell => {
+                let unhedged = size.saturating_sub(hedged_tokens);
+                (unhedged * 100000u128.saturating_sub(price as u128)) / 100000
+            }
+        }
+    }
+
+    /// Netted USDC requirement for a prospective order, using the user's free outcome tokens
+    /// (CTF balance minus what their other open sells on the same outcome already lock)
+    pub async fn get_netted_collateral_requirement(
+        &self,
+        account_id: &str,
+        market_id: &str,
+        outcome: u8,
+        side: &OrderSide,
+        price: u64,
+        size: u128,
+    ) -> Result<u128> {
+        let hedged_tokens = match side {
+            OrderSide::Buy => 0,
+            OrderSide::Sell => {
+                let max_retries = 3;
+                let condition_id = self.get_condition_id_with_retry(market_id, max_retries).await?;
+                let collateral_token = self.collateral_token_for_market(market_id).await?;
+                let token_balance = self.get_token_balance_with_retry(account_id, &condition_id, outcome, &collateral_token, max_retries).await
+                    .map_err(|e| ApiError::ChainUnavailable(e.to_string()))?;
+
+                let locked_by_sells: u128 = self.database.get_active_orders().await?
+                    .iter()
+                    .filter(|order| {
+                        order.user_account == account_id
+                            && order.market_id == market_id
+                            && order.outcome == outcome
+                            && order.side == OrderSide::Sell
+                    })
+                    .map(Self::calculate_locked_amount)
+                    .sum();
+
+                token_balance.saturating_sub(locked_by_sells)
+            }
+        };
+
+        let required = Self::netted_collateral_requirement(side, price, size, hedged_tokens);
+        info!(
+            "🧮 Netted requirement for {} in market {} outcome {} ({:?} {} @ {}): {} hedged, ${:.2} USDC required",
+            account_id, market_id, outcome, side, size, price, hedged_tokens.min(size), required as f64 / 1_000_000.0
+        );
+
+        Ok(required)
+    }
+
+    /// Collateral still locked by an open order: max(0, orderSize - fillAmount)
+    /// Buy orders lock USDC (unfilled size * price), sell orders lock outcome tokens
+    fn calculate_locked_amount(order: &Order) -> u128 {
+        let unfilled = order.original_size.saturating_sub(order.filled_size);
+
+        match order.side {
+            OrderSide::Buy => (unfilled * order.price as u128) / 100000,
+            OrderSide::Sell => unfilled,
+        }
+    }
+
+    /// Outcome tokens a user's open sells in `market_id` lock
+    fn locked_sell_tokens(orders: &[Order], account_id: &str, market_id: &str) -> u128 {
+        orders.iter()
+            .filter(|order| {
+                order.user_account == account_id
+                    && order.side == OrderSide::Sell
+                    && order.market_id == market_id
+            })
+            .map(Self::calculate_locked_amount)
+            .sum()
+    }
+
+    /// Collateral a user's open buys lock in `markets`, the markets settling in one collateral token
+    /// with their (maker, taker) fee rates: each buy's unfilled value plus its fee reserve
+    fn locked_buy_collateral(orders: &[Order], account_id: &str, markets: &HashMap<String, (u16, u16)>) -> u128 {
+        orders.iter()
+            .filter(|order| order.user_account == account_id && order.side == OrderSide::Buy)
+            .filter_map(|order| {
+                let (maker_fee_bps, taker_fee_bps) = markets.get(&order.market_id)?;
+                let value = Self::calculate_locked_amount(order);
+                Some(value + Self::fee_reserve(value, *maker_fee_bps, *taker_fee_bps))
+            })
+            .sum()
+    }
+
+    /// maxOrderSize = underlyingAssetBalance - Σmax(0, orderSize - fillAmount), never below zero
+    fn max_order_size(underlying_balance: u128, locked: u128) -> u128 {
+        underlying_balance.saturating_sub(locked)
+    }
+
+    /// Maximum collateral a user can commit to a new order in this market (Polymarket style)
+    /// Formula: maxOrderSize = underlyingAssetBalance - Σmax(0, orderSize - fillAmount)
+    /// Returned in the market's collateral token for buy orders and in outcome tokens for sell orders
+    pub async fn calculate_max_order_size(
+        &self,
+        account_id: &str,
+        market_id: &str,
+        side: &OrderSide,
+    ) -> Result<u128> {
+        let underlying_balance = match side {
+            OrderSide::Buy => {
+                let collateral_token = self.collateral_token_for_market(market_id).await?;
+                self.get_user_collateral_balance(account_id, &collateral_token).await?
+            }
+            OrderSide::Sell => self.get_user_outcome_token_balance(account_id, market_id).await?,
+        };
+
+        // Everything already committed to the user's pending orders on the same side
+        let locked = self.get_locked_amount_for_market(account_id, market_id, side).await?;
+        let max_order_size = Self::max_order_size(underlying_balance, locked);
+
+        info!(
+            "📐 Max order size for {} in market {} ({:?}): balance {} - locked {} = {}",
+            account_id, market_id, side, underlying_balance, locked, max_order_size
+        );
+
+        Ok(max_order_size)
+    }
+
+    /// Full on-chain collateral picture for an account: the balance of one collateral token,
+    /// outcome token balances from the CTF, and what open orders currently lock
+    pub async fn get_full_collateral_status(
+        &self,
+        account_id: &str,
+        market_ids: Vec<String>,
+        collateral_token: &str,
+    ) -> Result<CollateralStatus> {
+        let usdc_balance = self.get_user_collateral_balance(account_id, collateral_token).await?;
+        // Collateral locks span every market settling in the same token
+        let reserved_usdc = self.get_locked_collateral(account_id, collateral_token).await?;
+
+        let open_sells: Vec<Order> = self.database.get_active_orders().await?
+            .into_iter()
+            .filter(|order| order.user_account == account_id && order.side == OrderSide::Sell)
+            .collect();
+
+        let mut outcome_positions = Vec::new();
+        for market_id in market_ids {
+            let (condition_id, market_token) = match self.database.get_market_condition(&market_id).await? {
+                Some(record) => (record.condition_id, record.collateral_token),
+                None => {
+                    warn!("⚠️ Skipping collateral status for unregistered market {}", market_id);
+                    continue;
+                }
+            };
+
+            for outcome in [1u8, 0u8] {
+                let position_id = self.near_client.get_position_id_for_outcome(&condition_id, outcome, &market_token).await
+                    .map_err(|e| ApiError::ChainUnavailable(format!(
+                        "Failed to get position ID for {} outcome {}: {}", market_id, outcome, e
+                    )))?;
+                let balance = self.near_client.get_ctf_token_balance(account_id, &position_id).await
+                    .map_err(|e| ApiError::ChainUnavailable(format!(
+                        "Failed to get CTF balance for {} outcome {}: {}", market_id, outcome, e
+                    )))?;
+                let reserved = open_sells
+                    .iter()
+                    .filter(|order| order.market_id == market_id && order.outcome == outcome)
+                    .map(Self::calculate_locked_amount)
+                    .sum();
+
+                outcome_positions.push(PositionBalance {
+                    market_id: market_id.clone(),
+                    condition_id: condition_id.clone(),
+                    outcome,
+                    position_id,
+                    balance,
+                    reserved,
+                });
+            }
+        }
+
+        info!(
+            "📊 Collateral status for {}: {} {} ({} reserved), {} positions",
+            account_id,
+            usdc_balance,
+            self.config.collateral_name(collateral_token),
+            reserved_usdc,
+            outcome_positions.len()
+        );
+
+        Ok(CollateralStatus {
+            account_id: account_id.to_string(),
+            collateral_token: collateral_token.to_string(),
+            usdc_balance,
+            reserved_usdc,
+            available_usdc: usdc_balance.saturating_sub(reserved_usdc),
+            outcome_positions,
+        })
+    }
+
     /// Check and reserve balance for order placement (Polymarket style)
-    /// Returns whether the order can be placed based on available balance
+    /// Fails with ApiError::InsufficientBalance if the order can't be covered
     pub async fn check_and_reserve_balance(
         &self,
         order: &Order,
-    ) -> Result<bool> {
+    ) -> Result<()> {
+        // Sells are netted against outcome tokens the user already holds; only the unhedged
+        // remainder has to be margined in USDC
+        if matches!(order.side, OrderSide::Sell) {
+            let required_usdc = self.get_netted_collateral_requirement(
+                &order.user_account,
+                &order.market_id,
+                order.outcome,
+                &order.side,
+                order.price,
+                order.remaining_size,
+            ).await?;
+
+            if required_usdc == 0 {
+                info!("✅ Sell order {} fully hedged by held outcome tokens", order.order_id);
+                return Ok(());
+            }
+
+            let mut available_usdc = self.calculate_max_order_size(&order.user_account, &order.market_id, &OrderSide::Buy).await?;
+            if available_usdc < required_usdc {
+                available_usdc += self.top_up_from_yield(&order.user_account, &order.market_id, required_usdc - available_usdc).await?;
+            }
+            if available_usdc < required_usdc {
+                info!(
+                    "❌ Insufficient margin for unhedged sell {}: need ${}, have ${} available",
+                    order.order_id, required_usdc as f64 / 1_000_000.0, available_usdc as f64 / 1_000_000.0
+                );
+                let collateral_token = self.collateral_token_for_market(&order.market_id).await?;
+                return Err(ApiError::InsufficientBalance {
+                    asset: self.config.collateral_name(&collateral_token),
+                    required: required_usdc,
+                    available: available_usdc,
+                }.into());
+            }
+
+            info!(
+                "✅ Sell order {} partially hedged, ${} USDC margin covered",
+                order.order_id, required_usdc as f64 / 1_000_000.0
+            );
+            return Ok(());
+        }
+
         let required_balance = self.calculate_required_balance(order)?;
 
-        // Get user's available balance for this specific market
-        let available = self.get_available_market_balance(&order.user_account, &order.market_id, &order.side).await?;
+        // Net collateral after everything locked by the user's other open orders on this side
+        let mut available = self.calculate_max_order_size(&order.user_account, &order.market_id, &order.side).await?;
+        if matches!(order.side, OrderSide::Buy) && available < required_balance {
+            // Idle USDC parked for yield is withdrawn before the order is turned down
+            available += self.top_up_from_yield(&order.user_account, &order.market_id, required_balance - available).await?;
+        }
 
         info!(
             "💰 Balance check for order {}: need {}, have {} available",
@@ -79,7 +777,6 @@ impl CollateralManager {
             }
         );
 
-        // Polymarket's formula: maxOrderSize = underlyingAssetBalance - Σ(orderSize - orderFillAmount)
         if available < required_balance {
             info!(
                 "❌ Insufficient balance for order: need {}, have {} available in market {}",
@@ -95,7 +792,15 @@ impl CollateralManager {
                 },
                 order.market_id
             );
-            return Ok(false);
+            let asset = match order.side {
+                OrderSide::Buy => self.config.collateral_name(&self.collateral_token_for_market(&order.market_id).await?),
+                OrderSide::Sell => "outcome tokens".to_string(),
+            };
+            return Err(ApiError::InsufficientBalance {
+                asset,
+                required: required_balance,
+                available,
+            }.into());
         }
 
         // Don't create the reservation yet - just check if balance is sufficient
@@ -113,7 +818,7 @@ impl CollateralManager {
             order.user_account
         );
 
-        Ok(true)
+        Ok(())
     }
 
     /// Create collateral reservation after order is successfully inserted
@@ -177,21 +882,18 @@ impl CollateralManager {
             amount as f64 / 1_000_000.0, reservation.account_id, memo);
 
         // Since frontend handles approvals, we can directly call transfer_from
-        // The platform contract should have been approved to spend user's USDC
-        let usdc_contract_str = std::env::var("USDC_CONTRACT_ID")
-            .unwrap_or_else(|_| "3e2210e1184b45b64c8a434c0a7e7b23cc04ea7eb7a6c3c32520d03d4afcb8af".to_string());
-        let platform_account = std::env::var("PLATFORM_ACCOUNT_ID")
-            .unwrap_or_else(|_| "orderbook.near".to_string());
+        // The platform contract should have been approved to spend user's collateral
+        let collateral_token = self.collateral_token_for_market(&reservation.market_id).await?;
 
         let args = serde_json::json!({
             "from": reservation.account_id,
-            "to": platform_account,
+            "to": self.config.platform_account_id,
             "value": amount.to_string()
         });
 
         // Execute the transfer using the platform's authority
         self.near_client.call_contract_function_commit(
-            &usdc_contract_str.parse()?,
+            &collateral_token.parse()?,
             "transfer_from",
             &args,
             100_000_000_000_000, // 100000 TGas for transfer_from
@@ -245,47 +947,6 @@ impl CollateralManager {
         reservation.reserved_amount.saturating_sub(used_collateral)
     }
 
-    /// Get user's available balance for a specific market and order side (Polymarket style)
-    /// DEPRECATED: Use get_real_time_market_balance for atomic operations
-    async fn get_available_market_balance(
-        &self,
-        account_id: &str,
-        market_id: &str,
-        side: &OrderSide,
-    ) -> Result<u128> {
-        // Delegate to atomic implementation to prevent race conditions
-        self.get_real_time_market_balance(account_id, market_id, side).await
-    }
-
-    /// Get real-time available balance for a specific market and side with race condition protection
-    async fn get_real_time_market_balance(
-        &self,
-        account_id: &str,
-        market_id: &str,
-        side: &OrderSide,
-    ) -> Result<u128> {
-        match side {
-            OrderSide::Buy => {
-                // For buy orders, check USDC balance with fresh data
-                let usdc_balance = self.get_user_usdc_balance(account_id).await?;
-
-                // Subtract already reserved amounts for pending buy orders in this market
-                let reserved = self.get_reserved_usdc_for_market(account_id, market_id).await?;
-
-                Ok(usdc_balance.saturating_sub(reserved))
-            }
-            OrderSide::Sell => {
-                // For sell orders, check outcome token balance with fresh data
-                let token_balance = self.get_user_outcome_token_balance(account_id, market_id).await?;
-
-                // Subtract already reserved tokens for pending sell orders in this market
-                let reserved = self.get_reserved_tokens_for_market(account_id, market_id).await?;
-
-                Ok(token_balance.saturating_sub(reserved))
-            }
-        }
-    }
-
     /// Reserve balance for a specific market (Polymarket style)
     /// DEPRECATED: Use reserve_market_balance_atomic for atomic operations
     async fn reserve_market_balance(
@@ -333,36 +994,37 @@ impl CollateralManager {
         Ok(())
     }
 
-    /// Get user's USDC balance from on-chain contract with retry logic
-    async fn get_user_usdc_balance(&self, account_id: &str) -> Result<u128> {
+    /// Get user's balance of a collateral token from its NEP-141 contract with retry logic
+    async fn get_user_collateral_balance(&self, account_id: &str, collateral_token: &str) -> Result<u128> {
         let max_retries = 3;
         let mut retry_delay = std::time::Duration::from_millis(100); // Start with 100ms
+        let asset = self.config.collateral_name(collateral_token);
 
         for attempt in 1..=max_retries {
-            match self.near_client.get_usdc_balance(account_id).await {
+            match self.settlement_client.get_ft_balance(collateral_token, account_id).await {
                 Ok(balance) => {
                     if attempt > 1 {
-                        info!("✅ USDC balance retrieved on attempt {}/{}: ${}",
-                            attempt, max_retries, balance as f64 / 1_000_000.0);
+                        info!("✅ {} balance retrieved on attempt {}/{}: {}",
+                            asset, attempt, max_retries, balance);
                     } else {
-                        info!("💰 USDC balance for {}: ${}", account_id, balance as f64 / 1_000_000.0);
+                        info!("💰 {} balance for {}: {}", asset, account_id, balance);
                     }
                     return Ok(balance);
                 }
                 Err(e) => {
                     if attempt < max_retries {
-                        warn!("⚠️ USDC balance attempt {}/{} failed for {}: {}",
-                            attempt, max_retries, account_id, e);
+                        warn!("⚠️ {} balance attempt {}/{} failed for {}: {}",
+                            asset, attempt, max_retries, account_id, e);
                         warn!("🔄 Retrying in {}ms...", retry_delay.as_millis());
                         tokio::time::sleep(retry_delay).await;
                         retry_delay *= 2; // Exponential backoff
                     } else {
-                        error!("❌ CRITICAL: All {} USDC balance attempts failed for {}: {}",
-                            max_retries, account_id, e);
+                        error!("❌ CRITICAL: All {} {} balance attempts failed for {}: {}",
+                            max_retries, asset, account_id, e);
                         error!("🚨 HFT SYSTEM REQUIRES REAL BALANCES - ORDER REJECTED");
-                        return Err(anyhow::anyhow!(
-                            "Failed to get USDC balance after {} retries: {}", max_retries, e
-                        ));
+                        return Err(ApiError::ChainUnavailable(format!(
+                            "Failed to get {} balance after {} retries: {}", asset, max_retries, e
+                        )).into());
                     }
                 }
             }
@@ -377,10 +1039,11 @@ impl CollateralManager {
 
         // First, get condition ID with retry
         let condition_id = self.get_condition_id_with_retry(market_id, max_retries).await?;
+        let collateral_token = self.collateral_token_for_market(market_id).await?;
 
         // Get token balances with retry for both outcomes
-        let yes_balance = self.get_token_balance_with_retry(account_id, &condition_id, 1, max_retries).await;
-        let no_balance = self.get_token_balance_with_retry(account_id, &condition_id, 0, max_retries).await;
+        let yes_balance = self.get_token_balance_with_retry(account_id, &condition_id, 1, &collateral_token, max_retries).await;
+        let no_balance = self.get_token_balance_with_retry(account_id, &condition_id, 0, &collateral_token, max_retries).await;
 
         let total_balance = match (yes_balance, no_balance) {
             (Ok(yes), Ok(no)) => {
@@ -402,9 +1065,9 @@ impl CollateralManager {
                 error!("  YES error: {}", yes_err);
                 error!("  NO error: {}", no_err);
                 error!("🚨 HFT SYSTEM REQUIRES REAL BALANCES - ORDER REJECTED");
-                return Err(anyhow::anyhow!(
+                return Err(ApiError::ChainUnavailable(format!(
                     "Failed to get token balances: YES({}), NO({})", yes_err, no_err
-                ));
+                )).into());
             }
         };
 
@@ -418,7 +1081,7 @@ impl CollateralManager {
         let mut retry_delay = std::time::Duration::from_millis(50);
 
         for attempt in 1..=max_retries {
-            match self.near_client.get_market_condition_id(market_id).await {
+            match self.database.get_market_condition_id(market_id).await {
                 Ok(Some(id)) => {
                     if attempt > 1 {
                         info!("✅ Condition ID retrieved on attempt {}/{}: {}", attempt, max_retries, id);
@@ -426,7 +1089,7 @@ impl CollateralManager {
                     return Ok(id);
                 }
                 Ok(None) => {
-                    return Err(anyhow::anyhow!("Market {} has no condition ID registered", market_id));
+                    return Err(ApiError::MarketNotFound(market_id.to_string()).into());
                 }
                 Err(e) => {
                     if attempt < max_retries {
@@ -449,6 +1112,7 @@ impl CollateralManager {
         account_id: &str,
         condition_id: &str,
         outcome: u8,
+        collateral_token: &str,
         max_retries: u32,
     ) -> Result<u128> {
         let mut retry_delay = std::time::Duration::from_millis(50);
@@ -456,7 +1120,7 @@ impl CollateralManager {
 
         for attempt in 1..=max_retries {
             // Try to get position ID
-            let position_id = match self.near_client.get_position_id_for_outcome(condition_id, outcome).await {
+            let position_id = match self.settlement_client.get_position_id_for_outcome(condition_id, outcome, collateral_token).await {
                 Ok(id) => id,
                 Err(e) => {
                     if attempt < max_retries {
@@ -471,7 +1135,7 @@ impl CollateralManager {
             };
 
             // Try to get balance
-            match self.near_client.get_ctf_token_balance(account_id, &position_id).await {
+            match self.settlement_client.get_ctf_token_balance(account_id, &position_id).await {
                 Ok(balance) => {
                     if attempt > 1 {
                         info!("✅ {} token balance retrieved on attempt {}/{}: {}",
@@ -494,45 +1158,40 @@ impl CollateralManager {
         unreachable!()
     }
 
-    /// Get reserved USDC amount for pending buy orders in this market
-    async fn get_reserved_usdc_for_market(&self, account_id: &str, market_id: &str) -> Result<u128> {
-        // Query database for pending buy order reservations
-        let orders = self.database.get_active_orders().await?;
-
-        let reserved_usdc = orders
-            .iter()
-            .filter(|order| {
-                order.user_account == account_id
-                    && order.market_id == market_id
-                    && matches!(order.side, crate::types::OrderSide::Buy)
-            })
-            .map(|order| {
-                // Calculate USDC needed for this buy order
-                (order.remaining_size * order.price as u128) / 100000
-            })
-            .sum();
+    /// Sum of collateral still locked by a user's open orders on one side: Σmax(0, orderSize - fillAmount)
+    /// A collateral token is a single wallet balance, so buys count every market settling in the
+    /// market's token; outcome tokens are per market
+    async fn get_locked_amount_for_market(
+        &self,
+        account_id: &str,
+        market_id: &str,
+        side: &OrderSide,
+    ) -> Result<u128> {
+        if matches!(side, OrderSide::Buy) {
+            let collateral_token = self.collateral_token_for_market(market_id).await?;
+            return self.get_locked_collateral(account_id, &collateral_token).await;
+        }
 
-        info!("💰 Reserved USDC for {}: ${:.2}", account_id, reserved_usdc as f64 / 1_000_000.0);
-        Ok(reserved_usdc)
+        let orders = self.database.get_active_orders().await?;
+        Ok(Self::locked_sell_tokens(&orders, account_id, market_id))
     }
 
-    /// Get reserved token amount for pending sell orders in this market
-    async fn get_reserved_tokens_for_market(&self, account_id: &str, market_id: &str) -> Result<u128> {
-        // Query database for pending sell order reservations
+    /// Collateral locked by a user's open buys across every market that settles in `collateral_token`
+    async fn get_locked_collateral(&self, account_id: &str, collateral_token: &str) -> Result<u128> {
         let orders = self.database.get_active_orders().await?;
 
-        let reserved_tokens = orders
-            .iter()
-            .filter(|order| {
-                order.user_account == account_id
-                    && order.market_id == market_id
-                    && matches!(order.side, crate::types::OrderSide::Sell)
-            })
-            .map(|order| order.remaining_size)
-            .sum();
+        // Fee rates of the markets this user has buys in that settle in `collateral_token`
+        let mut markets: HashMap<String, (u16, u16)> = HashMap::new();
+        let mut checked: HashSet<&str> = HashSet::new();
+        for order in orders.iter().filter(|order| order.user_account == account_id && order.side == OrderSide::Buy) {
+            if checked.insert(order.market_id.as_str())
+                && self.collateral_token_for_market(&order.market_id).await? == collateral_token
+            {
+                markets.insert(order.market_id.clone(), self.fee_rates(&order.market_id));
+            }
+        }
 
-        info!("🪙 Reserved tokens for {}: {} tokens", account_id, reserved_tokens);
-        Ok(reserved_tokens)
+        Ok(Self::locked_buy_collateral(&orders, account_id, &markets))
     }
 
     /// Execute atomic swap: USDC transfer + token transfer (Polymarket style)
@@ -545,22 +1204,17 @@ impl CollateralManager {
         token_amount: u128,
         outcome: u8,
         condition_id: &str,
+        collateral_token: &str,
     ) -> Result<String> {
-        info!("⚡ Executing atomic swap via multicall: {} → {} (${} USDC ↔ {} tokens)",
-            buyer_account, seller_account, usdc_amount as f64 / 1_000_000.0, token_amount);
+        info!("⚡ Executing atomic swap via multicall: {} → {} ({} {} ↔ {} tokens)",
+            buyer_account, seller_account, usdc_amount, self.config.collateral_name(collateral_token), token_amount);
 
         // Check if multicall contract is available
         let _multicall_contract = std::env::var("MULTICALL_CONTRACT_ID")
             .unwrap_or_else(|_| "multicall.near".to_string());
 
-        // Get contract addresses
-        let usdc_contract_str = std::env::var("USDC_CONTRACT_ID")
-            .unwrap_or_else(|_| "3e2210e1184b45b64c8a434c0a7e7b23cc04ea7eb7a6c3c32520d03d4afcb8af".to_string());
-        let ctf_contract_id = std::env::var("CTF_CONTRACT_ID")
-            .unwrap_or_else(|_| "ctf.ashpk20.testnet".to_string());
-
         // Get position ID first (fail fast if invalid)
-        let position_id = self.near_client.get_position_id_for_outcome(condition_id, outcome).await
+        let position_id = self.near_client.get_position_id_for_outcome(condition_id, outcome, collateral_token).await
             .map_err(|e| anyhow::anyhow!("Failed to get position ID: {}", e))?;
 
         info!("📋 Atomic swap details:");
@@ -569,8 +1223,8 @@ impl CollateralManager {
 
         // Execute HTLC-style atomic swap with state tracking
         self.execute_htlc_atomic_swap(
-            &usdc_contract_str,
-            &ctf_contract_id,
+            collateral_token,
+            &self.config.ctf_contract_id,
             buyer_account,
             seller_account,
             usdc_amount,
@@ -855,40 +1509,26 @@ impl CollateralManager {
         Ok(())
     }
 
-    /// Execute USDC transfer from buyer's reserved collateral to seller
+    /// Execute a collateral transfer from buyer's reserved collateral to seller
     async fn execute_reserved_usdc_transfer(
         &self,
+        collateral_token: &str,
         buyer_account: &str,
         seller_account: &str,
         amount: u128,
         memo: &str,
     ) -> Result<String> {
-        info!("💸 Executing USDC transfer: {} → {} (${}, memo: {})",
-            buyer_account, seller_account, amount as f64 / 1_000_000.0, memo);
+        info!("💸 Executing {} transfer: {} → {} ({}, memo: {})",
+            self.config.collateral_name(collateral_token), buyer_account, seller_account, amount, memo);
 
         // In a real system, we would:
         // 1. Check buyer's reserved collateral
         // 2. Transfer USDC from buyer's balance to seller's balance
         // 3. Update internal accounting
 
-        // For now, use direct transfer_from since frontend handles approvals
-        let usdc_contract_str = std::env::var("USDC_CONTRACT_ID")
-            .unwrap_or_else(|_| "3e2210e1184b45b64c8a434c0a7e7b23cc04ea7eb7a6c3c32520d03d4afcb8af".to_string());
-
-        let args = serde_json::json!({
-            "from": buyer_account,
-            "to": seller_account,
-            "value": amount.to_string()
-        });
-
-        // Execute the transfer using platform's authority to move USDC between users
-        self.near_client.call_contract_function_commit(
-            &usdc_contract_str.parse()?,
-            "transfer_from",
-            &args,
-            100_000_000_000_000, // 100000 TGas
-            1, // 1 yoctoNEAR deposit
-        ).await
+        // For now, use direct transfer_from since frontend handles approvals: the platform's
+        // authority moves collateral between users
+        self.settlement_client.transfer_collateral_from(collateral_token, buyer_account, seller_account, amount).await
     }
 
     /// Calculate net settlement for matched trades (Polymarket's approach)
@@ -1076,6 +1716,311 @@ impl CollateralManager {
         }
     }
 
+    /// Settle a matching cycle's direct matches with the fewest transfers we can find.
+    /// Collateral is netted per (account, token) and outcome tokens per (account, position_id), so
+    /// Alice paying Bob 50 and Bob paying Charlie 30 goes out as Alice -> Charlie 30 and Alice -> Bob 20.
+    /// Fees are netted in with the collateral, owed to the signer account, and recorded for each
+    /// trade that settles.
+    /// A failed transfer holds up every trade whose maker or taker it was moving funds for. The
+    /// batch's other transfers already went through, so the failed ones are queued for
+    /// retry_batch_transfers and the trades they hold up must not be settled again; those trades
+    /// come back with `transfers_pending`
+    pub async fn batch_settle_trades(&self, trades: Vec<Trade>) -> Result<BatchSettlementResult> {
+        if trades.is_empty() {
+            return Err(anyhow::anyhow!("No trades to settle"));
+        }
+
+        let mut trade_errors: HashMap<Uuid, String> = HashMap::new();
+        let mut market_tokens: HashMap<String, String> = HashMap::new();
+        let mut position_ids: HashMap<(String, u8), String> = HashMap::new();
+        let mut lookup_failures: HashMap<(String, u8), String> = HashMap::new();
+
+        for trade in &trades {
+            if trade.trade_type != TradeType::DirectMatch {
+                trade_errors.insert(trade.trade_id, format!("{:?} trades settle through the CTF, not by transfer", trade.trade_type));
+                continue;
+            }
+
+            if !market_tokens.contains_key(&trade.market_id) {
+                match self.collateral_token_for_market(&trade.market_id).await {
+                    Ok(token) => { market_tokens.insert(trade.market_id.clone(), token); }
+                    Err(e) => {
+                        trade_errors.insert(trade.trade_id, format!("Collateral lookup failed: {}", e));
+                        continue;
+                    }
+                }
+            }
+            let collateral_token = &market_tokens[&trade.market_id];
+
+            let key = (trade.condition_id.clone(), trade.outcome);
+            if !position_ids.contains_key(&key) && !lookup_failures.contains_key(&key) {
+                match self.settlement_client.get_position_id_for_outcome(&trade.condition_id, trade.outcome, collateral_token).await {
+                    Ok(position_id) => { position_ids.insert(key.clone(), position_id); }
+                    Err(e) => { lookup_failures.insert(key.clone(), format!("Position lookup failed: {}", e)); }
+                }
+            }
+            if let Some(e) = lookup_failures.get(&key) {
+                trade_errors.insert(trade.trade_id, e.clone());
+            }
+        }
+
+        // Collateral only nets between trades in the same token
+        let mut by_token: BTreeMap<String, Vec<Trade>> = BTreeMap::new();
+        for trade in trades.iter().filter(|t| !trade_errors.contains_key(&t.trade_id)) {
+            by_token.entry(market_tokens[&trade.market_id].clone()).or_default().push(trade.clone());
+        }
+
+        let mut usdc_transfers = Vec::new();
+        let mut token_transfers = Vec::new();
+        let mut settleable = 0;
+        let fee_account = self.settlement_client.signer_account_id();
+        for (collateral_token, group) in &by_token {
+            let (collateral, tokens) = Self::plan_batch_transfers(group, &position_ids, fee_account);
+            usdc_transfers.extend(collateral.into_iter().map(|transfer| NetTransfer {
+                collateral_token: Some(collateral_token.clone()),
+                ..transfer
+            }));
+            token_transfers.extend(tokens);
+            settleable += group.len();
+        }
+
+        info!("📦 Batch settling {} trades with {} collateral and {} token transfers",
+            settleable, usdc_transfers.len(), token_transfers.len());
+
+        for transfer in usdc_transfers.iter_mut().chain(token_transfers.iter_mut()) {
+            match self.execute_net_transfer(transfer, "batch_settlement").await {
+                Ok(tx) => transfer.tx_hash = Some(tx),
+                Err(e) => {
+                    error!("❌ Batch transfer {} -> {} failed: {}", transfer.from_account, transfer.to_account, e);
+                    transfer.error = Some(e.to_string());
+                }
+            }
+        }
+
+        let batch_id = Uuid::new_v4();
+        let failed: Vec<NetTransfer> = usdc_transfers.iter().chain(&token_transfers)
+            .filter(|t| t.error.is_some())
+            .cloned()
+            .collect();
+        let mut held_up = Vec::new();
+        let mut trade_results = Vec::with_capacity(trades.len());
+        for trade in &trades {
+            if let Some(e) = trade_errors.get(&trade.trade_id) {
+                trade_results.push(TradeSettlementResult { trade_id: trade.trade_id, success: false, error: Some(e.clone()), transfers_pending: false });
+                continue;
+            }
+
+            // The failed transfers in this trade's token and position that move its maker's or taker's funds
+            let position_id = position_ids.get(&(trade.condition_id.clone(), trade.outcome));
+            let collateral_token = market_tokens.get(&trade.market_id);
+            let involved = |t: &NetTransfer| [&t.from_account, &t.to_account].iter()
+                .any(|a| **a == trade.maker_account || **a == trade.taker_account);
+            let legs: Vec<usize> = failed.iter().enumerate()
+                .filter(|(_, t)| match &t.position_id {
+                    Some(position) => Some(position) == position_id,
+                    None => t.collateral_token.as_ref() == collateral_token,
+                })
+                .filter(|(_, t)| involved(*t))
+                .map(|(i, _)| i)
+                .collect();
+
+            if legs.is_empty() {
+                self.record_netted_fees(trade).await;
+                trade_results.push(TradeSettlementResult { trade_id: trade.trade_id, success: true, error: None, transfers_pending: false });
+            } else {
+                trade_results.push(TradeSettlementResult {
+                    trade_id: trade.trade_id,
+                    success: false,
+                    error: failed[legs[0]].error.clone(),
+                    transfers_pending: true,
+                });
+                held_up.push((trade.clone(), legs));
+            }
+        }
+
+        if !held_up.is_empty() {
+            warn!("Batch {}: {} transfers failed, holding up {} trades until they are retried",
+                batch_id, failed.len(), held_up.len());
+            self.batch_queue.lock().await.push_back(PendingBatchTransfers {
+                batch_id,
+                transfers: failed,
+                trades: held_up,
+                attempts: 1,
+            });
+        }
+
+        Ok(BatchSettlementResult {
+            batch_id,
+            usdc_transfers,
+            token_transfers,
+            trade_results,
+        })
+    }
+
+    pub async fn pending_batch_transfers(&self) -> Vec<PendingBatchTransfers> {
+        self.batch_queue.lock().await.iter().cloned().collect()
+    }
+
+    /// Try the failed transfers of every queued batch once more, leaving the ones that went through
+    /// alone. Returns the trades no longer held up, with the hashes of the transfers they waited on;
+    /// a batch with trades still held up goes back on the queue until MAX_BATCH_TRANSFER_ATTEMPTS
+    pub async fn retry_batch_transfers(&self) -> Vec<(Trade, String)> {
+        let queued: Vec<PendingBatchTransfers> = self.batch_queue.lock().await.drain(..).collect();
+        let mut settled = Vec::new();
+
+        for mut pending in queued {
+            for transfer in pending.transfers.iter_mut().filter(|t| t.error.is_some()) {
+                match self.execute_net_transfer(transfer, "batch_settlement_retry").await {
+                    Ok(tx) => {
+                        transfer.tx_hash = Some(tx);
+                        transfer.error = None;
+                    }
+                    Err(e) => transfer.error = Some(e.to_string()),
+                }
+            }
+
+            let transfers = &pending.transfers;
+            let (done, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut pending.trades).into_iter()
+                .partition(|(_, legs)| legs.iter().all(|&i| transfers[i].error.is_none()));
+            for (trade, legs) in done {
+                self.record_netted_fees(&trade).await;
+                let tx_hash = legs.iter().filter_map(|&i| transfers[i].tx_hash.clone()).collect::<Vec<_>>().join(";");
+                settled.push((trade, tx_hash));
+            }
+
+            if waiting.is_empty() {
+                continue;
+            }
+            pending.trades = waiting;
+            pending.attempts += 1;
+            if pending.attempts >= MAX_BATCH_TRANSFER_ATTEMPTS {
+                let trade_ids: Vec<String> = pending.trades.iter().map(|(t, _)| t.trade_id.to_string()).collect();
+                error!("❌ Giving up on the failed transfers of batch {} after {} attempts; trades {} need reconciling by hand",
+                    pending.batch_id, pending.attempts, trade_ids.join(", "));
+            } else {
+                warn!("Batch {} still has failed transfers (attempt {})", pending.batch_id, pending.attempts);
+                self.batch_queue.lock().await.push_back(pending);
+            }
+        }
+        settled
+    }
+
+    /// Execute one netted transfer: outcome tokens if it names a position, collateral otherwise
+    async fn execute_net_transfer(&self, transfer: &NetTransfer, memo: &str) -> Result<String> {
+        match &transfer.position_id {
+            Some(position_id) => self.settlement_client
+                .transfer_position_from(&transfer.from_account, &transfer.to_account, position_id, transfer.amount).await,
+            None => {
+                let collateral_token = transfer.collateral_token.clone().unwrap_or_default();
+                self.execute_reserved_usdc_transfer(&collateral_token, &transfer.from_account, &transfer.to_account, transfer.amount, memo).await
+            }
+        }
+    }
+
+    /// Record the fees a batch netted into its transfers once the trade settled. A failed ledger
+    /// write queues the trade for retry_fee_collection with nothing left to transfer
+    async fn record_netted_fees(&self, trade: &Trade) {
+        if trade.maker_fee == 0 && trade.taker_fee == 0 {
+            return;
+        }
+        let recorded = match self.collateral_token_for_market(&trade.market_id).await {
+            Ok(collateral_token) => self.record_trade_fees(trade, &collateral_token, trade.maker_fee, trade.taker_fee).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = recorded {
+            warn!("Fees for trade {} not recorded, queued for retry: {}", trade.trade_id, e);
+            self.fee_queue.lock().await.push_back(PendingTradeFees { trade: trade.clone(), legs: Vec::new(), attempts: 1 });
+        }
+    }
+
+    /// Net transfers for a batch of direct matches: (collateral, outcome tokens). The trades must all
+    /// settle in one collateral token. Trades whose (condition_id, outcome) has no entry in
+    /// `position_ids` are skipped. The buyer pays its fee on top of the price, the seller's fee comes
+    /// out of what it receives, and both are owed to `fee_account`
+    pub fn plan_batch_transfers(
+        trades: &[Trade],
+        position_ids: &HashMap<(String, u8), String>,
+        fee_account: &str,
+    ) -> (Vec<NetTransfer>, Vec<NetTransfer>) {
+        let mut usdc_flows: HashMap<String, i128> = HashMap::new();
+        let mut token_flows: HashMap<(String, String), i128> = HashMap::new();
+
+        for trade in trades {
+            let Some(position_id) = position_ids.get(&(trade.condition_id.clone(), trade.outcome)) else {
+                continue;
+            };
+            let (buyer, seller, buyer_fee, seller_fee) = Self::trade_sides(trade);
+            let usdc_amount = (trade.size * trade.price as u128 / 100000) as i128;
+
+            *usdc_flows.entry(buyer.clone()).or_insert(0) -= usdc_amount + buyer_fee as i128;
+            *usdc_flows.entry(seller.clone()).or_insert(0) += usdc_amount - seller_fee as i128;
+            if buyer_fee + seller_fee > 0 {
+                *usdc_flows.entry(fee_account.to_string()).or_insert(0) += (buyer_fee + seller_fee) as i128;
+            }
+            *token_flows.entry((buyer.clone(), position_id.clone())).or_insert(0) += trade.size as i128;
+            *token_flows.entry((seller.clone(), position_id.clone())).or_insert(0) -= trade.size as i128;
+        }
+
+        let mut by_position: BTreeMap<String, HashMap<String, i128>> = BTreeMap::new();
+        for ((account, position_id), flow) in token_flows {
+            by_position.entry(position_id).or_default().insert(account, flow);
+        }
+
+        let usdc_transfers = Self::minimize_transfers(&usdc_flows, None);
+        let token_transfers = by_position.into_iter()
+            .flat_map(|(position_id, flows)| Self::minimize_transfers(&flows, Some(position_id)))
+            .collect();
+        (usdc_transfers, token_transfers)
+    }
+
+    /// (buyer, seller, buyer's fee, seller's fee) of a trade
+    fn trade_sides(trade: &Trade) -> (&String, &String, u128, u128) {
+        match trade.taker_side {
+            OrderSide::Buy => (&trade.taker_account, &trade.maker_account, trade.taker_fee, trade.maker_fee),
+            OrderSide::Sell => (&trade.maker_account, &trade.taker_account, trade.maker_fee, trade.taker_fee),
+        }
+    }
+
+    /// Pair the largest debtor with the largest creditor until every balance is zero. Each step
+    /// clears at least one account, so n non-zero accounts need at most n - 1 transfers
+    fn minimize_transfers(flows: &HashMap<String, i128>, position_id: Option<String>) -> Vec<NetTransfer> {
+        let largest_first = |a: &(String, u128), b: &(String, u128)| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0));
+        let mut debtors: Vec<(String, u128)> = flows.iter()
+            .filter(|(_, flow)| **flow < 0)
+            .map(|(account, flow)| (account.clone(), flow.unsigned_abs()))
+            .collect();
+        let mut creditors: Vec<(String, u128)> = flows.iter()
+            .filter(|(_, flow)| **flow > 0)
+            .map(|(account, flow)| (account.clone(), *flow as u128))
+            .collect();
+        debtors.sort_by(largest_first);
+        creditors.sort_by(largest_first);
+
+        let mut transfers = Vec::new();
+        let (mut d, mut c) = (0, 0);
+        while d < debtors.len() && c < creditors.len() {
+            let amount = debtors[d].1.min(creditors[c].1);
+            transfers.push(NetTransfer {
+                from_account: debtors[d].0.clone(),
+                to_account: creditors[c].0.clone(),
+                position_id: position_id.clone(),
+                collateral_token: None,
+                amount,
+                tx_hash: None,
+                error: None,
+            });
+            debtors[d].1 -= amount;
+            creditors[c].1 -= amount;
+            if debtors[d].1 == 0 {
+                d += 1;
+            }
+            if creditors[c].1 == 0 {
+                c += 1;
+            }
+        }
+        transfers
+    }
+
     async fn is_complementary_order_match(&self, transfers: &[CollateralTransfer]) -> bool {
         // Check if we have complementary YES/NO orders that sum to $1
         // This indicates new token minting is needed (Polymarket pattern)
@@ -1120,7 +2065,7 @@ impl CollateralManager {
         );
 
         // Get the real condition ID
-        let real_condition_id = match self.near_client.get_market_condition_id(&settlement.market_id).await {
+        let real_condition_id = match self.database.get_market_condition_id(&settlement.market_id).await {
             Ok(Some(id)) => {
                 info!("🔧 Using registered condition ID for market {}: {}", settlement.market_id, id);
                 id
@@ -1146,8 +2091,9 @@ impl CollateralManager {
         info!("💰 Minting {} token pairs using ${} USDC collateral", 
             mint_amount, mint_amount as f64 / 1_000_000.0);
 
+        let collateral_token = self.collateral_token_for_market(&settlement.market_id).await?;
         let split_tx = self.near_client
-            .split_position(&real_condition_id, mint_amount as u128)
+            .split_position(&real_condition_id, mint_amount as u128, &collateral_token)
             .await?;
 
         // Step 2: Collect USDC from users and distribute minted tokens
@@ -1167,7 +2113,7 @@ impl CollateralManager {
             }
 
             // Get the correct position ID for this outcome
-            let position_id: String = self.near_client.get_position_id_for_outcome(&real_condition_id, transfer.outcome).await?;
+            let position_id: String = self.near_client.get_position_id_for_outcome(&real_condition_id, transfer.outcome, &collateral_token).await?;
 
             info!("This is position ID we got: {}", position_id);
 
@@ -1197,35 +2143,32 @@ impl CollateralManager {
             info!("🔄 Executing atomic swap for trade {} (maker: {}, taker: {})",
                 trade.trade_id, trade.maker_account, trade.taker_account);
 
-            // Calculate USDC amounts for each participant
-            let (buyer_account, seller_account, usdc_amount) = match trade.maker_side {
-                crate::types::OrderSide::Buy => {
-                    // Maker is buying: pays USDC, gets tokens
-                    let amount = (trade.size as u128 * trade.price as u128) / 100000;
-                    (trade.maker_account.clone(), trade.taker_account.clone(), amount)
-                }
-                crate::types::OrderSide::Sell => {
-                    // Taker is buying: pays USDC, gets tokens
-                    let amount = (trade.size as u128 * trade.price as u128) / 100000;
-                    (trade.taker_account.clone(), trade.maker_account.clone(), amount)
-                }
-            };
+            // Calculate USDC amounts for each participant: the buyer pays USDC and gets tokens
+            let (buyer_account, seller_account, buyer_fee, seller_fee) = Self::trade_sides(trade);
+            let usdc_amount = (trade.size as u128 * trade.price as u128) / 100000;
 
-            // Execute atomic swap: USDC transfer + Token transfer
+            // Execute atomic swap: USDC transfer + Token transfer. The seller's fee stays out of
+            // what it is paid and the buyer pays both fees to the signer account afterwards
             if usdc_amount > 0 {
                 let usdc_tx = self.execute_atomic_swap(
-                    &buyer_account,
-                    &seller_account,
-                    usdc_amount,
+                    buyer_account,
+                    seller_account,
+                    usdc_amount.saturating_sub(seller_fee),
                     trade.size,
                     trade.outcome,
                     &real_condition_id,
+                    &collateral_token,
                 ).await?;
 
                 transaction_hashes.push(format!("atomic_swap:{}", usdc_tx));
                 info!("✅ Atomic swap completed: {} USDC + {} tokens (tx: {})",
                     usdc_amount as f64 / 1_000_000.0, trade.size, usdc_tx);
             }
+            // The swap stands whether or not the fee transfer goes through; a failed one is retried
+            let fee_legs = vec![FeeLeg { payer: buyer_account.clone(), amount: buyer_fee + seller_fee }];
+            for fee_tx in self.collect_trade_fees(trade, fee_legs).await {
+                transaction_hashes.push(format!("trading_fee:{}", fee_tx));
+            }
 
             // Release balance reservations for completed orders
             self.release_market_balance(&trade.maker_account, &trade.market_id, trade.size).await?;
@@ -1251,7 +2194,7 @@ impl CollateralManager {
         );
 
         // Step 1: Lookup real condition ID and split USDC into outcome token pairs
-        let real_condition_id = match self.near_client.get_market_condition_id(&settlement.market_id).await {
+        let real_condition_id = match self.database.get_market_condition_id(&settlement.market_id).await {
             Ok(Some(id)) => {
                 info!("🔧 Settlement using real condition ID for market {}: {} (was: {})", 
                     settlement.market_id, id, settlement.condition_id);
@@ -1269,8 +2212,9 @@ impl CollateralManager {
             }
         };
 
+        let collateral_token = self.collateral_token_for_market(&settlement.market_id).await?;
         let split_tx = self.near_client
-            .split_position(&real_condition_id, settlement.tokens_to_mint)
+            .split_position(&real_condition_id, settlement.tokens_to_mint, &collateral_token)
             .await?;
 
         // Step 2: Distribute tokens according to net transfers
@@ -1285,7 +2229,7 @@ impl CollateralManager {
                 );
 
                 // Execute token transfer with correct position ID
-                let position_id: String = self.near_client.get_position_id_for_outcome(&real_condition_id, transfer.outcome).await?;
+                let position_id: String = self.near_client.get_position_id_for_outcome(&real_condition_id, transfer.outcome, &collateral_token).await?;
 
                 match self.near_client.transfer_position(
                     &transfer.to_account,
@@ -1322,11 +2266,12 @@ impl CollateralManager {
         );
 
         let mut transaction_hashes = Vec::new();
+        let collateral_token = self.collateral_token_for_market(&settlement.market_id).await?;
 
         // Execute atomic swaps for each transfer
         for transfer in &settlement.net_transfers {
             // Get the real condition ID for position transfers
-            let real_condition_id = match self.near_client.get_market_condition_id(&settlement.market_id).await {
+            let real_condition_id = match self.database.get_market_condition_id(&settlement.market_id).await {
                 Ok(Some(id)) => id,
                 Ok(None) => {
                     warn!("⚠️ No registered condition ID for market {}, using settlement condition_id", settlement.market_id);
@@ -1344,7 +2289,7 @@ impl CollateralManager {
             
             // For now, assume 1:1 ratio (this should be based on actual trade price)
             let usdc_amount = transfer.amount;
-            let position_id: String = self.near_client.get_position_id_for_outcome(&real_condition_id, transfer.outcome).await?;
+            let position_id: String = self.near_client.get_position_id_for_outcome(&real_condition_id, transfer.outcome, &collateral_token).await?;
             
             match transfer.net_usdc_flow.cmp(&0) {
                 std::cmp::Ordering::Greater => {
@@ -1361,6 +2306,7 @@ impl CollateralManager {
 
                     // Execute USDC transfer from buyer's reserved collateral to seller
                     let usdc_tx = self.execute_reserved_usdc_transfer(
+                        &collateral_token,
                         &transfer.to_account,    // buyer account
                         &transfer.from_account,  // seller account
                         usdc_amount,
@@ -1393,7 +2339,8 @@ impl CollateralManager {
                     ).await?;
                     
                     // Transfer USDC from buyer to seller
-                    let usdc_tx = self.near_client.transfer_usdc(
+                    let usdc_tx = self.near_client.transfer_ft(
+                        &collateral_token,
                         &transfer.to_account, // This should be the buyer
                         &transfer.from_account,
                         usdc_amount
@@ -1484,7 +2431,7 @@ impl CollateralManager {
     /// Validate settlement requirements: balances and allowances
     async fn validate_settlement_requirements(
         &self,
-        _usdc_contract: &str,
+        usdc_contract: &str,
         _ctf_contract: &str,
         buyer_account: &str,
         seller_account: &str,
@@ -1496,7 +2443,7 @@ impl CollateralManager {
             usdc_amount as f64 / 1_000_000.0, token_amount);
 
         // 1. Check buyer's USDC balance using existing method
-        match self.near_client.get_usdc_balance(buyer_account).await {
+        match self.near_client.get_ft_balance(usdc_contract, buyer_account).await {
             Ok(balance) => {
                 if balance < usdc_amount {
                     return Err(anyhow::anyhow!(
@@ -0,0 +1,244 @@
+// Service configuration
+// Contract ids and accepted collateral tokens are read once at startup and shared through AppState.
+// Missing contract ids fail the boot rather than falling back to testnet addresses mid-settlement.
+//
+// Env: USDC_CONTRACT_ID (default collateral), CTF_CONTRACT_ID, VERIFIER_CONTRACT_ID, SOLVER_CONTRACT_ID,
+//      PLATFORM_ACCOUNT_ID, SIGNER_ACCOUNT_ID (defaults to PLATFORM_ACCOUNT_ID), NEAR_RPC_URL,
+//      COLLATERAL_TOKENS (comma-separated extra NEP-141 tokens markets may settle in, e.g. wrap.testnet),
+//      MAKER_FEE_BPS / TAKER_FEE_BPS (default trading fees; unset ones take the solver's fee at startup),
+//      YIELD_ENABLED / YIELD_PROTOCOL_ID / YIELD_IDLE_THRESHOLD (idle USDC parked in a lending protocol),
+//      IDEMPOTENCY_KEY_TTL_SECS (how long a retried order submission returns the first response),
+//      API_KEY_REQUIRED (default true; false lets keyless POST /orders and POST /quote share the global bucket),
+//      FEE_SWEEP_INTERVAL_SECS (3600, 0 disables the sweep of collected fees to PLATFORM_ACCOUNT_ID),
+//      RECONCILE_INTERVAL_SECS (300, 0 disables scheduled reconciliation against the solver contract),
+//      CHAIN_MAX_STALENESS_SECS / CHAIN_MAX_ERROR_RATE / CHAIN_ERROR_WINDOW_SECS (when the NEAR RPC counts
+//      as down), CHAIN_DEGRADED_REJECT_ORDERS (default true) / CHAIN_DEGRADED_QUOTE_BAND_BPS (what the
+//      engine does while it is)
+
+use std::str::FromStr;
+use anyhow::{anyhow, Result};
+use near_account_id::AccountId;
+use serde::Serialize;
+
+use crate::api::ApiError;
+use crate::fees::DEFAULT_FEE_SWEEP_INTERVAL_SECS;
+use crate::near_client::health::ChainHealthPolicy;
+use crate::reconciliation::DEFAULT_RECONCILE_INTERVAL_SECS;
+
+/// USDC (6 decimals) left liquid in a wallet before the excess goes to the yield protocol
+pub const DEFAULT_YIELD_IDLE_THRESHOLD: u128 = 10_000_000;
+
+/// Order submissions are remembered under their idempotency key for a day
+pub const DEFAULT_IDEMPOTENCY_KEY_TTL_SECS: u64 = 86_400;
+
+pub const DEFAULT_NEAR_RPC_URL: &str = "https://rpc.testnet.near.org";
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ServiceConfig {
+    pub ctf_contract_id: String,
+    pub verifier_contract_id: String,
+    pub solver_contract_id: String,
+    pub platform_account_id: String,    // receives collateral moved out of reservations
+    pub signer_account_id: String,      // signs every transaction NearClient sends
+    pub near_rpc_url: String,
+    pub collateral_tokens: Vec<String>, // accepted NEP-141 tokens; the first (USDC) is the default
+    pub maker_fee_bps: Option<u16>,     // default maker fee for markets without their own rate
+    pub taker_fee_bps: Option<u16>,     // default taker fee for markets without their own rate
+    pub yield_enabled: bool,            // park idle USDC in `yield_protocol`
+    pub yield_protocol: Option<String>, // lending protocol (e.g. Burrow) holding the deposits
+    pub yield_idle_threshold: u128,     // available USDC a user keeps liquid before the excess is deposited
+    pub idempotency_key_ttl_secs: u64,  // a repeat submission under the same key within this window is replayed
+    pub api_key_required: bool,         // refuse keyless requests on the keyed routes instead of using the global bucket
+    pub fee_sweep_interval_secs: u64,   // period of the platform fee sweep, 0 disables it
+    pub reconcile_interval_secs: u64,   // period of scheduled chain reconciliation, 0 disables it
+    pub chain_health: ChainHealthPolicy, // degraded mode thresholds and behaviour
+}
+
+impl ServiceConfig {
+    pub fn new(
+        usdc_contract_id: &str,
+        ctf_contract_id: &str,
+        verifier_contract_id: &str,
+        solver_contract_id: &str,
+        platform_account_id: &str,
+    ) -> Self {
+        Self {
+            ctf_contract_id: ctf_contract_id.to_string(),
+            verifier_contract_id: verifier_contract_id.to_string(),
+            solver_contract_id: solver_contract_id.to_string(),
+            platform_account_id: platform_account_id.to_string(),
+            signer_account_id: platform_account_id.to_string(),
+            near_rpc_url: DEFAULT_NEAR_RPC_URL.to_string(),
+            collateral_tokens: vec![usdc_contract_id.to_string()],
+            maker_fee_bps: None,
+            taker_fee_bps: None,
+            yield_enabled: false,
+            yield_protocol: None,
+            yield_idle_threshold: DEFAULT_YIELD_IDLE_THRESHOLD,
+            idempotency_key_ttl_secs: DEFAULT_IDEMPOTENCY_KEY_TTL_SECS,
+            api_key_required: true,
+            fee_sweep_interval_secs: DEFAULT_FEE_SWEEP_INTERVAL_SECS,
+            reconcile_interval_secs: DEFAULT_RECONCILE_INTERVAL_SECS,
+            chain_health: ChainHealthPolicy::default(),
+        }
+    }
+
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    /// Build from any variable source; `from_env` passes the process environment
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let account = |key: &str| -> Result<Option<String>> {
+            match var(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+                None => Ok(None),
+                Some(value) => {
+                    AccountId::from_str(&value).map_err(|e| anyhow!("{} is not a valid account id ({}): {}", key, value, e))?;
+                    Ok(Some(value))
+                }
+            }
+        };
+        let required = |key: &str| -> Result<String> {
+            account(key)?.ok_or_else(|| anyhow!("{} environment variable required", key))
+        };
+
+        let mut config = Self::new(
+            &required("USDC_CONTRACT_ID")?,
+            &required("CTF_CONTRACT_ID")?,
+            &required("VERIFIER_CONTRACT_ID")?,
+            &required("SOLVER_CONTRACT_ID")?,
+            &required("PLATFORM_ACCOUNT_ID")?,
+        );
+        if let Some(signer) = account("SIGNER_ACCOUNT_ID")? {
+            config.signer_account_id = signer;
+        }
+        if let Some(url) = var("NEAR_RPC_URL").map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+            config.near_rpc_url = url;
+        }
+        for token in var("COLLATERAL_TOKENS").unwrap_or_default().split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            AccountId::from_str(token).map_err(|e| anyhow!("COLLATERAL_TOKENS entry {} is not a valid account id: {}", token, e))?;
+            if !config.is_supported_collateral(token) {
+                config.collateral_tokens.push(token.to_string());
+            }
+        }
+
+        let fee_bps = |key: &str| -> Result<Option<u16>> {
+            match var(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+                None => Ok(None),
+                Some(value) => match value.parse::<u16>() {
+                    Ok(bps) if bps <= 10_000 => Ok(Some(bps)),
+                    _ => Err(anyhow!("{} must be basis points between 0 and 10000, got {}", key, value)),
+                },
+            }
+        };
+        config.maker_fee_bps = fee_bps("MAKER_FEE_BPS")?;
+        config.taker_fee_bps = fee_bps("TAKER_FEE_BPS")?;
+
+        config.yield_enabled = matches!(
+            var("YIELD_ENABLED").map(|v| v.trim().to_ascii_lowercase()).as_deref(),
+            Some("1" | "true" | "yes")
+        );
+        config.yield_protocol = account("YIELD_PROTOCOL_ID")?;
+        if config.yield_enabled && config.yield_protocol.is_none() {
+            return Err(anyhow!("YIELD_PROTOCOL_ID environment variable required when YIELD_ENABLED is set"));
+        }
+        if let Some(value) = var("YIELD_IDLE_THRESHOLD").map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+            config.yield_idle_threshold = value.parse()
+                .map_err(|_| anyhow!("YIELD_IDLE_THRESHOLD must be an amount in base units, got {}", value))?;
+        }
+        if let Some(value) = var("IDEMPOTENCY_KEY_TTL_SECS").map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+            config.idempotency_key_ttl_secs = value.parse().ok().filter(|secs| *secs > 0)
+                .ok_or_else(|| anyhow!("IDEMPOTENCY_KEY_TTL_SECS must be a positive number of seconds, got {}", value))?;
+        }
+
+        let flag = |key: &str| -> Result<Option<bool>> {
+            match var(key).map(|v| v.trim().to_ascii_lowercase()).filter(|v| !v.is_empty()).as_deref() {
+                None => Ok(None),
+                Some("1" | "true" | "yes") => Ok(Some(true)),
+                Some("0" | "false" | "no") => Ok(Some(false)),
+                Some(value) => Err(anyhow!("{} must be true or false, got {}", key, value)),
+            }
+        };
+        if let Some(required) = flag("API_KEY_REQUIRED")? {
+            config.api_key_required = required;
+        }
+
+        let seconds = |key: &str| -> Result<Option<u64>> {
+            match var(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+                None => Ok(None),
+                Some(value) => value.parse::<u64>().ok().filter(|secs| *secs > 0).map(Some)
+                    .ok_or_else(|| anyhow!("{} must be a positive number of seconds, got {}", key, value)),
+            }
+        };
+        // Background task periods, where 0 switches the task off
+        let interval = |key: &str| -> Result<Option<u64>> {
+            match var(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+                None => Ok(None),
+                Some(value) => value.parse::<u64>().map(Some)
+                    .map_err(|_| anyhow!("{} must be a number of seconds (0 disables), got {}", key, value)),
+            }
+        };
+        if let Some(secs) = interval("FEE_SWEEP_INTERVAL_SECS")? {
+            config.fee_sweep_interval_secs = secs;
+        }
+        if let Some(secs) = interval("RECONCILE_INTERVAL_SECS")? {
+            config.reconcile_interval_secs = secs;
+        }
+
+        let policy = &mut config.chain_health;
+        if let Some(secs) = seconds("CHAIN_MAX_STALENESS_SECS")? {
+            policy.max_staleness_secs = secs;
+        }
+        if let Some(secs) = seconds("CHAIN_ERROR_WINDOW_SECS")? {
+            policy.error_window_secs = secs;
+        }
+        if let Some(value) = var("CHAIN_MAX_ERROR_RATE").map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+            policy.max_error_rate = value.parse().ok().filter(|rate: &f64| (0.0..=1.0).contains(rate))
+                .ok_or_else(|| anyhow!("CHAIN_MAX_ERROR_RATE must be a fraction between 0 and 1, got {}", value))?;
+        }
+        if let Some(reject) = flag("CHAIN_DEGRADED_REJECT_ORDERS")? {
+            policy.reject_orders = reject;
+        }
+        policy.quote_band_bps = fee_bps("CHAIN_DEGRADED_QUOTE_BAND_BPS")?;
+
+        Ok(config)
+    }
+
+    /// Fill whichever default fee rate the environment left unset with the solver contract's fee
+    pub fn apply_solver_fee_bps(&mut self, solver_fee_bps: u16) {
+        self.maker_fee_bps.get_or_insert(solver_fee_bps);
+        self.taker_fee_bps.get_or_insert(solver_fee_bps);
+    }
+
+    /// Yield protocol to use, when the integration is switched on
+    pub fn yield_protocol(&self) -> Option<&str> {
+        self.yield_protocol.as_deref().filter(|_| self.yield_enabled)
+    }
+
+    /// Token markets settle in unless they declare another one
+    pub fn default_collateral_token(&self) -> &str {
+        &self.collateral_tokens[0]
+    }
+
+    pub fn is_supported_collateral(&self, token: &str) -> bool {
+        self.collateral_tokens.iter().any(|t| t == token)
+    }
+
+    /// Token named in a request, or the default when none was given
+    pub fn resolve_collateral_token(&self, token: Option<&str>) -> Result<String, ApiError> {
+        match token.map(str::trim).filter(|t| !t.is_empty()) {
+            None => Ok(self.default_collateral_token().to_string()),
+            Some(token) if self.is_supported_collateral(token) => Ok(token.to_string()),
+            Some(token) => Err(ApiError::InvalidRequest(format!("Unsupported collateral token: {}", token))),
+        }
+    }
+
+    /// Asset name for balance errors and logs: "USDC" for the default token, else the token id
+    pub fn collateral_name(&self, token: &str) -> String {
+        if token == self.default_collateral_token() {
+            "USDC".to_string()
+        } else {
+            token.to_string()
+        }
+    }
+}
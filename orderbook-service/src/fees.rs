@@ -0,0 +1,131 @@
+// Platform fee sweep
+// Settlement collects each trade's maker and taker fee into the service's signer account and
+// records it in the platform fee ledger. Every ServiceConfig::fee_sweep_interval_secs (3600, 0 disables) the
+// unswept entries are summed per collateral token and ft_transfer'd to PLATFORM_ACCOUNT_ID. Entries
+// are marked swept only after their transfer went through, so a failed sweep is retried next pass.
+// When the signer is the platform account there is nothing to move (NEP-141 refuses transfers to
+// self) and the entries are just marked.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::config::ServiceConfig;
+use crate::near_client::NearClient;
+use crate::storage::DatabaseTrait;
+use crate::types::{FeeLedgerEntry, FeeReport, FeeSweep};
+
+pub const DEFAULT_FEE_SWEEP_INTERVAL_SECS: u64 = 3600;
+
+/// Unswept fees grouped by collateral token: (total, trades the total covers)
+pub fn plan_sweeps(entries: &[FeeLedgerEntry]) -> BTreeMap<String, (u128, Vec<Uuid>)> {
+    let mut by_token: BTreeMap<String, (u128, Vec<Uuid>)> = BTreeMap::new();
+    for entry in entries.iter().filter(|entry| entry.sweep_id.is_none()) {
+        let (total, trade_ids) = by_token.entry(entry.collateral_token.clone()).or_default();
+        *total += entry.maker_fee + entry.taker_fee;
+        trade_ids.push(entry.trade_id);
+    }
+    by_token
+}
+
+/// Per-market fee totals and what is still waiting for a sweep (GET /admin/fees)
+pub async fn fee_report(database: &dyn DatabaseTrait, config: &ServiceConfig) -> Result<FeeReport> {
+    let markets = database.get_fee_summary().await?;
+    let mut unswept_by_token: BTreeMap<String, u128> = BTreeMap::new();
+    for market in markets.iter().filter(|market| market.unswept > 0) {
+        *unswept_by_token.entry(market.collateral_token.clone()).or_insert(0) += market.unswept;
+    }
+
+    Ok(FeeReport {
+        default_maker_fee_bps: config.maker_fee_bps.unwrap_or(0),
+        default_taker_fee_bps: config.taker_fee_bps.unwrap_or(0),
+        platform_account_id: config.platform_account_id.clone(),
+        markets,
+        unswept_by_token,
+    })
+}
+
+pub struct FeeSweeper {
+    database: Arc<dyn DatabaseTrait>,
+    near_client: Arc<NearClient>,
+    config: Arc<ServiceConfig>,
+    interval: Duration,
+    shutdown: CancellationToken,
+}
+
+impl FeeSweeper {
+    pub fn new(
+        database: Arc<dyn DatabaseTrait>,
+        near_client: Arc<NearClient>,
+        config: Arc<ServiceConfig>,
+        shutdown: CancellationToken,
+    ) -> Self {
+        Self {
+            database,
+            near_client,
+            interval: Duration::from_secs(config.fee_sweep_interval_secs),
+            config,
+            shutdown,
+        }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        if self.interval.is_zero() {
+            info!("Platform fee sweep disabled (FEE_SWEEP_INTERVAL_SECS=0)");
+            return Ok(());
+        }
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(self.interval) => {}
+                _ = self.shutdown.cancelled() => return Ok(()),
+            }
+
+            if let Err(e) = self.sweep().await {
+                error!("Platform fee sweep failed: {}", e);
+            }
+        }
+    }
+
+    /// Move every unswept fee to the platform account, one transfer per collateral token
+    pub async fn sweep(&self) -> Result<Vec<FeeSweep>> {
+        let unswept = self.database.get_unswept_fees().await?;
+        let fee_account = self.near_client.signer_account_id();
+        let platform_account = &self.config.platform_account_id;
+
+        let mut sweeps = Vec::new();
+        for (collateral_token, (amount, trade_ids)) in plan_sweeps(&unswept) {
+            let tx_hash = if amount == 0 || fee_account == platform_account.as_str() {
+                None
+            } else {
+                match self.near_client.transfer_ft(&collateral_token, fee_account, platform_account, amount).await {
+                    Ok(tx_hash) => Some(tx_hash),
+                    Err(e) => {
+                        error!("❌ Sweeping {} {} of fees to {} failed: {}",
+                            amount, self.config.collateral_name(&collateral_token), platform_account, e);
+                        continue;
+                    }
+                }
+            };
+
+            let sweep_id = Uuid::new_v4();
+            self.database.mark_fees_swept(&trade_ids, sweep_id).await?;
+            info!("🧹 Swept {} {} of fees from {} trades to {}",
+                amount, self.config.collateral_name(&collateral_token), trade_ids.len(), platform_account);
+
+            sweeps.push(FeeSweep {
+                sweep_id,
+                collateral_token,
+                amount,
+                trade_count: trade_ids.len(),
+                tx_hash,
+            });
+        }
+
+        Ok(sweeps)
+    }
+}
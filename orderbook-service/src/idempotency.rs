@@ -0,0 +1,124 @@
+// Idempotent order submission
+// A client retrying POST /orders after a timeout sends the same Idempotency-Key header (or
+// client_order_id); solver orders are keyed by their on-chain order id. Keys are scoped to the
+// submitting account. The first request claims the key with a hash of its payload before anything
+// is validated against balances or reserved, so a racing duplicate finds the claim and never
+// reaches the matching engine. Within IDEMPOTENCY_KEY_TTL_SECS a repeat gets the first response
+// back, one that arrives while the first is still running waits for it, and a different payload
+// under the same key is a 409. A submission that fails gives its key up so the retry runs again.
+
+use std::future::Future;
+use std::time::Duration;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::api::ApiError;
+use crate::storage::DatabaseTrait;
+use crate::types::IdempotencyRecord;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 128;
+
+/// How often, and how many times, a duplicate checks whether the first request has finished
+const IN_FLIGHT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const IN_FLIGHT_POLL_ATTEMPTS: u32 = 200;
+
+/// Response to hand back, and whether it was stored by an earlier request under the same key
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotentResponse {
+    pub response: Value,
+    pub order_id: Uuid,
+    pub replayed: bool,
+}
+
+/// Hex sha256 of a payload's JSON form
+pub fn payload_hash<T: Serialize>(payload: &T) -> Result<String, ApiError> {
+    let bytes = serde_json::to_vec(payload)
+        .map_err(|e| ApiError::Internal(format!("Failed to serialize order payload: {}", e)))?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+/// Check a client-supplied key; None when the client sent none
+pub fn client_key(key: Option<&str>) -> Result<Option<String>, ApiError> {
+    match key.map(str::trim) {
+        None => Ok(None),
+        Some(key) if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN => Err(ApiError::InvalidRequest(format!(
+            "Idempotency key must be 1-{} characters", MAX_IDEMPOTENCY_KEY_LEN
+        ))),
+        Some(key) => Ok(Some(key.to_string())),
+    }
+}
+
+/// Run `submit` once per (account, key). `submit` gets the order id reserved for the key and
+/// returns the response body to store
+pub async fn submit_once<F, Fut>(
+    database: &dyn DatabaseTrait,
+    account_id: &str,
+    key: &str,
+    payload_hash: String,
+    ttl_secs: u64,
+    submit: F,
+) -> Result<IdempotentResponse, ApiError>
+where
+    F: FnOnce(Uuid) -> Fut,
+    Fut: Future<Output = Result<Value, ApiError>>,
+{
+    let record = IdempotencyRecord {
+        idempotency_key: format!("{}:{}", account_id, key),
+        payload_hash,
+        order_id: Uuid::new_v4(),
+        response: None,
+        created_at: Utc::now(),
+    };
+    let expired_before = record.created_at - chrono::Duration::seconds(ttl_secs as i64);
+
+    if let Some(existing) = database.claim_idempotency_key(&record, expired_before).await? {
+        return replay(database, existing, &record.payload_hash).await;
+    }
+
+    match submit(record.order_id).await {
+        Ok(response) => {
+            database.complete_idempotency_key(&record.idempotency_key, &response).await?;
+            Ok(IdempotentResponse { response, order_id: record.order_id, replayed: false })
+        }
+        Err(e) => {
+            if let Err(release_error) = database.release_idempotency_key(&record.idempotency_key).await {
+                warn!("Failed to release idempotency key {}: {}", record.idempotency_key, release_error);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// The stored response for a key someone else claimed, waiting while that request is in flight
+async fn replay(database: &dyn DatabaseTrait, mut existing: IdempotencyRecord, payload_hash: &str) -> Result<IdempotentResponse, ApiError> {
+    if existing.payload_hash != payload_hash {
+        return Err(ApiError::IdempotencyConflict(format!(
+            "Idempotency key was already used for a different order ({})", existing.order_id
+        )));
+    }
+
+    for _ in 0..IN_FLIGHT_POLL_ATTEMPTS {
+        if let Some(response) = existing.response {
+            info!("Replaying order {} for idempotency key {}", existing.order_id, existing.idempotency_key);
+            return Ok(IdempotentResponse { response, order_id: existing.order_id, replayed: true });
+        }
+        tokio::time::sleep(IN_FLIGHT_POLL_INTERVAL).await;
+        match database.get_idempotency_record(&existing.idempotency_key).await? {
+            // A different hash means the first request failed and a new one took the key over
+            Some(record) if record.payload_hash == payload_hash => existing = record,
+            _ => {
+                return Err(ApiError::IdempotencyConflict(
+                    "Earlier request with this idempotency key did not complete; retry".to_string(),
+                ))
+            }
+        }
+    }
+
+    Err(ApiError::IdempotencyConflict("Earlier request with this idempotency key is still being processed".to_string()))
+}
@@ -0,0 +1,87 @@
+// Service event journal
+// Append-only JSON-lines file of lifecycle events (start, shutdown, settlement drain, stop).
+// Writes are buffered and only hit the disk on flush(), which the shutdown path calls last, so
+// a journal whose final entry isn't a clean `stopped` means the previous run died mid-flight.
+
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::Mutex;
+
+/// Journal location when ORDERBOOK_JOURNAL_PATH is not set
+pub const DEFAULT_JOURNAL_PATH: &str = "logs/events.jsonl";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEvent {
+    Started,
+    ShutdownRequested,
+    /// Queued trades the settlement worker settled before stopping
+    SettlementsDrained { trades: usize },
+    /// `clean` is false when the drain failed or ran past the timeout
+    Stopped { clean: bool },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: JournalEvent,
+}
+
+pub struct EventJournal {
+    path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl EventJournal {
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path).await?;
+
+        Ok(Self {
+            path,
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    pub async fn from_env() -> Result<Self> {
+        let path = std::env::var("ORDERBOOK_JOURNAL_PATH").unwrap_or_else(|_| DEFAULT_JOURNAL_PATH.to_string());
+        Self::open(path).await
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Buffer an event; it is written out on the next flush
+    pub async fn record(&self, event: JournalEvent) -> Result<()> {
+        let mut line = serde_json::to_string(&JournalEntry { at: Utc::now(), event })?;
+        line.push('\n');
+        self.writer.lock().await.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    pub async fn flush(&self) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+        writer.flush().await?;
+        writer.get_ref().sync_data().await?;
+        Ok(())
+    }
+
+    /// Every entry in a journal file, oldest first
+    pub async fn read_entries(path: impl AsRef<Path>) -> Result<Vec<JournalEntry>> {
+        let contents = tokio::fs::read_to_string(path).await?;
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+}
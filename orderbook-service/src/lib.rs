@@ -4,19 +4,29 @@ use std::sync::Arc;
 use tokio::sync::broadcast;
 
 pub mod api;
+pub mod config;
 pub mod matching;
 pub mod storage;
 pub mod near_client;
 pub mod types;
 pub mod solver_integration;
 pub mod collateral;
+pub mod market_registry;
+pub mod journal;
+pub mod shutdown;
+pub mod reconciliation;
+pub mod fees;
+pub mod idempotency;
 pub mod ui;
 
 pub use types::*;
+use crate::config::ServiceConfig;
 use crate::matching::MatchingEngine;
 use crate::storage::DatabaseTrait;
 use crate::near_client::NearClient;
 use crate::solver_integration::SolverIntegration;
+use crate::api::rate_limit::RateLimiter;
+use crate::reconciliation::Reconciler;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -25,4 +35,7 @@ pub struct AppState {
     pub near_client: Arc<NearClient>,
     pub solver_integration: Arc<SolverIntegration>,
     pub ws_broadcaster: broadcast::Sender<WebSocketMessage>,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub config: Arc<ServiceConfig>,
+    pub reconciler: Arc<Reconciler>,
 }
\ No newline at end of file
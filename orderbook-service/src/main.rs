@@ -2,7 +2,8 @@
 // Runs 24/7 to provide high-performance order matching
 
 use axum::{
-    routing::{get, post, delete},
+    middleware,
+    routing::{get, post, put, delete},
     Router,
 };
 use tower_http::cors::CorsLayer;
@@ -12,14 +13,25 @@ use std::time::Duration;
 
 use orderbook_service::{
     api::handlers::{
-        submit_order, cancel_order, get_orderbook, get_market_price,
-        health_check, websocket_handler, get_collateral_balance, deposit_collateral,
-        register_market_condition
+        submit_order, cancel_order, get_orderbook, get_orderbook_sync_snapshot, get_orderbook_checksum, get_market_price, get_price_candles, get_quote, post_quote,
+        health_check, get_metrics, websocket_handler, get_collateral_balance, get_collateral_status, deposit_collateral,
+        get_yield_balance, harvest_yield,
+        register_market_condition, get_market_condition, get_rate_limits, update_rate_limits, purge_expired_orders,
+        create_api_key, get_api_key_stats, reconcile_now, get_latest_reconciliation, get_platform_fees
     },
+    api::rate_limit::{RateLimiter, RateLimitConfig, limit_order_rate},
+    api::api_keys::require_api_key,
+    api::protocol::{deprecated_alias, REST_PREFIX},
     matching::MatchingEngine,
-    storage,
-    near_client::NearClient,
+    market_registry::{MarketRegistrySync, LEGACY_MARKET_FILE},
+    journal::{EventJournal, JournalEvent},
+    reconciliation::Reconciler,
+    fees::FeeSweeper,
+    shutdown::{drain_timeout_from_env, shutdown_signal, ShutdownCoordinator},
+    storage::{self, DatabaseTrait, retention::TradeRetention},
+    near_client::{NearClient, health::ChainProbe},
     solver_integration::{SolverIntegration, api::{submit_solver_order, get_market_liquidity, get_market_price as get_solver_market_price}},
+    config::ServiceConfig,
     AppState, WebSocketMessage,
     ui,
 };
@@ -93,11 +105,29 @@ async fn run_with_services(
     
     info!("Starting NEAR Prediction Marketplace Orderbook Service");
 
+    // Contract ids and collateral tokens are fixed for the lifetime of the process
+    let mut config = ServiceConfig::from_env()?;
+    info!("Collateral tokens: {} (default {})", config.collateral_tokens.join(", "), config.default_collateral_token());
+
+    let journal = Arc::new(EventJournal::from_env().await?);
+    journal.record(JournalEvent::Started).await?;
+    journal.flush().await?;
+
     // Initialize database connection (automatically chooses PostgreSQL or in-memory)
     let database = storage::create_database().await?;
 
     // Initialize NEAR client
-    let near_client = Arc::new(NearClient::new().await?);
+    let near_client = Arc::new(NearClient::new(&config).await?);
+
+    // Fee rates not set in the environment follow the solver contract's fee
+    if config.maker_fee_bps.is_none() || config.taker_fee_bps.is_none() {
+        match near_client.get_solver_fee_bps().await {
+            Ok(fee_bps) => config.apply_solver_fee_bps(fee_bps),
+            Err(e) => error!("Failed to fetch solver fee, unset trading fees default to 0: {}", e),
+        }
+    }
+    info!("Trading fees: maker {} bps, taker {} bps", config.maker_fee_bps.unwrap_or(0), config.taker_fee_bps.unwrap_or(0));
+    let config = Arc::new(config);
 
     // Create WebSocket broadcast channel for real-time notifications
     let (ws_tx, _ws_rx) = tokio::sync::broadcast::channel::<WebSocketMessage>(1000);
@@ -106,18 +136,37 @@ async fn run_with_services(
     let matching_engine = Arc::new(MatchingEngine::new(
         database.clone(),
         near_client.clone(),
+        config.clone(),
         ws_tx.clone()
     ).await?);
+    let shutdown = ShutdownCoordinator::new(matching_engine.shutdown_token(), journal, drain_timeout_from_env());
 
     // Initialize solver integration
-    let solver_contract_id = std::env::var("SOLVER_CONTRACT_ID")
-        .unwrap_or_else(|_| "solver.ashpk20.testnet".to_string());
     let solver_integration = Arc::new(SolverIntegration::new(
         near_client.clone(),
         matching_engine.clone(),
-        solver_contract_id,
+        config.solver_contract_id.clone(),
     ));
 
+    // Market registry: one-time import of the legacy JSON file, then keep in sync with the verifier
+    let market_registry = Arc::new(MarketRegistrySync::new(database.clone(), near_client.clone(), config.clone()));
+    if let Err(e) = market_registry.import_legacy_file(LEGACY_MARKET_FILE).await {
+        error!("Failed to import legacy market mappings: {}", e);
+    }
+    tokio::spawn(async move {
+        if let Err(e) = market_registry.run().await {
+            error!("Market registry sync error: {}", e);
+        }
+    });
+
+    // Compact settled trades older than the retention window into hourly candles
+    let trade_retention = TradeRetention::new(database.clone());
+    tokio::spawn(async move {
+        if let Err(e) = trade_retention.run().await {
+            error!("Trade retention error: {}", e);
+        }
+    });
+
     // Start matching engine background task
     let matching_engine_clone = matching_engine.clone();
     let ws_broadcaster = ws_tx.clone();
@@ -127,32 +176,105 @@ async fn run_with_services(
         }
     });
 
+    // Log drift between in-memory book checksums and the orders in the database
+    let matching_engine_for_audit = matching_engine.clone();
+    tokio::spawn(async move {
+        if let Err(e) = matching_engine_for_audit.run_checksum_audit().await {
+            error!("Checksum audit error: {}", e);
+        }
+    });
+
+    // Delete orders past their TTL from the database once a minute
+    let matching_engine_for_purge = matching_engine.clone();
+    tokio::spawn(async move {
+        if let Err(e) = matching_engine_for_purge.run_expiry_purge().await {
+            error!("Expired order purge error: {}", e);
+        }
+    });
+
+    // Halt trading and flag market data as stale while the NEAR RPC is unhealthy
+    let matching_engine_for_watchdog = matching_engine.clone();
+    let chain_probe: Arc<dyn ChainProbe> = near_client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = matching_engine_for_watchdog.run_chain_watchdog(chain_probe).await {
+            error!("Chain watchdog error: {}", e);
+        }
+    });
+
+    // Compare the book with the solver contract's orders and heal the safe divergences
+    let reconciler = Arc::new(Reconciler::new(matching_engine.clone(), near_client.clone(), solver_integration.clone(), &config));
+    let reconciler_for_schedule = reconciler.clone();
+    tokio::spawn(async move {
+        if let Err(e) = reconciler_for_schedule.run().await {
+            error!("Chain reconciliation error: {}", e);
+        }
+    });
+
+    // Move collected trading fees to the platform account
+    let fee_sweeper = FeeSweeper::new(database.clone(), near_client.clone(), config.clone(), matching_engine.shutdown_token());
+    tokio::spawn(async move {
+        if let Err(e) = fee_sweeper.run().await {
+            error!("Platform fee sweep error: {}", e);
+        }
+    });
+
+    let rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig::from_env()));
+    let order_rate_limit = middleware::from_fn_with_state(rate_limiter.clone(), limit_order_rate);
+
     let app_state = AppState {
         matching_engine: matching_engine.clone(),
         database: database.clone(),
         near_client: near_client.clone(),
         solver_integration,
         ws_broadcaster: ws_tx.clone(),
+        rate_limiter: rate_limiter.clone(),
+        config,
+        reconciler,
     };
+    // Market makers and bots on the high-throughput routes authenticate with X-API-Key
+    let api_key_auth = middleware::from_fn_with_state(app_state.clone(), require_api_key);
 
-    // Build API routes
-    let app = Router::new()
-        .route("/health", get(health_check))
+    // Build API routes, served under /v1
+    let api = Router::new()
         // Regular orderbook API
-        .route("/orders", post(submit_order))
+        .route("/orders", post(submit_order).layer(api_key_auth.clone()))
         .route("/orders/:order_id", delete(cancel_order))
         .route("/orderbook/:market_id/:outcome", get(get_orderbook))
+        .route("/orderbook/:market_id/:outcome/snapshot", get(get_orderbook_sync_snapshot))
+        .route("/orderbook/:market_id/:outcome/checksum", get(get_orderbook_checksum))
         .route("/price/:market_id/:outcome", get(get_market_price))
+        .route("/price/:market_id/:outcome/candles", get(get_price_candles))
+        .route("/quote", get(get_quote))
+        .route("/quote", post(post_quote).layer(api_key_auth))
         .route("/ws", get(websocket_handler))
         // Polymarket-style collateral API
         .route("/collateral/balance", post(get_collateral_balance))
+        .route("/collateral/status", post(get_collateral_status))
         .route("/collateral/deposit", post(deposit_collateral))
+        .route("/collateral/yield/:account_id", get(get_yield_balance))
+        .route("/collateral/yield/:account_id/harvest", post(harvest_yield))
         // Market registration API
         .route("/markets/register", post(register_market_condition))
+        .route("/markets/:market_id/condition", get(get_market_condition))
+        // Admin API
+        .route("/admin/rate-limits", get(get_rate_limits).put(update_rate_limits))
+        .route("/admin/purge-expired", get(purge_expired_orders))
+        .route("/admin/api-keys", post(create_api_key))
+        .route("/admin/api-keys/:key_id/stats", get(get_api_key_stats))
+        .route("/admin/reconcile", post(reconcile_now))
+        .route("/admin/reconciliation/latest", get(get_latest_reconciliation))
+        .route("/admin/fees", get(get_platform_fees))
         // Solver integration API
-        .route("/solver/orders", post(submit_solver_order))
+        .route("/solver/orders", post(submit_solver_order).layer(order_rate_limit))
         .route("/solver/liquidity/:market_id/:outcome", get(get_market_liquidity))
-        .route("/solver/price/:market_id/:outcome", get(get_solver_market_price))
+        .route("/solver/price/:market_id/:outcome", get(get_solver_market_price));
+
+    let app = Router::new()
+        .route("/health", get(health_check))
+        .route("/metrics", get(get_metrics))
+        .nest(REST_PREFIX, api.clone())
+        // Unversioned aliases of the /v1 routes, kept for one release
+        .merge(api.layer(middleware::from_fn(deprecated_alias)))
         .layer(CorsLayer::permissive())
         .with_state(app_state);
 
@@ -164,16 +286,29 @@ async fn run_with_services(
         if let Some((metrics_tx, metrics_rx, log_rx)) = tui_channels {
             // Spawn a metrics updater task that fetches real orderbook data
             let matching_engine_for_metrics = matching_engine.clone();
+            let database_for_metrics = database.clone();
             tokio::spawn(async move {
-                let mut orders_processed: u64 = 0;
-                let mut matches_executed: u64 = 0;
+                let mut cycle: u64 = 0;
                 loop {
+                    cycle += 1;
+
                     // Fetch real orderbook data for the first available market
-                    // First, try to get a list of available markets from the condition file
+                    // Markets with resting orders (cross-shard view) come first, then the registry
                     // Monitor all markets with activity - check multiple markets for orders
-                    let active_markets = get_active_markets();
+                    let mut active_markets: Vec<String> = matching_engine_for_metrics
+                        .get_shard_stats()
+                        .await
+                        .into_iter()
+                        .filter(|stats| stats.resting_orders > 0)
+                        .map(|stats| stats.market_id)
+                        .collect();
+                    for market_id in get_active_markets(database_for_metrics.as_ref()).await {
+                        if !active_markets.contains(&market_id) {
+                            active_markets.push(market_id);
+                        }
+                    }
 
-                    if orders_processed % 20 == 0 { // Log every 20 cycles to avoid spam
+                    if cycle % 20 == 0 { // Log every 20 cycles to avoid spam
                         info!("TUI monitoring {} active markets: {:?}", active_markets.len(),
                             active_markets.iter().take(3).collect::<Vec<_>>());
                     }
@@ -234,7 +369,7 @@ async fn run_with_services(
                         }
                     }
 
-                    if orders_processed % 20 == 0 && !markets_with_orders.is_empty() {
+                    if cycle % 20 == 0 && !markets_with_orders.is_empty() {
                         info!("TUI markets with orders: {:?}", markets_with_orders);
                     }
 
@@ -261,78 +396,62 @@ async fn run_with_services(
                         }
                     };
 
-                    // Update counters by parsing solver logs for actual activity
-                    if orders_processed % 20 == 0 { // Check every 20 cycles to avoid excessive file reads
-                        if let Ok(solver_content) = std::fs::read_to_string("logs/solver.log") {
-                            // Count actual order submissions
-                            let new_orders_count = solver_content.matches("📤 Submitting order to orderbook:").count() as u64;
-                            let new_trades_count = solver_content.matches("✅ Trade settled by orderbook:").count() as u64;
-
-                            // Only update if we have new activity
-                            if new_orders_count > orders_processed {
-                                orders_processed = new_orders_count;
-                            }
-                            if new_trades_count > matches_executed {
-                                matches_executed = new_trades_count;
-                            }
-                        }
-                    }
-
+                    let engine_metrics = matching_engine_for_metrics.get_metrics_snapshot();
+                    let chain = matching_engine_for_metrics.chain_status();
                     let snapshot = ui::MetricsSnapshot {
-                        orders_processed,
-                        matches_executed,
+                        orders_processed: engine_metrics.orders_accepted + engine_metrics.orders_rejected,
+                        matches_executed: engine_metrics.trades_executed,
                         best_bid,
                         best_ask,
-                        p50_latency_ms: 0.5, // Realistic latency
-                        p95_latency_ms: 2.1,
-                        p99_latency_ms: 5.8,
+                        p50_latency_ms: engine_metrics.order_to_match.p50_ms,
+                        p95_latency_ms: engine_metrics.order_to_match.p95_ms,
+                        p99_latency_ms: engine_metrics.order_to_match.p99_ms,
                         orderbook_data,
+                        chain_staleness_secs: Some(chain.staleness_secs).filter(|_| chain.degraded),
                     };
                     let _ = metrics_tx.send(snapshot);
                     tokio::time::sleep(Duration::from_millis(200)).await; // Faster updates to catch brief asks
                 }
             });
 
-            // Spawn the dashboard
+            // Spawn the dashboard; quitting it shuts the service down
+            let tui_shutdown = shutdown.token();
             tokio::spawn(async move {
                 if let Err(e) = ui::run_dashboard(log_rx, metrics_rx).await {
                     error!("TUI dashboard exited with error: {}", e);
                 }
+                tui_shutdown.cancel();
             });
         }
     }
 
-    axum::serve(listener, app).await?;
-
+    // Stop taking connections on a signal (or TUI exit); requests already in flight complete
+    let server_shutdown = shutdown.token();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            tokio::select! {
+                _ = shutdown_signal() => {}
+                _ = server_shutdown.cancelled() => {}
+            }
+            info!("🛑 Shutdown requested, no longer accepting orders");
+            server_shutdown.cancel();
+        })
+        .await?;
+
+    let outcome = shutdown.shutdown(matching_engine.drain_settlements()).await?;
+    info!("Orderbook service stopped ({} settlements drained, clean: {})", outcome.settled_trades, outcome.clean);
     Ok(())
 }
 
-fn get_active_markets() -> Vec<String> {
-    let mut markets = Vec::new();
-
-    // Read all markets from market_conditions.json
-    if let Ok(data) = std::fs::read_to_string("market_conditions.json") {
-        if let Ok(market_map) = serde_json::from_str::<std::collections::HashMap<String, String>>(&data) {
-            // Get all timestamped markets (real markets with activity)
-            let mut timestamped_markets: Vec<String> = market_map.keys()
-                .filter(|k| k.contains("_ashpk20.testnet"))
-                .cloned()
-                .collect();
-
-            // Sort by timestamp (newest first)
-            timestamped_markets.sort_by(|a, b| b.cmp(a));
-
-            // Add some numbered markets as well for testing
-            let numbered_markets: Vec<String> = market_map.keys()
-                .filter(|k| k.starts_with("market_") && !k.contains("_ashpk20.testnet"))
-                .take(5)
-                .cloned()
-                .collect();
-
-            markets.extend(timestamped_markets);
-            markets.extend(numbered_markets);
+async fn get_active_markets(database: &dyn DatabaseTrait) -> Vec<String> {
+    // Registry is already ordered newest first
+    let mut markets: Vec<String> = match database.get_registered_markets().await {
+        Ok(records) => records.into_iter().map(|r| r.market_id).collect(),
+        Err(e) => {
+            error!("Failed to load markets from registry: {}", e);
+            Vec::new()
         }
-    }
+    };
 
     // Always include some fallback markets
     if markets.is_empty() {
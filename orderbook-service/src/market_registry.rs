@@ -0,0 +1,241 @@
+// Database-backed market registry (market_id -> CTF condition_id)
+// Populated by POST /markets/register, a verifier sync task, and a one-time legacy JSON import.
+// Registered markets are re-read from the verifier on a slower interval to pick up pauses and fixes.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use chrono::Utc;
+use tracing::{info, warn, error};
+
+use crate::types::{MarketConditionRecord, MarketRegistrationSource};
+use crate::storage::DatabaseTrait;
+use crate::near_client::NearClient;
+use crate::config::ServiceConfig;
+use crate::api::ApiError;
+
+/// Legacy mapping file written by older versions of the service and test scripts
+pub const LEGACY_MARKET_FILE: &str = "market_conditions.json";
+
+/// Verifier markets are binary; used when the CTF condition can't be read
+pub const BINARY_OUTCOME_COUNT: u8 = 2;
+
+/// Build a registry record from the verifier's view of the market, None if the verifier doesn't know it.
+/// The verifier doesn't track collateral, so the caller supplies the token the market settles in
+pub async fn fetch_market_record(
+    near_client: &NearClient,
+    market_id: &str,
+    source: MarketRegistrationSource,
+    collateral_token: &str,
+) -> Result<Option<MarketConditionRecord>> {
+    let market = match near_client.get_verifier_market(market_id).await? {
+        Some(market) if !market.condition_id.is_empty() => market,
+        _ => return Ok(None),
+    };
+
+    let outcome_count = match near_client.get_ctf_condition(&market.condition_id).await {
+        Ok(Some(condition)) => condition.outcome_slot_count,
+        Ok(None) => BINARY_OUTCOME_COUNT,
+        Err(e) => {
+            warn!("⚠️ Failed to read CTF condition {}: {}", market.condition_id, e);
+            BINARY_OUTCOME_COUNT
+        }
+    };
+
+    let now = Utc::now();
+    Ok(Some(MarketConditionRecord {
+        market_id: market.market_id,
+        condition_id: market.condition_id,
+        source,
+        is_active: market.is_active,
+        collateral_token: collateral_token.to_string(),
+        outcome_count,
+        registered_at: now,
+        updated_at: now,
+    }))
+}
+
+/// Look up the condition id for an order's market, rejecting unknown and paused markets
+pub async fn resolve_active_condition(database: &dyn DatabaseTrait, market_id: &str) -> Result<String> {
+    let record = database.get_market_condition(market_id).await?
+        .ok_or_else(|| ApiError::MarketNotFound(market_id.to_string()))?;
+
+    if !record.is_active {
+        return Err(ApiError::MarketPaused(market_id.to_string()).into());
+    }
+
+    Ok(record.condition_id)
+}
+
+pub struct MarketRegistrySync {
+    database: Arc<dyn DatabaseTrait>,
+    near_client: Arc<NearClient>,
+    config: Arc<ServiceConfig>,
+    sync_interval: Duration,
+    refresh_interval: Duration,
+}
+
+impl MarketRegistrySync {
+    pub fn new(database: Arc<dyn DatabaseTrait>, near_client: Arc<NearClient>, config: Arc<ServiceConfig>) -> Self {
+        let sync_interval_secs = std::env::var("MARKET_SYNC_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+        let refresh_interval_secs = std::env::var("MARKET_REFRESH_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+
+        Self {
+            database,
+            near_client,
+            config,
+            sync_interval: Duration::from_secs(sync_interval_secs),
+            refresh_interval: Duration::from_secs(refresh_interval_secs),
+        }
+    }
+
+    /// Import an existing market_conditions.json once, then rename it so it isn't re-imported
+    pub async fn import_legacy_file(&self, path: &str) -> Result<usize> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) => return Ok(0), // Nothing to migrate
+        };
+
+        let legacy_markets: HashMap<String, String> = serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path, e))?;
+
+        let mut imported = 0;
+        for (market_id, condition_id) in legacy_markets {
+            // Never overwrite a mapping that is already in the registry
+            if self.database.get_market_condition_id(&market_id).await?.is_some() {
+                continue;
+            }
+
+            let now = Utc::now();
+            self.database.upsert_market_condition(&MarketConditionRecord {
+                market_id,
+                condition_id,
+                source: MarketRegistrationSource::LegacyImport,
+                is_active: true,
+                collateral_token: self.config.default_collateral_token().to_string(),
+                outcome_count: BINARY_OUTCOME_COUNT,
+                registered_at: now,
+                updated_at: now,
+            }).await?;
+            imported += 1;
+        }
+
+        let archived_path = format!("{}.imported", path);
+        if let Err(e) = std::fs::rename(path, &archived_path) {
+            warn!("⚠️ Imported {} but failed to archive it to {}: {}", path, archived_path, e);
+        }
+
+        info!("📥 Imported {} legacy market mappings from {}", imported, path);
+        Ok(imported)
+    }
+
+    /// Pull markets from the verifier and upsert any new or changed mappings
+    pub async fn sync_once(&self) -> Result<usize> {
+        let markets = self.near_client.get_verifier_markets().await?;
+        let registered: HashMap<String, MarketConditionRecord> = self.database.get_registered_markets().await?
+            .into_iter()
+            .map(|record| (record.market_id.clone(), record))
+            .collect();
+        let mut updated = 0;
+
+        for market in markets {
+            if market.condition_id.is_empty() {
+                continue;
+            }
+
+            // Skip unchanged mappings; a status flip on the verifier (pause/resume) counts as a change
+            if let Some(existing) = registered.get(&market.market_id) {
+                if existing.condition_id == market.condition_id && existing.is_active == market.is_active {
+                    continue;
+                }
+            }
+
+            let (collateral_token, outcome_count) = registered.get(&market.market_id)
+                .map(|existing| (existing.collateral_token.clone(), existing.outcome_count))
+                .unwrap_or_else(|| (self.config.default_collateral_token().to_string(), BINARY_OUTCOME_COUNT));
+
+            let now = Utc::now();
+            self.database.upsert_market_condition(&MarketConditionRecord {
+                market_id: market.market_id.clone(),
+                condition_id: market.condition_id.clone(),
+                source: MarketRegistrationSource::VerifierSync,
+                is_active: market.is_active,
+                collateral_token,
+                outcome_count,
+                registered_at: now,
+                updated_at: now,
+            }).await?;
+
+            info!("🔗 Synced market {} → condition {} from verifier", market.market_id, market.condition_id);
+            updated += 1;
+        }
+
+        Ok(updated)
+    }
+
+    /// Re-read every registered market from the verifier (and its CTF condition) one by one.
+    /// Catches markets that the bulk listing misses, e.g. ones registered by hand. The collateral
+    /// token is kept as registered
+    pub async fn refresh_registered(&self) -> Result<usize> {
+        let mut updated = 0;
+
+        for existing in self.database.get_registered_markets().await? {
+            let fresh = match fetch_market_record(&self.near_client, &existing.market_id, existing.source.clone(), &existing.collateral_token).await {
+                Ok(Some(fresh)) => fresh,
+                Ok(None) => {
+                    warn!("⚠️ Registered market {} not found on verifier", existing.market_id);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("⚠️ Failed to refresh market {}: {}", existing.market_id, e);
+                    continue;
+                }
+            };
+
+            if fresh.condition_id == existing.condition_id
+                && fresh.is_active == existing.is_active
+                && fresh.outcome_count == existing.outcome_count
+            {
+                continue;
+            }
+
+            self.database.upsert_market_condition(&fresh).await?;
+            info!("🔄 Refreshed market {} from verifier", fresh.market_id);
+            updated += 1;
+        }
+
+        Ok(updated)
+    }
+
+    /// Background task: poll the verifier forever
+    pub async fn run(&self) -> Result<()> {
+        info!(
+            "Market registry sync started (every {}s, full refresh every {}s)",
+            self.sync_interval.as_secs(), self.refresh_interval.as_secs()
+        );
+
+        let mut sync_ticker = tokio::time::interval(self.sync_interval);
+        let mut refresh_ticker = tokio::time::interval(self.refresh_interval);
+        loop {
+            tokio::select! {
+                _ = sync_ticker.tick() => match self.sync_once().await {
+                    Ok(0) => {}
+                    Ok(updated) => info!("Market registry sync updated {} markets", updated),
+                    Err(e) => error!("Market registry sync failed: {}", e),
+                },
+                _ = refresh_ticker.tick() => match self.refresh_registered().await {
+                    Ok(0) => {}
+                    Ok(updated) => info!("Market registry refresh updated {} markets", updated),
+                    Err(e) => error!("Market registry refresh failed: {}", e),
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Degraded mode
+// While the chain watchdog finds the NEAR RPC unhealthy the engine can't trust its collateral view
+// or the prices it quotes. Per ChainHealthPolicy it then refuses new orders, pulls resting quotes
+// too far from the mid, and flags snapshots and prices as degraded with the staleness age. The
+// first healthy check ends the mode.
+
+use chrono::{DateTime, Utc};
+use std::sync::RwLock;
+use tracing::debug;
+
+use crate::api::ApiError;
+use crate::near_client::health::{ChainHealthPolicy, ChainProbe, ChainStatus};
+use crate::types::{MarketPrice, OrderbookSnapshot};
+
+/// A check that moved the engine between normal and degraded mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModeChange {
+    Entered(ChainStatus),
+    Recovered(ChainStatus),
+}
+
+/// Latest chain status and the policy applied to it
+pub struct ChainGuard {
+    policy: ChainHealthPolicy,
+    status: RwLock<ChainStatus>,
+}
+
+impl ChainGuard {
+    pub fn new(policy: ChainHealthPolicy) -> Self {
+        Self { policy, status: RwLock::new(ChainStatus::healthy()) }
+    }
+
+    pub fn policy(&self) -> &ChainHealthPolicy {
+        &self.policy
+    }
+
+    pub fn status(&self) -> ChainStatus {
+        *self.status.read().unwrap()
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.status().degraded
+    }
+
+    /// Store the result of a check; Some when it crosses between healthy and degraded
+    pub fn update(&self, status: ChainStatus) -> Option<ModeChange> {
+        let previous = std::mem::replace(&mut *self.status.write().unwrap(), status);
+        match (previous.degraded, status.degraded) {
+            (false, true) => Some(ModeChange::Entered(status)),
+            (true, false) => Some(ModeChange::Recovered(status)),
+            _ => None,
+        }
+    }
+
+    /// CHAIN_UNAVAILABLE while degraded, unless the policy keeps taking orders
+    pub fn check_order_intake(&self) -> Result<(), ApiError> {
+        let status = self.status();
+        if status.degraded && self.policy.reject_orders {
+            return Err(ApiError::ChainUnavailable(format!(
+                "NEAR RPC unhealthy (last successful read {}s ago, {:.0}% of recent reads failed); trading is halted",
+                status.staleness_secs, status.error_rate * 100.0,
+            )));
+        }
+        Ok(())
+    }
+
+    /// Staleness age to stamp on market data, None while healthy
+    fn staleness(&self) -> Option<u64> {
+        let status = self.status();
+        status.degraded.then_some(status.staleness_secs)
+    }
+
+    pub fn annotate_snapshot(&self, mut snapshot: OrderbookSnapshot) -> OrderbookSnapshot {
+        snapshot.staleness_secs = self.staleness();
+        snapshot.degraded = snapshot.staleness_secs.is_some();
+        snapshot
+    }
+
+    pub fn annotate_price(&self, mut price: MarketPrice) -> MarketPrice {
+        price.staleness_secs = self.staleness();
+        price.degraded = price.staleness_secs.is_some();
+        price
+    }
+}
+
+/// Probe the chain through `client`, then judge its health at `now`
+pub async fn check_chain(client: &dyn ChainProbe, guard: &ChainGuard, now: DateTime<Utc>) -> Option<ModeChange> {
+    if let Err(e) = client.probe().await {
+        debug!("Chain health probe failed: {}", e);
+    }
+    guard.update(client.chain_health().status_at(guard.policy(), now))
+}
+
+/// Whether `price` is more than `band_bps` away from `reference`
+pub fn outside_band(price: u64, reference: u64, band_bps: u16) -> bool {
+    price.abs_diff(reference) as u128 * 10_000 > reference as u128 * band_bps as u128
+}
@@ -1,15 +1,32 @@
 // Core orderbook matching logic - similar to Polymarket's approach
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use uuid::Uuid;
 use chrono::Utc;
 use anyhow::Result;
 use tracing::{debug, info};
+use xxhash_rust::xxh3::xxh3_64;
 
 use crate::types::{
-    Order, Trade, OrderSide, OrderStatus, TradeType, SettlementStatus,
-    OrderbookSnapshot, PriceLevel, MarketPrice
+    Order, Trade, OrderSide, OrderStatus, OrderType, TradeType, SettlementStatus,
+    OrderbookSnapshot, OrderbookChecksum, OrderbookDelta, OrderbookSyncSnapshot, PriceLevel, MarketPrice
 };
+use super::degraded::outside_band;
+
+/// Checksum contribution of one resting order: xxh3-64 over the 16 order id bytes followed by
+/// filled_size as 16 little-endian bytes. A book's checksum is the XOR over its resting orders,
+/// so clients can maintain it from OrderUpdate messages without rehashing the whole book
+pub fn order_checksum(order_id: Uuid, filled_size: u128) -> u64 {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(order_id.as_bytes());
+    bytes[16..].copy_from_slice(&filled_size.to_le_bytes());
+    xxh3_64(&bytes)
+}
+
+/// Checksum of a set of (order_id, filled_size) resting orders
+pub fn book_checksum(orders: impl IntoIterator<Item = (Uuid, u128)>) -> u64 {
+    orders.into_iter().fold(0, |acc, (order_id, filled_size)| acc ^ order_checksum(order_id, filled_size))
+}
 
 // Helper struct for atomic trade execution
 #[derive(Clone)]
@@ -19,6 +36,7 @@ struct TradeParticipant {
     side: OrderSide,
 }
 
+#[derive(Clone)]
 pub struct OrderBook {
     // Price -> Size aggregated levels for quick lookup
     bids: BTreeMap<u64, PriceLevel>,    // Buy orders (descending price)
@@ -34,6 +52,17 @@ pub struct OrderBook {
     // Market statistics
     last_trade_price: Option<u64>,
     total_volume: u128,
+
+    // Rolling integrity checksum over resting orders, bumped with every book mutation.
+    // checksum_fills records what each order currently contributes to the checksum
+    checksum: u64,
+    sequence: u64,
+    checksum_fills: BTreeMap<Uuid, u128>,
+
+    // Price levels changed since the last delta was taken, and the sequence it was taken at
+    touched_bids: BTreeSet<u64>,
+    touched_asks: BTreeSet<u64>,
+    delta_sequence: u64,
 }
 
 impl OrderBook {
@@ -46,6 +75,39 @@ impl OrderBook {
             ask_orders: BTreeMap::new(),
             last_trade_price: None,
             total_volume: 0,
+            checksum: 0,
+            sequence: 0,
+            checksum_fills: BTreeMap::new(),
+            touched_bids: BTreeSet::new(),
+            touched_asks: BTreeSet::new(),
+            delta_sequence: 0,
+        }
+    }
+
+    /// Record a price level change for the next delta
+    fn touch_level(&mut self, price: u64, side: &OrderSide) {
+        match side {
+            OrderSide::Buy => self.touched_bids.insert(price),
+            OrderSide::Sell => self.touched_asks.insert(price),
+        };
+    }
+
+    /// Replace an order's checksum contribution; None once it leaves the book
+    fn track_fill(&mut self, order_id: Uuid, filled_size: Option<u128>) {
+        if let Some(previous) = self.checksum_fills.remove(&order_id) {
+            self.checksum ^= order_checksum(order_id, previous);
+        }
+        if let Some(filled_size) = filled_size {
+            self.checksum ^= order_checksum(order_id, filled_size);
+            self.checksum_fills.insert(order_id, filled_size);
+        }
+        self.sequence += 1;
+    }
+
+    pub fn checksum(&self) -> OrderbookChecksum {
+        OrderbookChecksum {
+            checksum: self.checksum,
+            sequence: self.sequence,
         }
     }
 
@@ -55,6 +117,8 @@ impl OrderBook {
         
         // Add to orders map
         self.orders.insert(order.order_id, order.clone());
+        self.track_fill(order.order_id, Some(order.filled_size));
+        self.touch_level(price, &order.side);
 
         // Add to appropriate price level
         match order.side {
@@ -92,6 +156,8 @@ impl OrderBook {
 
     pub async fn remove_order(&mut self, order_id: Uuid) -> Result<()> {
         if let Some(order) = self.orders.remove(&order_id) {
+            self.track_fill(order_id, None);
+            self.touch_level(order.price, &order.side);
             let price = order.price;
             let size = order.remaining_size;
 
@@ -142,6 +208,46 @@ impl OrderBook {
         Ok(())
     }
 
+    /// Match an order against resting liquidity according to its order type
+    pub async fn match_order(&mut self, order: Order) -> Result<Vec<Trade>> {
+        let trades = match order.order_type {
+            OrderType::Market => self.match_market_order(order).await?,
+            OrderType::Limit | OrderType::GTC | OrderType::GTD => {
+                // Standard limit order behavior
+                self.match_limit_order(order).await?
+            }
+            OrderType::FOK => {
+                // Fill-or-Kill: must fill completely or not at all
+                let potential_trades = self.match_limit_order(order.clone()).await?;
+                let total_filled: u128 = potential_trades.iter().map(|t| t.size).sum();
+                if total_filled == order.remaining_size {
+                    potential_trades
+                } else {
+                    // Cancel the order if it can't be filled completely
+                    info!("FOK order {} cannot be filled completely, canceling", order.order_id);
+                    Vec::new()
+                }
+            }
+            OrderType::FAK => {
+                // Fill-and-Kill: execute what's possible, cancel the rest
+                let order_id = order.order_id;
+                let trades = self.match_limit_order(order).await?;
+                if !trades.is_empty() {
+                    info!("FAK order {} partially filled with {} trades", order_id, trades.len());
+                }
+                trades
+            }
+        };
+
+        Ok(trades)
+    }
+
+    /// Dry-run of match_order against a copy of the book; the live book is left untouched
+    pub async fn simulate_order(&self, order: Order) -> Result<Vec<Trade>> {
+        let mut dry_run = self.clone();
+        dry_run.match_order(order).await
+    }
+
     pub async fn match_limit_order(&mut self, incoming_order: Order) -> Result<Vec<Trade>> {
         let mut trades = Vec::new();
         let mut remaining_order = incoming_order.clone();
@@ -337,6 +443,8 @@ impl OrderBook {
                     executed_at: Utc::now(),
                     settlement_status: SettlementStatus::Pending,
                     settlement_tx_hash: None,
+                    maker_fee: 0, // priced by apply_trade_fees before the trade is stored
+                    taker_fee: 0,
                 };
 
                 // Atomically update both orders
@@ -369,12 +477,14 @@ impl OrderBook {
                 maker_order.status = maker_new_status;
 
                 let maker_order_id = maker_order.order_id;
+                let maker_filled_size = maker_order.filled_size;
 
                 // Remove filled order from the book atomically
                 if should_remove_maker {
                     orders.remove(0);
                     self.orders.remove(&maker_order_id);
                 }
+                self.track_fill(maker_order_id, (!should_remove_maker).then_some(maker_filled_size));
 
                 // Update price levels atomically
                 self.update_price_level_after_trade(maker_price, trade_size, &maker_side).await?;
@@ -399,6 +509,7 @@ impl OrderBook {
         trade_size: u128,
         side: &OrderSide,
     ) -> Result<()> {
+        self.touch_level(price, side);
         match side {
             OrderSide::Buy => {
                 if let Some(level) = self.bids.get_mut(&price) {
@@ -495,12 +606,14 @@ impl OrderBook {
         if let Some(order) = self.orders.get_mut(&order_id) {
             let size_diff = order.remaining_size.saturating_sub(new_remaining_size);
             order.remaining_size = new_remaining_size;
+            order.filled_size = order.filled_size.saturating_add(size_diff);
+            let filled_size = order.filled_size;
 
             // Update price level accordingly
             let price = order.price;
-            let side = &order.side;
+            let side = order.side.clone();
 
-            match side {
+            match &side {
                 OrderSide::Buy => {
                     if let Some(level) = self.bids.get_mut(&price) {
                         level.size = level.size.saturating_sub(size_diff);
@@ -513,6 +626,8 @@ impl OrderBook {
                 }
             }
 
+            self.track_fill(order_id, Some(filled_size));
+            self.touch_level(price, &side);
             debug!("Updated order {} size to {}", order_id, new_remaining_size);
         }
         Ok(())
@@ -540,9 +655,12 @@ impl OrderBook {
         }
 
         // Also remove from the main orders map and price levels
-        self.orders.remove(&order_id);
+        if self.orders.remove(&order_id).is_some() {
+            self.track_fill(order_id, None);
+        }
 
         // Update price levels
+        self.touch_level(price, &side);
         match side {
             OrderSide::Buy => {
                 if let Some(level) = self.bids.get_mut(&price) {
@@ -586,6 +704,43 @@ impl OrderBook {
             asks,
             last_trade_price: self.last_trade_price,
             timestamp: Utc::now(),
+            degraded: false,
+            staleness_secs: None,
+        })
+    }
+
+    /// Snapshot stamped with the sequence it reflects, for clients building the book from deltas
+    pub async fn get_sync_snapshot(&self, market_id: &str, outcome: u8) -> Result<OrderbookSyncSnapshot> {
+        Ok(OrderbookSyncSnapshot {
+            snapshot: self.get_snapshot(market_id, outcome).await?,
+            sequence: self.sequence,
+            first_delta_sequence: self.sequence + 1,
+        })
+    }
+
+    /// Levels changed since the previous delta, each in its current state; None when the book
+    /// hasn't changed since
+    pub fn take_delta(&mut self, market_id: &str, outcome: u8) -> Option<OrderbookDelta> {
+        if self.sequence == self.delta_sequence {
+            return None;
+        }
+
+        let level = |levels: &BTreeMap<u64, PriceLevel>, price: u64| {
+            levels.get(&price).cloned().unwrap_or(PriceLevel { price, size: 0, order_count: 0 })
+        };
+        let bids = std::mem::take(&mut self.touched_bids).into_iter().rev().map(|price| level(&self.bids, price)).collect();
+        let asks = std::mem::take(&mut self.touched_asks).into_iter().map(|price| level(&self.asks, price)).collect();
+
+        let prev_sequence = std::mem::replace(&mut self.delta_sequence, self.sequence);
+        Some(OrderbookDelta {
+            market_id: market_id.to_string(),
+            outcome,
+            prev_sequence,
+            sequence: self.sequence,
+            bids,
+            asks,
+            last_trade_price: self.last_trade_price,
+            timestamp: Utc::now(),
         })
     }
 
@@ -606,9 +761,31 @@ impl OrderBook {
             mid,
             last: self.last_trade_price,
             timestamp: Utc::now(),
+            degraded: false,
+            staleness_secs: None,
         })
     }
 
+    /// Number of resting orders in this book
+    pub fn order_count(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Resting orders priced more than `band_bps` from the mid, or from the last trade while one
+    /// side is empty. None without a reference price
+    pub fn orders_outside_band(&self, band_bps: u16) -> Vec<Uuid> {
+        let reference = match (self.bids.keys().next_back(), self.asks.keys().next()) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2),
+            _ => self.last_trade_price,
+        };
+        let Some(reference) = reference else { return Vec::new() };
+
+        self.orders.values()
+            .filter(|order| outside_band(order.price, reference, band_bps))
+            .map(|order| order.order_id)
+            .collect()
+    }
+
     /// Cleanup empty price levels to prevent memory leaks
     pub async fn cleanup_empty_levels(&mut self) -> Result<usize> {
         let mut cleaned_count = 0;
@@ -623,6 +800,7 @@ impl OrderBook {
         for price in empty_bid_prices {
             self.bids.remove(&price);
             self.bid_orders.remove(&price);
+            self.touched_bids.insert(price);
             cleaned_count += 1;
         }
 
@@ -636,9 +814,15 @@ impl OrderBook {
         for price in empty_ask_prices {
             self.asks.remove(&price);
             self.ask_orders.remove(&price);
+            self.touched_asks.insert(price);
             cleaned_count += 1;
         }
 
+        // Dropping a level changes the depth clients see
+        if cleaned_count > 0 {
+            self.sequence += 1;
+        }
+
         // Clean orphaned order vectors
         let orphaned_bid_prices: Vec<u64> = self.bid_orders
             .iter()
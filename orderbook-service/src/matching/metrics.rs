@@ -0,0 +1,205 @@
+// Matching engine metrics
+// Lock-free counters and fixed-bucket latency histograms updated in the submit path. The TUI reads
+// them through MatchingEngine::get_metrics_snapshot(). Every sample is also recorded into Prometheus
+// collectors in the default registry, so GET /metrics exports the engine next to the NEAR RPC
+// metrics.
+//
+// Latencies are measured from the moment submit_order receives an order:
+//   order-to-ack   - until the submission result (trades or rejection) is returned
+//   order-to-match - until matching produced the order's first trades (taker side only)
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use prometheus::{register_histogram, register_int_counter, Histogram, IntCounter};
+use serde::Serialize;
+
+/// Histogram bucket upper bounds in microseconds (50us .. 1s); slower samples land in +Inf
+const LATENCY_BUCKETS_US: [u64; 14] = [
+    50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000, 1_000_000,
+];
+
+pub struct LatencyHistogram {
+    // One slot per bound plus the +Inf overflow slot, not cumulative
+    buckets: [AtomicU64; LATENCY_BUCKETS_US.len() + 1],
+    sum_us: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            sum_us: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub fn record(&self, elapsed: Duration) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        let slot = LATENCY_BUCKETS_US
+            .iter()
+            .position(|bound| micros <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+
+        self.buckets[slot].fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(micros, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> LatencySnapshot {
+        let counts: Vec<u64> = self.buckets.iter().map(|b| b.load(Ordering::Relaxed)).collect();
+        // Derive the count from the buckets so percentiles and totals agree under concurrent writes
+        let count = counts.iter().sum();
+
+        LatencySnapshot {
+            p50_ms: percentile_ms(&counts, count, 0.50),
+            p95_ms: percentile_ms(&counts, count, 0.95),
+            p99_ms: percentile_ms(&counts, count, 0.99),
+            sum_ms: self.sum_us.load(Ordering::Relaxed) as f64 / 1000.0,
+            count,
+            buckets: counts,
+        }
+    }
+}
+
+/// Upper bound of the bucket holding the q-th sample. Samples past the last bound report that bound
+fn percentile_ms(counts: &[u64], count: u64, q: f64) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+
+    let rank = ((count as f64 * q).ceil() as u64).max(1);
+    let mut seen = 0;
+    for (slot, bucket_count) in counts.iter().enumerate() {
+        seen += bucket_count;
+        if seen >= rank {
+            let bound = LATENCY_BUCKETS_US[slot.min(LATENCY_BUCKETS_US.len() - 1)];
+            return bound as f64 / 1000.0;
+        }
+    }
+    LATENCY_BUCKETS_US[LATENCY_BUCKETS_US.len() - 1] as f64 / 1000.0
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencySnapshot {
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub sum_ms: f64,
+    pub count: u64,
+    /// Per-bucket sample counts, aligned with the bucket bounds plus a trailing +Inf slot
+    pub buckets: Vec<u64>,
+}
+
+/// The engine's collectors in the default Prometheus registry, shared by every EngineMetrics
+struct PrometheusMetrics {
+    orders_accepted: IntCounter,
+    orders_rejected: IntCounter,
+    orders_matched: IntCounter,
+    trades_executed: IntCounter,
+    order_to_ack: Histogram,
+    order_to_match: Histogram,
+}
+
+static PROMETHEUS_METRICS: OnceLock<PrometheusMetrics> = OnceLock::new();
+
+fn prometheus_metrics() -> &'static PrometheusMetrics {
+    PROMETHEUS_METRICS.get_or_init(|| {
+        let buckets: Vec<f64> = LATENCY_BUCKETS_US.iter().map(|us| *us as f64 / 1_000_000.0).collect();
+
+        PrometheusMetrics {
+            orders_accepted: register_int_counter!("orderbook_orders_accepted_total", "Orders accepted by the matching engine")
+                .expect("register orderbook_orders_accepted_total"),
+            orders_rejected: register_int_counter!("orderbook_orders_rejected_total", "Orders rejected by the matching engine")
+                .expect("register orderbook_orders_rejected_total"),
+            orders_matched: register_int_counter!(
+                "orderbook_orders_matched_total",
+                "Incoming orders that produced at least one trade"
+            )
+            .expect("register orderbook_orders_matched_total"),
+            trades_executed: register_int_counter!("orderbook_trades_executed_total", "Trades produced by the matching engine")
+                .expect("register orderbook_trades_executed_total"),
+            order_to_ack: register_histogram!(
+                "orderbook_order_to_ack_seconds",
+                "Time from order receipt to submission result",
+                buckets.clone()
+            )
+            .expect("register orderbook_order_to_ack_seconds"),
+            order_to_match: register_histogram!(
+                "orderbook_order_to_match_seconds",
+                "Time from order receipt to its first match",
+                buckets
+            )
+            .expect("register orderbook_order_to_match_seconds"),
+        }
+    })
+}
+
+#[derive(Default)]
+pub struct EngineMetrics {
+    orders_accepted: AtomicU64,
+    orders_rejected: AtomicU64,
+    orders_matched: AtomicU64,
+    trades_executed: AtomicU64,
+    ack_latency: LatencyHistogram,
+    match_latency: LatencyHistogram,
+}
+
+impl EngineMetrics {
+    pub fn new() -> Self {
+        // Register up front so the engine series are scraped (at zero) before the first order
+        prometheus_metrics();
+        Self::default()
+    }
+
+    pub fn record_accepted(&self, elapsed: Duration) {
+        self.orders_accepted.fetch_add(1, Ordering::Relaxed);
+        self.ack_latency.record(elapsed);
+        let exported = prometheus_metrics();
+        exported.orders_accepted.inc();
+        exported.order_to_ack.observe(elapsed.as_secs_f64());
+    }
+
+    pub fn record_rejected(&self, elapsed: Duration) {
+        self.orders_rejected.fetch_add(1, Ordering::Relaxed);
+        self.ack_latency.record(elapsed);
+        let exported = prometheus_metrics();
+        exported.orders_rejected.inc();
+        exported.order_to_ack.observe(elapsed.as_secs_f64());
+    }
+
+    /// An incoming order crossed the book and produced `trades` fills
+    pub fn record_match(&self, trades: usize, elapsed: Duration) {
+        self.orders_matched.fetch_add(1, Ordering::Relaxed);
+        self.trades_executed.fetch_add(trades as u64, Ordering::Relaxed);
+        self.match_latency.record(elapsed);
+        let exported = prometheus_metrics();
+        exported.orders_matched.inc();
+        exported.trades_executed.inc_by(trades as u64);
+        exported.order_to_match.observe(elapsed.as_secs_f64());
+    }
+
+    pub fn snapshot(&self) -> EngineMetricsSnapshot {
+        EngineMetricsSnapshot {
+            orders_accepted: self.orders_accepted.load(Ordering::Relaxed),
+            orders_rejected: self.orders_rejected.load(Ordering::Relaxed),
+            orders_matched: self.orders_matched.load(Ordering::Relaxed),
+            trades_executed: self.trades_executed.load(Ordering::Relaxed),
+            order_to_ack: self.ack_latency.snapshot(),
+            order_to_match: self.match_latency.snapshot(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EngineMetricsSnapshot {
+    pub orders_accepted: u64,
+    pub orders_rejected: u64,
+    pub orders_matched: u64,
+    pub trades_executed: u64,
+    pub order_to_ack: LatencySnapshot,
+    pub order_to_match: LatencySnapshot,
+}
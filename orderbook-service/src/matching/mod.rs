@@ -1,78 +1,146 @@
 // High-performance order matching engine
 
-use std::collections::BTreeMap;
-use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc, broadcast};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{mpsc, broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 use anyhow::Result;
-use tracing::{info, error, debug, warn};
+use tracing::{debug, info, error, warn};
 use chrono::Utc;
 
-use crate::types::{Order, Trade, OrderStatus, OrderType, OrderSide, TradeType, WebSocketMessage};
+use crate::types::{Order, OrderQuote, OrderbookChecksum, OrderbookSyncSnapshot, Trade, OrderStatus, OrderType, OrderSide, TradeType, WebSocketMessage};
 use crate::storage::DatabaseTrait;
 use crate::near_client::NearClient;
+use crate::near_client::health::{ChainProbe, ChainStatus};
 use crate::collateral::CollateralManager;
+use crate::config::ServiceConfig;
+use crate::api::ApiError;
 
+pub mod degraded;
 pub mod engine;
+pub mod metrics;
 pub mod settlement;
+pub mod shards;
 
-use engine::OrderBook;
+use degraded::{check_chain, ChainGuard, ModeChange};
+use engine::{book_checksum, OrderBook};
+use self::metrics::{EngineMetrics, EngineMetricsSnapshot};
 use settlement::SettlementManager;
+use shards::{ShardRouter, ShardStats};
+
+/// How often in-memory book checksums are compared against the orders stored in the database
+const CHECKSUM_AUDIT_INTERVAL_SECS: u64 = 60;
+
+/// How often orders past their TTL are deleted from the database
+const EXPIRY_PURGE_INTERVAL_SECS: u64 = 60;
+
+/// How often the chain watchdog probes the NEAR RPC
+const CHAIN_WATCHDOG_INTERVAL_SECS: u64 = 10;
 
 pub struct MatchingEngine {
-    // Market ID -> Outcome -> OrderBook
-    orderbooks: Arc<RwLock<BTreeMap<String, BTreeMap<u8, OrderBook>>>>,
+    // Market ID -> shard (Outcome -> OrderBook), each market locked independently
+    shards: Arc<ShardRouter>,
     database: Arc<dyn DatabaseTrait>,
     settlement_manager: Arc<SettlementManager>,
     collateral_manager: Arc<CollateralManager>,
     trade_sender: mpsc::UnboundedSender<Trade>,
     ws_broadcaster: broadcast::Sender<WebSocketMessage>,
+    metrics: EngineMetrics,
+    // Cancelled on shutdown: submissions are rejected and the background loops exit
+    shutdown: CancellationToken,
+    // Submissions hold the read side so shutdown can wait for in-flight ones
+    submissions: RwLock<()>,
+    // Cancelled once no submission can produce trades anymore; the settlement worker then drains
+    settlement_shutdown: CancellationToken,
+    settlement_task: Mutex<Option<JoinHandle<Result<usize>>>>,
+    // Degraded mode while the NEAR RPC is unhealthy
+    chain_guard: ChainGuard,
 }
 
 impl MatchingEngine {
     pub async fn new(
         database: Arc<dyn DatabaseTrait>,
         near_client: Arc<NearClient>,
+        config: Arc<ServiceConfig>,
         ws_broadcaster: broadcast::Sender<WebSocketMessage>,
     ) -> Result<Self> {
-        let settlement_manager = Arc::new(
-            SettlementManager::new(database.clone(), near_client.clone()).await?
+        let collateral_manager = Arc::new(
+            CollateralManager::new(database.clone(), near_client.clone(), config.clone())
         );
 
-        let collateral_manager = Arc::new(
-            CollateralManager::new(database.clone(), near_client)
+        Ok(Self::with_collateral_manager(database, near_client, config, ws_broadcaster, collateral_manager))
+    }
+
+    /// Check balances and settle through an existing collateral manager, e.g. one whose settlement
+    /// client a test controls
+    pub fn with_collateral_manager(
+        database: Arc<dyn DatabaseTrait>,
+        near_client: Arc<NearClient>,
+        config: Arc<ServiceConfig>,
+        ws_broadcaster: broadcast::Sender<WebSocketMessage>,
+        collateral_manager: Arc<CollateralManager>,
+    ) -> Self {
+        let settlement_manager = Arc::new(
+            SettlementManager::with_collateral_manager(database.clone(), near_client, collateral_manager.clone())
         );
 
+        let chain_guard = ChainGuard::new(config.chain_health.clone());
+
         let (trade_sender, trade_receiver) = mpsc::unbounded_channel();
 
         // Start settlement worker
         let settlement_manager_clone = settlement_manager.clone();
-        tokio::spawn(async move {
-            if let Err(e) = settlement_manager_clone.run(trade_receiver).await {
+        let settlement_shutdown = CancellationToken::new();
+        let settlement_token = settlement_shutdown.clone();
+        let settlement_task = tokio::spawn(async move {
+            let result = settlement_manager_clone.run(trade_receiver, settlement_token).await;
+            if let Err(e) = &result {
                 error!("Settlement manager crashed: {}", e);
             }
+            result
         });
 
-        Ok(Self {
-            orderbooks: Arc::new(RwLock::new(BTreeMap::new())),
+        Self {
+            shards: Arc::new(ShardRouter::new()),
             database,
             settlement_manager,
             collateral_manager,
             trade_sender,
             ws_broadcaster,
-        })
+            metrics: EngineMetrics::new(),
+            shutdown: CancellationToken::new(),
+            submissions: RwLock::new(()),
+            settlement_shutdown,
+            settlement_task: Mutex::new(Some(settlement_task)),
+            chain_guard,
+        }
     }
 
     pub async fn submit_order(&self, order: Order) -> Result<Vec<Trade>> {
+        let received = Instant::now();
+
+        // Checked under the guard so nothing slips in after drain_settlements stopped waiting
+        let _submission = self.submissions.read().await;
+        if self.shutdown.is_cancelled() {
+            return Err(ApiError::ShuttingDown.into());
+        }
+        self.chain_guard.check_order_intake()?;
+
         // Atomic transaction scope for order submission
-        let transaction_result = self.execute_order_submission_transaction(order).await;
+        let transaction_result = self.execute_order_submission_transaction(order, received).await;
 
         match transaction_result {
             Ok((trades, order_stored)) => {
+                self.metrics.record_accepted(received.elapsed());
+
                 // Broadcast successful order updates
                 if !trades.is_empty() {
                     self.broadcast_order_updates(&trades).await;
                 }
+                self.broadcast_depth_updates(&order_stored.market_id).await;
 
                 info!("Order {} submitted successfully, generated {} trades",
                     order_stored.order_id, trades.len());
@@ -80,6 +148,7 @@ impl MatchingEngine {
                 Ok(trades)
             }
             Err(e) => {
+                self.metrics.record_rejected(received.elapsed());
                 error!("Order submission failed: {}", e);
                 Err(e)
             }
@@ -87,33 +156,23 @@ impl MatchingEngine {
     }
 
     /// Execute order submission as an atomic transaction
-    async fn execute_order_submission_transaction(&self, order: Order) -> Result<(Vec<Trade>, Order)> {
+    async fn execute_order_submission_transaction(&self, order: Order, received: Instant) -> Result<(Vec<Trade>, Order)> {
         info!("Starting atomic order submission transaction for order {}", order.order_id);
 
-        // Step 1: Acquire orderbook write lock FIRST to prevent race conditions
-        let mut orderbooks = self.orderbooks.write().await;
+        // Step 1: Serialize this account's balance checks (USDC is shared across markets),
+        // then lock only this market's shard so other markets keep matching
+        let account_lock = self.shards.account_lock(&order.user_account).await;
+        let _account_guard = account_lock.lock().await;
+        let shard = self.shards.get_or_create(&order.market_id).await;
+        let mut market_orderbooks = shard.write().await;
 
         // Step 2: Check and reserve balance WITHIN the lock scope (Polymarket-style)
-        let can_place_order = match self.collateral_manager.check_and_reserve_balance(&order).await {
-            Ok(result) => {
-                info!("Balance check for order {} completed: {}", order.order_id, result);
-                result
-            }
-            Err(e) => {
-                error!("Balance check failed for order {}: {}", order.order_id, e);
-                return Err(e);
-            }
-        };
-
-        if !can_place_order {
-            error!("Insufficient balance to place order {}", order.order_id);
-            return Err(anyhow::anyhow!("Insufficient balance to place order"));
+        // Errors carry ApiError (InsufficientBalance / ChainUnavailable) up to the HTTP layer
+        if let Err(e) = self.collateral_manager.check_and_reserve_balance(&order).await {
+            error!("Balance check failed for order {}: {}", order.order_id, e);
+            return Err(e);
         }
-
-        // Get or create orderbook for this market+outcome
-        let market_orderbooks = orderbooks
-            .entry(order.market_id.clone())
-            .or_insert_with(BTreeMap::new);
+        info!("Balance check for order {} passed", order.order_id);
 
         // Step 3: Store order in database WITHIN the lock scope
         if let Err(e) = self.database.insert_order(&order).await {
@@ -136,12 +195,12 @@ impl MatchingEngine {
         let mut trades = Vec::new();
 
         if working_order.remaining_size > 0 {
-            trades.extend(self.execute_regular_orderbook_matching(&mut working_order, market_orderbooks).await?);
+            trades.extend(self.execute_regular_orderbook_matching(&mut working_order, &mut market_orderbooks).await?);
         }
 
         // Step 5: Only try complementary matching if order still has remaining size after regular matching
         if working_order.remaining_size > 0 {
-            match self.check_complementary_matches_mutable(&mut working_order, market_orderbooks).await {
+            match self.check_complementary_matches_mutable(&mut working_order, &mut market_orderbooks).await {
                 Ok(mint_trades) => {
                     if !mint_trades.is_empty() {
                         info!("✅ Complementary minting after no regular liquidity: {} trades", mint_trades.len());
@@ -157,6 +216,10 @@ impl MatchingEngine {
             }
         }
 
+        if !trades.is_empty() {
+            self.metrics.record_match(trades.len(), received.elapsed());
+        }
+
         // Step 6: Add remaining order to orderbook if not fully filled
         if working_order.remaining_size > 0 {
             let orderbook = market_orderbooks
@@ -167,8 +230,9 @@ impl MatchingEngine {
             info!("📋 Order {} added to orderbook with {} remaining", working_order.order_id, working_order.remaining_size);
         }
 
-        // Step 4: Store trades atomically and send for settlement
-        for trade in &trades {
+        // Step 4: Price fees, store trades atomically and send for settlement
+        for trade in trades.iter_mut() {
+            self.collateral_manager.apply_trade_fees(trade).await?;
             self.database.insert_trade(trade).await?;
 
             // Send for settlement (non-blocking)
@@ -515,6 +579,8 @@ impl MatchingEngine {
             executed_at: Utc::now(),
             settlement_status: crate::types::SettlementStatus::Pending,
             settlement_tx_hash: None,
+            maker_fee: 0, // priced by apply_trade_fees before the trade is stored
+            taker_fee: 0,
         };
 
         info!("🔥 VALIDATED MINT TRADE CREATED: {} tokens @ complementary prices", validated_trade_size);
@@ -601,6 +667,8 @@ impl MatchingEngine {
             executed_at: Utc::now(),
             settlement_status: crate::types::SettlementStatus::Pending,
             settlement_tx_hash: None,
+            maker_fee: 0, // priced by apply_trade_fees before the trade is stored
+            taker_fee: 0,
         };
 
         info!("🔥 VALIDATED MINT TRADE CREATED (MUTABLE): {} tokens @ complementary prices", validated_trade_size);
@@ -616,45 +684,53 @@ impl MatchingEngine {
 
     pub async fn cancel_order(&self, order_id: Uuid, user_account: &str) -> Result<bool> {
         // Execute cancellation as atomic transaction to prevent race conditions
-        self.execute_order_cancellation_transaction(order_id, user_account).await
+        self.execute_order_cancellation_transaction(order_id, Some(user_account)).await
     }
 
-    /// Execute order cancellation as an atomic transaction
-    async fn execute_order_cancellation_transaction(&self, order_id: Uuid, user_account: &str) -> Result<bool> {
-        // Step 1: Acquire orderbook write lock FIRST
-        let mut orderbooks = self.orderbooks.write().await;
+    /// Cancel an order the solver contract already cancelled, on behalf of the reconciliation job.
+    /// Same as cancel_order without the ownership check
+    pub async fn cancel_order_cancelled_on_chain(&self, order_id: Uuid) -> Result<bool> {
+        self.execute_order_cancellation_transaction(order_id, None).await
+    }
+
+    /// Execute order cancellation as an atomic transaction. `owner` is checked when given
+    async fn execute_order_cancellation_transaction(&self, order_id: Uuid, owner: Option<&str>) -> Result<bool> {
+        // Step 1: Route to the order's market shard and acquire its write lock
+        let market_id = self.database.get_order(order_id).await?
+            .ok_or(ApiError::OrderNotFound(order_id))?
+            .market_id;
+        let shard = self.shards.get(&market_id).await
+            .ok_or(ApiError::OrderNotFound(order_id))?;
+        let mut market_orderbooks = shard.write().await;
 
         // Step 2: Retrieve and validate order WITHIN lock scope
         let mut order = self.database.get_order(order_id).await?
-            .ok_or_else(|| anyhow::anyhow!("Order not found"))?;
+            .ok_or(ApiError::OrderNotFound(order_id))?;
 
         // Verify ownership
-        if order.user_account != user_account {
-            return Err(anyhow::anyhow!("Not authorized to cancel this order"));
+        if owner.is_some_and(|owner| order.user_account != owner) {
+            return Err(ApiError::Unauthorized("Not authorized to cancel this order".to_string()).into());
         }
 
         // Can only cancel pending or partially filled orders
         if !matches!(order.status, OrderStatus::Pending | OrderStatus::PartiallyFilled) {
-            return Err(anyhow::anyhow!("Cannot cancel order in status: {:?}", order.status));
+            return Err(ApiError::InvalidRequest(format!("Cannot cancel order in status: {:?}", order.status)).into());
         }
 
         // Step 3: Calculate balance to release based on CURRENT remaining size
         let balance_to_release = self.collateral_manager.calculate_required_balance(&order)?;
 
         // Step 4: Remove from orderbook atomically (within the same lock scope)
-        let removal_successful = if let Some(market_orderbooks) = orderbooks.get_mut(&order.market_id) {
-            if let Some(orderbook) = market_orderbooks.get_mut(&order.outcome) {
-                orderbook.remove_order(order_id).await?;
-                true
-            } else {
-                false
-            }
+        let removal_successful = if let Some(orderbook) = market_orderbooks.get_mut(&order.outcome) {
+            orderbook.remove_order(order_id).await?;
+            true
         } else {
             false
         };
 
         if !removal_successful {
-            return Err(anyhow::anyhow!("Order not found in orderbook - may have been filled"));
+            // Not resting in the book any more - most likely filled in the meantime
+            return Err(ApiError::OrderNotFound(order_id).into());
         }
 
         // Step 5: Update order status atomically
@@ -662,11 +738,14 @@ impl MatchingEngine {
         self.database.update_order(&order).await?;
 
         // Step 6: Release balance reservation back to user
-        self.collateral_manager.release_market_balance(user_account, &order.market_id, balance_to_release).await?;
+        self.collateral_manager.release_market_balance(&order.user_account, &order.market_id, balance_to_release).await?;
+        self.collateral_manager.spawn_idle_deposit(&order.user_account);
 
         info!("Order {} cancelled by {}, released {} balance",
-            order_id, user_account, balance_to_release);
+            order_id, owner.unwrap_or("the service"), balance_to_release);
 
+        drop(market_orderbooks);
+        self.broadcast_depth_updates(&order.market_id).await;
         Ok(true)
     }
 
@@ -707,6 +786,254 @@ impl MatchingEngine {
         }
     }
 
+    /// Depth of every outcome book in a market, stamped with its sequence and checksum, preceded
+    /// by the price levels that changed since the last broadcast. Complementary matching can touch
+    /// both outcomes, so all of them are sent. The shard stays write-locked until both are out so
+    /// deltas reach subscribers in sequence order
+    async fn broadcast_depth_updates(&self, market_id: &str) {
+        let Some(shard) = self.shards.get(market_id).await else { return };
+        let mut market_orderbooks = shard.write().await;
+
+        for (outcome, orderbook) in market_orderbooks.iter_mut() {
+            if let Some(delta) = orderbook.take_delta(market_id, *outcome) {
+                if self.ws_broadcaster.send(WebSocketMessage::OrderbookDelta(delta)).is_err() {
+                    debug!("No subscribers for depth delta of {} outcome {}", market_id, outcome);
+                }
+            }
+
+            let snapshot = match orderbook.get_snapshot(market_id, *outcome).await {
+                Ok(snapshot) => self.chain_guard.annotate_snapshot(snapshot),
+                Err(e) => {
+                    error!("Failed to snapshot {} outcome {} for depth update: {}", market_id, outcome, e);
+                    continue;
+                }
+            };
+            let OrderbookChecksum { checksum, sequence } = orderbook.checksum();
+
+            // Only fails when no client is subscribed
+            if self.ws_broadcaster.send(WebSocketMessage::OrderbookUpdate {
+                market_id: market_id.to_string(),
+                outcome: *outcome,
+                snapshot,
+                sequence,
+                checksum,
+            }).is_err() {
+                debug!("No subscribers for depth update of {} outcome {}", market_id, outcome);
+            }
+        }
+    }
+
+    /// In-memory book for one outcome at its current sequence, None if the market has no book for it.
+    /// Unlike get_orderbook_snapshot this never reads the database, whose rows carry no sequence
+    pub async fn get_orderbook_sync_snapshot(&self, market_id: &str, outcome: u8) -> Result<Option<OrderbookSyncSnapshot>> {
+        let Some(shard) = self.shards.get(market_id).await else { return Ok(None) };
+        let market_orderbooks = shard.read().await;
+        match market_orderbooks.get(&outcome) {
+            Some(orderbook) => {
+                let mut sync = orderbook.get_sync_snapshot(market_id, outcome).await?;
+                sync.snapshot = self.chain_guard.annotate_snapshot(sync.snapshot);
+                Ok(Some(sync))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Checksum of the in-memory book for one outcome, None if the market has no book for it
+    pub async fn get_orderbook_checksum(&self, market_id: &str, outcome: u8) -> Option<OrderbookChecksum> {
+        let shard = self.shards.get(market_id).await?;
+        let market_orderbooks = shard.read().await;
+        market_orderbooks.get(&outcome).map(|orderbook| orderbook.checksum())
+    }
+
+    /// Periodically compare book checksums with the open orders in the database
+    pub async fn run_checksum_audit(&self) -> Result<()> {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(tokio::time::Duration::from_secs(CHECKSUM_AUDIT_INTERVAL_SECS)) => {}
+                _ = self.shutdown.cancelled() => return Ok(()),
+            }
+
+            match self.audit_checksums().await {
+                Ok(0) => debug!("Orderbook checksums match the database"),
+                Ok(drifted) => warn!("⚠️  {} orderbooks drifted from the database", drifted),
+                Err(e) => error!("Orderbook checksum audit failed: {}", e),
+            }
+        }
+    }
+
+    /// Periodically delete orders whose TTL has passed
+    pub async fn run_expiry_purge(&self) -> Result<()> {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(tokio::time::Duration::from_secs(EXPIRY_PURGE_INTERVAL_SECS)) => {}
+                _ = self.shutdown.cancelled() => return Ok(()),
+            }
+
+            match self.purge_expired_orders(false).await {
+                Ok(purged) if purged.is_empty() => debug!("No expired orders to purge"),
+                Ok(purged) => info!("🧹 Purged {} expired orders from the database", purged.len()),
+                Err(e) => error!("Expired order purge failed: {}", e),
+            }
+        }
+    }
+
+    /// Periodically probe the chain through `client` and enter or leave degraded mode
+    pub async fn run_chain_watchdog(&self, client: Arc<dyn ChainProbe>) -> Result<()> {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(tokio::time::Duration::from_secs(CHAIN_WATCHDOG_INTERVAL_SECS)) => {}
+                _ = self.shutdown.cancelled() => return Ok(()),
+            }
+
+            let change = check_chain(client.as_ref(), &self.chain_guard, Utc::now()).await;
+            self.apply_chain_mode_change(change).await;
+        }
+    }
+
+    /// Act on the latest chain check: pull wide quotes on entering degraded mode, republish every
+    /// book on a transition so snapshots carry the new flag, and keep subscribers told the mode
+    async fn apply_chain_mode_change(&self, change: Option<ModeChange>) {
+        match change {
+            Some(ModeChange::Entered(status)) => {
+                warn!("⚠️  NEAR RPC unhealthy (last successful read {}s ago, error rate {:.0}% over {} reads), entering degraded mode",
+                    status.staleness_secs, status.error_rate * 100.0, status.samples);
+                if let Some(band_bps) = self.chain_guard.policy().quote_band_bps {
+                    match self.pull_quotes_outside_band(band_bps).await {
+                        Ok(pulled) => warn!("Pulled {} resting orders more than {} bps from the mid", pulled, band_bps),
+                        Err(e) => error!("Failed to pull wide quotes: {}", e),
+                    }
+                }
+            }
+            Some(ModeChange::Recovered(status)) => {
+                info!("✅ NEAR RPC healthy again (error rate {:.0}%), leaving degraded mode", status.error_rate * 100.0);
+            }
+            None => {}
+        }
+
+        let status = self.chain_guard.status();
+        if (change.is_some() || status.degraded) && self.ws_broadcaster.send(WebSocketMessage::ChainStatus {
+            degraded: status.degraded,
+            staleness_secs: status.staleness_secs,
+        }).is_err() {
+            debug!("No subscribers for chain status");
+        }
+        if change.is_some() {
+            for (market_id, _) in self.shards.shards().await {
+                self.broadcast_depth_updates(&market_id).await;
+            }
+        }
+    }
+
+    /// Cancel every resting order priced more than `band_bps` from its book's mid, releasing its
+    /// balance. Returns how many were pulled
+    pub async fn pull_quotes_outside_band(&self, band_bps: u16) -> Result<usize> {
+        let mut wide = Vec::new();
+        for (_, shard) in self.shards.shards().await {
+            let market_orderbooks = shard.read().await;
+            for orderbook in market_orderbooks.values() {
+                wide.extend(orderbook.orders_outside_band(band_bps));
+            }
+        }
+
+        let mut pulled = 0;
+        for order_id in wide {
+            match self.execute_order_cancellation_transaction(order_id, None).await {
+                Ok(true) => pulled += 1,
+                Ok(false) => {}
+                // Filled or cancelled since the scan
+                Err(e) => debug!("Did not pull order {}: {}", order_id, e),
+            }
+        }
+        Ok(pulled)
+    }
+
+    /// Chain health as of the watchdog's last check
+    pub fn chain_status(&self) -> ChainStatus {
+        self.chain_guard.status()
+    }
+
+    /// Delete orders past their TTL that are neither filled nor cancelled and broadcast an
+    /// OrderExpired event for each. Orders still resting are pulled from their shard and their
+    /// balance released first. With `dry_run` nothing changes and the candidates are returned
+    pub async fn purge_expired_orders(&self, dry_run: bool) -> Result<Vec<Order>> {
+        let now = Utc::now();
+        let candidates = self.database.get_purgeable_orders(now).await?;
+        if dry_run {
+            return Ok(candidates);
+        }
+
+        let mut touched_markets = std::collections::BTreeSet::new();
+        for order in candidates.iter().filter(|o| matches!(o.status, OrderStatus::Pending | OrderStatus::PartiallyFilled)) {
+            let Some(shard) = self.shards.get(&order.market_id).await else { continue };
+            let mut market_orderbooks = shard.write().await;
+            if let Some(orderbook) = market_orderbooks.get_mut(&order.outcome) {
+                orderbook.remove_order(order.order_id).await?;
+            }
+            drop(market_orderbooks);
+
+            let balance_to_release = self.collateral_manager.calculate_required_balance(order)?;
+            if let Err(e) = self.collateral_manager.release_market_balance(&order.user_account, &order.market_id, balance_to_release).await {
+                warn!("Failed to release balance for expired order {}: {}", order.order_id, e);
+            }
+            self.collateral_manager.spawn_idle_deposit(&order.user_account);
+            touched_markets.insert(order.market_id.clone());
+        }
+
+        let purged = self.database.purge_expired_orders(now).await?;
+        for order in &purged {
+            if self.ws_broadcaster.send(WebSocketMessage::OrderExpired {
+                order_id: order.order_id,
+                market_id: order.market_id.clone(),
+                outcome: order.outcome,
+                user_account: order.user_account.clone(),
+                expires_at: order.expires_at,
+            }).is_err() {
+                debug!("No subscribers for expiry of order {}", order.order_id);
+            }
+        }
+
+        for market_id in touched_markets {
+            self.broadcast_depth_updates(&market_id).await;
+        }
+        Ok(purged)
+    }
+
+    /// Log every (market, outcome) book whose checksum differs from the one computed over its
+    /// open orders in the database. Returns the number of drifted books
+    pub async fn audit_checksums(&self) -> Result<usize> {
+        let mut stored: HashMap<(String, u8), Vec<(Uuid, u128)>> = HashMap::new();
+        for order in self.database.get_active_orders().await? {
+            stored.entry((order.market_id, order.outcome))
+                .or_default()
+                .push((order.order_id, order.filled_size));
+        }
+
+        let mut in_memory: HashMap<(String, u8), OrderbookChecksum> = HashMap::new();
+        for (market_id, shard) in self.shards.shards().await {
+            let market_orderbooks = shard.read().await;
+            for (outcome, orderbook) in market_orderbooks.iter() {
+                in_memory.insert((market_id.clone(), *outcome), orderbook.checksum());
+            }
+        }
+
+        let mut books: Vec<&(String, u8)> = stored.keys().chain(in_memory.keys()).collect();
+        books.sort();
+        books.dedup();
+
+        let mut drifted = 0;
+        for book in books {
+            let database_checksum = stored.get(book).map(|orders| book_checksum(orders.iter().copied())).unwrap_or(0);
+            let memory = in_memory.get(book).copied().unwrap_or(OrderbookChecksum { checksum: 0, sequence: 0 });
+            if memory.checksum != database_checksum {
+                drifted += 1;
+                warn!("Checksum drift in {} outcome {} at sequence {}: memory={:016x} database={:016x}",
+                    book.0, book.1, memory.sequence, memory.checksum, database_checksum);
+            }
+        }
+
+        Ok(drifted)
+    }
+
     pub async fn get_orderbook_snapshot(
         &self,
         market_id: &str,
@@ -716,16 +1043,16 @@ impl MatchingEngine {
         if let Ok(Some(snapshot)) = self.database.get_orderbook_snapshot(market_id, outcome).await {
             info!("📊 Retrieved orderbook snapshot from database: {} bids, {} asks",
                 snapshot.bids.len(), snapshot.asks.len());
-            return Ok(Some(snapshot));
+            return Ok(Some(self.chain_guard.annotate_snapshot(snapshot)));
         }
 
-        // Fallback to in-memory orderbooks
-        let orderbooks = self.orderbooks.read().await;
-
-        if let Some(market_orderbooks) = orderbooks.get(market_id) {
+        // Fallback to the in-memory shard for this market
+        if let Some(shard) = self.shards.get(market_id).await {
+            let market_orderbooks = shard.read().await;
             if let Some(orderbook) = market_orderbooks.get(&outcome) {
                 info!("📊 Retrieved orderbook snapshot from memory");
-                return Ok(Some(orderbook.get_snapshot(market_id, outcome).await?));
+                let snapshot = orderbook.get_snapshot(market_id, outcome).await?;
+                return Ok(Some(self.chain_guard.annotate_snapshot(snapshot)));
             }
         }
 
@@ -743,16 +1070,16 @@ impl MatchingEngine {
             info!("💰 Retrieved market price from database: bid={:?}, ask={:?}",
                 price.bid.map(|b| b as f64 / 100.0),
                 price.ask.map(|a| a as f64 / 100.0));
-            return Ok(Some(price));
+            return Ok(Some(self.chain_guard.annotate_price(price)));
         }
 
-        // Fallback to in-memory orderbooks
-        let orderbooks = self.orderbooks.read().await;
-
-        if let Some(market_orderbooks) = orderbooks.get(market_id) {
+        // Fallback to the in-memory shard for this market
+        if let Some(shard) = self.shards.get(market_id).await {
+            let market_orderbooks = shard.read().await;
             if let Some(orderbook) = market_orderbooks.get(&outcome) {
                 info!("💰 Retrieved market price from memory");
-                return Ok(Some(orderbook.get_market_price(market_id, outcome).await?));
+                let price = orderbook.get_market_price(market_id, outcome).await?;
+                return Ok(Some(self.chain_guard.annotate_price(price)));
             }
         }
 
@@ -760,23 +1087,100 @@ impl MatchingEngine {
         Ok(None)
     }
 
+    /// Simulated fill for an order against the current in-memory book, without touching it.
+    /// Runs the same match_order routine as submission; complementary (YES + NO) minting is
+    /// not simulated, so limit orders that would rest and later mint may fill more than quoted
+    pub async fn quote_order(&self, order: Order, fee_bps: u16) -> Result<OrderQuote> {
+        let trades = match self.shards.get(&order.market_id).await {
+            Some(shard) => {
+                let market_orderbooks = shard.read().await;
+                match market_orderbooks.get(&order.outcome) {
+                    Some(orderbook) => orderbook.simulate_order(order.clone()).await?,
+                    None => Vec::new(),
+                }
+            }
+            None => Vec::new(),
+        };
+
+        Ok(OrderQuote::from_trades(&order, &trades, fee_bps))
+    }
+
+    /// Cross-shard view of every market's books (for the TUI and health checks)
+    pub async fn get_shard_stats(&self) -> Vec<ShardStats> {
+        self.shards.stats().await
+    }
+
+    /// Supervisor loop: restores books, keeps one maintenance loop running per market shard,
+    /// and expires orders by routing them to their shard
     pub async fn run(&self) -> Result<()> {
         info!("Matching engine started");
 
         // Restore orderbooks from database on startup
         self.restore_orderbooks().await?;
 
-        // Main event loop - could add periodic tasks here
+        let mut shard_tasks: HashMap<String, JoinHandle<()>> = HashMap::new();
+
         loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-            
-            // Periodic tasks:
-            // 1. Expire old orders
-            // 2. Cleanup empty price levels
-            // 3. Update market prices
-            // 4. Health checks
+            tokio::select! {
+                _ = tokio::time::sleep(tokio::time::Duration::from_secs(1)) => {}
+                _ = self.shutdown.cancelled() => break,
+            }
+
+            self.supervise_shard_loops(&mut shard_tasks).await;
             self.expire_orders().await?;
-            self.cleanup_empty_price_levels().await?;
+        }
+
+        // Shard loops see the same token; let them finish their current pass
+        for (_, handle) in shard_tasks {
+            let _ = handle.await;
+        }
+        info!("Matching engine stopped");
+        Ok(())
+    }
+
+    /// Token cancelled on shutdown; cancelling it stops order intake and the engine loops
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Stop intake, wait for in-flight submissions, then let the settlement worker settle
+    /// everything still queued. Returns the number of trades settled on the way out
+    pub async fn drain_settlements(&self) -> Result<usize> {
+        self.shutdown.cancel();
+        drop(self.submissions.write().await);
+        self.settlement_shutdown.cancel();
+
+        let settlement_task = self.settlement_task.lock().unwrap().take();
+        match settlement_task {
+            Some(handle) => handle.await?,
+            None => Ok(0),
+        }
+    }
+
+    /// Start loops for new shards and restart any that exited
+    async fn supervise_shard_loops(&self, shard_tasks: &mut HashMap<String, JoinHandle<()>>) {
+        for (market_id, shard) in self.shards.shards().await {
+            let needs_start = match shard_tasks.get(&market_id) {
+                Some(handle) => handle.is_finished(),
+                None => true,
+            };
+
+            if !needs_start {
+                continue;
+            }
+
+            if shard_tasks.contains_key(&market_id) {
+                warn!("Restarting shard loop for market {}", market_id);
+            }
+
+            let loop_market_id = market_id.clone();
+            let shutdown = self.shutdown.clone();
+            let handle = tokio::spawn(async move {
+                if let Err(e) = shards::run_shard_loop(loop_market_id.clone(), shard, shutdown).await {
+                    error!("Shard loop for market {} crashed: {}", loop_market_id, e);
+                }
+            });
+            shard_tasks.insert(market_id, handle);
         }
     }
 
@@ -784,13 +1188,11 @@ impl MatchingEngine {
         info!("Restoring orderbooks from database...");
         
         let active_orders = self.database.get_active_orders().await?;
-        let mut orderbooks = self.orderbooks.write().await;
 
         for order in active_orders {
-            let market_orderbooks = orderbooks
-                .entry(order.market_id.clone())
-                .or_insert_with(BTreeMap::new);
-            
+            let shard = self.shards.get_or_create(&order.market_id).await;
+            let mut market_orderbooks = shard.write().await;
+
             let orderbook = market_orderbooks
                 .entry(order.outcome)
                 .or_insert_with(OrderBook::new);
@@ -798,18 +1200,18 @@ impl MatchingEngine {
             orderbook.add_order(order).await?;
         }
 
-        info!("Restored {} markets", orderbooks.len());
+        info!("Restored {} markets", self.shards.len().await);
         Ok(())
     }
 
     async fn expire_orders(&self) -> Result<()> {
         let expired_orders = self.database.get_expired_orders().await?;
         let expired_count = expired_orders.len();
-        let mut orderbooks = self.orderbooks.write().await;
 
         for mut order in expired_orders {
-            // Remove from orderbook
-            if let Some(market_orderbooks) = orderbooks.get_mut(&order.market_id) {
+            // Remove from its market shard
+            if let Some(shard) = self.shards.get(&order.market_id).await {
+                let mut market_orderbooks = shard.write().await;
                 if let Some(orderbook) = market_orderbooks.get_mut(&order.outcome) {
                     orderbook.remove_order(order.order_id).await?;
                 }
@@ -827,31 +1229,6 @@ impl MatchingEngine {
         Ok(())
     }
 
-    async fn cleanup_empty_price_levels(&self) -> Result<()> {
-        let mut orderbooks = self.orderbooks.write().await;
-        let mut cleaned_markets = 0;
-        let mut cleaned_levels = 0;
-
-        for (market_id, market_orderbooks) in orderbooks.iter_mut() {
-            for (outcome, orderbook) in market_orderbooks.iter_mut() {
-                let cleaned = orderbook.cleanup_empty_levels().await?;
-                if cleaned > 0 {
-                    cleaned_levels += cleaned;
-                    debug!("Cleaned {} empty price levels in market {} outcome {}",
-                          cleaned, market_id, outcome);
-                }
-            }
-            cleaned_markets += 1;
-        }
-
-        if cleaned_levels > 0 {
-            info!("Cleanup completed: {} empty price levels across {} markets",
-                  cleaned_levels, cleaned_markets);
-        }
-
-        Ok(())
-    }
-
     /// Execute regular orderbook matching (try existing liquidity first)
     async fn execute_regular_orderbook_matching(
         &self,
@@ -863,38 +1240,7 @@ impl MatchingEngine {
             .or_insert_with(OrderBook::new);
 
         // Attempt to match against existing orderbook liquidity
-        let order_type = working_order.order_type.clone();
-        let original_order = working_order.clone();
-
-        let trades = match order_type {
-            OrderType::Market => {
-                orderbook.match_market_order(original_order).await?
-            }
-            OrderType::Limit | OrderType::GTC | OrderType::GTD => {
-                // Standard limit order behavior
-                orderbook.match_limit_order(original_order).await?
-            }
-            OrderType::FOK => {
-                // Fill-or-Kill: must fill completely or not at all
-                let potential_trades = orderbook.match_limit_order(original_order.clone()).await?;
-                let total_filled: u128 = potential_trades.iter().map(|t| t.size).sum();
-                if total_filled == original_order.remaining_size {
-                    potential_trades
-                } else {
-                    // Cancel the order if it can't be filled completely
-                    info!("FOK order {} cannot be filled completely, canceling", original_order.order_id);
-                    Vec::new()
-                }
-            }
-            OrderType::FAK => {
-                // Fill-and-Kill: execute what's possible, cancel the rest
-                let trades = orderbook.match_limit_order(original_order).await?;
-                if !trades.is_empty() {
-                    info!("FAK order {} partially filled with {} trades", working_order.order_id, trades.len());
-                }
-                trades
-            }
-        };
+        let trades = orderbook.match_order(working_order.clone()).await?;
 
         // Update working order state based on trades
         for trade in &trades {
@@ -914,8 +1260,18 @@ impl MatchingEngine {
         Ok(trades)
     }
 
+    /// Engine counters and latency percentiles (TUI dashboard, GET /metrics)
+    pub fn get_metrics_snapshot(&self) -> EngineMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
     // Get collateral manager for external access
     pub fn get_collateral_manager(&self) -> &Arc<CollateralManager> {
         &self.collateral_manager
     }
+
+    // Get database (market registry lookups) for external access
+    pub fn get_database(&self) -> &Arc<dyn DatabaseTrait> {
+        &self.database
+    }
 }
\ No newline at end of file
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use tokio::sync::mpsc;
 use tokio::time::{interval, Duration};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 use anyhow::Result;
 use tracing::{info, warn, error};
@@ -12,6 +13,7 @@ use crate::types::{Trade, SettlementStatus, SettlementBatch, TradeType};
 use crate::storage::DatabaseTrait;
 use crate::near_client::NearClient;
 use crate::collateral::CollateralManager;
+use crate::config::ServiceConfig;
 
 pub struct SettlementManager {
     database: Arc<dyn DatabaseTrait>,
@@ -24,20 +26,32 @@ impl SettlementManager {
     pub async fn new(
         database: Arc<dyn DatabaseTrait>,
         near_client: Arc<NearClient>,
+        config: Arc<ServiceConfig>,
     ) -> Result<Self> {
         let collateral_manager = Arc::new(
-            CollateralManager::new(database.clone(), near_client.clone())
+            CollateralManager::new(database.clone(), near_client.clone(), config)
         );
         
-        Ok(Self {
+        Ok(Self::with_collateral_manager(database, near_client, collateral_manager))
+    }
+
+    /// Settle through an existing collateral manager, e.g. one whose settlement client a test controls
+    pub fn with_collateral_manager(
+        database: Arc<dyn DatabaseTrait>,
+        near_client: Arc<NearClient>,
+        collateral_manager: Arc<CollateralManager>,
+    ) -> Self {
+        Self {
             database,
             near_client,
             collateral_manager,
             pending_settlements: HashMap::new(),
-        })
+        }
     }
 
-    pub async fn run(&self, mut trade_receiver: mpsc::UnboundedReceiver<Trade>) -> Result<()> {
+    /// Settle incoming trades until `shutdown` is cancelled, then settle whatever is still queued
+    /// and return how many trades that was
+    pub async fn run(&self, mut trade_receiver: mpsc::UnboundedReceiver<Trade>, shutdown: CancellationToken) -> Result<usize> {
         info!("Settlement manager started with ordered processing");
 
         // Create batch settlement timer (every 5 seconds)
@@ -52,6 +66,21 @@ impl SettlementManager {
 
         loop {
             tokio::select! {
+                // Shutdown: no new trades can arrive, settle the backlog in order and stop
+                _ = shutdown.cancelled() => {
+                    trade_receiver.close();
+                    while let Some(trade) = trade_receiver.recv().await {
+                        settlement_sequence += 1;
+                        pending_trades.push_back((trade, settlement_sequence));
+                    }
+
+                    let drained = pending_trades.len();
+                    info!("Settlement manager draining {} queued trades before stopping", drained);
+                    self.settle_trades_batch_ordered(pending_trades.drain(..).collect()).await?;
+                    info!("Settlement manager stopped");
+                    return Ok(drained);
+                }
+
                 // New trade to settle - maintain ordering
                 trade = trade_receiver.recv() => {
                     if let Some(trade) = trade {
@@ -82,7 +111,7 @@ impl SettlementManager {
 
                 // Retry failed settlements with ordering
                 _ = retry_timer.tick() => {
-                    self.retry_failed_settlements_ordered().await?;
+                    self.retry_settlements().await?;
                 }
             }
         }
@@ -126,44 +155,51 @@ impl SettlementManager {
         Ok(())
     }
 
+    /// Settle direct matches as one netted batch, their fees netted into the collateral transfers.
+    /// A trade held up by a failed transfer stays Settling while the collateral manager retries just
+    /// that transfer, since the rest of the batch already moved; only a trade the batch moved
+    /// nothing for is marked Failed and settled again
     async fn settle_direct_matches_ordered(&self, trades_with_sequence: Vec<(Trade, u64)>) -> Result<()> {
-        // Sort by sequence to maintain strict ordering
         let mut sorted_trades = trades_with_sequence;
         sorted_trades.sort_by_key(|(_, sequence)| *sequence);
+        let trades: Vec<Trade> = sorted_trades.into_iter().map(|(trade, _)| trade).collect();
 
-        // Process direct trades in strict order to prevent race conditions
-        for (trade, sequence) in sorted_trades {
-            info!("⚡ Processing direct trade {} (sequence: {})", trade.trade_id, sequence);
+        for trade in &trades {
+            self.update_trade_status(trade, SettlementStatus::Settling).await?;
+        }
 
-            // Execute as atomic settlement transaction
-            match self.execute_direct_settlement_transaction(trade).await {
-                Ok(()) => {
-                    info!("✅ Direct trade settlement completed (sequence: {})", sequence);
-                }
-                Err(e) => {
-                    error!("❌ Direct trade settlement failed (sequence: {}): {}", sequence, e);
+        let batch = match self.collateral_manager.batch_settle_trades(trades.clone()).await {
+            Ok(batch) => batch,
+            Err(e) => {
+                error!("❌ Direct trade batch settlement failed: {}", e);
+                for trade in &trades {
+                    self.update_trade_status(trade, SettlementStatus::Failed).await?;
                 }
+                return Ok(());
             }
-        }
+        };
 
-        Ok(())
-    }
-
-    /// Execute direct settlement as atomic transaction
-    async fn execute_direct_settlement_transaction(&self, trade: Trade) -> Result<()> {
-        // Update status to settling
-        self.update_trade_status(&trade, SettlementStatus::Settling).await?;
-
-        // Call solver contract to execute the trade
-        let tx_hash = self.near_client.execute_direct_trade(&trade).await
-            .map_err(|e| {
-                error!("Failed to settle direct trade {}: {}", trade.trade_id, e);
-                e
-            })?;
+        for (trade, result) in trades.iter().zip(&batch.trade_results) {
+            if result.transfers_pending {
+                warn!("⏳ Direct trade {} waits on failed transfers of batch {}: {}",
+                    trade.trade_id, batch.batch_id, result.error.as_deref().unwrap_or("unknown error"));
+                continue;
+            }
+            if !result.success {
+                error!("❌ Direct trade {} settlement failed: {}", trade.trade_id, result.error.as_deref().unwrap_or("unknown error"));
+                self.update_trade_status(trade, SettlementStatus::Failed).await?;
+                continue;
+            }
 
-        // Update with transaction hash
-        self.update_trade_settlement(&trade, SettlementStatus::Settled, Some(tx_hash.clone())).await?;
-        info!("🎯 Direct trade {} settled: {}", trade.trade_id, tx_hash);
+            let involved = |account: &String| *account == trade.maker_account || *account == trade.taker_account;
+            let tx_hashes: Vec<String> = batch.usdc_transfers.iter().chain(&batch.token_transfers)
+                .filter(|t| involved(&t.from_account) || involved(&t.to_account))
+                .filter_map(|t| t.tx_hash.clone())
+                .collect();
+            let tx_hash = tx_hashes.join(";");
+            self.update_trade_settlement(trade, SettlementStatus::Settled, Some(tx_hash.clone())).await?;
+            info!("🎯 Direct trade {} settled in batch {}: {}", trade.trade_id, batch.batch_id, tx_hash);
+        }
 
         Ok(())
     }
@@ -222,9 +258,9 @@ impl SettlementManager {
                 e
             })?;
 
-        // Update all trades to settled status atomically
-        for trade in trades {
-            self.update_trade_settlement(&trade, SettlementStatus::Settled, Some(tx_hash.clone())).await?;
+        // Update all trades to settled status atomically; the settlement collected their fees
+        for trade in &trades {
+            self.update_trade_settlement(trade, SettlementStatus::Settled, Some(tx_hash.clone())).await?;
         }
 
         info!("🎯 Atomic collateral-based settlement for condition {} completed: {}", condition_id, tx_hash);
@@ -267,6 +303,8 @@ impl SettlementManager {
         trades: Vec<Trade>,
     ) -> Result<()> {
         let total_amount: u128 = trades.iter().map(|t| t.size).sum();
+        let market_id = trades.first().map(|t| t.market_id.clone()).unwrap_or_default();
+        let collateral_token = self.collateral_manager.collateral_token_for_market(&market_id).await?;
 
         // Update all trades to settling status atomically
         for trade in &trades {
@@ -274,21 +312,39 @@ impl SettlementManager {
         }
 
         // Call CTF to merge positions atomically
-        let tx_hash = self.near_client.merge_positions(condition_id, total_amount).await
+        let tx_hash = self.near_client.merge_positions(condition_id, total_amount, &collateral_token).await
             .map_err(|e| {
                 error!("Failed to settle burning batch for condition {}: {}", condition_id, e);
                 e
             })?;
 
-        // Update all trades to settled status atomically
-        for trade in trades {
-            self.update_trade_settlement(&trade, SettlementStatus::Settled, Some(tx_hash.clone())).await?;
+        // Update all trades to settled status atomically. The merged collateral is released to the
+        // signer account, which keeps the fees out of it
+        for trade in &trades {
+            self.update_trade_settlement(trade, SettlementStatus::Settled, Some(tx_hash.clone())).await?;
         }
+        self.collateral_manager.record_settled_fees(&trades).await?;
 
         info!("🎯 Burning batch for condition {} settled: {}", condition_id, tx_hash);
         Ok(())
     }
 
+    /// Retry what earlier rounds left undone: failed batch transfers that hold up direct matches,
+    /// fee transfers that failed after their trade settled, then trades marked Failed. Runs on the
+    /// retry timer
+    pub async fn retry_settlements(&self) -> Result<()> {
+        for (trade, tx_hash) in self.collateral_manager.retry_batch_transfers().await {
+            self.update_trade_settlement(&trade, SettlementStatus::Settled, Some(tx_hash.clone())).await?;
+            info!("🎯 Direct trade {} settled once its batch transfers went through: {}", trade.trade_id, tx_hash);
+        }
+
+        let collected = self.collateral_manager.retry_fee_collection().await;
+        if collected > 0 {
+            info!("🧾 Collected the outstanding fees of {} settled trades", collected);
+        }
+        self.retry_failed_settlements_ordered().await
+    }
+
     async fn retry_failed_settlements_ordered(&self) -> Result<()> {
         let failed_trades = self.database.get_failed_trades().await?;
 
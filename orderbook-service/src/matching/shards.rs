@@ -0,0 +1,134 @@
+// Per-market orderbook shards
+// Each market's books sit behind their own lock so a burst in one market doesn't stall the others.
+// Shards are keyed by market rather than (market, outcome): complementary matching (YES + NO mint)
+// has to see both outcome books of a market in the same critical section.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use anyhow::Result;
+use serde::Serialize;
+use tokio::sync::{Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+use super::engine::OrderBook;
+
+/// Outcome -> OrderBook for a single market
+pub type MarketBooks = BTreeMap<u8, OrderBook>;
+pub type MarketShard = Arc<RwLock<MarketBooks>>;
+
+/// Cross-shard view of one market, used by the TUI and health reporting
+#[derive(Debug, Clone, Serialize)]
+pub struct ShardStats {
+    pub market_id: String,
+    pub outcomes: usize,
+    pub resting_orders: usize,
+}
+
+/// Routes orders to their market shard by market_id
+pub struct ShardRouter {
+    shards: RwLock<HashMap<String, MarketShard>>,
+    // USDC collateral is shared across markets, so balance checks are serialized per account
+    account_locks: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+impl ShardRouter {
+    pub fn new() -> Self {
+        Self {
+            shards: RwLock::new(HashMap::new()),
+            account_locks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the shard for a market if one exists
+    pub async fn get(&self, market_id: &str) -> Option<MarketShard> {
+        self.shards.read().await.get(market_id).cloned()
+    }
+
+    /// Get the shard for a market, creating an empty one on first use
+    pub async fn get_or_create(&self, market_id: &str) -> MarketShard {
+        // Fast path: the routing table is only write-locked when a new market appears
+        if let Some(shard) = self.get(market_id).await {
+            return shard;
+        }
+
+        let mut shards = self.shards.write().await;
+        shards
+            .entry(market_id.to_string())
+            .or_insert_with(|| Arc::new(RwLock::new(BTreeMap::new())))
+            .clone()
+    }
+
+    /// All shards, for the supervisor and cross-shard views
+    pub async fn shards(&self) -> Vec<(String, MarketShard)> {
+        self.shards.read().await
+            .iter()
+            .map(|(market_id, shard)| (market_id.clone(), shard.clone()))
+            .collect()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.shards.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.shards.read().await.is_empty()
+    }
+
+    /// Lock guarding balance check + order insert for one account
+    pub async fn account_lock(&self, account_id: &str) -> Arc<Mutex<()>> {
+        let mut locks = self.account_locks.lock().await;
+        locks
+            .entry(account_id.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone()
+    }
+
+    /// Snapshot of every shard, sorted by resting order count (busiest first)
+    pub async fn stats(&self) -> Vec<ShardStats> {
+        let mut stats = Vec::new();
+
+        for (market_id, shard) in self.shards().await {
+            let books = shard.read().await;
+            stats.push(ShardStats {
+                market_id,
+                outcomes: books.len(),
+                resting_orders: books.values().map(|book| book.order_count()).sum(),
+            });
+        }
+
+        stats.sort_by(|a, b| b.resting_orders.cmp(&a.resting_orders));
+        stats
+    }
+}
+
+impl Default for ShardRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-market maintenance loop, started and restarted by the matching engine supervisor.
+/// Returns once `shutdown` is cancelled, never in the middle of a pass
+pub async fn run_shard_loop(market_id: String, shard: MarketShard, shutdown: CancellationToken) -> Result<()> {
+    debug!("Shard loop started for market {}", market_id);
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(tokio::time::Duration::from_secs(1)) => {}
+            _ = shutdown.cancelled() => {
+                debug!("Shard loop stopped for market {}", market_id);
+                return Ok(());
+            }
+        }
+
+        let mut books = shard.write().await;
+        for (outcome, orderbook) in books.iter_mut() {
+            let cleaned = orderbook.cleanup_empty_levels().await?;
+            if cleaned > 0 {
+                debug!("Cleaned {} empty price levels in market {} outcome {}",
+                      cleaned, market_id, outcome);
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// Chain health watchdog
+// NearClient records the outcome of every read (view calls and block lookups) here. The time since
+// the last successful read and the error rate over a recent window decide whether the chain is
+// usable; ChainHealthPolicy holds the thresholds. Handler errors (a view that panicked, an unknown
+// account) count as successful reads since the RPC answered.
+//
+// The matching engine's watchdog loop probes through ChainProbe, so tests can swap in a client
+// whose calls fail.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+/// Chain reads older than this are unusable for collateral checks
+pub const DEFAULT_MAX_STALENESS_SECS: u64 = 60;
+/// Share of failed reads in the window above which the chain counts as down
+pub const DEFAULT_MAX_ERROR_RATE: f64 = 0.5;
+/// Window the error rate is computed over
+pub const DEFAULT_ERROR_WINDOW_SECS: u64 = 60;
+/// Fewer reads than this in the window never trip the error rate on their own
+pub const MIN_ERROR_SAMPLES: usize = 5;
+
+/// When the service stops trusting the chain and what it does about it
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ChainHealthPolicy {
+    pub max_staleness_secs: u64,        // degraded once the last successful read is older than this
+    pub max_error_rate: f64,            // degraded once this share of recent reads failed (0.0 - 1.0)
+    pub error_window_secs: u64,         // reads older than this drop out of the error rate
+    pub reject_orders: bool,            // refuse new orders with CHAIN_UNAVAILABLE while degraded
+    pub quote_band_bps: Option<u16>,    // pull resting orders further than this from the mid while degraded
+}
+
+impl Default for ChainHealthPolicy {
+    fn default() -> Self {
+        Self {
+            max_staleness_secs: DEFAULT_MAX_STALENESS_SECS,
+            max_error_rate: DEFAULT_MAX_ERROR_RATE,
+            error_window_secs: DEFAULT_ERROR_WINDOW_SECS,
+            reject_orders: true,
+            quote_band_bps: None,
+        }
+    }
+}
+
+/// Health of the chain as seen at one instant
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct ChainStatus {
+    pub degraded: bool,
+    pub staleness_secs: u64,            // since the last successful read (or since startup before any)
+    pub error_rate: f64,                // failed share of the reads in the window
+    pub samples: usize,                 // reads in the window
+}
+
+impl ChainStatus {
+    /// Status before the first check: nothing has failed yet
+    pub fn healthy() -> Self {
+        Self { degraded: false, staleness_secs: 0, error_rate: 0.0, samples: 0 }
+    }
+}
+
+/// Something that reads from the chain; the watchdog calls `probe` when nothing else has lately
+#[async_trait::async_trait]
+pub trait ChainProbe: Send + Sync {
+    /// Cheap read recorded in the client's ChainHealth
+    async fn probe(&self) -> Result<()>;
+    fn chain_health(&self) -> &ChainHealth;
+}
+
+struct HealthWindow {
+    last_success: Option<DateTime<Utc>>,
+    reads: VecDeque<(DateTime<Utc>, bool)>,
+}
+
+/// Outcomes of recent chain reads
+pub struct ChainHealth {
+    started_at: DateTime<Utc>,
+    window: Mutex<HealthWindow>,
+}
+
+impl ChainHealth {
+    pub fn new() -> Self {
+        Self::started_at(Utc::now())
+    }
+
+    pub fn started_at(started_at: DateTime<Utc>) -> Self {
+        Self {
+            started_at,
+            window: Mutex::new(HealthWindow { last_success: None, reads: VecDeque::new() }),
+        }
+    }
+
+    pub fn record(&self, ok: bool) {
+        self.record_at(ok, Utc::now());
+    }
+
+    pub fn record_at(&self, ok: bool, at: DateTime<Utc>) {
+        let mut window = self.window.lock().unwrap();
+        if ok && !window.last_success.is_some_and(|last| last >= at) {
+            window.last_success = Some(at);
+        }
+        window.reads.push_back((at, ok));
+    }
+
+    pub fn last_success(&self) -> Option<DateTime<Utc>> {
+        self.window.lock().unwrap().last_success
+    }
+
+    /// Judge the chain at `now` under `policy`, dropping reads that left the error window
+    pub fn status_at(&self, policy: &ChainHealthPolicy, now: DateTime<Utc>) -> ChainStatus {
+        let mut window = self.window.lock().unwrap();
+        let cutoff = now - Duration::seconds(policy.error_window_secs as i64);
+        while window.reads.front().is_some_and(|(at, _)| *at < cutoff) {
+            window.reads.pop_front();
+        }
+
+        let samples = window.reads.len();
+        let failures = window.reads.iter().filter(|(_, ok)| !ok).count();
+        let error_rate = if samples == 0 { 0.0 } else { failures as f64 / samples as f64 };
+        let since = window.last_success.unwrap_or(self.started_at);
+        let staleness_secs = (now - since).num_seconds().max(0) as u64;
+
+        ChainStatus {
+            degraded: staleness_secs > policy.max_staleness_secs
+                || (samples >= MIN_ERROR_SAMPLES && error_rate > policy.max_error_rate),
+            staleness_secs,
+            error_rate,
+            samples,
+        }
+    }
+}
+
+impl Default for ChainHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,16 +1,21 @@
 // NEAR client using stable lower-level crates to avoid version conflicts
 
+pub mod health;
+pub mod rpc_metrics;
+pub mod settlement;
+
 use anyhow::{anyhow, Result};
 use serde_json::json;
 use tracing::{info, error};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::collections::HashMap;
 use std::sync::RwLock;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use near_account_id::AccountId;
 use near_crypto::{InMemorySigner, SecretKey, Signer};
 use near_jsonrpc_client::{JsonRpcClient, methods};
+use near_jsonrpc_client::errors::{JsonRpcError, JsonRpcServerError};
 use near_primitives::{
     transaction::{Action, FunctionCallAction, Transaction, SignedTransaction},
     types::{BlockReference, Finality},
@@ -18,60 +23,67 @@ use near_primitives::{
     hash::hash,
 };
 
+use crate::config::ServiceConfig;
 use crate::types::{Trade, OrderSide};
+use crate::solver_integration::SolverOrder;
+use health::{ChainHealth, ChainProbe};
+use settlement::SettlementClient;
+
+// Solver fee rarely changes; quotes re-read it at most once a minute
+const SOLVER_FEE_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Subset of the verifier's Market view needed by the orderbook
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct VerifierMarket {
+    pub market_id: String,
+    pub condition_id: String,
+    pub is_active: bool,
+}
+
+/// Subset of the CTF's Condition view needed by the market registry
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct CtfCondition {
+    pub outcome_slot_count: u8,
+}
 
 pub struct NearClient {
     rpc_client: JsonRpcClient,
     signer_account: AccountId,
     signer: Signer,
-    // Mock data for testing
-    mock_markets: RwLock<HashMap<String, String>>, // market_id -> condition_id
+    ctf_contract: AccountId,
+    verifier_contract: AccountId,
+    solver_contract: AccountId,
+    usdc_contract: AccountId,
     call_count: AtomicU64,
     total_gas_used: AtomicU64,
     failure_rate: RwLock<f64>,
     // Serialize TX creation/sending to avoid nonce races
     tx_lock: tokio::sync::Mutex<()>,
     nonce_tracker: tokio::sync::Mutex<Option<u64>>,
+    solver_fee_cache: tokio::sync::Mutex<Option<(u16, Instant)>>,
+    // Outcomes of reads, for the matching engine's chain watchdog
+    chain_health: ChainHealth,
 }
 
 impl NearClient {
-    pub async fn new() -> Result<Self> {
-        // Load environment variables
-        let signer_account_str = std::env::var("SIGNER_ACCOUNT_ID")
-            .unwrap_or_else(|_| "ashpk20.testnet".to_string());
-        let signer_account = AccountId::from_str(&signer_account_str)?;
-
+    /// Client for the contracts named in `config`, signing as its signer account with PRIVATE_KEY
+    pub async fn new(config: &ServiceConfig) -> Result<Self> {
         let private_key_str = std::env::var("PRIVATE_KEY")
             .map_err(|_| anyhow::anyhow!("PRIVATE_KEY environment variable required"))?;
         
         let private_key = SecretKey::from_str(&private_key_str)?;
+        Self::with_secret_key(config, private_key)
+    }
+
+    /// Client for the contracts named in `config`, signing as its signer account with `private_key`
+    pub fn with_secret_key(config: &ServiceConfig, private_key: SecretKey) -> Result<Self> {
+        let account = |id: &str| AccountId::from_str(id).map_err(|e| anyhow!("Invalid account id {}: {}", id, e));
+        let signer_account = account(&config.signer_account_id)?;
         let signer = InMemorySigner::from_secret_key(signer_account.clone(), private_key).into();
 
         // Setup RPC client
-        let rpc_url = std::env::var("NEAR_RPC_URL")
-            .unwrap_or_else(|_| "https://rpc.testnet.near.org".to_string());
-        let rpc_client = JsonRpcClient::connect(&rpc_url);
-
-        // Initialize mock markets and load persisted condition IDs
-        let mut mock_markets = HashMap::new();
-        
-        // Load persisted condition IDs from JSON file (test script writes these)
-        if let Ok(contents) = std::fs::read_to_string("market_conditions.json") {
-            if let Ok(persisted_markets) = serde_json::from_str::<HashMap<String, String>>(&contents) {
-                for (market_id, condition_id) in persisted_markets {
-                    mock_markets.insert(market_id.clone(), condition_id.clone());
-                    info!("Loaded persisted condition mapping: {} → {}", market_id, condition_id);
-                }
-            }
-        }
-        
-        // Add default mock markets for backwards compatibility
-        for i in 1..=100 {
-            let market_key = format!("market_{}", i);
-            if !mock_markets.contains_key(&market_key) {
-                mock_markets.insert(market_key, format!("condition_{}", i));
-            }
-        }
+        let rpc_url = &config.near_rpc_url;
+        let rpc_client = JsonRpcClient::connect(rpc_url);
 
         info!("NEAR client initialized using NEAR JSON-RPC");
         info!("Signer account: {}", signer_account);
@@ -81,55 +93,103 @@ impl NearClient {
             rpc_client,
             signer_account,
             signer,
-            mock_markets: RwLock::new(mock_markets),
+            ctf_contract: account(&config.ctf_contract_id)?,
+            verifier_contract: account(&config.verifier_contract_id)?,
+            solver_contract: account(&config.solver_contract_id)?,
+            usdc_contract: account(config.default_collateral_token())?,
             call_count: AtomicU64::new(0),
             total_gas_used: AtomicU64::new(0),
             failure_rate: RwLock::new(0.0),
             tx_lock: tokio::sync::Mutex::new(()),
             nonce_tracker: tokio::sync::Mutex::new(None),
+            solver_fee_cache: tokio::sync::Mutex::new(None),
+            chain_health: ChainHealth::new(),
         })
     }
 
-    pub async fn get_market_condition_id(&self, market_id: &str) -> Result<Option<String>> {
-        // ONLY use registered/stored condition IDs - no generation
-        let markets = self.mock_markets.read()
-            .map_err(|e| anyhow!("Failed to acquire read lock on markets: {}", e))?;
-        let result = markets.get(market_id).cloned();
-        
-        if let Some(condition_id) = &result {
-            info!("Found registered condition ID for market {}: {}", market_id, condition_id);
-        } else {
-            info!("No condition ID registered for market: {}", market_id);
-        }
-        
-        Ok(result)
+    /// Account every transaction is signed with; settlement collects trading fees into it
+    pub fn signer_account_id(&self) -> &str {
+        self.signer_account.as_str()
     }
 
-    pub async fn register_market_condition(&self, market_id: &str, condition_id: &str) -> Result<()> {
-        {
-            let mut markets = self.mock_markets.write()
-                .map_err(|e| anyhow!("Failed to acquire write lock on markets: {}", e))?;
-            markets.insert(market_id.to_string(), condition_id.to_string());
-            
-            // Persist to JSON file so mapping survives service restarts
-            if let Ok(json_data) = serde_json::to_string_pretty(&*markets) {
-                if let Err(e) = std::fs::write("market_conditions.json", json_data) {
-                    error!("Failed to persist market conditions to JSON: {}", e);
-                }
+    /// Fetch all markets from the verifier contract (used by the market registry sync)
+    pub async fn get_verifier_markets(&self) -> Result<Vec<VerifierMarket>> {
+        let verifier_contract = self.verifier_contract.clone();
+
+        self.call_view_function(
+            &verifier_contract,
+            "get_markets",
+            &json!({
+                "category": null,
+                "is_active": null
+            })
+        ).await
+    }
+
+    pub async fn get_verifier_market(&self, market_id: &str) -> Result<Option<VerifierMarket>> {
+        let verifier_contract = self.verifier_contract.clone();
+
+        self.call_view_function(
+            &verifier_contract,
+            "get_market",
+            &json!({ "market_id": market_id })
+        ).await
+    }
+
+    pub async fn get_ctf_condition(&self, condition_id: &str) -> Result<Option<CtfCondition>> {
+        let ctf_contract = self.ctf_contract.clone();
+
+        self.call_view_function(
+            &ctf_contract,
+            "get_condition",
+            &json!({ "condition_id": condition_id })
+        ).await
+    }
+
+    /// Solver fee in basis points, cached for SOLVER_FEE_CACHE_TTL.
+    /// A stale value is served if the refresh fails
+    pub async fn get_solver_fee_bps(&self) -> Result<u16> {
+        let mut cache = self.solver_fee_cache.lock().await;
+        if let Some((fee_bps, fetched_at)) = *cache {
+            if fetched_at.elapsed() < SOLVER_FEE_CACHE_TTL {
+                return Ok(fee_bps);
             }
         }
-        
-        info!("Registered and persisted market {} with condition {}", market_id, condition_id);
-        Ok(())
+
+        let solver_contract = self.solver_contract.clone();
+
+        match self.call_view_function::<u16>(&solver_contract, "get_solver_fee_bps", &json!({})).await {
+            Ok(fee_bps) => {
+                *cache = Some((fee_bps, Instant::now()));
+                Ok(fee_bps)
+            }
+            Err(e) => match *cache {
+                Some((fee_bps, _)) => {
+                    error!("Failed to refresh solver fee, using cached {} bps: {}", fee_bps, e);
+                    Ok(fee_bps)
+                }
+                None => Err(e),
+            },
+        }
     }
 
-    pub async fn execute_direct_trade(&self, trade: &Trade) -> Result<String> {
+    /// One page of the solver's orders (get_active_orders_paginated), cancelled and filled included
+    pub async fn get_solver_orders_page(&self, solver_contract: &str, from_index: u64, limit: u64) -> Result<Vec<SolverOrder>> {
+        let solver_contract = AccountId::from_str(solver_contract)?;
+        self.call_view_function(
+            &solver_contract,
+            "get_active_orders_paginated",
+            &json!({ "from_index": from_index, "limit": limit }),
+        ).await
+    }
+
+    pub async fn execute_direct_trade(&self, trade: &Trade, collateral_token: &str) -> Result<String> {
         info!("Executing direct trade: {} @ {} between {} and {}", 
             trade.size, trade.price, trade.maker_account, trade.taker_account);
 
         match (&trade.maker_side, &trade.taker_side) {
             (OrderSide::Buy, OrderSide::Sell) | (OrderSide::Sell, OrderSide::Buy) => {
-                self.execute_token_transfer(trade).await
+                self.execute_token_transfer(trade, collateral_token).await
             },
             _ => {
                 error!("Invalid trade sides: {:?} vs {:?}", trade.maker_side, trade.taker_side);
@@ -138,7 +198,7 @@ impl NearClient {
         }
     }
 
-    async fn execute_token_transfer(&self, trade: &Trade) -> Result<String> {
+    async fn execute_token_transfer(&self, trade: &Trade, collateral_token: &str) -> Result<String> {
         let (seller, buyer) = if trade.maker_side == OrderSide::Sell {
             (&trade.maker_account, &trade.taker_account)
         } else {
@@ -149,10 +209,8 @@ impl NearClient {
             trade.size, seller, buyer, trade.price);
 
         // Use proper deterministic position ID calculation
-        let position_id = self.get_position_id_for_outcome(&trade.condition_id, trade.outcome).await?;
-        let ctf_contract_str = std::env::var("CTF_CONTRACT_ID")
-            .unwrap_or_else(|_| "ctf.ashpk20.testnet".to_string());
-        let ctf_contract = AccountId::from_str(&ctf_contract_str)?;
+        let position_id = self.get_position_id_for_outcome(&trade.condition_id, trade.outcome, collateral_token).await?;
+        let ctf_contract = self.ctf_contract.clone();
 
         let args = json!({
             "from": seller,
@@ -171,19 +229,17 @@ impl NearClient {
         ).await
     }
 
-    pub async fn split_position(&self, condition_id: &str, amount: u128) -> Result<String> {
+    pub async fn split_position(&self, condition_id: &str, amount: u128, collateral_token: &str) -> Result<String> {
         info!("🔍 DEBUGGING: About to call split_position with:");
         info!("   condition_id: '{}'", condition_id);
         info!("   condition_id length: {}", condition_id.len());
         info!("   amount: {}", amount);
 
-        let ctf_contract_str = std::env::var("CTF_CONTRACT_ID")
-            .unwrap_or_else(|_| "ctf.ashpk20.testnet".to_string());
-        let ctf_contract = AccountId::from_str(&ctf_contract_str)?;
+        let ctf_contract = self.ctf_contract.clone();
 
         // For binary prediction markets, partition represents the index sets for NO (1) and YES (2) outcomes
         let args = json!({
-            "collateral_token": "3e2210e1184b45b64c8a434c0a7e7b23cc04ea7eb7a6c3c32520d03d4afcb8af",
+            "collateral_token": collateral_token,
             "parent_collection_id": "",
             "condition_id": condition_id,
             "partition": ["1", "2"], // NO=1 (binary 001), YES=2 (binary 010) for binary outcomes
@@ -199,16 +255,14 @@ impl NearClient {
         ).await
     }
 
-    pub async fn merge_positions(&self, condition_id: &str, amount: u128) -> Result<String> {
+    pub async fn merge_positions(&self, condition_id: &str, amount: u128, collateral_token: &str) -> Result<String> {
         info!("Merging positions for condition {} amount {}", condition_id, amount);
 
-        let ctf_contract_str = std::env::var("CTF_CONTRACT_ID")
-            .unwrap_or_else(|_| "ctf.ashpk20.testnet".to_string());
-        let ctf_contract = AccountId::from_str(&ctf_contract_str)?;
+        let ctf_contract = self.ctf_contract.clone();
 
         // For binary prediction markets, partition represents the index sets for NO (1) and YES (2) outcomes  
         let args = json!({
-            "collateral_token": "3e2210e1184b45b64c8a434c0a7e7b23cc04ea7eb7a6c3c32520d03d4afcb8af",
+            "collateral_token": collateral_token,
             "parent_collection_id": "",
             "condition_id": condition_id,
             "partition": ["1", "2"], // NO=1 (binary 001), YES=2 (binary 010) for binary outcomes
@@ -224,11 +278,9 @@ impl NearClient {
         ).await
     }
 
-    /// Calculate the correct position ID for a given outcome in a condition
-    pub async fn get_position_id_for_outcome(&self, condition_id: &str, outcome: u8) -> Result<String> {
-        let ctf_contract_str = std::env::var("CTF_CONTRACT_ID")
-            .unwrap_or_else(|_| "ctf.ashpk20.testnet".to_string());
-        let ctf_contract = AccountId::from_str(&ctf_contract_str)?;
+    /// Calculate the correct position ID for a given outcome in a condition split from `collateral_token`
+    pub async fn get_position_id_for_outcome(&self, condition_id: &str, outcome: u8, collateral_token: &str) -> Result<String> {
+        let ctf_contract = self.ctf_contract.clone();
 
         // Calculate index set for the outcome (binary outcomes: 1 for YES, 2 for NO)
         // outcome 0 (NO in orderbook) → index_set ["2"] → NO tokens
@@ -251,7 +303,7 @@ impl NearClient {
             &ctf_contract,
             "get_position_id",
             &json!({
-                "collateral_token": "3e2210e1184b45b64c8a434c0a7e7b23cc04ea7eb7a6c3c32520d03d4afcb8af",
+                "collateral_token": collateral_token,
                 "collection_id": collection_id
             })
         ).await?;
@@ -262,9 +314,7 @@ impl NearClient {
     pub async fn transfer_position(&self, to_account: &str, position_id: &str, amount: u128) -> Result<String> {
         info!("Transferring {} units of position {} to {}", amount, position_id, to_account);
 
-        let ctf_contract_str = std::env::var("CTF_CONTRACT_ID")
-            .unwrap_or_else(|_| "ctf.ashpk20.testnet".to_string());
-        let ctf_contract = AccountId::from_str(&ctf_contract_str)?;
+        let ctf_contract = self.ctf_contract.clone();
 
         let args = json!({
             "from": self.signer_account.to_string(),
@@ -283,12 +333,38 @@ impl NearClient {
         ).await
     }
 
+    /// Move positions between two users; the orderbook must be an approved operator for `from_account`
+    pub async fn transfer_position_from(&self, from_account: &str, to_account: &str, position_id: &str, amount: u128) -> Result<String> {
+        info!("Transferring {} units of position {} from {} to {}", amount, position_id, from_account, to_account);
+
+        let ctf_contract = self.ctf_contract.clone();
+
+        let args = json!({
+            "from": from_account,
+            "to": to_account,
+            "position_id": position_id,
+            "amount": amount.to_string(),
+            "data": ""
+        });
+
+        self.call_contract_function_commit(
+            &ctf_contract,
+            "safe_transfer_from",
+            &args,
+            150_000_000_000_000, // 150 TGas
+            0,
+        ).await
+    }
+
     pub async fn transfer_usdc(&self, from_account: &str, to_account: &str, amount: u128) -> Result<String> {
-        info!("Transferring {} USDC from {} to {}", amount as f64 / 1_000_000.0, from_account, to_account);
+        self.transfer_ft(self.usdc_contract.as_str(), from_account, to_account, amount).await
+    }
 
-        let usdc_contract_str = std::env::var("USDC_CONTRACT_ID")
-            .unwrap_or_else(|_| "3e2210e1184b45b64c8a434c0a7e7b23cc04ea7eb7a6c3c32520d03d4afcb8af".to_string());
-        let usdc_contract = AccountId::from_str(&usdc_contract_str)?;
+    /// ft_transfer on any NEP-141 collateral token
+    pub async fn transfer_ft(&self, token_contract: &str, from_account: &str, to_account: &str, amount: u128) -> Result<String> {
+        info!("Transferring {} of {} from {} to {}", amount, token_contract, from_account, to_account);
+
+        let token_account = AccountId::from_str(token_contract)?;
 
         let args = json!({
             "receiver_id": to_account,
@@ -297,7 +373,7 @@ impl NearClient {
         });
 
         self.call_contract_function(
-            &usdc_contract,
+            &token_account,
             "ft_transfer",
             &args,
             50_000_000_000_000, // 50 TGas
@@ -305,6 +381,79 @@ impl NearClient {
         ).await
     }
 
+    /// Deposit into a yield protocol: ft_transfer_call of the signer's tokens, credited to the signer
+    pub async fn yield_deposit(&self, protocol: &str, token_contract: &str, amount: u128) -> Result<String> {
+        let token_account = AccountId::from_str(token_contract)?;
+
+        let args = json!({
+            "receiver_id": protocol,
+            "amount": amount.to_string(),
+            "msg": "deposit"
+        });
+
+        self.call_contract_function_commit(
+            &token_account,
+            "ft_transfer_call",
+            &args,
+            150_000_000_000_000, // 150 TGas, the protocol's ft_on_transfer runs in the same call
+            1,
+        ).await
+    }
+
+    /// Withdraw from a yield protocol back to the signer account
+    pub async fn yield_withdraw(&self, protocol: &str, token_contract: &str, amount: u128) -> Result<String> {
+        let protocol_account = AccountId::from_str(protocol)?;
+
+        let args = json!({
+            "token_id": token_contract,
+            "amount": amount.to_string()
+        });
+
+        self.call_contract_function_commit(
+            &protocol_account,
+            "withdraw",
+            &args,
+            150_000_000_000_000, // 150 TGas, covers the protocol's ft_transfer back
+            1,
+        ).await
+    }
+
+    /// What the signer holds in a yield protocol, principal and interest together
+    pub async fn get_yield_protocol_balance(&self, protocol: &str, token_contract: &str) -> Result<u128> {
+        let protocol_account = AccountId::from_str(protocol)?;
+
+        let balance: String = self.call_view_function(
+            &protocol_account,
+            "get_balance",
+            &json!({ "account_id": self.signer_account_id(), "token_id": token_contract }),
+        ).await?;
+
+        balance.parse().map_err(|e| anyhow!("Invalid yield balance {} from {}: {}", balance, protocol, e))
+    }
+
+    /// rpc_client.call with latency and error metrics recorded under `method`
+    async fn timed_call<M>(&self, method: &str, request: M) -> Result<M::Response, JsonRpcError<M::Error>>
+    where
+        M: methods::RpcMethod,
+    {
+        let start = Instant::now();
+        let result = self.rpc_client.call(request).await;
+
+        let error_type = result.as_ref().err().map(|e| match e {
+            JsonRpcError::TransportError(_) => "transport",
+            JsonRpcError::ServerError(JsonRpcServerError::HandlerError(_)) => "handler",
+            JsonRpcError::ServerError(JsonRpcServerError::ResponseStatusError(_)) => "http_status",
+            JsonRpcError::ServerError(_) => "server",
+        });
+        rpc_metrics::observe_rpc_call(method, start.elapsed(), error_type);
+        // A handler error is still an answer from the chain
+        if method.starts_with("view:") || method == "block" {
+            self.chain_health.record(!matches!(error_type, Some("transport" | "http_status" | "server")));
+        }
+
+        result
+    }
+
     async fn call_view_function<T: serde::de::DeserializeOwned>(
         &self,
         contract_id: &AccountId,
@@ -322,7 +471,7 @@ impl NearClient {
             },
         };
 
-        let response = self.rpc_client.call(request).await
+        let response = self.timed_call(&format!("view:{}", method_name), request).await
             .map_err(|e| anyhow!("NEAR view function call failed: {}", e))?;
 
         if let near_jsonrpc_primitives::types::query::QueryResponseKind::CallResult(result) = response.kind {
@@ -356,8 +505,8 @@ impl NearClient {
             },
         };
 
-        let access_key_query_response = self.rpc_client
-            .call(access_key_query)
+        let access_key_query_response = self
+            .timed_call("query_access_key", access_key_query)
             .await?;
 
         let current_nonce = if let near_jsonrpc_primitives::types::query::QueryResponseKind::AccessKey(access_key) = access_key_query_response.kind {
@@ -371,8 +520,8 @@ impl NearClient {
             block_reference: BlockReference::Finality(Finality::Final),
         };
 
-        let block_response = self.rpc_client
-            .call(block_request)
+        let block_response = self
+            .timed_call("block", block_request)
             .await?;
 
         let block_hash = block_response.header.hash;
@@ -405,8 +554,8 @@ impl NearClient {
             signed_transaction,
         };
 
-        let response = self.rpc_client
-            .call(tx_request)
+        let response = self
+            .timed_call("broadcast_tx_async", tx_request)
             .await?;
 
         let tx_hash = response.to_string();
@@ -458,8 +607,8 @@ impl NearClient {
                             },
                         };
 
-                        let access_key_query_response = self.rpc_client
-                            .call(access_key_query)
+                        let access_key_query_response = self
+                            .timed_call("query_access_key", access_key_query)
                             .await?;
 
                         let current_nonce = if let near_jsonrpc_primitives::types::query::QueryResponseKind::AccessKey(access_key) = access_key_query_response.kind {
@@ -480,8 +629,8 @@ impl NearClient {
                 block_reference: BlockReference::Finality(Finality::Final),
             };
 
-            let block_response = self.rpc_client
-                .call(block_request)
+            let block_response = self
+                .timed_call("block", block_request)
                 .await?;
 
             let block_hash = block_response.header.hash;
@@ -514,7 +663,7 @@ impl NearClient {
                 signed_transaction,
             };
 
-            let result = self.rpc_client.call(tx_request).await;
+            let result = self.timed_call("broadcast_tx_commit", tx_request).await;
 
             match result {
                 Ok(outcome) => {
@@ -584,9 +733,12 @@ impl NearClient {
 
     // Balance checking methods for trade type determination
     pub async fn get_usdc_balance(&self, account_id: &str) -> Result<u128> {
-        let usdc_contract_str = std::env::var("USDC_CONTRACT_ID")
-            .unwrap_or_else(|_| "3e2210e1184b45b64c8a434c0a7e7b23cc04ea7eb7a6c3c32520d03d4afcb8af".to_string());
-        let usdc_contract = AccountId::from_str(&usdc_contract_str)?;
+        self.get_ft_balance(self.usdc_contract.as_str(), account_id).await
+    }
+
+    /// ft_balance_of on any NEP-141 collateral token
+    pub async fn get_ft_balance(&self, token_contract: &str, account_id: &str) -> Result<u128> {
+        let token_account = AccountId::from_str(token_contract)?;
 
         let args = json!({
             "account_id": account_id
@@ -595,13 +747,13 @@ impl NearClient {
         let request = methods::query::RpcQueryRequest {
             block_reference: BlockReference::Finality(Finality::Final),
             request: ViewRequest::CallFunction {
-                account_id: usdc_contract.clone(),
+                account_id: token_account.clone(),
                 method_name: "ft_balance_of".to_string(),
                 args: args.to_string().into_bytes().into(),
             },
         };
 
-        let response = self.rpc_client.call(request).await?;
+        let response = self.timed_call("view:ft_balance_of", request).await?;
 
         if let near_jsonrpc_primitives::types::query::QueryResponseKind::CallResult(result) = response.kind {
             let balance_str = String::from_utf8(result.result)?;
@@ -617,9 +769,7 @@ impl NearClient {
     }
 
     pub async fn get_ctf_token_balance(&self, account_id: &str, position_id: &str) -> Result<u128> {
-        let ctf_contract_str = std::env::var("CTF_CONTRACT_ID")
-            .unwrap_or_else(|_| "ctf.ashpk20.testnet".to_string());
-        let ctf_contract = AccountId::from_str(&ctf_contract_str)?;
+        let ctf_contract = self.ctf_contract.clone();
 
         let args = json!({
             "owner": account_id,
@@ -635,7 +785,7 @@ impl NearClient {
             },
         };
 
-        let response = self.rpc_client.call(request).await?;
+        let response = self.timed_call("view:balance_of", request).await?;
 
         if let near_jsonrpc_primitives::types::query::QueryResponseKind::CallResult(result) = response.kind {
             let balance_str = String::from_utf8(result.result)?;
@@ -660,5 +810,59 @@ impl NearClient {
         let balance = self.get_usdc_balance(account_id).await?;
         Ok(balance >= required_amount)
     }
+}
+
+#[async_trait::async_trait]
+impl ChainProbe for NearClient {
+    /// Latest final block; the cheapest read the RPC serves
+    async fn probe(&self) -> Result<()> {
+        let block_request = methods::block::RpcBlockRequest {
+            block_reference: BlockReference::Finality(Finality::Final),
+        };
+        self.timed_call("block", block_request).await?;
+        Ok(())
+    }
+
+    fn chain_health(&self) -> &ChainHealth {
+        &self.chain_health
+    }
+}
+
+#[async_trait::async_trait]
+impl SettlementClient for NearClient {
+    fn signer_account_id(&self) -> &str {
+        NearClient::signer_account_id(self)
+    }
 
-}
\ No newline at end of file
+    async fn get_position_id_for_outcome(&self, condition_id: &str, outcome: u8, collateral_token: &str) -> Result<String> {
+        NearClient::get_position_id_for_outcome(self, condition_id, outcome, collateral_token).await
+    }
+
+    async fn get_ft_balance(&self, token_contract: &str, account_id: &str) -> Result<u128> {
+        NearClient::get_ft_balance(self, token_contract, account_id).await
+    }
+
+    async fn get_ctf_token_balance(&self, account_id: &str, position_id: &str) -> Result<u128> {
+        NearClient::get_ctf_token_balance(self, account_id, position_id).await
+    }
+
+    async fn transfer_collateral_from(&self, collateral_token: &str, from: &str, to: &str, amount: u128) -> Result<String> {
+        let args = json!({
+            "from": from,
+            "to": to,
+            "value": amount.to_string()
+        });
+
+        self.call_contract_function_commit(
+            &collateral_token.parse()?,
+            "transfer_from",
+            &args,
+            100_000_000_000_000, // 100 TGas
+            1, // 1 yoctoNEAR deposit
+        ).await
+    }
+
+    async fn transfer_position_from(&self, from: &str, to: &str, position_id: &str, amount: u128) -> Result<String> {
+        NearClient::transfer_position_from(self, from, to, position_id, amount).await
+    }
+}
@@ -0,0 +1,66 @@
+// Prometheus metrics for NEAR RPC calls
+// Every rpc_client.call in NearClient goes through NearClient::timed_call, which records latency
+// per method label and counts failures by error type. Exposed in text format on GET /metrics,
+// together with everything else in the default registry (the matching engine's metrics).
+
+use std::sync::OnceLock;
+use std::time::Duration;
+use anyhow::Result;
+use prometheus::{
+    exponential_buckets, register_histogram_vec, register_int_counter_vec, Encoder, HistogramVec,
+    IntCounterVec, TextEncoder,
+};
+
+struct RpcMetrics {
+    latency: HistogramVec,
+    errors: IntCounterVec,
+}
+
+static RPC_METRICS: OnceLock<RpcMetrics> = OnceLock::new();
+
+fn rpc_metrics() -> &'static RpcMetrics {
+    RPC_METRICS.get_or_init(|| {
+        // 10ms doubling up to ~10s (10ms * 2^10 = 10.24s)
+        let buckets = exponential_buckets(0.01, 2.0, 11).expect("valid histogram buckets");
+
+        RpcMetrics {
+            latency: register_histogram_vec!(
+                "near_rpc_request_duration_seconds",
+                "NEAR RPC call latency by method and status",
+                &["method", "status"],
+                buckets
+            )
+            .expect("register near_rpc_request_duration_seconds"),
+            errors: register_int_counter_vec!(
+                "near_rpc_error_total",
+                "NEAR RPC call failures by method and error type",
+                &["method", "error_type"]
+            )
+            .expect("register near_rpc_error_total"),
+        }
+    })
+}
+
+/// Record one RPC call. `error_type` is None on success
+pub fn observe_rpc_call(method: &str, elapsed: Duration, error_type: Option<&str>) {
+    let metrics = rpc_metrics();
+    let status = if error_type.is_some() { "error" } else { "success" };
+
+    metrics.latency
+        .with_label_values(&[method, status])
+        .observe(elapsed.as_secs_f64());
+
+    if let Some(error_type) = error_type {
+        metrics.errors.with_label_values(&[method, error_type]).inc();
+    }
+}
+
+/// Render all registered metrics in the Prometheus text exposition format
+pub fn render() -> Result<String> {
+    // Make sure the RPC metrics show up (with zero samples) before the first call
+    rpc_metrics();
+
+    let mut buffer = Vec::new();
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+    Ok(String::from_utf8(buffer)?)
+}
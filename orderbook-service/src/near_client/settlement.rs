@@ -0,0 +1,21 @@
+// Chain calls settlement makes
+// CollateralManager reads the balances an order has to be covered by and moves collateral and
+// outcome tokens for settlement through SettlementClient, which NearClient implements, so tests can
+// swap in a client with balances they set and transfers that fail on command.
+
+use anyhow::Result;
+
+/// The balances backing an order and the transfers settling a trade on chain
+#[async_trait::async_trait]
+pub trait SettlementClient: Send + Sync {
+    /// Account every transaction is signed with; trading fees are collected into it
+    fn signer_account_id(&self) -> &str;
+    async fn get_position_id_for_outcome(&self, condition_id: &str, outcome: u8, collateral_token: &str) -> Result<String>;
+    /// Balance of a NEP-141 token
+    async fn get_ft_balance(&self, token_contract: &str, account_id: &str) -> Result<u128>;
+    /// Balance of a CTF position
+    async fn get_ctf_token_balance(&self, account_id: &str, position_id: &str) -> Result<u128>;
+    /// Move `amount` of `collateral_token` from `from` to `to` on the service's allowance
+    async fn transfer_collateral_from(&self, collateral_token: &str, from: &str, to: &str, amount: u128) -> Result<String>;
+    async fn transfer_position_from(&self, from: &str, to: &str, position_id: &str, amount: u128) -> Result<String>;
+}
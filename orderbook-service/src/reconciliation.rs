@@ -0,0 +1,256 @@
+// Chain reconciliation
+// The book and the solver contract drift when a fill report is lost or a cancel only lands on one
+// side. A reconciliation pass pages through the solver's orders, compares them with the local
+// orders they were submitted as, and stores a divergence report:
+//   - cancelled/expired on-chain but still resting here: safe, the local order is cancelled and
+//     its collateral released
+//   - filled further here than on-chain: the total fill is queued for the fill reporter's retry
+//   - anything else (on-chain fills the book never made, cancels only made here, orders the
+//     solver doesn't know) is left for an operator
+// Passes run every ServiceConfig::reconcile_interval_secs (300, 0 disables) and on POST /admin/reconcile.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use chrono::Utc;
+use tokio::sync::Mutex;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::config::ServiceConfig;
+use crate::matching::MatchingEngine;
+use crate::near_client::NearClient;
+use crate::solver_integration::{SolverIntegration, SolverOrder, SolverOrderStatus};
+use crate::storage::DatabaseTrait;
+use crate::types::{Divergence, DivergenceKind, Order, OrderSide, OrderStatus, ReconcileAction, ReconciliationReport};
+
+pub const DEFAULT_RECONCILE_INTERVAL_SECS: u64 = 300;
+/// Solver orders read per get_active_orders_paginated call
+const SOLVER_ORDER_PAGE_SIZE: u64 = 100;
+
+fn is_open(status: &OrderStatus) -> bool {
+    matches!(status, OrderStatus::Pending | OrderStatus::PartiallyFilled)
+}
+
+/// Divergences between local orders and the solver orders they are linked to, with the action a
+/// pass takes for each. Pure: nothing is changed here
+pub fn find_divergences(
+    local_orders: &[Order],
+    links: &HashMap<Uuid, String>,
+    onchain: &HashMap<String, SolverOrder>,
+) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+
+    for order in local_orders {
+        let Some(solver_order_id) = links.get(&order.order_id) else { continue };
+        let divergence = |kind, action, solver_order: Option<&SolverOrder>| Divergence {
+            order_id: order.order_id,
+            solver_order_id: solver_order_id.clone(),
+            kind,
+            action,
+            local_status: order.status.clone(),
+            local_filled: order.filled_size,
+            onchain_status: solver_order.map(|o| format!("{:?}", o.status)),
+            onchain_filled: solver_order.map(|o| o.filled_amount.parse().unwrap_or(0)),
+        };
+
+        let Some(solver_order) = onchain.get(solver_order_id) else {
+            // Closed orders may have been refunded and removed on-chain; only open ones matter
+            if is_open(&order.status) {
+                divergences.push(divergence(DivergenceKind::MissingOnChain, ReconcileAction::NeedsReview, None));
+            }
+            continue;
+        };
+
+        let onchain_filled: u128 = solver_order.filled_amount.parse().unwrap_or(0);
+        if order.filled_size > onchain_filled {
+            divergences.push(divergence(DivergenceKind::UnreportedFill, ReconcileAction::FillReportQueued, Some(solver_order)));
+        } else if onchain_filled > order.filled_size {
+            divergences.push(divergence(DivergenceKind::FillNotInBook, ReconcileAction::NeedsReview, Some(solver_order)));
+        }
+
+        let onchain_closed = matches!(solver_order.status, SolverOrderStatus::Cancelled | SolverOrderStatus::Expired);
+        let onchain_open = matches!(solver_order.status, SolverOrderStatus::Pending | SolverOrderStatus::PartiallyFilled);
+        if is_open(&order.status) && onchain_closed {
+            divergences.push(divergence(DivergenceKind::CancelledOnChain, ReconcileAction::AutoHealed, Some(solver_order)));
+        } else if matches!(order.status, OrderStatus::Cancelled | OrderStatus::Expired) && onchain_open {
+            divergences.push(divergence(DivergenceKind::ClosedLocallyOnly, ReconcileAction::NeedsReview, Some(solver_order)));
+        }
+    }
+
+    divergences
+}
+
+/// Compare every linked local order with `onchain`. Returns the number of local orders checked
+/// and the divergences found
+pub async fn compare_with_chain(
+    database: &dyn DatabaseTrait,
+    onchain: &[SolverOrder],
+) -> Result<(usize, Vec<Divergence>)> {
+    let links = database.get_solver_order_links().await?;
+    let mut local_orders = Vec::with_capacity(links.len());
+    for order_id in links.keys() {
+        if let Some(order) = database.get_order(*order_id).await? {
+            local_orders.push(order);
+        }
+    }
+    local_orders.sort_by_key(|order| order.created_at);
+
+    let onchain: HashMap<String, SolverOrder> = onchain.iter()
+        .map(|order| (order.order_id.clone(), order.clone()))
+        .collect();
+    Ok((local_orders.len(), find_divergences(&local_orders, &links, &onchain)))
+}
+
+/// Price to report with a re-sent fill: the order's worst trade, so the solver still rejects a
+/// fill that crossed the order's price bound
+pub async fn worst_fill_price(database: &dyn DatabaseTrait, order: &Order) -> Result<Option<u64>> {
+    let prices = database.get_trades_for_market(&order.market_id).await?
+        .into_iter()
+        .filter(|trade| trade.maker_order_id == order.order_id || trade.taker_order_id == order.order_id)
+        .map(|trade| trade.price);
+
+    Ok(match order.side {
+        OrderSide::Buy => prices.max(),
+        OrderSide::Sell => prices.min(),
+    })
+}
+
+/// Whether a re-sent fill earns the maker rebate: only when the order was the resting side of
+/// every one of its trades
+pub async fn filled_as_maker(database: &dyn DatabaseTrait, order: &Order) -> Result<bool> {
+    let mut trades = database.get_trades_for_market(&order.market_id).await?
+        .into_iter()
+        .filter(|trade| trade.maker_order_id == order.order_id || trade.taker_order_id == order.order_id)
+        .peekable();
+
+    Ok(trades.peek().is_some() && trades.all(|trade| trade.maker_order_id == order.order_id))
+}
+
+pub struct Reconciler {
+    matching_engine: Arc<MatchingEngine>,
+    near_client: Arc<NearClient>,
+    solver_integration: Arc<SolverIntegration>,
+    interval: Duration,
+    // One pass at a time; the schedule and the admin endpoint share it
+    pass_lock: Mutex<()>,
+}
+
+impl Reconciler {
+    pub fn new(
+        matching_engine: Arc<MatchingEngine>,
+        near_client: Arc<NearClient>,
+        solver_integration: Arc<SolverIntegration>,
+        config: &ServiceConfig,
+    ) -> Self {
+        Self {
+            matching_engine,
+            near_client,
+            solver_integration,
+            interval: Duration::from_secs(config.reconcile_interval_secs),
+            pass_lock: Mutex::new(()),
+        }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        if self.interval.is_zero() {
+            info!("Scheduled chain reconciliation disabled (RECONCILE_INTERVAL_SECS=0)");
+            return Ok(());
+        }
+
+        let shutdown = self.matching_engine.shutdown_token();
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(self.interval) => {}
+                _ = shutdown.cancelled() => return Ok(()),
+            }
+
+            if let Err(e) = self.reconcile().await {
+                error!("Chain reconciliation failed: {}", e);
+            }
+        }
+    }
+
+    async fn fetch_solver_orders(&self) -> Result<Vec<SolverOrder>> {
+        let mut orders = Vec::new();
+        loop {
+            let page = self.near_client
+                .get_solver_orders_page(self.solver_integration.solver_contract_id(), orders.len() as u64, SOLVER_ORDER_PAGE_SIZE)
+                .await?;
+            let done = (page.len() as u64) < SOLVER_ORDER_PAGE_SIZE;
+            orders.extend(page);
+            if done {
+                return Ok(orders);
+            }
+        }
+    }
+
+    /// Run one pass: compare, heal the safe cases, queue unreported fills and store the report
+    pub async fn reconcile(&self) -> Result<ReconciliationReport> {
+        let _pass = self.pass_lock.lock().await;
+        let started_at = Utc::now();
+        let database = self.matching_engine.get_database();
+
+        let onchain = self.fetch_solver_orders().await?;
+        let (orders_checked, mut divergences) = compare_with_chain(database.as_ref(), &onchain).await?;
+
+        for divergence in divergences.iter_mut() {
+            match divergence.action {
+                ReconcileAction::AutoHealed => {
+                    match self.matching_engine.cancel_order_cancelled_on_chain(divergence.order_id).await {
+                        Ok(_) => info!("🩹 Removed order {} cancelled on-chain as {}", divergence.order_id, divergence.solver_order_id),
+                        Err(e) => {
+                            warn!("Could not remove order {} cancelled on-chain: {}", divergence.order_id, e);
+                            divergence.action = ReconcileAction::NeedsReview;
+                        }
+                    }
+                }
+                ReconcileAction::FillReportQueued => {
+                    let (fill_price, is_maker) = match database.get_order(divergence.order_id).await? {
+                        Some(order) => (
+                            worst_fill_price(database.as_ref(), &order).await?,
+                            filled_as_maker(database.as_ref(), &order).await?,
+                        ),
+                        None => (None, false),
+                    };
+                    match fill_price {
+                        Some(fill_price) => {
+                            self.solver_integration
+                                .queue_fill_report(divergence.solver_order_id.clone(), divergence.local_filled, fill_price, is_maker)
+                                .await;
+                        }
+                        None => {
+                            // Trades already compacted away; nothing to price the report with
+                            warn!("No trades left to price the fill of order {}", divergence.order_id);
+                            divergence.action = ReconcileAction::NeedsReview;
+                        }
+                    }
+                }
+                ReconcileAction::NeedsReview => {}
+            }
+        }
+
+        let reported = self.solver_integration.retry_fill_reports().await;
+        if reported > 0 {
+            info!("Fill reporter re-sent {} fills to the solver", reported);
+        }
+
+        let report = ReconciliationReport {
+            report_id: Uuid::new_v4(),
+            started_at,
+            completed_at: Utc::now(),
+            onchain_orders: onchain.len(),
+            orders_checked,
+            divergences,
+        };
+        database.insert_reconciliation_report(&report).await?;
+
+        if report.divergences.is_empty() {
+            debug!("Chain reconciliation: {} orders match the solver", orders_checked);
+        } else {
+            warn!("⚠️  Chain reconciliation found {} divergences across {} orders", report.divergences.len(), orders_checked);
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,112 @@
+// Graceful shutdown
+// Ctrl-C / SIGTERM (or quitting the TUI) cancels the engine's shutdown token, after which:
+//   1. order submission answers 503 SHUTTING_DOWN and the HTTP server stops taking connections
+//   2. the engine supervisor, shard loops and checksum audit exit after their current iteration
+//   3. in-flight submissions finish, then the settlement worker settles every queued trade
+//   4. the journal records how the stop went and is flushed before the process exits
+// The drain is bounded by SHUTDOWN_DRAIN_TIMEOUT_SECS. A drain that fails or times out is journaled
+// as an unclean stop; its trades stay Pending in the database.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn, error};
+
+use crate::journal::{EventJournal, JournalEvent};
+
+/// How long queued settlements may take to drain when SHUTDOWN_DRAIN_TIMEOUT_SECS is not set
+pub const DEFAULT_DRAIN_TIMEOUT_SECS: u64 = 30;
+
+pub fn drain_timeout_from_env() -> Duration {
+    let secs = std::env::var("SHUTDOWN_DRAIN_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_DRAIN_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Resolves on Ctrl-C, or SIGTERM on unix
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("🛑 Received Ctrl-C"),
+        _ = terminate => info!("🛑 Received SIGTERM"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownOutcome {
+    pub settled_trades: usize,
+    pub clean: bool,
+}
+
+pub struct ShutdownCoordinator {
+    token: CancellationToken,
+    journal: Arc<EventJournal>,
+    drain_timeout: Duration,
+}
+
+impl ShutdownCoordinator {
+    pub fn new(token: CancellationToken, journal: Arc<EventJournal>, drain_timeout: Duration) -> Self {
+        Self { token, journal, drain_timeout }
+    }
+
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Cancel the token, wait up to the drain timeout for `drain` (which settles queued trades
+    /// and returns how many) and journal the result. The journal is flushed either way
+    pub async fn shutdown<F>(&self, drain: F) -> Result<ShutdownOutcome>
+    where
+        F: Future<Output = Result<usize>>,
+    {
+        self.journal.record(JournalEvent::ShutdownRequested).await?;
+        self.token.cancel();
+        info!("🛑 Shutting down: draining settlements (timeout {:?})", self.drain_timeout);
+
+        let outcome = match tokio::time::timeout(self.drain_timeout, drain).await {
+            Ok(Ok(settled_trades)) => {
+                self.journal.record(JournalEvent::SettlementsDrained { trades: settled_trades }).await?;
+                info!("✅ Drained {} queued settlements", settled_trades);
+                ShutdownOutcome { settled_trades, clean: true }
+            }
+            Ok(Err(e)) => {
+                error!("❌ Settlement drain failed: {}", e);
+                ShutdownOutcome { settled_trades: 0, clean: false }
+            }
+            Err(_) => {
+                warn!("⚠️  Settlement drain timed out after {:?}; remaining trades stay pending", self.drain_timeout);
+                ShutdownOutcome { settled_trades: 0, clean: false }
+            }
+        };
+
+        self.journal.record(JournalEvent::Stopped { clean: outcome.clean }).await?;
+        self.journal.flush().await?;
+        info!("Journal flushed to {}", self.journal.path().display());
+        Ok(outcome)
+    }
+}
@@ -2,8 +2,8 @@
 // User -> Solver -> Orderbook -> Settlement via CTF
 
 use std::sync::Arc;
-use std::collections::HashMap;
-use tokio::sync::RwLock;
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::{Mutex, RwLock};
 use anyhow::Result;
 use serde_json::json;
 use tracing::{info, warn, error};
@@ -13,6 +13,8 @@ use chrono::Utc;
 use crate::types::{Order, OrderSide, OrderType, OrderStatus, Trade, TradeType};
 use crate::matching::MatchingEngine;
 use crate::near_client::NearClient;
+use crate::market_registry::resolve_active_condition;
+use crate::api::ApiError;
 
 // NEAR contract call structures matching the solver contract
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
@@ -81,12 +83,27 @@ pub enum SolverTradeType {
     Burning,
 }
 
+/// Give up on a fill report after this many failed update_order_fill calls
+const MAX_FILL_REPORT_ATTEMPTS: u32 = 5;
+
+/// Fill the solver contract hasn't recorded yet. `filled_amount` is the order's total fill
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingFillReport {
+    pub solver_order_id: String,
+    pub filled_amount: u128,
+    pub fill_price: u64,
+    pub is_maker: bool,
+    pub attempts: u32,
+}
+
 pub struct SolverIntegration {
     near_client: Arc<NearClient>,
     matching_engine: Arc<MatchingEngine>,
     solver_contract_id: String,
     // Map orderbook UUID -> solver string ID for settlement callbacks
     order_id_mapping: Arc<RwLock<HashMap<Uuid, String>>>,
+    // Fill reports to send again, fed by the reconciliation job
+    fill_report_queue: Mutex<VecDeque<PendingFillReport>>,
 }
 
 impl SolverIntegration {
@@ -100,15 +117,74 @@ impl SolverIntegration {
             matching_engine,
             solver_contract_id,
             order_id_mapping: Arc::new(RwLock::new(HashMap::new())),
+            fill_report_queue: Mutex::new(VecDeque::new()),
         }
     }
 
+    pub fn solver_contract_id(&self) -> &str {
+        &self.solver_contract_id
+    }
+
+    /// Queue the total fill of a solver order for update_order_fill, replacing any report
+    /// already queued for it
+    pub async fn queue_fill_report(&self, solver_order_id: String, filled_amount: u128, fill_price: u64, is_maker: bool) {
+        let mut queue = self.fill_report_queue.lock().await;
+        queue.retain(|report| report.solver_order_id != solver_order_id);
+        queue.push_back(PendingFillReport { solver_order_id, filled_amount, fill_price, is_maker, attempts: 0 });
+    }
+
+    pub async fn pending_fill_reports(&self) -> Vec<PendingFillReport> {
+        self.fill_report_queue.lock().await.iter().cloned().collect()
+    }
+
+    /// Send every queued fill report once. Failed reports go back on the queue until they
+    /// reach MAX_FILL_REPORT_ATTEMPTS. Returns how many the solver accepted
+    pub async fn retry_fill_reports(&self) -> usize {
+        let reports: Vec<PendingFillReport> = self.fill_report_queue.lock().await.drain(..).collect();
+        let mut reported = 0;
+
+        for mut report in reports {
+            let args = json!({
+                "order_id": report.solver_order_id,
+                "filled_amount": report.filled_amount.to_string(),
+                "fill_price": report.fill_price,
+                "is_maker": report.is_maker
+            });
+            match self.near_client
+                .call_near_contract(&self.solver_contract_id, "update_order_fill", &args.to_string(), "30000000000000", "0")
+                .await
+            {
+                Ok(tx_hash) => {
+                    info!("✅ Re-reported fill of solver order {} ({}): {}", report.solver_order_id, report.filled_amount, tx_hash);
+                    reported += 1;
+                }
+                Err(e) => {
+                    report.attempts += 1;
+                    if report.attempts >= MAX_FILL_REPORT_ATTEMPTS {
+                        error!("❌ Giving up on fill report for solver order {} after {} attempts: {}",
+                            report.solver_order_id, report.attempts, e);
+                    } else {
+                        warn!("Fill report for solver order {} failed (attempt {}): {}", report.solver_order_id, report.attempts, e);
+                        self.fill_report_queue.lock().await.push_back(report);
+                    }
+                }
+            }
+        }
+        reported
+    }
+
     /// Convert incoming solver order to orderbook order format
     pub async fn process_solver_order(&self, solver_order: SolverOrder) -> Result<Vec<Trade>> {
+        self.process_solver_order_as(solver_order, Uuid::new_v4()).await
+    }
+
+    /// Same as process_solver_order, booking the order under an id the caller picked (the one
+    /// reserved for its idempotency key)
+    pub async fn process_solver_order_as(&self, solver_order: SolverOrder, orderbook_order_id: Uuid) -> Result<Vec<Trade>> {
         info!("Processing solver order: {}", solver_order.order_id);
 
         // Look up the real condition ID for this market (don't trust solver's condition_id)
-        let real_condition_id = match self.near_client.get_market_condition_id(&solver_order.market_id).await {
+        let real_condition_id = match self.matching_engine.get_database().get_market_condition_id(&solver_order.market_id).await {
             Ok(Some(id)) => {
                 info!("Using real condition ID for market {}: {}", solver_order.market_id, id);
                 id
@@ -124,13 +200,15 @@ impl SolverIntegration {
         };
 
         // Convert solver order format to orderbook order format
-        let orderbook_order_id = Uuid::new_v4(); // Create new UUID for internal use
-
         // Store mapping from orderbook UUID to solver string ID for later settlement
         {
             let mut mapping = self.order_id_mapping.write().await;
             mapping.insert(orderbook_order_id, solver_order.order_id.clone());
         }
+        // Persisted too so the reconciliation job can match orders across restarts
+        if let Err(e) = self.matching_engine.get_database().link_solver_order(orderbook_order_id, &solver_order.order_id).await {
+            warn!("Failed to persist solver order link {} -> {}: {}", orderbook_order_id, solver_order.order_id, e);
+        }
 
         info!("Mapped orderbook UUID {} to solver ID {}", orderbook_order_id, solver_order.order_id);
         let order = Order {
@@ -257,10 +335,17 @@ impl SolverIntegration {
             trade.price
         );
 
-        // Update maker order
+        // update_order_fill takes each order's total fill, like queue_fill_report, not this trade's size
+        let maker_filled = self.order_filled_total(trade.maker_order_id).await?;
+        let taker_filled = self.order_filled_total(trade.taker_order_id).await?;
+
+        // Update maker order. The solver rejects fills priced past the order's max/min price
+        // and credits the maker rebate on resting orders
         let maker_args = json!({
             "order_id": trade_execution.maker_order_id,
-            "filled_amount": trade_execution.amount
+            "filled_amount": maker_filled.to_string(),
+            "fill_price": trade_execution.price,
+            "is_maker": true
         });
 
         info!("Calling update_order_fill for maker with args: {}", maker_args);
@@ -296,7 +381,9 @@ impl SolverIntegration {
         // Update taker order
         let taker_args = json!({
             "order_id": trade_execution.taker_order_id,
-            "filled_amount": trade_execution.amount
+            "filled_amount": taker_filled.to_string(),
+            "fill_price": trade_execution.price,
+            "is_maker": false
         });
 
         info!("Calling update_order_fill for taker with args: {}", taker_args);
@@ -335,6 +422,13 @@ impl SolverIntegration {
         Ok(())
     }
 
+    /// Total filled so far of a local order, after the trades just matched
+    async fn order_filled_total(&self, order_id: Uuid) -> Result<u128> {
+        self.matching_engine.get_database().get_order(order_id).await?
+            .map(|order| order.filled_size)
+            .ok_or_else(|| anyhow::anyhow!("Order {} not found locally", order_id))
+    }
+
 
     /// Process multiple solver orders in batch (useful for intent batching)
     pub async fn process_solver_orders_batch(&self, orders: Vec<SolverOrder>) -> Result<Vec<Trade>> {
@@ -360,61 +454,41 @@ impl SolverIntegration {
     async fn validate_order_parameters(&self, order: &Order) -> Result<()> {
         // 1. Validate market exists
         if order.market_id.is_empty() {
-            return Err(anyhow::anyhow!("Market ID cannot be empty"));
+            return Err(ApiError::InvalidRequest("Market ID cannot be empty".to_string()).into());
         }
 
         // 2. Validate user account
         if order.user_account.is_empty() {
-            return Err(anyhow::anyhow!("User account cannot be empty"));
+            return Err(ApiError::InvalidRequest("User account cannot be empty".to_string()).into());
         }
 
         // 3. Validate outcome (binary market: 0 or 1)
         if order.outcome > 1 {
-            return Err(anyhow::anyhow!("Outcome must be 0 (NO) or 1 (YES) for binary markets"));
+            return Err(ApiError::InvalidRequest("Outcome must be 0 (NO) or 1 (YES) for binary markets".to_string()).into());
         }
 
         // 4. Validate order size
         if order.original_size == 0 {
-            return Err(anyhow::anyhow!("Order size must be greater than 0"));
+            return Err(ApiError::InvalidRequest("Order size must be greater than 0".to_string()).into());
         }
 
         // 5. Validate price based on order type (new format: 0-100000, where 100000 = $1.00)
         match order.order_type {
-            OrderType::Limit | OrderType::GTC | OrderType::GTD => {
-                if order.price == 0 {
-                    return Err(anyhow::anyhow!("Limit/GTC/GTD orders cannot have zero price - use Market order instead"));
-                }
-                if order.price > 100000 {
-                    return Err(anyhow::anyhow!("Price cannot exceed 100000 ($1.00)"));
-                }
-            }
-            OrderType::FOK | OrderType::FAK => {
-                if order.price == 0 {
-                    return Err(anyhow::anyhow!("FOK/FAK orders must specify a price"));
-                }
-                if order.price > 100000 {
-                    return Err(anyhow::anyhow!("Price cannot exceed 100000 ($1.00)"));
+            OrderType::Limit | OrderType::GTC | OrderType::GTD | OrderType::FOK | OrderType::FAK => {
+                if order.price == 0 || order.price > 100000 {
+                    return Err(ApiError::PriceOutOfBand { price: order.price, min: 1, max: 100000 }.into());
                 }
             }
             OrderType::Market => {
                 if order.price != 0 {
-                    return Err(anyhow::anyhow!("Market orders should not specify a price (should be 0)"));
+                    return Err(ApiError::InvalidRequest("Market orders should not specify a price (should be 0)".to_string()).into());
                 }
             }
         }
 
-        // 6. Validate condition ID exists for the market
-        match self.near_client.get_market_condition_id(&order.market_id).await {
-            Ok(Some(_)) => {
-                info!("✅ Market {} has valid condition ID", order.market_id);
-            }
-            Ok(None) => {
-                return Err(anyhow::anyhow!("Market {} does not have a registered condition ID", order.market_id));
-            }
-            Err(e) => {
-                return Err(anyhow::anyhow!("Failed to validate market {}: {}", order.market_id, e));
-            }
-        }
+        // 6. Validate the market is registered and not paused
+        resolve_active_condition(self.matching_engine.get_database().as_ref(), &order.market_id).await?;
+        info!("✅ Market {} has valid condition ID", order.market_id);
 
         info!("✅ Order parameters validated successfully for order {}", order.order_id);
         Ok(())
@@ -425,23 +499,21 @@ impl SolverIntegration {
         let collateral_manager = self.matching_engine.get_collateral_manager();
 
         // Check and reserve balance in one atomic operation
-        let balance_sufficient = collateral_manager
-            .check_and_reserve_balance(order)
-            .await
-            .map_err(|e| anyhow::anyhow!("Balance check and reservation failed: {}", e))?;
-
-        if !balance_sufficient {
-            return Err(anyhow::anyhow!(
-                "Insufficient balance for order: user {} needs {} tokens for {} {} order in market {}",
-                order.user_account,
+        // Keep the ApiError intact so the solver can tell "needs funds" from "RPC down"
+        if let Err(e) = collateral_manager.check_and_reserve_balance(order).await {
+            error!(
+                "Balance check failed for {} {} {} order by {} in market {}: {}",
                 order.original_size,
                 if order.outcome == 1 { "YES" } else { "NO" },
                 match order.side {
                     OrderSide::Buy => "buy",
                     OrderSide::Sell => "sell"
                 },
-                order.market_id
-            ));
+                order.user_account,
+                order.market_id,
+                e
+            );
+            return Err(e);
         }
 
         info!("✅ Balance validated and reserved for order {} (user: {}, amount: {})",
@@ -505,42 +577,65 @@ pub mod api {
     use super::*;
     use axum::{
         extract::{Path, State},
-        http::StatusCode,
+        http::{HeaderMap, StatusCode},
         response::IntoResponse,
         Json,
     };
+    use crate::idempotency::{self, IDEMPOTENCY_KEY_HEADER};
     use crate::AppState;
 
-    // Submit order from solver contract
+    fn trades_response(trades: &[Trade]) -> serde_json::Value {
+        json!({
+            "success": true,
+            "trades_generated": trades.len(),
+            "trades": trades.iter().map(|t| json!({
+                "trade_id": t.trade_id,
+                "price": t.price,
+                "size": t.size,
+                "maker": t.maker_account,
+                "taker": t.taker_account
+            })).collect::<Vec<_>>()
+        })
+    }
+
+    // Submit order from solver contract. Deduplicated by the solver's order id (or an
+    // Idempotency-Key header); a replay answers 200 too since the relayer only checks for 200
     pub async fn submit_solver_order(
         State(app_state): State<AppState>,
+        headers: HeaderMap,
         Json(order): Json<SolverOrder>,
     ) -> impl IntoResponse {
-        match app_state.solver_integration.process_solver_order(order).await {
-            Ok(trades) => (
-                StatusCode::OK,
-                Json(json!({
-                    "success": true,
-                    "trades_generated": trades.len(),
-                    "trades": trades.iter().map(|t| json!({
-                        "trade_id": t.trade_id,
-                        "price": t.price,
-                        "size": t.size,
-                        "maker": t.maker_account,
-                        "taker": t.taker_account
-                    })).collect::<Vec<_>>()
-                }))
-            ).into_response(),
-            Err(e) => {
-                error!("Failed to process solver order: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({
-                        "success": false,
-                        "error": e.to_string()
-                    }))
-                ).into_response()
+        let header_key = headers.get(IDEMPOTENCY_KEY_HEADER).and_then(|value| value.to_str().ok());
+        let key = match idempotency::client_key(header_key) {
+            Ok(key) => key.unwrap_or_else(|| order.order_id.clone()),
+            Err(e) => return e.into_response(),
+        };
+        let payload_hash = match idempotency::payload_hash(&order) {
+            Ok(hash) => hash,
+            Err(e) => return e.into_response(),
+        };
+
+        let database = app_state.database.clone();
+        let account_id = order.user.clone();
+        let ttl_secs = app_state.config.idempotency_key_ttl_secs;
+        let submitted = idempotency::submit_once(database.as_ref(), &account_id, &key, payload_hash, ttl_secs, |order_id| async move {
+            // Throttle by the end user, not the relayer that forwards every solver order
+            app_state.rate_limiter
+                .check_account(app_state.database.as_ref(), &order.user, &order.market_id)
+                .await?;
+
+            match app_state.solver_integration.process_solver_order_as(order, order_id).await {
+                Ok(trades) => Ok(trades_response(&trades)),
+                Err(e) => {
+                    error!("Failed to process solver order: {}", e);
+                    Err(ApiError::from(e))
+                }
             }
+        }).await;
+
+        match submitted {
+            Ok(submitted) => (StatusCode::OK, Json(submitted.response)).into_response(),
+            Err(e) => e.into_response(),
         }
     }
 
@@ -553,12 +648,7 @@ pub mod api {
             Ok(liquidity) => (StatusCode::OK, Json(liquidity)).into_response(),
             Err(e) => {
                 error!("Failed to get market liquidity: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({
-                        "error": e.to_string()
-                    }))
-                ).into_response()
+                ApiError::from(e).into_response()
             }
         }
     }
@@ -580,12 +670,7 @@ pub mod api {
             ).into_response(),
             Err(e) => {
                 error!("Failed to get market price: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({
-                        "error": e.to_string()
-                    }))
-                ).into_response()
+                ApiError::from(e).into_response()
             }
         }
     }}
@@ -2,11 +2,13 @@
 // Preserves existing functionality while adding PostgreSQL support
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use std::sync::Arc;
 use tracing::{info, error, warn};
 
 use super::{Database, SimplePostgresDatabase};
-use crate::types::{Order, Trade, SettlementStatus, CollateralBalance, CollateralReservation, OrderbookSnapshot, MarketPrice};
+use crate::types::{Order, Trade, SettlementStatus, CollateralBalance, CollateralReservation, OrderbookSnapshot, MarketPrice, MarketConditionRecord, OHLCV, ApiKey, ReconciliationReport, FeeLedgerEntry, MarketFeeSummary, IdempotencyRecord, YieldDeposit};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Debug)]
@@ -24,6 +26,10 @@ pub trait DatabaseTrait: Send + Sync {
     async fn get_order(&self, order_id: Uuid) -> Result<Option<Order>>;
     async fn get_active_orders(&self) -> Result<Vec<Order>>;
     async fn get_expired_orders(&self) -> Result<Vec<Order>>;
+    async fn get_purgeable_orders(&self, before: DateTime<Utc>) -> Result<Vec<Order>>;
+    async fn purge_expired_orders(&self, before: DateTime<Utc>) -> Result<Vec<Order>>;
+    async fn count_open_orders(&self, account_id: &str, market_id: &str) -> Result<usize>;
+    async fn get_recent_order_times(&self, account_id: &str, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>>;
 
     // Orderbook queries (enhanced for PostgreSQL)
     async fn get_orderbook_snapshot(&self, market_id: &str, outcome: u8) -> Result<Option<OrderbookSnapshot>>;
@@ -42,12 +48,50 @@ pub trait DatabaseTrait: Send + Sync {
     async fn get_settled_trades_for_condition(&self, condition_id: &str) -> Result<Vec<Trade>>;
     async fn get_trade_settlement_status(&self, trade_id: Uuid) -> Result<SettlementStatus>;
 
+    // Price history
+    async fn get_price_candles(&self, market_id: &str, outcome: u8, from: DateTime<Utc>, to: DateTime<Utc>, bucket_seconds: u64) -> Result<Vec<OHLCV>>;
+    async fn compact_trades_before(&self, cutoff: DateTime<Utc>) -> Result<usize>;
+
     // Collateral operations
     async fn get_collateral_balance(&self, account_id: &str, market_id: &str) -> Result<Option<CollateralBalance>>;
     async fn update_collateral_balance(&self, balance: &CollateralBalance) -> Result<()>;
     async fn store_collateral_reservation(&self, reservation: &CollateralReservation) -> Result<()>;
     async fn get_collateral_reservation(&self, order_id: Uuid) -> Result<Option<CollateralReservation>>;
     async fn remove_collateral_reservation(&self, order_id: Uuid) -> Result<()>;
+
+    // Market registry (market_id -> condition_id)
+    async fn upsert_market_condition(&self, record: &MarketConditionRecord) -> Result<()>;
+    async fn get_market_condition_id(&self, market_id: &str) -> Result<Option<String>>;
+    async fn get_market_condition(&self, market_id: &str) -> Result<Option<MarketConditionRecord>>;
+    async fn get_registered_markets(&self) -> Result<Vec<MarketConditionRecord>>;
+
+    // API keys
+    async fn insert_api_key(&self, key: &ApiKey) -> Result<()>;
+    async fn get_api_key(&self, key_id: &str) -> Result<Option<ApiKey>>;
+    async fn record_api_key_use(&self, key_id: &str, used_at: DateTime<Utc>) -> Result<()>;
+
+    // Chain reconciliation
+    async fn link_solver_order(&self, order_id: Uuid, solver_order_id: &str) -> Result<()>;
+    async fn get_solver_order_links(&self) -> Result<HashMap<Uuid, String>>;
+    async fn insert_reconciliation_report(&self, report: &ReconciliationReport) -> Result<()>;
+    async fn get_latest_reconciliation_report(&self) -> Result<Option<ReconciliationReport>>;
+
+    // Platform fee ledger
+    async fn record_trade_fees(&self, entry: &FeeLedgerEntry) -> Result<bool>;
+    async fn get_fee_summary(&self) -> Result<Vec<MarketFeeSummary>>;
+    async fn get_unswept_fees(&self) -> Result<Vec<FeeLedgerEntry>>;
+    async fn mark_fees_swept(&self, trade_ids: &[Uuid], sweep_id: Uuid) -> Result<usize>;
+
+    // Collateral yield principal and shares per user
+    async fn get_yield_deposits(&self) -> Result<HashMap<String, YieldDeposit>>;
+    async fn credit_yield_deposit(&self, account_id: &str, amount: u128, shares: u128) -> Result<YieldDeposit>;
+    async fn debit_yield_deposit(&self, account_id: &str, amount: u128, shares: u128) -> Result<YieldDeposit>;
+
+    // Idempotent order submission
+    async fn claim_idempotency_key(&self, record: &IdempotencyRecord, expired_before: DateTime<Utc>) -> Result<Option<IdempotencyRecord>>;
+    async fn get_idempotency_record(&self, idempotency_key: &str) -> Result<Option<IdempotencyRecord>>;
+    async fn complete_idempotency_key(&self, idempotency_key: &str, response: &serde_json::Value) -> Result<()>;
+    async fn release_idempotency_key(&self, idempotency_key: &str) -> Result<()>;
 }
 
 // Implement trait for in-memory Database
@@ -73,6 +117,22 @@ impl DatabaseTrait for Database {
         self.get_expired_orders().await
     }
 
+    async fn get_purgeable_orders(&self, before: DateTime<Utc>) -> Result<Vec<Order>> {
+        self.get_purgeable_orders(before).await
+    }
+
+    async fn purge_expired_orders(&self, before: DateTime<Utc>) -> Result<Vec<Order>> {
+        self.purge_expired_orders(before).await
+    }
+
+    async fn count_open_orders(&self, account_id: &str, market_id: &str) -> Result<usize> {
+        self.count_open_orders(account_id, market_id).await
+    }
+
+    async fn get_recent_order_times(&self, account_id: &str, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>> {
+        self.get_recent_order_times(account_id, since).await
+    }
+
     // For in-memory, implement basic orderbook snapshot from active orders
     async fn get_orderbook_snapshot(&self, market_id: &str, outcome: u8) -> Result<Option<OrderbookSnapshot>> {
         let orders = self.get_active_orders().await?;
@@ -121,6 +181,8 @@ impl DatabaseTrait for Database {
             asks: ask_levels,
             last_trade_price: None, // Not tracked in in-memory
             timestamp: chrono::Utc::now(),
+            degraded: false,
+            staleness_secs: None,
         }))
     }
 
@@ -137,6 +199,8 @@ impl DatabaseTrait for Database {
                 mid: None, // Could calculate if both bid and ask exist
                 last: s.last_trade_price,
                 timestamp: chrono::Utc::now(),
+                degraded: false,
+                staleness_secs: None,
             })),
             None => Ok(None),
         }
@@ -178,6 +242,14 @@ impl DatabaseTrait for Database {
         self.get_trade_settlement_status(trade_id).await
     }
 
+    async fn get_price_candles(&self, market_id: &str, outcome: u8, from: DateTime<Utc>, to: DateTime<Utc>, bucket_seconds: u64) -> Result<Vec<OHLCV>> {
+        self.get_price_candles(market_id, outcome, from, to, bucket_seconds).await
+    }
+
+    async fn compact_trades_before(&self, cutoff: DateTime<Utc>) -> Result<usize> {
+        self.compact_trades_before(cutoff).await
+    }
+
     async fn get_collateral_balance(&self, account_id: &str, market_id: &str) -> Result<Option<CollateralBalance>> {
         self.get_collateral_balance(account_id, market_id).await
     }
@@ -197,6 +269,94 @@ impl DatabaseTrait for Database {
     async fn remove_collateral_reservation(&self, order_id: Uuid) -> Result<()> {
         self.remove_collateral_reservation(order_id).await
     }
+
+    async fn upsert_market_condition(&self, record: &MarketConditionRecord) -> Result<()> {
+        self.upsert_market_condition(record).await
+    }
+
+    async fn get_market_condition_id(&self, market_id: &str) -> Result<Option<String>> {
+        self.get_market_condition_id(market_id).await
+    }
+
+    async fn get_market_condition(&self, market_id: &str) -> Result<Option<MarketConditionRecord>> {
+        self.get_market_condition(market_id).await
+    }
+
+    async fn get_registered_markets(&self) -> Result<Vec<MarketConditionRecord>> {
+        self.get_registered_markets().await
+    }
+
+    async fn insert_api_key(&self, key: &ApiKey) -> Result<()> {
+        self.insert_api_key(key).await
+    }
+
+    async fn get_api_key(&self, key_id: &str) -> Result<Option<ApiKey>> {
+        self.get_api_key(key_id).await
+    }
+
+    async fn record_api_key_use(&self, key_id: &str, used_at: DateTime<Utc>) -> Result<()> {
+        self.record_api_key_use(key_id, used_at).await
+    }
+
+    async fn link_solver_order(&self, order_id: Uuid, solver_order_id: &str) -> Result<()> {
+        self.link_solver_order(order_id, solver_order_id).await
+    }
+
+    async fn get_solver_order_links(&self) -> Result<HashMap<Uuid, String>> {
+        self.get_solver_order_links().await
+    }
+
+    async fn insert_reconciliation_report(&self, report: &ReconciliationReport) -> Result<()> {
+        self.insert_reconciliation_report(report).await
+    }
+
+    async fn get_latest_reconciliation_report(&self) -> Result<Option<ReconciliationReport>> {
+        self.get_latest_reconciliation_report().await
+    }
+
+    async fn record_trade_fees(&self, entry: &FeeLedgerEntry) -> Result<bool> {
+        self.record_trade_fees(entry).await
+    }
+
+    async fn get_fee_summary(&self) -> Result<Vec<MarketFeeSummary>> {
+        self.get_fee_summary().await
+    }
+
+    async fn get_unswept_fees(&self) -> Result<Vec<FeeLedgerEntry>> {
+        self.get_unswept_fees().await
+    }
+
+    async fn mark_fees_swept(&self, trade_ids: &[Uuid], sweep_id: Uuid) -> Result<usize> {
+        self.mark_fees_swept(trade_ids, sweep_id).await
+    }
+
+    async fn get_yield_deposits(&self) -> Result<HashMap<String, YieldDeposit>> {
+        self.get_yield_deposits().await
+    }
+
+    async fn credit_yield_deposit(&self, account_id: &str, amount: u128, shares: u128) -> Result<YieldDeposit> {
+        self.credit_yield_deposit(account_id, amount, shares).await
+    }
+
+    async fn debit_yield_deposit(&self, account_id: &str, amount: u128, shares: u128) -> Result<YieldDeposit> {
+        self.debit_yield_deposit(account_id, amount, shares).await
+    }
+
+    async fn claim_idempotency_key(&self, record: &IdempotencyRecord, expired_before: DateTime<Utc>) -> Result<Option<IdempotencyRecord>> {
+        self.claim_idempotency_key(record, expired_before).await
+    }
+
+    async fn get_idempotency_record(&self, idempotency_key: &str) -> Result<Option<IdempotencyRecord>> {
+        self.get_idempotency_record(idempotency_key).await
+    }
+
+    async fn complete_idempotency_key(&self, idempotency_key: &str, response: &serde_json::Value) -> Result<()> {
+        self.complete_idempotency_key(idempotency_key, response).await
+    }
+
+    async fn release_idempotency_key(&self, idempotency_key: &str) -> Result<()> {
+        self.release_idempotency_key(idempotency_key).await
+    }
 }
 
 // Implement trait for SimplePostgresDatabase
@@ -222,6 +382,22 @@ impl DatabaseTrait for SimplePostgresDatabase {
         self.get_expired_orders().await
     }
 
+    async fn get_purgeable_orders(&self, before: DateTime<Utc>) -> Result<Vec<Order>> {
+        self.get_purgeable_orders(before).await
+    }
+
+    async fn purge_expired_orders(&self, before: DateTime<Utc>) -> Result<Vec<Order>> {
+        self.purge_expired_orders(before).await
+    }
+
+    async fn count_open_orders(&self, account_id: &str, market_id: &str) -> Result<usize> {
+        self.count_open_orders(account_id, market_id).await
+    }
+
+    async fn get_recent_order_times(&self, account_id: &str, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>> {
+        self.get_recent_order_times(account_id, since).await
+    }
+
     async fn get_orderbook_snapshot(&self, market_id: &str, outcome: u8) -> Result<Option<OrderbookSnapshot>> {
         self.get_orderbook_snapshot(market_id, outcome).await
     }
@@ -266,6 +442,14 @@ impl DatabaseTrait for SimplePostgresDatabase {
         self.get_trade_settlement_status(trade_id).await
     }
 
+    async fn get_price_candles(&self, market_id: &str, outcome: u8, from: DateTime<Utc>, to: DateTime<Utc>, bucket_seconds: u64) -> Result<Vec<OHLCV>> {
+        self.get_price_candles(market_id, outcome, from, to, bucket_seconds).await
+    }
+
+    async fn compact_trades_before(&self, cutoff: DateTime<Utc>) -> Result<usize> {
+        self.compact_trades_before(cutoff).await
+    }
+
     async fn get_collateral_balance(&self, account_id: &str, market_id: &str) -> Result<Option<CollateralBalance>> {
         self.get_collateral_balance(account_id, market_id).await
     }
@@ -285,6 +469,94 @@ impl DatabaseTrait for SimplePostgresDatabase {
     async fn remove_collateral_reservation(&self, order_id: Uuid) -> Result<()> {
         self.remove_collateral_reservation(order_id).await
     }
+
+    async fn upsert_market_condition(&self, record: &MarketConditionRecord) -> Result<()> {
+        self.upsert_market_condition(record).await
+    }
+
+    async fn get_market_condition_id(&self, market_id: &str) -> Result<Option<String>> {
+        self.get_market_condition_id(market_id).await
+    }
+
+    async fn get_market_condition(&self, market_id: &str) -> Result<Option<MarketConditionRecord>> {
+        self.get_market_condition(market_id).await
+    }
+
+    async fn get_registered_markets(&self) -> Result<Vec<MarketConditionRecord>> {
+        self.get_registered_markets().await
+    }
+
+    async fn insert_api_key(&self, key: &ApiKey) -> Result<()> {
+        self.insert_api_key(key).await
+    }
+
+    async fn get_api_key(&self, key_id: &str) -> Result<Option<ApiKey>> {
+        self.get_api_key(key_id).await
+    }
+
+    async fn record_api_key_use(&self, key_id: &str, used_at: DateTime<Utc>) -> Result<()> {
+        self.record_api_key_use(key_id, used_at).await
+    }
+
+    async fn link_solver_order(&self, order_id: Uuid, solver_order_id: &str) -> Result<()> {
+        self.link_solver_order(order_id, solver_order_id).await
+    }
+
+    async fn get_solver_order_links(&self) -> Result<HashMap<Uuid, String>> {
+        self.get_solver_order_links().await
+    }
+
+    async fn insert_reconciliation_report(&self, report: &ReconciliationReport) -> Result<()> {
+        self.insert_reconciliation_report(report).await
+    }
+
+    async fn get_latest_reconciliation_report(&self) -> Result<Option<ReconciliationReport>> {
+        self.get_latest_reconciliation_report().await
+    }
+
+    async fn record_trade_fees(&self, entry: &FeeLedgerEntry) -> Result<bool> {
+        self.record_trade_fees(entry).await
+    }
+
+    async fn get_fee_summary(&self) -> Result<Vec<MarketFeeSummary>> {
+        self.get_fee_summary().await
+    }
+
+    async fn get_unswept_fees(&self) -> Result<Vec<FeeLedgerEntry>> {
+        self.get_unswept_fees().await
+    }
+
+    async fn mark_fees_swept(&self, trade_ids: &[Uuid], sweep_id: Uuid) -> Result<usize> {
+        self.mark_fees_swept(trade_ids, sweep_id).await
+    }
+
+    async fn get_yield_deposits(&self) -> Result<HashMap<String, YieldDeposit>> {
+        self.get_yield_deposits().await
+    }
+
+    async fn credit_yield_deposit(&self, account_id: &str, amount: u128, shares: u128) -> Result<YieldDeposit> {
+        self.credit_yield_deposit(account_id, amount, shares).await
+    }
+
+    async fn debit_yield_deposit(&self, account_id: &str, amount: u128, shares: u128) -> Result<YieldDeposit> {
+        self.debit_yield_deposit(account_id, amount, shares).await
+    }
+
+    async fn claim_idempotency_key(&self, record: &IdempotencyRecord, expired_before: DateTime<Utc>) -> Result<Option<IdempotencyRecord>> {
+        self.claim_idempotency_key(record, expired_before).await
+    }
+
+    async fn get_idempotency_record(&self, idempotency_key: &str) -> Result<Option<IdempotencyRecord>> {
+        self.get_idempotency_record(idempotency_key).await
+    }
+
+    async fn complete_idempotency_key(&self, idempotency_key: &str, response: &serde_json::Value) -> Result<()> {
+        self.complete_idempotency_key(idempotency_key, response).await
+    }
+
+    async fn release_idempotency_key(&self, idempotency_key: &str) -> Result<()> {
+        self.release_idempotency_key(idempotency_key).await
+    }
 }
 
 // Removed unused imports
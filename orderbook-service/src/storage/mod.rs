@@ -2,11 +2,11 @@
 
 use uuid::Uuid;
 use anyhow::{anyhow, Result};
-use chrono::Utc;
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::RwLock;
 
-use crate::types::{Order, Trade, SettlementStatus, CollateralBalance, CollateralReservation};
+use crate::types::{Order, Trade, SettlementStatus, CollateralBalance, CollateralReservation, MarketConditionRecord, OHLCV, ApiKey, ReconciliationReport, FeeLedgerEntry, MarketFeeSummary, IdempotencyRecord, YieldDeposit};
 
 // Simplified PostgreSQL implementation (runtime queries)
 pub mod simple_postgres;
@@ -16,6 +16,9 @@ pub use simple_postgres::SimplePostgresDatabase;
 pub mod factory;
 pub use factory::{DatabaseTrait, create_database, create_test_database};
 
+// Old trades folded into hourly candles
+pub mod retention;
+
 // Simple in-memory database for testing
 pub struct Database {
     orders: RwLock<HashMap<Uuid, Order>>,
@@ -23,6 +26,21 @@ pub struct Database {
     // Polymarket-style collateral storage
     collateral_balances: RwLock<HashMap<String, CollateralBalance>>, // key: "account:market"
     collateral_reservations: RwLock<HashMap<Uuid, CollateralReservation>>, // key: order_id
+    // Market registry
+    market_conditions: RwLock<HashMap<String, MarketConditionRecord>>, // key: market_id
+    // Hourly candles of compacted trades
+    trade_candles: RwLock<BTreeMap<(String, u8, u64), OHLCV>>, // key: (market_id, outcome, hour start)
+    // Market maker API keys
+    api_keys: RwLock<HashMap<String, ApiKey>>, // key: key_id
+    // Chain reconciliation
+    solver_order_links: RwLock<HashMap<Uuid, String>>, // order_id -> solver contract order_id
+    reconciliation_reports: RwLock<Vec<ReconciliationReport>>, // oldest first
+    // Platform fee ledger
+    platform_fees: RwLock<HashMap<Uuid, FeeLedgerEntry>>, // key: trade_id
+    // USDC principal each user has in the yield protocol
+    yield_deposits: RwLock<HashMap<String, YieldDeposit>>, // key: account_id
+    // Order submissions by idempotency key
+    idempotency_keys: RwLock<HashMap<String, IdempotencyRecord>>, // key: "account:client key"
 }
 
 impl Database {
@@ -32,6 +50,14 @@ impl Database {
             trades: RwLock::new(HashMap::new()),
             collateral_balances: RwLock::new(HashMap::new()),
             collateral_reservations: RwLock::new(HashMap::new()),
+            market_conditions: RwLock::new(HashMap::new()),
+            trade_candles: RwLock::new(BTreeMap::new()),
+            api_keys: RwLock::new(HashMap::new()),
+            solver_order_links: RwLock::new(HashMap::new()),
+            reconciliation_reports: RwLock::new(Vec::new()),
+            platform_fees: RwLock::new(HashMap::new()),
+            yield_deposits: RwLock::new(HashMap::new()),
+            idempotency_keys: RwLock::new(HashMap::new()),
         })
     }
 
@@ -85,6 +111,62 @@ impl Database {
             .collect())
     }
 
+    /// Orders whose TTL passed before `before`, excluding filled/cancelled ones and any
+    /// order a trade still points at
+    pub async fn get_purgeable_orders(&self, before: DateTime<Utc>) -> Result<Vec<Order>> {
+        let orders = self.orders.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on orders: {}", e))?;
+        let trades = self.trades.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on trades: {}", e))?;
+
+        let mut purgeable: Vec<Order> = orders.values()
+            .filter(|o| o.expires_at.is_some_and(|expires_at| expires_at < before))
+            .filter(|o| !matches!(o.status, crate::types::OrderStatus::Filled | crate::types::OrderStatus::Cancelled))
+            .filter(|o| !trades.values().any(|t| t.maker_order_id == o.order_id || t.taker_order_id == o.order_id))
+            .cloned()
+            .collect();
+        purgeable.sort_by_key(|o| o.expires_at);
+        Ok(purgeable)
+    }
+
+    /// Delete the orders get_purgeable_orders would return, with their collateral reservations
+    pub async fn purge_expired_orders(&self, before: DateTime<Utc>) -> Result<Vec<Order>> {
+        let purgeable = self.get_purgeable_orders(before).await?;
+
+        let mut orders = self.orders.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on orders: {}", e))?;
+        let mut reservations = self.collateral_reservations.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on collateral reservations: {}", e))?;
+        for order in &purgeable {
+            orders.remove(&order.order_id);
+            reservations.remove(&order.order_id);
+        }
+
+        Ok(purgeable)
+    }
+
+    /// Resting orders an account has in one market
+    pub async fn count_open_orders(&self, account_id: &str, market_id: &str) -> Result<usize> {
+        let orders = self.orders.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on orders: {}", e))?;
+        Ok(orders.values()
+            .filter(|o| o.user_account == account_id && o.market_id == market_id)
+            .filter(|o| matches!(o.status, crate::types::OrderStatus::Pending | crate::types::OrderStatus::PartiallyFilled))
+            .count())
+    }
+
+    /// Creation times of an account's orders since `since`, oldest first
+    pub async fn get_recent_order_times(&self, account_id: &str, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>> {
+        let orders = self.orders.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on orders: {}", e))?;
+        let mut times: Vec<DateTime<Utc>> = orders.values()
+            .filter(|o| o.user_account == account_id && o.created_at >= since)
+            .map(|o| o.created_at)
+            .collect();
+        times.sort();
+        Ok(times)
+    }
+
     pub async fn insert_trade(&self, trade: &Trade) -> Result<()> {
         let mut trades = self.trades.write()
             .map_err(|e| anyhow!("Failed to acquire write lock on trades: {}", e))?;
@@ -159,6 +241,71 @@ impl Database {
             .collect())
     }
 
+    /// Candles over [from, to) from raw trades plus compacted hourly candles
+    pub async fn get_price_candles(
+        &self,
+        market_id: &str,
+        outcome: u8,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        bucket_seconds: u64,
+    ) -> Result<Vec<OHLCV>> {
+        let (from_secs, to_secs) = (from.timestamp().max(0) as u64, to.timestamp().max(0) as u64);
+        let mut points: Vec<OHLCV> = {
+            let candles = self.trade_candles.read()
+                .map_err(|e| anyhow!("Failed to acquire read lock on trade candles: {}", e))?;
+            candles.iter()
+                .filter(|((m, o, start), _)| m == market_id && *o == outcome && *start >= from_secs && *start < to_secs)
+                .map(|(_, candle)| candle.clone())
+                .collect()
+        };
+
+        let trades = self.trades.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on trades: {}", e))?;
+        let mut market_trades: Vec<&Trade> = trades.values()
+            .filter(|t| t.market_id == market_id && t.outcome == outcome && t.executed_at >= from && t.executed_at < to)
+            .collect();
+        market_trades.sort_by_key(|t| t.executed_at);
+        points.extend(market_trades.into_iter().map(OHLCV::from_trade));
+        points.sort_by_key(|p| p.timestamp);
+
+        Ok(OHLCV::aggregate(points, bucket_seconds))
+    }
+
+    /// Fold settled trades executed before `cutoff` into hourly candles and drop them.
+    /// Returns the number of hourly candles written
+    pub async fn compact_trades_before(&self, cutoff: DateTime<Utc>) -> Result<usize> {
+        let mut trades = self.trades.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on trades: {}", e))?;
+        let mut old: Vec<Trade> = trades.values()
+            .filter(|t| t.executed_at < cutoff && matches!(t.settlement_status, SettlementStatus::Settled))
+            .cloned()
+            .collect();
+        old.sort_by_key(|t| t.executed_at);
+
+        let mut candles = self.trade_candles.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on trade candles: {}", e))?;
+        let mut touched = std::collections::HashSet::new();
+        for trade in old {
+            trades.remove(&trade.trade_id);
+            let print = OHLCV::from_trade(&trade);
+            let hour = print.timestamp - print.timestamp % 3600;
+            let key = (trade.market_id.clone(), trade.outcome, hour);
+            touched.insert(key.clone());
+
+            candles.entry(key)
+                .and_modify(|candle| {
+                    candle.high = candle.high.max(print.high);
+                    candle.low = candle.low.min(print.low);
+                    candle.close = print.close;
+                    candle.volume += print.volume;
+                })
+                .or_insert(OHLCV { timestamp: hour, ..print });
+        }
+
+        Ok(touched.len())
+    }
+
     pub async fn get_trade_settlement_status(&self, trade_id: Uuid) -> Result<SettlementStatus> {
         let trades = self.trades.read()
             .map_err(|e| anyhow!("Failed to acquire read lock on trades: {}", e))?;
@@ -205,4 +352,239 @@ impl Database {
         reservations.remove(&order_id);
         Ok(())
     }
-}
\ No newline at end of file
+
+    // ================================
+    // MARKET REGISTRY
+    // ================================
+
+    pub async fn upsert_market_condition(&self, record: &MarketConditionRecord) -> Result<()> {
+        let mut markets = self.market_conditions.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on market conditions: {}", e))?;
+        let registered_at = markets.get(&record.market_id)
+            .map(|existing| existing.registered_at)
+            .unwrap_or(record.registered_at);
+        let mut stored = record.clone();
+        stored.registered_at = registered_at;
+        markets.insert(record.market_id.clone(), stored);
+        Ok(())
+    }
+
+    pub async fn get_market_condition_id(&self, market_id: &str) -> Result<Option<String>> {
+        let markets = self.market_conditions.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on market conditions: {}", e))?;
+        Ok(markets.get(market_id).map(|m| m.condition_id.clone()))
+    }
+
+    pub async fn get_market_condition(&self, market_id: &str) -> Result<Option<MarketConditionRecord>> {
+        let markets = self.market_conditions.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on market conditions: {}", e))?;
+        Ok(markets.get(market_id).cloned())
+    }
+
+    pub async fn get_registered_markets(&self) -> Result<Vec<MarketConditionRecord>> {
+        let markets = self.market_conditions.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on market conditions: {}", e))?;
+        let mut records: Vec<MarketConditionRecord> = markets.values().cloned().collect();
+        records.sort_by(|a, b| b.registered_at.cmp(&a.registered_at));
+        Ok(records)
+    }
+
+    // ================================
+    // API KEYS
+    // ================================
+
+    pub async fn insert_api_key(&self, key: &ApiKey) -> Result<()> {
+        let mut keys = self.api_keys.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on api keys: {}", e))?;
+        keys.insert(key.key_id.clone(), key.clone());
+        Ok(())
+    }
+
+    pub async fn get_api_key(&self, key_id: &str) -> Result<Option<ApiKey>> {
+        let keys = self.api_keys.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on api keys: {}", e))?;
+        Ok(keys.get(key_id).cloned())
+    }
+
+    /// Bump request_count and last_used_at for an authenticated request
+    pub async fn record_api_key_use(&self, key_id: &str, used_at: DateTime<Utc>) -> Result<()> {
+        let mut keys = self.api_keys.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on api keys: {}", e))?;
+        if let Some(key) = keys.get_mut(key_id) {
+            key.request_count += 1;
+            key.last_used_at = Some(used_at);
+        }
+        Ok(())
+    }
+
+    // ================================
+    // CHAIN RECONCILIATION
+    // ================================
+
+    pub async fn link_solver_order(&self, order_id: Uuid, solver_order_id: &str) -> Result<()> {
+        let mut links = self.solver_order_links.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on solver order links: {}", e))?;
+        links.insert(order_id, solver_order_id.to_string());
+        Ok(())
+    }
+
+    pub async fn get_solver_order_links(&self) -> Result<HashMap<Uuid, String>> {
+        let links = self.solver_order_links.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on solver order links: {}", e))?;
+        Ok(links.clone())
+    }
+
+    pub async fn insert_reconciliation_report(&self, report: &ReconciliationReport) -> Result<()> {
+        let mut reports = self.reconciliation_reports.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on reconciliation reports: {}", e))?;
+        reports.push(report.clone());
+        Ok(())
+    }
+
+    pub async fn get_latest_reconciliation_report(&self) -> Result<Option<ReconciliationReport>> {
+        let reports = self.reconciliation_reports.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on reconciliation reports: {}", e))?;
+        Ok(reports.last().cloned())
+    }
+
+    // ================================
+    // PLATFORM FEES
+    // ================================
+
+    /// Record a trade's collected fees; false if the trade already has an entry
+    pub async fn record_trade_fees(&self, entry: &FeeLedgerEntry) -> Result<bool> {
+        let mut fees = self.platform_fees.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on platform fees: {}", e))?;
+        if fees.contains_key(&entry.trade_id) {
+            return Ok(false);
+        }
+        fees.insert(entry.trade_id, entry.clone());
+        Ok(true)
+    }
+
+    pub async fn get_fee_summary(&self) -> Result<Vec<MarketFeeSummary>> {
+        let fees = self.platform_fees.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on platform fees: {}", e))?;
+        let mut by_market: BTreeMap<(String, String), MarketFeeSummary> = BTreeMap::new();
+        for entry in fees.values() {
+            let summary = by_market.entry((entry.market_id.clone(), entry.collateral_token.clone()))
+                .or_insert_with(|| MarketFeeSummary {
+                    market_id: entry.market_id.clone(),
+                    collateral_token: entry.collateral_token.clone(),
+                    trade_count: 0,
+                    maker_fees: 0,
+                    taker_fees: 0,
+                    unswept: 0,
+                });
+            summary.trade_count += 1;
+            summary.maker_fees += entry.maker_fee;
+            summary.taker_fees += entry.taker_fee;
+            if entry.sweep_id.is_none() {
+                summary.unswept += entry.maker_fee + entry.taker_fee;
+            }
+        }
+        Ok(by_market.into_values().collect())
+    }
+
+    pub async fn get_unswept_fees(&self) -> Result<Vec<FeeLedgerEntry>> {
+        let fees = self.platform_fees.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on platform fees: {}", e))?;
+        let mut unswept: Vec<FeeLedgerEntry> = fees.values()
+            .filter(|entry| entry.sweep_id.is_none())
+            .cloned()
+            .collect();
+        unswept.sort_by_key(|entry| entry.collected_at);
+        Ok(unswept)
+    }
+
+    /// Mark the given trades' fees as moved by `sweep_id`; returns how many entries changed
+    pub async fn mark_fees_swept(&self, trade_ids: &[Uuid], sweep_id: Uuid) -> Result<usize> {
+        let mut fees = self.platform_fees.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on platform fees: {}", e))?;
+        let mut marked = 0;
+        for trade_id in trade_ids {
+            if let Some(entry) = fees.get_mut(trade_id).filter(|entry| entry.sweep_id.is_none()) {
+                entry.sweep_id = Some(sweep_id);
+                marked += 1;
+            }
+        }
+        Ok(marked)
+    }
+
+    // ================================
+    // COLLATERAL YIELD
+    // ================================
+
+    pub async fn get_yield_deposits(&self) -> Result<HashMap<String, YieldDeposit>> {
+        let deposits = self.yield_deposits.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on yield deposits: {}", e))?;
+        Ok(deposits.clone())
+    }
+
+    /// Add to a user's principal and shares in the yield protocol; returns the new position
+    pub async fn credit_yield_deposit(&self, account_id: &str, amount: u128, shares: u128) -> Result<YieldDeposit> {
+        let mut deposits = self.yield_deposits.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on yield deposits: {}", e))?;
+        let deposit = deposits.entry(account_id.to_string()).or_default();
+        deposit.principal += amount;
+        deposit.shares += shares;
+        Ok(*deposit)
+    }
+
+    /// Take from a user's principal and shares, never below zero; returns the new position.
+    /// A position without shares left is closed
+    pub async fn debit_yield_deposit(&self, account_id: &str, amount: u128, shares: u128) -> Result<YieldDeposit> {
+        let mut deposits = self.yield_deposits.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on yield deposits: {}", e))?;
+        let current = deposits.get(account_id).copied().unwrap_or_default();
+        let remaining = YieldDeposit {
+            principal: current.principal.saturating_sub(amount),
+            shares: current.shares.saturating_sub(shares),
+        };
+        if remaining.shares == 0 {
+            deposits.remove(account_id);
+            return Ok(YieldDeposit::default());
+        }
+        deposits.insert(account_id.to_string(), remaining);
+        Ok(remaining)
+    }
+
+    // ================================
+    // IDEMPOTENCY KEYS
+    // ================================
+
+    /// Store `record` unless its key is already held by a submission made at or after
+    /// `expired_before`; returns that submission instead. Expired keys are dropped on the way
+    pub async fn claim_idempotency_key(&self, record: &IdempotencyRecord, expired_before: DateTime<Utc>) -> Result<Option<IdempotencyRecord>> {
+        let mut keys = self.idempotency_keys.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on idempotency keys: {}", e))?;
+        keys.retain(|_, existing| existing.created_at >= expired_before);
+        if let Some(existing) = keys.get(&record.idempotency_key) {
+            return Ok(Some(existing.clone()));
+        }
+        keys.insert(record.idempotency_key.clone(), record.clone());
+        Ok(None)
+    }
+
+    pub async fn get_idempotency_record(&self, idempotency_key: &str) -> Result<Option<IdempotencyRecord>> {
+        let keys = self.idempotency_keys.read()
+            .map_err(|e| anyhow!("Failed to acquire read lock on idempotency keys: {}", e))?;
+        Ok(keys.get(idempotency_key).cloned())
+    }
+
+    pub async fn complete_idempotency_key(&self, idempotency_key: &str, response: &serde_json::Value) -> Result<()> {
+        let mut keys = self.idempotency_keys.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on idempotency keys: {}", e))?;
+        if let Some(record) = keys.get_mut(idempotency_key) {
+            record.response = Some(response.clone());
+        }
+        Ok(())
+    }
+
+    pub async fn release_idempotency_key(&self, idempotency_key: &str) -> Result<()> {
+        let mut keys = self.idempotency_keys.write()
+            .map_err(|e| anyhow!("Failed to acquire write lock on idempotency keys: {}", e))?;
+        keys.remove(idempotency_key);
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+// Trade retention: settled trades older than the retention window are folded into hourly
+// OHLCV candles and removed from the trades table. Candle queries read both, so price history
+// stays available at hourly resolution after the raw prints are gone.
+
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use chrono::Utc;
+use tracing::{info, error};
+
+use super::DatabaseTrait;
+
+/// Raw trades are kept this long before compaction
+pub const DEFAULT_TRADE_RETENTION_DAYS: i64 = 7;
+
+pub struct TradeRetention {
+    database: Arc<dyn DatabaseTrait>,
+    retention: chrono::Duration,
+    interval: Duration,
+}
+
+impl TradeRetention {
+    pub fn new(database: Arc<dyn DatabaseTrait>) -> Self {
+        let retention_days = std::env::var("TRADE_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(DEFAULT_TRADE_RETENTION_DAYS);
+        let interval_secs = std::env::var("TRADE_COMPACTION_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(3600);
+
+        Self {
+            database,
+            retention: chrono::Duration::days(retention_days),
+            interval: Duration::from_secs(interval_secs),
+        }
+    }
+
+    /// Compact everything past the retention window, returns the number of hourly candles written
+    pub async fn compact_once(&self) -> Result<usize> {
+        self.database.compact_trades_before(Utc::now() - self.retention).await
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        info!(
+            "Trade retention started (keeping {} days of raw trades, compacting every {}s)",
+            self.retention.num_days(), self.interval.as_secs()
+        );
+
+        let mut ticker = tokio::time::interval(self.interval);
+        loop {
+            ticker.tick().await;
+            match self.compact_once().await {
+                Ok(0) => {}
+                Ok(candles) => info!("Trade retention compacted old trades into {} hourly candles", candles),
+                Err(e) => error!("Trade retention failed: {}", e),
+            }
+        }
+    }
+}
@@ -5,14 +5,17 @@ use sqlx::{PgPool, Row, postgres::PgPoolOptions};
 use sqlx::types::BigDecimal;
 use uuid::Uuid;
 use anyhow::{anyhow, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use tracing::info;
 use std::str::FromStr;
 
 use crate::types::{
     Order, Trade, SettlementStatus, CollateralBalance, CollateralReservation,
-    OrderStatus, OrderSide, OrderType, TradeType, OrderbookSnapshot, MarketPrice, PriceLevel
+    OrderStatus, OrderSide, OrderType, TradeType, OrderbookSnapshot, MarketPrice, PriceLevel,
+    MarketConditionRecord, MarketRegistrationSource, OHLCV, ApiKey, ReconciliationReport,
+    FeeLedgerEntry, MarketFeeSummary, IdempotencyRecord, YieldDeposit
 };
+use std::collections::HashMap;
 
 pub struct SimplePostgresDatabase {
     pool: PgPool,
@@ -169,6 +172,81 @@ impl SimplePostgresDatabase {
         Ok(rows.into_iter().map(|r| self.row_to_order(r)).collect())
     }
 
+    /// Orders whose TTL passed before `before` that the purge may delete: not filled or
+    /// cancelled, and not referenced by a trade (the trade foreign keys don't cascade)
+    pub async fn get_purgeable_orders(&self, before: DateTime<Utc>) -> Result<Vec<Order>> {
+        let query = r#"
+            SELECT * FROM orders o
+            WHERE o.expires_at < $1
+              AND o.status NOT IN ('Filled', 'Cancelled')
+              AND NOT EXISTS (
+                  SELECT 1 FROM trades t
+                  WHERE t.maker_order_id = o.order_id OR t.taker_order_id = o.order_id
+              )
+            ORDER BY o.expires_at ASC
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(before)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|r| self.row_to_order(r)).collect())
+    }
+
+    /// Delete purgeable expired orders and return them; collateral reservations cascade
+    pub async fn purge_expired_orders(&self, before: DateTime<Utc>) -> Result<Vec<Order>> {
+        let query = r#"
+            DELETE FROM orders o
+            WHERE o.expires_at < $1
+              AND o.status NOT IN ('Filled', 'Cancelled')
+              AND NOT EXISTS (
+                  SELECT 1 FROM trades t
+                  WHERE t.maker_order_id = o.order_id OR t.taker_order_id = o.order_id
+              )
+            RETURNING o.*
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(before)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|r| self.row_to_order(r)).collect())
+    }
+
+    pub async fn count_open_orders(&self, account_id: &str, market_id: &str) -> Result<usize> {
+        let query = r#"
+            SELECT COUNT(*) as count FROM orders
+            WHERE user_account = $1 AND market_id = $2
+              AND status IN ('Pending', 'PartiallyFilled')
+        "#;
+
+        let row = sqlx::query(query)
+            .bind(account_id)
+            .bind(market_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.get::<i64, _>("count") as usize)
+    }
+
+    pub async fn get_recent_order_times(&self, account_id: &str, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>> {
+        let query = r#"
+            SELECT created_at FROM orders
+            WHERE user_account = $1 AND created_at >= $2
+            ORDER BY created_at ASC
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(account_id)
+            .bind(since)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|r| r.get("created_at")).collect())
+    }
+
     // ================================
     // ENHANCED ORDERBOOK QUERIES (The key improvement!)
     // ================================
@@ -248,6 +326,8 @@ impl SimplePostgresDatabase {
             asks,
             last_trade_price: last_trade_row.map(|r| r.get::<i64, _>("price") as u64),
             timestamp: Utc::now(),
+            degraded: false,
+            staleness_secs: None,
         }))
     }
 
@@ -274,6 +354,8 @@ impl SimplePostgresDatabase {
                     mid: r.get::<Option<i64>, _>("mid_price").map(|m| m as u64),
                     last: r.get::<Option<i64>, _>("last_price").map(|l| l as u64),
                     timestamp: r.get("updated_at"),
+                    degraded: false,
+                    staleness_secs: None,
                 }));
             }
         }
@@ -298,6 +380,8 @@ impl SimplePostgresDatabase {
                     mid,
                     last: s.last_trade_price,
                     timestamp: Utc::now(),
+                    degraded: false,
+                    staleness_secs: None,
                 }))
             }
             None => Ok(None),
@@ -313,8 +397,9 @@ impl SimplePostgresDatabase {
             INSERT INTO trades (
                 trade_id, market_id, condition_id, maker_order_id, taker_order_id,
                 maker_account, taker_account, maker_side, taker_side, outcome,
-                price, size, trade_type, executed_at, settlement_status, settlement_tx_hash
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                price, size, trade_type, executed_at, settlement_status, settlement_tx_hash,
+                maker_fee, taker_fee
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
         "#;
 
         sqlx::query(query)
@@ -334,6 +419,8 @@ impl SimplePostgresDatabase {
             .bind(trade.executed_at)
             .bind(self.settlement_status_to_string(&trade.settlement_status))
             .bind(&trade.settlement_tx_hash)
+            .bind(Self::u128_to_bigdecimal(trade.maker_fee))
+            .bind(Self::u128_to_bigdecimal(trade.taker_fee))
             .execute(&self.pool)
             .await?;
 
@@ -382,6 +469,101 @@ impl SimplePostgresDatabase {
         Ok(rows.into_iter().map(|r| self.row_to_trade(r)).collect())
     }
 
+    // ================================
+    // PRICE HISTORY
+    // ================================
+
+    pub async fn get_price_candles(
+        &self,
+        market_id: &str,
+        outcome: u8,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        bucket_seconds: u64,
+    ) -> Result<Vec<OHLCV>> {
+        // Raw trades and compacted hourly candles, bucketed on epoch-aligned boundaries
+        let query = r#"
+            WITH points AS (
+                SELECT executed_at AS at, price AS open, price AS high, price AS low, price AS close, size AS volume
+                FROM trades
+                WHERE market_id = $1 AND outcome = $2 AND executed_at >= $3 AND executed_at < $4
+                UNION ALL
+                SELECT bucket_start, open, high, low, close, volume
+                FROM trade_candles
+                WHERE market_id = $1 AND outcome = $2 AND bucket_start >= $3 AND bucket_start < $4
+            )
+            SELECT
+                (FLOOR(EXTRACT(EPOCH FROM at) / $5) * $5)::BIGINT AS bucket,
+                (ARRAY_AGG(open ORDER BY at))[1] AS open,
+                MAX(high) AS high,
+                MIN(low) AS low,
+                (ARRAY_AGG(close ORDER BY at DESC))[1] AS close,
+                SUM(volume) AS volume
+            FROM points
+            GROUP BY bucket
+            ORDER BY bucket
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(market_id)
+            .bind(outcome as i16)
+            .bind(from)
+            .bind(to)
+            .bind(bucket_seconds as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|r| OHLCV {
+            timestamp: r.get::<i64, _>("bucket") as u64,
+            open: r.get::<i64, _>("open") as u64,
+            high: r.get::<i64, _>("high") as u64,
+            low: r.get::<i64, _>("low") as u64,
+            close: r.get::<i64, _>("close") as u64,
+            volume: Self::bigdecimal_to_u128(r.get("volume")),
+        }).collect())
+    }
+
+    pub async fn compact_trades_before(&self, cutoff: DateTime<Utc>) -> Result<usize> {
+        // Trades still referenced by a settlement batch are kept until the batch is gone
+        let query = r#"
+            WITH moved AS (
+                DELETE FROM trades t
+                WHERE t.executed_at < $1
+                  AND t.settlement_status = 'Settled'
+                  AND NOT EXISTS (SELECT 1 FROM batch_trades b WHERE b.trade_id = t.trade_id)
+                RETURNING t.market_id, t.outcome, t.price, t.size, t.executed_at
+            )
+            INSERT INTO trade_candles AS c (
+                market_id, outcome, bucket_start, open, high, low, close, volume, trade_count
+            )
+            SELECT
+                market_id,
+                outcome,
+                DATE_TRUNC('hour', executed_at) AS bucket_start,
+                (ARRAY_AGG(price ORDER BY executed_at))[1],
+                MAX(price),
+                MIN(price),
+                (ARRAY_AGG(price ORDER BY executed_at DESC))[1],
+                SUM(size),
+                COUNT(*)
+            FROM moved
+            GROUP BY market_id, outcome, DATE_TRUNC('hour', executed_at)
+            ON CONFLICT (market_id, outcome, bucket_start) DO UPDATE SET
+                high = GREATEST(c.high, EXCLUDED.high),
+                low = LEAST(c.low, EXCLUDED.low),
+                close = EXCLUDED.close,
+                volume = c.volume + EXCLUDED.volume,
+                trade_count = c.trade_count + EXCLUDED.trade_count
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() as usize)
+    }
+
     // ================================
     // TEST-ONLY METHODS (Preserving exact interface)
     // ================================
@@ -553,10 +735,395 @@ impl SimplePostgresDatabase {
         Ok(())
     }
 
+    // ================================
+    // MARKET REGISTRY
+    // ================================
+
+    pub async fn upsert_market_condition(&self, record: &MarketConditionRecord) -> Result<()> {
+        let query = r#"
+            INSERT INTO market_conditions (
+                market_id, condition_id, source, is_active, collateral_token, outcome_count,
+                registered_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (market_id)
+            DO UPDATE SET
+                condition_id = EXCLUDED.condition_id,
+                source = EXCLUDED.source,
+                is_active = EXCLUDED.is_active,
+                collateral_token = EXCLUDED.collateral_token,
+                outcome_count = EXCLUDED.outcome_count,
+                updated_at = EXCLUDED.updated_at
+        "#;
+
+        sqlx::query(query)
+            .bind(&record.market_id)
+            .bind(&record.condition_id)
+            .bind(self.registration_source_to_string(&record.source))
+            .bind(record.is_active)
+            .bind(&record.collateral_token)
+            .bind(record.outcome_count as i16)
+            .bind(record.registered_at)
+            .bind(record.updated_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_market_condition_id(&self, market_id: &str) -> Result<Option<String>> {
+        let query = "SELECT condition_id FROM market_conditions WHERE market_id = $1";
+        let row = sqlx::query(query)
+            .bind(market_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|r| r.get::<String, _>("condition_id")))
+    }
+
+    pub async fn get_market_condition(&self, market_id: &str) -> Result<Option<MarketConditionRecord>> {
+        let query = "SELECT * FROM market_conditions WHERE market_id = $1";
+        let row = sqlx::query(query)
+            .bind(market_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|r| self.row_to_market_condition(r)))
+    }
+
+    pub async fn get_registered_markets(&self) -> Result<Vec<MarketConditionRecord>> {
+        let query = "SELECT * FROM market_conditions ORDER BY registered_at DESC";
+        let rows = sqlx::query(query)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|r| self.row_to_market_condition(r)).collect())
+    }
+
+    // ================================
+    // API KEYS
+    // ================================
+
+    pub async fn insert_api_key(&self, key: &ApiKey) -> Result<()> {
+        let query = r#"
+            INSERT INTO api_keys (
+                key_id, secret_hash, rate_limit_multiplier, allowed_endpoints,
+                created_at, last_used_at, request_count
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#;
+
+        sqlx::query(query)
+            .bind(&key.key_id)
+            .bind(&key.secret_hash)
+            .bind(key.rate_limit_multiplier as i32)
+            .bind(&key.allowed_endpoints)
+            .bind(key.created_at)
+            .bind(key.last_used_at)
+            .bind(key.request_count as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_api_key(&self, key_id: &str) -> Result<Option<ApiKey>> {
+        let row = sqlx::query("SELECT * FROM api_keys WHERE key_id = $1")
+            .bind(key_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|r| ApiKey {
+            key_id: r.get("key_id"),
+            secret_hash: r.get("secret_hash"),
+            rate_limit_multiplier: r.get::<i32, _>("rate_limit_multiplier") as u32,
+            allowed_endpoints: r.get("allowed_endpoints"),
+            created_at: r.get("created_at"),
+            last_used_at: r.get("last_used_at"),
+            request_count: r.get::<i64, _>("request_count") as u64,
+        }))
+    }
+
+    pub async fn record_api_key_use(&self, key_id: &str, used_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("UPDATE api_keys SET request_count = request_count + 1, last_used_at = $2 WHERE key_id = $1")
+            .bind(key_id)
+            .bind(used_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // ================================
+    // CHAIN RECONCILIATION
+    // ================================
+
+    pub async fn link_solver_order(&self, order_id: Uuid, solver_order_id: &str) -> Result<()> {
+        let query = r#"
+            INSERT INTO solver_order_links (order_id, solver_order_id) VALUES ($1, $2)
+            ON CONFLICT (order_id) DO UPDATE SET solver_order_id = EXCLUDED.solver_order_id
+        "#;
+
+        sqlx::query(query)
+            .bind(order_id)
+            .bind(solver_order_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_solver_order_links(&self) -> Result<HashMap<Uuid, String>> {
+        let rows = sqlx::query("SELECT order_id, solver_order_id FROM solver_order_links")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|r| (r.get("order_id"), r.get("solver_order_id"))).collect())
+    }
+
+    pub async fn insert_reconciliation_report(&self, report: &ReconciliationReport) -> Result<()> {
+        let query = r#"
+            INSERT INTO reconciliation_reports (report_id, started_at, completed_at, divergence_count, report)
+            VALUES ($1, $2, $3, $4, $5::jsonb)
+        "#;
+
+        sqlx::query(query)
+            .bind(report.report_id)
+            .bind(report.started_at)
+            .bind(report.completed_at)
+            .bind(report.divergences.len() as i32)
+            .bind(serde_json::to_string(report)?)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_latest_reconciliation_report(&self) -> Result<Option<ReconciliationReport>> {
+        let row = sqlx::query("SELECT report::text AS report FROM reconciliation_reports ORDER BY completed_at DESC LIMIT 1")
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|r| Ok(serde_json::from_str(&r.get::<String, _>("report"))?))
+            .transpose()
+    }
+
+    // ================================
+    // PLATFORM FEES
+    // ================================
+
+    pub async fn record_trade_fees(&self, entry: &FeeLedgerEntry) -> Result<bool> {
+        let query = r#"
+            INSERT INTO platform_fees (trade_id, market_id, collateral_token, maker_fee, taker_fee, collected_at, sweep_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (trade_id) DO NOTHING
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(entry.trade_id)
+            .bind(&entry.market_id)
+            .bind(&entry.collateral_token)
+            .bind(Self::u128_to_bigdecimal(entry.maker_fee))
+            .bind(Self::u128_to_bigdecimal(entry.taker_fee))
+            .bind(entry.collected_at)
+            .bind(entry.sweep_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_fee_summary(&self) -> Result<Vec<MarketFeeSummary>> {
+        let query = r#"
+            SELECT market_id, collateral_token, COUNT(*) AS trade_count,
+                   SUM(maker_fee) AS maker_fees, SUM(taker_fee) AS taker_fees,
+                   COALESCE(SUM(maker_fee + taker_fee) FILTER (WHERE sweep_id IS NULL), 0) AS unswept
+            FROM platform_fees
+            GROUP BY market_id, collateral_token
+            ORDER BY market_id, collateral_token
+        "#;
+
+        let rows = sqlx::query(query).fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(|r| MarketFeeSummary {
+            market_id: r.get("market_id"),
+            collateral_token: r.get("collateral_token"),
+            trade_count: r.get::<i64, _>("trade_count") as usize,
+            maker_fees: Self::bigdecimal_to_u128(r.get::<BigDecimal, _>("maker_fees")),
+            taker_fees: Self::bigdecimal_to_u128(r.get::<BigDecimal, _>("taker_fees")),
+            unswept: Self::bigdecimal_to_u128(r.get::<BigDecimal, _>("unswept")),
+        }).collect())
+    }
+
+    pub async fn get_unswept_fees(&self) -> Result<Vec<FeeLedgerEntry>> {
+        let rows = sqlx::query("SELECT * FROM platform_fees WHERE sweep_id IS NULL ORDER BY collected_at")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|r| FeeLedgerEntry {
+            trade_id: r.get("trade_id"),
+            market_id: r.get("market_id"),
+            collateral_token: r.get("collateral_token"),
+            maker_fee: Self::bigdecimal_to_u128(r.get::<BigDecimal, _>("maker_fee")),
+            taker_fee: Self::bigdecimal_to_u128(r.get::<BigDecimal, _>("taker_fee")),
+            collected_at: r.get("collected_at"),
+            sweep_id: r.get("sweep_id"),
+        }).collect())
+    }
+
+    pub async fn mark_fees_swept(&self, trade_ids: &[Uuid], sweep_id: Uuid) -> Result<usize> {
+        let result = sqlx::query("UPDATE platform_fees SET sweep_id = $1 WHERE trade_id = ANY($2) AND sweep_id IS NULL")
+            .bind(sweep_id)
+            .bind(trade_ids)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() as usize)
+    }
+
+    // ================================
+    // COLLATERAL YIELD
+    // ================================
+
+    fn row_to_yield_deposit(row: &sqlx::postgres::PgRow) -> YieldDeposit {
+        YieldDeposit {
+            principal: Self::bigdecimal_to_u128(row.get::<BigDecimal, _>("amount")),
+            shares: Self::bigdecimal_to_u128(row.get::<BigDecimal, _>("shares")),
+        }
+    }
+
+    pub async fn get_yield_deposits(&self) -> Result<HashMap<String, YieldDeposit>> {
+        let rows = sqlx::query("SELECT account_id, amount, shares FROM yield_deposits WHERE shares > 0")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter()
+            .map(|r| (r.get("account_id"), Self::row_to_yield_deposit(r)))
+            .collect())
+    }
+
+    pub async fn credit_yield_deposit(&self, account_id: &str, amount: u128, shares: u128) -> Result<YieldDeposit> {
+        let query = r#"
+            INSERT INTO yield_deposits (account_id, amount, shares, updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (account_id) DO UPDATE
+            SET amount = yield_deposits.amount + EXCLUDED.amount,
+                shares = yield_deposits.shares + EXCLUDED.shares,
+                updated_at = NOW()
+            RETURNING amount, shares
+        "#;
+
+        let row = sqlx::query(query)
+            .bind(account_id)
+            .bind(Self::u128_to_bigdecimal(amount))
+            .bind(Self::u128_to_bigdecimal(shares))
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(Self::row_to_yield_deposit(&row))
+    }
+
+    pub async fn debit_yield_deposit(&self, account_id: &str, amount: u128, shares: u128) -> Result<YieldDeposit> {
+        // A position without shares left is closed, whatever principal it had
+        let query = r#"
+            UPDATE yield_deposits
+            SET shares = GREATEST(shares - $3, 0),
+                amount = CASE WHEN shares <= $3 THEN 0 ELSE GREATEST(amount - $2, 0) END,
+                updated_at = NOW()
+            WHERE account_id = $1
+            RETURNING amount, shares
+        "#;
+
+        let row = sqlx::query(query)
+            .bind(account_id)
+            .bind(Self::u128_to_bigdecimal(amount))
+            .bind(Self::u128_to_bigdecimal(shares))
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(Self::row_to_yield_deposit).unwrap_or_default())
+    }
+
+    // ================================
+    // IDEMPOTENCY KEYS
+    // ================================
+
+    pub async fn claim_idempotency_key(&self, record: &IdempotencyRecord, expired_before: DateTime<Utc>) -> Result<Option<IdempotencyRecord>> {
+        sqlx::query("DELETE FROM order_idempotency_keys WHERE created_at < $1")
+            .bind(expired_before)
+            .execute(&self.pool)
+            .await?;
+
+        // The primary key settles racing claims: only one insert goes through
+        let query = r#"
+            INSERT INTO order_idempotency_keys (idempotency_key, payload_hash, order_id, response, created_at)
+            VALUES ($1, $2, $3, NULL, $4)
+            ON CONFLICT (idempotency_key) DO NOTHING
+        "#;
+        let inserted = sqlx::query(query)
+            .bind(&record.idempotency_key)
+            .bind(&record.payload_hash)
+            .bind(record.order_id)
+            .bind(record.created_at)
+            .execute(&self.pool)
+            .await?;
+
+        if inserted.rows_affected() > 0 {
+            return Ok(None);
+        }
+        self.get_idempotency_record(&record.idempotency_key).await
+    }
+
+    pub async fn get_idempotency_record(&self, idempotency_key: &str) -> Result<Option<IdempotencyRecord>> {
+        let row = sqlx::query("SELECT * FROM order_idempotency_keys WHERE idempotency_key = $1")
+            .bind(idempotency_key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|r| -> Result<IdempotencyRecord> {
+            Ok(IdempotencyRecord {
+                idempotency_key: r.get("idempotency_key"),
+                payload_hash: r.get("payload_hash"),
+                order_id: r.get("order_id"),
+                response: r.get::<Option<String>, _>("response")
+                    .map(|response| serde_json::from_str(&response))
+                    .transpose()?,
+                created_at: r.get("created_at"),
+            })
+        }).transpose()
+    }
+
+    pub async fn complete_idempotency_key(&self, idempotency_key: &str, response: &serde_json::Value) -> Result<()> {
+        sqlx::query("UPDATE order_idempotency_keys SET response = $2 WHERE idempotency_key = $1")
+            .bind(idempotency_key)
+            .bind(response.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn release_idempotency_key(&self, idempotency_key: &str) -> Result<()> {
+        sqlx::query("DELETE FROM order_idempotency_keys WHERE idempotency_key = $1")
+            .bind(idempotency_key)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     // ================================
     // CONVERSION HELPERS
     // ================================
 
+    fn row_to_market_condition(&self, r: sqlx::postgres::PgRow) -> MarketConditionRecord {
+        MarketConditionRecord {
+            market_id: r.get("market_id"),
+            condition_id: r.get("condition_id"),
+            source: self.string_to_registration_source(&r.get::<String, _>("source")),
+            is_active: r.get("is_active"),
+            collateral_token: r.get("collateral_token"),
+            outcome_count: r.get::<i16, _>("outcome_count") as u8,
+            registered_at: r.get("registered_at"),
+            updated_at: r.get("updated_at"),
+        }
+    }
+
     fn row_to_order(&self, r: sqlx::postgres::PgRow) -> Order {
         Order {
             order_id: r.get("order_id"),
@@ -595,6 +1162,8 @@ impl SimplePostgresDatabase {
             executed_at: r.get("executed_at"),
             settlement_status: self.string_to_settlement_status(&r.get::<String, _>("settlement_status")),
             settlement_tx_hash: r.get("settlement_tx_hash"),
+            maker_fee: Self::bigdecimal_to_u128(r.get::<BigDecimal, _>("maker_fee")),
+            taker_fee: Self::bigdecimal_to_u128(r.get::<BigDecimal, _>("taker_fee")),
         }
     }
 
@@ -694,4 +1263,20 @@ impl SimplePostgresDatabase {
             _ => SettlementStatus::Failed,
         }
     }
+
+    fn registration_source_to_string(&self, source: &MarketRegistrationSource) -> &'static str {
+        match source {
+            MarketRegistrationSource::Manual => "Manual",
+            MarketRegistrationSource::VerifierSync => "VerifierSync",
+            MarketRegistrationSource::LegacyImport => "LegacyImport",
+        }
+    }
+
+    fn string_to_registration_source(&self, s: &str) -> MarketRegistrationSource {
+        match s {
+            "VerifierSync" => MarketRegistrationSource::VerifierSync,
+            "LegacyImport" => MarketRegistrationSource::LegacyImport,
+            _ => MarketRegistrationSource::Manual,
+        }
+    }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 // Core types for the orderbook service
 
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
@@ -127,6 +128,8 @@ pub struct Trade {
     pub executed_at: DateTime<Utc>,
     pub settlement_status: SettlementStatus,
     pub settlement_tx_hash: Option<String>,
+    pub maker_fee: u128,           // Platform fee owed by the maker, in the market's collateral token
+    pub taker_fee: u128,           // Platform fee owed by the taker, in the market's collateral token
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -152,15 +155,88 @@ pub struct OrderbookSnapshot {
     pub asks: Vec<PriceLevel>,  // Sell orders (lowest price first)
     pub last_trade_price: Option<u64>,
     pub timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,                 // NEAR RPC unhealthy: prices may be stale and new orders are refused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staleness_secs: Option<u64>,    // while degraded, seconds since the last successful chain read
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Integrity checksum of one in-memory book (XOR of xxh3 over resting order ids and fills).
+/// `sequence` increases with every book mutation; on a checksum mismatch clients refetch the snapshot
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct OrderbookChecksum {
+    pub checksum: u64,
+    pub sequence: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PriceLevel {
     pub price: u64,
     pub size: u128,
     pub order_count: u32,
 }
 
+/// In-memory snapshot of one book at `sequence`, the starting point for client-side book building.
+/// Deltas with a sequence below `first_delta_sequence` are already contained in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderbookSyncSnapshot {
+    #[serde(flatten)]
+    pub snapshot: OrderbookSnapshot,
+    pub sequence: u64,
+    pub first_delta_sequence: u64,
+}
+
+/// Price levels of one book that changed after `prev_sequence`, each in its state at `sequence`.
+/// A level with order_count 0 is gone. Consecutive deltas chain: prev_sequence is the sequence
+/// of the delta before
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OrderbookDelta {
+    pub market_id: String,
+    pub outcome: u8,
+    pub prev_sequence: u64,
+    pub sequence: u64,
+    pub bids: Vec<PriceLevel>,  // Highest price first
+    pub asks: Vec<PriceLevel>,  // Lowest price first
+    pub last_trade_price: Option<u64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl OrderbookSyncSnapshot {
+    /// Apply one buffered delta. Deltas at or below the snapshot's sequence are skipped (false);
+    /// one that starts past it means a delta was missed, and the snapshot has to be refetched
+    pub fn apply_delta(&mut self, delta: &OrderbookDelta) -> Result<bool, String> {
+        if delta.sequence <= self.sequence {
+            return Ok(false);
+        }
+        if delta.prev_sequence > self.sequence {
+            return Err(format!("Missed deltas between sequence {} and {}", self.sequence, delta.prev_sequence));
+        }
+
+        fn merge(levels: &mut Vec<PriceLevel>, changes: &[PriceLevel], descending: bool) {
+            let mut by_price: BTreeMap<u64, PriceLevel> = levels.drain(..).map(|level| (level.price, level)).collect();
+            for change in changes {
+                if change.order_count == 0 {
+                    by_price.remove(&change.price);
+                } else {
+                    by_price.insert(change.price, change.clone());
+                }
+            }
+            levels.extend(by_price.into_values());
+            if descending {
+                levels.reverse();
+            }
+        }
+
+        merge(&mut self.snapshot.bids, &delta.bids, true);
+        merge(&mut self.snapshot.asks, &delta.asks, false);
+        self.snapshot.last_trade_price = delta.last_trade_price;
+        self.snapshot.timestamp = delta.timestamp;
+        self.sequence = delta.sequence;
+        self.first_delta_sequence = delta.sequence + 1;
+        Ok(true)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MarketPrice {
     pub market_id: String,
@@ -170,10 +246,113 @@ pub struct MarketPrice {
     pub mid: Option<u64>,       // Mid price (bid+ask)/2
     pub last: Option<u64>,      // Last trade price
     pub timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,         // Quoted while the NEAR RPC is unhealthy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staleness_secs: Option<u64>,
+}
+
+/// One price candle (GET /price/:market_id/:outcome/candles)
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OHLCV {
+    pub timestamp: u64,         // bucket start, unix seconds
+    pub open: u64,
+    pub high: u64,
+    pub low: u64,
+    pub close: u64,
+    pub volume: u128,
+}
+
+impl OHLCV {
+    /// A single trade as a one-print candle
+    pub fn from_trade(trade: &Trade) -> Self {
+        Self {
+            timestamp: trade.executed_at.timestamp().max(0) as u64,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: trade.size,
+        }
+    }
+
+    /// Merge time-ordered candles (or one-print trade candles) into `bucket_seconds` buckets
+    /// aligned to the unix epoch
+    pub fn aggregate(points: impl IntoIterator<Item = OHLCV>, bucket_seconds: u64) -> Vec<OHLCV> {
+        let mut candles: Vec<OHLCV> = Vec::new();
+        for point in points {
+            let bucket = point.timestamp - point.timestamp % bucket_seconds;
+            match candles.last_mut() {
+                Some(candle) if candle.timestamp == bucket => {
+                    candle.high = candle.high.max(point.high);
+                    candle.low = candle.low.min(point.low);
+                    candle.close = point.close;
+                    candle.volume += point.volume;
+                }
+                _ => candles.push(OHLCV { timestamp: bucket, ..point }),
+            }
+        }
+        candles
+    }
+}
+
+/// Simulated fill of an order against the current book (GET/POST /quote)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OrderQuote {
+    pub market_id: String,
+    pub outcome: u8,
+    pub side: OrderSide,
+    pub requested_size: u128,
+    pub filled_size: u128,
+    pub average_price: Option<u64>,   // size-weighted, 1/100000 of a dollar
+    pub best_price: Option<u64>,      // first level touched
+    pub worst_price: Option<u64>,     // deepest level touched
+    pub slippage_bps: u64,            // average vs best price
+    pub notional: u128,               // USDC (6 decimals) exchanged for filled_size
+    pub fee_bps: u16,
+    pub estimated_fee: u128,          // USDC (6 decimals)
+}
+
+impl OrderQuote {
+    pub fn from_trades(order: &Order, trades: &[Trade], fee_bps: u16) -> Self {
+        let filled_size: u128 = trades.iter().map(|t| t.size).sum();
+        let price_weighted: u128 = trades.iter().map(|t| t.size * t.price as u128).sum();
+        let notional = price_weighted / 100000;
+
+        let prices = trades.iter().map(|t| t.price);
+        let (best_price, worst_price) = match order.side {
+            // Buys walk up the asks, sells walk down the bids
+            OrderSide::Buy => (prices.clone().min(), prices.max()),
+            OrderSide::Sell => (prices.clone().max(), prices.min()),
+        };
+
+        let average_price = (filled_size > 0).then(|| (price_weighted / filled_size) as u64);
+        let slippage_bps = match (average_price, best_price) {
+            (Some(average), Some(best)) if best > 0 => average.abs_diff(best) * 10000 / best,
+            _ => 0,
+        };
+
+        Self {
+            market_id: order.market_id.clone(),
+            outcome: order.outcome,
+            side: order.side.clone(),
+            requested_size: order.remaining_size,
+            filled_size,
+            average_price,
+            best_price,
+            worst_price,
+            slippage_bps,
+            notional,
+            fee_bps,
+            estimated_fee: notional * fee_bps as u128 / 10000,
+        }
+    }
 }
 
 // API Request/Response types
-#[derive(Debug, Deserialize)]
+// Serialized only to hash the payload stored with an idempotency key, so client_order_id is left out
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubmitOrderRequest {
     pub market_id: String,
     pub user_account: String,
@@ -184,6 +363,8 @@ pub struct SubmitOrderRequest {
     pub price: Option<u64>,     // None for market orders
     pub size: u128,
     pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing)]
+    pub client_order_id: Option<String>,   // idempotency key when no Idempotency-Key header is sent
 }
 
 #[derive(Debug, Serialize)]
@@ -217,7 +398,11 @@ pub enum WebSocketMessage {
         market_id: String,
         outcome: u8,
         snapshot: OrderbookSnapshot,
+        sequence: u64,
+        checksum: u64,
     },
+    /// Price levels changed since the previous delta for the same book
+    OrderbookDelta(OrderbookDelta),
     TradeExecuted {
         trade: Trade,
     },
@@ -226,6 +411,19 @@ pub enum WebSocketMessage {
         status: OrderStatus,
         filled_size: u128,
     },
+    /// An expired order was purged from storage
+    OrderExpired {
+        order_id: Uuid,
+        market_id: String,
+        outcome: u8,
+        user_account: String,
+        expires_at: Option<DateTime<Utc>>,
+    },
+    /// The engine entered or left degraded mode; repeated while degraded so the staleness age stays current
+    ChainStatus {
+        degraded: bool,
+        staleness_secs: u64,
+    },
 }
 
 // Settlement batch for efficient on-chain execution
@@ -254,6 +452,28 @@ pub struct CollateralBalance {
     pub last_updated: DateTime<Utc>,
 }
 
+/// Live on-chain collateral view for an account (USDC wallet + CTF outcome tokens)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollateralStatus {
+    pub account_id: String,
+    pub collateral_token: String,       // NEP-141 token the balances below are in (USDC by default)
+    pub usdc_balance: u128,             // collateral held in the NEP-141 contract
+    pub reserved_usdc: u128,            // collateral locked by open buy orders (all markets using this token)
+    pub available_usdc: u128,           // usdc_balance - reserved_usdc
+    pub outcome_positions: Vec<PositionBalance>,
+}
+
+/// CTF outcome token balance for one market outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionBalance {
+    pub market_id: String,
+    pub condition_id: String,
+    pub outcome: u8,                    // 0=NO, 1=YES
+    pub position_id: String,
+    pub balance: u128,                  // Tokens held on the CTF contract
+    pub reserved: u128,                 // Tokens locked by open sell orders for this outcome
+}
+
 /// Collateral reservation for an order
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollateralReservation {
@@ -291,6 +511,37 @@ pub struct CollateralTransfer {
     pub net_usdc_flow: i128,           // Net USDC change (+ = receive, - = pay)
 }
 
+/// One transfer left after netting a batch of trades
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NetTransfer {
+    pub from_account: String,
+    pub to_account: String,
+    pub position_id: Option<String>,    // None = collateral
+    #[serde(default)]
+    pub collateral_token: Option<String>, // NEP-141 token of a collateral transfer
+    pub amount: u128,
+    pub tx_hash: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeSettlementResult {
+    pub trade_id: Uuid,
+    pub success: bool,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub transfers_pending: bool,        // held up by failed batch transfers queued for retry
+}
+
+/// Outcome of CollateralManager::batch_settle_trades
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchSettlementResult {
+    pub batch_id: Uuid,
+    pub usdc_transfers: Vec<NetTransfer>,
+    pub token_transfers: Vec<NetTransfer>,
+    pub trade_results: Vec<TradeSettlementResult>,
+}
+
 #[derive(Debug, Clone)]
 pub enum CollateralSettlementType {
     PureMinting,     // Create new token pairs from USDC
@@ -303,7 +554,183 @@ pub enum CollateralSettlementType {
 #[derive(Debug, Clone)]
 pub struct MarketCollateralConfig {
     pub market_id: String,
-    pub min_collateral: u128,           // Minimum USDC to place orders
+    pub collateral_token: String,       // NEP-141 token orders are margined and settled in
+    pub min_collateral: u128,           // Minimum collateral to place orders
     pub margin_requirement: f64,        // Additional margin (e.g., 1.1 = 110% collateralization)
     pub max_leverage: f64,              // Maximum leverage allowed
-}
\ No newline at end of file
+    pub maker_fee_bps: u16,             // Charged on the maker's collateral leg of each trade
+    pub taker_fee_bps: u16,             // Charged on the taker's collateral leg of each trade
+}
+// ================================
+// MARKET REGISTRY
+// ================================
+
+/// Registered market_id -> condition_id mapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketConditionRecord {
+    pub market_id: String,
+    pub condition_id: String,
+    pub source: MarketRegistrationSource,
+    pub is_active: bool,           // Mirrors the verifier's market status; inactive markets reject orders
+    pub collateral_token: String,  // NEP-141 token the condition is split from
+    pub outcome_count: u8,         // CTF outcome slots (2 for binary markets)
+    pub registered_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum MarketRegistrationSource {
+    Manual,          // POST /markets/register
+    VerifierSync,    // Pulled from the verifier contract
+    LegacyImport,    // Imported from market_conditions.json
+}
+
+/// API key for market makers and bots: GET /admin/api-keys/:key_id/stats returns it as-is.
+/// Clients send `X-API-Key: <key_id>.<secret>`; only the secret's sha256 is stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub key_id: String,
+    pub secret_hash: String,           // hex sha256 of the secret
+    pub rate_limit_multiplier: u32,    // scales the per-account order limits
+    pub allowed_endpoints: Vec<String>, // route patterns, `*` matches one path segment; empty allows all
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub request_count: u64,
+}
+
+// ================================
+// CHAIN RECONCILIATION
+// ================================
+
+/// Ways an order in the local book can disagree with its solver contract counterpart
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DivergenceKind {
+    CancelledOnChain,  // cancelled/expired on-chain, still resting here
+    UnreportedFill,    // filled further here than on-chain: a fill report was lost
+    FillNotInBook,     // filled further on-chain than here
+    ClosedLocallyOnly, // cancelled/expired here, still open on-chain
+    MissingOnChain,    // open here, unknown to the solver
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ReconcileAction {
+    AutoHealed,        // local order removed and its collateral released
+    FillReportQueued,  // handed to the fill reporter's retry queue
+    NeedsReview,       // left alone for an operator
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Divergence {
+    pub order_id: Uuid,
+    pub solver_order_id: String,
+    pub kind: DivergenceKind,
+    pub action: ReconcileAction,
+    pub local_status: OrderStatus,
+    pub local_filled: u128,
+    pub onchain_status: Option<String>, // None when the solver has no such order
+    pub onchain_filled: Option<u128>,
+}
+
+/// Result of one reconciliation pass (GET /admin/reconciliation/latest)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    pub report_id: Uuid,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    pub onchain_orders: usize,  // solver orders paged through
+    pub orders_checked: usize,  // local orders linked to a solver order
+    pub divergences: Vec<Divergence>,
+}
+
+// ================================
+// PLATFORM FEES
+// ================================
+
+/// One trade's fees as collected into the fee ledger; `sweep_id` is set once a sweep has moved
+/// them to the platform account
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeLedgerEntry {
+    pub trade_id: Uuid,
+    pub market_id: String,
+    pub collateral_token: String,
+    pub maker_fee: u128,
+    pub taker_fee: u128,
+    pub collected_at: DateTime<Utc>,
+    pub sweep_id: Option<Uuid>,
+}
+
+/// Fees collected in one market
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MarketFeeSummary {
+    pub market_id: String,
+    pub collateral_token: String,
+    pub trade_count: usize,
+    pub maker_fees: u128,
+    pub taker_fees: u128,
+    pub unswept: u128,   // collected but still held by the service account
+}
+
+/// GET /admin/fees
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeReport {
+    pub default_maker_fee_bps: u16,
+    pub default_taker_fee_bps: u16,
+    pub platform_account_id: String,
+    pub markets: Vec<MarketFeeSummary>,
+    pub unswept_by_token: BTreeMap<String, u128>,
+}
+
+/// One token's transfer in a fee sweep
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeSweep {
+    pub sweep_id: Uuid,
+    pub collateral_token: String,
+    pub amount: u128,
+    pub trade_count: usize,
+    pub tx_hash: Option<String>,   // None when the service account is the platform account
+}
+
+// ================================
+// COLLATERAL YIELD
+// ================================
+
+/// A user's stake in the yield protocol. Shares are minted at the protocol's balance per share when
+/// the USDC goes in, so interest earned before a deposit never counts towards it
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct YieldDeposit {
+    pub principal: u128,   // USDC moved out of the user's wallet and not withdrawn yet
+    pub shares: u128,      // the user's claim on the protocol balance
+}
+
+/// A user's idle USDC parked in the yield protocol
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct YieldBalance {
+    pub account_id: String,
+    pub yield_protocol: String,
+    pub collateral_token: String,
+    pub deposited: u128,   // principal moved out of the user's wallet
+    pub accrued: u128,     // the user's share of interest not harvested yet
+}
+
+/// Interest paid out to a user by POST /collateral/yield/:account_id/harvest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YieldHarvest {
+    pub account_id: String,
+    pub amount: u128,
+    pub tx_hash: Option<String>,   // None when there was nothing to harvest
+}
+
+// ================================
+// IDEMPOTENT ORDER SUBMISSION
+// ================================
+
+/// An order submission remembered under its idempotency key. `response` stays None while the
+/// first request is still running
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdempotencyRecord {
+    pub idempotency_key: String,     // "<account>:<client key>"
+    pub payload_hash: String,        // sha256 of the submitted payload
+    pub order_id: Uuid,
+    pub response: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
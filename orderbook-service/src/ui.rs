@@ -25,10 +25,13 @@ pub struct MetricsSnapshot {
     pub matches_executed: u64,
     pub best_bid: Option<f64>,
     pub best_ask: Option<f64>,
+    // Order-to-match latency percentiles from the matching engine
     pub p50_latency_ms: f64,
     pub p95_latency_ms: f64,
     pub p99_latency_ms: f64,
     pub orderbook_data: Option<OrderbookData>,
+    // Set while the NEAR RPC is unhealthy: the prices shown are stale and trading is halted
+    pub chain_staleness_secs: Option<u64>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -355,8 +358,13 @@ fn render_orderbook_panel(
         Line::from(format!("Last Price: ${} | Spread: ${}", last_price, spread)),
         Line::from(format!("Best Bid: ${} | Best Ask: ${}", best_bid, best_ask)),
     ];
+    let stats_title = match metrics.chain_staleness_secs {
+        Some(secs) => Span::styled(format!("Market Stats - STALE: chain unreachable for {}s, trading halted", secs),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        None => Span::raw("Market Stats"),
+    };
     let market_stats = Paragraph::new(market_info)
-        .block(Block::default().title("Market Stats").borders(Borders::ALL));
+        .block(Block::default().title(stats_title).borders(Borders::ALL));
     f.render_widget(market_stats, orderbook_chunks[2]);
 }
 
@@ -414,8 +422,9 @@ fn render_system_metrics(
             Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))]),
         Line::from(format!("Orders: {}", metrics.orders_processed)),
         Line::from(format!("Matches: {}", metrics.matches_executed)),
-        Line::from(format!("p50: {:.1}ms", metrics.p50_latency_ms)),
-        Line::from(format!("p95: {:.1}ms", metrics.p95_latency_ms)),
+        Line::from(format!("Match p50: {:.2}ms", metrics.p50_latency_ms)),
+        Line::from(format!("Match p95: {:.2}ms", metrics.p95_latency_ms)),
+        Line::from(format!("Match p99: {:.2}ms", metrics.p99_latency_ms)),
     ];
     let perf_panel = Paragraph::new(perf_lines)
         .block(Block::default().title("Metrics").borders(Borders::ALL));
@@ -0,0 +1,123 @@
+// API key authentication against the in-memory database: key generation, header checks,
+// endpoint patterns, usage stats and the per-account limit multiplier
+
+use std::collections::HashSet;
+use axum::http::{HeaderMap, HeaderValue};
+use chrono::Utc;
+use uuid::Uuid;
+
+use orderbook_service::api::ApiError;
+use orderbook_service::api::api_keys::{authenticate, endpoint_matches, generate_api_key, hash_secret, CreateApiKeyRequest, API_KEY_HEADER};
+use orderbook_service::api::rate_limit::{RateLimitConfig, RateLimiter};
+use orderbook_service::storage::Database;
+use orderbook_service::types::{Order, OrderSide, OrderStatus, OrderType};
+
+fn headers(api_key: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(API_KEY_HEADER, HeaderValue::from_str(api_key).unwrap());
+    headers
+}
+
+async fn issue_key(db: &Database, multiplier: u32, endpoints: &[&str]) -> (String, String) {
+    let (key, api_key) = generate_api_key(&CreateApiKeyRequest {
+        rate_limit_multiplier: multiplier,
+        allowed_endpoints: endpoints.iter().map(|e| e.to_string()).collect(),
+    }).unwrap();
+    db.insert_api_key(&key).await.unwrap();
+    (key.key_id, api_key)
+}
+
+#[tokio::test]
+async fn test_valid_key_authenticates_and_counts_usage() {
+    let db = Database::new().await.unwrap();
+    let (key_id, api_key) = issue_key(&db, 5, &[]).await;
+
+    // Only the hash of the secret is stored
+    let stored = db.get_api_key(&key_id).await.unwrap().unwrap();
+    let (prefix, secret) = api_key.split_once('.').unwrap();
+    assert_eq!(prefix, key_id);
+    assert_eq!(stored.secret_hash, hash_secret(secret));
+    assert_eq!(stored.request_count, 0);
+
+    for _ in 0..2 {
+        let key = authenticate(&db, &headers(&api_key), "/orders").await.unwrap().unwrap();
+        assert_eq!(key.rate_limit_multiplier, 5);
+    }
+    let stats = db.get_api_key(&key_id).await.unwrap().unwrap();
+    assert_eq!(stats.request_count, 2);
+    assert!(stats.last_used_at.is_some());
+
+    // No header is not an error here; the middleware decides whether keys are required
+    assert!(authenticate(&db, &HeaderMap::new(), "/orders").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_wrong_secret_and_unknown_key_rejected() {
+    let db = Database::new().await.unwrap();
+    let (key_id, _) = issue_key(&db, 1, &[]).await;
+
+    for bad in [format!("{}.wrong", key_id), "ak_missing.secret".to_string(), "no-separator".to_string()] {
+        let result = authenticate(&db, &headers(&bad), "/orders").await;
+        assert!(matches!(result, Err(ApiError::Unauthorized(_))), "{} accepted", bad);
+    }
+    assert_eq!(db.get_api_key(&key_id).await.unwrap().unwrap().request_count, 0);
+}
+
+#[tokio::test]
+async fn test_allowed_endpoints_restrict_key() {
+    let db = Database::new().await.unwrap();
+    let (_, api_key) = issue_key(&db, 1, &["/quote", "/price/*/vwap"]).await;
+
+    assert!(authenticate(&db, &headers(&api_key), "/quote").await.unwrap().is_some());
+    assert!(matches!(
+        authenticate(&db, &headers(&api_key), "/orders").await,
+        Err(ApiError::Unauthorized(_))
+    ));
+
+    assert!(endpoint_matches("/price/*/vwap", "/price/market_1/vwap"));
+    assert!(!endpoint_matches("/price/*/vwap", "/price/market_1/1/vwap"));
+    assert!(endpoint_matches("/orders", "/orders/"));
+}
+
+#[test]
+fn test_invalid_key_requests_rejected() {
+    let zero = CreateApiKeyRequest { rate_limit_multiplier: 0, allowed_endpoints: vec![] };
+    assert!(matches!(generate_api_key(&zero), Err(ApiError::InvalidRequest(_))));
+
+    let relative = CreateApiKeyRequest { rate_limit_multiplier: 1, allowed_endpoints: vec!["orders".to_string()] };
+    assert!(matches!(generate_api_key(&relative), Err(ApiError::InvalidRequest(_))));
+}
+
+#[tokio::test]
+async fn test_multiplier_scales_account_limits() {
+    let db = Database::new().await.unwrap();
+    let limiter = RateLimiter::new(RateLimitConfig {
+        global_orders_per_second: 0,
+        max_open_orders_per_market: 2,
+        max_orders_per_minute: 100,
+        exempt_accounts: HashSet::new(),
+    });
+
+    for _ in 0..3 {
+        db.insert_order(&Order {
+            order_id: Uuid::new_v4(),
+            market_id: "market_1".to_string(),
+            condition_id: "condition_1".to_string(),
+            user_account: "mm.testnet".to_string(),
+            outcome: 1,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            price: 50000,
+            original_size: 100,
+            remaining_size: 100,
+            filled_size: 0,
+            status: OrderStatus::Pending,
+            created_at: Utc::now(),
+            expires_at: None,
+            solver_account: "solver.testnet".to_string(),
+        }).await.unwrap();
+    }
+
+    assert!(limiter.check_account(&db, "mm.testnet", "market_1").await.is_err());
+    assert!(limiter.check_account_scaled(&db, "mm.testnet", "market_1", 2).await.is_ok());
+}
@@ -0,0 +1,127 @@
+// Batch settlement netting: a matching cycle's trades collapse into the fewest transfers
+
+use std::collections::HashMap;
+use chrono::Utc;
+use uuid::Uuid;
+
+use orderbook_service::collateral::CollateralManager;
+use orderbook_service::types::{NetTransfer, OrderSide, SettlementStatus, Trade, TradeType};
+
+fn trade(buyer: &str, seller: &str, outcome: u8, price: u64, size: u128) -> Trade {
+    Trade {
+        trade_id: Uuid::new_v4(),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        maker_order_id: Uuid::new_v4(),
+        taker_order_id: Uuid::new_v4(),
+        maker_account: seller.to_string(),
+        taker_account: buyer.to_string(),
+        maker_side: OrderSide::Sell,
+        taker_side: OrderSide::Buy,
+        outcome,
+        price,
+        size,
+        trade_type: TradeType::DirectMatch,
+        executed_at: Utc::now(),
+        settlement_status: SettlementStatus::Pending,
+        settlement_tx_hash: None,
+        maker_fee: 0,
+        taker_fee: 0,
+    }
+}
+
+fn position_ids() -> HashMap<(String, u8), String> {
+    HashMap::from([
+        (("condition_1".to_string(), 0), "position_no".to_string()),
+        (("condition_1".to_string(), 1), "position_yes".to_string()),
+    ])
+}
+
+fn transfer(from: &str, to: &str, position_id: Option<&str>, amount: u128) -> NetTransfer {
+    NetTransfer {
+        from_account: from.to_string(),
+        to_account: to.to_string(),
+        position_id: position_id.map(String::from),
+        collateral_token: None,
+        amount,
+        tx_hash: None,
+        error: None,
+    }
+}
+
+#[test]
+fn test_chained_payments_net_to_two_usdc_transfers() {
+    // Alice buys 100 YES from Bob @ $0.50, Bob buys 100 YES from Charlie @ $0.30
+    let trades = vec![
+        trade("alice.testnet", "bob.testnet", 1, 50000, 100_000_000),
+        trade("bob.testnet", "charlie.testnet", 1, 30000, 100_000_000),
+    ];
+
+    let (usdc, tokens) = CollateralManager::plan_batch_transfers(&trades, &position_ids(), "fees.testnet");
+
+    // Alice -50, Bob +20, Charlie +30
+    assert_eq!(usdc, vec![
+        transfer("alice.testnet", "charlie.testnet", None, 30_000_000),
+        transfer("alice.testnet", "bob.testnet", None, 20_000_000),
+    ]);
+    // Bob's YES passes straight through from Charlie to Alice
+    assert_eq!(tokens, vec![transfer("charlie.testnet", "alice.testnet", Some("position_yes"), 100_000_000)]);
+}
+
+#[test]
+fn test_positions_net_separately_and_round_trips_cancel() {
+    let trades = vec![
+        trade("alice.testnet", "bob.testnet", 1, 60000, 10_000_000),
+        trade("bob.testnet", "alice.testnet", 1, 60000, 10_000_000),
+        trade("alice.testnet", "bob.testnet", 0, 40000, 5_000_000),
+    ];
+
+    let (usdc, tokens) = CollateralManager::plan_batch_transfers(&trades, &position_ids(), "fees.testnet");
+
+    // The YES round trip cancels out; only the NO trade moves anything
+    assert_eq!(usdc, vec![transfer("alice.testnet", "bob.testnet", None, 2_000_000)]);
+    assert_eq!(tokens, vec![transfer("bob.testnet", "alice.testnet", Some("position_no"), 5_000_000)]);
+}
+
+#[test]
+fn test_trades_without_position_id_are_skipped() {
+    let mut unknown = trade("alice.testnet", "bob.testnet", 1, 50000, 10_000_000);
+    unknown.condition_id = "condition_2".to_string();
+
+    let (usdc, tokens) = CollateralManager::plan_batch_transfers(&[unknown], &position_ids(), "fees.testnet");
+    assert!(usdc.is_empty());
+    assert!(tokens.is_empty());
+}
+
+#[test]
+fn test_fees_net_into_the_collateral_leg() {
+    // Alice takes 100 YES from Bob @ $0.50: 20 bps taker fee, 10 bps maker fee
+    let mut fee_trade = trade("alice.testnet", "bob.testnet", 1, 50000, 100_000_000);
+    (fee_trade.maker_fee, fee_trade.taker_fee) = CollateralManager::trade_fees(&fee_trade, 10, 20);
+    assert_eq!((fee_trade.maker_fee, fee_trade.taker_fee), (50_000, 100_000));
+
+    let (usdc, tokens) = CollateralManager::plan_batch_transfers(&[fee_trade], &position_ids(), "fees.testnet");
+
+    // Alice pays her fee on top of the price, Bob's comes out of what he receives
+    assert_eq!(usdc, vec![
+        transfer("alice.testnet", "bob.testnet", None, 49_950_000),
+        transfer("alice.testnet", "fees.testnet", None, 150_000),
+    ]);
+    assert_eq!(tokens, vec![transfer("bob.testnet", "alice.testnet", Some("position_yes"), 100_000_000)]);
+}
+
+#[test]
+fn test_buy_reservation_covers_its_fee() {
+    // A 50 USDC buy reserves 20 bps on top, whether it ends up making or taking
+    let reserve = CollateralManager::fee_reserve(50_000_000, 10, 20);
+    assert_eq!(reserve, 100_000);
+    assert_eq!(CollateralManager::fee_reserve(50_000_000, 20, 10), reserve);
+
+    let mut fill = trade("alice.testnet", "bob.testnet", 1, 50000, 100_000_000);
+    (fill.maker_fee, fill.taker_fee) = CollateralManager::trade_fees(&fill, 10, 20);
+    assert!(fill.taker_fee <= reserve);
+
+    // Rounded up, so dust-sized orders still reserve what any fill could be charged
+    assert_eq!(CollateralManager::fee_reserve(1, 10, 20), 1);
+    assert_eq!(CollateralManager::fee_reserve(50_000_000, 0, 0), 0);
+}
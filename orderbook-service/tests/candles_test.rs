@@ -0,0 +1,82 @@
+// Price candle tests against the in-memory database
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use orderbook_service::storage::Database;
+use orderbook_service::types::{OrderSide, SettlementStatus, Trade, TradeType, OHLCV};
+
+fn trade(executed_at: DateTime<Utc>, price: u64, size: u128, status: SettlementStatus) -> Trade {
+    Trade {
+        trade_id: Uuid::new_v4(),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        maker_order_id: Uuid::new_v4(),
+        taker_order_id: Uuid::new_v4(),
+        maker_account: "alice.testnet".to_string(),
+        taker_account: "bob.testnet".to_string(),
+        maker_side: OrderSide::Sell,
+        taker_side: OrderSide::Buy,
+        outcome: 1,
+        price,
+        size,
+        trade_type: TradeType::DirectMatch,
+        executed_at,
+        settlement_status: status,
+        settlement_tx_hash: None,
+        maker_fee: 0,
+        taker_fee: 0,
+    }
+}
+
+fn at(secs: i64) -> DateTime<Utc> {
+    DateTime::<Utc>::from_timestamp(secs, 0).unwrap()
+}
+
+#[tokio::test]
+async fn test_candles_bucket_trades() {
+    let db = Database::new().await.unwrap();
+    let base = 1_699_999_900; // 100s into a 300s bucket
+
+    for (offset, price, size) in [(0, 50000, 10), (60, 52000, 5), (120, 49000, 7), (400, 51000, 3)] {
+        db.insert_trade(&trade(at(base + offset), price, size, SettlementStatus::Settled)).await.unwrap();
+    }
+    // Other outcome and out-of-range trades are ignored
+    let mut other = trade(at(base), 1000, 99, SettlementStatus::Settled);
+    other.outcome = 0;
+    db.insert_trade(&other).await.unwrap();
+    db.insert_trade(&trade(at(base + 10_000), 1000, 99, SettlementStatus::Settled)).await.unwrap();
+
+    let candles = db.get_price_candles("market_1", 1, at(base - 100), at(base + 1000), 300).await.unwrap();
+    assert_eq!(candles, vec![
+        OHLCV { timestamp: (base - 100) as u64, open: 50000, high: 52000, low: 49000, close: 49000, volume: 22 },
+        OHLCV { timestamp: (base + 200) as u64, open: 51000, high: 51000, low: 51000, close: 51000, volume: 3 },
+    ]);
+}
+
+#[tokio::test]
+async fn test_compaction_keeps_candles_and_recent_trades() {
+    let db = Database::new().await.unwrap();
+    let hour = 1_699_999_200; // aligned to the hour
+    let cutoff = at(hour + 7200);
+
+    db.insert_trade(&trade(at(hour + 10), 40000, 1, SettlementStatus::Settled)).await.unwrap();
+    db.insert_trade(&trade(at(hour + 3000), 45000, 2, SettlementStatus::Settled)).await.unwrap();
+    db.insert_trade(&trade(at(hour + 3600), 42000, 4, SettlementStatus::Settled)).await.unwrap();
+    // Unsettled trades are never compacted
+    db.insert_trade(&trade(at(hour + 3700), 41000, 8, SettlementStatus::Pending)).await.unwrap();
+    // Newer than the cutoff
+    db.insert_trade(&trade(cutoff + Duration::seconds(5), 43000, 16, SettlementStatus::Settled)).await.unwrap();
+
+    let before = db.get_price_candles("market_1", 1, at(hour), at(hour + 10_800), 3600).await.unwrap();
+    assert_eq!(db.compact_trades_before(cutoff).await.unwrap(), 2);
+    assert_eq!(db.get_trades_for_market("market_1").await.unwrap().len(), 2);
+
+    // Hourly history reads the same before and after compaction
+    let after = db.get_price_candles("market_1", 1, at(hour), at(hour + 10_800), 3600).await.unwrap();
+    assert_eq!(after, before);
+    assert_eq!(after[0], OHLCV { timestamp: hour as u64, open: 40000, high: 45000, low: 40000, close: 45000, volume: 3 });
+
+    // Running again finds nothing left to compact
+    assert_eq!(db.compact_trades_before(cutoff).await.unwrap(), 0);
+}
@@ -0,0 +1,166 @@
+// Chain health watchdog: a client whose RPC reads fail puts the engine in degraded mode (orders
+// refused, market data flagged stale, wide quotes found for pulling) and recovery lifts it
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use orderbook_service::api::ApiError;
+use orderbook_service::matching::degraded::{check_chain, outside_band, ChainGuard, ModeChange};
+use orderbook_service::matching::engine::OrderBook;
+use orderbook_service::near_client::health::{ChainHealth, ChainHealthPolicy, ChainProbe};
+use orderbook_service::types::{Order, OrderSide, OrderStatus, OrderType};
+
+/// Stand-in for NearClient: reads succeed or fail on command, at a clock the test controls
+struct FakeClient {
+    health: ChainHealth,
+    failing: AtomicBool,
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl FakeClient {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self { health: ChainHealth::started_at(now), failing: AtomicBool::new(false), now: Mutex::new(now) }
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+
+    fn advance(&self, secs: i64) -> DateTime<Utc> {
+        let mut now = self.now.lock().unwrap();
+        *now += Duration::seconds(secs);
+        *now
+    }
+}
+
+#[async_trait::async_trait]
+impl ChainProbe for FakeClient {
+    async fn probe(&self) -> Result<()> {
+        let ok = !self.failing.load(Ordering::SeqCst);
+        self.health.record_at(ok, self.now());
+        if ok { Ok(()) } else { Err(anyhow!("transport error: connection refused")) }
+    }
+
+    fn chain_health(&self) -> &ChainHealth {
+        &self.health
+    }
+}
+
+fn order(side: OrderSide, price: u64) -> Order {
+    Order {
+        order_id: Uuid::new_v4(),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        user_account: "alice.testnet".to_string(),
+        outcome: 1,
+        side,
+        order_type: OrderType::Limit,
+        price,
+        original_size: 1_000_000,
+        remaining_size: 1_000_000,
+        filled_size: 0,
+        status: OrderStatus::Pending,
+        created_at: Utc::now(),
+        expires_at: None,
+        solver_account: "solver.testnet".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_failing_rpc_degrades_and_recovery_resumes_trading() {
+    let client = FakeClient::new(Utc::now());
+    let guard = ChainGuard::new(ChainHealthPolicy::default());
+
+    assert_eq!(check_chain(&client, &guard, client.now()).await, None);
+    assert!(guard.check_order_intake().is_ok());
+
+    // A few failures are noise; the error rate trips once there are enough reads to judge
+    client.failing.store(true, Ordering::SeqCst);
+    for _ in 0..3 {
+        let now = client.advance(1);
+        assert_eq!(check_chain(&client, &guard, now).await, None);
+    }
+    let now = client.advance(1);
+    let Some(ModeChange::Entered(status)) = check_chain(&client, &guard, now).await else {
+        panic!("expected the engine to enter degraded mode");
+    };
+    assert_eq!((status.samples, status.staleness_secs), (5, 4));
+    assert!(status.error_rate > 0.5);
+
+    assert!(matches!(guard.check_order_intake(), Err(ApiError::ChainUnavailable(_))));
+    let snapshot = guard.annotate_snapshot(OrderBook::new().get_snapshot("market_1", 1).await.unwrap());
+    assert!(snapshot.degraded);
+    assert_eq!(snapshot.staleness_secs, Some(4));
+
+    // Still down: no new transition, and the staleness keeps growing
+    let now = client.advance(10);
+    assert_eq!(check_chain(&client, &guard, now).await, None);
+    assert_eq!(guard.status().staleness_secs, 14);
+
+    // Back up once the failures age out of the window
+    client.failing.store(false, Ordering::SeqCst);
+    let now = client.advance(60);
+    assert!(matches!(check_chain(&client, &guard, now).await, Some(ModeChange::Recovered(_))));
+    assert!(guard.check_order_intake().is_ok());
+    let price = guard.annotate_price(OrderBook::new().get_market_price("market_1", 1).await.unwrap());
+    assert!(!price.degraded);
+    assert_eq!(price.staleness_secs, None);
+}
+
+#[test]
+fn test_stale_reads_degrade_without_errors() {
+    let started = Utc::now();
+    let health = ChainHealth::started_at(started);
+    let policy = ChainHealthPolicy { max_staleness_secs: 30, ..ChainHealthPolicy::default() };
+
+    // No reads at all counts from startup
+    assert!(!health.status_at(&policy, started + Duration::seconds(30)).degraded);
+    assert!(health.status_at(&policy, started + Duration::seconds(31)).degraded);
+
+    health.record_at(true, started + Duration::seconds(40));
+    let status = health.status_at(&policy, started + Duration::seconds(45));
+    assert!(!status.degraded);
+    assert_eq!((status.staleness_secs, status.error_rate), (5, 0.0));
+    assert_eq!(health.last_success(), Some(started + Duration::seconds(40)));
+}
+
+#[test]
+fn test_policy_can_keep_taking_orders_while_degraded() {
+    let guard = ChainGuard::new(ChainHealthPolicy { reject_orders: false, ..ChainHealthPolicy::default() });
+    let health = ChainHealth::started_at(Utc::now());
+
+    let change = guard.update(health.status_at(guard.policy(), Utc::now() + Duration::seconds(120)));
+    assert!(matches!(change, Some(ModeChange::Entered(_))));
+    assert!(guard.is_degraded());
+    assert!(guard.check_order_intake().is_ok());
+}
+
+#[tokio::test]
+async fn test_quotes_outside_the_band_are_selected_for_pulling() {
+    let mut book = OrderBook::new();
+    assert!(book.orders_outside_band(1000).is_empty());
+
+    let far_bid = order(OrderSide::Buy, 40000);
+    let near_bid = order(OrderSide::Buy, 49000);
+    let near_ask = order(OrderSide::Sell, 51000);
+    let far_ask = order(OrderSide::Sell, 90000);
+    for order in [&far_bid, &near_bid, &near_ask, &far_ask] {
+        book.add_order(order.clone()).await.unwrap();
+    }
+
+    // Mid is 50000; 10% either side keeps 45000 - 55000
+    let mut pulled = book.orders_outside_band(1000);
+    pulled.sort();
+    let mut expected = vec![far_bid.order_id, far_ask.order_id];
+    expected.sort();
+    assert_eq!(pulled, expected);
+    assert_eq!(book.orders_outside_band(10_000), Vec::<Uuid>::new());
+
+    assert!(!outside_band(55000, 50000, 1000));
+    assert!(outside_band(55001, 50000, 1000));
+    assert!(outside_band(44999, 50000, 1000));
+}
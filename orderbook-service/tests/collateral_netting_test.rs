@@ -0,0 +1,251 @@
+// Cross-market netting: sells backed by held outcome tokens need no USDC margin, and open orders
+// on either side count against the max order size. Balances come from a stand-in chain client and
+// open orders from the in-memory database
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use near_crypto::{KeyType, SecretKey};
+use uuid::Uuid;
+
+use orderbook_service::api::ApiError;
+use orderbook_service::collateral::CollateralManager;
+use orderbook_service::config::ServiceConfig;
+use orderbook_service::near_client::settlement::SettlementClient;
+use orderbook_service::near_client::NearClient;
+use orderbook_service::storage::Database;
+use orderbook_service::types::{
+    MarketConditionRecord, MarketRegistrationSource, Order, OrderSide, OrderStatus, OrderType,
+};
+
+const USDC: &str = "usdc.testnet";
+
+/// Stand-in for NearClient: token and position balances the test sets, no transfers
+#[derive(Default)]
+struct FakeChain {
+    ft_balances: Mutex<HashMap<(String, String), u128>>,  // (token, account)
+    ctf_balances: Mutex<HashMap<(String, String), u128>>, // (account, position)
+}
+
+impl FakeChain {
+    fn set_ft_balance(&self, token: &str, account_id: &str, balance: u128) {
+        self.ft_balances.lock().unwrap().insert((token.to_string(), account_id.to_string()), balance);
+    }
+
+    fn set_position_balance(&self, account_id: &str, market_id: &str, outcome: u8, balance: u128) {
+        let position = position(&condition(market_id), outcome);
+        self.ctf_balances.lock().unwrap().insert((account_id.to_string(), position), balance);
+    }
+}
+
+#[async_trait::async_trait]
+impl SettlementClient for FakeChain {
+    fn signer_account_id(&self) -> &str {
+        "platform.testnet"
+    }
+
+    async fn get_position_id_for_outcome(&self, condition_id: &str, outcome: u8, _collateral_token: &str) -> Result<String> {
+        Ok(position(condition_id, outcome))
+    }
+
+    async fn get_ft_balance(&self, token_contract: &str, account_id: &str) -> Result<u128> {
+        Ok(self.ft_balances.lock().unwrap().get(&(token_contract.to_string(), account_id.to_string())).copied().unwrap_or(0))
+    }
+
+    async fn get_ctf_token_balance(&self, account_id: &str, position_id: &str) -> Result<u128> {
+        Ok(self.ctf_balances.lock().unwrap().get(&(account_id.to_string(), position_id.to_string())).copied().unwrap_or(0))
+    }
+
+    async fn transfer_collateral_from(&self, _collateral_token: &str, _from: &str, _to: &str, _amount: u128) -> Result<String> {
+        Err(anyhow!("no transfers in balance checks"))
+    }
+
+    async fn transfer_position_from(&self, _from: &str, _to: &str, _position_id: &str, _amount: u128) -> Result<String> {
+        Err(anyhow!("no transfers in balance checks"))
+    }
+}
+
+fn condition(market_id: &str) -> String {
+    format!("condition_{}", market_id)
+}
+
+fn position(condition_id: &str, outcome: u8) -> String {
+    format!("{}:{}", condition_id, outcome)
+}
+
+fn order(user: &str, market_id: &str, side: OrderSide, price: u64, size: u128, filled: u128) -> Order {
+    Order {
+        order_id: Uuid::new_v4(),
+        market_id: market_id.to_string(),
+        condition_id: format!("condition_{}", market_id),
+        user_account: user.to_string(),
+        outcome: 1,
+        side,
+        order_type: OrderType::Limit,
+        price,
+        original_size: size,
+        remaining_size: size - filled,
+        filled_size: filled,
+        status: if filled > 0 { OrderStatus::PartiallyFilled } else { OrderStatus::Pending },
+        created_at: Utc::now(),
+        expires_at: None,
+        solver_account: "solver.testnet".to_string(),
+    }
+}
+
+#[test]
+fn test_fully_hedged_sell_requires_no_collateral() {
+    // 100 YES held against a 100 YES sell @ $0.60
+    assert_eq!(CollateralManager::netted_collateral_requirement(&OrderSide::Sell, 60000, 100_000_000, 100_000_000), 0);
+
+    // Holding more than the order size doesn't produce a negative requirement
+    assert_eq!(CollateralManager::netted_collateral_requirement(&OrderSide::Sell, 60000, 100_000_000, 250_000_000), 0);
+}
+
+#[test]
+fn test_partially_hedged_sell_margins_remainder() {
+    // 60 of 100 tokens held: the other 40 need (1 - 0.60) * 40 = 16 USDC
+    assert_eq!(
+        CollateralManager::netted_collateral_requirement(&OrderSide::Sell, 60000, 100_000_000, 60_000_000),
+        16_000_000
+    );
+
+    // Nothing held: the whole order is margined
+    assert_eq!(
+        CollateralManager::netted_collateral_requirement(&OrderSide::Sell, 60000, 100_000_000, 0),
+        40_000_000
+    );
+}
+
+#[test]
+fn test_buy_requirement_ignores_held_tokens() {
+    // Buy 100 YES @ $0.60 = 60 USDC, regardless of existing position
+    assert_eq!(
+        CollateralManager::netted_collateral_requirement(&OrderSide::Buy, 60000, 100_000_000, 100_000_000),
+        60_000_000
+    );
+}
+
+/// alice's open orders: in market_1 two buys (one half filled) and two sells; elsewhere a buy in a
+/// market settling in the same token, one settling in another token, and a sell whose outcome
+/// tokens are a different balance. bob's orders never count against alice
+fn open_orders() -> Vec<Order> {
+    vec![
+        order("alice.testnet", "market_1", OrderSide::Buy, 60000, 100_000_000, 0),
+        order("alice.testnet", "market_1", OrderSide::Buy, 40000, 50_000_000, 25_000_000),
+        order("alice.testnet", "market_1", OrderSide::Sell, 70000, 30_000_000, 10_000_000),
+        order("alice.testnet", "market_1", OrderSide::Sell, 80000, 15_000_000, 0),
+        order("alice.testnet", "market_2", OrderSide::Buy, 50000, 20_000_000, 0),
+        order("alice.testnet", "market_3", OrderSide::Buy, 50000, 80_000_000, 0),
+        order("alice.testnet", "market_2", OrderSide::Sell, 50000, 40_000_000, 0),
+        order("bob.testnet", "market_1", OrderSide::Buy, 60000, 500_000_000, 0),
+        order("bob.testnet", "market_1", OrderSide::Sell, 60000, 500_000_000, 0),
+    ]
+}
+
+/// A collateral manager over `open_orders`, with market_1 and market_2 settling in USDC and market_3
+/// in another token
+async fn manager(configure: impl FnOnce(&mut ServiceConfig)) -> (CollateralManager, Arc<FakeChain>) {
+    let mut config = ServiceConfig::new(USDC, "ctf.testnet", "verifier.testnet", "solver.testnet", "platform.testnet");
+    configure(&mut config);
+    let config = Arc::new(config);
+
+    let db = Arc::new(Database::new().await.unwrap());
+    for (market_id, collateral_token) in [("market_1", USDC), ("market_2", USDC), ("market_3", "wrap.testnet")] {
+        db.upsert_market_condition(&MarketConditionRecord {
+            market_id: market_id.to_string(),
+            condition_id: condition(market_id),
+            source: MarketRegistrationSource::Manual,
+            is_active: true,
+            collateral_token: collateral_token.to_string(),
+            outcome_count: 2,
+            registered_at: Utc::now(),
+            updated_at: Utc::now(),
+        }).await.unwrap();
+    }
+    for order in open_orders() {
+        db.insert_order(&order).await.unwrap();
+    }
+
+    let chain = Arc::new(FakeChain::default());
+    let near_client = Arc::new(NearClient::with_secret_key(&config, SecretKey::from_seed(KeyType::ED25519, "netting-test")).unwrap());
+    let manager = CollateralManager::new(db, near_client, config).with_settlement_client(chain.clone());
+    (manager, chain)
+}
+
+fn insufficient_balance(err: &anyhow::Error) -> Option<(String, u128, u128)> {
+    match err.downcast_ref::<ApiError>() {
+        Some(ApiError::InsufficientBalance { asset, required, available }) => Some((asset.clone(), *required, *available)),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn test_max_order_size_nets_open_orders_on_both_sides() {
+    let (collateral, chain) = manager(|_| {}).await;
+    chain.set_ft_balance(USDC, "alice.testnet", 100_000_000);
+    chain.set_ft_balance("wrap.testnet", "alice.testnet", 1_000_000_000);
+    chain.set_position_balance("alice.testnet", "market_1", 1, 50_000_000);
+    chain.set_position_balance("alice.testnet", "market_2", 1, 30_000_000);
+
+    // Sells lock their unfilled tokens in this market only: 50 held - (20 + 15)
+    assert_eq!(collateral.calculate_max_order_size("alice.testnet", "market_1", &OrderSide::Sell).await.unwrap(), 15_000_000);
+
+    // Buys lock their unfilled value in every market of the token: 100 - (60 + 25 * 0.40 + 20 * 0.50).
+    // market_3's buy is margined in its own token
+    assert_eq!(collateral.calculate_max_order_size("alice.testnet", "market_1", &OrderSide::Buy).await.unwrap(), 20_000_000);
+    assert_eq!(collateral.calculate_max_order_size("alice.testnet", "market_2", &OrderSide::Buy).await.unwrap(), 20_000_000);
+    assert_eq!(collateral.calculate_max_order_size("alice.testnet", "market_3", &OrderSide::Buy).await.unwrap(), 960_000_000);
+
+    // Committed beyond the balance: nothing left, rather than an underflow
+    assert_eq!(collateral.calculate_max_order_size("alice.testnet", "market_2", &OrderSide::Sell).await.unwrap(), 0);
+    assert_eq!(collateral.calculate_max_order_size("carol.testnet", "market_1", &OrderSide::Buy).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_buy_locks_include_the_fee_reserve() {
+    // Every market charges 0.5% to makers and 1% to takers: each buy reserves 1% on top
+    let (collateral, chain) = manager(|config| {
+        config.maker_fee_bps = Some(50);
+        config.taker_fee_bps = Some(100);
+    }).await;
+    chain.set_ft_balance(USDC, "alice.testnet", 100_000_000);
+
+    // 100 - 80 * 1.01
+    assert_eq!(collateral.calculate_max_order_size("alice.testnet", "market_1", &OrderSide::Buy).await.unwrap(), 19_200_000);
+
+    // A new buy of 10 YES @ $0.60 needs 6 + 0.06 and fits; 40 YES needs 24.24 and doesn't
+    collateral.check_and_reserve_balance(&order("alice.testnet", "market_1", OrderSide::Buy, 60000, 10_000_000, 0)).await.unwrap();
+    let err = collateral.check_and_reserve_balance(&order("alice.testnet", "market_1", OrderSide::Buy, 60000, 40_000_000, 0)).await.unwrap_err();
+    assert_eq!(insufficient_balance(&err), Some(("USDC".to_string(), 24_240_000, 19_200_000)));
+}
+
+#[tokio::test]
+async fn test_buy_is_checked_against_the_netted_balance() {
+    let (collateral, chain) = manager(|_| {}).await;
+    chain.set_ft_balance(USDC, "alice.testnet", 100_000_000);
+
+    // 20 USDC is free after alice's open buys: 30 YES @ $0.60 fits, 40 doesn't
+    collateral.check_and_reserve_balance(&order("alice.testnet", "market_1", OrderSide::Buy, 60000, 30_000_000, 0)).await.unwrap();
+    let err = collateral.check_and_reserve_balance(&order("alice.testnet", "market_1", OrderSide::Buy, 60000, 40_000_000, 0)).await.unwrap_err();
+    assert_eq!(insufficient_balance(&err), Some(("USDC".to_string(), 24_000_000, 20_000_000)));
+}
+
+#[tokio::test]
+async fn test_sell_is_margined_only_beyond_free_held_tokens() {
+    let (collateral, chain) = manager(|_| {}).await;
+    chain.set_ft_balance(USDC, "alice.testnet", 100_000_000);
+    chain.set_position_balance("alice.testnet", "market_1", 1, 50_000_000);
+
+    // 15 of the 50 YES held are free of alice's open sells: selling those needs no USDC at all
+    collateral.check_and_reserve_balance(&order("alice.testnet", "market_1", OrderSide::Sell, 70000, 15_000_000, 0)).await.unwrap();
+
+    // 65 YES @ $0.70: the unhedged 50 need (1 - 0.70) * 50 = 15 USDC of the 20 free
+    collateral.check_and_reserve_balance(&order("alice.testnet", "market_1", OrderSide::Sell, 70000, 65_000_000, 0)).await.unwrap();
+
+    // 115 YES: the unhedged 100 need 30
+    let err = collateral.check_and_reserve_balance(&order("alice.testnet", "market_1", OrderSide::Sell, 70000, 115_000_000, 0)).await.unwrap_err();
+    assert_eq!(insufficient_balance(&err), Some(("USDC".to_string(), 30_000_000, 20_000_000)));
+}
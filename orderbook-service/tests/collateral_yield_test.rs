@@ -0,0 +1,73 @@
+// Collateral yield: how the protocol's interest is shared across users' shares and how the
+// principal and share ledger moves with deposits and withdrawals
+
+use orderbook_service::collateral::CollateralManager;
+use orderbook_service::storage::Database;
+use orderbook_service::types::YieldDeposit;
+
+fn deposit(principal: u128, shares: u128) -> YieldDeposit {
+    YieldDeposit { principal, shares }
+}
+
+#[test]
+fn test_interest_is_shared_by_shares() {
+    // $300 deposited in one go, the protocol now holds $330: $30 of interest
+    assert_eq!(CollateralManager::accrued_yield(&deposit(100_000_000, 100_000_000), 300_000_000, 330_000_000), 10_000_000);
+    assert_eq!(CollateralManager::accrued_yield(&deposit(200_000_000, 200_000_000), 300_000_000, 330_000_000), 20_000_000);
+
+    // Rounded down
+    assert_eq!(CollateralManager::accrued_yield(&deposit(1, 1), 3, 4), 0);
+    assert_eq!(CollateralManager::accrued_yield(&deposit(2, 2), 3, 5), 1);
+}
+
+#[test]
+fn test_late_deposit_does_not_share_earlier_interest() {
+    // Alice's $100 grew to $110 before Bob deposits $110
+    let alice = deposit(100_000_000, CollateralManager::shares_for_deposit(100_000_000, 0, 0));
+    let bob_shares = CollateralManager::shares_for_deposit(110_000_000, alice.shares, 110_000_000);
+    assert_eq!(bob_shares, 100_000_000);
+    let bob = deposit(110_000_000, bob_shares);
+
+    // Splitting by principal would hand Bob part of Alice's $10; by shares he has earned nothing yet
+    let total_shares = alice.shares + bob.shares;
+    assert_eq!(CollateralManager::accrued_yield(&alice, total_shares, 220_000_000), 10_000_000);
+    assert_eq!(CollateralManager::accrued_yield(&bob, total_shares, 220_000_000), 0);
+
+    // Interest earned from here on is split evenly
+    assert_eq!(CollateralManager::accrued_yield(&alice, total_shares, 240_000_000), 20_000_000);
+    assert_eq!(CollateralManager::accrued_yield(&bob, total_shares, 240_000_000), 10_000_000);
+}
+
+#[test]
+fn test_withdrawal_burns_enough_shares() {
+    // 300 shares backed by $330: taking $110 out burns 100 shares, rounding against the withdrawer
+    assert_eq!(CollateralManager::shares_for_withdrawal(110_000_000, 300_000_000, 330_000_000), 100_000_000);
+    assert_eq!(CollateralManager::shares_for_withdrawal(1, 3, 4), 1);
+    assert_eq!(CollateralManager::share_value(200_000_000, 300_000_000, 330_000_000), 220_000_000);
+}
+
+#[test]
+fn test_no_interest_without_shares_or_while_under_water() {
+    assert_eq!(CollateralManager::accrued_yield(&deposit(0, 0), 0, 5_000_000), 0);
+    assert_eq!(CollateralManager::accrued_yield(&deposit(100_000_000, 100_000_000), 300_000_000, 290_000_000), 0);
+}
+
+#[tokio::test]
+async fn test_principal_ledger_tracks_deposits_and_withdrawals() {
+    let db = Database::new().await.unwrap();
+
+    assert_eq!(db.credit_yield_deposit("alice.testnet", 50_000_000, 50_000_000).await.unwrap(), deposit(50_000_000, 50_000_000));
+    assert_eq!(db.credit_yield_deposit("alice.testnet", 25_000_000, 20_000_000).await.unwrap(), deposit(75_000_000, 70_000_000));
+    assert_eq!(db.credit_yield_deposit("bob.testnet", 10_000_000, 8_000_000).await.unwrap(), deposit(10_000_000, 8_000_000));
+
+    assert_eq!(db.debit_yield_deposit("alice.testnet", 30_000_000, 28_000_000).await.unwrap(), deposit(45_000_000, 42_000_000));
+    // Harvesting interest burns shares and leaves the principal
+    assert_eq!(db.debit_yield_deposit("alice.testnet", 0, 2_000_000).await.unwrap(), deposit(45_000_000, 40_000_000));
+    // Burning every share closes the position rather than underflowing
+    assert_eq!(db.debit_yield_deposit("bob.testnet", 9_000_000, 99_000_000).await.unwrap(), deposit(0, 0));
+    assert_eq!(db.debit_yield_deposit("carol.testnet", 1, 1).await.unwrap(), deposit(0, 0));
+
+    let deposits = db.get_yield_deposits().await.unwrap();
+    assert_eq!(deposits.len(), 1);
+    assert_eq!(deposits.get("alice.testnet"), Some(&deposit(45_000_000, 40_000_000)));
+}
@@ -0,0 +1,153 @@
+// Per-market shards in the real engine: a submission holds only its own market's lock, so an order
+// stuck on a slow balance read holds up later orders in that market and nothing in the others
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use near_crypto::{KeyType, SecretKey};
+use tokio::sync::{broadcast, Notify, Semaphore};
+use uuid::Uuid;
+
+use orderbook_service::collateral::CollateralManager;
+use orderbook_service::config::ServiceConfig;
+use orderbook_service::matching::MatchingEngine;
+use orderbook_service::near_client::settlement::SettlementClient;
+use orderbook_service::near_client::NearClient;
+use orderbook_service::storage::Database;
+use orderbook_service::types::{Order, OrderSide, OrderStatus, OrderType};
+
+const BALANCE: u128 = 1_000_000_000;
+
+/// Stand-in for NearClient where everyone holds plenty of everything, but reading `slow_account`'s
+/// balance waits until the test opens the gate
+struct FakeChain {
+    slow_account: String,
+    reached: Notify,
+    gate: Semaphore,
+}
+
+impl FakeChain {
+    fn new(slow_account: &str) -> Self {
+        Self { slow_account: slow_account.to_string(), reached: Notify::new(), gate: Semaphore::new(0) }
+    }
+
+    async fn read_balance(&self, account_id: &str) -> Result<u128> {
+        if account_id == self.slow_account {
+            self.reached.notify_one();
+            let _open = self.gate.acquire().await?;
+        }
+        Ok(BALANCE)
+    }
+
+    fn open_gate(&self) {
+        self.gate.add_permits(Semaphore::MAX_PERMITS);
+    }
+}
+
+#[async_trait::async_trait]
+impl SettlementClient for FakeChain {
+    fn signer_account_id(&self) -> &str {
+        "platform.testnet"
+    }
+
+    async fn get_position_id_for_outcome(&self, condition_id: &str, outcome: u8, _collateral_token: &str) -> Result<String> {
+        Ok(format!("{}:{}", condition_id, outcome))
+    }
+
+    async fn get_ft_balance(&self, _token_contract: &str, account_id: &str) -> Result<u128> {
+        self.read_balance(account_id).await
+    }
+
+    async fn get_ctf_token_balance(&self, account_id: &str, _position_id: &str) -> Result<u128> {
+        self.read_balance(account_id).await
+    }
+
+    async fn transfer_collateral_from(&self, _collateral_token: &str, _from: &str, _to: &str, _amount: u128) -> Result<String> {
+        Ok(format!("tx_{}", Uuid::new_v4()))
+    }
+
+    async fn transfer_position_from(&self, _from: &str, _to: &str, _position_id: &str, _amount: u128) -> Result<String> {
+        Ok(format!("tx_{}", Uuid::new_v4()))
+    }
+}
+
+fn order(user: &str, market_id: &str, side: OrderSide, price: u64, size: u128) -> Order {
+    Order {
+        order_id: Uuid::new_v4(),
+        market_id: market_id.to_string(),
+        condition_id: format!("condition_{}", market_id),
+        user_account: user.to_string(),
+        outcome: 1,
+        side,
+        order_type: OrderType::Limit,
+        price,
+        original_size: size,
+        remaining_size: size,
+        filled_size: 0,
+        status: OrderStatus::Pending,
+        created_at: Utc::now(),
+        expires_at: None,
+        solver_account: "solver.testnet".to_string(),
+    }
+}
+
+async fn engine(chain: Arc<FakeChain>) -> Arc<MatchingEngine> {
+    let config = Arc::new(ServiceConfig::new("usdc.testnet", "ctf.testnet", "verifier.testnet", "solver.testnet", "platform.testnet"));
+    let db = Arc::new(Database::new().await.unwrap());
+    let near_client = Arc::new(NearClient::with_secret_key(&config, SecretKey::from_seed(KeyType::ED25519, "concurrency-test")).unwrap());
+    let collateral = Arc::new(
+        CollateralManager::new(db.clone(), near_client.clone(), config.clone()).with_settlement_client(chain)
+    );
+    let (ws_broadcaster, _) = broadcast::channel(1024);
+    Arc::new(MatchingEngine::with_collateral_manager(db, near_client, config, ws_broadcaster, collateral))
+}
+
+async fn resting_orders(engine: &MatchingEngine, market_id: &str) -> usize {
+    engine.get_shard_stats().await
+        .into_iter()
+        .find(|stats| stats.market_id == market_id)
+        .map_or(0, |stats| stats.resting_orders)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_slow_order_blocks_only_its_own_market() {
+    let chain = Arc::new(FakeChain::new("alice.testnet"));
+    let engine = engine(chain.clone()).await;
+
+    // alice's buy in market_1 stalls on her balance read, holding market_1's shard
+    let alice = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.submit_order(order("alice.testnet", "market_1", OrderSide::Buy, 50000, 10_000_000)).await }
+    });
+    tokio::time::timeout(Duration::from_secs(5), chain.reached.notified()).await
+        .expect("alice's balance check never started");
+
+    // market_2 keeps matching meanwhile
+    let within = Duration::from_secs(5);
+    let resting = tokio::time::timeout(within, engine.submit_order(order("bob.testnet", "market_2", OrderSide::Buy, 60000, 10_000_000))).await
+        .expect("market_2 was blocked by market_1")
+        .unwrap();
+    assert!(resting.is_empty());
+    let trades = tokio::time::timeout(within, engine.submit_order(order("carol.testnet", "market_2", OrderSide::Sell, 60000, 10_000_000))).await
+        .expect("market_2 was blocked by market_1")
+        .unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!((trades[0].maker_account.as_str(), trades[0].taker_account.as_str()), ("bob.testnet", "carol.testnet"));
+    assert_eq!(resting_orders(&engine, "market_2").await, 0);
+
+    // Another order in market_1 waits for alice's to finish
+    let dave = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.submit_order(order("dave.testnet", "market_1", OrderSide::Buy, 40000, 5_000_000)).await }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!alice.is_finished());
+    assert!(!dave.is_finished());
+
+    chain.open_gate();
+    assert!(alice.await.unwrap().unwrap().is_empty());
+    assert!(dave.await.unwrap().unwrap().is_empty());
+    assert_eq!(resting_orders(&engine, "market_1").await, 2);
+}
@@ -0,0 +1,69 @@
+// Matching engine metrics: counters, bucketed latency percentiles and Prometheus rendering
+
+use std::time::Duration;
+
+use orderbook_service::matching::metrics::{EngineMetrics, LatencyHistogram};
+use orderbook_service::near_client::rpc_metrics;
+
+#[test]
+fn test_counters_track_submissions_and_matches() {
+    let metrics = EngineMetrics::new();
+
+    metrics.record_accepted(Duration::from_micros(300));
+    metrics.record_accepted(Duration::from_micros(800));
+    metrics.record_rejected(Duration::from_micros(40));
+    metrics.record_match(3, Duration::from_micros(700));
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.orders_accepted, 2);
+    assert_eq!(snapshot.orders_rejected, 1);
+    assert_eq!(snapshot.orders_matched, 1);
+    assert_eq!(snapshot.trades_executed, 3);
+    // Rejections still count towards order-to-ack latency
+    assert_eq!(snapshot.order_to_ack.count, 3);
+    assert_eq!(snapshot.order_to_match.count, 1);
+}
+
+#[test]
+fn test_percentiles_report_bucket_upper_bounds() {
+    let histogram = LatencyHistogram::default();
+    assert_eq!(histogram.snapshot().p50_ms, 0.0);
+
+    // 90 fast samples (<= 100us), 9 at ~2ms, 1 at ~40ms
+    for _ in 0..90 {
+        histogram.record(Duration::from_micros(80));
+    }
+    for _ in 0..9 {
+        histogram.record(Duration::from_micros(2_000));
+    }
+    histogram.record(Duration::from_millis(40));
+
+    let snapshot = histogram.snapshot();
+    assert_eq!(snapshot.count, 100);
+    assert_eq!(snapshot.p50_ms, 0.1);
+    assert_eq!(snapshot.p95_ms, 2.5);
+    assert_eq!(snapshot.p99_ms, 2.5);
+
+    // Anything slower than the last bucket is clamped to it
+    histogram.record(Duration::from_secs(5));
+    histogram.record(Duration::from_secs(5));
+    assert_eq!(histogram.snapshot().p99_ms, 1000.0);
+}
+
+#[test]
+fn test_prometheus_rendering() {
+    let metrics = EngineMetrics::new();
+    metrics.record_accepted(Duration::from_micros(200));
+    metrics.record_match(1, Duration::from_micros(150));
+    metrics.record_match(2, Duration::from_secs(3));
+
+    // Engine metrics are rendered with the RPC metrics from the default registry. Other tests record
+    // into it concurrently, so only the shape is asserted here
+    let text = rpc_metrics::render().unwrap();
+    assert!(text.contains("# TYPE orderbook_orders_accepted_total counter\n"));
+    assert!(text.contains("# HELP orderbook_trades_executed_total Trades produced by the matching engine\n"));
+    assert!(text.contains("# TYPE orderbook_order_to_match_seconds histogram\n"));
+    assert!(text.contains("orderbook_order_to_match_seconds_bucket{le=\"0.00025\"} "));
+    assert!(text.contains("orderbook_order_to_match_seconds_bucket{le=\"1\"} "));
+    assert!(text.contains("orderbook_order_to_match_seconds_bucket{le=\"+Inf\"} "));
+}
@@ -71,6 +71,8 @@ impl TestOrderbook {
                                     executed_at: Utc::now(),
                                     settlement_status: SettlementStatus::Pending,
                                     settlement_tx_hash: None,
+                                    maker_fee: 0,
+                                    taker_fee: 0,
                                 };
 
                                 trades.push(trade);
@@ -128,6 +130,8 @@ impl TestOrderbook {
                                     executed_at: Utc::now(),
                                     settlement_status: SettlementStatus::Pending,
                                     settlement_tx_hash: None,
+                                    maker_fee: 0,
+                                    taker_fee: 0,
                                 };
 
                                 trades.push(trade);
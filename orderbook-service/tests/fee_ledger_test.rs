@@ -0,0 +1,134 @@
+// Trading fees: pricing each side of a trade, the platform fee ledger's per-market totals and what
+// a sweep moves to the platform account
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use orderbook_service::collateral::CollateralManager;
+use orderbook_service::config::ServiceConfig;
+use orderbook_service::fees::{fee_report, plan_sweeps};
+use orderbook_service::storage::Database;
+use orderbook_service::types::{FeeLedgerEntry, OrderSide, SettlementStatus, Trade, TradeType};
+
+fn trade(trade_type: TradeType, price: u64, size: u128) -> Trade {
+    Trade {
+        trade_id: Uuid::new_v4(),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        maker_order_id: Uuid::new_v4(),
+        taker_order_id: Uuid::new_v4(),
+        maker_account: "alice.testnet".to_string(),
+        taker_account: "bob.testnet".to_string(),
+        maker_side: OrderSide::Sell,
+        taker_side: OrderSide::Buy,
+        outcome: 1,
+        price,
+        size,
+        trade_type,
+        executed_at: Utc::now(),
+        settlement_status: SettlementStatus::Pending,
+        settlement_tx_hash: None,
+        maker_fee: 0,
+        taker_fee: 0,
+    }
+}
+
+fn entry(market_id: &str, collateral_token: &str, maker_fee: u128, taker_fee: u128) -> FeeLedgerEntry {
+    FeeLedgerEntry {
+        trade_id: Uuid::new_v4(),
+        market_id: market_id.to_string(),
+        collateral_token: collateral_token.to_string(),
+        maker_fee,
+        taker_fee,
+        collected_at: Utc::now(),
+        sweep_id: None,
+    }
+}
+
+#[test]
+fn test_fees_are_charged_on_each_sides_collateral_leg() {
+    // 100 YES @ $0.30: both sides of a direct match move $30
+    let direct = trade(TradeType::DirectMatch, 30000, 100_000_000);
+    assert_eq!(CollateralManager::collateral_legs(&direct), (30_000_000, 30_000_000));
+    assert_eq!(CollateralManager::trade_fees(&direct, 10, 20), (30_000, 60_000));
+
+    // A mint's maker pays the complement of the taker's price
+    let mint = trade(TradeType::Minting, 30000, 100_000_000);
+    assert_eq!(CollateralManager::collateral_legs(&mint), (70_000_000, 30_000_000));
+    assert_eq!(CollateralManager::trade_fees(&mint, 10, 20), (70_000, 60_000));
+
+    // Rounded down, and free markets charge nothing
+    assert_eq!(CollateralManager::trade_fees(&trade(TradeType::DirectMatch, 50000, 999), 10, 10), (0, 0));
+    assert_eq!(CollateralManager::trade_fees(&direct, 0, 0), (0, 0));
+}
+
+#[tokio::test]
+async fn test_ledger_records_each_trade_once() {
+    let db = Database::new().await.unwrap();
+    let first = entry("market_1", "usdc.testnet", 500, 1000);
+
+    assert!(db.record_trade_fees(&first).await.unwrap());
+    assert!(!db.record_trade_fees(&FeeLedgerEntry { maker_fee: 9999, ..first.clone() }).await.unwrap());
+
+    let summary = db.get_fee_summary().await.unwrap();
+    assert_eq!(summary.len(), 1);
+    assert_eq!((summary[0].trade_count, summary[0].maker_fees, summary[0].taker_fees), (1, 500, 1000));
+}
+
+#[tokio::test]
+async fn test_report_breaks_fees_down_per_market_and_token() {
+    let db = Database::new().await.unwrap();
+    for fees in [
+        entry("market_1", "usdc.testnet", 500, 1000),
+        entry("market_1", "usdc.testnet", 250, 500),
+        entry("market_2", "usdc.testnet", 100, 200),
+        entry("market_3", "wrap.testnet", 7, 9),
+    ] {
+        db.record_trade_fees(&fees).await.unwrap();
+    }
+
+    let mut config = ServiceConfig::new("usdc.testnet", "ctf.testnet", "verifier.testnet", "solver.testnet", "platform.testnet");
+    config.apply_solver_fee_bps(25);
+    let report = fee_report(&db, &config).await.unwrap();
+
+    assert_eq!((report.default_maker_fee_bps, report.default_taker_fee_bps), (25, 25));
+    assert_eq!(report.platform_account_id, "platform.testnet");
+    let markets: Vec<(&str, usize, u128, u128, u128)> = report.markets.iter()
+        .map(|m| (m.market_id.as_str(), m.trade_count, m.maker_fees, m.taker_fees, m.unswept))
+        .collect();
+    assert_eq!(markets, vec![
+        ("market_1", 2, 750, 1500, 2250),
+        ("market_2", 1, 100, 200, 300),
+        ("market_3", 1, 7, 9, 16),
+    ]);
+    assert_eq!(report.unswept_by_token.get("usdc.testnet"), Some(&2550));
+    assert_eq!(report.unswept_by_token.get("wrap.testnet"), Some(&16));
+}
+
+#[tokio::test]
+async fn test_sweep_plan_covers_only_unswept_fees() {
+    let db = Database::new().await.unwrap();
+    let swept = entry("market_1", "usdc.testnet", 500, 1000);
+    let pending = entry("market_1", "usdc.testnet", 250, 500);
+    let wrapped = entry("market_2", "wrap.testnet", 7, 9);
+    for fees in [&swept, &pending, &wrapped] {
+        db.record_trade_fees(fees).await.unwrap();
+    }
+
+    let earlier_sweep = Uuid::new_v4();
+    assert_eq!(db.mark_fees_swept(&[swept.trade_id], earlier_sweep).await.unwrap(), 1);
+    // Already swept entries are not claimed by a later sweep
+    assert_eq!(db.mark_fees_swept(&[swept.trade_id], Uuid::new_v4()).await.unwrap(), 0);
+
+    let plan = plan_sweeps(&db.get_unswept_fees().await.unwrap());
+    assert_eq!(plan.len(), 2);
+    assert_eq!(plan["usdc.testnet"], (750, vec![pending.trade_id]));
+    assert_eq!(plan["wrap.testnet"], (16, vec![wrapped.trade_id]));
+
+    db.mark_fees_swept(&[pending.trade_id, wrapped.trade_id], Uuid::new_v4()).await.unwrap();
+    assert!(db.get_unswept_fees().await.unwrap().is_empty());
+
+    let summary = db.get_fee_summary().await.unwrap();
+    assert_eq!(summary[0].unswept, 0);
+    assert_eq!(summary[0].maker_fees + summary[0].taker_fees, 2250);
+}
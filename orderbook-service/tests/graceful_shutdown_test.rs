@@ -0,0 +1,89 @@
+// Graceful shutdown: the coordinator cancels intake, waits for queued settlements to drain and
+// journals a clean stop marker; a drain that overruns the timeout is journaled as unclean
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use orderbook_service::journal::{EventJournal, JournalEvent};
+use orderbook_service::shutdown::{ShutdownCoordinator, ShutdownOutcome};
+
+fn journal_path() -> PathBuf {
+    std::env::temp_dir().join(format!("orderbook-journal-{}.jsonl", Uuid::new_v4()))
+}
+
+async fn journal_events(path: &PathBuf) -> Vec<JournalEvent> {
+    EventJournal::read_entries(path)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.event)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_pending_settlements_and_journals_clean_stop() {
+    let path = journal_path();
+    let journal = Arc::new(EventJournal::open(&path).await.unwrap());
+    journal.record(JournalEvent::Started).await.unwrap();
+
+    let token = CancellationToken::new();
+    let coordinator = ShutdownCoordinator::new(token.clone(), journal, Duration::from_secs(5));
+
+    // Stand-in settlement worker with three trades queued; like the real one it only drains once cancelled
+    let (trade_tx, mut trade_rx) = mpsc::unbounded_channel::<Uuid>();
+    for _ in 0..3 {
+        trade_tx.send(Uuid::new_v4()).unwrap();
+    }
+    let worker_token = token.clone();
+    let worker = tokio::spawn(async move {
+        worker_token.cancelled().await;
+        trade_rx.close();
+        let mut settled = 0;
+        while trade_rx.recv().await.is_some() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            settled += 1;
+        }
+        settled
+    });
+
+    let outcome = coordinator
+        .shutdown(async { Ok::<_, anyhow::Error>(worker.await?) })
+        .await
+        .unwrap();
+
+    assert!(token.is_cancelled());
+    assert_eq!(outcome, ShutdownOutcome { settled_trades: 3, clean: true });
+    assert_eq!(journal_events(&path).await, vec![
+        JournalEvent::Started,
+        JournalEvent::ShutdownRequested,
+        JournalEvent::SettlementsDrained { trades: 3 },
+        JournalEvent::Stopped { clean: true },
+    ]);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_drain_timeout_journals_unclean_stop() {
+    let path = journal_path();
+    let journal = Arc::new(EventJournal::open(&path).await.unwrap());
+    let coordinator = ShutdownCoordinator::new(CancellationToken::new(), journal, Duration::from_millis(50));
+
+    // A settlement stuck on-chain never finishes draining
+    let outcome = coordinator
+        .shutdown(std::future::pending::<anyhow::Result<usize>>())
+        .await
+        .unwrap();
+
+    assert_eq!(outcome, ShutdownOutcome { settled_trades: 0, clean: false });
+    assert_eq!(journal_events(&path).await, vec![
+        JournalEvent::ShutdownRequested,
+        JournalEvent::Stopped { clean: false },
+    ]);
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,113 @@
+// Idempotent order submission: duplicates racing the first request, replays, payload conflicts and
+// keys freed by failures or expiry
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use orderbook_service::api::ApiError;
+use orderbook_service::idempotency::{client_key, payload_hash, submit_once, IdempotentResponse};
+use orderbook_service::storage::Database;
+
+const TTL_SECS: u64 = 86_400;
+
+/// Submit under `key`, counting how many times the order actually gets placed
+async fn submit(
+    db: &Database,
+    account: &str,
+    key: &str,
+    payload: Value,
+    placed: &AtomicUsize,
+) -> Result<IdempotentResponse, ApiError> {
+    let hash = payload_hash(&payload).unwrap();
+    submit_once(db, account, key, hash, TTL_SECS, |order_id| async move {
+        placed.fetch_add(1, Ordering::SeqCst);
+        // Long enough for the duplicates to arrive while this one is still in flight
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok(json!({ "order_id": order_id, "status": "Pending" }))
+    }).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_duplicates_place_the_order_once() {
+    let db = Arc::new(Database::new().await.unwrap());
+    let placed = Arc::new(AtomicUsize::new(0));
+    let payload = json!({ "market_id": "market_1", "side": "Buy", "price": 30000, "size": 1000 });
+
+    let handles: Vec<_> = (0..8).map(|_| {
+        let (db, placed, payload) = (db.clone(), placed.clone(), payload.clone());
+        tokio::spawn(async move { submit(&db, "alice.testnet", "retry-1", payload, &placed).await })
+    }).collect();
+
+    let mut responses = Vec::new();
+    for handle in handles {
+        responses.push(handle.await.unwrap().unwrap());
+    }
+
+    assert_eq!(placed.load(Ordering::SeqCst), 1);
+    assert_eq!(responses.iter().filter(|r| !r.replayed).count(), 1);
+    assert!(responses.iter().all(|r| r.order_id == responses[0].order_id && r.response == responses[0].response));
+    assert_eq!(responses[0].response["order_id"], json!(responses[0].order_id));
+}
+
+#[tokio::test]
+async fn test_different_payload_under_same_key_conflicts() {
+    let db = Database::new().await.unwrap();
+    let placed = AtomicUsize::new(0);
+
+    let first = submit(&db, "alice.testnet", "retry-1", json!({ "size": 1000 }), &placed).await.unwrap();
+    let replay = submit(&db, "alice.testnet", "retry-1", json!({ "size": 1000 }), &placed).await.unwrap();
+    assert!(replay.replayed);
+    assert_eq!(replay.order_id, first.order_id);
+
+    let conflict = submit(&db, "alice.testnet", "retry-1", json!({ "size": 2000 }), &placed).await;
+    assert!(matches!(conflict, Err(ApiError::IdempotencyConflict(_))));
+    assert_eq!(placed.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_failed_submission_frees_the_key() {
+    let db = Database::new().await.unwrap();
+    let hash = payload_hash(&json!({ "size": 1000 })).unwrap();
+
+    let failed = submit_once(&db, "alice.testnet", "retry-1", hash.clone(), TTL_SECS, |_| async {
+        Err::<Value, _>(ApiError::InsufficientBalance { asset: "USDC".to_string(), required: 1000, available: 0 })
+    }).await;
+    assert!(matches!(failed, Err(ApiError::InsufficientBalance { .. })));
+
+    let retried = submit_once(&db, "alice.testnet", "retry-1", hash, TTL_SECS, |order_id| async move {
+        Ok(json!({ "order_id": order_id }))
+    }).await.unwrap();
+    assert!(!retried.replayed);
+}
+
+#[tokio::test]
+async fn test_keys_expire_and_are_scoped_per_account() {
+    let db = Database::new().await.unwrap();
+    let placed = AtomicUsize::new(0);
+    let payload = json!({ "size": 1000 });
+
+    let alice = submit(&db, "alice.testnet", "retry-1", payload.clone(), &placed).await.unwrap();
+    let bob = submit(&db, "bob.testnet", "retry-1", payload.clone(), &placed).await.unwrap();
+    assert!(!bob.replayed);
+    assert_ne!(alice.order_id, bob.order_id);
+
+    // Past the TTL the same key places a new order
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let hash = payload_hash(&payload).unwrap();
+    let again = submit_once(&db, "alice.testnet", "retry-1", hash, 0, |order_id| async move {
+        Ok(json!({ "order_id": order_id }))
+    }).await.unwrap();
+    assert!(!again.replayed);
+    assert_ne!(again.order_id, alice.order_id);
+}
+
+#[test]
+fn test_client_keys_are_validated() {
+    assert_eq!(client_key(None).unwrap(), None);
+    assert_eq!(client_key(Some(" retry-1 ")).unwrap(), Some("retry-1".to_string()));
+    assert!(client_key(Some("  ")).is_err());
+    assert!(client_key(Some(&"k".repeat(129))).is_err());
+}
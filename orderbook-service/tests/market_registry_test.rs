@@ -0,0 +1,79 @@
+// Market registry tests against the in-memory database
+
+use chrono::{Duration, Utc};
+
+use orderbook_service::storage::Database;
+use orderbook_service::types::{MarketConditionRecord, MarketRegistrationSource};
+
+fn record(market_id: &str, condition_id: &str, source: MarketRegistrationSource) -> MarketConditionRecord {
+    let now = Utc::now();
+    MarketConditionRecord {
+        market_id: market_id.to_string(),
+        condition_id: condition_id.to_string(),
+        source,
+        is_active: true,
+        collateral_token: "usdc.testnet".to_string(),
+        outcome_count: 2,
+        registered_at: now,
+        updated_at: now,
+    }
+}
+
+#[tokio::test]
+async fn test_upsert_and_lookup_market_condition() {
+    let db = Database::new().await.unwrap();
+
+    assert_eq!(db.get_market_condition_id("market_1").await.unwrap(), None);
+
+    db.upsert_market_condition(&record("market_1", "condition_a", MarketRegistrationSource::Manual))
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_market_condition_id("market_1").await.unwrap(),
+        Some("condition_a".to_string())
+    );
+
+    // Re-registering updates the condition but keeps the original registration time
+    let original = db.get_registered_markets().await.unwrap()[0].registered_at;
+    let mut updated = record("market_1", "condition_b", MarketRegistrationSource::VerifierSync);
+    updated.registered_at = original + Duration::hours(1);
+    db.upsert_market_condition(&updated).await.unwrap();
+
+    let markets = db.get_registered_markets().await.unwrap();
+    assert_eq!(markets.len(), 1);
+    assert_eq!(markets[0].condition_id, "condition_b");
+    assert_eq!(markets[0].source, MarketRegistrationSource::VerifierSync);
+    assert_eq!(markets[0].registered_at, original);
+}
+
+#[tokio::test]
+async fn test_registered_markets_newest_first() {
+    let db = Database::new().await.unwrap();
+
+    let mut older = record("market_old", "condition_old", MarketRegistrationSource::LegacyImport);
+    older.registered_at = Utc::now() - Duration::days(1);
+    db.upsert_market_condition(&older).await.unwrap();
+    db.upsert_market_condition(&record("market_new", "condition_new", MarketRegistrationSource::Manual))
+        .await
+        .unwrap();
+
+    let market_ids: Vec<String> = db.get_registered_markets().await.unwrap()
+        .into_iter()
+        .map(|m| m.market_id)
+        .collect();
+    assert_eq!(market_ids, vec!["market_new".to_string(), "market_old".to_string()]);
+}
+
+#[tokio::test]
+async fn test_market_condition_keeps_collateral_info() {
+    let db = Database::new().await.unwrap();
+
+    let mut categorical = record("market_cat", "condition_cat", MarketRegistrationSource::Manual);
+    categorical.outcome_count = 3;
+    db.upsert_market_condition(&categorical).await.unwrap();
+
+    let stored = db.get_market_condition("market_cat").await.unwrap().unwrap();
+    assert_eq!(stored.collateral_token, "usdc.testnet");
+    assert_eq!(stored.outcome_count, 3);
+    assert!(db.get_market_condition("market_missing").await.unwrap().is_none());
+}
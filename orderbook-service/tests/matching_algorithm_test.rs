@@ -77,6 +77,8 @@ impl TestOrderbook {
                                     executed_at: Utc::now(),
                                     settlement_status: SettlementStatus::Pending,
                                     settlement_tx_hash: None,
+                                    maker_fee: 0,
+                                    taker_fee: 0,
                                 };
 
                                 trades.push(trade);
@@ -140,6 +142,8 @@ impl TestOrderbook {
                                     executed_at: Utc::now(),
                                     settlement_status: SettlementStatus::Pending,
                                     settlement_tx_hash: None,
+                                    maker_fee: 0,
+                                    taker_fee: 0,
                                 };
 
                                 trades.push(trade);
@@ -0,0 +1,94 @@
+// Expired order purge against the in-memory database: orders past their TTL are deleted unless
+// they are filled, cancelled or referenced by a trade
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use orderbook_service::storage::Database;
+use orderbook_service::types::{Order, OrderSide, OrderStatus, OrderType, SettlementStatus, Trade, TradeType};
+
+fn order(status: OrderStatus, expires_at: Option<DateTime<Utc>>) -> Order {
+    Order {
+        order_id: Uuid::new_v4(),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        user_account: "alice.testnet".to_string(),
+        outcome: 1,
+        side: OrderSide::Buy,
+        order_type: OrderType::Limit,
+        price: 50000,
+        original_size: 100,
+        remaining_size: 100,
+        filled_size: 0,
+        status,
+        created_at: Utc::now() - Duration::hours(1),
+        expires_at,
+        solver_account: "solver.testnet".to_string(),
+    }
+}
+
+fn trade_against(maker: &Order) -> Trade {
+    Trade {
+        trade_id: Uuid::new_v4(),
+        market_id: maker.market_id.clone(),
+        condition_id: maker.condition_id.clone(),
+        maker_order_id: maker.order_id,
+        taker_order_id: Uuid::new_v4(),
+        maker_account: maker.user_account.clone(),
+        taker_account: "bob.testnet".to_string(),
+        maker_side: OrderSide::Buy,
+        taker_side: OrderSide::Sell,
+        outcome: maker.outcome,
+        price: maker.price,
+        size: 40,
+        trade_type: TradeType::DirectMatch,
+        executed_at: Utc::now(),
+        settlement_status: SettlementStatus::Settled,
+        settlement_tx_hash: None,
+        maker_fee: 0,
+        taker_fee: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_purge_deletes_only_expired_untraded_orders() {
+    let db = Database::new().await.unwrap();
+    let now = Utc::now();
+    let past = Some(now - Duration::minutes(5));
+
+    let resting = order(OrderStatus::Pending, past);
+    let marked_expired = order(OrderStatus::Expired, past);
+    let filled = order(OrderStatus::Filled, past);
+    let cancelled = order(OrderStatus::Cancelled, past);
+    let not_yet = order(OrderStatus::Pending, Some(now + Duration::minutes(5)));
+    let no_ttl = order(OrderStatus::Pending, None);
+    let mut traded = order(OrderStatus::Expired, past);
+    traded.filled_size = 40;
+    traded.remaining_size = 60;
+
+    for o in [&resting, &marked_expired, &filled, &cancelled, &not_yet, &no_ttl, &traded] {
+        db.insert_order(o).await.unwrap();
+    }
+    db.insert_trade(&trade_against(&traded)).await.unwrap();
+
+    // A dry run reports the candidates without deleting anything
+    let mut candidates: Vec<Uuid> = db.get_purgeable_orders(now).await.unwrap().iter().map(|o| o.order_id).collect();
+    let mut expected = vec![resting.order_id, marked_expired.order_id];
+    candidates.sort();
+    expected.sort();
+    assert_eq!(candidates, expected);
+    assert!(db.get_order(resting.order_id).await.unwrap().is_some());
+
+    let mut purged: Vec<Uuid> = db.purge_expired_orders(now).await.unwrap().iter().map(|o| o.order_id).collect();
+    purged.sort();
+    assert_eq!(purged, expected);
+
+    assert!(db.get_order(resting.order_id).await.unwrap().is_none());
+    assert!(db.get_order(marked_expired.order_id).await.unwrap().is_none());
+    for kept in [&filled, &cancelled, &not_yet, &no_ttl, &traded] {
+        assert!(db.get_order(kept.order_id).await.unwrap().is_some());
+    }
+
+    // Nothing left to purge on the next pass
+    assert!(db.purge_expired_orders(now).await.unwrap().is_empty());
+}
@@ -0,0 +1,79 @@
+// Orderbook integrity checksum: XOR of xxh3 over resting order ids and fills, kept in step with
+// every book mutation so it always equals a checksum recomputed from scratch
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use orderbook_service::matching::engine::{book_checksum, order_checksum, OrderBook};
+use orderbook_service::types::{Order, OrderSide, OrderStatus, OrderType};
+
+fn order(side: OrderSide, price: u64, size: u128) -> Order {
+    Order {
+        order_id: Uuid::new_v4(),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        user_account: "alice.testnet".to_string(),
+        outcome: 1,
+        side,
+        order_type: OrderType::Limit,
+        price,
+        original_size: size,
+        remaining_size: size,
+        filled_size: 0,
+        status: OrderStatus::Pending,
+        created_at: Utc::now(),
+        expires_at: None,
+        solver_account: "solver.testnet".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_checksum_follows_adds_fills_and_removals() {
+    let mut book = OrderBook::new();
+    assert_eq!(book.checksum().checksum, 0);
+
+    let ask = order(OrderSide::Sell, 60000, 100);
+    let bid = order(OrderSide::Buy, 40000, 50);
+    book.add_order(ask.clone()).await.unwrap();
+    book.add_order(bid.clone()).await.unwrap();
+    assert_eq!(book.checksum().checksum, book_checksum([(ask.order_id, 0), (bid.order_id, 0)]));
+
+    // A taker fills the ask completely, taking it out of the checksum
+    let trades = book.match_order(order(OrderSide::Buy, 60000, 100)).await.unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(book.checksum().checksum, order_checksum(bid.order_id, 0));
+
+    // A complementary partial fill moves the bid's contribution to its new fill
+    book.update_order_size(bid.order_id, 20).await.unwrap();
+    assert_eq!(book.checksum().checksum, order_checksum(bid.order_id, 30));
+
+    book.remove_order(bid.order_id).await.unwrap();
+    assert_eq!(book.checksum().checksum, 0);
+}
+
+#[tokio::test]
+async fn test_sequence_increases_with_every_mutation() {
+    let mut book = OrderBook::new();
+    let ask = order(OrderSide::Sell, 60000, 100);
+
+    book.add_order(ask.clone()).await.unwrap();
+    let after_add = book.checksum();
+    book.update_order_size(ask.order_id, 80).await.unwrap();
+    let after_fill = book.checksum();
+
+    assert!(after_fill.sequence > after_add.sequence);
+    assert_eq!(after_fill.checksum, order_checksum(ask.order_id, 20));
+
+    // Removing an order that isn't resting leaves the book untouched
+    book.remove_order(Uuid::new_v4()).await.unwrap();
+    assert_eq!(book.checksum(), after_fill);
+}
+
+#[test]
+fn test_order_checksum_depends_on_fill() {
+    let order_id = Uuid::new_v4();
+    assert_ne!(order_checksum(order_id, 0), order_checksum(order_id, 1));
+    // XOR makes the book checksum independent of order
+    let other = Uuid::new_v4();
+    assert_eq!(book_checksum([(order_id, 5), (other, 7)]), book_checksum([(other, 7), (order_id, 5)]));
+}
@@ -0,0 +1,121 @@
+// Client-side book building: a snapshot at sequence S plus the deltas after S reproduces every
+// later snapshot, however the deltas straddle the moment the snapshot was taken
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use orderbook_service::matching::engine::OrderBook;
+use orderbook_service::types::{Order, OrderSide, OrderStatus, OrderType, OrderbookSyncSnapshot};
+
+fn order(side: OrderSide, price: u64, size: u128) -> Order {
+    Order {
+        order_id: Uuid::new_v4(),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        user_account: "alice.testnet".to_string(),
+        outcome: 1,
+        side,
+        order_type: OrderType::Limit,
+        price,
+        original_size: size,
+        remaining_size: size,
+        filled_size: 0,
+        status: OrderStatus::Pending,
+        created_at: Utc::now(),
+        expires_at: None,
+        solver_account: "solver.testnet".to_string(),
+    }
+}
+
+async fn snapshot(book: &OrderBook) -> OrderbookSyncSnapshot {
+    book.get_sync_snapshot("market_1", 1).await.unwrap()
+}
+
+fn assert_same_book(local: &OrderbookSyncSnapshot, live: &OrderbookSyncSnapshot) {
+    assert_eq!(local.sequence, live.sequence);
+    assert_eq!(local.snapshot.bids, live.snapshot.bids);
+    assert_eq!(local.snapshot.asks, live.snapshot.asks);
+    assert_eq!(local.snapshot.last_trade_price, live.snapshot.last_trade_price);
+}
+
+#[tokio::test]
+async fn test_replaying_deltas_over_a_snapshot_reproduces_the_book() {
+    let mut book = OrderBook::new();
+    let mut deltas = Vec::new();
+
+    let resting_bid = order(OrderSide::Buy, 40000, 100);
+    book.add_order(resting_bid.clone()).await.unwrap();
+    book.add_order(order(OrderSide::Sell, 60000, 100)).await.unwrap();
+    book.add_order(order(OrderSide::Sell, 60000, 50)).await.unwrap();
+    deltas.extend(book.take_delta("market_1", 1));
+
+    // The snapshot is taken while a delta is still pending: the 45000 bid goes out in the same
+    // delta as the fill after it
+    book.add_order(order(OrderSide::Buy, 45000, 30)).await.unwrap();
+    let mut local = snapshot(&book).await;
+    assert_eq!(local.first_delta_sequence, local.sequence + 1);
+
+    // A taker sweeps the 60000 level and partially fills into a new 61000 ask
+    book.add_order(order(OrderSide::Sell, 61000, 80)).await.unwrap();
+    let trades = book.match_order(order(OrderSide::Buy, 61000, 170)).await.unwrap();
+    assert_eq!(trades.len(), 3);
+    deltas.extend(book.take_delta("market_1", 1));
+
+    // An order that comes and goes within one delta, next to a complementary fill
+    let fleeting = order(OrderSide::Buy, 40000, 5);
+    book.add_order(fleeting.clone()).await.unwrap();
+    book.remove_order(fleeting.order_id).await.unwrap();
+    book.update_order_size(resting_bid.order_id, 60).await.unwrap();
+    deltas.extend(book.take_delta("market_1", 1));
+
+    book.remove_order(resting_bid.order_id).await.unwrap();
+    deltas.extend(book.take_delta("market_1", 1));
+    assert!(book.take_delta("market_1", 1).is_none());
+
+    // Deltas chain, and the first one predates the snapshot
+    for pair in deltas.windows(2) {
+        assert_eq!(pair[1].prev_sequence, pair[0].sequence);
+    }
+    assert!(deltas[0].sequence <= local.sequence);
+
+    let mut applied = 0;
+    for delta in &deltas {
+        if local.apply_delta(delta).unwrap() {
+            applied += 1;
+        }
+    }
+    assert_eq!(applied, deltas.len() - 1);
+    assert_same_book(&local, &snapshot(&book).await);
+    assert!(local.snapshot.bids.iter().all(|level| level.price != 40000));
+}
+
+#[tokio::test]
+async fn test_delta_reports_removed_levels_with_zero_orders() {
+    let mut book = OrderBook::new();
+    let ask = order(OrderSide::Sell, 55000, 10);
+    book.add_order(ask.clone()).await.unwrap();
+    book.take_delta("market_1", 1);
+
+    book.remove_order(ask.order_id).await.unwrap();
+    let delta = book.take_delta("market_1", 1).unwrap();
+    assert!(delta.bids.is_empty());
+    assert_eq!((delta.asks[0].price, delta.asks[0].order_count), (55000, 0));
+}
+
+#[tokio::test]
+async fn test_missed_delta_is_reported() {
+    let mut book = OrderBook::new();
+    book.add_order(order(OrderSide::Buy, 40000, 100)).await.unwrap();
+    let mut local = snapshot(&book).await;
+    book.take_delta("market_1", 1);
+
+    book.add_order(order(OrderSide::Buy, 41000, 100)).await.unwrap();
+    let missed = book.take_delta("market_1", 1).unwrap();
+    book.add_order(order(OrderSide::Buy, 42000, 100)).await.unwrap();
+    let next = book.take_delta("market_1", 1).unwrap();
+
+    assert!(local.apply_delta(&next).is_err());
+    assert!(local.apply_delta(&missed).unwrap());
+    assert!(local.apply_delta(&next).unwrap());
+    assert_same_book(&local, &snapshot(&book).await);
+}
@@ -0,0 +1,228 @@
+// Wire protocol contract: every message type serializes exactly as its golden fixture in
+// tests/fixtures/protocol/v<n>. A failure here means a client-visible format change; add a protocol
+// version with its own structs instead of editing the fixture
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+use orderbook_service::api::protocol::{self, ClientMessage, ControlMessage, SUPPORTED_VERSIONS};
+use orderbook_service::types::{
+    OrderSide, OrderStatus, OrderType, OrderbookDelta, OrderbookSnapshot, PriceLevel, SettlementStatus, SubmitOrderRequest,
+    SubmitOrderResponse, Trade, TradeMatch, TradeType, WebSocketMessage,
+};
+
+fn fixture(name: &str) -> Value {
+    let path = format!("{}/tests/fixtures/protocol/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("missing fixture {}: {}", path, e));
+    serde_json::from_str(&text).unwrap()
+}
+
+fn assert_wire_format(name: &str, encoded: &str) {
+    let actual: Value = serde_json::from_str(encoded).unwrap();
+    assert_eq!(actual, fixture(name), "wire format of {} changed without a protocol version bump", name);
+}
+
+fn id(n: u128) -> Uuid {
+    Uuid::from_u128(n)
+}
+
+fn timestamp() -> DateTime<Utc> {
+    "2025-01-01T00:00:00Z".parse().unwrap()
+}
+
+fn trade() -> Trade {
+    Trade {
+        trade_id: id(3),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        maker_order_id: id(1),
+        taker_order_id: id(2),
+        maker_account: "alice.testnet".to_string(),
+        taker_account: "bob.testnet".to_string(),
+        maker_side: OrderSide::Sell,
+        taker_side: OrderSide::Buy,
+        outcome: 1,
+        price: 50000,
+        size: 1_000_000,
+        trade_type: TradeType::DirectMatch,
+        executed_at: timestamp(),
+        settlement_status: SettlementStatus::Pending,
+        settlement_tx_hash: None,
+        maker_fee: 500,
+        taker_fee: 1000,
+    }
+}
+
+fn snapshot() -> OrderbookSnapshot {
+    OrderbookSnapshot {
+        market_id: "market_1".to_string(),
+        outcome: 1,
+        bids: vec![PriceLevel { price: 49000, size: 2_000_000, order_count: 2 }],
+        asks: vec![PriceLevel { price: 51000, size: 1_000_000, order_count: 1 }],
+        last_trade_price: Some(50000),
+        timestamp: timestamp(),
+        degraded: false,
+        staleness_secs: None,
+    }
+}
+
+fn orderbook_update(snapshot: OrderbookSnapshot) -> WebSocketMessage {
+    WebSocketMessage::OrderbookUpdate {
+        market_id: "market_1".to_string(),
+        outcome: 1,
+        snapshot,
+        sequence: 42,
+        checksum: 123456789,
+    }
+}
+
+fn market_data_messages() -> Vec<(&'static str, WebSocketMessage)> {
+    vec![
+        ("orderbook_update.json", orderbook_update(snapshot())),
+        ("trade_executed.json", WebSocketMessage::TradeExecuted { trade: trade() }),
+        ("order_update.json", WebSocketMessage::OrderUpdate {
+            order_id: id(1),
+            status: OrderStatus::PartiallyFilled,
+            filled_size: 400_000,
+        }),
+        ("order_expired.json", WebSocketMessage::OrderExpired {
+            order_id: id(1),
+            market_id: "market_1".to_string(),
+            outcome: 1,
+            user_account: "alice.testnet".to_string(),
+            expires_at: Some(timestamp()),
+        }),
+    ]
+}
+
+#[test]
+fn test_v1_market_data_messages_match_fixtures() {
+    // v1 trades never carry fees
+    for (name, message) in &market_data_messages() {
+        assert_wire_format(&format!("v1/{}", name), &protocol::encode(message, 1).unwrap().unwrap());
+    }
+}
+
+#[test]
+fn test_v2_market_data_messages_match_fixtures() {
+    for (name, message) in &market_data_messages() {
+        assert_wire_format(&format!("v2/{}", name), &protocol::encode(message, 2).unwrap().unwrap());
+    }
+}
+
+fn orderbook_delta() -> WebSocketMessage {
+    WebSocketMessage::OrderbookDelta(OrderbookDelta {
+        market_id: "market_1".to_string(),
+        outcome: 1,
+        prev_sequence: 42,
+        sequence: 44,
+        bids: vec![PriceLevel { price: 49000, size: 0, order_count: 0 }],
+        asks: vec![PriceLevel { price: 51000, size: 600_000, order_count: 1 }],
+        last_trade_price: Some(51000),
+        timestamp: timestamp(),
+    })
+}
+
+#[test]
+fn test_v3_market_data_messages_match_fixtures() {
+    let mut messages = market_data_messages();
+    messages.push(("orderbook_delta.json", orderbook_delta()));
+    for (name, message) in &messages {
+        assert_wire_format(&format!("v3/{}", name), &protocol::encode(message, 3).unwrap().unwrap());
+    }
+}
+
+#[test]
+fn test_deltas_are_not_sent_before_v3() {
+    assert_eq!(protocol::encode(&orderbook_delta(), 1).unwrap(), None);
+    assert_eq!(protocol::encode(&orderbook_delta(), 2).unwrap(), None);
+}
+
+fn chain_status() -> WebSocketMessage {
+    WebSocketMessage::ChainStatus { degraded: true, staleness_secs: 95 }
+}
+
+#[test]
+fn test_v4_market_data_messages_match_fixtures() {
+    let stale = OrderbookSnapshot { degraded: true, staleness_secs: Some(95), ..snapshot() };
+    let mut messages = market_data_messages();
+    messages.push(("orderbook_delta.json", orderbook_delta()));
+    messages.push(("chain_status.json", chain_status()));
+    messages.push(("orderbook_update_degraded.json", orderbook_update(stale)));
+
+    for (name, message) in &messages {
+        assert_wire_format(&format!("v4/{}", name), &protocol::encode(message, 4).unwrap().unwrap());
+    }
+}
+
+#[test]
+fn test_chain_health_is_not_sent_before_v4() {
+    for version in 1..=3 {
+        assert_eq!(protocol::encode(&chain_status(), version).unwrap(), None);
+    }
+
+    // Older snapshots drop the degraded flag
+    let stale = orderbook_update(OrderbookSnapshot { degraded: true, staleness_secs: Some(95), ..snapshot() });
+    assert_wire_format("v3/orderbook_update.json", &protocol::encode(&stale, 3).unwrap().unwrap());
+}
+
+#[test]
+fn test_v1_control_messages_match_fixtures() {
+    let messages = [
+        ("v1/connection_established.json", ControlMessage::ConnectionEstablished {
+            v: 1,
+            message: "WebSocket connection successful".to_string(),
+            timestamp: timestamp().to_rfc3339(),
+            supported_versions: vec![1],
+        }),
+        ("v1/hello_ack.json", ControlMessage::HelloAck { v: 1, supported_versions: vec![1] }),
+        ("v1/error.json", ControlMessage::Error {
+            v: 1,
+            message: format!("No common protocol version; server supports {:?}", [1]),
+        }),
+    ];
+
+    for (name, message) in &messages {
+        assert_wire_format(name, &serde_json::to_string(message).unwrap());
+    }
+
+    let hello: ClientMessage = serde_json::from_value(fixture("v1/hello.json")).unwrap();
+    assert_eq!(hello, ClientMessage::Hello { versions: vec![1, 2] });
+}
+
+#[test]
+fn test_v1_order_payloads_match_fixtures() {
+    let request: SubmitOrderRequest = serde_json::from_value(fixture("v1/submit_order_request.json")).unwrap();
+    assert_eq!(request.market_id, "market_1");
+    assert_eq!(request.side, OrderSide::Buy);
+    assert!(matches!(request.order_type, OrderType::Limit));
+    assert_eq!((request.price, request.size, request.expires_at), (Some(50000), 1_000_000, None));
+
+    let response = SubmitOrderResponse {
+        order_id: id(2),
+        status: "PartiallyFilled".to_string(),
+        message: "Order placed".to_string(),
+        matches: vec![TradeMatch {
+            trade_id: id(3),
+            counterparty: "alice.testnet".to_string(),
+            price: 50000,
+            size: 400_000,
+            settlement_pending: true,
+        }],
+    };
+    assert_wire_format("v1/submit_order_response.json", &serde_json::to_string(&response).unwrap());
+}
+
+#[test]
+fn test_version_negotiation() {
+    // Highest common version wins; none in common is refused
+    assert_eq!(protocol::negotiate_version(&[1]), Some(1));
+    assert_eq!(protocol::negotiate_version(&[1, 2]), Some(2));
+    assert_eq!(protocol::negotiate_version(&[1, 2, 3]), Some(3));
+    assert_eq!(protocol::negotiate_version(&[1, 2, 3, 4]), Some(4));
+    assert_eq!(protocol::negotiate_version(&[2]), Some(2));
+    assert_eq!(protocol::negotiate_version(&[5]), None);
+    assert_eq!(protocol::negotiate_version(&[]), None);
+    assert!(SUPPORTED_VERSIONS.contains(&protocol::DEFAULT_VERSION));
+}
@@ -0,0 +1,91 @@
+// Quote endpoint: a dry run must match what the real matcher does on the same book
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use orderbook_service::matching::engine::OrderBook;
+use orderbook_service::types::{Order, OrderQuote, OrderSide, OrderStatus, OrderType};
+
+fn create_order(user: &str, side: OrderSide, order_type: OrderType, price: u64, size: u128) -> Order {
+    Order {
+        order_id: Uuid::new_v4(),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        user_account: user.to_string(),
+        outcome: 1,
+        side,
+        order_type,
+        price,
+        original_size: size,
+        remaining_size: size,
+        filled_size: 0,
+        status: OrderStatus::Pending,
+        created_at: Utc::now(),
+        expires_at: None,
+        solver_account: "solver.testnet".to_string(),
+    }
+}
+
+async fn frozen_book() -> OrderBook {
+    let mut book = OrderBook::new();
+    book.add_order(create_order("maker1.testnet", OrderSide::Sell, OrderType::Limit, 50000, 100_000_000)).await.unwrap();
+    book.add_order(create_order("maker2.testnet", OrderSide::Sell, OrderType::Limit, 52000, 100_000_000)).await.unwrap();
+    book.add_order(create_order("maker3.testnet", OrderSide::Sell, OrderType::Limit, 55000, 200_000_000)).await.unwrap();
+    book
+}
+
+#[tokio::test]
+async fn test_quote_matches_actual_fill() {
+    let mut book = frozen_book().await;
+    let taker = create_order("taker.testnet", OrderSide::Buy, OrderType::Market, 0, 250_000_000);
+
+    let simulated = book.simulate_order(taker.clone()).await.unwrap();
+    let quote = OrderQuote::from_trades(&taker, &simulated, 100);
+
+    // The dry run leaves the book as it was
+    let snapshot = book.get_snapshot("market_1", 1).await.unwrap();
+    assert_eq!(snapshot.asks.len(), 3);
+    assert_eq!(book.order_count(), 3);
+
+    let actual = book.match_order(taker.clone()).await.unwrap();
+    let fills = |trades: &[orderbook_service::types::Trade]| {
+        trades.iter().map(|t| (t.price, t.size, t.maker_order_id)).collect::<Vec<_>>()
+    };
+    assert_eq!(fills(&simulated), fills(&actual));
+    assert_eq!(quote, OrderQuote::from_trades(&taker, &actual, 100));
+
+    // 100 @ 0.50 + 100 @ 0.52 + 50 @ 0.55 = 129.5 USDC
+    assert_eq!(quote.filled_size, 250_000_000);
+    assert_eq!(quote.notional, 129_500_000);
+    assert_eq!(quote.average_price, Some(51800));
+    assert_eq!(quote.best_price, Some(50000));
+    assert_eq!(quote.worst_price, Some(55000));
+    assert_eq!(quote.slippage_bps, 360);
+    assert_eq!(quote.estimated_fee, 1_295_000);
+}
+
+#[tokio::test]
+async fn test_quote_limit_order_partial_fill() {
+    let book = frozen_book().await;
+    let taker = create_order("taker.testnet", OrderSide::Buy, OrderType::Limit, 52000, 300_000_000);
+
+    let quote = OrderQuote::from_trades(&taker, &book.simulate_order(taker.clone()).await.unwrap(), 0);
+
+    // Only the two levels at or below the limit fill
+    assert_eq!(quote.requested_size, 300_000_000);
+    assert_eq!(quote.filled_size, 200_000_000);
+    assert_eq!(quote.worst_price, Some(52000));
+    assert_eq!(quote.estimated_fee, 0);
+}
+
+#[tokio::test]
+async fn test_quote_fok_that_cannot_fill() {
+    let book = frozen_book().await;
+    let taker = create_order("taker.testnet", OrderSide::Buy, OrderType::FOK, 50000, 150_000_000);
+
+    let quote = OrderQuote::from_trades(&taker, &book.simulate_order(taker.clone()).await.unwrap(), 100);
+
+    assert_eq!(quote.filled_size, 0);
+    assert_eq!(quote.average_price, None);
+    assert_eq!(quote.slippage_bps, 0);
+}
@@ -0,0 +1,115 @@
+// Order rate limiter tests against the in-memory database
+// Every request in these tests would arrive from the same relayer IP, so the limits must be
+// counted per account.
+
+use std::collections::HashSet;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use orderbook_service::api::ApiError;
+use orderbook_service::api::rate_limit::{RateLimitConfig, RateLimitUpdate, RateLimiter};
+use orderbook_service::storage::Database;
+use orderbook_service::types::{Order, OrderSide, OrderStatus, OrderType};
+
+fn order(account: &str, market_id: &str, status: OrderStatus, age_secs: i64) -> Order {
+    Order {
+        order_id: Uuid::new_v4(),
+        market_id: market_id.to_string(),
+        condition_id: format!("condition_{}", market_id),
+        user_account: account.to_string(),
+        outcome: 1,
+        side: OrderSide::Buy,
+        order_type: OrderType::Limit,
+        price: 50000,
+        original_size: 100,
+        remaining_size: 100,
+        filled_size: 0,
+        status,
+        created_at: Utc::now() - Duration::seconds(age_secs),
+        expires_at: None,
+        solver_account: "solver.testnet".to_string(),
+    }
+}
+
+fn limiter(max_open: usize, max_per_minute: usize) -> RateLimiter {
+    RateLimiter::new(RateLimitConfig {
+        global_orders_per_second: 0,
+        max_open_orders_per_market: max_open,
+        max_orders_per_minute: max_per_minute,
+        exempt_accounts: HashSet::from(["mm.testnet".to_string()]),
+    })
+}
+
+fn retry_after(result: Result<(), ApiError>) -> u64 {
+    match result {
+        Err(ApiError::RateLimited { retry_after_secs, .. }) => retry_after_secs,
+        other => panic!("expected RATE_LIMITED, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_open_order_limit_is_per_account_and_market() {
+    let db = Database::new().await.unwrap();
+    let limiter = limiter(2, 100);
+
+    for _ in 0..2 {
+        db.insert_order(&order("alice.testnet", "market_1", OrderStatus::Pending, 600)).await.unwrap();
+    }
+    // Filled orders don't count against the open order limit
+    db.insert_order(&order("alice.testnet", "market_1", OrderStatus::Filled, 600)).await.unwrap();
+
+    retry_after(limiter.check_account(&db, "alice.testnet", "market_1").await);
+    assert!(limiter.check_account(&db, "alice.testnet", "market_2").await.is_ok());
+    assert!(limiter.check_account(&db, "bob.testnet", "market_1").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_orders_per_minute_is_per_account() {
+    let db = Database::new().await.unwrap();
+    let limiter = limiter(100, 3);
+
+    // Orders older than the window are ignored
+    db.insert_order(&order("alice.testnet", "market_1", OrderStatus::Filled, 120)).await.unwrap();
+    for age in [45, 20, 5] {
+        db.insert_order(&order("alice.testnet", "market_1", OrderStatus::Cancelled, age)).await.unwrap();
+    }
+
+    // The oldest order in the window (45s ago) ages out in about 15s
+    let wait = retry_after(limiter.check_account(&db, "alice.testnet", "market_2").await);
+    assert!((14..=16).contains(&wait), "retry after {}", wait);
+
+    assert!(limiter.check_account(&db, "bob.testnet", "market_1").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_exempt_accounts_and_admin_update() {
+    let db = Database::new().await.unwrap();
+    let limiter = limiter(1, 1);
+
+    db.insert_order(&order("mm.testnet", "market_1", OrderStatus::Pending, 1)).await.unwrap();
+    db.insert_order(&order("alice.testnet", "market_1", OrderStatus::Pending, 1)).await.unwrap();
+
+    assert!(limiter.check_account(&db, "mm.testnet", "market_1").await.is_ok());
+    retry_after(limiter.check_account(&db, "alice.testnet", "market_1").await);
+
+    let config = limiter.update_config(RateLimitUpdate {
+        max_open_orders_per_market: Some(5),
+        max_orders_per_minute: Some(5),
+        ..Default::default()
+    });
+    assert_eq!(config.exempt_accounts.len(), 1);
+    assert!(limiter.check_account(&db, "alice.testnet", "market_1").await.is_ok());
+}
+
+#[test]
+fn test_global_bucket_exhausts() {
+    let limiter = RateLimiter::new(RateLimitConfig {
+        global_orders_per_second: 3,
+        ..Default::default()
+    });
+
+    for _ in 0..3 {
+        assert!(limiter.try_acquire_global().is_ok());
+    }
+    assert_eq!(retry_after(limiter.try_acquire_global()), 1);
+}
@@ -0,0 +1,189 @@
+// Chain reconciliation against the in-memory database: each class of divergence between a linked
+// local order and its solver contract order is reported with the action a pass takes for it
+
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use orderbook_service::reconciliation::{compare_with_chain, filled_as_maker, worst_fill_price};
+use orderbook_service::solver_integration::{SolverOrder, SolverOrderSide, SolverOrderStatus, SolverOrderType};
+use orderbook_service::storage::Database;
+use orderbook_service::types::{
+    DivergenceKind, Order, OrderSide, OrderStatus, OrderType, ReconcileAction, ReconciliationReport,
+    SettlementStatus, Trade, TradeType,
+};
+
+fn order(status: OrderStatus, filled_size: u128) -> Order {
+    Order {
+        order_id: Uuid::new_v4(),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        user_account: "alice.testnet".to_string(),
+        outcome: 1,
+        side: OrderSide::Buy,
+        order_type: OrderType::Limit,
+        price: 50000,
+        original_size: 100,
+        remaining_size: 100 - filled_size,
+        filled_size,
+        status,
+        created_at: Utc::now() - Duration::minutes(10),
+        expires_at: None,
+        solver_account: "solver.testnet".to_string(),
+    }
+}
+
+fn solver_order(order_id: &str, status: SolverOrderStatus, filled_amount: u128) -> SolverOrder {
+    SolverOrder {
+        order_id: order_id.to_string(),
+        intent_id: format!("intent_{}", order_id),
+        user: "alice.testnet".to_string(),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        outcome: 1,
+        side: SolverOrderSide::Buy,
+        order_type: SolverOrderType::Limit,
+        price: 50000,
+        amount: "100".to_string(),
+        filled_amount: filled_amount.to_string(),
+        status,
+        created_at: 0,
+        expires_at: 0,
+    }
+}
+
+async fn linked(db: &Database, order: &Order, solver_order_id: &str) {
+    db.insert_order(order).await.unwrap();
+    db.link_solver_order(order.order_id, solver_order_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_matching_orders_report_no_divergence() {
+    let db = Database::new().await.unwrap();
+    let resting = order(OrderStatus::PartiallyFilled, 40);
+    linked(&db, &resting, "order_1").await;
+    // Closed locally and already gone on-chain
+    let cancelled = order(OrderStatus::Cancelled, 0);
+    linked(&db, &cancelled, "order_2").await;
+
+    let onchain = vec![solver_order("order_1", SolverOrderStatus::PartiallyFilled, 40)];
+    let (checked, divergences) = compare_with_chain(&db, &onchain).await.unwrap();
+
+    assert_eq!(checked, 2);
+    assert!(divergences.is_empty());
+}
+
+#[tokio::test]
+async fn test_cancelled_on_chain_is_auto_healed() {
+    let db = Database::new().await.unwrap();
+    let resting = order(OrderStatus::Pending, 0);
+    linked(&db, &resting, "order_1").await;
+    let expired = order(OrderStatus::PartiallyFilled, 30);
+    linked(&db, &expired, "order_2").await;
+
+    let onchain = vec![
+        solver_order("order_1", SolverOrderStatus::Cancelled, 0),
+        solver_order("order_2", SolverOrderStatus::Expired, 30),
+    ];
+    let (_, divergences) = compare_with_chain(&db, &onchain).await.unwrap();
+
+    assert_eq!(divergences.len(), 2);
+    for divergence in &divergences {
+        assert_eq!(divergence.kind, DivergenceKind::CancelledOnChain);
+        assert_eq!(divergence.action, ReconcileAction::AutoHealed);
+    }
+    assert_eq!(divergences[0].order_id, resting.order_id);
+    assert_eq!(divergences[0].onchain_status.as_deref(), Some("Cancelled"));
+    assert_eq!(divergences[1].onchain_status.as_deref(), Some("Expired"));
+}
+
+#[tokio::test]
+async fn test_unreported_fill_is_queued_at_the_worst_trade_price() {
+    let db = Database::new().await.unwrap();
+    let filled = order(OrderStatus::Filled, 100);
+    linked(&db, &filled, "order_1").await;
+
+    let onchain = vec![solver_order("order_1", SolverOrderStatus::PartiallyFilled, 60)];
+    let (_, divergences) = compare_with_chain(&db, &onchain).await.unwrap();
+
+    assert_eq!(divergences.len(), 1);
+    assert_eq!(divergences[0].kind, DivergenceKind::UnreportedFill);
+    assert_eq!(divergences[0].action, ReconcileAction::FillReportQueued);
+    assert_eq!(divergences[0].local_filled, 100);
+    assert_eq!(divergences[0].onchain_filled, Some(60));
+
+    // A buy reports the highest price it paid
+    for (price, size) in [(48000, 60), (49500, 40)] {
+        db.insert_trade(&Trade {
+            trade_id: Uuid::new_v4(),
+            market_id: filled.market_id.clone(),
+            condition_id: filled.condition_id.clone(),
+            maker_order_id: Uuid::new_v4(),
+            taker_order_id: filled.order_id,
+            maker_account: "bob.testnet".to_string(),
+            taker_account: filled.user_account.clone(),
+            maker_side: OrderSide::Sell,
+            taker_side: OrderSide::Buy,
+            outcome: filled.outcome,
+            price,
+            size,
+            trade_type: TradeType::DirectMatch,
+            executed_at: Utc::now(),
+            settlement_status: SettlementStatus::Settled,
+            settlement_tx_hash: None,
+            maker_fee: 0,
+            taker_fee: 0,
+        }).await.unwrap();
+    }
+    assert_eq!(worst_fill_price(&db, &filled).await.unwrap(), Some(49500));
+    // It took liquidity, so the re-sent fill earns no maker rebate
+    assert!(!filled_as_maker(&db, &filled).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_divergences_left_for_review() {
+    let db = Database::new().await.unwrap();
+    let behind = order(OrderStatus::Pending, 0);
+    linked(&db, &behind, "order_1").await;
+    let cancelled_here = order(OrderStatus::Cancelled, 0);
+    linked(&db, &cancelled_here, "order_2").await;
+    let unknown = order(OrderStatus::Pending, 0);
+    linked(&db, &unknown, "order_3").await;
+
+    let onchain = vec![
+        solver_order("order_1", SolverOrderStatus::PartiallyFilled, 25),
+        solver_order("order_2", SolverOrderStatus::Pending, 0),
+    ];
+    let (_, divergences) = compare_with_chain(&db, &onchain).await.unwrap();
+
+    let kind_of = |order_id| divergences.iter()
+        .find(|d| d.order_id == order_id)
+        .map(|d| d.kind.clone());
+    assert_eq!(divergences.len(), 3);
+    assert!(divergences.iter().all(|d| d.action == ReconcileAction::NeedsReview));
+    assert_eq!(kind_of(behind.order_id), Some(DivergenceKind::FillNotInBook));
+    assert_eq!(kind_of(cancelled_here.order_id), Some(DivergenceKind::ClosedLocallyOnly));
+    assert_eq!(kind_of(unknown.order_id), Some(DivergenceKind::MissingOnChain));
+}
+
+#[tokio::test]
+async fn test_latest_report_is_returned() {
+    let db = Database::new().await.unwrap();
+    assert!(db.get_latest_reconciliation_report().await.unwrap().is_none());
+
+    let report = |orders_checked| ReconciliationReport {
+        report_id: Uuid::new_v4(),
+        started_at: Utc::now(),
+        completed_at: Utc::now(),
+        onchain_orders: orders_checked,
+        orders_checked,
+        divergences: Vec::new(),
+    };
+    let first = report(3);
+    let second = report(5);
+    db.insert_reconciliation_report(&first).await.unwrap();
+    db.insert_reconciliation_report(&second).await.unwrap();
+
+    let latest = db.get_latest_reconciliation_report().await.unwrap().unwrap();
+    assert_eq!(latest.report_id, second.report_id);
+    assert_eq!(latest.orders_checked, 5);
+}
@@ -0,0 +1,193 @@
+// Service configuration: required contract ids, the accepted collateral tokens and how requests
+// pick one
+
+use std::collections::HashMap;
+
+use orderbook_service::api::ApiError;
+use orderbook_service::config::{
+    ServiceConfig, DEFAULT_IDEMPOTENCY_KEY_TTL_SECS, DEFAULT_NEAR_RPC_URL, DEFAULT_YIELD_IDLE_THRESHOLD,
+};
+use orderbook_service::fees::DEFAULT_FEE_SWEEP_INTERVAL_SECS;
+use orderbook_service::near_client::health::ChainHealthPolicy;
+use orderbook_service::reconciliation::DEFAULT_RECONCILE_INTERVAL_SECS;
+
+fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    move |key| vars.get(key).cloned()
+}
+
+const REQUIRED: [(&str, &str); 5] = [
+    ("USDC_CONTRACT_ID", "usdc.testnet"),
+    ("CTF_CONTRACT_ID", "ctf.testnet"),
+    ("PLATFORM_ACCOUNT_ID", "orderbook.testnet"),
+    ("VERIFIER_CONTRACT_ID", "verifier.testnet"),
+    ("SOLVER_CONTRACT_ID", "solver.testnet"),
+];
+
+#[test]
+fn test_missing_contract_id_fails_instead_of_falling_back() {
+    for missing in ["USDC_CONTRACT_ID", "CTF_CONTRACT_ID", "PLATFORM_ACCOUNT_ID", "VERIFIER_CONTRACT_ID", "SOLVER_CONTRACT_ID"] {
+        let pairs: Vec<(&str, &str)> = REQUIRED.into_iter().filter(|(k, _)| *k != missing).collect();
+        let err = ServiceConfig::from_vars(vars(&pairs)).unwrap_err();
+        assert!(err.to_string().contains(missing), "{}", err);
+    }
+
+    let mut pairs = REQUIRED.to_vec();
+    pairs[2] = ("PLATFORM_ACCOUNT_ID", "Not An Account");
+    assert!(ServiceConfig::from_vars(vars(&pairs)).is_err());
+}
+
+#[test]
+fn test_signer_defaults_to_the_platform_account() {
+    let mut pairs = REQUIRED.to_vec();
+    pairs.push(("NEAR_RPC_URL", "https://rpc.mainnet.near.org"));
+    let config = ServiceConfig::from_vars(vars(&pairs)).unwrap();
+    assert_eq!(config.signer_account_id, "orderbook.testnet");
+    assert_eq!(config.near_rpc_url, "https://rpc.mainnet.near.org");
+
+    pairs.push(("SIGNER_ACCOUNT_ID", "relayer.testnet"));
+    assert_eq!(ServiceConfig::from_vars(vars(&pairs)).unwrap().signer_account_id, "relayer.testnet");
+
+    *pairs.last_mut().unwrap() = ("SIGNER_ACCOUNT_ID", "Not An Account");
+    let err = ServiceConfig::from_vars(vars(&pairs)).unwrap_err();
+    assert!(err.to_string().contains("SIGNER_ACCOUNT_ID"), "{}", err);
+}
+
+#[test]
+fn test_collateral_tokens_extend_the_usdc_default() {
+    let mut pairs = REQUIRED.to_vec();
+    pairs.push(("COLLATERAL_TOKENS", " wrap.testnet, usdc.testnet,,wrap.testnet "));
+    let config = ServiceConfig::from_vars(vars(&pairs)).unwrap();
+
+    assert_eq!(config, ServiceConfig {
+        ctf_contract_id: "ctf.testnet".to_string(),
+        verifier_contract_id: "verifier.testnet".to_string(),
+        solver_contract_id: "solver.testnet".to_string(),
+        platform_account_id: "orderbook.testnet".to_string(),
+        signer_account_id: "orderbook.testnet".to_string(),
+        near_rpc_url: DEFAULT_NEAR_RPC_URL.to_string(),
+        collateral_tokens: vec!["usdc.testnet".to_string(), "wrap.testnet".to_string()],
+        maker_fee_bps: None,
+        taker_fee_bps: None,
+        yield_enabled: false,
+        yield_protocol: None,
+        yield_idle_threshold: DEFAULT_YIELD_IDLE_THRESHOLD,
+        idempotency_key_ttl_secs: DEFAULT_IDEMPOTENCY_KEY_TTL_SECS,
+        api_key_required: true,
+        fee_sweep_interval_secs: DEFAULT_FEE_SWEEP_INTERVAL_SECS,
+        reconcile_interval_secs: DEFAULT_RECONCILE_INTERVAL_SECS,
+        chain_health: ChainHealthPolicy::default(),
+    });
+    assert_eq!(config.default_collateral_token(), "usdc.testnet");
+    assert_eq!(config.collateral_name("usdc.testnet"), "USDC");
+    assert_eq!(config.collateral_name("wrap.testnet"), "wrap.testnet");
+}
+
+#[test]
+fn test_resolve_collateral_token() {
+    let mut config = ServiceConfig::new("usdc.testnet", "ctf.testnet", "verifier.testnet", "solver.testnet", "orderbook.testnet");
+    config.collateral_tokens.push("wrap.testnet".to_string());
+
+    assert_eq!(config.resolve_collateral_token(None).unwrap(), "usdc.testnet");
+    assert_eq!(config.resolve_collateral_token(Some("")).unwrap(), "usdc.testnet");
+    assert_eq!(config.resolve_collateral_token(Some("wrap.testnet")).unwrap(), "wrap.testnet");
+    assert!(matches!(
+        config.resolve_collateral_token(Some("dai.testnet")),
+        Err(ApiError::InvalidRequest(_))
+    ));
+}
+
+#[test]
+fn test_fee_rates_default_to_the_solver_fee() {
+    let mut pairs = REQUIRED.to_vec();
+    pairs.push(("MAKER_FEE_BPS", "5"));
+    let mut config = ServiceConfig::from_vars(vars(&pairs)).unwrap();
+    assert_eq!((config.maker_fee_bps, config.taker_fee_bps), (Some(5), None));
+
+    // Only the rate the environment left unset takes the solver's fee
+    config.apply_solver_fee_bps(30);
+    assert_eq!((config.maker_fee_bps, config.taker_fee_bps), (Some(5), Some(30)));
+
+    pairs.push(("TAKER_FEE_BPS", "10001"));
+    let err = ServiceConfig::from_vars(vars(&pairs)).unwrap_err();
+    assert!(err.to_string().contains("TAKER_FEE_BPS"), "{}", err);
+}
+
+#[test]
+fn test_yield_needs_a_protocol_once_enabled() {
+    let mut pairs = REQUIRED.to_vec();
+    pairs.push(("YIELD_PROTOCOL_ID", "contract.main.burrow.near"));
+    let config = ServiceConfig::from_vars(vars(&pairs)).unwrap();
+    // A configured protocol stays unused until the integration is switched on
+    assert_eq!(config.yield_protocol(), None);
+
+    pairs.push(("YIELD_ENABLED", "true"));
+    pairs.push(("YIELD_IDLE_THRESHOLD", "25000000"));
+    let config = ServiceConfig::from_vars(vars(&pairs)).unwrap();
+    assert_eq!(config.yield_protocol(), Some("contract.main.burrow.near"));
+    assert_eq!(config.yield_idle_threshold, 25_000_000);
+
+    let pairs: Vec<(&str, &str)> = pairs.into_iter().filter(|(k, _)| *k != "YIELD_PROTOCOL_ID").collect();
+    let err = ServiceConfig::from_vars(vars(&pairs)).unwrap_err();
+    assert!(err.to_string().contains("YIELD_PROTOCOL_ID"), "{}", err);
+}
+
+#[test]
+fn test_chain_health_policy_from_env() {
+    let mut pairs = REQUIRED.to_vec();
+    pairs.extend([
+        ("CHAIN_MAX_STALENESS_SECS", "30"),
+        ("CHAIN_MAX_ERROR_RATE", "0.25"),
+        ("CHAIN_DEGRADED_REJECT_ORDERS", "false"),
+        ("CHAIN_DEGRADED_QUOTE_BAND_BPS", "500"),
+    ]);
+    let config = ServiceConfig::from_vars(vars(&pairs)).unwrap();
+    assert_eq!(config.chain_health, ChainHealthPolicy {
+        max_staleness_secs: 30,
+        max_error_rate: 0.25,
+        reject_orders: false,
+        quote_band_bps: Some(500),
+        ..ChainHealthPolicy::default()
+    });
+
+    for (key, value) in [("CHAIN_MAX_ERROR_RATE", "1.5"), ("CHAIN_MAX_STALENESS_SECS", "0"), ("CHAIN_DEGRADED_REJECT_ORDERS", "maybe")] {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push((key, value));
+        let err = ServiceConfig::from_vars(vars(&pairs)).unwrap_err();
+        assert!(err.to_string().contains(key), "{}", err);
+    }
+}
+
+#[test]
+fn test_api_keys_are_required_unless_disabled() {
+    let mut pairs = REQUIRED.to_vec();
+    assert!(ServiceConfig::from_vars(vars(&pairs)).unwrap().api_key_required);
+
+    pairs.push(("API_KEY_REQUIRED", "false"));
+    assert!(!ServiceConfig::from_vars(vars(&pairs)).unwrap().api_key_required);
+
+    *pairs.last_mut().unwrap() = ("API_KEY_REQUIRED", "sometimes");
+    let err = ServiceConfig::from_vars(vars(&pairs)).unwrap_err();
+    assert!(err.to_string().contains("API_KEY_REQUIRED"), "{}", err);
+}
+
+#[test]
+fn test_task_intervals_can_be_disabled_but_not_garbled() {
+    let mut pairs = REQUIRED.to_vec();
+    let config = ServiceConfig::from_vars(vars(&pairs)).unwrap();
+    assert_eq!(
+        (config.fee_sweep_interval_secs, config.reconcile_interval_secs),
+        (DEFAULT_FEE_SWEEP_INTERVAL_SECS, DEFAULT_RECONCILE_INTERVAL_SECS)
+    );
+
+    pairs.extend([("FEE_SWEEP_INTERVAL_SECS", "0"), ("RECONCILE_INTERVAL_SECS", "60")]);
+    let config = ServiceConfig::from_vars(vars(&pairs)).unwrap();
+    assert_eq!((config.fee_sweep_interval_secs, config.reconcile_interval_secs), (0, 60));
+
+    for key in ["FEE_SWEEP_INTERVAL_SECS", "RECONCILE_INTERVAL_SECS"] {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push((key, "-5"));
+        let err = ServiceConfig::from_vars(vars(&pairs)).unwrap_err();
+        assert!(err.to_string().contains(key), "{}", err);
+    }
+}
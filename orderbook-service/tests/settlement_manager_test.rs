@@ -0,0 +1,308 @@
+// Settlement manager: trades settle through a stand-in chain client whose transfers fail on
+// command, and retries repeat only the transfers that failed
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use near_crypto::{KeyType, SecretKey};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use orderbook_service::collateral::{CollateralManager, FeeLeg};
+use orderbook_service::config::ServiceConfig;
+use orderbook_service::matching::settlement::SettlementManager;
+use orderbook_service::near_client::settlement::SettlementClient;
+use orderbook_service::near_client::NearClient;
+use orderbook_service::storage::Database;
+use orderbook_service::types::{OrderSide, SettlementStatus, Trade, TradeType};
+
+const USDC: &str = "usdc.testnet";
+const PLATFORM: &str = "platform.testnet";
+
+/// Stand-in for NearClient: records the transfers that went through and fails those out of the
+/// accounts the test names
+#[derive(Default)]
+struct FakeChain {
+    failing: Mutex<HashSet<String>>,
+    lookups_failing: AtomicBool,
+    transfers: Mutex<Vec<(String, String, String, u128)>>, // (collateral token or position, from, to, amount)
+}
+
+impl FakeChain {
+    fn fail_from(&self, account: &str) {
+        self.failing.lock().unwrap().insert(account.to_string());
+    }
+
+    fn recover(&self) {
+        self.failing.lock().unwrap().clear();
+        self.lookups_failing.store(false, Ordering::SeqCst);
+    }
+
+    /// Transfers that went through, sorted since a batch's order depends on its netting
+    fn transfers(&self) -> Vec<(String, String, String, u128)> {
+        let mut transfers = self.transfers.lock().unwrap().clone();
+        transfers.sort();
+        transfers
+    }
+
+    fn transfer(&self, asset: &str, from: &str, to: &str, amount: u128) -> Result<String> {
+        if self.failing.lock().unwrap().contains(from) {
+            return Err(anyhow!("transfer from {} rejected", from));
+        }
+        let mut transfers = self.transfers.lock().unwrap();
+        transfers.push((asset.to_string(), from.to_string(), to.to_string(), amount));
+        Ok(format!("tx{}", transfers.len()))
+    }
+}
+
+#[async_trait::async_trait]
+impl SettlementClient for FakeChain {
+    fn signer_account_id(&self) -> &str {
+        PLATFORM
+    }
+
+    async fn get_position_id_for_outcome(&self, condition_id: &str, outcome: u8, _collateral_token: &str) -> Result<String> {
+        if self.lookups_failing.load(Ordering::SeqCst) {
+            return Err(anyhow!("transport error: connection refused"));
+        }
+        Ok(position(condition_id, outcome))
+    }
+
+    // Settlement never reads balances
+    async fn get_ft_balance(&self, _token_contract: &str, _account_id: &str) -> Result<u128> {
+        Ok(0)
+    }
+
+    async fn get_ctf_token_balance(&self, _account_id: &str, _position_id: &str) -> Result<u128> {
+        Ok(0)
+    }
+
+    async fn transfer_collateral_from(&self, collateral_token: &str, from: &str, to: &str, amount: u128) -> Result<String> {
+        self.transfer(collateral_token, from, to, amount)
+    }
+
+    async fn transfer_position_from(&self, from: &str, to: &str, position_id: &str, amount: u128) -> Result<String> {
+        self.transfer(position_id, from, to, amount)
+    }
+}
+
+fn position(condition_id: &str, outcome: u8) -> String {
+    format!("{}:{}", condition_id, outcome)
+}
+
+fn trade(trade_type: TradeType, maker: &str, taker: &str, maker_fee: u128, taker_fee: u128) -> Trade {
+    Trade {
+        trade_id: Uuid::new_v4(),
+        market_id: "market_1".to_string(),
+        condition_id: "condition_1".to_string(),
+        maker_order_id: Uuid::new_v4(),
+        taker_order_id: Uuid::new_v4(),
+        maker_account: maker.to_string(),
+        taker_account: taker.to_string(),
+        maker_side: OrderSide::Sell,
+        taker_side: OrderSide::Buy,
+        outcome: 1,
+        price: 30000,
+        size: 100_000_000,
+        trade_type,
+        executed_at: Utc::now(),
+        settlement_status: SettlementStatus::Pending,
+        settlement_tx_hash: None,
+        maker_fee,
+        taker_fee,
+    }
+}
+
+struct Harness {
+    chain: Arc<FakeChain>,
+    db: Arc<Database>,
+    collateral: Arc<CollateralManager>,
+    manager: SettlementManager,
+}
+
+async fn harness() -> Harness {
+    let config = Arc::new(ServiceConfig::new(USDC, "ctf.testnet", "verifier.testnet", "solver.testnet", PLATFORM));
+    let chain = Arc::new(FakeChain::default());
+    let db = Arc::new(Database::new().await.unwrap());
+    let near_client = Arc::new(NearClient::with_secret_key(&config, SecretKey::from_seed(KeyType::ED25519, "settlement-test")).unwrap());
+    let collateral = Arc::new(
+        CollateralManager::new(db.clone(), near_client.clone(), config).with_settlement_client(chain.clone())
+    );
+    let manager = SettlementManager::with_collateral_manager(db.clone(), near_client, collateral.clone());
+    Harness { chain, db, collateral, manager }
+}
+
+impl Harness {
+    /// Queue `trades` and let the manager settle them as it does on shutdown
+    async fn settle(&self, trades: &[Trade]) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        for trade in trades {
+            self.db.insert_trade(trade).await.unwrap();
+            sender.send(trade.clone()).unwrap();
+        }
+        let shutdown = CancellationToken::new();
+        shutdown.cancel();
+        self.manager.run(receiver, shutdown).await.unwrap();
+    }
+
+    async fn status(&self, trade: &Trade) -> SettlementStatus {
+        self.db.get_trade_settlement_status(trade.trade_id).await.unwrap()
+    }
+
+    async fn ledger(&self) -> Vec<(Uuid, u128, u128)> {
+        self.db.get_unswept_fees().await.unwrap().into_iter()
+            .map(|entry| (entry.trade_id, entry.maker_fee, entry.taker_fee))
+            .collect()
+    }
+}
+
+fn collateral_transfer(from: &str, to: &str, amount: u128) -> (String, String, String, u128) {
+    (USDC.to_string(), from.to_string(), to.to_string(), amount)
+}
+
+fn token_transfer(from: &str, to: &str, amount: u128) -> (String, String, String, u128) {
+    (position("condition_1", 1), from.to_string(), to.to_string(), amount)
+}
+
+fn sorted(mut transfers: Vec<(String, String, String, u128)>) -> Vec<(String, String, String, u128)> {
+    transfers.sort();
+    transfers
+}
+
+#[tokio::test]
+async fn test_direct_matches_settle_in_one_netted_batch() {
+    let h = harness().await;
+    // 100 YES @ $0.30 each: alice sells to bob, carol sells to dave
+    let first = trade(TradeType::DirectMatch, "alice.testnet", "bob.testnet", 30_000, 60_000);
+    let second = trade(TradeType::DirectMatch, "carol.testnet", "dave.testnet", 0, 0);
+
+    h.settle(&[first.clone(), second.clone()]).await;
+
+    assert_eq!(h.status(&first).await, SettlementStatus::Settled);
+    assert_eq!(h.status(&second).await, SettlementStatus::Settled);
+    // Collateral nets across the trades: bob's payment goes to carol, and dave pays alice and the fees
+    assert_eq!(h.chain.transfers(), sorted(vec![
+        collateral_transfer("bob.testnet", "carol.testnet", 30_000_000),
+        collateral_transfer("bob.testnet", "alice.testnet", 60_000),
+        collateral_transfer("dave.testnet", "alice.testnet", 29_910_000),
+        collateral_transfer("dave.testnet", PLATFORM, 90_000),
+        token_transfer("alice.testnet", "bob.testnet", 100_000_000),
+        token_transfer("carol.testnet", "dave.testnet", 100_000_000),
+    ]));
+    assert!(h.collateral.pending_batch_transfers().await.is_empty());
+    assert_eq!(h.ledger().await, vec![(first.trade_id, 30_000, 60_000)]);
+}
+
+#[tokio::test]
+async fn test_failed_batch_transfer_is_retried_alone() {
+    let h = harness().await;
+    let first = trade(TradeType::DirectMatch, "alice.testnet", "bob.testnet", 30_000, 60_000);
+    let second = trade(TradeType::DirectMatch, "carol.testnet", "dave.testnet", 0, 0);
+    h.chain.fail_from("dave.testnet");
+
+    h.settle(&[first.clone(), second.clone()]).await;
+
+    // dave's transfers to alice and the platform failed, which holds up both trades. Neither is
+    // marked Failed, which would settle it again on top of the transfers that went through
+    assert_eq!(h.status(&first).await, SettlementStatus::Settling);
+    assert_eq!(h.status(&second).await, SettlementStatus::Settling);
+    let moved = sorted(vec![
+        collateral_transfer("bob.testnet", "carol.testnet", 30_000_000),
+        collateral_transfer("bob.testnet", "alice.testnet", 60_000),
+        token_transfer("alice.testnet", "bob.testnet", 100_000_000),
+        token_transfer("carol.testnet", "dave.testnet", 100_000_000),
+    ]);
+    assert_eq!(h.chain.transfers(), moved);
+    let pending = h.collateral.pending_batch_transfers().await;
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].transfers.len(), 2);
+    assert_eq!(pending[0].trades.len(), 2);
+    assert!(h.ledger().await.is_empty());
+
+    // The retry repeats dave's two transfers and nothing else, then settles both trades
+    h.chain.recover();
+    h.manager.retry_settlements().await.unwrap();
+    let mut all = moved.clone();
+    all.push(collateral_transfer("dave.testnet", "alice.testnet", 29_910_000));
+    all.push(collateral_transfer("dave.testnet", PLATFORM, 90_000));
+    assert_eq!(h.chain.transfers(), sorted(all));
+    assert_eq!(h.status(&first).await, SettlementStatus::Settled);
+    assert_eq!(h.status(&second).await, SettlementStatus::Settled);
+    assert!(h.collateral.pending_batch_transfers().await.is_empty());
+    assert_eq!(h.ledger().await, vec![(first.trade_id, 30_000, 60_000)]);
+
+    // Nothing left to retry
+    h.manager.retry_settlements().await.unwrap();
+    assert_eq!(h.chain.transfers().len(), 6);
+}
+
+#[tokio::test]
+async fn test_trade_the_batch_moved_nothing_for_is_settled_again() {
+    let h = harness().await;
+    let direct = trade(TradeType::DirectMatch, "alice.testnet", "bob.testnet", 0, 0);
+    h.chain.lookups_failing.store(true, Ordering::SeqCst);
+
+    h.settle(&[direct.clone()]).await;
+
+    assert_eq!(h.status(&direct).await, SettlementStatus::Failed);
+    assert!(h.chain.transfers().is_empty());
+    assert!(h.collateral.pending_batch_transfers().await.is_empty());
+
+    h.chain.recover();
+    h.manager.retry_settlements().await.unwrap();
+    assert_eq!(h.status(&direct).await, SettlementStatus::Settled);
+    assert_eq!(h.chain.transfers(), sorted(vec![
+        collateral_transfer("bob.testnet", "alice.testnet", 30_000_000),
+        token_transfer("alice.testnet", "bob.testnet", 100_000_000),
+    ]));
+}
+
+#[tokio::test]
+async fn test_failed_fee_transfer_is_retried_on_its_own() {
+    let h = harness().await;
+    let mint = trade(TradeType::Minting, "alice.testnet", "bob.testnet", 70_000, 60_000);
+    h.chain.fail_from("bob.testnet");
+
+    let legs = vec![
+        FeeLeg { payer: "alice.testnet".to_string(), amount: 70_000 },
+        FeeLeg { payer: "bob.testnet".to_string(), amount: 60_000 },
+    ];
+    assert_eq!(h.collateral.collect_trade_fees(&mint, legs).await.len(), 1);
+
+    // Only bob's fee is still owed and nothing is in the ledger yet
+    assert_eq!(h.chain.transfers(), vec![collateral_transfer("alice.testnet", PLATFORM, 70_000)]);
+    let pending = h.collateral.pending_trade_fees().await;
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].legs, vec![FeeLeg { payer: "bob.testnet".to_string(), amount: 60_000 }]);
+    assert!(h.ledger().await.is_empty());
+
+    // The retry charges bob alone, not alice a second time, and records the trade's fees
+    h.chain.recover();
+    h.manager.retry_settlements().await.unwrap();
+    assert_eq!(h.chain.transfers(), vec![
+        collateral_transfer("alice.testnet", PLATFORM, 70_000),
+        collateral_transfer("bob.testnet", PLATFORM, 60_000),
+    ]);
+    assert!(h.collateral.pending_trade_fees().await.is_empty());
+    assert_eq!(h.ledger().await, vec![(mint.trade_id, 70_000, 60_000)]);
+
+    h.manager.retry_settlements().await.unwrap();
+    assert_eq!(h.chain.transfers().len(), 2);
+}
+
+#[tokio::test]
+async fn test_fee_legs_without_an_amount_make_no_transfers() {
+    let h = harness().await;
+    let mint = trade(TradeType::Minting, "alice.testnet", "bob.testnet", 0, 0);
+
+    let legs = vec![FeeLeg { payer: "bob.testnet".to_string(), amount: 0 }];
+    assert!(h.collateral.collect_trade_fees(&mint, legs).await.is_empty());
+
+    assert!(h.chain.transfers().is_empty());
+    assert!(h.collateral.pending_trade_fees().await.is_empty());
+    assert!(h.ledger().await.is_empty());
+}
@@ -626,6 +626,10 @@ class SolverDaemon {
                     intent_id: intentId,
                     timestamp: Date.now()
                 })));
+
+                for (const trade of trades) {
+                    await this.checkTakeProfitTriggers(trade);
+                }
             } else {
                 console.log(`❌ Failed to process intent: ${response.status}`);
                 this.markIntentForRetry(intentId, `HTTP ${response.status}`);
@@ -769,6 +773,30 @@ class SolverDaemon {
         };
     }
 
+    // Let the solver contract fire take-profit orders the trade price reached.
+    // Triggered take profits come back as SellShares intents, submitted like any other intent
+    async checkTakeProfitTriggers(trade) {
+        // The contract compares against the YES price; a NO trade at p means YES at 100000 - p
+        const yesPrice = trade.outcome === 1 ? Number(trade.price) : 100000 - Number(trade.price);
+
+        try {
+            const result = execSync(
+                `NEAR_CLI_OUTPUT=json near call ${CONFIG.SOLVER_CONTRACT} check_take_profit_triggers '{"market_id": "${trade.market_id}", "current_price": ${yesPrice}}' --accountId ${CONFIG.ACCOUNT_ID} --gas ${CONFIG.MAX_GAS}`,
+                { encoding: 'utf8' }
+            );
+            const jsonLine = result.trim().split('\n').reverse().find(line => line.trim().startsWith('['));
+            const intents = jsonLine ? JSON.parse(jsonLine) : [];
+
+            for (const intent of intents) {
+                console.log(`🎯 Take profit triggered: ${intent.intent_id} sells ${intent.amount} @ ${intent.min_price}`);
+                this.pendingIntents.set(intent.intent_id, intent);
+                await this.processIntent(intent);
+            }
+        } catch (error) {
+            console.log(`⚠️ Failed to check take profits for market ${trade.market_id}:`, error.message);
+        }
+    }
+
     async settlePendingTrades() {
         if (this.pendingTrades.length === 0) return;
 
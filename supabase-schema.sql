@@ -31,6 +31,8 @@ CREATE TABLE orders (
 CREATE INDEX idx_orders_market_outcome_side_status ON orders (market_id, outcome, side, status);
 CREATE INDEX idx_orders_active ON orders (market_id, outcome, side, price) WHERE status IN ('Pending', 'PartiallyFilled');
 CREATE INDEX idx_orders_user ON orders (user_account);
+CREATE INDEX idx_orders_user_market_status ON orders (user_account, market_id, status);
+CREATE INDEX idx_orders_user_created ON orders (user_account, created_at);
 CREATE INDEX idx_orders_created ON orders (created_at DESC);
 CREATE INDEX idx_orders_expires ON orders (expires_at) WHERE expires_at IS NOT NULL;
 
@@ -53,7 +55,9 @@ CREATE TABLE trades (
     trade_type TEXT NOT NULL,             -- 'DirectMatch', 'Minting', 'Burning'
     executed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     settlement_status TEXT NOT NULL DEFAULT 'Pending', -- 'Pending', 'Settling', 'Settled', 'Failed'
-    settlement_tx_hash TEXT
+    settlement_tx_hash TEXT,
+    maker_fee NUMERIC(39,0) NOT NULL DEFAULT 0,  -- u128: fee charged to the maker
+    taker_fee NUMERIC(39,0) NOT NULL DEFAULT 0   -- u128: fee charged to the taker
 );
 
 -- Add foreign key constraints
@@ -66,6 +70,7 @@ ALTER TABLE trades ADD CONSTRAINT fk_trades_taker_order
 CREATE INDEX idx_trades_market_outcome ON trades (market_id, outcome);
 CREATE INDEX idx_trades_settlement_status ON trades (settlement_status);
 CREATE INDEX idx_trades_executed ON trades (executed_at DESC);
+CREATE INDEX idx_trades_market_outcome_executed ON trades (market_id, outcome, executed_at);
 CREATE INDEX idx_trades_accounts ON trades (maker_account, taker_account);
 CREATE INDEX idx_trades_pending ON trades (settlement_status) WHERE settlement_status = 'Pending';
 
@@ -133,6 +138,39 @@ CREATE TABLE market_stats (
 -- Indexes for market stats
 CREATE INDEX idx_market_stats_updated ON market_stats (updated_at DESC);
 
+-- ================================
+-- MARKET CONDITIONS (market_id -> CTF condition_id registry)
+-- ================================
+CREATE TABLE market_conditions (
+    market_id TEXT PRIMARY KEY,
+    condition_id TEXT NOT NULL,
+    source TEXT NOT NULL DEFAULT 'Manual', -- 'Manual', 'VerifierSync', 'LegacyImport'
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    collateral_token TEXT NOT NULL DEFAULT '',
+    outcome_count SMALLINT NOT NULL DEFAULT 2,
+    registered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_market_conditions_registered ON market_conditions (registered_at DESC);
+
+-- ================================
+-- TRADE CANDLES (settled trades older than 7 days, compacted hourly)
+-- ================================
+CREATE TABLE trade_candles (
+    market_id TEXT NOT NULL,
+    outcome SMALLINT NOT NULL,
+    bucket_start TIMESTAMPTZ NOT NULL,    -- Start of the hour
+    open BIGINT NOT NULL,
+    high BIGINT NOT NULL,
+    low BIGINT NOT NULL,
+    close BIGINT NOT NULL,
+    volume NUMERIC(39,0) NOT NULL,
+    trade_count INTEGER NOT NULL,
+
+    PRIMARY KEY (market_id, outcome, bucket_start)
+);
+
 -- ================================
 -- SETTLEMENT BATCHES (For efficient on-chain execution)
 -- ================================
@@ -163,6 +201,75 @@ ALTER TABLE batch_trades ADD CONSTRAINT fk_batch_trades_batch
 ALTER TABLE batch_trades ADD CONSTRAINT fk_batch_trades_trade
     FOREIGN KEY (trade_id) REFERENCES trades(trade_id);
 
+-- ================================
+-- API KEYS (market makers and bots; only the secret's sha256 is stored)
+-- ================================
+CREATE TABLE api_keys (
+    key_id TEXT PRIMARY KEY,
+    secret_hash TEXT NOT NULL,
+    rate_limit_multiplier INTEGER NOT NULL DEFAULT 1,
+    allowed_endpoints TEXT[] NOT NULL DEFAULT '{}', -- e.g. '/orders', '/price/*/vwap'; empty allows all
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ,
+    request_count BIGINT NOT NULL DEFAULT 0
+);
+
+-- ================================
+-- CHAIN RECONCILIATION (orderbook order <-> solver contract order, and each pass's report)
+-- ================================
+CREATE TABLE solver_order_links (
+    order_id UUID PRIMARY KEY,
+    solver_order_id TEXT NOT NULL
+);
+
+CREATE TABLE reconciliation_reports (
+    report_id UUID PRIMARY KEY,
+    started_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL,
+    divergence_count INTEGER NOT NULL,
+    report JSONB NOT NULL
+);
+
+CREATE INDEX idx_reconciliation_reports_completed ON reconciliation_reports (completed_at DESC);
+
+-- ================================
+-- PLATFORM FEES (fees collected per trade, swept to the platform account)
+-- ================================
+CREATE TABLE platform_fees (
+    trade_id UUID PRIMARY KEY,
+    market_id TEXT NOT NULL,
+    collateral_token TEXT NOT NULL,
+    maker_fee NUMERIC(39,0) NOT NULL,
+    taker_fee NUMERIC(39,0) NOT NULL,
+    collected_at TIMESTAMPTZ NOT NULL,
+    sweep_id UUID                         -- NULL until swept
+);
+
+CREATE INDEX idx_platform_fees_market ON platform_fees (market_id);
+CREATE INDEX idx_platform_fees_unswept ON platform_fees (collected_at) WHERE sweep_id IS NULL;
+
+-- ================================
+-- YIELD DEPOSITS (idle USDC principal parked in the yield protocol)
+-- ================================
+CREATE TABLE yield_deposits (
+    account_id TEXT PRIMARY KEY,
+    amount NUMERIC(39,0) NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- ================================
+-- ORDER IDEMPOTENCY KEYS (retried submissions return the first response)
+-- ================================
+CREATE TABLE order_idempotency_keys (
+    idempotency_key TEXT PRIMARY KEY,     -- "<account>:<client key>"
+    payload_hash TEXT NOT NULL,
+    order_id UUID NOT NULL,
+    response TEXT,                        -- NULL while the first request runs
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_order_idempotency_keys_created ON order_idempotency_keys (created_at);
+
 -- ================================
 -- FUNCTIONS FOR MARKET STATS UPDATES
 -- ================================
@@ -221,10 +328,15 @@ BEGIN
       AND side = 'Sell'
       AND status IN ('Pending', 'PartiallyFilled');
 
-    -- Calculate total volume and trade count
-    SELECT COALESCE(SUM(size), 0), COUNT(*) INTO v_total_volume, v_trade_count
-    FROM trades
-    WHERE market_id = p_market_id AND outcome = p_outcome;
+    -- Calculate total volume and trade count, including trades compacted into hourly candles
+    SELECT COALESCE(SUM(volume), 0), COALESCE(SUM(n), 0) INTO v_total_volume, v_trade_count
+    FROM (
+        SELECT size AS volume, 1 AS n FROM trades
+        WHERE market_id = p_market_id AND outcome = p_outcome
+        UNION ALL
+        SELECT volume, trade_count AS n FROM trade_candles
+        WHERE market_id = p_market_id AND outcome = p_outcome
+    ) history;
 
     -- Calculate spread and mid price
     IF v_best_bid IS NOT NULL AND v_best_ask IS NOT NULL THEN
@@ -337,11 +449,21 @@ ALTER TABLE collateral_reservations ENABLE ROW LEVEL SECURITY;
 
 -- Note: Market stats and settlement data should be publicly readable
 ALTER TABLE market_stats DISABLE ROW LEVEL SECURITY;
+ALTER TABLE market_conditions DISABLE ROW LEVEL SECURITY;
+ALTER TABLE trade_candles DISABLE ROW LEVEL SECURITY;
 ALTER TABLE settlement_batches DISABLE ROW LEVEL SECURITY;
 ALTER TABLE batch_trades DISABLE ROW LEVEL SECURITY;
+ALTER TABLE api_keys DISABLE ROW LEVEL SECURITY;
+ALTER TABLE solver_order_links DISABLE ROW LEVEL SECURITY;
+ALTER TABLE reconciliation_reports DISABLE ROW LEVEL SECURITY;
+ALTER TABLE platform_fees DISABLE ROW LEVEL SECURITY;
+ALTER TABLE yield_deposits DISABLE ROW LEVEL SECURITY;
+ALTER TABLE order_idempotency_keys DISABLE ROW LEVEL SECURITY;
 
 -- Comments for documentation
 COMMENT ON TABLE orders IS 'Persistent orderbook orders matching Rust Order struct';
 COMMENT ON TABLE trades IS 'Executed trades matching Rust Trade struct';
 COMMENT ON TABLE market_stats IS 'Real-time market statistics for TUI display';
+COMMENT ON TABLE market_conditions IS 'Market to CTF condition registry (manual + verifier sync)';
+COMMENT ON TABLE trade_candles IS 'Hourly OHLCV of trades removed by the retention job';
 COMMENT ON FUNCTION update_market_stats IS 'Updates market stats after order/trade changes';
\ No newline at end of file